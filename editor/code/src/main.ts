@@ -1,7 +1,7 @@
 import * as fs from 'fs';
 import * as path from 'path';
 
-import { ExtensionContext, window } from "vscode";
+import { ExtensionContext, TextDocumentContentProvider, Uri, window, workspace } from "vscode";
 import { LanguageClientOptions, LanguageClient, ServerOptions, Executable, TransportKind } from "vscode-languageclient/node";
 
 const exec = "nmlc";
@@ -10,7 +10,7 @@ const name = "nml";
 
 let client: LanguageClient;
 
-export async function activate(_context: ExtensionContext) {
+export async function activate(context: ExtensionContext) {
     const server = getServerPath();
     console.log(server);
     const run: Executable = {
@@ -31,6 +31,16 @@ export async function activate(_context: ExtensionContext) {
 
     client = new LanguageClient(name, serverOptions, clientOptions);
     await client.start();
+
+    // Documents which only exist in the compiler, like the prelude, are shown
+    // read-only.
+    const provider: TextDocumentContentProvider = {
+        provideTextDocumentContent(uri: Uri): Promise<string> {
+            return client.sendRequest<string>("nml/virtualDocument", { uri: uri.toString() });
+        },
+    };
+
+    context.subscriptions.push(workspace.registerTextDocumentContentProvider(lang, provider));
 }
 
 export async function deactivate() { }
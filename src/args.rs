@@ -3,6 +3,8 @@ use std::str::FromStr;
 
 use argh::FromArgs;
 
+use crate::ir::optimize;
+
 /// test message 123
 #[derive(FromArgs, Debug)]
 pub struct Args {
@@ -14,7 +16,46 @@ pub struct Args {
 #[argh(subcommand)]
 pub enum Command {
     Lsp(Lsp),
+    Build(Build),
     Check(Check),
+    Doc(Doc),
+    Dump(Dump),
+    Fmt(Fmt),
+    Run(Run),
+}
+
+/// Compile the package to a file which can be run.
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "build")]
+pub struct Build {
+    /// the source file to build
+    #[argh(positional)]
+    pub path: PathBuf,
+
+    /// the amount of logging to perform
+    #[argh(option)]
+    pub log: Option<LogLevel>,
+
+    /// do not bring the standard prelude into scope
+    #[argh(switch)]
+    pub no_prelude: bool,
+
+    /// the kind of code to generate
+    #[argh(option, default = "Target::C")]
+    pub target: Target,
+
+    /// the file to write the generated code to
+    #[argh(option, short = 'o')]
+    pub output: Option<PathBuf>,
+
+    /// a top-level binding to export from a WebAssembly module
+    #[argh(option)]
+    pub export: Vec<String>,
+
+    /// how much to optimize the program: 0, 1 or 2 (the JavaScript backend
+    /// never optimizes)
+    #[argh(option, short = 'O', default = "OptLevel::O1")]
+    pub opt_level: OptLevel,
 }
 
 /// Check the package for static errors.
@@ -28,7 +69,112 @@ pub struct Check {
     /// the amount of logging to perform
     #[argh(option)]
     pub log: Option<LogLevel>,
+
+    /// do not bring the standard prelude into scope
+    #[argh(switch)]
+    pub no_prelude: bool,
+
+    /// type check the core program the source lowers to
+    #[argh(switch)]
+    pub verify_core: bool,
+}
+
+/// Generate documentation for the top-level items of a program.
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "doc")]
+pub struct Doc {
+    /// the source file to document
+    #[argh(positional)]
+    pub path: PathBuf,
+
+    /// the amount of logging to perform
+    #[argh(option)]
+    pub log: Option<LogLevel>,
+
+    /// do not bring the standard prelude into scope
+    #[argh(switch)]
+    pub no_prelude: bool,
+
+    /// generate a Markdown file rather than HTML
+    #[argh(switch)]
+    pub markdown: bool,
+
+    /// the directory to write the documentation to (`doc` by default)
+    #[argh(option, short = 'o')]
+    pub output: Option<PathBuf>,
+}
+
+/// Print the intermediate representations of a program.
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "dump")]
+pub struct Dump {
+    /// the source file to dump
+    #[argh(positional)]
+    pub path: PathBuf,
+
+    /// the amount of logging to perform
+    #[argh(option)]
+    pub log: Option<LogLevel>,
+
+    /// do not bring the standard prelude into scope
+    #[argh(switch)]
+    pub no_prelude: bool,
+
+    /// the representation to print: `tokens`, `green`, `parsed`,
+    /// `resolved`, `inferred` or `types`, which is the default unless
+    /// decision trees or bytecode are printed
+    #[argh(option)]
+    pub stage: Option<Stage>,
+
+    /// print the decision tree of every pattern match
+    #[argh(switch)]
+    pub decision_trees: bool,
+
+    /// print the disassembled bytecode of the program
+    #[argh(switch)]
+    pub bytecode: bool,
+}
+
+/// Format source files in place.
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "fmt")]
+pub struct Fmt {
+    /// the source files or directories of source files to format (standard
+    /// input is formatted to standard output if there are none)
+    #[argh(positional)]
+    pub paths: Vec<PathBuf>,
+
+    /// check that the files are formatted, without changing them
+    #[argh(switch)]
+    pub check: bool,
+}
+
+/// Run a program with the bytecode interpreter.
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "run")]
+pub struct Run {
+    /// the bytecode file or source file to run
+    #[argh(positional)]
+    pub path: PathBuf,
+
+    /// the amount of logging to perform
+    #[argh(option)]
+    pub log: Option<LogLevel>,
+
+    /// do not bring the standard prelude into scope
+    #[argh(switch)]
+    pub no_prelude: bool,
+
+    /// how much to optimize a source file: 0, 1 or 2
+    #[argh(option, short = 'O', default = "OptLevel::O1")]
+    pub opt_level: OptLevel,
+
+    /// also run a source file unoptimized, and check that both runs produce
+    /// the same result
+    #[argh(switch)]
+    pub differential: bool,
 }
+
 /// Run the compiler as a language server.
 #[derive(FromArgs, Debug)]
 #[argh(subcommand, name = "lsp")]
@@ -42,6 +188,109 @@ pub struct Lsp {
     pub stdio: bool,
 }
 
+#[derive(Clone, Copy, Debug)]
+pub enum Target {
+    Bytecode,
+    C,
+    Js,
+    Wasm,
+}
+
+impl Target {
+    /// The extension of files containing code for this target.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Bytecode => "nmlb",
+            Self::C => "c",
+            Self::Js => "js",
+            Self::Wasm => "wasm",
+        }
+    }
+}
+
+impl FromStr for Target {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bytecode" => Ok(Self::Bytecode),
+            "c" => Ok(Self::C),
+            "js" => Ok(Self::Js),
+            "wasm" => Ok(Self::Wasm),
+            _ => Err("expected `bytecode`, `c`, `js` or `wasm`"),
+        }
+    }
+}
+
+/// An intermediate representation of a program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// The tokens and their spans.
+    Tokens,
+
+    /// The lossless green tree.
+    Green,
+
+    /// The abstract syntax tree of each item.
+    Parsed,
+
+    /// The items with names resolved, in dependency order.
+    Resolved,
+
+    /// The type of every expression.
+    Inferred,
+
+    /// The scheme of every binding.
+    Types,
+}
+
+impl FromStr for Stage {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tokens" => Ok(Self::Tokens),
+            "green" => Ok(Self::Green),
+            "parsed" => Ok(Self::Parsed),
+            "resolved" => Ok(Self::Resolved),
+            "inferred" => Ok(Self::Inferred),
+            "types" => Ok(Self::Types),
+            _ => Err("expected `tokens`, `green`, `parsed`, `resolved`, `inferred` or `types`"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum OptLevel {
+    O0,
+    O1,
+    O2,
+}
+
+impl OptLevel {
+    /// The level of optimization to perform, if any.
+    pub fn to_level(self) -> Option<optimize::Level> {
+        match self {
+            Self::O0 => None,
+            Self::O1 => Some(optimize::Level::Simplify),
+            Self::O2 => Some(optimize::Level::Inline),
+        }
+    }
+}
+
+impl FromStr for OptLevel {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(Self::O0),
+            "1" => Ok(Self::O1),
+            "2" => Ok(Self::O2),
+            _ => Err("expected `0`, `1` or `2`"),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum LogLevel {
     Off,
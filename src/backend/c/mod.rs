@@ -0,0 +1,628 @@
+//! The C backend turns a core program into a single, portable C file, which
+//! starts with a small [runtime](runtime.c) and can be compiled with any C99
+//! compiler.
+//!
+//! Types are erased, every lambda is closure converted into a C function taking
+//! the values of its free variables along with its argument, and every
+//! top-level binding becomes a global initialized in program order. If the
+//! program defines `main`, the executable prints its value.
+//!
+//! Records store their fields sorted by label. Whenever the row of a record is
+//! closed, the offset of a field is computed from the row at compile time.
+//! Otherwise, the offset is found at runtime by searching the labels of the
+//! record.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+
+use bumpalo::Bump;
+
+use super::{closed_fields, curried, offset, saturated};
+use crate::frontend::builtins::{integer, Primitive};
+use crate::frontend::names::{Label, Name, Names};
+use crate::ir::analyze::{free_vars, labels};
+use crate::ir::lower::substitute;
+use crate::ir::{Data, Expr, Item, Program, Type, TypeArg};
+
+const RUNTIME: &str = include_str!("runtime.c");
+const INDENT: &str = "    ";
+
+/// Emit the C code for a program.
+pub fn emit<'a>(alloc: &'a Bump, names: &Names, program: &'a Program<'a, '_>) -> String {
+    let mut emitter = Emitter {
+        alloc,
+        names,
+        globals: BTreeMap::new(),
+        ctors: BTreeMap::new(),
+        datas: BTreeMap::new(),
+        labels: labels(program)
+            .into_iter()
+            .enumerate()
+            .map(|(id, label)| (label, id))
+            .collect(),
+        label_arrays: BTreeMap::new(),
+        prototypes: Vec::new(),
+        functions: Vec::new(),
+        printers: BTreeMap::new(),
+        counter: 0,
+    };
+
+    emitter.program(program)
+}
+
+/// A constructor, along with its index in its data type and the number of
+/// arguments it takes.
+struct Ctor {
+    tag: usize,
+    arity: usize,
+}
+
+/// The code of the C function currently being emitted.
+struct Function {
+    /// The C expression for each local variable in scope.
+    locals: BTreeMap<Name, String>,
+    code: String,
+    indent: String,
+}
+
+struct Emitter<'n, 'src, 'a> {
+    alloc: &'a Bump,
+    names: &'n Names<'src>,
+
+    /// The C name of every global and constructor.
+    globals: BTreeMap<Name, String>,
+    ctors: BTreeMap<Name, Ctor>,
+    datas: BTreeMap<Name, &'a Data<'a>>,
+
+    /// The runtime representation of each label, which is ordered like the
+    /// names of the labels.
+    labels: BTreeMap<Label<'a>, usize>,
+    label_arrays: BTreeMap<Vec<usize>, String>,
+
+    prototypes: Vec<String>,
+    functions: Vec<String>,
+
+    /// The function printing values of each type, keyed by the type.
+    printers: BTreeMap<String, String>,
+    counter: usize,
+}
+
+impl<'a> Emitter<'_, '_, 'a> {
+    fn program(&mut self, program: &'a Program<'a, '_>) -> String {
+        let mut globals = Vec::new();
+        let mut init = Function::new();
+        let mut main = None;
+
+        for item in program.items {
+            match item {
+                Item::Data(data) => self.data(&mut globals, &mut init, data),
+                Item::Group(bindings) => {
+                    for binding in bindings.iter() {
+                        let global = self.fresh("g", &binding.name);
+                        globals.push(format!("static nml_value {global};"));
+                        self.globals.insert(binding.name, global);
+
+                        if self.name(&binding.name) == "main" {
+                            main = Some(binding);
+                        }
+                    }
+
+                    for binding in bindings.iter() {
+                        let value = self.expr(&mut init, &binding.body);
+                        let global = &self.globals[&binding.name];
+                        init.line(format!("{global} = {value};"));
+                    }
+                }
+            }
+        }
+
+        let print = main.map(|main| {
+            let printer = self.printer(main.ty);
+            let global = &self.globals[&main.name];
+            format!("{INDENT}{printer}({global}, 0);\n{INDENT}putchar('\\n');\n")
+        });
+
+        let mut result = String::from(RUNTIME);
+
+        result.push('\n');
+        for (label, id) in self.labels.iter() {
+            result.push_str(&format!("/* {id}: {} */\n", label.0.name()));
+        }
+
+        for (labels, name) in self.label_arrays.iter() {
+            let labels: Vec<_> = labels.iter().map(|label| label.to_string()).collect();
+            result.push_str(&format!(
+                "static const intptr_t {name}[] = {{{}}};\n",
+                labels.join(", ")
+            ));
+        }
+
+        result.push('\n');
+        for global in globals {
+            result.push_str(&global);
+            result.push('\n');
+        }
+
+        result.push('\n');
+        for prototype in self.prototypes.iter() {
+            result.push_str(prototype);
+            result.push_str(";\n");
+        }
+
+        for function in self.functions.iter() {
+            result.push('\n');
+            result.push_str(function);
+        }
+
+        result.push_str("\nstatic void nml_init(void) {\n");
+        result.push_str(&init.code);
+        result.push_str("}\n\nint main(void) {\n");
+        result.push_str(&format!("{INDENT}nml_init();\n"));
+        if let Some(print) = print {
+            result.push_str(&print);
+        }
+
+        result.push_str(&format!("{INDENT}return 0;\n}}\n"));
+        result
+    }
+
+    /// Define a global for each constructor of a data type. Constructors
+    /// without arguments are values, while the others are curried functions.
+    fn data(&mut self, globals: &mut Vec<String>, init: &mut Function, data: &'a Data<'a>) {
+        self.datas.insert(data.name, data);
+
+        for (tag, ctor) in data.ctors.iter().enumerate() {
+            let arity = ctor.fields.len();
+            self.ctors.insert(ctor.name, Ctor { tag, arity });
+
+            let global = self.fresh("g", &ctor.name);
+            globals.push(format!("static nml_value {global};"));
+            self.globals.insert(ctor.name, global.clone());
+
+            if arity == 0 {
+                init.line(format!("{global} = nml_data({tag}, 0);"));
+                continue;
+            }
+
+            let first = curried(arity, |n, next| {
+                let function = self.fresh("f", &ctor.name);
+                let mut body = Function::new();
+
+                let result = match next {
+                    None => {
+                        let result = body.temp(self, format!("nml_data({tag}, {arity})"));
+                        for i in 0..n {
+                            body.line(format!("NML_FIELD({result}, {i}) = env[{i}];"));
+                        }
+
+                        body.line(format!("NML_FIELD({result}, {n}) = arg;"));
+                        result
+                    }
+
+                    Some(next) => {
+                        let result = body.temp(self, format!("nml_closure({next}, {})", n + 1));
+                        for i in 0..n {
+                            body.line(format!("NML_ENV({result}, {i}) = env[{i}];"));
+                        }
+
+                        body.line(format!("NML_ENV({result}, {n}) = arg;"));
+                        result
+                    }
+                };
+
+                self.function(&function, "arg", body, &result);
+                function
+            });
+
+            init.line(format!("{global} = nml_closure({first}, 0);"));
+        }
+    }
+
+    /// Emit the statements evaluating an expression to `f`, and return a C
+    /// expression for its value.
+    fn expr(&mut self, f: &mut Function, expr: &Expr<'a, '_>) -> String {
+        match expr {
+            Expr::Var(name) => match f.locals.get(name) {
+                Some(local) => local.clone(),
+                None => self.globals[name].clone(),
+            },
+
+            Expr::Unit => "0".into(),
+            Expr::Number(value) => {
+                let value = integer(value).expect("literals are checked to fit when parsed");
+                format!("(nml_value){value}")
+            }
+            Expr::Fail(_) => "nml_fail()".into(),
+
+            Expr::Field(record, ty, label) => {
+                let record = self.expr(f, record);
+                let record = f.atom(self, record);
+                match offset(ty, label) {
+                    Some(offset) => format!("NML_VALUE({record}, {offset})"),
+                    None => {
+                        let label = self.labels[label];
+                        format!("NML_VALUE({record}, nml_offset({record}, {label}))")
+                    }
+                }
+            }
+
+            Expr::Record(fields, extend) => {
+                let mut values: Vec<_> = fields
+                    .iter()
+                    .map(|(label, field)| {
+                        let value = self.expr(f, field);
+                        (self.labels[label], f.atom(self, value))
+                    })
+                    .collect();
+
+                values.sort_by_key(|(label, _)| *label);
+
+                let labels: Vec<_> = values.iter().map(|(label, _)| *label).collect();
+                let labels = self.label_array(labels);
+                let record = f.temp(self, format!("nml_record({}, {labels})", values.len()));
+                for (index, (_, value)) in values.into_iter().enumerate() {
+                    f.line(format!("NML_VALUE({record}, {index}) = {value};"));
+                }
+
+                match extend {
+                    Some(extend) => {
+                        let extend = self.expr(f, extend);
+                        format!("nml_extend({record}, {extend})")
+                    }
+
+                    None => record,
+                }
+            }
+
+            Expr::Restrict(record, label) => {
+                let record = self.expr(f, record);
+                let label = self.labels[label];
+                format!("nml_restrict({record}, {label})")
+            }
+
+            Expr::Apply([fun, arg]) => {
+                if let Some(result) = self.construct(f, expr) {
+                    return result;
+                }
+
+                let fun = self.expr(f, fun);
+                let fun = f.atom(self, fun);
+                let arg = self.expr(f, arg);
+                format!("nml_apply({fun}, {arg})")
+            }
+
+            Expr::Primitive(primitive, args) => {
+                let args: Vec<_> = args
+                    .iter()
+                    .map(|arg| {
+                        let value = self.expr(f, arg);
+                        f.atom(self, value)
+                    })
+                    .collect();
+
+                match primitive {
+                    Primitive::Add => format!("nml_add({}, {})", args[0], args[1]),
+                    Primitive::Sub => format!("nml_sub({}, {})", args[0], args[1]),
+                    Primitive::Mul => format!("nml_mul({}, {})", args[0], args[1]),
+                    Primitive::Less => {
+                        format!("({} < {} ? {} : {})", args[0], args[1], args[2], args[3])
+                    }
+                }
+            }
+
+            Expr::Lambda(param, _, body) => {
+                let captures: Vec<_> = free_vars(expr)
+                    .into_iter()
+                    .filter_map(|name| Some((name, f.locals.get(&name)?.clone())))
+                    .collect();
+
+                let function = self.fresh("f", param);
+                let param_var = self.fresh("v", param);
+
+                let mut inner = Function::new();
+                for (index, (name, _)) in captures.iter().enumerate() {
+                    inner.locals.insert(*name, format!("env[{index}]"));
+                }
+
+                inner.locals.insert(*param, param_var.clone());
+                let result = self.expr(&mut inner, body);
+                self.function(&function, &param_var, inner, &result);
+
+                let closure = f.temp(self, format!("nml_closure({function}, {})", captures.len()));
+
+                for (index, (_, local)) in captures.iter().enumerate() {
+                    f.line(format!("NML_ENV({closure}, {index}) = {local};"));
+                }
+
+                closure
+            }
+
+            Expr::TypeApply(expr, _) | Expr::TypeLambda(_, expr) => self.expr(f, expr),
+
+            Expr::Let(binding, body) => {
+                let value = self.expr(f, &binding.body);
+                let local = self.fresh("v", &binding.name);
+                f.line(format!("nml_value {local} = {value};"));
+                f.locals.insert(binding.name, local);
+                self.expr(f, body)
+            }
+
+            Expr::Case(scrutinee, alts, default) => {
+                let scrutinee = self.expr(f, scrutinee);
+                let scrutinee = f.atom(self, scrutinee);
+                let result = self.fresh_temp();
+
+                f.line(format!("nml_value {result};"));
+                f.line(format!("switch (NML_TAG({scrutinee})) {{"));
+
+                for alt in alts.iter() {
+                    let tag = self.ctors[&alt.ctor].tag;
+                    f.line(format!("case {tag}: {{"));
+                    f.indent();
+
+                    let used = free_vars(&alt.body);
+                    for (index, (name, _)) in alt.binds.iter().enumerate() {
+                        if !used.contains(name) {
+                            continue;
+                        }
+
+                        let local = self.fresh("v", name);
+                        f.line(format!(
+                            "nml_value {local} = NML_FIELD({scrutinee}, {index});"
+                        ));
+                        f.locals.insert(*name, local);
+                    }
+
+                    let value = self.expr(f, &alt.body);
+                    f.line(format!("{result} = {value};"));
+                    f.line("break;".into());
+                    f.dedent();
+                    f.line("}".into());
+                }
+
+                f.line("default: {".into());
+                f.indent();
+                let value = match default {
+                    Some(default) => self.expr(f, default),
+                    None => "nml_fail()".into(),
+                };
+
+                f.line(format!("{result} = {value};"));
+                f.line("break;".into());
+                f.dedent();
+                f.line("}".into());
+                f.line("}".into());
+
+                result
+            }
+        }
+    }
+
+    /// If the expression applies a constructor to all of its arguments,
+    /// construct the value directly instead of calling the curried
+    /// constructor.
+    fn construct(&mut self, f: &mut Function, expr: &Expr<'a, '_>) -> Option<String> {
+        let (name, args) = saturated(expr, |name| Some(self.ctors.get(name)?.arity))?;
+        let ctor = &self.ctors[&name];
+        let (tag, arity) = (ctor.tag, ctor.arity);
+        let args: Vec<_> = args
+            .into_iter()
+            .map(|arg| {
+                let value = self.expr(f, arg);
+                f.atom(self, value)
+            })
+            .collect();
+
+        let result = f.temp(self, format!("nml_data({tag}, {arity})"));
+        for (index, arg) in args.into_iter().enumerate() {
+            f.line(format!("NML_FIELD({result}, {index}) = {arg};"));
+        }
+
+        Some(result)
+    }
+
+    /// Get the function printing values of the given type, emitting it if it
+    /// doesn't exist yet. Printers take the value and whether it is nested
+    /// within a constructor, in which case it is parenthesized if needed.
+    fn printer(&mut self, ty: &'a Type<'a>) -> String {
+        let key = format!("{ty:?}");
+        if let Some(printer) = self.printers.get(&key) {
+            return printer.clone();
+        }
+
+        self.counter += 1;
+        let printer = format!("print_{}", self.counter);
+        self.printers.insert(key, printer.clone());
+
+        let mut body = Function::new();
+
+        match ty {
+            Type::Forall(_, ty) => {
+                let printer = self.printer(ty);
+                body.line(format!("{printer}(value, nested);"));
+            }
+
+            Type::Integer => body.line("printf(\"%\" PRIdPTR, value);".into()),
+            Type::Unit => body.line("fputs(\"()\", stdout);".into()),
+
+            Type::Record(row) => match closed_fields(row) {
+                Some(fields) if fields.is_empty() => body.line("fputs(\"{}\", stdout);".into()),
+
+                Some(fields) => {
+                    for (index, (label, ty)) in fields.into_iter().enumerate() {
+                        let printer = self.printer(ty);
+                        let sep = if index == 0 { "{ " } else { ", " };
+                        let label = label.0.name();
+                        body.line(format!("fputs(\"{sep}{label} = \", stdout);"));
+                        body.line(format!("{printer}(NML_VALUE(value, {index}), 0);"));
+                    }
+
+                    body.line("fputs(\" }\", stdout);".into());
+                }
+
+                None => body.line("fputs(\"<record>\", stdout);".into()),
+            },
+
+            ty if ty.as_arrow().is_some() => body.line("fputs(\"<function>\", stdout);".into()),
+
+            ty => match self.constructors(ty) {
+                Some(ctors) => {
+                    body.line("switch (NML_TAG(value)) {".into());
+                    for (tag, (name, fields)) in ctors.into_iter().enumerate() {
+                        body.line(format!("case {tag}:"));
+                        body.indent();
+
+                        if fields.is_empty() {
+                            body.line(format!("fputs(\"{name}\", stdout);"));
+                        } else {
+                            body.line("if (nested) putchar('(');".into());
+                            body.line(format!("fputs(\"{name}\", stdout);"));
+                            for (index, field) in fields.into_iter().enumerate() {
+                                let printer = self.printer(field);
+                                body.line("putchar(' ');".into());
+                                body.line(format!("{printer}(NML_FIELD(value, {index}), 1);"));
+                            }
+
+                            body.line("if (nested) putchar(')');".into());
+                        }
+
+                        body.line("break;".into());
+                        body.dedent();
+                    }
+
+                    body.line("}".into());
+                }
+
+                None => body.line("fputs(\"<value>\", stdout);".into()),
+            },
+        }
+
+        let signature = format!("static void {printer}(nml_value value, int nested)");
+        self.prototypes.push(signature.clone());
+        self.functions.push(format!(
+            "{signature} {{\n{INDENT}(void)nested;\n{}}}\n",
+            body.code
+        ));
+
+        printer
+    }
+
+    /// Get the name and argument types of each constructor of a data type
+    /// applied to some arguments.
+    fn constructors(&self, ty: &'a Type<'a>) -> Option<Vec<(String, Vec<&'a Type<'a>>)>> {
+        let (Type::Named(name), args) = ty.spine() else {
+            return None;
+        };
+
+        let data = self.datas.get(name)?;
+        if data.params.len() != args.len() {
+            return None;
+        }
+
+        let subst = data
+            .params
+            .iter()
+            .copied()
+            .zip(args.into_iter().map(TypeArg::Type))
+            .collect();
+
+        Some(
+            data.ctors
+                .iter()
+                .map(|ctor| {
+                    let name = self.name(&ctor.name);
+                    let fields = ctor
+                        .fields
+                        .iter()
+                        .map(|field| substitute(self.alloc, &subst, field))
+                        .collect();
+                    (name, fields)
+                })
+                .collect(),
+        )
+    }
+
+    fn function(&mut self, name: &str, param: &str, body: Function, result: &str) {
+        let signature = format!("static nml_value {name}(nml_value *env, nml_value {param})");
+        self.prototypes.push(signature.clone());
+        self.functions.push(format!(
+            "{signature} {{\n{INDENT}(void)env;\n{}{INDENT}return {result};\n}}\n",
+            body.code
+        ));
+    }
+
+    fn label_array(&mut self, labels: Vec<usize>) -> String {
+        let count = self.label_arrays.len();
+        self.label_arrays
+            .entry(labels)
+            .or_insert_with(|| format!("labels_{count}"))
+            .clone()
+    }
+
+    /// Make a fresh C identifier for a name.
+    fn fresh(&mut self, prefix: &str, name: &Name) -> String {
+        self.counter += 1;
+        let ident: String = self
+            .name(name)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        format!("{prefix}_{ident}_{}", self.counter)
+    }
+
+    fn fresh_temp(&mut self) -> String {
+        self.counter += 1;
+        format!("t_{}", self.counter)
+    }
+
+    fn name(&self, name: &Name) -> String {
+        self.names.get_name(name).name.name().into()
+    }
+}
+
+impl Function {
+    fn new() -> Self {
+        Self {
+            locals: BTreeMap::new(),
+            code: String::new(),
+            indent: INDENT.into(),
+        }
+    }
+
+    fn line(&mut self, line: String) {
+        self.code.push_str(&self.indent);
+        self.code.push_str(&line);
+        self.code.push('\n');
+    }
+
+    fn indent(&mut self) {
+        self.indent.push_str(INDENT);
+    }
+
+    fn dedent(&mut self) {
+        self.indent.truncate(self.indent.len() - INDENT.len());
+    }
+
+    /// Store a value in a fresh temporary, returning the temporary.
+    fn temp(&mut self, emitter: &mut Emitter, value: String) -> String {
+        let temp = emitter.fresh_temp();
+        self.line(format!("nml_value {temp} = {value};"));
+        temp
+    }
+
+    /// Store a value in a fresh temporary if it isn't a variable or a literal,
+    /// so that it is evaluated exactly once and before anything after it.
+    fn atom(&mut self, emitter: &mut Emitter, value: String) -> String {
+        let simple = value.starts_with("env[")
+            || value.starts_with("(nml_value)")
+            || value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        if simple {
+            value
+        } else {
+            self.temp(emitter, value)
+        }
+    }
+}
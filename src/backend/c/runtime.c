@@ -0,0 +1,174 @@
+/* The nml runtime. Every value is a single machine word: integers and unit are
+ * stored directly, while closures, constructors and records are pointers to
+ * objects which are allocated on the heap and never freed. */
+
+#include <inttypes.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+typedef intptr_t nml_value;
+
+/* Integers are 64 bits in every backend. */
+_Static_assert(sizeof(nml_value) == 8, "nml needs a 64-bit target");
+typedef nml_value (*nml_code)(nml_value *env, nml_value arg);
+
+/* A function along with the values of its free variables. */
+struct nml_closure {
+    nml_code code;
+    nml_value env[];
+};
+
+/* A constructor, identified by its index in its data type, applied to its
+ * arguments. */
+struct nml_data {
+    intptr_t tag;
+    nml_value fields[];
+};
+
+/* A record stores its fields sorted by label. If a label occurs several times,
+ * the field added last comes first. */
+struct nml_record {
+    intptr_t size;
+    const intptr_t *labels;
+    nml_value values[];
+};
+
+#define NML_ENV(closure, i) (((struct nml_closure *)(closure))->env[i])
+#define NML_TAG(data) (((struct nml_data *)(data))->tag)
+#define NML_FIELD(data, i) (((struct nml_data *)(data))->fields[i])
+#define NML_SIZE(record) (((struct nml_record *)(record))->size)
+#define NML_LABEL(record, i) (((struct nml_record *)(record))->labels[i])
+#define NML_VALUE(record, i) (((struct nml_record *)(record))->values[i])
+
+#define NML_CHUNK_SIZE ((size_t)1 << 20)
+
+static char *nml_heap = NULL;
+static size_t nml_heap_left = 0;
+
+static inline void *nml_alloc(size_t size) {
+    size = (size + sizeof(nml_value) - 1) & ~(sizeof(nml_value) - 1);
+
+    if (size > nml_heap_left) {
+        size_t chunk = size > NML_CHUNK_SIZE ? size : NML_CHUNK_SIZE;
+        nml_heap = malloc(chunk);
+        nml_heap_left = chunk;
+
+        if (nml_heap == NULL) {
+            fputs("out of memory\n", stderr);
+            exit(1);
+        }
+    }
+
+    void *result = nml_heap;
+    nml_heap += size;
+    nml_heap_left -= size;
+    return result;
+}
+
+static inline nml_value nml_fail(void) {
+    fputs("pattern match failure\n", stderr);
+    exit(1);
+}
+
+static inline nml_value nml_closure(nml_code code, intptr_t captures) {
+    struct nml_closure *closure =
+        nml_alloc(sizeof(struct nml_closure) + captures * sizeof(nml_value));
+    closure->code = code;
+    return (nml_value)closure;
+}
+
+static inline nml_value nml_apply(nml_value fun, nml_value arg) {
+    struct nml_closure *closure = (struct nml_closure *)fun;
+    return closure->code(closure->env, arg);
+}
+
+static inline nml_value nml_data(intptr_t tag, intptr_t arity) {
+    struct nml_data *data = nml_alloc(sizeof(struct nml_data) + arity * sizeof(nml_value));
+    data->tag = tag;
+    return (nml_value)data;
+}
+
+static inline nml_value nml_record(intptr_t size, const intptr_t *labels) {
+    struct nml_record *record =
+        nml_alloc(sizeof(struct nml_record) + size * sizeof(nml_value));
+    record->size = size;
+    record->labels = labels;
+    return (nml_value)record;
+}
+
+/* Find the index of the first field with the given label. */
+static inline intptr_t nml_offset(nml_value record, intptr_t label) {
+    intptr_t low = 0;
+    intptr_t high = NML_SIZE(record);
+
+    while (low < high) {
+        intptr_t mid = low + (high - low) / 2;
+        if (NML_LABEL(record, mid) < label) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    return low;
+}
+
+/* Add the fields of `fields` to `record`, in front of any fields with the same
+ * label. */
+static inline nml_value nml_extend(nml_value fields, nml_value record) {
+    intptr_t size = NML_SIZE(fields) + NML_SIZE(record);
+    intptr_t *labels = nml_alloc(size * sizeof(intptr_t));
+    nml_value result = nml_record(size, labels);
+
+    intptr_t i = 0, j = 0, k = 0;
+    while (k < size) {
+        int from_fields = j >= NML_SIZE(record) ||
+                          (i < NML_SIZE(fields) && NML_LABEL(fields, i) <= NML_LABEL(record, j));
+
+        if (from_fields) {
+            labels[k] = NML_LABEL(fields, i);
+            NML_VALUE(result, k) = NML_VALUE(fields, i);
+            i++;
+        } else {
+            labels[k] = NML_LABEL(record, j);
+            NML_VALUE(result, k) = NML_VALUE(record, j);
+            j++;
+        }
+
+        k++;
+    }
+
+    return result;
+}
+
+/* Remove the first field with the given label. */
+static inline nml_value nml_restrict(nml_value record, intptr_t label) {
+    intptr_t size = NML_SIZE(record) - 1;
+    intptr_t removed = nml_offset(record, label);
+    intptr_t *labels = nml_alloc(size * sizeof(intptr_t));
+    nml_value result = nml_record(size, labels);
+
+    for (intptr_t i = 0, k = 0; i <= size; i++) {
+        if (i != removed) {
+            labels[k] = NML_LABEL(record, i);
+            NML_VALUE(result, k) = NML_VALUE(record, i);
+            k++;
+        }
+    }
+
+    return result;
+}
+
+/* Arithmetic wraps around on overflow, like in the other backends. */
+static inline nml_value nml_add(nml_value a, nml_value b) {
+    return (nml_value)((uintptr_t)a + (uintptr_t)b);
+}
+
+static inline nml_value nml_sub(nml_value a, nml_value b) {
+    return (nml_value)((uintptr_t)a - (uintptr_t)b);
+}
+
+static inline nml_value nml_mul(nml_value a, nml_value b) {
+    return (nml_value)((uintptr_t)a * (uintptr_t)b);
+}
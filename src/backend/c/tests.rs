@@ -0,0 +1,136 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use super::emit;
+use crate::backend::testing;
+use crate::frontend::testing::elaborated;
+use crate::ir::lower::lower;
+
+/// Generate C code for the given (well typed) source.
+fn generate(source: &str) -> String {
+    elaborated(source, false, |alloc, names, _, program| {
+        let core = alloc.alloc(lower(alloc, names, &program));
+        emit(alloc, names, core)
+    })
+}
+
+/// Compile the generated code for the given source with the system C compiler
+/// and return what it prints. Returns `None` if no C compiler is installed and
+/// such tests are skipped.
+fn run(name: &str, source: &str) -> Option<String> {
+    let code = generate(source);
+
+    let dir = std::env::temp_dir().join(format!("nml-c-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let input = dir.join(format!("{name}.c"));
+    let output: PathBuf = dir.join(name);
+    std::fs::write(&input, &code).unwrap();
+
+    let compiled = testing::output(
+        Command::new("cc")
+            .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-o"])
+            .arg(&output)
+            .arg(&input),
+    )?;
+
+    assert!(
+        compiled.status.success(),
+        "{}\n\n{code}",
+        String::from_utf8_lossy(&compiled.stderr)
+    );
+
+    let ran = Command::new(&output).output().unwrap();
+    assert!(ran.status.success());
+    Some(String::from_utf8(ran.stdout).unwrap())
+}
+
+#[test]
+fn lists_and_classes() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+
+        class Functor f = { map : ('a -> 'b) -> f 'a -> f 'b }
+
+        instance Functor List = {
+            map = f => (Nil => Nil | Cons x xs => Cons (f x) (map f xs))
+        }
+
+        let append = Nil => ys => ys | Cons x xs => ys => Cons x (append xs ys)
+        let main = map (x => { x }) (append (Cons 1 Nil) (Cons 2 Nil))
+    ";
+
+    if let Some(output) = run("lists_and_classes", source) {
+        assert_eq!("Cons { x = 1 } (Cons { x = 2 } Nil)\n", output);
+    }
+}
+
+#[test]
+fn open_records() {
+    let source = "
+        let get r = r.b
+        let extend r = { a = 5, ... r }
+        let main = { got = get { a = 1, b = 2, c = 3 }, extended = extend { a = 1, b = 2 } }
+    ";
+
+    if let Some(output) = run("open_records", source) {
+        assert_eq!("{ extended = { a = 5, a = 1, b = 2 }, got = 2 }\n", output);
+    }
+}
+
+#[test]
+fn or_patterns() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        let second = (Cons x Nil | Cons _ (Cons x _)) => x | _ => 0
+        let main = { one = second (Cons 1 Nil), two = second (Cons 1 (Cons 2 Nil)), none = second Nil }
+    ";
+
+    if let Some(output) = run("or_patterns", source) {
+        assert_eq!("{ none = 0, one = 1, two = 2 }\n", output);
+    }
+}
+
+#[test]
+fn nominal_records() {
+    let source = "
+        data Person = Person { name : int, age : int }
+        let swap = Person { name, age } => Person { name = age, age = name }
+        let main = swap (Person { name = 1, age = 2 })
+    ";
+
+    if let Some(output) = run("nominal_records", source) {
+        assert_eq!("Person { age = 1, name = 2 }\n", output);
+    }
+}
+
+#[test]
+fn closures_capture_variables() {
+    let source = "
+        let const x = y => { x, y }
+        let main = let f = const 1 in f 2
+    ";
+
+    if let Some(output) = run("closures_capture_variables", source) {
+        assert_eq!("{ x = 1, y = 2 }\n", output);
+    }
+}
+
+#[test]
+fn programs_without_main_print_nothing() {
+    if let Some(output) = run("no_main", "let id x = x") {
+        assert_eq!("", output);
+    }
+}
+
+#[test]
+fn integer_operations() {
+    let source = "
+        let max a b = less a b b a
+        let main = { max = max 3 8, poly = sub (mul 6 7) (add 1 2), pick = less 2 1 { a = 1 } { a = 2 } }
+    ";
+
+    if let Some(output) = run("integer_operations", source) {
+        assert_eq!("{ max = 8, pick = { a = 2 }, poly = 39 }\n", output);
+    }
+}
@@ -0,0 +1,971 @@
+//! The JavaScript backend turns a typed program into a readable ES module,
+//! along with a source map relating its lines to the source program.
+//!
+//! Unlike the other backends, this works on the (elaborated) typed program
+//! rather than the core program, since that is much closer to what was written.
+//! Every top-level binding of the source is exported, while those of the
+//! prelude are only declared. The groups of mutually recursive bindings found
+//! by the type checker are emitted in order, with every function hoisted so the
+//! bindings of a group can refer to each other freely.
+//!
+//! Records are plain objects, and constructors are objects tagged with the name
+//! of the constructor, like `{ tag: "Cons", args: [x, xs] }`. A data type with
+//! a single constructor wrapping a record is represented by the record itself,
+//! which makes the dictionaries of classes plain objects too. Lambdas matching
+//! on their argument become functions switching on the tags of its parts.
+//!
+//! Integers are `BigInt`s, and arithmetic on them is wrapped to 64 bits with
+//! `BigInt.asIntN`, so that they behave exactly like in the other backends.
+
+mod source_map;
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bumpalo::Bump;
+
+use self::source_map::SourceMap;
+use crate::frontend::builtins::Primitive;
+use crate::frontend::names::{Name, Names};
+use crate::frontend::source::{Source, SourceId, Span};
+use crate::frontend::trees::{inferred, typed};
+use crate::frontend::tyck::Type;
+use crate::ir::lower::{bind_name, bound_names, strip_groups};
+use crate::matching::{self, Access, Constructors, Decision, Match, Occurrence};
+
+const INDENT: &str = "    ";
+
+/// The generated module and its source map.
+pub struct Output {
+    pub code: String,
+    pub map: String,
+}
+
+/// Emit an ES module for a program. The source map refers to the source by
+/// `source_name`, and to the module by `file`.
+pub fn emit<'a, 'src>(
+    alloc: &'a Bump,
+    names: &Names<'src>,
+    source: &Source,
+    source_name: &str,
+    file: &str,
+    program: &typed::Program<'a, 'src>,
+) -> Output {
+    let mut emitter = Emitter {
+        alloc,
+        names,
+        constructors: Constructors::new(program),
+        ctors: BTreeMap::new(),
+        idents: BTreeMap::new(),
+        used: BTreeSet::new(),
+    };
+
+    let block = emitter.program(program);
+
+    let mut map = SourceMap::new(&source.content);
+    let mut code = String::new();
+    for (line, Line { indent, text, span }) in block.lines.into_iter().enumerate() {
+        // Code from the prelude has no position in the source file.
+        if let Some(span) = span.filter(|span| span.source == source.id) {
+            map.add(line, indent * INDENT.len(), span);
+        }
+
+        if !text.is_empty() {
+            code.push_str(&INDENT.repeat(indent));
+            code.push_str(&text);
+        }
+
+        code.push('\n');
+    }
+
+    code.push_str(&format!("//# sourceMappingURL={file}.map\n"));
+
+    Output {
+        code,
+        map: map.encode(file, source_name),
+    }
+}
+
+/// A constructor, along with the number of arguments it takes.
+struct Ctor {
+    arity: usize,
+
+    /// Whether this is the only constructor of its type and wraps a record,
+    /// in which case it is represented by the record alone.
+    nominal: bool,
+}
+
+/// A line of code, along with the span of source it was generated from.
+struct Line {
+    indent: usize,
+    text: String,
+    span: Option<Span>,
+}
+
+struct Block {
+    lines: Vec<Line>,
+    indent: usize,
+}
+
+/// The code evaluated when an arm of a match is chosen.
+enum Target<'p, 'a, 'src> {
+    /// Return the value of the body of the arm.
+    Expr(&'p typed::Expr<'a, 'src>),
+
+    /// Return the result of calling the given function with the names bound by
+    /// the pattern of the arm.
+    Call(String, Vec<Name>),
+
+    /// Return an object holding the names bound by the pattern of the arm.
+    Binds(Vec<Name>),
+}
+
+struct Emitter<'n, 'src, 'a> {
+    alloc: &'a Bump,
+    names: &'n Names<'src>,
+
+    constructors: Constructors,
+    ctors: BTreeMap<Name, Ctor>,
+
+    /// The JavaScript identifier of every name, which are all distinct so that
+    /// no identifier is ever shadowed or declared twice.
+    idents: BTreeMap<Name, String>,
+    used: BTreeSet<String>,
+}
+
+impl<'a, 'src> Emitter<'_, 'src, 'a> {
+    fn program(&mut self, program: &typed::Program<'a, 'src>) -> Block {
+        // Top-level names are declared first so that they keep their names,
+        // with those of the source before those of the prelude.
+        let items = || program.items.iter().flat_map(|items| items.iter());
+        let prelude = |item: &&typed::Item| item.span.source == SourceId::PRELUDE;
+        let source = items().filter(|item| !prelude(item));
+        for item in source.chain(items().filter(prelude)) {
+            match &item.node {
+                typed::ItemNode::Let(pattern, ..) => {
+                    for name in bound_names(pattern) {
+                        self.ident(&name.0);
+                    }
+                }
+
+                typed::ItemNode::Data(_, data) => {
+                    for ctor in constructors(data) {
+                        self.ident(&ctor.0);
+                    }
+                }
+
+                typed::ItemNode::Instance((dict, _), ..) => {
+                    self.ident(dict);
+                }
+
+                typed::ItemNode::Alias(..)
+                | typed::ItemNode::Class(..)
+                | typed::ItemNode::Invalid(_) => {}
+            }
+        }
+
+        let mut block = Block::new(0);
+
+        for item in items() {
+            let before = block.lines.len();
+
+            match &item.node {
+                typed::ItemNode::Data(_, data) => self.data(&mut block, item.span, data),
+                typed::ItemNode::Let(pattern, expr, _) => {
+                    self.let_item(&mut block, item.span, pattern, expr);
+                }
+
+                typed::ItemNode::Instance((dict, _), body, _) => {
+                    let value = self.expr(&mut block, body);
+                    let dict = self.ident(dict);
+                    block.line(Some(item.span), format!("const {dict} = {value};"));
+                }
+
+                typed::ItemNode::Alias(..)
+                | typed::ItemNode::Class(..)
+                | typed::ItemNode::Invalid(_) => {}
+            }
+
+            if block.lines.len() > before {
+                block.line(None, String::new());
+            }
+        }
+
+        block
+    }
+
+    /// Define each constructor of a data type. Constructors without arguments
+    /// are values, while the others are curried functions.
+    fn data(&mut self, block: &mut Block, span: Span, data: &inferred::Data<'a>) {
+        let ctors = constructors(data);
+        let nominal =
+            matches!(&ctors[..], [(_, [field])] if matches!(field.expand(), Type::Record(_)));
+
+        for (name, fields) in ctors {
+            let arity = fields.len();
+            self.ctors.insert(name, Ctor { arity, nominal });
+
+            let ident = self.ident(&name);
+            let value = if nominal {
+                "(record) => record".into()
+            } else if arity == 0 {
+                format!("{{ tag: \"{}\" }}", self.name(&name))
+            } else {
+                let args: Vec<_> = (0..arity).map(|index| format!("x{index}")).collect();
+                let params: Vec<_> = args.iter().map(|arg| format!("({arg}) => ")).collect();
+                format!(
+                    "{}({{ tag: \"{}\", args: [{}] }})",
+                    params.concat(),
+                    self.name(&name),
+                    args.join(", ")
+                )
+            };
+
+            block.line(
+                Some(span),
+                format!("{}const {ident} = {value};", export(span)),
+            );
+        }
+    }
+
+    fn let_item(
+        &mut self,
+        block: &mut Block,
+        span: Span,
+        pattern: &typed::Pattern<'a>,
+        expr: &typed::Expr<'a, 'src>,
+    ) {
+        if let Some(name) = bind_name(pattern) {
+            let ident = self.ident(&name);
+            if let typed::ExprNode::Lambda(arms) = &strip_groups(expr).node {
+                let head = format!("{}function {ident}", export(span));
+                self.function(block, span, &head, arms);
+            } else {
+                let value = self.expr(block, expr);
+                block.line(
+                    Some(span),
+                    format!("{}const {ident} = {value};", export(span)),
+                );
+            }
+
+            return;
+        }
+
+        let value = self.expr(block, expr);
+        let value = self.atom(block, span, value);
+        let names = bound_names(pattern).into_iter().map(|(name, _)| name);
+        let names: Vec<_> = names.collect();
+
+        if let Some(binds) = self.irrefutable(pattern, &value) {
+            for (name, at) in binds {
+                let ident = self.ident(&name);
+                block.line(Some(span), format!("{}const {ident} = {at};", export(span)));
+            }
+
+            return;
+        }
+
+        let function = self.fresh("destructure");
+        let mut inner = Block::new(block.indent + 1);
+        let param = self.fresh("value");
+        self.matching(
+            &mut inner,
+            span,
+            &param,
+            vec![(pattern, Target::Binds(names.clone()))],
+        );
+
+        block.line(Some(span), format!("function {function}({param}) {{"));
+        block.extend(inner);
+        block.line(None, "}".into());
+
+        let idents: Vec<_> = names.iter().map(|name| self.ident(name)).collect();
+        block.line(
+            Some(span),
+            format!(
+                "{}const {{ {} }} = {function}({value});",
+                export(span),
+                idents.join(", ")
+            ),
+        );
+    }
+
+    /// Emit the code evaluating an expression to `block`, and return a
+    /// JavaScript expression for its value.
+    fn expr(&mut self, block: &mut Block, expr: &typed::Expr<'a, 'src>) -> String {
+        match &expr.node {
+            typed::ExprNode::Invalid(_) | typed::ExprNode::Hole => {
+                block.line(
+                    Some(expr.span),
+                    "throw new Error(\"erroneous code\");".into(),
+                );
+                "undefined".into()
+            }
+
+            typed::ExprNode::Var(var) => match Primitive::from_name(self.names, &var.name) {
+                Some(primitive) => {
+                    let params: Vec<_> = (0..primitive.arity()).map(|_| self.fresh("x")).collect();
+                    let body = operation(primitive, &params);
+                    let params: Vec<_> = params
+                        .iter()
+                        .map(|param| format!("({param}) => "))
+                        .collect();
+                    format!("{}{body}", params.concat())
+                }
+
+                None => self.ident(&var.name),
+            },
+            typed::ExprNode::Unit => "undefined".into(),
+            typed::ExprNode::Number(value) => {
+                let digits = value.replace('_', "");
+                let digits = digits.trim_start_matches('0');
+                format!("{}n", if digits.is_empty() { "0" } else { digits })
+            }
+
+            typed::ExprNode::Anno(_, v) => match *v {},
+            typed::ExprNode::Group(expr) => self.expr(block, expr),
+
+            typed::ExprNode::Field(record, label, _) => {
+                let Ok(label) = label else {
+                    return "undefined".into();
+                };
+
+                let record = self.callee(block, record);
+                format!("{record}.{}", label.0.name())
+            }
+
+            typed::ExprNode::Record(fields, extend) => {
+                let mut parts = Vec::new();
+                if let Some(extend) = extend {
+                    parts.push(format!("...{}", self.expr(block, extend)));
+                }
+
+                for (label, _, field) in fields.iter() {
+                    let value = self.expr(block, field);
+                    let Ok(label) = label else {
+                        continue;
+                    };
+
+                    let label = label.0.name();
+                    if value == label {
+                        parts.push(value);
+                    } else {
+                        parts.push(format!("{label}: {value}"));
+                    }
+                }
+
+                if parts.is_empty() {
+                    "{}".into()
+                } else {
+                    format!("{{ {} }}", parts.join(", "))
+                }
+            }
+
+            typed::ExprNode::Restrict(record, label) => {
+                let record = self.expr(block, record);
+                let rest = self.fresh("rest");
+                block.line(
+                    Some(expr.span),
+                    format!("const {rest} = {{ ...{record} }};"),
+                );
+                block.line(
+                    Some(expr.span),
+                    format!("delete {rest}.{};", label.0.name()),
+                );
+                rest
+            }
+
+            typed::ExprNode::Apply([fun, arg]) => {
+                if let Some(value) = self.construct(block, expr) {
+                    return value;
+                }
+
+                if let Some(value) = self.primitive(block, expr) {
+                    return value;
+                }
+
+                let fun = self.callee(block, fun);
+                let arg = self.expr(block, arg);
+                format!("{fun}({arg})")
+            }
+
+            typed::ExprNode::Lambda(arms) => self.lambda(block, expr.span, arms),
+
+            typed::ExprNode::Let(pattern, [bound, body], _) => {
+                if let Some(name) = bind_name(pattern) {
+                    let ident = self.ident(&name);
+                    if let typed::ExprNode::Lambda(arms) = &strip_groups(bound).node {
+                        self.function(block, expr.span, &format!("function {ident}"), arms);
+                    } else {
+                        let value = self.expr(block, bound);
+                        block.line(Some(expr.span), format!("const {ident} = {value};"));
+                    }
+
+                    return self.expr(block, body);
+                }
+
+                let value = self.expr(block, bound);
+                let value = self.atom(block, expr.span, value);
+
+                if let Some(binds) = self.irrefutable(pattern, &value) {
+                    for (name, at) in binds {
+                        let ident = self.ident(&name);
+                        block.line(Some(expr.span), format!("const {ident} = {at};"));
+                    }
+
+                    return self.expr(block, body);
+                }
+
+                // A refutable pattern is matched like the lambda `pattern =>
+                // body` applied to the bound value.
+                let function = self.fresh("matching");
+                let mut inner = Block::new(block.indent + 1);
+                let param = self.fresh("value");
+                self.matching(
+                    &mut inner,
+                    expr.span,
+                    &param,
+                    vec![(pattern, Target::Expr(body))],
+                );
+
+                block.line(Some(expr.span), format!("function {function}({param}) {{"));
+                block.extend(inner);
+                block.line(None, "}".into());
+
+                format!("{function}({value})")
+            }
+        }
+    }
+
+    /// Emit an expression in a position where it is called or projected from,
+    /// parenthesizing it if needed.
+    fn callee(&mut self, block: &mut Block, expr: &typed::Expr<'a, 'src>) -> String {
+        let value = self.expr(block, expr);
+        if value.starts_with('(') && value.contains(") => ") {
+            format!("({value})")
+        } else {
+            value
+        }
+    }
+
+    /// If the expression applies a constructor to all of its arguments,
+    /// construct the value directly instead of calling the curried
+    /// constructor.
+    fn construct(&mut self, block: &mut Block, expr: &typed::Expr<'a, 'src>) -> Option<String> {
+        let (name, args) = saturated(expr, |name| Some(self.ctors.get(name)?.arity))?;
+        let nominal = self.ctors[&name].nominal;
+        let args: Vec<_> = args.into_iter().map(|arg| self.expr(block, arg)).collect();
+
+        if nominal {
+            return args.into_iter().next();
+        }
+
+        Some(format!(
+            "{{ tag: \"{}\", args: [{}] }}",
+            self.name(&name),
+            args.join(", ")
+        ))
+    }
+
+    /// If the expression applies a primitive operation to all of its
+    /// arguments, apply the operation directly. The arguments are evaluated
+    /// before the operation, since `less` evaluates both of its choices.
+    fn primitive(&mut self, block: &mut Block, expr: &typed::Expr<'a, 'src>) -> Option<String> {
+        let names = self.names;
+        let arity = |name: &Name| Some(Primitive::from_name(names, name)?.arity());
+        let (name, args) = saturated(expr, arity)?;
+        let primitive = Primitive::from_name(names, &name)?;
+        let args: Vec<_> = args
+            .into_iter()
+            .map(|arg| {
+                let value = self.expr(block, arg);
+                self.atom(block, expr.span, value)
+            })
+            .collect();
+
+        Some(operation(primitive, &args))
+    }
+
+    /// Emit a lambda as an arrow function if its body is a simple expression,
+    /// and as a local function otherwise.
+    fn lambda(
+        &mut self,
+        block: &mut Block,
+        span: Span,
+        arms: &[(typed::Pattern<'a>, typed::Expr<'a, 'src>)],
+    ) -> String {
+        if let [(pattern, body)] = arms {
+            if let Some(name) = bind_name(pattern) {
+                let param = self.ident(&name);
+                let mut inner = Block::new(block.indent + 1);
+                let value = self.expr(&mut inner, body);
+
+                if inner.lines.is_empty() {
+                    return if value.starts_with('{') {
+                        format!("({param}) => ({value})")
+                    } else {
+                        format!("({param}) => {value}")
+                    };
+                }
+
+                let function = self.fresh("lambda");
+                inner.line(Some(body.span), format!("return {value};"));
+                block.line(Some(span), format!("function {function}({param}) {{"));
+                block.extend(inner);
+                block.line(None, "}".into());
+                return function;
+            }
+        }
+
+        let function = self.fresh("lambda");
+        self.function(block, span, &format!("function {function}"), arms);
+        function
+    }
+
+    /// Emit a function declaration, starting with `header`, for a lambda.
+    fn function(
+        &mut self,
+        block: &mut Block,
+        span: Span,
+        header: &str,
+        arms: &[(typed::Pattern<'a>, typed::Expr<'a, 'src>)],
+    ) {
+        let mut inner = Block::new(block.indent + 1);
+
+        let param = match arms {
+            [(pattern, body)] if bind_name(pattern).is_some() => {
+                let name = bind_name(pattern).expect("checked above");
+                let value = self.expr(&mut inner, body);
+                inner.line(Some(body.span), format!("return {value};"));
+                self.ident(&name)
+            }
+
+            _ => {
+                let param = self.fresh("x");
+                let arms = arms
+                    .iter()
+                    .map(|(pattern, body)| (pattern, Target::Expr(body)))
+                    .collect();
+                self.matching(&mut inner, span, &param, arms);
+                param
+            }
+        };
+
+        block.line(Some(span), format!("{header}({param}) {{"));
+        block.extend(inner);
+        block.line(None, "}".into());
+    }
+
+    /// Match `scrutinee` against the patterns of each arm, returning from the
+    /// current function with the target of the first arm which matches. An arm
+    /// chosen in several places is made a local function called from each of
+    /// them.
+    fn matching<'p>(
+        &mut self,
+        block: &mut Block,
+        span: Span,
+        scrutinee: &str,
+        arms: Vec<(&'p typed::Pattern<'a>, Target<'p, 'a, 'src>)>,
+    ) {
+        let Some((first, _)) = arms.first() else {
+            block.line(Some(span), fail());
+            return;
+        };
+
+        let tree = matching::compile(
+            self.alloc,
+            &self.constructors,
+            first.scheme.ty,
+            arms.iter().map(|(pattern, _)| *pattern),
+        );
+
+        let uses = tree.uses(arms.len());
+        let targets: Vec<_> = arms
+            .into_iter()
+            .zip(uses)
+            .map(|((pattern, target), uses)| {
+                let Target::Expr(body) = target else {
+                    return target;
+                };
+
+                if uses <= 1 {
+                    return target;
+                }
+
+                let names: Vec<_> = bound_names(pattern).into_iter().map(|(n, _)| n).collect();
+                let params: Vec<_> = names.iter().map(|name| self.ident(name)).collect();
+                let function = self.fresh("arm");
+
+                let mut inner = Block::new(block.indent + 1);
+                let value = self.expr(&mut inner, body);
+                inner.line(Some(body.span), format!("return {value};"));
+
+                block.line(
+                    Some(body.span),
+                    format!("function {function}({}) {{", params.join(", ")),
+                );
+                block.extend(inner);
+                block.line(None, "}".into());
+
+                Target::Call(function, names)
+            })
+            .collect();
+
+        self.decision(block, span, scrutinee, &tree, &tree.tree, &targets);
+    }
+
+    fn decision(
+        &mut self,
+        block: &mut Block,
+        span: Span,
+        scrutinee: &str,
+        tree: &Match<'a>,
+        decision: &Decision<'a>,
+        targets: &[Target<'_, 'a, 'src>],
+    ) {
+        match decision {
+            Decision::Fail => block.line(Some(span), fail()),
+
+            Decision::Success(arm, binds) => {
+                for (name, at) in binds.iter() {
+                    let ident = self.ident(name);
+                    let at = self.occurrence(scrutinee, tree, *at);
+                    block.line(Some(span), format!("const {ident} = {at};"));
+                }
+
+                match &targets[*arm] {
+                    Target::Expr(body) => {
+                        let value = self.expr(block, body);
+                        block.line(Some(body.span), format!("return {value};"));
+                    }
+
+                    Target::Call(function, names) => {
+                        let args: Vec<_> = names.iter().map(|name| self.ident(name)).collect();
+                        block.line(
+                            Some(span),
+                            format!("return {function}({});", args.join(", ")),
+                        );
+                    }
+
+                    Target::Binds(names) => {
+                        let idents: Vec<_> = names.iter().map(|name| self.ident(name)).collect();
+                        block.line(Some(span), format!("return {{ {} }};", idents.join(", ")));
+                    }
+                }
+            }
+
+            // A type with a single constructor needs no test.
+            Decision::Switch(_, [case], None) => {
+                self.decision(block, span, scrutinee, tree, &case.tree, targets);
+            }
+
+            Decision::Switch(at, cases, default) => {
+                let at = self.occurrence(scrutinee, tree, *at);
+                block.line(Some(span), format!("switch ({at}.tag) {{"));
+
+                for case in cases.iter() {
+                    block.line(None, format!("case \"{}\": {{", self.name(&case.ctor)));
+                    block.indent += 1;
+                    self.decision(block, span, scrutinee, tree, &case.tree, targets);
+                    block.indent -= 1;
+                    block.line(None, "}".into());
+                }
+
+                if let Some(default) = default {
+                    block.line(None, "default: {".into());
+                    block.indent += 1;
+                    self.decision(block, span, scrutinee, tree, default, targets);
+                    block.indent -= 1;
+                    block.line(None, "}".into());
+                }
+
+                block.line(None, "}".into());
+            }
+        }
+    }
+
+    /// Get the JavaScript expression for an occurrence within the scrutinee.
+    fn occurrence(&self, scrutinee: &str, tree: &Match, at: Occurrence) -> String {
+        match tree.access(at) {
+            Access::Scrutinee => scrutinee.into(),
+            Access::Argument(of, ctor, index) => {
+                let of = self.occurrence(scrutinee, tree, of);
+                if self.ctors.get(&ctor).is_some_and(|ctor| ctor.nominal) {
+                    of
+                } else {
+                    format!("{of}.args[{index}]")
+                }
+            }
+
+            Access::Field(of, label) => {
+                format!(
+                    "{}.{}",
+                    self.occurrence(scrutinee, tree, of),
+                    label.0.name()
+                )
+            }
+        }
+    }
+
+    /// If a pattern never fails to match, get the expression for each name
+    /// it binds when matched against `value`.
+    fn irrefutable(
+        &self,
+        pattern: &typed::Pattern<'a>,
+        value: &str,
+    ) -> Option<Vec<(Name, String)>> {
+        let tree = matching::compile(self.alloc, &self.constructors, pattern.scheme.ty, [pattern]);
+        let Decision::Success(_, binds) = tree.tree else {
+            return None;
+        };
+
+        Some(
+            binds
+                .iter()
+                .map(|(name, at)| (*name, self.occurrence(value, &tree, *at)))
+                .collect(),
+        )
+    }
+
+    /// Store a value in a fresh constant if it isn't a variable, so that it is
+    /// evaluated exactly once.
+    fn atom(&mut self, block: &mut Block, span: Span, value: String) -> String {
+        if value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return value;
+        }
+
+        let ident = self.fresh("value");
+        block.line(Some(span), format!("const {ident} = {value};"));
+        ident
+    }
+
+    fn ident(&mut self, name: &Name) -> String {
+        if let Some(ident) = self.idents.get(name) {
+            return ident.clone();
+        }
+
+        let ident = self.fresh(&self.name(name));
+        self.idents.insert(*name, ident.clone());
+        ident
+    }
+
+    /// Make an identifier based on `hint` which is distinct from every other
+    /// identifier. Symbols are spelled out, so that `<=` becomes `less_equal`.
+    fn fresh(&mut self, hint: &str) -> String {
+        let mut base = String::new();
+        for c in hint.chars() {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                base.push(c);
+                continue;
+            }
+
+            if !base.is_empty() && !base.ends_with('_') {
+                base.push('_');
+            }
+
+            match symbol(c) {
+                Some(word) => base.push_str(word),
+                None => base.push_str(&format!("u{:x}", c as u32)),
+            }
+        }
+
+        if base.is_empty() || base.starts_with(|c: char| c.is_ascii_digit()) {
+            base.insert(0, '_');
+        }
+
+        if RESERVED.contains(&base.as_str()) {
+            base.push('_');
+        }
+
+        let mut ident = base.clone();
+        let mut counter = 0;
+        while !self.used.insert(ident.clone()) {
+            counter += 1;
+            ident = format!("{base}_{counter}");
+        }
+
+        ident
+    }
+
+    fn name(&self, name: &Name) -> String {
+        self.names.get_name(name).name.name().into()
+    }
+}
+
+impl Block {
+    fn new(indent: usize) -> Self {
+        Self {
+            lines: Vec::new(),
+            indent,
+        }
+    }
+
+    fn line(&mut self, span: Option<Span>, text: String) {
+        self.lines.push(Line {
+            indent: self.indent,
+            text,
+            span,
+        });
+    }
+
+    fn extend(&mut self, other: Block) {
+        self.lines.extend(other.lines);
+    }
+}
+
+/// If an expression applies a variable to exactly as many arguments as
+/// `arity` gives for it, get the variable and its arguments in the order they
+/// are applied. This is [`super::saturated`] for typed trees.
+fn saturated<'e, 'a, 'src>(
+    expr: &'e typed::Expr<'a, 'src>,
+    arity: impl FnOnce(&Name) -> Option<usize>,
+) -> Option<(Name, Vec<&'e typed::Expr<'a, 'src>>)> {
+    let mut args = Vec::new();
+    let mut head = expr;
+    loop {
+        match &head.node {
+            typed::ExprNode::Apply([fun, arg]) => {
+                args.push(arg);
+                head = fun;
+            }
+
+            typed::ExprNode::Group(expr) => head = expr,
+            _ => break,
+        }
+    }
+
+    let typed::ExprNode::Var(var) = &head.node else {
+        return None;
+    };
+
+    if arity(&var.name)? != args.len() {
+        return None;
+    }
+
+    args.reverse();
+    Some((var.name, args))
+}
+
+/// Get the name and fields of every constructor of a data type.
+fn constructors<'a>(data: &inferred::Data<'a>) -> Vec<(Name, &'a [Type<'a>])> {
+    let inferred::DataNode::Sum(ctors) = &data.node else {
+        return Vec::new();
+    };
+
+    ctors
+        .iter()
+        .filter_map(|ctor| match &ctor.node {
+            inferred::ConstructorNode::Constructor(name, fields) => Some((*name, *fields)),
+            inferred::ConstructorNode::Invalid(_) => None,
+        })
+        .collect()
+}
+
+/// Apply a primitive operation to atomic arguments. Arithmetic on `BigInt`s
+/// is exact, so it is wrapped around to 64 bits explicitly.
+fn operation(primitive: Primitive, args: &[String]) -> String {
+    match (primitive, args) {
+        (Primitive::Add, [a, b]) => format!("BigInt.asIntN(64, {a} + {b})"),
+        (Primitive::Sub, [a, b]) => format!("BigInt.asIntN(64, {a} - {b})"),
+        (Primitive::Mul, [a, b]) => format!("BigInt.asIntN(64, {a} * {b})"),
+        (Primitive::Less, [a, b, x, y]) => format!("({a} < {b} ? {x} : {y})"),
+        _ => unreachable!("primitives are applied to all of their arguments"),
+    }
+}
+
+fn fail() -> String {
+    "throw new Error(\"pattern match failure\");".into()
+}
+
+/// The keyword exporting a top-level declaration, if it comes from the source
+/// rather than the prelude.
+fn export(span: Span) -> &'static str {
+    if span.source == SourceId::PRELUDE {
+        ""
+    } else {
+        "export "
+    }
+}
+
+/// The word spelling out a symbol in an identifier.
+fn symbol(c: char) -> Option<&'static str> {
+    Some(match c {
+        '+' => "plus",
+        '-' => "minus",
+        '*' => "star",
+        '/' => "slash",
+        '\\' => "backslash",
+        '%' => "percent",
+        '^' => "caret",
+        '<' => "less",
+        '>' => "greater",
+        '=' => "equal",
+        '!' => "bang",
+        '?' => "question",
+        '~' => "tilde",
+        '&' => "ampersand",
+        '|' => "bar",
+        '.' => "dot",
+        ':' => "colon",
+        '@' => "at",
+        '#' => "hash",
+        '$' => "dollar",
+        '\'' => "prime",
+        _ => return None,
+    })
+}
+
+/// Words which cannot be used as identifiers in a module.
+const RESERVED: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "undefined",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
@@ -0,0 +1,126 @@
+//! Source maps relate positions in generated code to positions in the source,
+//! following version 3 of the source map format.
+
+use crate::frontend::source::Span;
+
+pub struct SourceMap<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+
+    /// The generated line and column, and the source line and column, of every
+    /// mapping, ordered by their generated position.
+    mappings: Vec<(usize, usize, usize, usize)>,
+}
+
+impl<'a> SourceMap<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        Self {
+            source,
+            line_starts,
+            mappings: Vec::new(),
+        }
+    }
+
+    /// Map the given generated position to the start of a span.
+    pub fn add(&mut self, line: usize, column: usize, span: Span) {
+        let (source_line, source_column) = self.position(span.start);
+        self.mappings
+            .push((line, column, source_line, source_column));
+    }
+
+    /// Encode the map as JSON, for the generated file `file` produced from the
+    /// source file `source`.
+    pub fn encode(&self, file: &str, source: &str) -> String {
+        let map = serde_json::json!({
+            "version": 3,
+            "file": file,
+            "sources": [source],
+            "sourcesContent": [self.source],
+            "names": [],
+            "mappings": self.mappings(),
+        });
+
+        map.to_string()
+    }
+
+    /// Encode the mappings as lines of segments, where every number is
+    /// relative to the previous segment (and generated columns to the previous
+    /// segment on the same line).
+    fn mappings(&self) -> String {
+        let mut result = String::new();
+        let mut current_line = 0;
+        let mut previous_column = 0;
+        let mut previous_source_line = 0;
+        let mut previous_source_column = 0;
+        let mut first_on_line = true;
+
+        for &(line, column, source_line, source_column) in self.mappings.iter() {
+            while current_line < line {
+                result.push(';');
+                current_line += 1;
+                previous_column = 0;
+                first_on_line = true;
+            }
+
+            if !first_on_line {
+                result.push(',');
+            }
+
+            vlq(&mut result, column as i64 - previous_column as i64);
+            vlq(&mut result, 0);
+            vlq(
+                &mut result,
+                source_line as i64 - previous_source_line as i64,
+            );
+            vlq(
+                &mut result,
+                source_column as i64 - previous_source_column as i64,
+            );
+
+            previous_column = column;
+            previous_source_line = source_line;
+            previous_source_column = source_column;
+            first_on_line = false;
+        }
+
+        result
+    }
+
+    /// Get the zero-based line and UTF-16 column of a byte offset.
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let start = self.line_starts[line];
+        let column = self.source[start..offset].encode_utf16().count();
+        (line, column)
+    }
+}
+
+/// Write a base 64 variable length quantity.
+fn vlq(out: &mut String, value: i64) {
+    const DIGITS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut value = if value < 0 {
+        ((-value) << 1) | 1
+    } else {
+        value << 1
+    };
+
+    loop {
+        let mut digit = value & 0b11111;
+        value >>= 5;
+
+        if value != 0 {
+            digit |= 0b100000;
+        }
+
+        out.push(DIGITS[digit as usize] as char);
+
+        if value == 0 {
+            break;
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use std::process::Command;
+
+use super::source_map::SourceMap;
+use super::{emit, Output};
+use crate::backend::testing;
+use crate::bytecode::{compile::compile, vm};
+use crate::frontend::source::Sources;
+use crate::frontend::testing::elaborated;
+use crate::ir::lower::lower;
+
+/// Generate a module for the given (well typed) source.
+fn generate(source: &str) -> Output {
+    generate_with(source, false)
+}
+
+/// Generate a module for the given (well typed) source, with the prelude in
+/// scope if `with_prelude` is true.
+fn generate_with(source: &str, with_prelude: bool) -> Output {
+    elaborated(source, with_prelude, |alloc, names, source, program| {
+        emit(alloc, names, source, "test.nml", "test.js", &program)
+    })
+}
+
+/// Run the module for the given source with Node and return the JSON of its
+/// `main` export, where integers are strings since JSON has no `BigInt`s.
+/// Returns `None` if Node is not installed and such tests are skipped.
+fn run(source: &str) -> Option<String> {
+    let code = generate(source).code;
+    let script = format!(
+        "{code}\nconsole.log(JSON.stringify(main, (_, v) => typeof v === \"bigint\" ? String(v) : v));"
+    );
+
+    let ran = testing::output(
+        Command::new("node")
+            .args(["--input-type=module", "-e"])
+            .arg(&script),
+    )?;
+
+    assert!(
+        ran.status.success(),
+        "{}\n\n{code}",
+        String::from_utf8_lossy(&ran.stderr)
+    );
+
+    Some(String::from_utf8(ran.stdout).unwrap().trim_end().into())
+}
+
+/// Run the given source with the bytecode VM and show the value of `main`.
+fn reference(source: &str) -> String {
+    elaborated(source, false, |alloc, names, _, program| {
+        let core = lower(alloc, names, &program);
+        let program = compile(names, &core);
+        let value = vm::run(&program).unwrap().unwrap();
+        vm::show(&program, &value, false)
+    })
+}
+
+#[test]
+fn matches_become_switches() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        let head = Cons x _ => x | Nil => 0
+    ";
+
+    let expected = r#"export const Nil = { tag: "Nil" };
+export const Cons = (x0) => (x1) => ({ tag: "Cons", args: [x0, x1] });
+
+export function head(x) {
+    switch (x.tag) {
+    case "Cons": {
+        const x_1 = x.args[0];
+        return x_1;
+    }
+    case "Nil": {
+        return 0n;
+    }
+    }
+}
+
+//# sourceMappingURL=test.js.map
+"#;
+
+    assert_eq!(expected, generate(source).code);
+}
+
+#[test]
+fn records_and_nominal_records() {
+    let source = "
+        data Person = Person { name : int, age : int }
+        let age = Person { age, name } => age
+        let extend r = { c = 5, ... r }
+        let main = { age = age (Person { name = 1, age = 2 }), extended = extend { a = 1 } }
+    ";
+
+    if let Some(output) = run(source) {
+        assert_eq!(r#"{"age":"2","extended":{"a":"1","c":"5"}}"#, output);
+    }
+}
+
+#[test]
+fn mutually_recursive_functions() {
+    let source = "
+        data Nat = Z | S Nat
+        data Bool = False | True
+        let even = Z => True | S n => odd n
+        let odd = Z => False | S n => even n
+        let main = { four = even (S (S (S (S Z)))), three = even (S (S (S Z))) }
+    ";
+
+    if let Some(output) = run(source) {
+        assert_eq!(r#"{"four":{"tag":"True"},"three":{"tag":"False"}}"#, output);
+    }
+}
+
+#[test]
+fn classes_and_instances() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+
+        class Functor f = { map : ('a -> 'b) -> f 'a -> f 'b }
+
+        instance Functor List = {
+            map = f => (Nil => Nil | Cons x xs => Cons (f x) (map f xs))
+        }
+
+        let main = map (x => { x }) (Cons 1 (Cons 2 Nil))
+    ";
+
+    if let Some(output) = run(source) {
+        let expected =
+            r#"{"tag":"Cons","args":[{"x":"1"},{"tag":"Cons","args":[{"x":"2"},{"tag":"Nil"}]}]}"#;
+        assert_eq!(expected, output);
+    }
+}
+
+#[test]
+fn shared_arms_and_destructuring() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        let second = (Cons x Nil | Cons _ (Cons x _)) => x | _ => 0
+        let { p, q } = { p = second (Cons 1 (Cons 2 Nil)), q = second Nil }
+        let first xs = let Cons x _ = xs in x
+        let main = { p, q, first = first (Cons 3 Nil) }
+    ";
+
+    if let Some(output) = run(source) {
+        assert_eq!(r#"{"p":"2","q":"0","first":"3"}"#, output);
+    }
+}
+
+#[test]
+fn reserved_words_are_renamed() {
+    let source = "
+        let new x = { this = x }
+        let delete this = this.this
+        let main = delete (new 1)
+    ";
+
+    if let Some(output) = run(source) {
+        assert_eq!(r#""1""#, output);
+    }
+}
+
+#[test]
+fn lines_map_to_their_sources() {
+    let source = "let a = 1\nlet b = 2";
+    let output = generate(source);
+
+    // `export const a` maps to the first line, and `export const b` to the
+    // second, with an empty line between them.
+    assert!(
+        output.map.contains(r#""mappings":"AAAA;;AACA""#),
+        "{}",
+        output.map
+    );
+}
+
+#[test]
+fn mappings_are_relative() {
+    let source = "ab\ncd\nef";
+    let sources = Sources::new();
+    let id = sources.add(source).id;
+
+    let mut map = SourceMap::new(source);
+    map.add(0, 0, id.span(3, 4));
+    map.add(0, 4, id.span(7, 8));
+    map.add(2, 8, id.span(0, 1));
+
+    let map = map.encode("out.js", "in.nml");
+    assert!(map.contains(r#""mappings":"AACA,IACC;;QAFD""#), "{map}");
+}
+
+#[test]
+fn integer_operations() {
+    let source = "
+        let max a b = less a b b a
+        let sum = add
+        let main = { max = max 3 8, poly = sub (mul 6 7) (add 1 2), sum = sum 1 2 }
+    ";
+
+    if let Some(output) = run(source) {
+        assert_eq!(r#"{"max":"8","poly":"39","sum":"3"}"#, output);
+    }
+}
+
+#[test]
+fn operators_are_spelled_out() {
+    let source = "
+        let infixl 6 <+> a b = add a b
+        let map = 5
+        let main = 1 <+> 2 + 3 <= map
+    ";
+
+    let expected = r#"const False = { tag: "False" };
+const True = { tag: "True" };
+
+function plus(a) {
+    return (b) => BigInt.asIntN(64, a + b);
+}
+
+function less_equal(a_1) {
+    return (b_1) => (b_1 < a_1 ? False : True);
+}
+
+export function less_plus_greater(a_2) {
+    return (b_2) => BigInt.asIntN(64, a_2 + b_2);
+}
+
+export const map = 5n;
+
+export const main = less_equal(plus(less_plus_greater(1n)(2n))(3n))(map);
+
+//# sourceMappingURL=test.js.map
+"#;
+
+    assert_eq!(expected, generate_with(source, true).code);
+}
+
+#[test]
+fn integers_are_exact_and_wrap_like_bytecode() {
+    let cases = [
+        "add 9007199254740992 1",
+        "mul 9007199254740993 3",
+        "sub (add 9007199254740992 1) 9007199254740992",
+        "less 9007199254740992 9007199254740993 1 0",
+        "add 9223372036854775807 1",
+        "mul 4294967296 4294967296",
+        "sub (sub 0 9223372036854775807) 2",
+    ];
+
+    for case in cases {
+        let source = format!("let main = {case}");
+        if let Some(output) = run(&source) {
+            assert_eq!(format!("\"{}\"", reference(&source)), output, "{case}");
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Backends turn core programs into something which can be run outside of the
+//! compiler.
+
+pub mod c;
+pub mod js;
+pub mod wasm;
+
+#[cfg(test)]
+mod testing;
+
+use crate::frontend::names::{Label, Name};
+use crate::ir::{Expr, Row, Type};
+
+/// Get the fields of a closed row sorted by their labels, keeping fields with
+/// the same label in the order they occur in the row.
+pub fn closed_fields<'a>(mut row: &'a Row<'a>) -> Option<Vec<(Label<'a>, &'a Type<'a>)>> {
+    let mut fields = Vec::new();
+    loop {
+        match row {
+            Row::Empty => break,
+            Row::Param(_) => return None,
+            Row::Extend(label, ty, rest) => {
+                fields.push((*label, *ty));
+                row = rest;
+            }
+        }
+    }
+
+    fields.sort_by_key(|(label, _)| *label);
+    Some(fields)
+}
+
+/// Get the offset of a field within a record of the given type, if it is known
+/// statically.
+pub fn offset(ty: &Type, label: &Label) -> Option<usize> {
+    let Type::Record(row) = ty else {
+        return None;
+    };
+
+    closed_fields(row)?
+        .iter()
+        .position(|(other, _)| other == label)
+}
+
+/// If an expression applies a variable to exactly as many arguments as
+/// `arity` gives for it, get the variable and its arguments in the order they
+/// are applied. Type applications along the way are skipped.
+///
+/// Backends use this to construct values and apply primitives directly,
+/// instead of calling their curried functions.
+pub fn saturated<'e, 'a, 'src>(
+    expr: &'e Expr<'a, 'src>,
+    arity: impl FnOnce(&Name) -> Option<usize>,
+) -> Option<(Name, Vec<&'e Expr<'a, 'src>>)> {
+    let mut args = Vec::new();
+    let mut head = expr;
+    loop {
+        match head {
+            Expr::Apply([fun, arg]) => {
+                args.push(arg);
+                head = fun;
+            }
+
+            Expr::TypeApply(expr, _) => head = expr,
+            _ => break,
+        }
+    }
+
+    let Expr::Var(name) = head else {
+        return None;
+    };
+
+    if arity(name)? != args.len() {
+        return None;
+    }
+
+    args.reverse();
+    Some((*name, args))
+}
+
+/// Build the curried functions of a constructor taking `arity` arguments,
+/// returning the one taking the first argument.
+///
+/// Functions are built from the last to the first: `function(n, next)` builds
+/// the function taking the `n`th argument, which captures the arguments before
+/// it, and either constructs the value if `next` is `None`, or returns a
+/// closure of `next` otherwise.
+pub fn curried<T>(arity: usize, mut function: impl FnMut(usize, Option<T>) -> T) -> T {
+    let mut next = None;
+    for n in (0..arity).rev() {
+        next = Some(function(n, next));
+    }
+
+    next.expect("the constructor takes at least one argument")
+}
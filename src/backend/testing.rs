@@ -0,0 +1,28 @@
+//! Helpers for tests which run the output of a backend with an external tool,
+//! such as Node or a C compiler.
+
+use std::io::ErrorKind;
+use std::process::{Command, Output};
+
+/// Set to skip the tests needing a tool which is not installed, rather than
+/// fail them.
+const SKIP: &str = "NML_SKIP_MISSING_TOOLS";
+
+/// Run `command` and get its output. Returns `None` if its program is not
+/// installed and [`SKIP`] is set. Otherwise a missing program fails the test,
+/// so that no test passes without running what it checks.
+pub fn output(command: &mut Command) -> Option<Output> {
+    match command.output() {
+        Ok(output) => Some(output),
+
+        Err(e) if e.kind() == ErrorKind::NotFound && std::env::var_os(SKIP).is_some() => {
+            eprintln!("skipped: {:?} is not installed", command.get_program());
+            None
+        }
+
+        Err(e) => panic!(
+            "could not run {:?}: {e}\nset {SKIP} to skip the tests which need it",
+            command.get_program()
+        ),
+    }
+}
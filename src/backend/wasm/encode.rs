@@ -0,0 +1,395 @@
+//! The binary encoding of WebAssembly modules. Only the small subset of the
+//! format used by the backend is supported: every value is an `i64`, there is
+//! a single (32-bit) memory and a single table of functions.
+//!
+//! Addresses and table indices are values like any other, and are wrapped to
+//! the `i32`s WebAssembly expects by the instructions taking them from the top
+//! of the stack. Only [`Instr::Store`] takes its address from below the top, so
+//! its address must be wrapped with [`Instr::Wrap`] before the value is pushed.
+
+/// The type of values produced by a block.
+#[derive(Clone, Copy, Debug)]
+pub enum BlockType {
+    Empty,
+    Value,
+}
+
+#[derive(Clone, Debug)]
+pub enum Instr {
+    Unreachable,
+    Block(BlockType),
+    Loop,
+    If(BlockType),
+    Else,
+    End,
+    Br(u32),
+    BrIf(u32),
+    /// Branch to the target at the index on the stack.
+    BrTable(Vec<u32>, u32),
+    Call(u32),
+    /// Call the function at the table index on the stack.
+    CallIndirect(u32),
+
+    /// Choose the first of two values if the condition on top of the stack is
+    /// nonzero, and the second otherwise.
+    Select,
+
+    LocalGet(u32),
+    LocalSet(u32),
+    LocalTee(u32),
+    GlobalGet(u32),
+    GlobalSet(u32),
+
+    /// Load a word at the given offset from the address on the stack.
+    Load(u32),
+    /// Store a word at the given offset from the (wrapped) address below the
+    /// value on the stack.
+    Store(u32),
+    /// Wrap the address on the stack to an `i32`.
+    Wrap,
+    MemorySize,
+    MemoryGrow,
+
+    Const(i64),
+    Ne,
+    LtS,
+    LeS,
+    GeS,
+    GtS,
+    LeU,
+    Add,
+    Sub,
+    Mul,
+    Shl,
+    ShrU,
+}
+
+/// A function along with the index of its type.
+#[derive(Clone, Debug, Default)]
+pub struct Func {
+    pub ty: u32,
+    /// The number of locals which are not parameters.
+    pub locals: u32,
+    pub code: Vec<Instr>,
+}
+
+/// A function type taking and returning some number of `i64`s.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FuncType {
+    pub params: u32,
+    pub results: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct Module {
+    pub types: Vec<FuncType>,
+    pub funcs: Vec<Func>,
+    /// The functions which can be called indirectly, by their index in this
+    /// table.
+    pub table: Vec<u32>,
+    /// The minimum number of pages of memory.
+    pub pages: u32,
+    /// The initial values of the (mutable) globals.
+    pub globals: Vec<i64>,
+    pub exports: Vec<(String, u32)>,
+    pub start: Option<u32>,
+    /// The initial contents of memory, starting at the given address.
+    pub data: Vec<(u32, Vec<u8>)>,
+}
+
+impl Module {
+    /// Get the index of a function type, adding it if it doesn't exist yet.
+    pub fn ty(&mut self, ty: FuncType) -> u32 {
+        match self.types.iter().position(|other| *other == ty) {
+            Some(index) => index as u32,
+            None => {
+                self.types.push(ty);
+                self.types.len() as u32 - 1
+            }
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut result = b"\0asm".to_vec();
+        result.extend(1u32.to_le_bytes());
+
+        section(&mut result, 1, &self.types, |out, ty| {
+            out.push(0x60);
+            vec(out, &vec![(); ty.params as usize], |out, ()| out.push(I64));
+            vec(out, &vec![(); ty.results as usize], |out, ()| out.push(I64));
+        });
+
+        section(&mut result, 3, &self.funcs, |out, func| {
+            unsigned(out, func.ty)
+        });
+
+        section(&mut result, 4, &[self.table.len() as u32], |out, size| {
+            out.push(0x70);
+            out.push(0x00);
+            unsigned(out, *size);
+        });
+
+        section(&mut result, 5, &[self.pages], |out, pages| {
+            out.push(0x00);
+            unsigned(out, *pages);
+        });
+
+        section(&mut result, 6, &self.globals, |out, value| {
+            out.push(I64);
+            out.push(0x01);
+            Instr::Const(*value).encode(out);
+            Instr::End.encode(out);
+        });
+
+        let memory = [("memory".to_string(), None)];
+        let exports = self
+            .exports
+            .iter()
+            .map(|(name, func)| (name.clone(), Some(*func)));
+        let exports: Vec<_> = memory.into_iter().chain(exports).collect();
+
+        section(&mut result, 7, &exports, |out, (name, func)| {
+            vec(out, name.as_bytes(), |out, byte| out.push(*byte));
+            match func {
+                Some(func) => {
+                    out.push(0x00);
+                    unsigned(out, *func);
+                }
+
+                None => {
+                    out.push(0x02);
+                    unsigned(out, 0);
+                }
+            }
+        });
+
+        if let Some(start) = self.start {
+            let mut body = Vec::new();
+            unsigned(&mut body, start);
+            raw_section(&mut result, 8, &body);
+        }
+
+        section(&mut result, 9, &[&self.table], |out, table| {
+            out.push(0x00);
+            segment_offset(out, 0);
+            vec(out, table, |out, func| unsigned(out, *func));
+        });
+
+        section(&mut result, 10, &self.funcs, |out, func| {
+            let mut body = Vec::new();
+            if func.locals == 0 {
+                unsigned(&mut body, 0);
+            } else {
+                unsigned(&mut body, 1);
+                unsigned(&mut body, func.locals);
+                body.push(I64);
+            }
+
+            for instr in func.code.iter() {
+                instr.encode(&mut body);
+            }
+
+            Instr::End.encode(&mut body);
+
+            unsigned(out, body.len() as u32);
+            out.extend(body);
+        });
+
+        section(&mut result, 11, &self.data, |out, (address, bytes)| {
+            out.push(0x00);
+            segment_offset(out, *address);
+            vec(out, bytes, |out, byte| out.push(*byte));
+        });
+
+        result
+    }
+}
+
+impl Instr {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Unreachable => out.push(0x00),
+            Self::Block(ty) => {
+                out.push(0x02);
+                ty.encode(out);
+            }
+
+            Self::Loop => {
+                out.push(0x03);
+                BlockType::Empty.encode(out);
+            }
+
+            Self::If(ty) => {
+                out.push(0x04);
+                ty.encode(out);
+            }
+
+            Self::Else => out.push(0x05),
+            Self::End => out.push(0x0b),
+            Self::Br(depth) => {
+                out.push(0x0c);
+                unsigned(out, *depth);
+            }
+
+            Self::BrIf(depth) => {
+                out.push(0x0d);
+                unsigned(out, *depth);
+            }
+
+            Self::BrTable(depths, default) => {
+                Self::Wrap.encode(out);
+                out.push(0x0e);
+                vec(out, depths, |out, depth| unsigned(out, *depth));
+                unsigned(out, *default);
+            }
+
+            Self::Call(func) => {
+                out.push(0x10);
+                unsigned(out, *func);
+            }
+
+            Self::CallIndirect(ty) => {
+                Self::Wrap.encode(out);
+                out.push(0x11);
+                unsigned(out, *ty);
+                unsigned(out, 0);
+            }
+
+            Self::Select => out.push(0x1b),
+
+            Self::LocalGet(index) => {
+                out.push(0x20);
+                unsigned(out, *index);
+            }
+
+            Self::LocalSet(index) => {
+                out.push(0x21);
+                unsigned(out, *index);
+            }
+
+            Self::LocalTee(index) => {
+                out.push(0x22);
+                unsigned(out, *index);
+            }
+
+            Self::GlobalGet(index) => {
+                out.push(0x23);
+                unsigned(out, *index);
+            }
+
+            Self::GlobalSet(index) => {
+                out.push(0x24);
+                unsigned(out, *index);
+            }
+
+            Self::Load(offset) => {
+                Self::Wrap.encode(out);
+                out.push(0x29);
+                unsigned(out, 3);
+                unsigned(out, *offset);
+            }
+
+            Self::Store(offset) => {
+                out.push(0x37);
+                unsigned(out, 3);
+                unsigned(out, *offset);
+            }
+
+            Self::Wrap => out.push(0xa7),
+
+            // Memory is measured in `i32`s, which are extended to values.
+            Self::MemorySize => out.extend([0x3f, 0x00, 0xac]),
+            Self::MemoryGrow => out.extend([0xa7, 0x40, 0x00, 0xac]),
+
+            Self::Const(value) => {
+                out.push(0x42);
+                signed(out, *value);
+            }
+
+            Self::Ne => out.push(0x52),
+            Self::LtS => out.push(0x53),
+            Self::GtS => out.push(0x55),
+            Self::LeS => out.push(0x57),
+            Self::LeU => out.push(0x58),
+            Self::GeS => out.push(0x59),
+            Self::Add => out.push(0x7c),
+            Self::Sub => out.push(0x7d),
+            Self::Mul => out.push(0x7e),
+            Self::Shl => out.push(0x86),
+            Self::ShrU => out.push(0x88),
+        }
+    }
+}
+
+impl BlockType {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Empty => out.push(0x40),
+            Self::Value => out.push(I64),
+        }
+    }
+}
+
+const I64: u8 = 0x7e;
+
+/// Write the constant expression of the address a segment starts at.
+fn segment_offset(out: &mut Vec<u8>, address: u32) {
+    out.push(0x41);
+    signed(out, address as i64);
+    Instr::End.encode(out);
+}
+
+/// Write a section containing a vector of items, unless the vector is empty.
+fn section<T>(out: &mut Vec<u8>, id: u8, items: &[T], f: impl FnMut(&mut Vec<u8>, &T)) {
+    if items.is_empty() {
+        return;
+    }
+
+    let mut body = Vec::new();
+    vec(&mut body, items, f);
+    raw_section(out, id, &body);
+}
+
+fn raw_section(out: &mut Vec<u8>, id: u8, body: &[u8]) {
+    out.push(id);
+    unsigned(out, body.len() as u32);
+    out.extend(body);
+}
+
+fn vec<T>(out: &mut Vec<u8>, items: &[T], mut f: impl FnMut(&mut Vec<u8>, &T)) {
+    unsigned(out, items.len() as u32);
+    for item in items {
+        f(out, item);
+    }
+}
+
+/// Write an unsigned LEB128 integer.
+fn unsigned(out: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            out.push(byte);
+            break;
+        }
+
+        out.push(byte | 0x80);
+    }
+}
+
+/// Write a signed LEB128 integer.
+fn signed(out: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            break;
+        }
+
+        out.push(byte | 0x80);
+    }
+}
@@ -0,0 +1,598 @@
+//! The WebAssembly backend turns a core program into a standalone WebAssembly
+//! module, which needs no imports and exports its memory along with some
+//! chosen top-level bindings.
+//!
+//! Like in the C backend, types are erased and every lambda is closure
+//! converted. Every value is a single `i64`: integers (which wrap around at 64
+//! bits, like in the other backends) and unit are stored directly, while
+//! closures, constructors and records are addresses of objects in linear
+//! memory, laid out as described in the [runtime]. Top-level bindings become
+//! globals initialized by the start function of the module.
+//!
+//! An exported binding of type `t1 -> ... -> tn -> u` is exported as a function
+//! taking `n` arguments and applying the binding to them, so values are
+//! exported as functions without parameters.
+
+mod encode;
+mod runtime;
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+
+use self::encode::{BlockType, Func, FuncType, Instr, Module};
+use self::runtime::{ALLOC, EXTEND, HEAP, OFFSET, RESTRICT, SHIFT, VALUES, WORD};
+use super::{curried, offset, saturated};
+use crate::frontend::builtins::{integer, Primitive};
+use crate::frontend::names::{Label, Name, Names};
+use crate::ir::analyze::{free_vars, labels};
+use crate::ir::{Data, Expr, Item, Program, Type};
+
+/// The address of the first label array. Nothing is stored at address zero.
+const DATA_START: u32 = 8;
+
+const PAGE_SIZE: u32 = 1 << 16;
+
+/// Emit a WebAssembly module for a program, exporting the top-level bindings
+/// with the given names. If any of them do not exist, the name of the first
+/// such binding is returned instead.
+pub fn emit(names: &Names, program: &Program, exports: &[String]) -> Result<Vec<u8>, String> {
+    let mut emitter = Emitter {
+        names,
+        module: Module::default(),
+        globals: BTreeMap::new(),
+        ctors: BTreeMap::new(),
+        labels: labels(program)
+            .into_iter()
+            .enumerate()
+            .map(|(id, label)| (label, id as i64))
+            .collect(),
+        label_arrays: BTreeMap::new(),
+        data: Vec::new(),
+        closure: 0,
+    };
+
+    emitter.program(program, exports)
+}
+
+/// A constructor, along with its index in its data type and the number of
+/// arguments it takes.
+struct Ctor {
+    tag: i64,
+    arity: usize,
+}
+
+/// Where the value of a local variable is stored.
+#[derive(Clone, Copy)]
+enum Local {
+    /// A local of the function.
+    Local(u32),
+
+    /// The word at some index of the environment of the closure, whose
+    /// address is the first parameter of the function.
+    Env(u32),
+}
+
+/// The function currently being emitted.
+struct Function {
+    locals: BTreeMap<Name, Local>,
+    params: u32,
+    count: u32,
+    code: Vec<Instr>,
+}
+
+struct Emitter<'n, 'src, 'a> {
+    names: &'n Names<'src>,
+    module: Module,
+
+    /// The index of the global holding every top-level binding and
+    /// constructor.
+    globals: BTreeMap<Name, u32>,
+    ctors: BTreeMap<Name, Ctor>,
+
+    /// The runtime representation of each label, which is ordered like the
+    /// names of the labels.
+    labels: BTreeMap<Label<'a>, i64>,
+    /// The address of every array of labels.
+    label_arrays: BTreeMap<Vec<i64>, u32>,
+    /// The contents of memory starting at [`DATA_START`].
+    data: Vec<u8>,
+
+    /// The type of the code of closures, which takes the address of the
+    /// environment and the argument.
+    closure: u32,
+}
+
+impl<'a> Emitter<'_, '_, 'a> {
+    fn program(
+        &mut self,
+        program: &Program<'a, '_>,
+        exports: &[String],
+    ) -> Result<Vec<u8>, String> {
+        self.closure = self.module.ty(FuncType {
+            params: 2,
+            results: 1,
+        });
+
+        for (params, mut func) in runtime::functions() {
+            func.ty = self.module.ty(FuncType { params, results: 1 });
+            self.module.funcs.push(func);
+        }
+
+        self.module.globals.push(0);
+
+        let mut init = Function::new(0);
+        let mut bindings = BTreeMap::new();
+
+        for item in program.items {
+            match item {
+                Item::Data(data) => self.data(&mut init, data),
+                Item::Group(group) => {
+                    for binding in group.iter() {
+                        self.global(binding.name);
+                        bindings.insert(self.name(&binding.name), (binding.name, binding.ty));
+                    }
+
+                    for binding in group.iter() {
+                        self.expr(&mut init, &binding.body);
+                        init.code
+                            .push(Instr::GlobalSet(self.globals[&binding.name]));
+                    }
+                }
+            }
+        }
+
+        let ty = self.module.ty(FuncType {
+            params: 0,
+            results: 0,
+        });
+        let start = self.push(ty, init);
+        self.module.start = Some(start);
+
+        for export in exports {
+            let Some((name, ty)) = bindings.get(export) else {
+                return Err(export.clone());
+            };
+
+            let func = self.export(*name, ty);
+            self.module.exports.push((export.clone(), func));
+        }
+
+        let heap = (DATA_START + self.data.len() as u32).next_multiple_of(WORD as u32);
+        self.module.globals[HEAP as usize] = heap as i64;
+        self.module.pages = heap.div_ceil(PAGE_SIZE).max(1);
+
+        let data = std::mem::take(&mut self.data);
+        if !data.is_empty() {
+            self.module.data.push((DATA_START, data));
+        }
+
+        Ok(self.module.encode())
+    }
+
+    /// Define a global for each constructor of a data type. Constructors
+    /// without arguments are values, while the others are curried functions.
+    fn data(&mut self, init: &mut Function, data: &Data) {
+        for (tag, ctor) in data.ctors.iter().enumerate() {
+            let tag = tag as i64;
+            let arity = ctor.fields.len();
+            self.ctors.insert(ctor.name, Ctor { tag, arity });
+
+            let global = self.global(ctor.name);
+
+            if arity == 0 {
+                self.object(init, tag, &[]);
+                init.code.push(Instr::GlobalSet(global));
+                continue;
+            }
+
+            let first = curried(arity, |n, next| {
+                let n = n as u32;
+                let mut body = Function::new(2);
+                let captured: Vec<_> = (0..n).map(Local::Env).chain([Local::Local(1)]).collect();
+
+                match next {
+                    None => self.object(&mut body, tag, &captured),
+                    Some(next) => self.object(&mut body, next, &captured),
+                }
+
+                let func = self.push(self.closure, body);
+                self.table(func)
+            });
+
+            self.object(init, first, &[]);
+            init.code.push(Instr::GlobalSet(global));
+        }
+    }
+
+    /// Emit the code evaluating an expression, leaving its value on the stack.
+    fn expr(&mut self, f: &mut Function, expr: &Expr<'a, '_>) {
+        match expr {
+            Expr::Var(name) => match f.locals.get(name) {
+                Some(local) => f.get(*local),
+                None => f.code.push(Instr::GlobalGet(self.globals[name])),
+            },
+
+            Expr::Unit => f.code.push(Instr::Const(0)),
+            Expr::Number(value) => {
+                let value = integer(value).expect("literals are checked to fit when parsed");
+                f.code.push(Instr::Const(value));
+            }
+            Expr::Fail(_) => f.code.push(Instr::Unreachable),
+
+            Expr::Field(record, ty, label) => {
+                self.expr(f, record);
+
+                match offset(ty, label) {
+                    Some(offset) => f
+                        .code
+                        .push(Instr::Load(VALUES + offset as u32 * WORD as u32)),
+                    None => {
+                        let record = f.local();
+                        f.code.extend([
+                            Instr::LocalTee(record),
+                            Instr::LocalGet(record),
+                            Instr::Const(self.labels[label]),
+                            Instr::Call(OFFSET),
+                            Instr::Const(SHIFT),
+                            Instr::Shl,
+                            Instr::Add,
+                            Instr::Load(VALUES),
+                        ]);
+                    }
+                }
+            }
+
+            Expr::Record(fields, extend) => {
+                let mut values: Vec<_> = fields
+                    .iter()
+                    .map(|(label, field)| {
+                        self.expr(f, field);
+                        let local = f.local();
+                        f.code.push(Instr::LocalSet(local));
+                        (self.labels[label], Local::Local(local))
+                    })
+                    .collect();
+
+                values.sort_by_key(|(label, _)| *label);
+
+                let labels = values.iter().map(|(label, _)| *label).collect();
+                let labels = self.label_array(labels);
+                let size = values.len() as i64;
+                let values: Vec<_> = values.into_iter().map(|(_, value)| value).collect();
+
+                let record = f.local();
+                f.code.extend([
+                    Instr::Const(VALUES as i64 + size * WORD),
+                    Instr::Call(ALLOC),
+                    Instr::LocalTee(record),
+                    Instr::Wrap,
+                    Instr::Const(size),
+                    Instr::Store(0),
+                    Instr::LocalGet(record),
+                    Instr::Wrap,
+                    Instr::Const(labels as i64),
+                    Instr::Store(WORD as u32),
+                ]);
+
+                f.store(record, VALUES, &values);
+                f.code.push(Instr::LocalGet(record));
+
+                if let Some(extend) = extend {
+                    self.expr(f, extend);
+                    f.code.push(Instr::Call(EXTEND));
+                }
+            }
+
+            Expr::Restrict(record, label) => {
+                self.expr(f, record);
+                f.code.push(Instr::Const(self.labels[label]));
+                f.code.push(Instr::Call(RESTRICT));
+            }
+
+            Expr::Primitive(primitive, args) => match primitive {
+                Primitive::Add | Primitive::Sub | Primitive::Mul => {
+                    self.expr(f, &args[0]);
+                    self.expr(f, &args[1]);
+                    f.code.push(match primitive {
+                        Primitive::Add => Instr::Add,
+                        Primitive::Sub => Instr::Sub,
+                        _ => Instr::Mul,
+                    });
+                }
+
+                // The operands are compared after evaluating both choices,
+                // which `select` expects below the condition.
+                Primitive::Less => {
+                    let operands: Vec<_> = args[..2]
+                        .iter()
+                        .map(|arg| {
+                            self.expr(f, arg);
+                            let local = f.local();
+                            f.code.push(Instr::LocalSet(local));
+                            local
+                        })
+                        .collect();
+
+                    self.expr(f, &args[2]);
+                    self.expr(f, &args[3]);
+                    f.code.extend([
+                        Instr::LocalGet(operands[0]),
+                        Instr::LocalGet(operands[1]),
+                        Instr::LtS,
+                        Instr::Select,
+                    ]);
+                }
+            },
+
+            Expr::Apply([fun, arg]) => {
+                if self.construct(f, expr) {
+                    return;
+                }
+
+                self.expr(f, fun);
+                let fun = f.local();
+                f.code
+                    .extend([Instr::LocalTee(fun), Instr::Const(WORD), Instr::Add]);
+
+                self.expr(f, arg);
+                f.code.extend([
+                    Instr::LocalGet(fun),
+                    Instr::Load(0),
+                    Instr::CallIndirect(self.closure),
+                ]);
+            }
+
+            Expr::Lambda(param, _, body) => {
+                let captures: Vec<_> = free_vars(expr)
+                    .into_iter()
+                    .filter_map(|name| Some((name, *f.locals.get(&name)?)))
+                    .collect();
+
+                let mut inner = Function::new(2);
+                for (index, (name, _)) in captures.iter().enumerate() {
+                    inner.locals.insert(*name, Local::Env(index as u32));
+                }
+
+                inner.locals.insert(*param, Local::Local(1));
+                self.expr(&mut inner, body);
+
+                let func = self.push(self.closure, inner);
+                let code = self.table(func);
+                let captures: Vec<_> = captures.into_iter().map(|(_, local)| local).collect();
+                self.object(f, code, &captures);
+            }
+
+            Expr::TypeApply(expr, _) | Expr::TypeLambda(_, expr) => self.expr(f, expr),
+
+            Expr::Let(binding, body) => {
+                self.expr(f, &binding.body);
+                let local = f.local();
+                f.code.push(Instr::LocalSet(local));
+                f.locals.insert(binding.name, Local::Local(local));
+                self.expr(f, body);
+            }
+
+            Expr::Case(scrutinee, alts, default) => {
+                self.expr(f, scrutinee);
+                let scrutinee = f.local();
+                f.code.push(Instr::LocalSet(scrutinee));
+
+                // Every alternative gets a block, with the first alternative
+                // innermost. Branching to the end of the block of an
+                // alternative starts its code, which then branches past the
+                // remaining alternatives and the default.
+                let count = alts.len() as u32;
+                let mut targets = Vec::new();
+                for (index, alt) in alts.iter().enumerate() {
+                    let tag = self.ctors[&alt.ctor].tag as usize;
+                    if targets.len() <= tag {
+                        targets.resize(tag + 1, count);
+                    }
+
+                    targets[tag] = index as u32;
+                }
+
+                f.code.push(Instr::Block(BlockType::Value));
+                f.code.push(Instr::Block(BlockType::Empty));
+                for _ in alts.iter() {
+                    f.code.push(Instr::Block(BlockType::Empty));
+                }
+
+                f.code.extend([
+                    Instr::LocalGet(scrutinee),
+                    Instr::Load(0),
+                    Instr::BrTable(targets, count),
+                ]);
+
+                for (index, alt) in alts.iter().enumerate() {
+                    f.code.push(Instr::End);
+
+                    let used = free_vars(&alt.body);
+                    for (field, (name, _)) in alt.binds.iter().enumerate() {
+                        if !used.contains(name) {
+                            continue;
+                        }
+
+                        let local = f.local();
+                        f.code.extend([
+                            Instr::LocalGet(scrutinee),
+                            Instr::Load((field as u32 + 1) * WORD as u32),
+                            Instr::LocalSet(local),
+                        ]);
+                        f.locals.insert(*name, Local::Local(local));
+                    }
+
+                    self.expr(f, &alt.body);
+                    f.code.push(Instr::Br(count - index as u32));
+                }
+
+                f.code.push(Instr::End);
+                match default {
+                    Some(default) => self.expr(f, default),
+                    None => f.code.push(Instr::Unreachable),
+                }
+
+                f.code.push(Instr::End);
+            }
+        }
+    }
+
+    /// If the expression applies a constructor to all of its arguments,
+    /// construct the value directly instead of calling the curried
+    /// constructor.
+    fn construct(&mut self, f: &mut Function, expr: &Expr<'a, '_>) -> bool {
+        let Some((name, args)) = saturated(expr, |name| Some(self.ctors.get(name)?.arity)) else {
+            return false;
+        };
+
+        let tag = self.ctors[&name].tag;
+        let args: Vec<_> = args
+            .into_iter()
+            .map(|arg| {
+                self.expr(f, arg);
+                let local = f.local();
+                f.code.push(Instr::LocalSet(local));
+                Local::Local(local)
+            })
+            .collect();
+
+        self.object(f, tag, &args);
+        true
+    }
+
+    /// Emit a function which applies the given top-level binding to each of
+    /// its parameters.
+    fn export(&mut self, name: Name, mut ty: &Type) -> u32 {
+        let mut params = 0;
+        loop {
+            if let Type::Forall(_, inner) = ty {
+                ty = inner;
+                continue;
+            }
+
+            let Some((_, result)) = ty.as_arrow() else {
+                break;
+            };
+
+            params += 1;
+            ty = result;
+        }
+
+        let mut f = Function::new(params);
+        let fun = f.local();
+        f.code.push(Instr::GlobalGet(self.globals[&name]));
+        for param in 0..params {
+            f.code.extend([
+                Instr::LocalTee(fun),
+                Instr::Const(WORD),
+                Instr::Add,
+                Instr::LocalGet(param),
+                Instr::LocalGet(fun),
+                Instr::Load(0),
+                Instr::CallIndirect(self.closure),
+            ]);
+        }
+
+        let ty = self.module.ty(FuncType { params, results: 1 });
+        self.push(ty, f)
+    }
+
+    /// Allocate an object of words, where the first is `first` and the rest
+    /// are the given values, leaving its address on the stack.
+    fn object(&mut self, f: &mut Function, first: i64, values: &[Local]) {
+        let object = f.local();
+        f.code.extend([
+            Instr::Const(WORD * (values.len() as i64 + 1)),
+            Instr::Call(ALLOC),
+            Instr::LocalTee(object),
+            Instr::Wrap,
+            Instr::Const(first),
+            Instr::Store(0),
+        ]);
+
+        f.store(object, WORD as u32, values);
+        f.code.push(Instr::LocalGet(object));
+    }
+
+    fn global(&mut self, name: Name) -> u32 {
+        let global = self.module.globals.len() as u32;
+        self.module.globals.push(0);
+        self.globals.insert(name, global);
+        global
+    }
+
+    /// Add a function to the module, returning its index.
+    fn push(&mut self, ty: u32, f: Function) -> u32 {
+        self.module.funcs.push(Func {
+            ty,
+            locals: f.count - f.params,
+            code: f.code,
+        });
+
+        self.module.funcs.len() as u32 - 1
+    }
+
+    /// Add a function to the table, returning its index in the table.
+    fn table(&mut self, func: u32) -> i64 {
+        self.module.table.push(func);
+        self.module.table.len() as i64 - 1
+    }
+
+    /// Get the address of an array of labels, adding it to memory if needed.
+    fn label_array(&mut self, labels: Vec<i64>) -> u32 {
+        if let Some(address) = self.label_arrays.get(&labels) {
+            return *address;
+        }
+
+        let address = DATA_START + self.data.len() as u32;
+        for label in labels.iter() {
+            self.data.extend(label.to_le_bytes());
+        }
+
+        self.label_arrays.insert(labels, address);
+        address
+    }
+
+    fn name(&self, name: &Name) -> String {
+        self.names.get_name(name).name.name().into()
+    }
+}
+
+impl Function {
+    fn new(params: u32) -> Self {
+        Self {
+            locals: BTreeMap::new(),
+            params,
+            count: params,
+            code: Vec::new(),
+        }
+    }
+
+    /// Make a fresh local.
+    fn local(&mut self) -> u32 {
+        self.count += 1;
+        self.count - 1
+    }
+
+    fn get(&mut self, local: Local) {
+        match local {
+            Local::Local(index) => self.code.push(Instr::LocalGet(index)),
+            Local::Env(index) => self
+                .code
+                .extend([Instr::LocalGet(0), Instr::Load(index * WORD as u32)]),
+        }
+    }
+
+    /// Store the given values in consecutive words, starting at `offset` from
+    /// the address in `object`.
+    fn store(&mut self, object: u32, offset: u32, values: &[Local]) {
+        for (index, value) in values.iter().enumerate() {
+            self.code.extend([Instr::LocalGet(object), Instr::Wrap]);
+            self.get(*value);
+            self.code
+                .push(Instr::Store(offset + index as u32 * WORD as u32));
+        }
+    }
+}
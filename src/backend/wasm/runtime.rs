@@ -0,0 +1,306 @@
+//! The functions making up the runtime of the WebAssembly backend.
+//!
+//! Memory is allocated by bumping the global [`HEAP`] pointer, growing memory
+//! whenever it runs out, and is never freed. Every object is a sequence of
+//! words:
+//!
+//! - a closure is the table index of its code followed by its environment,
+//! - a constructor is its tag followed by its arguments,
+//! - a record is its size, the address of its labels, and then its values.
+//!   Fields are sorted by label, and if a label occurs several times, the
+//!   field added last comes first.
+
+use super::encode::{BlockType, Func, Instr};
+
+/// The global holding the address of the next allocation.
+pub const HEAP: u32 = 0;
+
+/// `alloc(size) -> address`, where `size` is a multiple of the word size.
+pub const ALLOC: u32 = 0;
+
+/// `offset(record, label) -> index`, the index of the first field with the
+/// given label.
+pub const OFFSET: u32 = 1;
+
+/// `extend(fields, record) -> record`, adding the fields of `fields` in front
+/// of any fields of `record` with the same label.
+pub const EXTEND: u32 = 2;
+
+/// `restrict(record, label) -> record`, removing the first field with the
+/// given label.
+pub const RESTRICT: u32 = 3;
+
+/// The size of a word, in bytes.
+pub const WORD: i64 = 8;
+
+/// The base two logarithm of [`WORD`], to turn indices into offsets.
+pub const SHIFT: i64 = 3;
+
+/// The offset of the values of a record.
+pub const VALUES: u32 = 2 * WORD as u32;
+
+/// The runtime functions, in the order of their indices, along with the number
+/// of parameters each takes.
+pub fn functions() -> Vec<(u32, Func)> {
+    vec![(1, alloc()), (2, offset()), (2, extend()), (2, restrict())]
+}
+
+fn alloc() -> Func {
+    use Instr::*;
+
+    let (size, result) = (0, 1);
+    let code = vec![
+        GlobalGet(HEAP),
+        LocalSet(result),
+        GlobalGet(HEAP),
+        LocalGet(size),
+        Add,
+        GlobalSet(HEAP),
+        // Grow memory if the allocation doesn't fit, trapping if that fails.
+        Block(BlockType::Empty),
+        GlobalGet(HEAP),
+        MemorySize,
+        Const(16),
+        Shl,
+        LeU,
+        BrIf(0),
+        GlobalGet(HEAP),
+        Const(0xffff),
+        Add,
+        Const(16),
+        ShrU,
+        MemorySize,
+        Sub,
+        MemoryGrow,
+        Const(-1),
+        Ne,
+        BrIf(0),
+        Unreachable,
+        End,
+        LocalGet(result),
+    ];
+
+    Func {
+        ty: 0,
+        locals: 1,
+        code,
+    }
+}
+
+fn offset() -> Func {
+    use Instr::*;
+
+    let (record, label, low, high, mid) = (0, 1, 2, 3, 4);
+    let mut code = vec![LocalGet(record), Load(0), LocalSet(high)];
+
+    code.extend([
+        Block(BlockType::Empty),
+        Loop,
+        LocalGet(low),
+        LocalGet(high),
+        GeS,
+        BrIf(1),
+        LocalGet(low),
+        LocalGet(high),
+        Add,
+        Const(1),
+        ShrU,
+        LocalSet(mid),
+    ]);
+
+    code.extend(label_at(record, mid));
+    code.extend([
+        LocalGet(label),
+        LtS,
+        If(BlockType::Empty),
+        LocalGet(mid),
+        Const(1),
+        Add,
+        LocalSet(low),
+        Else,
+        LocalGet(mid),
+        LocalSet(high),
+        End,
+        Br(0),
+        End,
+        End,
+        LocalGet(low),
+    ]);
+
+    Func {
+        ty: 0,
+        locals: 3,
+        code,
+    }
+}
+
+fn extend() -> Func {
+    use Instr::*;
+
+    let (fields, record, size, labels, result, i, j, k) = (0, 1, 2, 3, 4, 5, 6, 7);
+    let mut code = vec![
+        LocalGet(fields),
+        Load(0),
+        LocalGet(record),
+        Load(0),
+        Add,
+        LocalSet(size),
+    ];
+
+    code.extend(new_record(size, labels, result));
+    code.extend([
+        Block(BlockType::Empty),
+        Loop,
+        LocalGet(k),
+        LocalGet(size),
+        GeS,
+        BrIf(1),
+        // Take the next field from `fields` if `record` has run out, or if its
+        // label comes before or is the same as the next label of `record`.
+        LocalGet(j),
+        LocalGet(record),
+        Load(0),
+        GeS,
+        If(BlockType::Value),
+        Const(1),
+        Else,
+        LocalGet(i),
+        LocalGet(fields),
+        Load(0),
+        LtS,
+        If(BlockType::Value),
+        Const(1),
+        Const(0),
+    ]);
+
+    code.extend(label_at(fields, i));
+    code.extend(label_at(record, j));
+    code.extend([LeS, Select, Else, Const(0), End, End]);
+    code.extend([Const(0), Ne, If(BlockType::Empty)]);
+    code.extend(copy_field(fields, i, labels, result, k));
+    code.extend(increment(i));
+    code.push(Else);
+    code.extend(copy_field(record, j, labels, result, k));
+    code.extend(increment(j));
+    code.push(End);
+    code.extend([Br(0), End, End, LocalGet(result)]);
+
+    Func {
+        ty: 0,
+        locals: 6,
+        code,
+    }
+}
+
+fn restrict() -> Func {
+    use Instr::*;
+
+    let (record, label, size, labels, result, removed, i, k) = (0, 1, 2, 3, 4, 5, 6, 7);
+    let mut code = vec![
+        LocalGet(record),
+        Load(0),
+        Const(1),
+        Sub,
+        LocalSet(size),
+        LocalGet(record),
+        LocalGet(label),
+        Call(OFFSET),
+        LocalSet(removed),
+    ];
+
+    code.extend(new_record(size, labels, result));
+    code.extend([
+        Block(BlockType::Empty),
+        Loop,
+        LocalGet(i),
+        LocalGet(size),
+        GtS,
+        BrIf(1),
+        LocalGet(i),
+        LocalGet(removed),
+        Ne,
+        If(BlockType::Empty),
+    ]);
+
+    code.extend(copy_field(record, i, labels, result, k));
+    code.push(End);
+    code.extend(increment(i));
+    code.extend([Br(0), End, End, LocalGet(result)]);
+
+    Func {
+        ty: 0,
+        locals: 6,
+        code,
+    }
+}
+
+/// Allocate a record with `size` fields and room for as many labels.
+fn new_record(size: u32, labels: u32, result: u32) -> Vec<Instr> {
+    use Instr::*;
+
+    vec![
+        LocalGet(size),
+        Const(SHIFT),
+        Shl,
+        Call(ALLOC),
+        LocalSet(labels),
+        LocalGet(size),
+        Const(SHIFT),
+        Shl,
+        Const(VALUES as i64),
+        Add,
+        Call(ALLOC),
+        LocalSet(result),
+        LocalGet(result),
+        Wrap,
+        LocalGet(size),
+        Store(0),
+        LocalGet(result),
+        Wrap,
+        LocalGet(labels),
+        Store(WORD as u32),
+    ]
+}
+
+/// Copy the field at index `from` of the record `source` to index `k` of
+/// `result`, and move `k` forward.
+fn copy_field(source: u32, from: u32, labels: u32, result: u32, k: u32) -> Vec<Instr> {
+    use Instr::*;
+
+    let mut code = vec![LocalGet(labels), LocalGet(k), Const(SHIFT), Shl, Add, Wrap];
+    code.extend(label_at(source, from));
+    code.extend([
+        Store(0),
+        LocalGet(result),
+        LocalGet(k),
+        Const(SHIFT),
+        Shl,
+        Add,
+        Wrap,
+    ]);
+    code.extend([LocalGet(source), LocalGet(from), Const(SHIFT), Shl, Add]);
+    code.extend([Load(VALUES), Store(VALUES)]);
+    code.extend(increment(k));
+    code
+}
+
+/// Load the label at some index of a record.
+fn label_at(record: u32, index: u32) -> Vec<Instr> {
+    use Instr::*;
+
+    vec![
+        LocalGet(record),
+        Load(WORD as u32),
+        LocalGet(index),
+        Const(SHIFT),
+        Shl,
+        Add,
+        Load(0),
+    ]
+}
+
+fn increment(local: u32) -> Vec<Instr> {
+    use Instr::*;
+
+    vec![LocalGet(local), Const(1), Add, LocalSet(local)]
+}
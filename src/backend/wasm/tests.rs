@@ -0,0 +1,169 @@
+use std::process::Command;
+
+use super::emit;
+use crate::backend::testing;
+use crate::frontend::testing::elaborated;
+use crate::ir::lower::lower;
+
+/// Generate a module for the given (well typed) source.
+fn generate(source: &str, exports: &[&str]) -> Result<Vec<u8>, String> {
+    elaborated(source, false, |alloc, names, _, program| {
+        let core = lower(alloc, names, &program);
+        let exports: Vec<_> = exports.iter().map(|name| name.to_string()).collect();
+        emit(names, &core, &exports)
+    })
+}
+
+/// Instantiate the module for the given source with Node, and print the
+/// results of calling each export with the given arguments. Values are `i64`s,
+/// which are `BigInt`s in JavaScript. Returns `None` if Node is not installed
+/// and such tests are skipped.
+fn run(name: &str, source: &str, calls: &[(&str, &[i64])]) -> Option<String> {
+    let exports: Vec<_> = calls.iter().map(|(export, _)| *export).collect();
+    let module = generate(source, &exports).unwrap();
+
+    let dir = std::env::temp_dir().join(format!("nml-wasm-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join(format!("{name}.wasm"));
+    std::fs::write(&path, module).unwrap();
+
+    let calls: Vec<_> = calls
+        .iter()
+        .map(|(export, args)| {
+            let args: Vec<_> = args.iter().map(|arg| format!("{arg}n")).collect();
+            format!(
+                "console.log(String(exports.{export}({})));",
+                args.join(", ")
+            )
+        })
+        .collect();
+
+    let script = format!(
+        "const bytes = require('fs').readFileSync({path:?});
+         const exports = new WebAssembly.Instance(new WebAssembly.Module(bytes), {{}}).exports;
+         {}",
+        calls.join("\n")
+    );
+
+    let ran = testing::output(Command::new("node").arg("-e").arg(script))?;
+    assert!(
+        ran.status.success(),
+        "{}",
+        String::from_utf8_lossy(&ran.stderr)
+    );
+    Some(String::from_utf8(ran.stdout).unwrap())
+}
+
+#[test]
+fn lists_and_classes() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+
+        class Functor f = { map : ('a -> 'b) -> f 'a -> f 'b }
+
+        instance Functor List = {
+            map = f => (Nil => Nil | Cons x xs => Cons (f x) (map f xs))
+        }
+
+        let append = Nil => ys => ys | Cons x xs => ys => Cons x (append xs ys)
+        let last = Nil => 0 | Cons x Nil => x | Cons _ xs => last xs
+        let run n = last (map (x => { x, n }.n) (append (Cons 1 Nil) (Cons 2 Nil)))
+        let first = last (append (Cons 1 Nil) (Cons 2 Nil))
+    ";
+
+    if let Some(output) = run(
+        "lists_and_classes",
+        source,
+        &[("run", &[42]), ("first", &[])],
+    ) {
+        assert_eq!("42\n2\n", output);
+    }
+}
+
+#[test]
+fn open_records() {
+    let source = "
+        let get r = r.b
+        let extend r = { a = 5, ... r }
+        let got x = get { a = 1, b = x, c = 3 }
+        let extended = (extend { a = 1, b = 2 }).a
+    ";
+
+    if let Some(output) = run("open_records", source, &[("got", &[7]), ("extended", &[])]) {
+        assert_eq!("7\n5\n", output);
+    }
+}
+
+#[test]
+fn closures_capture_variables() {
+    let source = "
+        let const x = y => { x, y }
+        let pick x y = (const x y).x
+    ";
+
+    if let Some(output) = run("closures_capture_variables", source, &[("pick", &[3, 4])]) {
+        assert_eq!("3\n", output);
+    }
+}
+
+#[test]
+fn or_patterns() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        let second = (Cons x Nil | Cons _ (Cons x _)) => x | _ => 0
+        let one = second (Cons 1 Nil)
+        let two = second (Cons 1 (Cons 2 Nil))
+        let none = second Nil
+    ";
+
+    let calls: &[(&str, &[i64])] = &[("one", &[]), ("two", &[]), ("none", &[])];
+    if let Some(output) = run("or_patterns", source, calls) {
+        assert_eq!("1\n2\n0\n", output);
+    }
+}
+
+#[test]
+fn unknown_exports_are_reported() {
+    let result = generate("let x = 5", &["x", "y"]);
+    assert_eq!(Err("y".into()), result);
+}
+
+#[test]
+fn integer_operations() {
+    let source = "
+        let clamp x = less x 0 0 (less 10 x 10 x)
+        let low = clamp (sub 0 5)
+        let high = clamp 15
+        let poly x = sub (mul x x) (add x 1)
+    ";
+
+    let calls: &[(&str, &[i64])] = &[("low", &[]), ("high", &[]), ("poly", &[4])];
+    if let Some(output) = run("integer_operations", source, calls) {
+        assert_eq!("0\n10\n11\n", output);
+    }
+}
+
+#[test]
+fn integers_are_64_bits() {
+    let source = "
+        let big = mul 4294967296 3
+        let max = 9223372036854775807
+        let wrapped = add max 1
+        let record x = { a = x, b = big }.b
+    ";
+
+    let calls: &[(&str, &[i64])] = &[
+        ("big", &[]),
+        ("max", &[]),
+        ("wrapped", &[]),
+        ("record", &[i64::MIN]),
+    ];
+
+    if let Some(output) = run("integers_are_64_bits", source, calls) {
+        assert_eq!(
+            "12884901888\n9223372036854775807\n-9223372036854775808\n12884901888\n",
+            output
+        );
+    }
+}
@@ -0,0 +1,379 @@
+//! Printing the intermediate representations of a program, to see what the
+//! compiler makes of it without having to turn on logging.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use bumpalo::Bump;
+
+use self::tree::{Node, Outline};
+use super::{resolve, BatchError};
+use crate::args::Stage;
+use crate::bytecode;
+use crate::frontend::elaborate::elaborate;
+use crate::frontend::errors::Errors;
+use crate::frontend::names::{Name, Names};
+use crate::frontend::parse::parse;
+use crate::frontend::source::{Source, Sources, Span};
+use crate::frontend::trees::nodes::{ExprNode, ItemNode, PatternNode};
+use crate::frontend::trees::{resolved, typed};
+use crate::frontend::tyck::{check, Generic, Prettifier, Pretty, Scheme};
+use crate::ir;
+use crate::matching;
+use crate::matching::Constructors;
+use crate::syntax;
+
+mod tree;
+
+/// Print the given `stage` of the program, the decision tree of every pattern
+/// match and the disassembled bytecode of the program. The latter two are only
+/// printed if the program has no errors. If nothing else is printed, the
+/// `types` stage is. Unless the latter two are printed, the program is only
+/// compiled as far as the given stage.
+pub fn dump(
+    path: &Path,
+    prelude: bool,
+    stage: Option<Stage>,
+    decision_trees: bool,
+    bytecode: bool,
+) -> Result<(), BatchError> {
+    let stage = stage.or((!decision_trees && !bytecode).then_some(Stage::Types));
+    let last = stage.filter(|_| !decision_trees && !bytecode);
+
+    let file = std::fs::read_to_string(path)?;
+    let sources = Sources::new();
+    let source = sources.add(file);
+
+    match stage {
+        Some(Stage::Tokens) => tokens(&source),
+        Some(Stage::Green) => println!("{:?}", syntax::parse::parse(&source.content)),
+        _ => {}
+    }
+
+    if matches!(last, Some(Stage::Tokens | Stage::Green)) {
+        return Ok(());
+    }
+
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+
+    if let Some(Stage::Parsed) = stage {
+        let outline = Outline::parsed(&names, &source);
+
+        for item in parsed.items {
+            item.print(&outline, 0);
+        }
+    }
+
+    if last == Some(Stage::Parsed) {
+        return finish(&parsed.errors);
+    }
+
+    let resolved = resolve(&names, &alloc, &parsed, prelude);
+
+    if let Some(Stage::Resolved) = stage {
+        components(&names, &source, &resolved);
+    }
+
+    if last == Some(Stage::Resolved) {
+        return finish(&resolved.errors);
+    }
+
+    let typed = check(&alloc, &names, &resolved);
+    let mut printer = Printer::new(&names, &source);
+    let items = typed
+        .items
+        .iter()
+        .flat_map(|items| items.iter())
+        .filter(|item| item.span.source == source.id);
+
+    match stage {
+        Some(Stage::Inferred) => items.for_each(|item| printer.item_exprs(item)),
+        Some(Stage::Types) => items.for_each(|item| printer.item_schemes(item)),
+        _ => {}
+    }
+
+    if last.is_some() {
+        return finish(&typed.errors);
+    }
+
+    let result = elaborate(&alloc, &names, typed);
+
+    if decision_trees && result.errors.num_errors() == 0 {
+        let ctors = Constructors::new(&result);
+        let printer = matching::pretty::Printer::new(&names);
+
+        let matches = matching::program(&alloc, &ctors, &result);
+        for (span, tree) in matches.iter().filter(|(span, _)| span.source == source.id) {
+            println!("match at {}:", line_column(&source, span.start));
+            println!("{}\n", printer.tree(tree));
+        }
+    }
+
+    if bytecode && result.errors.num_errors() == 0 {
+        let core = ir::lower::lower(&alloc, &names, &result);
+        let program = bytecode::compile::compile(&names, &core);
+        print!("{}", bytecode::disassemble::program(&program));
+    }
+
+    finish(&result.errors)
+}
+
+/// Fail if the stages which ran reported any errors or warnings.
+fn finish(errors: &Errors) -> Result<(), BatchError> {
+    if errors.is_perfect() {
+        Ok(())
+    } else {
+        Err(BatchError::CompilerError {
+            num_errors: errors.num_errors(),
+            num_warnings: errors.num_warnings(),
+        })
+    }
+}
+
+fn tokens(source: &Source) {
+    let mut start = 0;
+    for token in syntax::parse::tokenize(&source.content) {
+        let end = start + token.width();
+        println!("{start}..{end} {:?}", token.kind());
+        start = end;
+    }
+}
+
+/// Print the items of the source in each strongly connected component of the
+/// dependency graph, in the order they are checked in.
+fn components(names: &Names, source: &Source, program: &resolved::Program) {
+    let outline = Outline::resolved(names, source, program);
+
+    for (index, items) in program.items.iter().enumerate() {
+        let items: Vec<_> = items
+            .iter()
+            .filter(|item| item.span.source == source.id)
+            .collect();
+
+        if !items.is_empty() {
+            println!("component {index}:");
+            for item in items {
+                item.print(&outline, 0);
+            }
+
+            println!();
+        }
+    }
+}
+
+/// Prints inferred types, including the levels of type variables which have
+/// not been generalized.
+struct Printer<'a, 'src> {
+    pretty: Pretty<'a, 'src>,
+
+    /// The names of the parameters of the schemes of the bindings being
+    /// printed, which the types within them may mention.
+    params: BTreeMap<Generic, String>,
+
+    names: &'a Names<'src>,
+    source: &'a Source,
+}
+
+impl<'a, 'src> Printer<'a, 'src> {
+    fn new(names: &'a Names<'src>, source: &'a Source) -> Self {
+        Self {
+            pretty: Pretty::new(names).with_show_levels(true),
+            params: BTreeMap::new(),
+            names,
+            source,
+        }
+    }
+
+    /// Print the type of every expression in an item as an indented tree.
+    fn item_exprs(&mut self, item: &typed::Item) {
+        match &item.node {
+            ItemNode::Let(typed::Pattern { scheme, .. }, expr, _)
+            | ItemNode::Instance((_, scheme), expr, _) => {
+                println!("item at {}:", self.span(item.span));
+                self.params.clear();
+                self.bind(scheme);
+                self.expr(1, expr);
+                println!();
+            }
+
+            ItemNode::Invalid(_)
+            | ItemNode::Data(..)
+            | ItemNode::Alias(..)
+            | ItemNode::Class(..) => {}
+        }
+    }
+
+    /// Print the scheme of every name bound in an item, including those bound
+    /// by `let` expressions within it.
+    fn item_schemes(&mut self, item: &typed::Item) {
+        match &item.node {
+            ItemNode::Let(pattern, expr, _) => {
+                self.pattern(pattern);
+                self.let_schemes(expr);
+            }
+
+            ItemNode::Instance(_, expr, _) => self.let_schemes(expr),
+
+            ItemNode::Invalid(_)
+            | ItemNode::Data(..)
+            | ItemNode::Alias(..)
+            | ItemNode::Class(..) => {}
+        }
+    }
+
+    fn expr(&mut self, depth: usize, expr: &typed::Expr) {
+        if let ExprNode::Let(pattern, _, _) = &expr.node {
+            self.bind(&pattern.scheme);
+        }
+
+        let ty = self.pretty.build().ty_with_subst(expr.ty, &self.params);
+        let indent = "  ".repeat(depth);
+        let span = self.span(expr.span);
+
+        let (node, children): (String, Vec<&typed::Expr>) = match &expr.node {
+            ExprNode::Invalid(_) => ("Invalid".into(), vec![]),
+            ExprNode::Var(var) => (format!("Var {}", self.name(&var.name)), vec![]),
+            ExprNode::Hole => ("Hole".into(), vec![]),
+            ExprNode::Unit => ("Unit".into(), vec![]),
+            ExprNode::Number(number) => (format!("Number {number}"), vec![]),
+            ExprNode::Anno(_, v) => match *v {},
+            ExprNode::Group(expr) => ("Group".into(), vec![expr]),
+            ExprNode::Field(expr, _, _) => ("Field".into(), vec![expr]),
+            ExprNode::Record(fields, rest) => (
+                "Record".into(),
+                fields
+                    .iter()
+                    .map(|(_, _, expr)| expr)
+                    .chain(rest.iter().copied())
+                    .collect(),
+            ),
+            ExprNode::Restrict(expr, label) => (format!("Restrict {}", label.0.name()), vec![expr]),
+            ExprNode::Apply([f, x]) => ("Apply".into(), vec![f, x]),
+            ExprNode::Lambda(arms) => ("Lambda".into(), arms.iter().map(|(_, e)| e).collect()),
+            ExprNode::Let(_, [bound, body], _) => ("Let".into(), vec![bound, body]),
+        };
+
+        println!("{indent}{node} {span} : {ty}");
+
+        for child in children {
+            self.expr(depth + 1, child);
+        }
+    }
+
+    /// Print the schemes of the names bound by `let` expressions within an
+    /// expression.
+    fn let_schemes(&mut self, expr: &typed::Expr) {
+        match &expr.node {
+            ExprNode::Invalid(_)
+            | ExprNode::Var(_)
+            | ExprNode::Hole
+            | ExprNode::Unit
+            | ExprNode::Number(_) => {}
+
+            ExprNode::Anno(_, v) => match *v {},
+
+            ExprNode::Group(expr) | ExprNode::Field(expr, _, _) | ExprNode::Restrict(expr, _) => {
+                self.let_schemes(expr)
+            }
+
+            ExprNode::Record(fields, rest) => {
+                for (_, _, expr) in fields.iter() {
+                    self.let_schemes(expr);
+                }
+
+                if let Some(rest) = rest {
+                    self.let_schemes(rest);
+                }
+            }
+
+            ExprNode::Apply([f, x]) => {
+                self.let_schemes(f);
+                self.let_schemes(x);
+            }
+
+            ExprNode::Lambda(arms) => {
+                for (_, expr) in arms.iter() {
+                    self.let_schemes(expr);
+                }
+            }
+
+            ExprNode::Let(pattern, [bound, body], _) => {
+                self.pattern(pattern);
+                self.let_schemes(bound);
+                self.let_schemes(body);
+            }
+        }
+    }
+
+    fn pattern(&mut self, pattern: &typed::Pattern) {
+        match &pattern.node {
+            PatternNode::Invalid(_)
+            | PatternNode::Wildcard
+            | PatternNode::Unit
+            | PatternNode::Constructor(_) => {}
+
+            PatternNode::Bind(name) => {
+                let name = self.name(name);
+                let span = self.span(pattern.span);
+                let scheme = self.build().scheme(&pattern.scheme);
+                println!("{name} {span} : {scheme}");
+            }
+
+            PatternNode::Group(pattern) => self.pattern(pattern),
+
+            PatternNode::Apply([a, b]) | PatternNode::Or([a, b]) | PatternNode::And([a, b]) => {
+                self.pattern(a);
+                self.pattern(b);
+            }
+
+            PatternNode::Record(fields) => {
+                for (_, _, pattern) in fields.iter() {
+                    self.pattern(pattern);
+                }
+            }
+
+            PatternNode::Anno(_, v) => match *v {},
+        }
+    }
+
+    /// Name the implicit parameters of a scheme, after those already named.
+    fn bind(&mut self, scheme: &Scheme) {
+        for param in &scheme.params {
+            if let Generic::Implicit(_) = param {
+                let name = format!("'{}", self.params.len());
+                self.params.entry(*param).or_insert(name);
+            }
+        }
+    }
+
+    fn build(&mut self) -> Prettifier<'_, 'a, 'src> {
+        self.pretty.build()
+    }
+
+    fn name(&self, name: &Name) -> &'src str {
+        self.names.get_name(name).name.name()
+    }
+
+    fn span(&self, span: Span) -> String {
+        location(self.source, span)
+    }
+}
+
+/// Show a span along with the line it starts on, counting from one.
+fn location(source: &Source, span: Span) -> String {
+    let line = source.content[..span.start].matches('\n').count() + 1;
+    format!("{}..{} (line {line})", span.start, span.end)
+}
+
+/// Show an offset as a line and column, both counting from one. Columns count
+/// characters rather than bytes.
+fn line_column(source: &Source, offset: usize) -> String {
+    let before = &source.content[..offset];
+    let line = before.matches('\n').count() + 1;
+    let start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    let column = before[start..].chars().count() + 1;
+    format!("{line}:{column}")
+}
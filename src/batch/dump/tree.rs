@@ -0,0 +1,555 @@
+//! Printing parsed and resolved trees as outlines, one node per line with its
+//! children indented below it.
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fmt::Display;
+
+use super::location;
+use crate::frontend::errors::ErrorId;
+use crate::frontend::names::{Ident, Label, Name, Names};
+use crate::frontend::source::{Source, SourceId, Span};
+use crate::frontend::trees::nodes::{
+    ConstructorNode, DataNode, ExprNode, ItemNode, PatternNode, TypeNode,
+};
+use crate::frontend::trees::parsed::{Affix, Associativity};
+use crate::frontend::trees::{parsed, resolved};
+
+pub(super) struct Outline<'a, 'src> {
+    names: &'a Names<'src>,
+    source: &'a Source,
+
+    /// Where every resolved name is defined, if the tree is resolved.
+    defs: Option<&'a BTreeMap<Name, Span>>,
+}
+
+/// A part of a tree which can be printed as an outline.
+pub(super) trait Node {
+    fn print(&self, outline: &Outline, depth: usize);
+}
+
+/// A name within a tree.
+pub(super) trait Shown {
+    fn show(&self, outline: &Outline) -> String;
+
+    /// Show the name where it is bound, which is where it is defined.
+    fn bound(&self, outline: &Outline) -> String {
+        self.show(outline)
+    }
+}
+
+impl<'a, 'src> Outline<'a, 'src> {
+    pub fn parsed(names: &'a Names<'src>, source: &'a Source) -> Self {
+        Self {
+            names,
+            source,
+            defs: None,
+        }
+    }
+
+    pub fn resolved(
+        names: &'a Names<'src>,
+        source: &'a Source,
+        program: &'a resolved::Program,
+    ) -> Self {
+        Self {
+            names,
+            source,
+            defs: Some(&program.defs),
+        }
+    }
+
+    /// Print a line for a node with a span.
+    fn line(&self, depth: usize, text: impl Display, span: Span) {
+        println!(
+            "{}{text} {}",
+            "  ".repeat(depth),
+            location(self.source, span)
+        );
+    }
+
+    /// Print a line for a part of a node which has no span of its own.
+    fn marker(&self, depth: usize, text: impl Display) {
+        println!("{}{text}", "  ".repeat(depth));
+    }
+
+    fn item<E, P, TP, D, A, C, I, G>(
+        &self,
+        depth: usize,
+        node: &ItemNode<E, P, TP, D, A, C, I, G>,
+        span: Span,
+    ) where
+        E: Node,
+        P: Node,
+        TP: Node,
+        D: Node,
+        A: Node,
+        C: Node,
+        I: Node,
+    {
+        match node {
+            ItemNode::Invalid(_) => self.line(depth, "Invalid", span),
+            ItemNode::Let(pattern, expr, _) => {
+                self.line(depth, "Let", span);
+                pattern.print(self, depth + 1);
+                expr.print(self, depth + 1);
+            }
+
+            ItemNode::Data(pattern, body) => {
+                self.line(depth, "Data", span);
+                pattern.print(self, depth + 1);
+                body.print(self, depth + 1);
+            }
+
+            ItemNode::Alias(pattern, body) => {
+                self.line(depth, "Alias", span);
+                pattern.print(self, depth + 1);
+                body.print(self, depth + 1);
+            }
+
+            ItemNode::Class(pattern, body) => {
+                self.line(depth, "Class", span);
+                pattern.print(self, depth + 1);
+                body.print(self, depth + 1);
+            }
+
+            ItemNode::Instance(head, expr, _) => {
+                self.line(depth, "Instance", span);
+                head.print(self, depth + 1);
+                expr.print(self, depth + 1);
+            }
+        }
+    }
+
+    fn expr<E, P, T, N, A, G>(
+        &self,
+        depth: usize,
+        node: &ExprNode<'_, '_, E, P, T, N, A, G>,
+        span: Span,
+    ) where
+        E: Node,
+        P: Node,
+        T: Node,
+        N: Shown,
+        A: AsRef<[E]>,
+    {
+        match node {
+            ExprNode::Invalid(_) => self.line(depth, "Invalid", span),
+            ExprNode::Var(name) => self.line(depth, format!("Var {}", name.show(self)), span),
+            ExprNode::Hole => self.line(depth, "Hole", span),
+            ExprNode::Unit => self.line(depth, "Unit", span),
+            ExprNode::Number(number) => self.line(depth, format!("Number {number}"), span),
+            ExprNode::Anno(expr, ty) => {
+                self.line(depth, "Anno", span);
+                expr.print(self, depth + 1);
+                ty.print(self, depth + 1);
+            }
+
+            ExprNode::Group(expr) => {
+                self.line(depth, "Group", span);
+                expr.print(self, depth + 1);
+            }
+
+            ExprNode::Field(expr, label, _) => {
+                self.line(depth, format!("Field {}", show_label(label)), span);
+                expr.print(self, depth + 1);
+            }
+
+            ExprNode::Record(fields, rest) => {
+                self.line(depth, "Record", span);
+                self.fields(depth + 1, fields);
+
+                if let Some(rest) = rest {
+                    self.marker(depth + 1, "...");
+                    rest.print(self, depth + 2);
+                }
+            }
+
+            ExprNode::Restrict(expr, label) => {
+                self.line(depth, format!("Restrict {}", label.0.name()), span);
+                expr.print(self, depth + 1);
+            }
+
+            ExprNode::Apply(exprs) => {
+                self.line(depth, "Apply", span);
+                for expr in exprs.as_ref() {
+                    expr.print(self, depth + 1);
+                }
+            }
+
+            ExprNode::Lambda(arms) => {
+                self.line(depth, "Lambda", span);
+                for (pattern, expr) in arms.iter() {
+                    self.marker(depth + 1, "=>");
+                    pattern.print(self, depth + 2);
+                    expr.print(self, depth + 2);
+                }
+            }
+
+            ExprNode::Let(pattern, [bound, body], _) => {
+                self.line(depth, "Let", span);
+                pattern.print(self, depth + 1);
+                bound.print(self, depth + 1);
+                body.print(self, depth + 1);
+            }
+        }
+    }
+
+    fn pattern<P, T, N, C, A>(
+        &self,
+        depth: usize,
+        node: &PatternNode<'_, '_, P, T, N, C, A>,
+        span: Span,
+    ) where
+        P: Node,
+        T: Node,
+        N: Shown,
+        C: Shown,
+        A: AsRef<[P]>,
+    {
+        match node {
+            PatternNode::Invalid(_) => self.line(depth, "Invalid", span),
+            PatternNode::Wildcard => self.line(depth, "Wildcard", span),
+            PatternNode::Unit => self.line(depth, "Unit", span),
+            PatternNode::Bind(name) => self.line(depth, format!("Bind {}", name.bound(self)), span),
+            PatternNode::Constructor(name) => {
+                self.line(depth, format!("Constructor {}", name.show(self)), span)
+            }
+
+            PatternNode::Anno(pattern, ty) => {
+                self.line(depth, "Anno", span);
+                pattern.print(self, depth + 1);
+                ty.print(self, depth + 1);
+            }
+
+            PatternNode::Group(pattern) => {
+                self.line(depth, "Group", span);
+                pattern.print(self, depth + 1);
+            }
+
+            PatternNode::Apply(patterns) => {
+                self.line(depth, "Apply", span);
+                for pattern in patterns.as_ref() {
+                    pattern.print(self, depth + 1);
+                }
+            }
+
+            PatternNode::Record(fields) => {
+                self.line(depth, "Record", span);
+                self.fields(depth + 1, fields);
+            }
+
+            PatternNode::Or([a, b]) => {
+                self.line(depth, "Or", span);
+                a.print(self, depth + 1);
+                b.print(self, depth + 1);
+            }
+
+            PatternNode::And([a, b]) => {
+                self.line(depth, "And", span);
+                a.print(self, depth + 1);
+                b.print(self, depth + 1);
+            }
+        }
+    }
+
+    fn ty<T, N, U, A>(&self, depth: usize, node: &TypeNode<'_, '_, T, N, U, A>, span: Span)
+    where
+        T: Node,
+        N: Shown,
+        U: Shown,
+        A: AsRef<[T]>,
+    {
+        match node {
+            TypeNode::Invalid(_) => self.line(depth, "Invalid", span),
+            TypeNode::Wildcard => self.line(depth, "Wildcard", span),
+            TypeNode::Named(name) => self.line(depth, format!("Named {}", name.show(self)), span),
+            TypeNode::Universal(name) => {
+                self.line(depth, format!("Universal {}", name.show(self)), span)
+            }
+
+            TypeNode::Function([t, u]) => {
+                self.line(depth, "Function", span);
+                t.print(self, depth + 1);
+                u.print(self, depth + 1);
+            }
+
+            TypeNode::Record(fields) => {
+                self.line(depth, "Record", span);
+                self.fields(depth + 1, fields);
+            }
+
+            TypeNode::Group(ty) => {
+                self.line(depth, "Group", span);
+                ty.print(self, depth + 1);
+            }
+
+            TypeNode::Apply(types) => {
+                self.line(depth, "Apply", span);
+                for ty in types.as_ref() {
+                    ty.print(self, depth + 1);
+                }
+            }
+        }
+    }
+
+    fn data<C: Node>(&self, depth: usize, node: &DataNode<C>, span: Span) {
+        match node {
+            DataNode::Invalid(_) => self.line(depth, "Invalid", span),
+            DataNode::Sum(ctors) => {
+                self.line(depth, "Sum", span);
+                for ctor in ctors.iter() {
+                    ctor.print(self, depth + 1);
+                }
+            }
+        }
+    }
+
+    fn constructor<N: Shown, T: Node>(
+        &self,
+        depth: usize,
+        node: &ConstructorNode<N, T>,
+        span: Span,
+    ) {
+        match node {
+            ConstructorNode::Invalid(_) => self.line(depth, "Invalid", span),
+            ConstructorNode::Constructor(name, args) => {
+                self.line(depth, format!("Constructor {}", name.bound(self)), span);
+                for arg in args.iter() {
+                    arg.print(self, depth + 1);
+                }
+            }
+        }
+    }
+
+    fn fields<T: Node>(&self, depth: usize, fields: &[(Result<Label, ErrorId>, Span, T)]) {
+        for (label, span, node) in fields {
+            self.line(depth, format!("{} =", show_label(label)), *span);
+            node.print(self, depth + 1);
+        }
+    }
+
+    /// Show a resolved name along with where it is defined, to tell apart
+    /// names which are spelled the same.
+    fn resolved_name(&self, name: &Name) -> String {
+        let text = self.names.get_name(name).name.name();
+        let Some(span) = self.defs.and_then(|defs| defs.get(name)) else {
+            return text.into();
+        };
+
+        if span.source == self.source.id {
+            let line = self.source.content[..span.start].matches('\n').count() + 1;
+            format!("{text} (defined on line {line})")
+        } else if span.source == SourceId::PRELUDE {
+            format!("{text} (from the prelude)")
+        } else {
+            text.into()
+        }
+    }
+}
+
+fn show_label<'src>(label: &Result<Label<'src>, ErrorId>) -> &'src str {
+    match label {
+        Ok(label) => label.0.name(),
+        Err(_) => "<error>",
+    }
+}
+
+fn show_kind(kind: &resolved::Kind) -> String {
+    match &kind.node {
+        resolved::KindNode::Invalid(_) => "<error>".into(),
+        resolved::KindNode::Type => "Type".into(),
+        resolved::KindNode::Row => "Row".into(),
+        resolved::KindNode::Arrow([k, l]) => format!("({} -> {})", show_kind(k), show_kind(l)),
+    }
+}
+
+/* Names -------------------------------------------------------------------- */
+
+impl Shown for Ident<'_> {
+    fn show(&self, _: &Outline) -> String {
+        self.name().into()
+    }
+}
+
+impl Shown for (Affix, Ident<'_>) {
+    fn show(&self, _: &Outline) -> String {
+        let (affix, name) = self;
+        let name = name.name();
+
+        match affix {
+            Affix::Prefix => name.into(),
+            Affix::Postfix => format!("postfix {name}"),
+            Affix::Infix(fixity) => {
+                let assoc = match fixity.assoc {
+                    Associativity::Left => "infixl",
+                    Associativity::Right => "infixr",
+                    Associativity::None => "infix",
+                };
+
+                format!("{assoc} {} {name}", fixity.precedence)
+            }
+        }
+    }
+}
+
+impl Shown for Name {
+    fn show(&self, outline: &Outline) -> String {
+        outline.resolved_name(self)
+    }
+
+    fn bound(&self, outline: &Outline) -> String {
+        outline.names.get_name(self).name.name().into()
+    }
+}
+
+impl Shown for Result<Name, ErrorId> {
+    fn show(&self, outline: &Outline) -> String {
+        match self {
+            Ok(name) => name.show(outline),
+            Err(_) => "<error>".into(),
+        }
+    }
+
+    fn bound(&self, outline: &Outline) -> String {
+        match self {
+            Ok(name) => name.bound(outline),
+            Err(_) => "<error>".into(),
+        }
+    }
+}
+
+impl Shown for Infallible {
+    fn show(&self, _: &Outline) -> String {
+        match *self {}
+    }
+}
+
+/* Parsed trees ------------------------------------------------------------- */
+
+impl<T: Node + ?Sized> Node for &T {
+    fn print(&self, outline: &Outline, depth: usize) {
+        (**self).print(outline, depth)
+    }
+}
+
+impl Node for parsed::Item<'_, '_> {
+    fn print(&self, outline: &Outline, depth: usize) {
+        outline.item(depth, &self.node, self.span)
+    }
+}
+
+impl Node for parsed::Expr<'_, '_> {
+    fn print(&self, outline: &Outline, depth: usize) {
+        outline.expr(depth, &self.node, self.span)
+    }
+}
+
+impl Node for parsed::Pattern<'_, '_> {
+    fn print(&self, outline: &Outline, depth: usize) {
+        outline.pattern(depth, &self.node, self.span)
+    }
+}
+
+impl Node for parsed::Type<'_, '_> {
+    fn print(&self, outline: &Outline, depth: usize) {
+        outline.ty(depth, &self.node, self.span)
+    }
+}
+
+impl Node for parsed::Data<'_, '_> {
+    fn print(&self, outline: &Outline, depth: usize) {
+        outline.data(depth, &self.node, self.span)
+    }
+}
+
+impl Node for parsed::Constructor<'_, '_> {
+    fn print(&self, outline: &Outline, depth: usize) {
+        outline.constructor(depth, &self.node, self.span)
+    }
+}
+
+impl Node for [(Result<Label<'_>, ErrorId>, Span, parsed::Type<'_, '_>)] {
+    fn print(&self, outline: &Outline, depth: usize) {
+        for (label, span, ty) in self {
+            outline.line(depth, format!("Method {}", show_label(label)), *span);
+            ty.print(outline, depth + 1);
+        }
+    }
+}
+
+/* Resolved trees ----------------------------------------------------------- */
+
+impl Node for resolved::Item<'_, '_> {
+    fn print(&self, outline: &Outline, depth: usize) {
+        outline.item(depth, &self.node, self.span)
+    }
+}
+
+impl Node for resolved::Expr<'_, '_> {
+    fn print(&self, outline: &Outline, depth: usize) {
+        outline.expr(depth, &self.node, self.span)
+    }
+}
+
+impl Node for resolved::Pattern<'_, '_> {
+    fn print(&self, outline: &Outline, depth: usize) {
+        outline.pattern(depth, &self.node, self.span)
+    }
+}
+
+impl Node for resolved::Type<'_, '_> {
+    fn print(&self, outline: &Outline, depth: usize) {
+        outline.ty(depth, &self.node, self.span)
+    }
+}
+
+impl Node for resolved::Data<'_, '_> {
+    fn print(&self, outline: &Outline, depth: usize) {
+        outline.data(depth, &self.node, self.span)
+    }
+}
+
+impl Node for resolved::Constructor<'_, '_> {
+    fn print(&self, outline: &Outline, depth: usize) {
+        outline.constructor(depth, &self.node, self.span)
+    }
+}
+
+impl Node for resolved::DataPattern<'_> {
+    fn print(&self, outline: &Outline, depth: usize) {
+        let mut text = format!("Type {}", self.name.bound(outline));
+
+        for (arg, kind) in self.args.iter().zip(self.kinds.iter()) {
+            let arg = arg.bound(outline);
+            match kind {
+                Some(kind) => text.push_str(&format!(" ({arg} : {})", show_kind(kind))),
+                None => text.push_str(&format!(" {arg}")),
+            }
+        }
+
+        if let Some(kind) = &self.anno {
+            text.push_str(&format!(" : {}", show_kind(kind)));
+        }
+
+        outline.marker(depth, text);
+    }
+}
+
+impl Node for [resolved::Method<'_, '_>] {
+    fn print(&self, outline: &Outline, depth: usize) {
+        for method in self {
+            let name = method.name.bound(outline);
+            outline.line(depth, format!("Method {name}"), method.span);
+            method.ty.print(outline, depth + 1);
+        }
+    }
+}
+
+impl Node for (Name, resolved::Type<'_, '_>) {
+    fn print(&self, outline: &Outline, depth: usize) {
+        let (class, ty) = self;
+        outline.marker(depth, format!("Class {}", class.show(outline)));
+        ty.print(outline, depth);
+    }
+}
@@ -1,17 +1,33 @@
 //! At the command line, the compiler is mostly used as a "batch" compiler - run
 //! occasionally, parsing, checking, and building in one go.
 
-use std::path::Path;
+pub use self::dump::dump;
+
+mod dump;
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
 
 use bumpalo::Bump;
 
+use crate::args::{OptLevel, Target};
+use crate::backend;
+use crate::bytecode;
+use crate::bytecode::file::DecodeError;
+use crate::doc;
+use crate::frontend::elaborate::elaborate;
 use crate::frontend::names::Names;
 use crate::frontend::parse::parse;
-use crate::frontend::resolve::resolve;
+use crate::frontend::prelude;
+use crate::frontend::resolve::{self, resolve_with_prelude};
 use crate::frontend::source::Sources;
-use crate::frontend::tyck::infer;
+use crate::frontend::trees::{parsed, resolved, typed};
+use crate::frontend::tyck::{self, check};
+use crate::ir;
+use crate::ir::pretty::Printer;
+use crate::syntax::format;
 
-pub fn run(path: &Path) -> Result<(), BatchError> {
+pub fn run(path: &Path, prelude: bool, verify_core: bool) -> Result<(), BatchError> {
     let file = std::fs::read_to_string(path)?;
     let sources = Sources::new();
     let source = sources.add(file);
@@ -20,8 +36,188 @@ pub fn run(path: &Path) -> Result<(), BatchError> {
     let names = Names::new();
 
     let parsed = parse(&alloc, &names, &source);
-    let resolved = resolve(&names, &alloc, &parsed);
-    let result = infer(&alloc, &names, &resolved);
+    let resolved = resolve(&names, &alloc, &parsed, prelude);
+    let typed = check(&alloc, &names, &resolved);
+    let result = elaborate(&alloc, &names, typed);
+
+    if verify_core && result.errors.num_errors() == 0 {
+        let core = ir::lower::lower(&alloc, &names, &result);
+        log::trace!("core program:\n{}", Printer::new(&names).program(&core));
+
+        let errors = ir::check::check(&alloc, &names, &core);
+        if !errors.is_empty() {
+            return Err(BatchError::IllTypedCore(errors));
+        }
+    }
+
+    let result = result.errors;
+
+    if result.is_perfect() {
+        Ok(())
+    } else {
+        Err(BatchError::CompilerError {
+            num_errors: result.num_errors(),
+            num_warnings: result.num_warnings(),
+        })
+    }
+}
+
+/// Compile the program to code for the given target, writing it to `output`
+/// if the program has no errors. The bindings in `exports` are exported from
+/// WebAssembly modules.
+pub fn build(
+    path: &Path,
+    prelude: bool,
+    target: Target,
+    level: OptLevel,
+    output: &Path,
+    exports: &[String],
+) -> Result<(), BatchError> {
+    let file = std::fs::read_to_string(path)?;
+    let sources = Sources::new();
+    let source = sources.add(file);
+
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let resolved = resolve(&names, &alloc, &parsed, prelude);
+    let typed = check(&alloc, &names, &resolved);
+    let result = elaborate(&alloc, &names, typed);
+
+    if result.errors.num_errors() == 0 {
+        let code = match target {
+            Target::Bytecode => {
+                let core = lower(&alloc, &names, &result, level);
+                bytecode::file::encode(&bytecode::compile::compile(&names, &core))
+            }
+
+            Target::C => {
+                let core = alloc.alloc(lower(&alloc, &names, &result, level));
+                backend::c::emit(&alloc, &names, core).into_bytes()
+            }
+
+            Target::Js => {
+                let module = backend::js::emit(
+                    &alloc,
+                    &names,
+                    &source,
+                    &file_name(path),
+                    &file_name(output),
+                    &result,
+                );
+
+                let mut map = output.as_os_str().to_owned();
+                map.push(".map");
+                std::fs::write(map, module.map)?;
+                module.code.into_bytes()
+            }
+
+            Target::Wasm => {
+                let core = lower(&alloc, &names, &result, level);
+                backend::wasm::emit(&names, &core, exports).map_err(BatchError::UnknownExport)?
+            }
+        };
+
+        std::fs::write(output, code)?;
+    }
+
+    let result = result.errors;
+
+    if result.is_perfect() {
+        Ok(())
+    } else {
+        Err(BatchError::CompilerError {
+            num_errors: result.num_errors(),
+            num_warnings: result.num_warnings(),
+        })
+    }
+}
+
+/// Resolve a parsed program, with the prelude in scope if `prelude` is true.
+fn resolve<'a, 'src>(
+    names: &'a Names<'src>,
+    alloc: &'a Bump,
+    program: &parsed::Source<'_, 'src>,
+    prelude: bool,
+) -> resolved::Program<'a, 'src> {
+    if prelude {
+        resolve_with_prelude(names, alloc, prelude::resolved(), program)
+    } else {
+        resolve::resolve(names, alloc, program)
+    }
+}
+
+/// Lower a program to core and optimize it at the given level.
+fn lower<'a, 'src>(
+    alloc: &'a Bump,
+    names: &'a Names<'src>,
+    program: &typed::Program<'a, 'src>,
+    level: OptLevel,
+) -> ir::Program<'a, 'src> {
+    let core = ir::lower::lower(alloc, names, program);
+    match level.to_level() {
+        Some(level) => ir::optimize::optimize(alloc, names, &core, level),
+        None => core,
+    }
+}
+
+/// Get the name of the file at a path, without the directories leading to it.
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Run a program with the bytecode interpreter, printing the value of its
+/// `main` binding if it has one. Files with the `nmlb` extension are loaded as
+/// bytecode, while anything else is compiled from source first. If
+/// `differential` is true, a source program is also run without optimizations
+/// and both runs must have the same result.
+pub fn execute(
+    path: &Path,
+    prelude: bool,
+    level: OptLevel,
+    differential: bool,
+) -> Result<(), BatchError> {
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "nmlb")
+    {
+        let program = bytecode::file::decode(&std::fs::read(path)?)?;
+        return interpret(&program);
+    }
+
+    let file = std::fs::read_to_string(path)?;
+    let sources = Sources::new();
+    let source = sources.add(file);
+
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let resolved = resolve(&names, &alloc, &parsed, prelude);
+    let typed = check(&alloc, &names, &resolved);
+    let result = elaborate(&alloc, &names, typed);
+
+    if result.errors.num_errors() == 0 {
+        let core = lower(&alloc, &names, &result, level);
+        let program = bytecode::compile::compile(&names, &core);
+
+        if differential {
+            let core = lower(&alloc, &names, &result, OptLevel::O0);
+            let unoptimized = bytecode::compile::compile(&names, &core);
+
+            let expected = outcome(&unoptimized);
+            let actual = outcome(&program);
+            if expected != actual {
+                return Err(BatchError::OptimizationMismatch { expected, actual });
+            }
+        }
+
+        interpret(&program)?;
+    }
+
     let result = result.errors;
 
     if result.is_perfect() {
@@ -34,12 +230,176 @@ pub fn run(path: &Path) -> Result<(), BatchError> {
     }
 }
 
+/// Run a program and describe its result.
+fn outcome(program: &bytecode::Program) -> String {
+    match bytecode::vm::run(program) {
+        Ok(Some(value)) => bytecode::vm::show(program, &value, false),
+        Ok(None) => "no value".into(),
+        Err(bytecode::vm::Error::PatternMatchFailure) => "a pattern match failure".into(),
+        Err(bytecode::vm::Error::Malformed) => "malformed bytecode".into(),
+    }
+}
+
+fn interpret(program: &bytecode::Program) -> Result<(), BatchError> {
+    if let Some(value) = bytecode::vm::run(program)? {
+        println!("{}", bytecode::vm::show(program, &value, false));
+    }
+
+    Ok(())
+}
+
+/// Write the documentation of a program to the `output` directory as HTML, or
+/// as Markdown if `markdown` is true, if the program has no errors.
+pub fn doc(path: &Path, prelude: bool, markdown: bool, output: &Path) -> Result<(), BatchError> {
+    let file = std::fs::read_to_string(path)?;
+    let sources = Sources::new();
+    let source = sources.add(file);
+
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let resolved = resolve(&names, &alloc, &parsed, prelude);
+    let inferred = tyck::infer(&alloc, &names, &resolved);
+
+    if inferred.errors.num_errors() == 0 {
+        let title = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let entries = doc::entries(&names, &source, &inferred);
+        let files = if markdown {
+            doc::markdown(&title, &entries)
+        } else {
+            doc::html(&title, &entries)
+        };
+
+        std::fs::create_dir_all(output)?;
+        for file in files {
+            std::fs::write(output.join(file.name), file.content)?;
+        }
+    }
+
+    let result = inferred.errors;
+
+    if result.is_perfect() {
+        Ok(())
+    } else {
+        Err(BatchError::CompilerError {
+            num_errors: result.num_errors(),
+            num_warnings: result.num_warnings(),
+        })
+    }
+}
+
+/// Format the source files at the given paths, including every `nml` file
+/// within directories. If there are no paths, standard input is formatted to
+/// standard output instead. If `check` is true, the files are not changed,
+/// and any files which are not formatted are reported.
+pub fn format(paths: &[PathBuf], check: bool) -> Result<(), BatchError> {
+    let config = format::Config::default();
+
+    if paths.is_empty() {
+        let mut source = String::new();
+        std::io::stdin().read_to_string(&mut source)?;
+
+        let stdin = PathBuf::from("<stdin>");
+        let formatted = format::format(&source, &config)
+            .map_err(|error| BatchError::CannotFormat(stdin.clone(), error))?;
+
+        return if !check {
+            print!("{formatted}");
+            Ok(())
+        } else if formatted == source {
+            Ok(())
+        } else {
+            Err(BatchError::Unformatted(vec![stdin]))
+        };
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        source_files(path, &mut files)?;
+    }
+
+    let mut unformatted = Vec::new();
+    for path in files {
+        let source = std::fs::read_to_string(&path)?;
+        let formatted = match format::format(&source, &config) {
+            Ok(formatted) => formatted,
+            Err(error) => return Err(BatchError::CannotFormat(path, error)),
+        };
+
+        if formatted == source {
+            continue;
+        }
+
+        if check {
+            unformatted.push(path);
+        } else {
+            std::fs::write(&path, formatted)?;
+        }
+    }
+
+    if unformatted.is_empty() {
+        Ok(())
+    } else {
+        Err(BatchError::Unformatted(unformatted))
+    }
+}
+
+/// Collect the path itself if it is a file, or every `nml` file within it if
+/// it is a directory.
+fn source_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), BatchError> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let mut entries = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() || entry.extension().is_some_and(|ext| ext == "nml") {
+            source_files(&entry, files)?;
+        }
+    }
+
+    Ok(())
+}
+
 pub enum BatchError {
     IoError(std::io::Error),
     CompilerError {
         num_errors: usize,
         num_warnings: usize,
     },
+
+    /// The core program the source lowered to is ill-typed, which is a bug in
+    /// the compiler.
+    IllTypedCore(Vec<String>),
+
+    /// A binding to export does not exist.
+    UnknownExport(String),
+
+    InvalidBytecode(DecodeError),
+    RuntimeError(bytecode::vm::Error),
+
+    /// These files are not formatted.
+    Unformatted(Vec<PathBuf>),
+
+    /// The file at this path could not be formatted.
+    CannotFormat(PathBuf, format::Error),
+
+    /// The optimized program has a different result than the unoptimized
+    /// one, which is a bug in the optimizer.
+    OptimizationMismatch {
+        expected: String,
+        actual: String,
+    },
 }
 
 impl From<std::io::Error> for BatchError {
@@ -47,3 +407,15 @@ impl From<std::io::Error> for BatchError {
         Self::IoError(value)
     }
 }
+
+impl From<DecodeError> for BatchError {
+    fn from(value: DecodeError) -> Self {
+        Self::InvalidBytecode(value)
+    }
+}
+
+impl From<bytecode::vm::Error> for BatchError {
+    fn from(value: bytecode::vm::Error) -> Self {
+        Self::RuntimeError(value)
+    }
+}
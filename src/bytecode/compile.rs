@@ -0,0 +1,428 @@
+//! Compiling core programs to bytecode. Like in the backends, types are erased
+//! and every lambda is closure converted into a function taking the values of
+//! its free variables through its closure. Applications in tail position
+//! become tail calls.
+
+use std::collections::BTreeMap;
+
+use super::{Ctor, Function, Instr, Program};
+use crate::backend::{curried, offset, saturated};
+use crate::frontend::builtins::{integer, Primitive};
+use crate::frontend::names::{Label, Name, Names};
+use crate::ir;
+use crate::ir::analyze::{free_vars, labels};
+use crate::ir::{Data, Expr, Item};
+
+/// Compile a core program to bytecode.
+pub fn compile(names: &Names, program: &ir::Program) -> Program {
+    let labels = labels(program);
+
+    let mut compiler = Compiler {
+        names,
+        program: Program {
+            functions: Vec::new(),
+            globals: Vec::new(),
+            labels: labels.iter().map(|label| label.0.name().into()).collect(),
+            shapes: Vec::new(),
+            ctors: Vec::new(),
+            init: 0,
+            main: None,
+        },
+        globals: BTreeMap::new(),
+        ctors: BTreeMap::new(),
+        labels: labels
+            .into_iter()
+            .enumerate()
+            .map(|(id, label)| (label, id as u32))
+            .collect(),
+        shapes: BTreeMap::new(),
+    };
+
+    compiler.program(program);
+    compiler.program
+}
+
+/// Where the value of a local variable is stored.
+#[derive(Clone, Copy)]
+enum Local {
+    Local(u32),
+    Capture(u32),
+}
+
+/// The function currently being compiled.
+struct Builder {
+    locals: BTreeMap<Name, Local>,
+    count: u32,
+    code: Vec<Instr>,
+}
+
+struct Compiler<'n, 'src, 'a> {
+    names: &'n Names<'src>,
+    program: Program,
+
+    /// The global holding every top-level binding and constructor.
+    globals: BTreeMap<Name, u32>,
+
+    /// The index of every constructor in the constructor table.
+    ctors: BTreeMap<Name, u32>,
+
+    /// The runtime representation of each label, which is ordered like the
+    /// names of the labels.
+    labels: BTreeMap<Label<'a>, u32>,
+    shapes: BTreeMap<Vec<u32>, u32>,
+}
+
+impl<'a> Compiler<'_, '_, 'a> {
+    fn program(&mut self, program: &ir::Program<'a, '_>) {
+        let mut init = Builder::new();
+
+        for item in program.items {
+            match item {
+                Item::Data(data) => self.data(&mut init, data),
+                Item::Group(group) => {
+                    for binding in group.iter() {
+                        let global = self.global(binding.name);
+                        if self.name(&binding.name) == "main" {
+                            self.program.main = Some(global);
+                        }
+                    }
+
+                    for binding in group.iter() {
+                        let name = self.name(&binding.name);
+                        self.named(&mut init, &binding.body, name);
+                        init.code
+                            .push(Instr::SetGlobal(self.globals[&binding.name]));
+                    }
+                }
+            }
+        }
+
+        init.code.extend([Instr::Unit, Instr::Return]);
+        self.program.init = self.push("init".into(), init);
+    }
+
+    /// Define a global for each constructor of a data type. Constructors
+    /// without arguments are values, while the others are curried functions.
+    fn data(&mut self, init: &mut Builder, data: &Data) {
+        for (tag, ctor) in data.ctors.iter().enumerate() {
+            let name = self.name(&ctor.name);
+            let arity = ctor.fields.len() as u32;
+            let index = self.program.ctors.len() as u32;
+
+            self.program.ctors.push(Ctor {
+                name: name.clone(),
+                tag: tag as u32,
+                arity,
+            });
+
+            self.ctors.insert(ctor.name, index);
+            let global = self.global(ctor.name);
+
+            if arity == 0 {
+                init.code
+                    .extend([Instr::Construct(index), Instr::SetGlobal(global)]);
+                continue;
+            }
+
+            let first = curried(arity as usize, |n, next| {
+                let n = n as u32;
+                let mut body = Builder::new();
+                body.code.extend((0..n).map(Instr::Capture));
+                body.code.push(Instr::Local(0));
+
+                match next {
+                    None => body.code.push(Instr::Construct(index)),
+                    Some(next) => body.code.push(Instr::Closure(next, n + 1)),
+                }
+
+                body.code.push(Instr::Return);
+                self.push(name.clone(), body)
+            });
+
+            init.code
+                .extend([Instr::Closure(first, 0), Instr::SetGlobal(global)]);
+        }
+    }
+
+    /// Compile the expression bound to a name, naming the function it
+    /// evaluates to after it if it is a lambda.
+    fn named(&mut self, f: &mut Builder, expr: &Expr<'a, '_>, name: String) {
+        match expr {
+            Expr::TypeApply(expr, _) | Expr::TypeLambda(_, expr) => self.named(f, expr, name),
+            Expr::Lambda(..) => self.lambda(f, expr, name),
+            expr => self.expr(f, expr, false),
+        }
+    }
+
+    /// Compile the code evaluating an expression, leaving its value on the
+    /// stack. If `tail` is true, the code instead returns the value.
+    fn expr(&mut self, f: &mut Builder, expr: &Expr<'a, '_>, tail: bool) {
+        match expr {
+            Expr::Var(name) => match f.locals.get(name) {
+                Some(local) => f.get(*local),
+                None => f.code.push(Instr::Global(self.globals[name])),
+            },
+
+            Expr::Unit => f.code.push(Instr::Unit),
+            Expr::Number(value) => {
+                let value = integer(value).expect("literals are checked to fit when parsed");
+                f.code.push(Instr::Int(value));
+            }
+            Expr::Fail(_) => return f.code.push(Instr::Fail),
+
+            Expr::Field(record, ty, label) => {
+                self.expr(f, record, false);
+                match offset(ty, label) {
+                    Some(offset) => f.code.push(Instr::Offset(offset as u32)),
+                    None => f.code.push(Instr::Field(self.labels[label])),
+                }
+            }
+
+            Expr::Record(fields, extend) => {
+                let labels = fields.iter().map(|(label, _)| self.labels[label]).collect();
+                for (_, field) in fields.iter() {
+                    self.expr(f, field, false);
+                }
+
+                let shape = self.shape(labels);
+                f.code.push(Instr::Record(shape));
+
+                if let Some(extend) = extend {
+                    self.expr(f, extend, false);
+                    f.code.push(Instr::Extend);
+                }
+            }
+
+            Expr::Restrict(record, label) => {
+                self.expr(f, record, false);
+                f.code.push(Instr::Restrict(self.labels[label]));
+            }
+
+            Expr::Apply([fun, arg]) => {
+                if !self.construct(f, expr) {
+                    self.expr(f, fun, false);
+                    self.expr(f, arg, false);
+
+                    if tail {
+                        return f.code.push(Instr::TailApply);
+                    }
+
+                    f.code.push(Instr::Apply);
+                }
+            }
+
+            Expr::Lambda(..) => self.lambda(f, expr, "lambda".into()),
+
+            Expr::Primitive(primitive, args) => {
+                for arg in args.iter() {
+                    self.expr(f, arg, false);
+                }
+
+                f.code.push(match primitive {
+                    Primitive::Add => Instr::Add,
+                    Primitive::Sub => Instr::Sub,
+                    Primitive::Mul => Instr::Mul,
+                    Primitive::Less => Instr::Less,
+                });
+            }
+
+            Expr::TypeApply(expr, _) | Expr::TypeLambda(_, expr) => {
+                return self.expr(f, expr, tail)
+            }
+
+            Expr::Let(binding, body) => {
+                let name = self.name(&binding.name);
+                self.named(f, &binding.body, name);
+
+                let local = f.local();
+                f.code.push(Instr::SetLocal(local));
+                f.locals.insert(binding.name, Local::Local(local));
+                return self.expr(f, body, tail);
+            }
+
+            Expr::Case(scrutinee, alts, default) => {
+                return self.case(f, scrutinee, alts, default.as_deref(), tail)
+            }
+        }
+
+        if tail {
+            f.code.push(Instr::Return);
+        }
+    }
+
+    fn case(
+        &mut self,
+        f: &mut Builder,
+        scrutinee: &Expr<'a, '_>,
+        alts: &[ir::Alt<'a, '_>],
+        default: Option<&Expr<'a, '_>>,
+        tail: bool,
+    ) {
+        self.expr(f, scrutinee, false);
+        let scrutinee = f.local();
+        f.code
+            .extend([Instr::SetLocal(scrutinee), Instr::Local(scrutinee)]);
+
+        let switch = f.code.len();
+        f.code.push(Instr::Fail);
+
+        // Unless the case is in tail position, every alternative ends by
+        // jumping past the others.
+        let mut ends = Vec::new();
+        let mut targets = Vec::new();
+
+        for alt in alts.iter() {
+            let tag = self.program.ctors[self.ctors[&alt.ctor] as usize].tag as usize;
+            if targets.len() <= tag {
+                targets.resize(tag + 1, None);
+            }
+
+            targets[tag] = Some(f.code.len() as u32);
+
+            let used = free_vars(&alt.body);
+            for (index, (name, _)) in alt.binds.iter().enumerate() {
+                if !used.contains(name) {
+                    continue;
+                }
+
+                let local = f.local();
+                f.code.extend([
+                    Instr::Local(scrutinee),
+                    Instr::Argument(index as u32),
+                    Instr::SetLocal(local),
+                ]);
+                f.locals.insert(*name, Local::Local(local));
+            }
+
+            self.expr(f, &alt.body, tail);
+            if !tail {
+                ends.push(f.code.len());
+                f.code.push(Instr::Fail);
+            }
+        }
+
+        let otherwise = f.code.len() as u32;
+        match default {
+            Some(default) => self.expr(f, default, tail),
+            None => f.code.push(Instr::Fail),
+        }
+
+        let targets = targets
+            .into_iter()
+            .map(|target| target.unwrap_or(otherwise))
+            .collect();
+        f.code[switch] = Instr::Switch(targets, otherwise);
+
+        let end = f.code.len() as u32;
+        for index in ends {
+            f.code[index] = Instr::Jump(end);
+        }
+    }
+
+    /// Compile a lambda to a function, and leave a closure of it on the stack.
+    fn lambda(&mut self, f: &mut Builder, expr: &Expr<'a, '_>, name: String) {
+        let Expr::Lambda(param, _, body) = expr else {
+            unreachable!("only lambdas are compiled to functions");
+        };
+
+        let captures: Vec<_> = free_vars(expr)
+            .into_iter()
+            .filter_map(|name| Some((name, *f.locals.get(&name)?)))
+            .collect();
+
+        let mut inner = Builder::new();
+        for (index, (name, _)) in captures.iter().enumerate() {
+            inner.locals.insert(*name, Local::Capture(index as u32));
+        }
+
+        inner.locals.insert(*param, Local::Local(0));
+        self.expr(&mut inner, body, true);
+
+        let function = self.push(name, inner);
+        for (_, local) in captures.iter() {
+            f.get(*local);
+        }
+
+        f.code.push(Instr::Closure(function, captures.len() as u32));
+    }
+
+    /// If the expression applies a constructor to all of its arguments,
+    /// construct the value directly instead of calling the curried
+    /// constructor.
+    fn construct(&mut self, f: &mut Builder, expr: &Expr<'a, '_>) -> bool {
+        let arity = |name: &Name| {
+            let ctor = *self.ctors.get(name)?;
+            Some(self.program.ctors[ctor as usize].arity as usize)
+        };
+
+        let Some((name, args)) = saturated(expr, arity) else {
+            return false;
+        };
+
+        let ctor = self.ctors[&name];
+        for arg in args {
+            self.expr(f, arg, false);
+        }
+
+        f.code.push(Instr::Construct(ctor));
+        true
+    }
+
+    fn global(&mut self, name: Name) -> u32 {
+        let global = self.program.globals.len() as u32;
+        self.program.globals.push(self.name(&name));
+        self.globals.insert(name, global);
+        global
+    }
+
+    /// Add a function to the program, returning its index.
+    fn push(&mut self, name: String, f: Builder) -> u32 {
+        self.program.functions.push(Function {
+            name,
+            locals: f.count,
+            code: f.code,
+        });
+
+        self.program.functions.len() as u32 - 1
+    }
+
+    /// Get the index of a record shape, adding it if it doesn't exist yet.
+    fn shape(&mut self, labels: Vec<u32>) -> u32 {
+        if let Some(shape) = self.shapes.get(&labels) {
+            return *shape;
+        }
+
+        let shape = self.program.shapes.len() as u32;
+        self.program.shapes.push(labels.clone());
+        self.shapes.insert(labels, shape);
+        shape
+    }
+
+    fn name(&self, name: &Name) -> String {
+        self.names.get_name(name).name.name().into()
+    }
+}
+
+impl Builder {
+    /// Make a function whose only local is its argument.
+    fn new() -> Self {
+        Self {
+            locals: BTreeMap::new(),
+            count: 1,
+            code: Vec::new(),
+        }
+    }
+
+    /// Make a fresh local.
+    fn local(&mut self) -> u32 {
+        self.count += 1;
+        self.count - 1
+    }
+
+    fn get(&mut self, local: Local) {
+        match local {
+            Local::Local(index) => self.code.push(Instr::Local(index)),
+            Local::Capture(index) => self.code.push(Instr::Capture(index)),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Printing bytecode programs in a readable form. Every instruction is printed
+//! on its own line along with its index, and operands referring to globals,
+//! functions, constructors and labels are followed by their names.
+
+use super::{Function, Instr, Program};
+
+const INDENT: &str = "    ";
+
+pub fn program(program: &Program) -> String {
+    let mut result = String::new();
+
+    for (index, name) in program.globals.iter().enumerate() {
+        result.push_str(&format!("global {index} {name}\n"));
+    }
+
+    for (index, function) in program.functions.iter().enumerate() {
+        result.push('\n');
+        self::function(&mut result, program, index, function);
+    }
+
+    result.push_str(&format!("\ninit = function {}\n", program.init));
+    if let Some(main) = program.main {
+        result.push_str(&format!("main = global {main}\n"));
+    }
+
+    result
+}
+
+fn function(result: &mut String, program: &Program, index: usize, function: &Function) {
+    let s = if function.locals != 1 { "s" } else { "" };
+    result.push_str(&format!(
+        "function {index} {} ({} local{s}):\n",
+        function.name, function.locals
+    ));
+
+    let width = function.code.len().saturating_sub(1).to_string().len();
+    for (index, instr) in function.code.iter().enumerate() {
+        result.push_str(&format!(
+            "{INDENT}{index:>width$}  {}\n",
+            self::instr(program, instr)
+        ));
+    }
+}
+
+fn instr(program: &Program, instr: &Instr) -> String {
+    let global = |index: &u32| name(&program.globals, |name| name, *index);
+    let label = |index: &u32| name(&program.labels, |name| name, *index);
+    let ctor = |index: &u32| name(&program.ctors, |ctor| &ctor.name, *index);
+    let function = |index: &u32| name(&program.functions, |function| &function.name, *index);
+
+    match instr {
+        Instr::Unit => "unit".into(),
+        Instr::Int(value) => format!("int {value}"),
+        Instr::Global(index) => format!("global {index} {}", global(index)),
+        Instr::SetGlobal(index) => format!("set global {index} {}", global(index)),
+        Instr::Local(index) => format!("local {index}"),
+        Instr::SetLocal(index) => format!("set local {index}"),
+        Instr::Capture(index) => format!("capture {index}"),
+        Instr::Closure(index, count) => {
+            format!("closure {index} {}, {count}", function(index))
+        }
+
+        Instr::Apply => "apply".into(),
+        Instr::TailApply => "tail apply".into(),
+        Instr::Return => "return".into(),
+        Instr::Construct(index) => format!("construct {index} {}", ctor(index)),
+        Instr::Argument(index) => format!("argument {index}"),
+
+        Instr::Record(index) => {
+            let labels: Vec<_> = program
+                .shapes
+                .get(*index as usize)
+                .map(|shape| shape.iter().map(label).collect())
+                .unwrap_or_default();
+
+            if labels.is_empty() {
+                format!("record {index} {{}}")
+            } else {
+                format!("record {index} {{ {} }}", labels.join(", "))
+            }
+        }
+
+        Instr::Extend => "extend".into(),
+        Instr::Restrict(index) => format!("restrict {index} {}", label(index)),
+        Instr::Field(index) => format!("field {index} {}", label(index)),
+        Instr::Offset(offset) => format!("offset {offset}"),
+        Instr::Jump(target) => format!("jump {target}"),
+
+        Instr::Switch(targets, default) => {
+            let targets: Vec<_> = targets.iter().map(|target| target.to_string()).collect();
+            format!("switch [{}] else {default}", targets.join(", "))
+        }
+
+        Instr::Fail => "fail".into(),
+        Instr::Add => "add".into(),
+        Instr::Sub => "sub".into(),
+        Instr::Mul => "mul".into(),
+        Instr::Less => "less".into(),
+    }
+}
+
+/// Get the name of the thing at an index, or `?` if there is no such thing.
+fn name<T>(things: &[T], name: impl Fn(&T) -> &String, index: u32) -> String {
+    things
+        .get(index as usize)
+        .map(|thing| name(thing).clone())
+        .unwrap_or_else(|| "?".into())
+}
@@ -0,0 +1,307 @@
+//! The file format for bytecode programs. A file starts with the magic bytes
+//! `nmlb` and a format version, followed by the tables of the program and its
+//! functions. Numbers are stored as LEB128 integers (signed for integer
+//! constants, unsigned otherwise), strings as their length followed by their
+//! UTF-8 bytes, and lists as their length followed by their elements.
+//! Instructions are an opcode followed by their operands.
+
+use super::{Ctor, Function, Instr, Program};
+
+const MAGIC: &[u8] = b"nmlb";
+const VERSION: u32 = 1;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The file does not start with the magic bytes.
+    NotBytecode,
+
+    /// The file uses a version of the format this compiler does not support.
+    UnsupportedVersion(u32),
+
+    /// The file is truncated or otherwise invalid.
+    Malformed,
+}
+
+pub fn encode(program: &Program) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    unsigned(&mut out, VERSION);
+
+    list(&mut out, &program.globals, |out, name| string(out, name));
+    list(&mut out, &program.labels, |out, name| string(out, name));
+    list(&mut out, &program.shapes, |out, shape| {
+        list(out, shape, |out, label| unsigned(out, *label))
+    });
+
+    list(&mut out, &program.ctors, |out, ctor| {
+        string(out, &ctor.name);
+        unsigned(out, ctor.tag);
+        unsigned(out, ctor.arity);
+    });
+
+    list(&mut out, &program.functions, |out, function| {
+        string(out, &function.name);
+        unsigned(out, function.locals);
+        list(out, &function.code, instr);
+    });
+
+    unsigned(&mut out, program.init);
+    match program.main {
+        Some(main) => unsigned(&mut out, main + 1),
+        None => unsigned(&mut out, 0),
+    }
+
+    out
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Program, DecodeError> {
+    let Some(bytes) = bytes.strip_prefix(MAGIC) else {
+        return Err(DecodeError::NotBytecode);
+    };
+
+    let mut reader = Reader { bytes };
+
+    let version = reader.unsigned()?;
+    if version != VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+
+    let globals = reader.list(Reader::string)?;
+    let labels = reader.list(Reader::string)?;
+    let shapes = reader.list(|reader| reader.list(Reader::unsigned))?;
+
+    let ctors = reader.list(|reader| {
+        Ok(Ctor {
+            name: reader.string()?,
+            tag: reader.unsigned()?,
+            arity: reader.unsigned()?,
+        })
+    })?;
+
+    let functions = reader.list(|reader| {
+        Ok(Function {
+            name: reader.string()?,
+            locals: reader.unsigned()?,
+            code: reader.list(Reader::instr)?,
+        })
+    })?;
+
+    let init = reader.unsigned()?;
+    let main = reader.unsigned()?.checked_sub(1);
+
+    if !reader.bytes.is_empty() {
+        return Err(DecodeError::Malformed);
+    }
+
+    Ok(Program {
+        functions,
+        globals,
+        labels,
+        shapes,
+        ctors,
+        init,
+        main,
+    })
+}
+
+fn instr(out: &mut Vec<u8>, instr: &Instr) {
+    match instr {
+        Instr::Unit => out.push(0x00),
+        Instr::Int(value) => {
+            out.push(0x01);
+            signed(out, *value);
+        }
+
+        Instr::Global(index) => op(out, 0x02, *index),
+        Instr::SetGlobal(index) => op(out, 0x03, *index),
+        Instr::Local(index) => op(out, 0x04, *index),
+        Instr::SetLocal(index) => op(out, 0x05, *index),
+        Instr::Capture(index) => op(out, 0x06, *index),
+        Instr::Closure(function, count) => {
+            op(out, 0x07, *function);
+            unsigned(out, *count);
+        }
+
+        Instr::Apply => out.push(0x08),
+        Instr::TailApply => out.push(0x09),
+        Instr::Return => out.push(0x0a),
+        Instr::Construct(ctor) => op(out, 0x0b, *ctor),
+        Instr::Argument(index) => op(out, 0x0c, *index),
+        Instr::Record(shape) => op(out, 0x0d, *shape),
+        Instr::Extend => out.push(0x0e),
+        Instr::Restrict(label) => op(out, 0x0f, *label),
+        Instr::Field(label) => op(out, 0x10, *label),
+        Instr::Offset(offset) => op(out, 0x11, *offset),
+        Instr::Jump(target) => op(out, 0x12, *target),
+        Instr::Switch(targets, default) => {
+            out.push(0x13);
+            list(out, targets, |out, target| unsigned(out, *target));
+            unsigned(out, *default);
+        }
+
+        Instr::Fail => out.push(0x14),
+        Instr::Add => out.push(0x15),
+        Instr::Sub => out.push(0x16),
+        Instr::Mul => out.push(0x17),
+        Instr::Less => out.push(0x18),
+    }
+}
+
+/// Write an opcode followed by a single operand.
+fn op(out: &mut Vec<u8>, opcode: u8, operand: u32) {
+    out.push(opcode);
+    unsigned(out, operand);
+}
+
+fn list<T>(out: &mut Vec<u8>, items: &[T], mut f: impl FnMut(&mut Vec<u8>, &T)) {
+    unsigned(out, items.len() as u32);
+    for item in items {
+        f(out, item);
+    }
+}
+
+fn string(out: &mut Vec<u8>, string: &str) {
+    list(out, string.as_bytes(), |out, byte| out.push(*byte));
+}
+
+/// Write an unsigned LEB128 integer.
+fn unsigned(out: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            out.push(byte);
+            break;
+        }
+
+        out.push(byte | 0x80);
+    }
+}
+
+/// Write a signed LEB128 integer.
+fn signed(out: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            break;
+        }
+
+        out.push(byte | 0x80);
+    }
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+}
+
+impl Reader<'_> {
+    fn instr(&mut self) -> Result<Instr, DecodeError> {
+        Ok(match self.byte()? {
+            0x00 => Instr::Unit,
+            0x01 => Instr::Int(self.signed()?),
+            0x02 => Instr::Global(self.unsigned()?),
+            0x03 => Instr::SetGlobal(self.unsigned()?),
+            0x04 => Instr::Local(self.unsigned()?),
+            0x05 => Instr::SetLocal(self.unsigned()?),
+            0x06 => Instr::Capture(self.unsigned()?),
+            0x07 => Instr::Closure(self.unsigned()?, self.unsigned()?),
+            0x08 => Instr::Apply,
+            0x09 => Instr::TailApply,
+            0x0a => Instr::Return,
+            0x0b => Instr::Construct(self.unsigned()?),
+            0x0c => Instr::Argument(self.unsigned()?),
+            0x0d => Instr::Record(self.unsigned()?),
+            0x0e => Instr::Extend,
+            0x0f => Instr::Restrict(self.unsigned()?),
+            0x10 => Instr::Field(self.unsigned()?),
+            0x11 => Instr::Offset(self.unsigned()?),
+            0x12 => Instr::Jump(self.unsigned()?),
+            0x13 => Instr::Switch(self.list(Reader::unsigned)?, self.unsigned()?),
+            0x14 => Instr::Fail,
+            0x15 => Instr::Add,
+            0x16 => Instr::Sub,
+            0x17 => Instr::Mul,
+            0x18 => Instr::Less,
+            _ => return Err(DecodeError::Malformed),
+        })
+    }
+
+    fn list<T>(
+        &mut self,
+        mut f: impl FnMut(&mut Self) -> Result<T, DecodeError>,
+    ) -> Result<Vec<T>, DecodeError> {
+        let len = self.unsigned()? as usize;
+
+        // Every element takes at least one byte, so this avoids allocating a
+        // huge vector for a corrupted length.
+        let mut items = Vec::with_capacity(len.min(self.bytes.len()));
+        for _ in 0..len {
+            items.push(f(self)?);
+        }
+
+        Ok(items)
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let len = self.unsigned()? as usize;
+        if len > self.bytes.len() {
+            return Err(DecodeError::Malformed);
+        }
+
+        let (string, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        String::from_utf8(string.to_vec()).map_err(|_| DecodeError::Malformed)
+    }
+
+    fn unsigned(&mut self) -> Result<u32, DecodeError> {
+        let mut result = 0u32;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.byte()?;
+            if shift >= 32 {
+                return Err(DecodeError::Malformed);
+            }
+
+            result |= ((byte & 0x7f) as u32) << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+    }
+
+    fn signed(&mut self) -> Result<i64, DecodeError> {
+        let mut result = 0i64;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.byte()?;
+            if shift >= 64 {
+                return Err(DecodeError::Malformed);
+            }
+
+            result |= ((byte & 0x7f) as i64) << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    result |= -1 << shift;
+                }
+
+                return Ok(result);
+            }
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        let (byte, rest) = self.bytes.split_first().ok_or(DecodeError::Malformed)?;
+        self.bytes = rest;
+        Ok(*byte)
+    }
+}
@@ -0,0 +1,140 @@
+//! The bytecode is a compact, untyped representation of programs for a simple
+//! stack machine, which can be [compiled](compile) from a core program,
+//! [run](vm) directly, [disassembled](disassemble) for inspection, and
+//! [saved](file) to a file to be run later.
+//!
+//! A program consists of a set of functions, each taking a single argument.
+//! Every function has some number of local slots, the first of which holds the
+//! argument, along with the values captured by the closure it is called
+//! through. Instructions operate on a stack of values, which is separate for
+//! every call. Top-level bindings and constructors are stored in globals, which
+//! are initialized in program order by a separate function.
+
+pub mod compile;
+pub mod disassemble;
+pub mod file;
+pub mod vm;
+
+#[cfg(test)]
+mod tests;
+
+pub struct Program {
+    pub functions: Vec<Function>,
+
+    /// The name of every global.
+    pub globals: Vec<String>,
+
+    /// The name of every label, in the order used at runtime.
+    pub labels: Vec<String>,
+
+    /// The labels of the fields of every record construction, in the order
+    /// their values are pushed.
+    pub shapes: Vec<Vec<u32>>,
+
+    pub ctors: Vec<Ctor>,
+
+    /// The function initializing the globals.
+    pub init: u32,
+
+    /// The global whose value is printed after initialization, if any.
+    pub main: Option<u32>,
+}
+
+pub struct Function {
+    /// A name describing the function, for disassembly.
+    pub name: String,
+
+    /// The number of local slots, including the argument.
+    pub locals: u32,
+
+    pub code: Vec<Instr>,
+}
+
+pub struct Ctor {
+    pub name: String,
+
+    /// The index of the constructor within its data type.
+    pub tag: u32,
+
+    pub arity: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Instr {
+    /// Push unit.
+    Unit,
+
+    /// Push an integer.
+    Int(i64),
+
+    Global(u32),
+    SetGlobal(u32),
+    Local(u32),
+    SetLocal(u32),
+
+    /// Push the value with the given index in the closure of the current call.
+    Capture(u32),
+
+    /// Pop the given number of values and push a closure of the given function
+    /// capturing them, with the first value pushed first.
+    Closure(u32, u32),
+
+    /// Pop an argument and a function, and push the result of calling the
+    /// function with the argument.
+    Apply,
+
+    /// Like [`Instr::Apply`], but returns the result of the call from the
+    /// current function, reusing its frame.
+    TailApply,
+
+    /// Return the value on top of the stack.
+    Return,
+
+    /// Pop the arguments of a constructor and push the constructed value.
+    Construct(u32),
+
+    /// Pop a constructed value and push its argument with the given index.
+    Argument(u32),
+
+    /// Pop the values of the fields of a record with the given shape and push
+    /// the record.
+    Record(u32),
+
+    /// Pop a record and a record of fields, and push the record extended with
+    /// the fields.
+    Extend,
+
+    /// Pop a record and push it without the first field with the given label.
+    Restrict(u32),
+
+    /// Pop a record and push the value of its first field with the given label.
+    Field(u32),
+
+    /// Pop a record and push the value of the field at the given offset. Used
+    /// when the offset of a field is known statically.
+    Offset(u32),
+
+    /// Continue at the given instruction.
+    Jump(u32),
+
+    /// Pop a constructed value, and continue at the instruction given by the
+    /// tag of its constructor in the table, or the default if the table has no
+    /// entry for it.
+    Switch(Vec<u32>, u32),
+
+    /// Stop with a pattern match failure.
+    Fail,
+
+    /// Pop two integers and push their sum.
+    Add,
+
+    /// Pop two integers and push the first minus the second.
+    Sub,
+
+    /// Pop two integers and push their product.
+    Mul,
+
+    /// Pop two integers `a` and `b` and two values `x` and `y`, pushed in that
+    /// order, and push `x` if `a < b` and `y` otherwise.
+    Less,
+}
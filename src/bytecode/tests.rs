@@ -0,0 +1,231 @@
+use super::compile::compile;
+use super::file::{decode, encode, DecodeError};
+use super::{disassemble, vm, Program};
+use crate::frontend::testing::elaborated;
+use crate::ir::lower::lower;
+
+/// Compile the given (well typed) source to bytecode.
+fn generate(source: &str) -> Program {
+    generate_with(source, false)
+}
+
+/// Compile the given (well typed) source to bytecode, with the prelude in scope
+/// if `with_prelude` is true.
+fn generate_with(source: &str, with_prelude: bool) -> Program {
+    elaborated(source, with_prelude, |alloc, names, _, program| {
+        let core = lower(alloc, names, &program);
+        compile(names, &core)
+    })
+}
+
+/// Run the given source and print the value of `main`.
+fn run(source: &str) -> Result<Option<String>, vm::Error> {
+    let program = generate(source);
+    let value = vm::run(&program)?;
+    Ok(value.map(|value| vm::show(&program, &value, false)))
+}
+
+fn runs_to(source: &str, expected: &str) {
+    assert_eq!(expected, run(source).unwrap().unwrap());
+}
+
+#[test]
+fn lists_and_classes() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+
+        class Functor f = { map : ('a -> 'b) -> f 'a -> f 'b }
+
+        instance Functor List = {
+            map = f => (Nil => Nil | Cons x xs => Cons (f x) (map f xs))
+        }
+
+        let append = Nil => ys => ys | Cons x xs => ys => Cons x (append xs ys)
+        let main = map (x => { x }) (append (Cons 1 Nil) (Cons 2 Nil))
+    ";
+
+    runs_to(source, "Cons { x = 1 } (Cons { x = 2 } Nil)");
+}
+
+#[test]
+fn open_records() {
+    let source = "
+        let get r = r.b
+        let extend r = { a = 5, ... r }
+        let main = { got = get { a = 1, b = 2, c = 3 }, extended = extend { a = 1, b = 2 } }
+    ";
+
+    runs_to(source, "{ extended = { a = 5, a = 1, b = 2 }, got = 2 }");
+}
+
+#[test]
+fn or_patterns() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        let second = (Cons x Nil | Cons _ (Cons x _)) => x | _ => 0
+        let main = { one = second (Cons 1 Nil), two = second (Cons 1 (Cons 2 Nil)), none = second Nil }
+    ";
+
+    runs_to(source, "{ none = 0, one = 1, two = 2 }");
+}
+
+#[test]
+fn nominal_records() {
+    let source = "
+        data Person = Person { name : int, age : int }
+        let swap = Person { name, age } => Person { name = age, age = name }
+        let main = swap (Person { name = 1, age = 2 })
+    ";
+
+    runs_to(source, "Person { age = 1, name = 2 }");
+}
+
+#[test]
+fn closures_capture_variables() {
+    let source = "
+        let const x = y => { x, y }
+        let main = let f = const 1 in f 2
+    ";
+
+    runs_to(source, "{ x = 1, y = 2 }");
+}
+
+#[test]
+fn deep_recursion() {
+    let source = "
+        data Nat = Z | S Nat
+        let double = Z => Z | S n => S (S (double n))
+        let count = Z => 0 | S n => count n
+        let big = double (double (double (double (double (double (S Z))))))
+        let main = count (double (double (double (double (double (double big))))))
+    ";
+
+    runs_to(source, "0");
+}
+
+#[test]
+fn pattern_match_failures() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        let first xs = let Cons x _ = xs in x
+        let main = first Nil
+    ";
+
+    assert!(matches!(run(source), Err(vm::Error::PatternMatchFailure)));
+}
+
+#[test]
+fn programs_without_main_return_nothing() {
+    assert!(run("let id x = x").unwrap().is_none());
+}
+
+#[test]
+fn calls_in_tail_position() {
+    let program = generate("let apply f x = f x");
+    let expected = "global 0 apply
+
+function 0 lambda (1 local):
+    0  capture 0
+    1  local 0
+    2  tail apply
+
+function 1 apply (1 local):
+    0  local 0
+    1  closure 0 lambda, 1
+    2  return
+
+function 2 init (1 local):
+    0  closure 1 apply, 0
+    1  set global 0 apply
+    2  unit
+    3  return
+
+init = function 2
+";
+
+    assert_eq!(expected, disassemble::program(&program));
+}
+
+#[test]
+fn files_round_trip() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        let last = Nil => 0 | Cons x Nil => x | Cons _ xs => last xs
+        let main = { last = last (Cons 1 (Cons 2 Nil)), big = 12345678901, r = { a = 1, ... { b = 2 } } }
+    ";
+
+    let program = generate(source);
+    let decoded = decode(&encode(&program)).unwrap();
+    assert_eq!(
+        disassemble::program(&program),
+        disassemble::program(&decoded)
+    );
+
+    let value = vm::run(&decoded).unwrap().unwrap();
+    assert_eq!(
+        "{ big = 12345678901, last = 2, r = { a = 1, b = 2 } }",
+        vm::show(&decoded, &value, false)
+    );
+}
+
+#[test]
+fn invalid_files_are_rejected() {
+    let bytes = encode(&generate("let main = 5"));
+
+    assert!(matches!(decode(b"\0asm"), Err(DecodeError::NotBytecode)));
+    assert!(matches!(
+        decode(&bytes[..bytes.len() - 1]),
+        Err(DecodeError::Malformed)
+    ));
+
+    let mut newer = bytes.clone();
+    newer[4] = 2;
+    assert!(matches!(
+        decode(&newer),
+        Err(DecodeError::UnsupportedVersion(2))
+    ));
+}
+
+#[test]
+fn integer_operations() {
+    let source = "
+        let max a b = less a b b a
+        let main = { max = max 3 8, poly = sub (mul 6 7) (add 1 2), wrapped = add 9223372036854775807 1 }
+    ";
+
+    runs_to(
+        source,
+        "{ max = 8, poly = 39, wrapped = -9223372036854775808 }",
+    );
+}
+
+#[test]
+fn sections() {
+    let source = "
+        let infixl 6 + a b = add a b
+        let infixl 6 - a b = sub a b
+        let infixl 7 * a b = mul a b
+        let main = { a = (- 1 * 2) 10, b = (1 - 2 -) 10, c = (1 * 2 +) 10 }
+    ";
+
+    runs_to(source, "{ a = 8, b = -11, c = 12 }");
+}
+
+#[test]
+fn the_prelude() {
+    let source = "
+        let xs = Cons 3 (Cons 1 (Cons 2 Nil))
+        let main = {
+            sum = fold (a => b => a + b) 0 (map (x => x * x) xs),
+            big = filter (x => x >= 2) xs,
+            same = length xs == length (reverse xs) && not (1 /= 1)
+        }
+    ";
+
+    let program = generate_with(source, true);
+    let value = vm::run(&program).unwrap().unwrap();
+    assert_eq!(
+        "{ big = Cons 3 (Cons 2 Nil), same = True, sum = 14 }",
+        vm::show(&program, &value, false)
+    );
+}
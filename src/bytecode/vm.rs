@@ -0,0 +1,437 @@
+//! The virtual machine running bytecode programs. Values are reference counted
+//! and carry enough information to be printed without their types.
+//!
+//! Calls do not use the native stack, so deep (non-tail) recursion is only
+//! limited by memory, and tail calls run in constant space. The machine checks
+//! every instruction as it runs it, so malformed bytecode (from a corrupted
+//! file, say) results in an error rather than a crash.
+
+use std::rc::Rc;
+
+use super::{Instr, Program};
+
+#[derive(Clone, Debug)]
+pub enum Value {
+    Unit,
+    Int(i64),
+    Closure(Rc<Closure>),
+    Data(Rc<Data>),
+
+    /// The fields of a record sorted by label. If a label occurs several times,
+    /// the field added last comes first.
+    Record(Rc<[(u32, Value)]>),
+}
+
+#[derive(Debug)]
+pub struct Closure {
+    pub function: u32,
+    pub captures: Box<[Value]>,
+}
+
+#[derive(Debug)]
+pub struct Data {
+    pub ctor: u32,
+    pub args: Box<[Value]>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The program ran a `fail` instruction.
+    PatternMatchFailure,
+
+    /// The program refers to things which don't exist, or uses a value in a
+    /// way not allowed by its kind.
+    Malformed,
+}
+
+/// Run the program, returning the value of its `main` global if it has one.
+pub fn run(program: &Program) -> Result<Option<Value>, Error> {
+    let mut machine = Machine {
+        program,
+        globals: vec![Value::Unit; program.globals.len()],
+        stack: Vec::new(),
+        frames: Vec::new(),
+    };
+
+    let init = Rc::new(Closure {
+        function: program.init,
+        captures: Box::new([]),
+    });
+
+    machine.execute(init, Value::Unit)?;
+
+    match program.main {
+        Some(main) => Ok(Some(
+            machine
+                .globals
+                .get(main as usize)
+                .ok_or(Error::Malformed)?
+                .clone(),
+        )),
+
+        None => Ok(None),
+    }
+}
+
+/// Print a value like the C backend does, parenthesizing constructed values
+/// with arguments if `nested` is true.
+pub fn show(program: &Program, value: &Value, nested: bool) -> String {
+    match value {
+        Value::Unit => "()".into(),
+        Value::Int(value) => value.to_string(),
+        Value::Closure(_) => "<function>".into(),
+
+        Value::Data(data) => {
+            let name = program
+                .ctors
+                .get(data.ctor as usize)
+                .map(|ctor| ctor.name.as_str())
+                .unwrap_or("<constructor>");
+
+            if data.args.is_empty() {
+                return name.into();
+            }
+
+            let args: Vec<_> = data
+                .args
+                .iter()
+                .map(|arg| show(program, arg, true))
+                .collect();
+
+            if nested {
+                format!("({name} {})", args.join(" "))
+            } else {
+                format!("{name} {}", args.join(" "))
+            }
+        }
+
+        Value::Record(fields) if fields.is_empty() => "{}".into(),
+        Value::Record(fields) => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|(label, value)| {
+                    let label = program
+                        .labels
+                        .get(*label as usize)
+                        .map(String::as_str)
+                        .unwrap_or("<label>");
+                    format!("{label} = {}", show(program, value, false))
+                })
+                .collect();
+
+            format!("{{ {} }}", fields.join(", "))
+        }
+    }
+}
+
+struct Frame {
+    closure: Rc<Closure>,
+    ip: usize,
+
+    /// The index of the first local of the call on the stack.
+    base: usize,
+
+    /// The index of the bottom of the operand stack of the call, just above
+    /// its locals.
+    floor: usize,
+}
+
+struct Machine<'p> {
+    program: &'p Program,
+    globals: Vec<Value>,
+
+    /// The locals and operands of every call.
+    stack: Vec<Value>,
+    frames: Vec<Frame>,
+}
+
+impl Machine<'_> {
+    /// Call a closure and run until it returns.
+    fn execute(&mut self, closure: Rc<Closure>, arg: Value) -> Result<Value, Error> {
+        let program = self.program;
+        let depth = self.frames.len();
+        self.call(closure, arg)?;
+
+        loop {
+            let frame = self.frames.last_mut().expect("a call is running");
+            let function = &program.functions[frame.closure.function as usize];
+            let instr = function.code.get(frame.ip).ok_or(Error::Malformed)?;
+            frame.ip += 1;
+
+            match instr {
+                Instr::Unit => self.stack.push(Value::Unit),
+                Instr::Int(value) => self.stack.push(Value::Int(*value)),
+
+                Instr::Global(index) => {
+                    let value = self.globals.get(*index as usize).ok_or(Error::Malformed)?;
+                    self.stack.push(value.clone());
+                }
+
+                Instr::SetGlobal(index) => {
+                    let value = self.pop()?;
+                    *self
+                        .globals
+                        .get_mut(*index as usize)
+                        .ok_or(Error::Malformed)? = value;
+                }
+
+                Instr::Local(index) => {
+                    let value = self.local(*index)?.clone();
+                    self.stack.push(value);
+                }
+
+                Instr::SetLocal(index) => {
+                    let value = self.pop()?;
+                    *self.local(*index)? = value;
+                }
+
+                Instr::Capture(index) => {
+                    let value = frame
+                        .closure
+                        .captures
+                        .get(*index as usize)
+                        .ok_or(Error::Malformed)?
+                        .clone();
+                    self.stack.push(value);
+                }
+
+                Instr::Closure(function, count) => {
+                    let captures = self.pop_many(*count as usize)?;
+                    self.stack.push(Value::Closure(Rc::new(Closure {
+                        function: *function,
+                        captures,
+                    })));
+                }
+
+                Instr::Apply => {
+                    let arg = self.pop()?;
+                    let closure = self.pop_closure()?;
+                    self.call(closure, arg)?;
+                }
+
+                Instr::TailApply => {
+                    let arg = self.pop()?;
+                    let closure = self.pop_closure()?;
+                    let frame = self.frames.pop().expect("a call is running");
+                    self.stack.truncate(frame.base);
+                    self.call(closure, arg)?;
+                }
+
+                Instr::Return => {
+                    let value = self.pop()?;
+                    let frame = self.frames.pop().expect("a call is running");
+                    self.stack.truncate(frame.base);
+
+                    if self.frames.len() == depth {
+                        return Ok(value);
+                    }
+
+                    self.stack.push(value);
+                }
+
+                Instr::Construct(ctor) => {
+                    let arity = program
+                        .ctors
+                        .get(*ctor as usize)
+                        .ok_or(Error::Malformed)?
+                        .arity;
+                    let args = self.pop_many(arity as usize)?;
+                    self.stack
+                        .push(Value::Data(Rc::new(Data { ctor: *ctor, args })));
+                }
+
+                Instr::Argument(index) => {
+                    let Value::Data(data) = self.pop()? else {
+                        return Err(Error::Malformed);
+                    };
+
+                    let arg = data.args.get(*index as usize).ok_or(Error::Malformed)?;
+                    self.stack.push(arg.clone());
+                }
+
+                Instr::Record(shape) => {
+                    let labels = program
+                        .shapes
+                        .get(*shape as usize)
+                        .ok_or(Error::Malformed)?;
+                    let values = self.pop_many(labels.len())?;
+                    let mut fields: Vec<_> =
+                        labels.iter().copied().zip(values.into_vec()).collect();
+                    fields.sort_by_key(|(label, _)| *label);
+                    self.stack.push(Value::Record(fields.into()));
+                }
+
+                Instr::Extend => {
+                    let record = self.pop_record()?;
+                    let fields = self.pop_record()?;
+                    self.stack.push(Value::Record(extend(&fields, &record)));
+                }
+
+                Instr::Restrict(label) => {
+                    let record = self.pop_record()?;
+                    let removed = position(&record, *label)?;
+                    let fields: Vec<_> = record
+                        .iter()
+                        .enumerate()
+                        .filter(|(index, _)| *index != removed)
+                        .map(|(_, field)| field.clone())
+                        .collect();
+                    self.stack.push(Value::Record(fields.into()));
+                }
+
+                Instr::Field(label) => {
+                    let record = self.pop_record()?;
+                    let index = position(&record, *label)?;
+                    self.stack.push(record[index].1.clone());
+                }
+
+                Instr::Offset(offset) => {
+                    let record = self.pop_record()?;
+                    let (_, value) = record.get(*offset as usize).ok_or(Error::Malformed)?;
+                    self.stack.push(value.clone());
+                }
+
+                Instr::Jump(target) => frame.ip = *target as usize,
+
+                Instr::Switch(targets, default) => {
+                    let Value::Data(data) = self.pop()? else {
+                        return Err(Error::Malformed);
+                    };
+
+                    let tag = program
+                        .ctors
+                        .get(data.ctor as usize)
+                        .ok_or(Error::Malformed)?
+                        .tag;
+
+                    let frame = self.frames.last_mut().expect("a call is running");
+                    frame.ip = *targets.get(tag as usize).unwrap_or(default) as usize;
+                }
+
+                Instr::Fail => return Err(Error::PatternMatchFailure),
+
+                Instr::Add | Instr::Sub | Instr::Mul => {
+                    let b = self.pop_int()?;
+                    let a = self.pop_int()?;
+                    self.stack.push(Value::Int(match instr {
+                        Instr::Add => a.wrapping_add(b),
+                        Instr::Sub => a.wrapping_sub(b),
+                        _ => a.wrapping_mul(b),
+                    }));
+                }
+
+                Instr::Less => {
+                    let y = self.pop()?;
+                    let x = self.pop()?;
+                    let b = self.pop_int()?;
+                    let a = self.pop_int()?;
+                    self.stack.push(if a < b { x } else { y });
+                }
+            }
+        }
+    }
+
+    /// Start a call of a closure, with its argument as the first local.
+    fn call(&mut self, closure: Rc<Closure>, arg: Value) -> Result<(), Error> {
+        let function = self
+            .program
+            .functions
+            .get(closure.function as usize)
+            .ok_or(Error::Malformed)?;
+
+        let base = self.stack.len();
+        let floor = base + function.locals.max(1) as usize;
+
+        self.stack.push(arg);
+        self.stack.resize(floor, Value::Unit);
+        self.frames.push(Frame {
+            closure,
+            ip: 0,
+            base,
+            floor,
+        });
+
+        Ok(())
+    }
+
+    fn local(&mut self, index: u32) -> Result<&mut Value, Error> {
+        let frame = self.frames.last().expect("a call is running");
+        let index = frame.base + index as usize;
+        if index >= frame.floor {
+            return Err(Error::Malformed);
+        }
+
+        Ok(&mut self.stack[index])
+    }
+
+    fn pop(&mut self) -> Result<Value, Error> {
+        let frame = self.frames.last().expect("a call is running");
+        if self.stack.len() <= frame.floor {
+            return Err(Error::Malformed);
+        }
+
+        Ok(self.stack.pop().expect("the operand stack is not empty"))
+    }
+
+    /// Pop some values, returning them in the order they were pushed.
+    fn pop_many(&mut self, count: usize) -> Result<Box<[Value]>, Error> {
+        let frame = self.frames.last().expect("a call is running");
+        if self.stack.len() < frame.floor + count {
+            return Err(Error::Malformed);
+        }
+
+        let start = self.stack.len() - count;
+        Ok(self.stack.drain(start..).collect())
+    }
+
+    fn pop_int(&mut self) -> Result<i64, Error> {
+        match self.pop()? {
+            Value::Int(value) => Ok(value),
+            _ => Err(Error::Malformed),
+        }
+    }
+
+    fn pop_closure(&mut self) -> Result<Rc<Closure>, Error> {
+        match self.pop()? {
+            Value::Closure(closure) => Ok(closure),
+            _ => Err(Error::Malformed),
+        }
+    }
+
+    fn pop_record(&mut self) -> Result<Rc<[(u32, Value)]>, Error> {
+        match self.pop()? {
+            Value::Record(fields) => Ok(fields),
+            _ => Err(Error::Malformed),
+        }
+    }
+}
+
+/// Find the index of the first field with the given label.
+fn position(record: &[(u32, Value)], label: u32) -> Result<usize, Error> {
+    let index = record.partition_point(|(other, _)| *other < label);
+    match record.get(index) {
+        Some((other, _)) if *other == label => Ok(index),
+        _ => Err(Error::Malformed),
+    }
+}
+
+/// Add the fields of `fields` to `record`, in front of any fields with the same
+/// label.
+fn extend(fields: &[(u32, Value)], record: &[(u32, Value)]) -> Rc<[(u32, Value)]> {
+    let mut result = Vec::with_capacity(fields.len() + record.len());
+    let (mut i, mut j) = (0, 0);
+
+    while i < fields.len() || j < record.len() {
+        let from_fields = j >= record.len() || (i < fields.len() && fields[i].0 <= record[j].0);
+
+        if from_fields {
+            result.push(fields[i].clone());
+            i += 1;
+        } else {
+            result.push(record[j].clone());
+            j += 1;
+        }
+    }
+
+    result.into()
+}
@@ -0,0 +1,195 @@
+//! A single page listing every entry, with a search box filtering them by
+//! name. The search index is kept in a script of its own, which is loaded
+//! like any other script so that the page works without a server.
+
+use std::collections::BTreeSet;
+
+use serde_json::json;
+
+use super::{anchor, types, words, Entry, EntryKind, File};
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 50em; margin: auto; padding: 1em; }
+code { font-family: monospace; }
+section { border-top: 1px solid #ccc; }
+#results:empty { display: none; }";
+
+const SEARCH: &str = "const search = document.getElementById('search');
+const results = document.getElementById('results');
+search.addEventListener('input', () => {
+  const query = search.value.toLowerCase();
+  results.replaceChildren(...searchIndex
+    .filter((entry) => query && entry.name.toLowerCase().includes(query))
+    .map((entry) => {
+      const link = document.createElement('a');
+      link.href = '#' + entry.anchor;
+      link.textContent = entry.name + ' (' + entry.kind + ')';
+      const item = document.createElement('li');
+      item.append(link);
+      return item;
+    }));
+});";
+
+pub fn html(title: &str, entries: &[Entry]) -> Vec<File> {
+    let types = types(entries);
+    let mut page = String::new();
+
+    page.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+    page.push_str("<meta charset=\"utf-8\">\n");
+    page.push_str(&format!("<title>{}</title>\n", escape(title)));
+    page.push_str(&format!("<style>\n{STYLE}\n</style>\n"));
+    page.push_str("</head>\n<body>\n");
+    page.push_str(&format!("<h1>{}</h1>\n", escape(title)));
+    page.push_str("<input id=\"search\" type=\"search\" placeholder=\"Search\">\n");
+    page.push_str("<ul id=\"results\"></ul>\n");
+
+    for entry in entries {
+        page.push_str(&format!("<section id=\"{}\">\n", entry.anchor()));
+
+        match &entry.kind {
+            EntryKind::Value(scheme) => {
+                let name = escape(&entry.name);
+                let scheme = linked(scheme, &types);
+                page.push_str(&format!("<h2><code>{name} : {scheme}</code></h2>\n"));
+            }
+
+            EntryKind::Data(head, _) => {
+                let head = linked(head, &types);
+                page.push_str(&format!("<h2><code>data {head}</code></h2>\n"));
+            }
+
+            EntryKind::Alias(head, expansion) => {
+                let head = linked(head, &types);
+                let expansion = linked(expansion, &types);
+                page.push_str(&format!(
+                    "<h2><code>type {head} = {expansion}</code></h2>\n"
+                ));
+            }
+
+            EntryKind::Class(head, _) => {
+                let head = linked(head, &types);
+                page.push_str(&format!("<h2><code>class {head}</code></h2>\n"));
+            }
+        }
+
+        if let Some(doc) = &entry.doc {
+            page.push_str(&paragraphs(doc));
+        }
+
+        if let EntryKind::Data(_, ctors) = &entry.kind {
+            page.push_str("<ul>\n");
+            for (name, signature) in ctors {
+                let id = anchor("constructor", name);
+                let signature = linked(signature, &types);
+                page.push_str(&format!("<li id=\"{id}\"><code>{signature}</code></li>\n"));
+            }
+            page.push_str("</ul>\n");
+        }
+
+        if let EntryKind::Class(_, methods) = &entry.kind {
+            page.push_str("<ul>\n");
+            for method in methods {
+                let id = anchor("method", &method.name);
+                let name = escape(&method.name);
+                let ty = linked(&method.ty, &types);
+                page.push_str(&format!("<li id=\"{id}\"><code>{name} : {ty}</code>\n"));
+
+                if let Some(doc) = &method.doc {
+                    page.push_str(&paragraphs(doc));
+                }
+
+                page.push_str("</li>\n");
+            }
+            page.push_str("</ul>\n");
+        }
+
+        page.push_str("</section>\n");
+    }
+
+    page.push_str("<script src=\"search-index.js\"></script>\n");
+    page.push_str(&format!("<script>\n{SEARCH}\n</script>\n"));
+    page.push_str("</body>\n</html>\n");
+
+    vec![
+        File {
+            name: "index.html",
+            content: page,
+        },
+        File {
+            name: "search-index.js",
+            content: search_index(entries),
+        },
+    ]
+}
+
+/// Every entry, constructor and method, along with the fragment to jump to.
+fn search_index(entries: &[Entry]) -> String {
+    let mut index = Vec::new();
+
+    for entry in entries {
+        let kind = match &entry.kind {
+            EntryKind::Value(_) => "value",
+            EntryKind::Data(..) => "data",
+            EntryKind::Alias(..) => "type",
+            EntryKind::Class(..) => "class",
+        };
+
+        index.push(json!({ "name": entry.name, "kind": kind, "anchor": entry.anchor() }));
+
+        if let EntryKind::Data(_, ctors) = &entry.kind {
+            for (name, _) in ctors {
+                let anchor = anchor("constructor", name);
+                index.push(json!({ "name": name, "kind": "constructor", "anchor": anchor }));
+            }
+        }
+
+        if let EntryKind::Class(_, methods) = &entry.kind {
+            for method in methods {
+                let anchor = anchor("method", &method.name);
+                index.push(json!({ "name": method.name, "kind": "method", "anchor": anchor }));
+            }
+        }
+    }
+
+    let index = serde_json::to_string_pretty(&index).expect("values are serializable");
+    format!("const searchIndex = {index};\n")
+}
+
+/// Split documentation into paragraphs at blank lines.
+fn paragraphs(doc: &str) -> String {
+    doc.split("\n\n")
+        .filter(|par| !par.trim().is_empty())
+        .map(|par| format!("<p>{}</p>\n", escape(par.trim())))
+        .collect()
+}
+
+/// Escape a type, linking the names of documented types to them.
+fn linked(text: &str, types: &BTreeSet<&str>) -> String {
+    words(text)
+        .map(|(word, piece)| {
+            if word && types.contains(piece) {
+                format!(
+                    "<a href=\"#{}\">{}</a>",
+                    anchor("type", piece),
+                    escape(piece)
+                )
+            } else {
+                escape(piece)
+            }
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
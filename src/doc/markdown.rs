@@ -0,0 +1,109 @@
+//! A single Markdown file listing every entry. Types are written as text
+//! rather than code, since links within code are not links.
+
+use std::collections::BTreeSet;
+
+use super::{anchor, types, words, Entry, EntryKind, File};
+
+pub fn markdown(title: &str, entries: &[Entry]) -> Vec<File> {
+    let types = types(entries);
+    let mut page = format!("# {}\n", escape(title));
+
+    for entry in entries {
+        page.push_str(&format!("\n<a id=\"{}\"></a>\n\n", entry.anchor()));
+        page.push_str(&format!("## {}\n\n", escape(&entry.name)));
+
+        match &entry.kind {
+            EntryKind::Value(scheme) => {
+                let name = escape(&entry.name);
+                page.push_str(&format!("{name} : {}\n", linked(scheme, &types)));
+            }
+
+            EntryKind::Data(head, _) => {
+                page.push_str(&format!("data {}\n", linked(head, &types)));
+            }
+
+            EntryKind::Alias(head, expansion) => {
+                let head = linked(head, &types);
+                let expansion = linked(expansion, &types);
+                page.push_str(&format!("type {head} = {expansion}\n"));
+            }
+
+            EntryKind::Class(head, _) => {
+                page.push_str(&format!("class {}\n", linked(head, &types)));
+            }
+        }
+
+        if let Some(doc) = &entry.doc {
+            page.push_str(&format!("\n{}\n", doc.trim()));
+        }
+
+        if let EntryKind::Data(_, ctors) = &entry.kind {
+            if !ctors.is_empty() {
+                page.push('\n');
+            }
+
+            for (name, signature) in ctors {
+                let id = anchor("constructor", name);
+                let signature = linked(signature, &types);
+                page.push_str(&format!("- <a id=\"{id}\"></a>{signature}\n"));
+            }
+        }
+
+        if let EntryKind::Class(_, methods) = &entry.kind {
+            for method in methods {
+                let id = anchor("method", &method.name);
+                let name = escape(&method.name);
+                let ty = linked(&method.ty, &types);
+                page.push_str(&format!("\n- <a id=\"{id}\"></a>{name} : {ty}\n"));
+
+                // Indented to continue the list item
+                if let Some(doc) = &method.doc {
+                    for line in doc.trim().lines() {
+                        page.push('\n');
+                        if !line.is_empty() {
+                            page.push_str("  ");
+                            page.push_str(line);
+                        }
+                    }
+
+                    page.push('\n');
+                }
+            }
+        }
+    }
+
+    vec![File {
+        name: "index.md",
+        content: page,
+    }]
+}
+
+/// Escape a type, linking the names of documented types to them.
+fn linked(text: &str, types: &BTreeSet<&str>) -> String {
+    words(text)
+        .map(|(word, piece)| {
+            if word && types.contains(piece) {
+                format!("[{}](#{})", escape(piece), anchor("type", piece))
+            } else {
+                escape(piece)
+            }
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#'
+        ) {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
@@ -0,0 +1,258 @@
+//! Documentation is generated from the top-level items of a source: every
+//! `let` binding with its generalized type, every `data` type with its
+//! constructors, every `type` alias with its expansion, and every `class` with
+//! its methods, along with the documentation comments before them. Instances
+//! are anonymous, so there is nothing to list them under. The output depends
+//! on nothing but the source, so that the documentation of two versions of a
+//! program can be compared.
+
+mod html;
+mod markdown;
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeSet;
+
+use crate::frontend::names::{Name, Names};
+use crate::frontend::source::Source;
+use crate::frontend::trees::inferred::{
+    self, ConstructorNode, DataNode, ItemNode, PolyPattern, PolyPatternNode,
+};
+use crate::frontend::tyck::{Pretty, Scheme, Type};
+
+pub use self::html::html;
+pub use self::markdown::markdown;
+
+/// A generated file, named relative to the output directory.
+pub struct File {
+    pub name: &'static str,
+    pub content: String,
+}
+
+/// A documented top-level item.
+pub struct Entry {
+    pub name: String,
+    pub doc: Option<String>,
+    pub kind: EntryKind,
+}
+
+pub enum EntryKind {
+    /// A `let` binding with its type.
+    Value(String),
+
+    /// A `data` type applied to its parameters, and the name and signature of
+    /// each of its constructors.
+    Data(String, Vec<(String, String)>),
+
+    /// A `type` alias applied to its parameters, and the type it expands to.
+    Alias(String, String),
+
+    /// A `class` applied to its parameter, and each of its methods.
+    Class(String, Vec<Method>),
+}
+
+/// A method of a class, with its type as declared in the class.
+pub struct Method {
+    pub name: String,
+    pub ty: String,
+    pub doc: Option<String>,
+}
+
+impl Entry {
+    /// The fragment identifying this entry within the page.
+    pub fn anchor(&self) -> String {
+        match self.kind {
+            EntryKind::Value(_) => anchor("value", &self.name),
+            EntryKind::Data(..) | EntryKind::Alias(..) | EntryKind::Class(..) => {
+                anchor("type", &self.name)
+            }
+        }
+    }
+}
+
+/// Get the entries for the items defined in `source`, in the order they are
+/// defined.
+pub fn entries(names: &Names, source: &Source, program: &inferred::Program) -> Vec<Entry> {
+    let mut pretty = Pretty::new(names);
+    let mut items: Vec<_> = program
+        .items
+        .iter()
+        .flat_map(|items| items.iter())
+        .filter(|item| item.span.source == source.id)
+        .collect();
+
+    items.sort_by_key(|item| item.span.start);
+
+    let ident = |name: &Name| names.get_name(name).name.name().to_string();
+    let mut entries = Vec::new();
+
+    for item in items {
+        match &item.node {
+            ItemNode::Let(pattern, _, _) => {
+                let mut binds = Vec::new();
+                bound(pattern, &mut binds);
+
+                for (name, scheme) in binds {
+                    entries.push(Entry {
+                        name: ident(&name),
+                        doc: program.docs.get(&name).cloned(),
+                        kind: EntryKind::Value(pretty.build().scheme(scheme)),
+                    });
+                }
+            }
+
+            ItemNode::Data(scheme, data) => {
+                let Some(name) = head(scheme.ty) else {
+                    continue;
+                };
+
+                let ctors = match &data.node {
+                    DataNode::Invalid(_) => Vec::new(),
+                    DataNode::Sum(ctors) => ctors
+                        .iter()
+                        .filter_map(|ctor| match &ctor.node {
+                            ConstructorNode::Invalid(_) => None,
+                            ConstructorNode::Constructor(name, args) => {
+                                Some((ident(name), pretty.build().constructor(name, args)))
+                            }
+                        })
+                        .collect(),
+                };
+
+                entries.push(Entry {
+                    name: ident(&name),
+                    doc: program.docs.get(&name).cloned(),
+                    kind: EntryKind::Data(pretty.build().scheme(scheme), ctors),
+                });
+            }
+
+            ItemNode::Alias(scheme, body) => {
+                let Some(name) = head(scheme.ty) else {
+                    continue;
+                };
+
+                entries.push(Entry {
+                    name: ident(&name),
+                    doc: program.docs.get(&name).cloned(),
+                    kind: EntryKind::Alias(pretty.build().scheme(scheme), pretty.build().ty(body)),
+                });
+            }
+
+            ItemNode::Class(scheme, methods) => {
+                let Some(name) = head(scheme.ty) else {
+                    continue;
+                };
+
+                let doc = program.docs.get(&name);
+
+                // Methods without documentation of their own share that of
+                // the class, which is already shown above them
+                let methods = methods
+                    .iter()
+                    .map(|(method, scheme)| Method {
+                        name: ident(method),
+                        ty: pretty.build().ty(scheme.ty),
+                        doc: program
+                            .docs
+                            .get(method)
+                            .filter(|own| Some(*own) != doc)
+                            .cloned(),
+                    })
+                    .collect();
+
+                entries.push(Entry {
+                    name: ident(&name),
+                    doc: doc.cloned(),
+                    kind: EntryKind::Class(pretty.build().scheme(scheme), methods),
+                });
+            }
+
+            ItemNode::Invalid(_) | ItemNode::Instance(..) => {}
+        }
+    }
+
+    entries
+}
+
+/// Get the names of the documented data types, aliases and classes, which
+/// types link to.
+fn types(entries: &[Entry]) -> BTreeSet<&str> {
+    entries
+        .iter()
+        .filter(|entry| !matches!(entry.kind, EntryKind::Value(_)))
+        .map(|entry| entry.name.as_str())
+        .collect()
+}
+
+/// Make a fragment for a name, which may be an operator. Anything but ASCII
+/// letters and digits is written as its hexadecimal code point.
+fn anchor(prefix: &str, name: &str) -> String {
+    let mut anchor = format!("{prefix}-");
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            anchor.push(c);
+        } else {
+            anchor.push_str(&format!("_{:x}", c as u32));
+        }
+    }
+
+    anchor
+}
+
+/// Split a pretty-printed type into words and the text between them, such
+/// that the words can be linked to their types. Type parameters start with a
+/// tick, and are never linked.
+fn words(text: &str) -> impl Iterator<Item = (bool, &str)> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '\'';
+    let mut rest = text;
+
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let word = is_word(first);
+        let end = rest
+            .find(|c: char| is_word(c) != word)
+            .unwrap_or(rest.len());
+
+        let (piece, after) = rest.split_at(end);
+        rest = after;
+        Some((word && first != '\'', piece))
+    })
+}
+
+/// Get every name bound by a pattern along with its scheme.
+fn bound<'p, 'a>(pattern: &'p PolyPattern<'a>, into: &mut Vec<(Name, &'p Scheme<'a>)>) {
+    match &pattern.node {
+        PolyPatternNode::Invalid(_)
+        | PolyPatternNode::Wildcard
+        | PolyPatternNode::Unit
+        | PolyPatternNode::Constructor(_) => {}
+
+        PolyPatternNode::Bind(name) => into.push((*name, &pattern.scheme)),
+        PolyPatternNode::Group(pattern) => bound(pattern, into),
+
+        PolyPatternNode::Apply([a, b])
+        | PolyPatternNode::Or([a, b])
+        | PolyPatternNode::And([a, b]) => {
+            bound(a, into);
+            bound(b, into);
+        }
+
+        PolyPatternNode::Record(fields) => {
+            for (_, _, pattern) in fields.iter() {
+                bound(pattern, into);
+            }
+        }
+
+        PolyPatternNode::Anno(_, v) => match *v {},
+    }
+}
+
+/// Get the name of a data type, alias or class from its head.
+fn head(ty: &Type) -> Option<Name> {
+    match ty {
+        Type::Named(name) => Some(*name),
+        Type::Apply(ty, _) => head(ty),
+        _ => None,
+    }
+}
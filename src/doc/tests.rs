@@ -0,0 +1,215 @@
+use bumpalo::Bump;
+
+use super::{entries, html, markdown, File};
+use crate::frontend::names::Names;
+use crate::frontend::parse::parse;
+use crate::frontend::resolve::resolve;
+use crate::frontend::source::Sources;
+use crate::frontend::tyck;
+
+/// Document the given (well typed) source with the given generator.
+fn generate(source: &str, generator: fn(&str, &[super::Entry]) -> Vec<File>) -> Vec<File> {
+    let sources = Sources::new();
+    let source = sources.add(source);
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let resolved = resolve(&names, &alloc, &parsed);
+    let program = tyck::infer(&alloc, &names, &resolved);
+    assert_eq!(0, program.errors.num_errors());
+
+    generator("test", &entries(&names, &source, &program))
+}
+
+fn file<'a>(files: &'a [File], name: &str) -> &'a str {
+    &files
+        .iter()
+        .find(|file| file.name == name)
+        .expect("file is generated")
+        .content
+}
+
+const LIST: &str = "
+--| A list of things.
+--|
+--| Either `Nil` or `Cons`.
+data List a = Nil | Cons a (List a)
+
+--| Apply a function to every element.
+let map f = Nil => Nil | Cons x xs => Cons (f x) (map f xs)
+
+let (<>) = x => y => x
+";
+
+#[test]
+fn markdown_lists_items_in_order() {
+    let expected = r#"# test
+
+<a id="type-List"></a>
+
+## List
+
+data [List](#type-List) a
+
+A list of things.
+
+Either `Nil` or `Cons`.
+
+- <a id="constructor-Nil"></a>Nil
+- <a id="constructor-Cons"></a>Cons a ([List](#type-List) a)
+
+<a id="value-map"></a>
+
+## map
+
+map : ('0 -\> '1) -\> [List](#type-List) '0 -\> [List](#type-List) '1
+
+Apply a function to every element.
+
+<a id="value-_3c_3e"></a>
+
+## \<\>
+
+\<\> : '0 -\> '1 -\> '0
+"#;
+
+    let files = generate(LIST, markdown);
+    assert_eq!(expected, file(&files, "index.md"));
+}
+
+#[test]
+fn html_links_and_escapes_types() {
+    let files = generate(LIST, html);
+    let page = file(&files, "index.html");
+
+    assert!(page.contains(
+        "<h2><code>map : ('0 -&gt; '1) -&gt; <a href=\"#type-List\">List</a> '0 -&gt; <a href=\"#type-List\">List</a> '1</code></h2>"
+    ));
+
+    assert!(page.contains(
+        "<li id=\"constructor-Cons\"><code>Cons a (<a href=\"#type-List\">List</a> a)</code></li>"
+    ));
+
+    assert!(page.contains("<p>Either `Nil` or `Cons`.</p>"));
+    assert!(page.contains("<section id=\"value-_3c_3e\">\n<h2><code>&lt;&gt; : "));
+}
+
+#[test]
+fn search_index_includes_constructors() {
+    let files = generate(
+        "data Bool = False | True\nlet not = False => True | True => False",
+        html,
+    );
+    let index = file(&files, "search-index.js");
+
+    let names: Vec<_> = index
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("\"name\": "))
+        .collect();
+
+    assert_eq!(vec!["\"Bool\"", "\"False\"", "\"True\"", "\"not\""], names);
+}
+
+#[test]
+fn generation_is_deterministic() {
+    let first = generate(LIST, html);
+    let second = generate(LIST, html);
+
+    for (first, second) in first.iter().zip(&second) {
+        assert_eq!(first.content, second.content);
+    }
+}
+
+const CLASS: &str = "
+data Bool = False | True
+
+--| A pair of things.
+type Pair a b = { fst : a, snd : b }
+
+--| Things which can be compared.
+class Eq a = {
+  --| Whether two things are equal.
+  --|
+  --| Or not.
+  eq : a -> a -> Bool,
+  ne : a -> a -> Pair a a -> Bool
+}
+";
+
+#[test]
+fn markdown_lists_aliases_and_classes() {
+    let expected = r#"# test
+
+<a id="type-Bool"></a>
+
+## Bool
+
+data [Bool](#type-Bool)
+
+- <a id="constructor-False"></a>False
+- <a id="constructor-True"></a>True
+
+<a id="type-Pair"></a>
+
+## Pair
+
+type [Pair](#type-Pair) a b = { snd: b, fst: a }
+
+A pair of things.
+
+<a id="type-Eq"></a>
+
+## Eq
+
+class [Eq](#type-Eq) a
+
+Things which can be compared.
+
+- <a id="method-eq"></a>eq : a -\> a -\> [Bool](#type-Bool)
+
+  Whether two things are equal.
+
+  Or not.
+
+- <a id="method-ne"></a>ne : a -\> a -\> [Pair](#type-Pair) a a -\> [Bool](#type-Bool)
+"#;
+
+    let files = generate(CLASS, markdown);
+    assert_eq!(expected, file(&files, "index.md"));
+}
+
+#[test]
+fn html_links_aliases_and_classes() {
+    let files = generate(CLASS, html);
+    let page = file(&files, "index.html");
+
+    assert!(page.contains(
+        "<h2><code>type <a href=\"#type-Pair\">Pair</a> a b = { snd: b, fst: a }</code></h2>"
+    ));
+
+    assert!(page.contains("<h2><code>class <a href=\"#type-Eq\">Eq</a> a</code></h2>"));
+    assert!(page.contains(
+        "<li id=\"method-eq\"><code>eq : a -&gt; a -&gt; <a href=\"#type-Bool\">Bool</a></code>\n<p>Whether two things are equal.</p>\n<p>Or not.</p>\n</li>"
+    ));
+    assert!(page.contains("<li id=\"method-ne\"><code>ne : "));
+
+    let index = file(&files, "search-index.js");
+    let names: Vec<_> = index
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("\"kind\": "))
+        .collect();
+
+    assert_eq!(
+        vec![
+            "\"data\",",
+            "\"constructor\",",
+            "\"constructor\",",
+            "\"type\",",
+            "\"class\",",
+            "\"method\",",
+            "\"method\","
+        ],
+        names
+    );
+}
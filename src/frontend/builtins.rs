@@ -0,0 +1,110 @@
+//! Built-in names are the ones which are in scope everywhere without being
+//! defined by the program, like the `int` type, the `->` type constructor, or
+//! the primitive operations on integers.
+
+use crate::frontend::names::{Name, Names, ScopeName};
+use crate::frontend::trees::parsed::{Affix, Associativity, Fixity};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Builtin {
+    /// `int`
+    Integer,
+
+    /// `->`
+    Arrow,
+}
+
+impl Builtin {
+    pub const TYPES: [Builtin; 2] = [Builtin::Integer, Builtin::Arrow];
+
+    /// Get the builtin a particular name refers to, if any.
+    pub fn from_name(names: &Names, name: &Name) -> Option<Self> {
+        let name = names.get_name(name);
+        if name.parent != ScopeName::Builtin {
+            return None;
+        }
+
+        Self::TYPES
+            .into_iter()
+            .find(|builtin| builtin.ident() == name.name.name())
+    }
+
+    pub fn ident(&self) -> &'static str {
+        match self {
+            Self::Integer => "int",
+            Self::Arrow => "->",
+        }
+    }
+
+    pub fn affix(&self) -> Affix {
+        match self {
+            Self::Integer => Affix::Prefix,
+            Self::Arrow => Affix::Infix(Fixity {
+                assoc: Associativity::Right,
+                precedence: 0,
+            }),
+        }
+    }
+}
+
+/// Get the value of an integer literal, if it fits in an `int`. Integers are
+/// 64 bits in every backend, wrapping around on overflow.
+pub fn integer(literal: &str) -> Option<i64> {
+    literal.replace('_', "").parse().ok()
+}
+
+/// The primitive operations, which are values implemented directly by every
+/// backend.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Primitive {
+    /// `add : int -> int -> int`
+    Add,
+
+    /// `sub : int -> int -> int`
+    Sub,
+
+    /// `mul : int -> int -> int`
+    Mul,
+
+    /// `less : int -> int -> 'a -> 'a -> 'a`, where `less a b x y` is `x` if
+    /// `a < b` and `y` otherwise.
+    Less,
+}
+
+impl Primitive {
+    pub const VALUES: [Primitive; 4] = [
+        Primitive::Add,
+        Primitive::Sub,
+        Primitive::Mul,
+        Primitive::Less,
+    ];
+
+    /// Get the primitive a particular name refers to, if any.
+    pub fn from_name(names: &Names, name: &Name) -> Option<Self> {
+        let name = names.get_name(name);
+        if name.parent != ScopeName::Builtin {
+            return None;
+        }
+
+        Self::VALUES
+            .into_iter()
+            .find(|primitive| primitive.ident() == name.name.name())
+    }
+
+    pub fn ident(&self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Sub => "sub",
+            Self::Mul => "mul",
+            Self::Less => "less",
+        }
+    }
+
+    /// The number of arguments the operation takes.
+    pub fn arity(&self) -> usize {
+        match self {
+            Self::Add | Self::Sub | Self::Mul => 2,
+            Self::Less => 4,
+        }
+    }
+}
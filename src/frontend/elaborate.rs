@@ -0,0 +1,318 @@
+//! Elaboration makes the dictionary passing implied by type classes explicit.
+//! Every binding whose scheme has predicates becomes a function of one
+//! dictionary per predicate, every use of such a binding is applied to the
+//! dictionaries solving its predicates, and every use of a method becomes a
+//! field access on the dictionary of its class. Classes and instances are kept
+//! as they are, describing the types of the dictionaries and the dictionaries
+//! themselves.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bumpalo::Bump;
+
+use crate::frontend::names::{Label, Name, Names};
+use crate::frontend::source::Span;
+use crate::frontend::trees::typed::{
+    Evidence, Expr, ExprNode, Item, ItemNode, Pattern, PatternNode, Program, Var,
+};
+use crate::frontend::tyck::{Dict, DictVar, Predicate, Scheme, Type, TypeArg};
+
+pub fn elaborate<'a, 'src>(
+    alloc: &'a Bump,
+    names: &'a Names<'src>,
+    program: Program<'a, 'src>,
+) -> Program<'a, 'src> {
+    let methods = program
+        .items
+        .iter()
+        .flat_map(|items| items.iter())
+        .flat_map(|item| match &item.node {
+            ItemNode::Class(_, methods) => *methods,
+            _ => &[],
+        })
+        .map(|(name, _)| *name)
+        .collect();
+
+    let mut elaborator = Elaborator {
+        alloc,
+        names,
+        dicts: &program.dicts,
+        methods,
+        group: BTreeSet::new(),
+        params: Vec::new(),
+    };
+
+    let items =
+        alloc.alloc_slice_fill_iter(program.items.iter().map(|items| elaborator.items(items)));
+
+    Program { items, ..program }
+}
+
+struct Elaborator<'a, 'src, 'p> {
+    alloc: &'a Bump,
+    names: &'a Names<'src>,
+    dicts: &'p BTreeMap<DictVar, Evidence<'a>>,
+
+    /// The name of every method of every class.
+    methods: BTreeSet<Name>,
+
+    /// The names bound by the current group of items, along with the
+    /// dictionary parameters of the group. Recursive references within a group
+    /// are monomorphic, so they are passed the parameters of the group as is.
+    group: BTreeSet<Name>,
+    params: Vec<(Name, &'a Type<'a>)>,
+}
+
+impl<'a, 'src> Elaborator<'a, 'src, '_> {
+    fn items(&mut self, items: &[Item<'a, 'src>]) -> &'a [Item<'a, 'src>] {
+        self.group.clear();
+        self.params.clear();
+
+        for item in items {
+            if let ItemNode::Let(pattern, _, dicts) = &item.node {
+                if !dicts.is_empty() {
+                    bound_names(&mut self.group, pattern);
+                    self.params = self.dict_params(dicts, &pattern.scheme);
+                }
+            }
+        }
+
+        let items: Vec<_> = items
+            .iter()
+            .map(|item| {
+                let node = match &item.node {
+                    ItemNode::Invalid(e) => ItemNode::Invalid(*e),
+                    ItemNode::Let(pattern, expr, dicts) => {
+                        let params = self.dict_params(dicts, &pattern.scheme);
+                        let pattern = self.pattern(&params, pattern);
+                        let expr = self.expr(expr);
+                        let expr = self.abstract_dicts(&params, expr);
+                        ItemNode::Let(pattern, expr, &[])
+                    }
+
+                    ItemNode::Data(scheme, data) => ItemNode::Data(scheme.clone(), data),
+
+                    ItemNode::Alias(scheme, body) => ItemNode::Alias(scheme.clone(), body),
+                    ItemNode::Class(scheme, methods) => ItemNode::Class(scheme.clone(), methods),
+
+                    ItemNode::Instance((dict, scheme), body, _) => {
+                        ItemNode::Instance((*dict, scheme.clone()), self.expr(body), &[])
+                    }
+                };
+
+                Item {
+                    node,
+                    span: item.span,
+                    id: item.id,
+                }
+            })
+            .collect();
+
+        self.alloc.alloc_slice_fill_iter(items)
+    }
+
+    fn expr(&mut self, expr: &Expr<'a, 'src>) -> Expr<'a, 'src> {
+        let span = expr.span;
+        let ty = expr.ty;
+        let node = match &expr.node {
+            ExprNode::Invalid(e) => ExprNode::Invalid(*e),
+            ExprNode::Var(var) => return self.var(span, ty, var),
+            ExprNode::Hole => ExprNode::Hole,
+            ExprNode::Unit => ExprNode::Unit,
+            ExprNode::Number(v) => ExprNode::Number(v),
+            ExprNode::Anno(_, v) => match *v {},
+            ExprNode::Group(expr) => ExprNode::Group(self.alloc.alloc(self.expr(expr))),
+
+            ExprNode::Field(record, label, label_span) => {
+                let record = self.expr(record);
+                ExprNode::Field(self.alloc.alloc(record), *label, *label_span)
+            }
+
+            ExprNode::Record(fields, extend) => {
+                let fields = self.alloc.alloc_slice_fill_iter(
+                    fields
+                        .iter()
+                        .map(|(label, span, field)| (*label, *span, self.expr(field))),
+                );
+
+                let extend = extend.map(|extend| &*self.alloc.alloc(self.expr(extend)));
+                ExprNode::Record(fields, extend)
+            }
+
+            ExprNode::Restrict(record, label) => {
+                let record = self.expr(record);
+                ExprNode::Restrict(self.alloc.alloc(record), *label)
+            }
+
+            ExprNode::Apply([fun, arg]) => {
+                let fun = self.expr(fun);
+                let arg = self.expr(arg);
+                ExprNode::Apply(self.alloc.alloc([fun, arg]))
+            }
+
+            ExprNode::Lambda(arrows) => ExprNode::Lambda(
+                self.alloc.alloc_slice_fill_iter(
+                    arrows
+                        .iter()
+                        .map(|(pattern, body)| (pattern.clone(), self.expr(body))),
+                ),
+            ),
+
+            ExprNode::Let(pattern, [bound, body], dicts) => {
+                let params = self.dict_params(dicts, &pattern.scheme);
+                let pattern = self.pattern(&params, pattern);
+                let bound = self.expr(bound);
+                let bound = self.abstract_dicts(&params, bound);
+                let body = self.expr(body);
+                ExprNode::Let(pattern, self.alloc.alloc([bound, body]), &[])
+            }
+        };
+
+        Expr { node, span, ty }
+    }
+
+    /// Pass the dictionaries to a variable, or look the method up in its
+    /// dictionary.
+    fn var(&mut self, span: Span, ty: &'a Type<'a>, var: &Var<'a>) -> Expr<'a, 'src> {
+        if let (true, [dict]) = (self.methods.contains(&var.name), var.dicts) {
+            let dict = self.dict(span, dict);
+            let label = Label(self.names.get_name(&var.name).name);
+            let node = ExprNode::Field(self.alloc.alloc(dict), Ok(label), span);
+            return Expr { node, span, ty };
+        }
+
+        let args: Vec<_> = if var.dicts.is_empty() && self.group.contains(&var.name) {
+            self.params
+                .iter()
+                .map(|(name, ty)| plain_var(span, ty, *name, &[]))
+                .collect()
+        } else {
+            var.dicts.iter().map(|dict| self.dict(span, dict)).collect()
+        };
+
+        let fun_ty = args.iter().rev().fold(ty, |ty, arg| self.arrow(arg.ty, ty));
+
+        args.into_iter()
+            .fold(plain_var(span, fun_ty, var.name, var.args), |fun, arg| {
+                let Type::Apply(Type::Apply(Type::Arrow, _), ty) = fun.ty else {
+                    unreachable!("the function type has an arrow for every dictionary")
+                };
+
+                let node = ExprNode::Apply(self.alloc.alloc([fun, arg]));
+                Expr { node, span, ty }
+            })
+    }
+
+    /// Get the expression for the dictionary solving a predicate.
+    fn dict(&mut self, span: Span, var: &DictVar) -> Expr<'a, 'src> {
+        let Evidence { dict, args, ty } = self.dicts[var];
+        match dict {
+            Dict::Invalid(e) => Expr {
+                node: ExprNode::Invalid(e),
+                span,
+                ty,
+            },
+
+            Dict::Param(name) | Dict::Instance(name) => plain_var(span, ty, name, args),
+        }
+    }
+
+    /// Make a binding a function of its dictionary parameters.
+    fn abstract_dicts(
+        &mut self,
+        params: &[(Name, &'a Type<'a>)],
+        body: Expr<'a, 'src>,
+    ) -> Expr<'a, 'src> {
+        params.iter().rev().fold(body, |body, (name, ty)| {
+            let span = body.span;
+            let pattern = Pattern {
+                node: PatternNode::Bind(*name),
+                span,
+                scheme: Scheme::mono(ty),
+            };
+
+            let ty = self.arrow(ty, body.ty);
+            let node = ExprNode::Lambda(self.alloc.alloc_slice_fill_iter([(pattern, body)]));
+            Expr { node, span, ty }
+        })
+    }
+
+    /// Give the name bound by a constrained binding the type of a function of
+    /// its dictionaries.
+    fn pattern(&mut self, params: &[(Name, &'a Type<'a>)], pattern: &Pattern<'a>) -> Pattern<'a> {
+        if params.is_empty() {
+            return pattern.clone();
+        }
+
+        match &pattern.node {
+            PatternNode::Bind(_) => {
+                let ty = params
+                    .iter()
+                    .rev()
+                    .fold(pattern.scheme.ty, |ty, (_, param)| self.arrow(param, ty));
+
+                let scheme = Scheme {
+                    params: pattern.scheme.params.clone(),
+                    preds: Vec::new(),
+                    ty,
+                };
+
+                Pattern {
+                    node: pattern.node,
+                    span: pattern.span,
+                    scheme,
+                }
+            }
+
+            PatternNode::Group(inner) => self.pattern(params, inner),
+
+            // Constrained bindings with any other pattern are reported by the
+            // type checker.
+            _ => pattern.clone(),
+        }
+    }
+
+    fn dict_params(&self, dicts: &[Name], scheme: &Scheme<'a>) -> Vec<(Name, &'a Type<'a>)> {
+        dicts
+            .iter()
+            .zip(scheme.preds.iter())
+            .map(|(name, pred)| (*name, self.dict_type(pred)))
+            .collect()
+    }
+
+    fn dict_type(&self, pred: &Predicate<'a>) -> &'a Type<'a> {
+        let class = self.alloc.alloc(Type::Named(pred.class));
+        self.alloc.alloc(Type::Apply(class, pred.ty))
+    }
+
+    fn arrow(&self, t: &'a Type<'a>, u: &'a Type<'a>) -> &'a Type<'a> {
+        let arrow = self.alloc.alloc(Type::Arrow);
+        let arrow = self.alloc.alloc(Type::Apply(arrow, t));
+        self.alloc.alloc(Type::Apply(arrow, u))
+    }
+}
+
+fn plain_var<'a, 'src>(
+    span: Span,
+    ty: &'a Type<'a>,
+    name: Name,
+    args: &'a [TypeArg<'a>],
+) -> Expr<'a, 'src> {
+    let node = ExprNode::Var(Var {
+        name,
+        args,
+        dicts: &[],
+    });
+    Expr { node, span, ty }
+}
+
+fn bound_names(names: &mut BTreeSet<Name>, pattern: &Pattern) {
+    match &pattern.node {
+        PatternNode::Bind(name) => {
+            names.insert(*name);
+        }
+
+        PatternNode::Group(pattern) => bound_names(names, pattern),
+        _ => {}
+    }
+}
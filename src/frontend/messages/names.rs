@@ -23,6 +23,13 @@ impl NameErrors<'_> {
         self.errors.add(error)
     }
 
+    pub fn implicit_type_var_in_class(&mut self) -> ErrorId {
+        let error = self
+            .error("implicit type variables are not allowed as class parameters")
+            .with_help("use a normal type name for the class parameter");
+        self.errors.add(error)
+    }
+
     pub fn implicit_type_var_in_data(&mut self) -> ErrorId {
         let error = self
             .error("implicit type variables are not allowed in data types")
@@ -30,6 +37,13 @@ impl NameErrors<'_> {
         self.errors.add(error)
     }
 
+    pub fn implicit_type_var_in_alias(&mut self) -> ErrorId {
+        let error = self
+            .error("implicit type variables are not allowed in type aliases")
+            .with_help("add the type variable as a parameter to the type alias");
+        self.errors.add(error)
+    }
+
     pub fn or_patterns_disagree<'s>(&mut self, names: impl Iterator<Item = &'s str>) -> ErrorId {
         let names: Vec<_> = names.map(|name| format!("`{name}`")).collect();
         let s = if names.len() == 1 { "" } else { "s" };
@@ -55,6 +69,13 @@ impl NameErrors<'_> {
         self.errors.add(error)
     }
 
+    pub fn unknown_kind(&mut self, name: &str) -> ErrorId {
+        let error = self
+            .error(format!("unknown kind `{name}`"))
+            .with_note("kinds are `Type`, `Row`, and arrows between them (`Type -> Type`)");
+        self.errors.add(error)
+    }
+
     pub fn unknown_name(&mut self, name: &str) -> ErrorId {
         let error = self.error(format!("unknown name `{name}`"));
         self.errors.add(error)
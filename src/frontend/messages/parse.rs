@@ -16,11 +16,22 @@ pub(crate) struct ParseErrors<'a> {
 }
 
 impl ParseErrors<'_> {
-    pub fn ambiguous_infix_operators(&mut self, prev: Span) -> ErrorId {
+    pub fn ambiguous_infix_operators(
+        &mut self,
+        prev: Span,
+        prev_name: &str,
+        name: &str,
+    ) -> ErrorId {
+        let note = if prev_name == name {
+            format!("`{name}` is not associative")
+        } else {
+            format!("`{prev_name}` and `{name}` have the same precedence but cannot be chained")
+        };
+
         let error = self
             .error("ambiguous expression")
             .with_label(prev, "previous infix operator here")
-            .with_note("infix operators have no precedence")
+            .with_note(note)
             .with_help("disambiguate by adding explicit parentheses");
         self.errors.add(error)
     }
@@ -78,6 +89,20 @@ impl ParseErrors<'_> {
         self.errors.add(error)
     }
 
+    pub fn expected_kind(&mut self) -> ErrorId {
+        let error = self
+            .error("expected a kind")
+            .with_note("kinds are `Type`, `Row`, and arrows between them (`Type -> Type`)");
+        self.errors.add(error)
+    }
+
+    pub fn expected_methods(&mut self) -> ErrorId {
+        let error = self
+            .error("expected a record of method signatures")
+            .with_note("the methods of a class look like `{ name : type }`");
+        self.errors.add(error)
+    }
+
     pub fn expected_name(&mut self) -> ErrorId {
         let error = self.error("expected a name");
         self.errors.add(error)
@@ -107,7 +132,18 @@ impl ParseErrors<'_> {
     }
 
     pub fn infix_function(&mut self, name: &str) -> ErrorId {
-        let error = self.error(format!("`{name}` is an infix function"));
+        let error = self
+            .error(format!("`{name}` is an infix function"))
+            .with_help(format!(
+                "use `({name})` to refer to the function, or a section like `({name} x)` to partially apply it"
+            ));
+        self.errors.add(error)
+    }
+
+    pub fn invalid_precedence(&mut self, max: u8) -> ErrorId {
+        let error = self
+            .error("invalid operator precedence")
+            .with_note(format!("precedences range from 0 to {max}"));
         self.errors.add(error)
     }
 
@@ -120,11 +156,6 @@ impl ParseErrors<'_> {
         self.errors.add(error)
     }
 
-    pub fn kind_annotations_unsupported(&mut self) -> ErrorId {
-        let error = self.error("kind annotations are not yet supported");
-        self.errors.add(error)
-    }
-
     pub fn missing_definition(&mut self) -> ErrorId {
         let error = self.error("expected a `=` and a body");
         self.errors.add(error)
@@ -147,6 +178,16 @@ impl ParseErrors<'_> {
         self.errors.add(error)
     }
 
+    pub fn number_too_large(&mut self) -> ErrorId {
+        let error = self
+            .error("integer literal is too large")
+            .with_note(format!(
+                "integers are 64 bits, so the largest is {}",
+                i64::MAX
+            ));
+        self.errors.add(error)
+    }
+
     pub fn postfix_function(&mut self, name: &str) -> ErrorId {
         let error = self.error(format!("`{name}` is a postfix function"));
         self.errors.add(error)
@@ -169,6 +210,17 @@ impl ParseErrors<'_> {
         self.errors.add(error)
     }
 
+    pub fn section_not_outermost(&mut self, other: Span, name: &str, other_name: &str) -> ErrorId {
+        let error = self
+            .error("section operator must be outermost")
+            .with_label(other, format!("`{other_name}` does not bind more tightly than `{name}`"))
+            .with_note(format!(
+                "a section of `{name}` applies `{name}` last, so every operator in its operand must bind more tightly"
+            ))
+            .with_help("put the operand of the section in parentheses");
+        self.errors.add(error)
+    }
+
     pub fn unclosed_brace(&mut self, possible_placement: Span) -> ErrorId {
         let error = self
             .error("unclosed brace")
@@ -16,6 +16,60 @@ pub(crate) struct TypeErrors<'a> {
 }
 
 impl TypeErrors<'_> {
+    pub fn ambiguous_method(&mut self, name: &str) -> ErrorId {
+        let error = self
+            .error(format!(
+                "the type of method `{name}` does not mention the class parameter"
+            ))
+            .with_note("the instance to use for this method could never be determined");
+        self.errors.add(error)
+    }
+
+    pub fn ambiguous_predicate(&mut self, pred: String) -> ErrorId {
+        let error = self
+            .error(format!("ambiguous constraint `{pred}`"))
+            .with_note("the type it constrains is not known here")
+            .with_help("add a type annotation");
+        self.errors.add(error)
+    }
+
+    pub fn class_arguments(&mut self, class: &str, actual: usize) -> ErrorId {
+        let error = self
+            .error(format!(
+                "class `{class}` must be applied to exactly one type"
+            ))
+            .with_note(format!("`{class}` is given {actual}"));
+        self.errors.add(error)
+    }
+
+    pub fn class_parameters(&mut self, class: &str, actual: usize) -> ErrorId {
+        let s = if actual == 1 { "" } else { "s" };
+        let error = self
+            .error(format!("class `{class}` must have exactly one parameter"))
+            .with_note(format!("`{class}` has {actual} parameter{s}"));
+        self.errors.add(error)
+    }
+
+    pub fn constrained_pattern_binding(&mut self) -> ErrorId {
+        let error = self
+            .error("constrained bindings must bind a single name")
+            .with_note("the type of this binding has class constraints, so it must be a function of its dictionaries")
+            .with_help("split the pattern into separate bindings");
+        self.errors.add(error)
+    }
+
+    pub fn duplicate_method(&mut self, name: &str) -> ErrorId {
+        let error = self.error(format!("method `{name}` is defined more than once"));
+        self.errors.add(error)
+    }
+
+    pub fn expected_method_record(&mut self) -> ErrorId {
+        let error = self
+            .error("expected a record of methods")
+            .with_note("the body of an instance looks like `{ name = value }`");
+        self.errors.add(error)
+    }
+
     pub fn inequal_types(&mut self, lhs: String, rhs: String) -> ErrorId {
         let error = self
             .error("incompatible types")
@@ -24,6 +78,20 @@ impl TypeErrors<'_> {
         self.errors.add(error)
     }
 
+    pub fn inequal_nominal_record(&mut self, lhs: String, rhs: String, name: String) -> ErrorId {
+        let error = self
+            .error("incompatible types")
+            .with_note(format!("expected `{lhs}`"))
+            .with_note(format!(" but got `{rhs}`"))
+            .with_note(format!(
+                "`{name}` is a nominal record, whose fields are only accessible where its type is already known"
+            ))
+            .with_help(format!(
+                "annotate the type of the record whose fields are used, as in `(p : {name})`"
+            ));
+        self.errors.add(error)
+    }
+
     pub fn incompatible_labels(&mut self, lhs: String, rhs: String) -> ErrorId {
         let error = self.error("incompatible record types").with_note(format!(
             "record cannot have both labels `{lhs}` and `{rhs}`"
@@ -31,11 +99,85 @@ impl TypeErrors<'_> {
         self.errors.add(error)
     }
 
+    pub fn inequal_kinds(&mut self, lhs: String, rhs: String) -> ErrorId {
+        let error = self
+            .error("incompatible kinds")
+            .with_note(format!("expected a type of kind `{lhs}`"))
+            .with_note(format!(" but got one of kind `{rhs}`"));
+        self.errors.add(error)
+    }
+
+    pub fn invalid_instance_head(&mut self) -> ErrorId {
+        let error = self.error("invalid instance head").with_note(
+            "an instance must be for a type constructor applied to distinct type variables",
+        );
+        self.errors.add(error)
+    }
+
+    pub fn missing_methods(&mut self, class: &str, names: &[&str]) -> ErrorId {
+        let names = names
+            .iter()
+            .map(|name| format!("`{name}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let error = self
+            .error(format!("instance of `{class}` is missing methods"))
+            .with_note(format!("missing {names}"));
+        self.errors.add(error)
+    }
+
+    pub fn no_instance(&mut self, pred: String) -> ErrorId {
+        let error = self.error(format!("no instance for `{pred}`"));
+        self.errors.add(error)
+    }
+
     pub fn no_such_label(&mut self, label: String) -> ErrorId {
         let error = self.error(format!("record has no field `{label}`"));
         self.errors.add(error)
     }
 
+    pub fn not_a_class(&mut self, name: &str) -> ErrorId {
+        let error = self.error(format!("`{name}` is not a class"));
+        self.errors.add(error)
+    }
+
+    pub fn overlapping_instances(&mut self, prev: Span, class: &str) -> ErrorId {
+        let error = self
+            .error(format!("overlapping instances of `{class}`"))
+            .with_label(prev, "previous instance here");
+        self.errors.add(error)
+    }
+
+    pub fn partial_alias_application(
+        &mut self,
+        name: &str,
+        expected: usize,
+        actual: usize,
+    ) -> ErrorId {
+        let s = if expected == 1 { "" } else { "s" };
+        let error = self
+            .error(format!("type alias `{name}` is not fully applied"))
+            .with_note(format!(
+                "`{name}` takes {expected} argument{s}, but is given {actual}"
+            ));
+        self.errors.add(error)
+    }
+
+    pub fn recursive_alias(&mut self, name: &str) -> ErrorId {
+        let error = self
+            .error(format!("type alias `{name}` refers to itself"))
+            .with_note("aliases are expanded where they are used, so they cannot be recursive")
+            .with_help("use a `data` type instead");
+        self.errors.add(error)
+    }
+
+    pub fn recursive_kind(&mut self) -> ErrorId {
+        let error = self
+            .error("infinite kind")
+            .with_note("the kind of this type would have to contain itself");
+        self.errors.add(error)
+    }
+
     pub fn recursive_type(&mut self, var: String, ty: String) -> ErrorId {
         let error = self
             .error("infinite type")
@@ -46,6 +188,18 @@ impl TypeErrors<'_> {
         self.errors.add(error)
     }
 
+    pub fn too_many_type_arguments(&mut self, kind: String) -> ErrorId {
+        let error = self
+            .error("type applied to too many arguments")
+            .with_note(format!("a type of kind `{kind}` takes no arguments"));
+        self.errors.add(error)
+    }
+
+    pub fn unknown_method(&mut self, class: &str, name: &str) -> ErrorId {
+        let error = self.error(format!("class `{class}` has no method `{name}`"));
+        self.errors.add(error)
+    }
+
     fn error(&mut self, title: impl Into<String>) -> Error {
         Error::new(ErrorType::Type, Severity::Error, self.primary, title)
     }
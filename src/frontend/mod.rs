@@ -1,10 +1,18 @@
+pub mod builtins;
+pub mod elaborate;
 pub mod errors;
 pub mod names;
 pub mod parse;
+pub mod prelude;
 pub mod resolve;
 pub mod source;
+pub mod topology;
 pub mod trees;
 pub mod tyck;
 
 mod messages;
-mod topology;
+
+#[cfg(test)]
+pub mod testing;
+#[cfg(test)]
+mod ui;
@@ -21,6 +21,7 @@ pub type Ident<'src> = Identifier<'src>;
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum ScopeName {
     Anonymous(usize),
+    Builtin,
     Item(Name),
     TopLevel(SourceId),
 }
@@ -68,4 +69,25 @@ impl<'src> Names<'src> {
             .get(name)
             .expect("names from separate name stores are never mixed")
     }
+
+    /// Get every name in this store.
+    pub fn all(&self) -> Vec<(Name, Qualified<'src>)> {
+        self.names
+            .iter()
+            .map(|entry| (*entry.key(), *entry.value()))
+            .collect()
+    }
+
+    /// Add names from another store to this one, such that trees built with
+    /// the other store can be used with this one. This store must not have
+    /// made any names of its own yet.
+    pub fn import(&self, names: &[(Name, Qualified<'src>)]) {
+        let next = names.iter().map(|(Name(n), _)| n + 1).max().unwrap_or(0);
+        let prev = self.counter.swap(next, Ordering::SeqCst);
+        assert_eq!(0, prev, "names are imported before any are made");
+
+        for (name, qualified) in names {
+            self.names.insert(*name, *qualified);
+        }
+    }
 }
@@ -1,24 +1,26 @@
-use super::Abstractifier;
+use super::parts::Definition;
+use super::{children, Abstractifier, Part};
+use crate::frontend::builtins;
 use crate::frontend::errors::ErrorId;
 use crate::frontend::names::Label;
-use crate::frontend::parse::cst;
 use crate::frontend::source::Span;
 use crate::frontend::trees::parsed as ast;
+use crate::syntax::green::Kind;
+use crate::syntax::red::Cursor;
 
 impl<'a, 'src> Abstractifier<'a, 'src, '_> {
-    pub fn expr(&mut self, node: &cst::Thing<'_, 'src>) -> ast::Expr<'a, 'src> {
-        let span = node.span;
-        let node = match &node.node {
-            cst::Node::Invalid(e) => ast::ExprNode::Invalid(*e),
+    pub fn expr(&mut self, cursor: &Cursor) -> ast::Expr<'a, 'src> {
+        let span = self.span(cursor);
+        let node = match cursor.kind() {
+            Kind::Underscore => ast::ExprNode::Hole,
 
-            cst::Node::Wildcard => ast::ExprNode::Hole,
-
-            cst::Node::Name(cst::Name::Normal(name)) => {
-                let name = self.names.intern(name);
+            Kind::Name | Kind::PostTick => {
+                let name = self.names.intern(self.text(cursor));
                 ast::ExprNode::Var(name)
             }
 
-            cst::Node::Name(cst::Name::Universal(name)) => {
+            Kind::PreTick => {
+                let name = self.text(cursor);
                 let e = self
                     .errors
                     .parse_error(span)
@@ -26,130 +28,187 @@ impl<'a, 'src> Abstractifier<'a, 'src, '_> {
                 ast::ExprNode::Invalid(e)
             }
 
-            cst::Node::Number(lit) => ast::ExprNode::Number(lit),
+            Kind::Number => {
+                let lit = self.text(cursor);
+                match builtins::integer(lit) {
+                    Some(_) => ast::ExprNode::Number(lit),
+                    None => {
+                        ast::ExprNode::Invalid(self.errors.parse_error(span).number_too_large())
+                    }
+                }
+            }
 
-            cst::Node::Anno(expr, ty) => {
-                let expr = self.alloc.alloc(self.expr(expr));
-                let ty = self.ty(ty);
+            Kind::Annotate => {
+                let (expr, ty) = self.annotation(cursor);
+                let expr = self.or_invalid(&expr, Self::expr);
+                let expr = self.alloc.alloc(expr);
+                let ty = self.or_invalid(&ty, Self::ty);
                 ast::ExprNode::Anno(expr, ty)
             }
 
-            cst::Node::Group(expr) => {
-                let expr = self.alloc.alloc(self.expr(expr));
-                ast::ExprNode::Group(expr)
+            Kind::ParenGroup => {
+                let (expr, span) = self.group(cursor);
+                let expr = self.or_invalid(&expr, Self::expr);
+                let node = ast::ExprNode::Group(self.alloc.alloc(expr));
+                return ast::Expr { node, span };
             }
 
-            cst::Node::Field(of, fields) => {
-                let mut expr = self.expr(of);
+            Kind::Qualified => return self.fields(cursor),
 
-                for (field, field_span) in fields {
-                    let field_span = *field_span;
-                    let name = match field {
-                        cst::Name::Normal(name) => Ok(self.names.label(name)),
-
-                        cst::Name::Universal(name) => Err(self
-                            .errors
-                            .parse_error(field_span)
-                            .expected_non_universal_name(name)),
-                    };
-
-                    let span = expr.span + field_span;
-                    let node = ast::ExprNode::Field(self.alloc.alloc(expr), name, field_span);
-                    expr = ast::Expr { node, span };
-                }
-
-                return expr;
+            Kind::BraceGroup => {
+                let (definitions, span) = self.record(cursor);
+                let node = self.record_expr(definitions);
+                return ast::Expr { node, span };
             }
 
-            cst::Node::Record { defs } => self.record(defs),
+            Kind::CaseGroup => {
+                let case = self.case(cursor);
+                let (cases, arms_span) = match &case.arms {
+                    Ok(arms) => self.cases(arms),
+                    Err(span) => (&[][..], *span),
+                };
 
-            cst::Node::Case(scrutinee, terms) => {
-                let span = terms.span;
-                let cases = self.cases(terms);
                 let node = ast::ExprNode::Lambda(cases);
 
-                if let Some(scrutinee) = scrutinee {
-                    let case = ast::Expr { node, span };
+                let node = if let Some(scrutinee) = &case.scrutinee {
+                    let case = ast::Expr {
+                        node,
+                        span: arms_span,
+                    };
                     let scrutinee = self.expr(scrutinee);
 
                     let exprs = self.alloc.alloc([case, scrutinee]);
                     ast::ExprNode::Apply(exprs)
                 } else {
                     node
-                }
-            }
+                };
 
-            cst::Node::Apply(things) => {
-                let exprs = self
-                    .alloc
-                    .alloc_slice_fill_iter(things.iter().map(|node| self.expr(node)));
-                ast::ExprNode::Apply(exprs)
+                return ast::Expr {
+                    node,
+                    span: case.span,
+                };
             }
 
-            cst::Node::Arrow(pattern, body) => {
-                let pattern = self.pattern(pattern);
-                let body = self.expr(body);
-                ast::ExprNode::Lambda(
-                    self.alloc
-                        .alloc_slice_fill_iter(std::iter::once((pattern, body))),
-                )
+            Kind::Apply => {
+                let exprs: Vec<_> = children(cursor)
+                    .iter()
+                    .map(|child| self.expr(child))
+                    .collect();
+                ast::ExprNode::Apply(self.alloc.alloc_slice_fill_iter(exprs))
             }
 
-            cst::Node::Alt(nodes) => {
-                let arrows = self
-                    .alloc
-                    .alloc_slice_fill_iter(nodes.iter().map(|node| self.arrow(node)));
-                ast::ExprNode::Lambda(arrows)
+            Kind::Disjoined | Kind::Implied => {
+                let (cases, span) = self.cases(cursor);
+                let node = ast::ExprNode::Lambda(cases);
+                return ast::Expr { node, span };
             }
 
-            cst::Node::Let {
-                kw: (cst::LetKw::Let, _),
-                defs,
-                within,
-            } => {
-                let mut body = if let Some(within) = within {
-                    self.expr(within)
-                } else {
-                    let e = self
-                        .errors
-                        .parse_error(span)
-                        .value_definition_without_body();
-                    let node = ast::ExprNode::Invalid(e);
-                    ast::Expr { node, span }
-                };
+            Kind::Scoped | Kind::DefinitionGroup => return self.let_expr(cursor),
 
-                for def in defs.1.iter().rev().chain(std::iter::once(&defs.0)) {
-                    let binding = self.pattern(def.pattern);
-                    let bound = if let Some(bound) = def.definition {
-                        self.expr(bound)
-                    } else {
-                        let span = binding.span;
-                        let e = self.errors.parse_error(span).missing_definition();
-                        let node = ast::ExprNode::Invalid(e);
-                        ast::Expr { node, span }
-                    };
+            _ => {
+                self.syntax_errors(cursor);
+                let e = self.errors.parse_error(span).expected_expr();
+                ast::ExprNode::Invalid(e)
+            }
+        };
 
-                    let span = def.span;
-                    let node = ast::ExprNode::Let(binding, self.alloc.alloc([bound, body]), ());
-                    body = ast::Expr { node, span };
+        ast::Expr { node, span }
+    }
+
+    /// ```abnf
+    /// let = "let" def *("and" def) ["in" thing]
+    /// ```
+    fn let_expr(&mut self, cursor: &Cursor) -> ast::Expr<'a, 'src> {
+        let definitions = self.definitions(cursor);
+        let span = definitions.span;
+
+        if definitions.keyword != Kind::Let {
+            for def in definitions.definitions {
+                self.part_errors(&def.pattern);
+                if let Some(body) = &def.body {
+                    self.part_errors(body);
                 }
+            }
 
-                return body;
+            if let Some(within) = &definitions.within {
+                self.part_errors(within);
             }
 
-            _ => {
-                let e = self.errors.parse_error(span).expected_expr();
-                ast::ExprNode::Invalid(e)
+            let e = self.errors.parse_error(span).expected_expr();
+            let node = ast::ExprNode::Invalid(e);
+            return ast::Expr { node, span };
+        }
+
+        let mut body = match &definitions.within {
+            Some(within) => self.or_invalid(within, Self::expr),
+            None => {
+                let e = self
+                    .errors
+                    .parse_error(span)
+                    .value_definition_without_body();
+                let node = ast::ExprNode::Invalid(e);
+                ast::Expr { node, span }
             }
         };
 
-        ast::Expr { node, span }
+        for def in definitions.definitions.iter().rev() {
+            let binding = self.or_invalid(&def.pattern, Self::pattern);
+            let bound = match &def.body {
+                Some(bound) => self.or_invalid(bound, Self::expr),
+                None => {
+                    let span = binding.span;
+                    let e = self.errors.parse_error(span).missing_definition();
+                    let node = ast::ExprNode::Invalid(e);
+                    ast::Expr { node, span }
+                }
+            };
+
+            let span = def.span;
+            let node = ast::ExprNode::Let(binding, self.alloc.alloc([bound, body]), ());
+            body = ast::Expr { node, span };
+        }
+
+        body
     }
 
-    fn record(&mut self, defs: &[cst::ValueDef<'_, 'src>]) -> ast::ExprNode<'a, 'src> {
+    /// ```abnf
+    /// qual = atom *("." name)
+    /// ```
+    fn fields(&mut self, cursor: &Cursor) -> ast::Expr<'a, 'src> {
+        let (of, fields) = match self.qualified(cursor) {
+            Ok(qualified) => qualified,
+            Err((e, span)) => {
+                let node = ast::ExprNode::Invalid(e);
+                return ast::Expr { node, span };
+            }
+        };
+
+        let mut expr = self.or_invalid(&of, Self::expr);
+
+        for field in fields {
+            let field_span = field.span(self.source);
+            let name = self.text(&field);
+            let name = match field.kind() {
+                Kind::PreTick => Err(self
+                    .errors
+                    .parse_error(field_span)
+                    .expected_non_universal_name(name)),
+
+                _ => Ok(self.names.label(name)),
+            };
+
+            let span = expr.span + field_span;
+            let node = ast::ExprNode::Field(self.alloc.alloc(expr), name, field_span);
+            expr = ast::Expr { node, span };
+        }
+
+        expr
+    }
+
+    fn record_expr(&mut self, definitions: Vec<Definition>) -> ast::ExprNode<'a, 'src> {
         let mut extend = None;
 
-        let fields: Vec<_> = defs
+        let fields: Vec<_> = definitions
             .iter()
             .flat_map(|def| self.record_field(def, &mut extend))
             .collect();
@@ -178,25 +237,27 @@ impl<'a, 'src> Abstractifier<'a, 'src, '_> {
     /// record extensions met so far.
     fn record_field(
         &mut self,
-        def: &cst::ValueDef<'_, 'src>,
+        def: &Definition,
         extend: &mut Option<Result<ast::Expr<'a, 'src>, Span>>,
     ) -> Option<(Result<Label<'src>, ErrorId>, Span, ast::Expr<'a, 'src>)> {
-        if let Some(extension_terms) = Self::get_record_extension(def.pattern) {
+        if let Some(extension_terms) = get_record_extension(&def.pattern) {
             // Get the extension term
-            let mut term = match extension_terms {
+            let mut term = match &extension_terms[..] {
                 [] => unreachable!("record extensions are ellipses applied to at least one thing"),
                 [term] => self.expr(term),
                 terms @ [first, .., last] => {
-                    let span = first.span + last.span;
-                    let node = cst::Node::Apply(terms.to_vec());
-                    let term = cst::Thing { node, span };
-                    self.expr(&term)
+                    let span = self.span(first) + self.span(last);
+                    let exprs: Vec<_> = terms.iter().map(|term| self.expr(term)).collect();
+                    let node = ast::ExprNode::Apply(self.alloc.alloc_slice_fill_iter(exprs));
+                    ast::Expr { node, span }
                 }
             };
 
             // Error on `... x = y`
-            if let Some(definition) = def.definition {
-                let span = definition.span;
+            if let Some(definition) = &def.body {
+                self.part_errors(definition);
+
+                let span = self.part_span(definition);
                 let e = self
                     .errors
                     .parse_error(span)
@@ -214,33 +275,31 @@ impl<'a, 'src> Abstractifier<'a, 'src, '_> {
 
             None
         } else {
-            let (name, name_span) = self.normal_name(def.pattern);
+            let (name, name_span) = self.normal_name(&def.pattern);
             let name = name.map(Label);
 
-            let body = if let Some(body) = def.definition {
-                self.expr(body)
+            let body = if let Some(body) = &def.body {
+                self.part_errors(&def.pattern);
+                self.or_invalid(body, Self::expr)
             } else {
-                self.expr(def.pattern)
+                self.or_invalid(&def.pattern, Self::expr)
             };
 
             Some((name, name_span, body))
         }
     }
+}
+
+/// Returns `Some([x, y, z])` if the given part is an application like
+/// `... x y z`.
+fn get_record_extension(part: &Part) -> Option<Vec<Cursor>> {
+    let cursor = part.as_ref().ok()?;
+    if cursor.kind() != Kind::Apply {
+        return None;
+    }
 
-    /// Returns `Some(x y z) if the given node is an application like
-    /// `... x y z`.
-    fn get_record_extension<'tree, 's>(
-        node: &'tree cst::Thing<'tree, 's>,
-    ) -> Option<&'tree [&'tree cst::Thing<'tree, 's>]> {
-        let cst::Node::Apply(terms) = &node.node else {
-            return None;
-        };
-        match &terms[..] {
-            [cst::Thing {
-                node: cst::Node::Ellipses,
-                ..
-            }, rest @ ..] => Some(rest),
-            _ => None,
-        }
+    match &children(cursor)[..] {
+        [ellipses, rest @ ..] if ellipses.kind() == Kind::Ellipses => Some(rest.to_vec()),
+        _ => None,
     }
 }
@@ -1,177 +1,359 @@
 use bumpalo::collections::Vec;
 
-use super::Abstractifier;
+use super::lambda::Lambda;
+use super::parts::Definition;
+use super::{children, is_name, Abstractifier, Part};
 use crate::frontend::errors::ErrorId;
 use crate::frontend::names::Ident;
-use crate::frontend::parse::cst;
 use crate::frontend::trees::parsed as ast;
+use crate::syntax::green::Kind;
+use crate::syntax::red::Cursor;
 
 impl<'a, 'src> Abstractifier<'a, 'src, '_> {
-    pub fn item(&mut self, into: &mut Vec<ast::Item<'a, 'src>>, node: &cst::Thing<'_, 'src>) {
-        let span = node.span;
-        let node = match &node.node {
-            cst::Node::Invalid(e) => ast::ItemNode::Invalid(*e),
-            cst::Node::Let {
-                kw: (cst::LetKw::Let, _),
-                defs,
-                within,
-            } => {
-                if let Some(within) = within {
-                    let e = self
-                        .errors
-                        .parse_error(within.span)
-                        .item_definition_with_body();
-                    self.parse_errors.push((e, within.span));
+    pub fn item(&mut self, into: &mut Vec<ast::Item<'a, 'src>>, cursor: &Cursor) {
+        let span = self.span(cursor);
+        let (node, span) = match cursor.kind() {
+            Kind::Scoped | Kind::DefinitionGroup => {
+                let definitions = self.definitions(cursor);
+
+                if let Some(within) = &definitions.within {
+                    self.part_errors(within);
+
+                    let span = self.part_span(within);
+                    let e = self.errors.parse_error(span).item_definition_with_body();
+                    self.unattached.push((e, span));
                 }
 
-                into.reserve_exact(defs.1.len() + 1);
-                into.push(self.single_value(&defs.0));
-                into.extend(defs.1.iter().map(|def| self.single_value(def)));
+                let single: fn(&mut Self, &Definition<'a>) -> ast::Item<'a, 'src> =
+                    match definitions.keyword {
+                        Kind::Class => Self::single_class,
+                        Kind::Data => Self::single_data_type,
+                        Kind::Instance => Self::single_instance,
+                        Kind::Type => Self::single_alias,
+                        _ => Self::single_value,
+                    };
+
+                into.reserve_exact(definitions.definitions.len());
+                into.extend(definitions.definitions.iter().map(|def| single(self, def)));
                 return;
             }
 
-            cst::Node::Let {
-                kw: (cst::LetKw::Data, _),
-                defs,
-                within,
-            } => {
-                if let Some(within) = within {
-                    let e = self
-                        .errors
-                        .parse_error(within.span)
-                        .item_definition_with_body();
-                    self.parse_errors.push((e, within.span));
-                }
-
-                into.reserve_exact(defs.1.len() + 1);
-                into.push(self.single_data_type(&defs.0));
-                into.extend(defs.1.iter().map(|def| self.single_value(def)));
-                return;
-            }
+            Kind::ParenGroup => match self.group(cursor).0 {
+                Ok(item) => return self.item(into, &item),
+                Err((e, span)) => (ast::ItemNode::Invalid(e), span),
+            },
 
-            cst::Node::Group(item) => {
-                return self.item(into, item);
-            }
-
-            _ => {
-                let e = self.errors.parse_error(span).expected_item();
-                ast::ItemNode::Invalid(e)
-            }
+            _ => match self.misplaced(cursor) {
+                Some((e, span)) => (ast::ItemNode::Invalid(e), span),
+                None => {
+                    let e = self.errors.parse_error(span).expected_item();
+                    (ast::ItemNode::Invalid(e), span)
+                }
+            },
         };
 
-        into.push(ast::Item { node, span });
+        into.push(ast::Item {
+            node,
+            span,
+            doc: None,
+            method_docs: &[],
+        });
     }
 
-    fn single_value(&mut self, def: &cst::ValueDef<'_, 'src>) -> ast::Item<'a, 'src> {
-        let pattern = self.pattern(def.pattern);
+    fn single_value(&mut self, def: &Definition<'a>) -> ast::Item<'a, 'src> {
+        let pattern = self.or_invalid(&def.pattern, Self::pattern);
 
-        let body = def
-            .definition
-            .map(|node| self.expr(node))
-            .unwrap_or_else(|| {
+        let body = match &def.body {
+            Some(body) => self.or_invalid(body, Self::expr),
+            None => {
                 let span = pattern.span;
                 let e = self.errors.parse_error(span).missing_definition();
                 let node = ast::ExprNode::Invalid(e);
                 ast::Expr { node, span }
-            });
+            }
+        };
 
         let span = def.span;
         let node = ast::ItemNode::Let(pattern, body, ());
-        ast::Item { node, span }
+        ast::Item {
+            node,
+            span,
+            doc: def.doc,
+            method_docs: &[],
+        }
     }
 
-    fn single_data_type(&mut self, def: &cst::ValueDef<'_, 'src>) -> ast::Item<'a, 'src> {
-        let pattern = self.pattern(def.pattern);
+    fn single_data_type(&mut self, def: &Definition<'a>) -> ast::Item<'a, 'src> {
+        let pattern = self.or_invalid(&def.pattern, Self::pattern);
 
         let span = def.span;
-        let body = if let Some(body) = def.definition {
-            self.data_body(body)
-        } else {
-            ast::Data {
+        let body = match &def.body {
+            Some(Ok(body)) => self.data_body(body),
+            Some(Err(missing)) => self.single_constructor(Err(*missing)),
+            None => ast::Data {
                 node: ast::DataNode::Sum(self.alloc.alloc([])),
                 span,
-            }
+            },
         };
 
         let node = ast::ItemNode::Data(pattern, body);
-        ast::Item { node, span }
+        ast::Item {
+            node,
+            span,
+            doc: def.doc,
+            method_docs: &[],
+        }
+    }
+
+    fn single_alias(&mut self, def: &Definition<'a>) -> ast::Item<'a, 'src> {
+        let pattern = self.or_invalid(&def.pattern, Self::alias_pattern);
+
+        let body = match &def.body {
+            Some(body) => self.or_invalid(body, Self::ty),
+            None => {
+                let span = pattern.span;
+                let e = self.errors.parse_error(span).missing_definition();
+                let node = ast::TypeNode::Invalid(e);
+                ast::Type { node, span }
+            }
+        };
+
+        let span = def.span;
+        let node = ast::ItemNode::Alias(pattern, body);
+        ast::Item {
+            node,
+            span,
+            doc: def.doc,
+            method_docs: &[],
+        }
     }
 
-    fn data_body(&mut self, node: &cst::Thing<'_, 'src>) -> ast::Data<'a, 'src> {
-        let span = node.span;
-        match &node.node {
-            cst::Node::Group(thing) => self.data_body(thing),
-
-            cst::Node::Alt(things) => {
-                let ctors = self
-                    .alloc
-                    .alloc_slice_fill_iter(things.iter().map(|thing| self.data_constructor(thing)));
-                ast::Data {
-                    node: ast::DataNode::Sum(ctors),
-                    span,
+    fn single_class(&mut self, def: &Definition<'a>) -> ast::Item<'a, 'src> {
+        let pattern = self.or_invalid(&def.pattern, Self::pattern);
+
+        let span = def.span;
+        let mut method_docs = Vec::new_in(self.alloc);
+        let node = match &def.body {
+            // The methods are read here rather than as a record type, since
+            // they keep their documentation
+            Some(Ok(body)) if body.kind() == Kind::BraceGroup => {
+                let (definitions, _) = self.record(body);
+                let methods: std::vec::Vec<_> =
+                    definitions.iter().map(|def| self.field(def)).collect();
+
+                method_docs.extend(
+                    definitions
+                        .iter()
+                        .zip(&methods)
+                        .filter_map(|(def, (_, span, _))| Some((*span, def.doc?))),
+                );
+
+                ast::ItemNode::Class(pattern, self.alloc.alloc_slice_fill_iter(methods))
+            }
+
+            Some(body) => match self.or_invalid(body, Self::ty) {
+                ast::Type {
+                    node: ast::TypeNode::Record(methods),
+                    ..
+                } => ast::ItemNode::Class(pattern, methods),
+
+                body => {
+                    let e = self.errors.parse_error(body.span).expected_methods();
+                    ast::ItemNode::Invalid(e)
                 }
+            },
+
+            None => ast::ItemNode::Class(pattern, self.alloc.alloc([])),
+        };
+
+        ast::Item {
+            node,
+            span,
+            doc: def.doc,
+            method_docs: method_docs.into_bump_slice(),
+        }
+    }
+
+    fn single_instance(&mut self, def: &Definition<'a>) -> ast::Item<'a, 'src> {
+        let head = self.or_invalid(&def.pattern, Self::ty);
+
+        let body = match &def.body {
+            Some(body) => self.or_invalid(body, Self::expr),
+            None => {
+                let span = head.span;
+                let e = self.errors.parse_error(span).missing_definition();
+                let node = ast::ExprNode::Invalid(e);
+                ast::Expr { node, span }
             }
+        };
+
+        let span = def.span;
+        let node = ast::ItemNode::Instance(head, body, ());
+        ast::Item {
+            node,
+            span,
+            doc: def.doc,
+            method_docs: &[],
+        }
+    }
+
+    /// Prefix type aliases may take universal type parameters like `'a`, which
+    /// are bound like any other name.
+    fn alias_pattern(&mut self, cursor: &Cursor) -> ast::Pattern<'a, 'src> {
+        let span = self.span(cursor);
+        let node = match cursor.kind() {
+            Kind::PreTick => {
+                let name = self.names.intern(self.text(cursor));
+                ast::PatternNode::Bind((ast::Affix::Prefix, name))
+            }
+
+            Kind::Apply if is_name(&children(cursor)[0]) => {
+                let terms: std::vec::Vec<_> = children(cursor)
+                    .iter()
+                    .map(|term| self.alias_pattern(term))
+                    .collect();
+                ast::PatternNode::Apply(self.alloc.alloc_slice_fill_iter(terms))
+            }
+
+            Kind::ParenGroup => {
+                let (inner, span) = self.group(cursor);
+                let pattern = self.or_invalid(&inner, Self::alias_pattern);
+                let node = ast::PatternNode::Group(self.alloc.alloc(pattern));
+                return ast::Pattern { node, span };
+            }
+
+            _ => return self.pattern(cursor),
+        };
+
+        ast::Pattern { node, span }
+    }
 
-            cst::Node::Case(scrutinee, alts) => {
-                if let Some(scrutinee) = scrutinee {
-                    let span = scrutinee.span;
+    fn data_body(&mut self, cursor: &Cursor) -> ast::Data<'a, 'src> {
+        match cursor.kind() {
+            Kind::ParenGroup => match self.group(cursor).0 {
+                Ok(inner) => self.data_body(&inner),
+                Err((e, span)) => self.single_constructor(Err((e, span))),
+            },
+
+            Kind::Disjoined | Kind::Implied => match self.lambda(cursor) {
+                Lambda::Alternatives(alternatives) => {
+                    let ctors: std::vec::Vec<_> = alternatives
+                        .parts
+                        .iter()
+                        .map(|part| self.data_constructor(part))
+                        .collect();
+                    ast::Data {
+                        node: ast::DataNode::Sum(self.alloc.alloc_slice_fill_iter(ctors)),
+                        span: alternatives.span,
+                    }
+                }
+
+                arms => {
+                    let span = arms.span();
+                    for part in &arms.into_parts() {
+                        self.part_errors(part);
+                    }
+
+                    let e = self.errors.parse_error(span).expected_constructor_name();
+                    self.single_constructor(Err((e, span)))
+                }
+            },
+
+            Kind::CaseGroup => {
+                let case = self.case(cursor);
+                if let Some(scrutinee) = case.scrutinee {
+                    self.syntax_errors(&scrutinee);
+                    if let Ok(arms) = &case.arms {
+                        self.syntax_errors(arms);
+                    }
+
+                    let span = self.span(&scrutinee);
                     let e = self.errors.parse_error(span).scrutinee_in_sum_data_type();
                     ast::Data {
                         node: ast::DataNode::Invalid(e),
                         span,
                     }
                 } else {
-                    self.data_body(alts)
+                    match case.arms {
+                        Ok(arms) => self.data_body(&arms),
+                        Err(span) => ast::Data {
+                            node: ast::DataNode::Sum(self.alloc.alloc([])),
+                            span,
+                        },
+                    }
                 }
             }
 
-            _ => {
-                let ctor = self.data_constructor(node);
-                let ctors = self.alloc.alloc([ctor]);
-                ast::Data {
-                    node: ast::DataNode::Sum(ctors),
-                    span,
-                }
-            }
+            _ => self.single_constructor(Ok(cursor.clone())),
         }
     }
 
-    fn data_constructor(&mut self, node: &cst::Thing<'_, 'src>) -> ast::Constructor<'a, 'src> {
-        let span = node.span;
-        match &node.node {
-            cst::Node::Group(thing) => self.data_constructor(thing),
+    fn single_constructor(&mut self, part: Part) -> ast::Data<'a, 'src> {
+        let span = self.part_span(&part);
+        let ctor = self.data_constructor(&part);
+        let ctors = self.alloc.alloc([ctor]);
+        ast::Data {
+            node: ast::DataNode::Sum(ctors),
+            span,
+        }
+    }
+
+    fn data_constructor(&mut self, part: &Part) -> ast::Constructor<'a, 'src> {
+        let cursor = match part {
+            Ok(cursor) => cursor,
+            Err((e, span)) => {
+                let node = ast::ConstructorNode::Invalid(*e);
+                return ast::Constructor { node, span: *span };
+            }
+        };
 
-            cst::Node::Name(cst::Name::Normal(name)) => {
+        let span = self.span(cursor);
+        match cursor.kind() {
+            Kind::ParenGroup => {
+                let (inner, _) = self.group(cursor);
+                self.data_constructor(&inner)
+            }
+
+            Kind::Name | Kind::PostTick => {
                 let affix = ast::Affix::Prefix;
-                let name = self.names.intern(name);
+                let name = self.names.intern(self.text(cursor));
                 let params = self.alloc.alloc([]);
 
                 let node = ast::ConstructorNode::Constructor((affix, name), params);
                 ast::Constructor { node, span }
             }
 
-            cst::Node::Apply(run) => {
+            Kind::Apply => {
+                let run = children(cursor);
                 let [name, params @ ..] = &run[..] else {
                     unreachable!("application runs have at least two terms");
                 };
 
-                let name = match self.data_constructor_name(name) {
+                let name = match self.data_constructor_name(&Ok(name.clone())) {
                     Ok(name) => name,
                     Err(e) => {
+                        for param in params {
+                            self.syntax_errors(param);
+                        }
+
                         let node = ast::ConstructorNode::Invalid(e);
                         return ast::Constructor { node, span };
                     }
                 };
 
-                let params = self
-                    .alloc
-                    .alloc_slice_fill_iter(params.iter().map(|thing| self.ty(thing)));
+                let params: std::vec::Vec<_> = params.iter().map(|param| self.ty(param)).collect();
+                let params = self.alloc.alloc_slice_fill_iter(params);
 
                 let node = ast::ConstructorNode::Constructor(name, params);
                 ast::Constructor { node, span }
             }
 
             _ => {
+                if let Some((e, span)) = self.misplaced(cursor) {
+                    let node = ast::ConstructorNode::Invalid(e);
+                    return ast::Constructor { node, span };
+                }
+
                 let e = self.errors.parse_error(span).expected_constructor_name();
                 let node = ast::ConstructorNode::Invalid(e);
                 ast::Constructor { node, span }
@@ -179,74 +361,89 @@ impl<'a, 'src> Abstractifier<'a, 'src, '_> {
         }
     }
 
-    fn data_constructor_name(
-        &mut self,
-        node: &cst::Thing<'_, 'src>,
-    ) -> Result<(ast::Affix, Ident<'src>), ErrorId> {
-        match &node.node {
-            cst::Node::Invalid(e) => Err(*e),
-            cst::Node::Group(thing) => self.data_constructor_name(thing),
+    fn data_constructor_name(&mut self, part: &Part) -> Result<(ast::Affix, Ident<'src>), ErrorId> {
+        let cursor = part.as_ref().map_err(|(e, _)| *e)?;
+
+        match cursor.kind() {
+            Kind::ParenGroup => {
+                let (inner, _) = self.group(cursor);
+                self.data_constructor_name(&inner)
+            }
 
-            cst::Node::Name(cst::Name::Normal(name)) => {
+            Kind::Name | Kind::PostTick => {
                 let affix = ast::Affix::Prefix;
-                let name = self.names.intern(name);
+                let name = self.names.intern(self.text(cursor));
                 Ok((affix, name))
             }
 
-            cst::Node::Apply(run) => {
-                let [affix, name, rest @ ..] = &run[..] else {
-                    unreachable!("application runs have at least two terms");
-                };
+            Kind::Apply => {
+                let run = children(cursor);
+                let result = self.affixed_constructor_name(cursor, &run);
 
-                let affix = match &affix.node {
-                    cst::Node::Infix => ast::Affix::Infix,
-                    cst::Node::Postfix => ast::Affix::Postfix,
-
-                    cst::Node::Name(_) => {
-                        let span = rest
-                            .iter()
-                            .map(|node| node.span)
-                            .fold(name.span, |a, b| a + b);
-                        return Err(self
-                            .errors
-                            .parse_error(span)
-                            .constructor_parameters_not_after_name());
+                if result.is_err() {
+                    for term in &run {
+                        self.syntax_errors(term);
                     }
+                }
 
-                    _ => {
-                        return Err(self
-                            .errors
-                            .parse_error(affix.span)
-                            .expected_constructor_name())
-                    }
-                };
+                result
+            }
 
-                let name = match &name.node {
-                    cst::Node::Name(cst::Name::Normal(name)) => name,
-                    _ => {
-                        return Err(self
-                            .errors
-                            .parse_error(name.span)
-                            .expected_constructor_name())
-                    }
-                };
+            _ => {
+                self.syntax_errors(cursor);
+                let span = self.span(cursor);
+                Err(self.errors.parse_error(span).expected_constructor_name())
+            }
+        }
+    }
 
-                let name = self.names.intern(name);
+    fn affixed_constructor_name(
+        &mut self,
+        cursor: &Cursor,
+        run: &[Cursor],
+    ) -> Result<(ast::Affix, Ident<'src>), ErrorId> {
+        let [affix, rest @ ..] = run else {
+            unreachable!("application runs have at least two terms");
+        };
 
-                if let Some(rest) = rest.iter().map(|node| node.span).reduce(|a, b| a + b) {
-                    Err(self
-                        .errors
-                        .parse_error(rest)
-                        .constructor_parameters_not_after_name())
-                } else {
-                    Ok((affix, name))
-                }
+        let (affix, rest) = match self.affix(affix, rest.first()) {
+            Some((affix, true)) => (affix, &rest[1..]),
+            Some((affix, false)) => (affix, rest),
+
+            None if is_name(affix) => {
+                let span = self.span(&rest[0]) + self.span(rest.last().unwrap());
+                return Err(self
+                    .errors
+                    .parse_error(span)
+                    .constructor_parameters_not_after_name());
             }
 
-            _ => Err(self
+            None => {
+                let span = self.span(affix);
+                return Err(self.errors.parse_error(span).expected_constructor_name());
+            }
+        };
+
+        let [name, rest @ ..] = rest else {
+            let span = self.span(cursor);
+            return Err(self.errors.parse_error(span).expected_constructor_name());
+        };
+
+        if !matches!(name.kind(), Kind::Name | Kind::PostTick) {
+            let span = self.span(name);
+            return Err(self.errors.parse_error(span).expected_constructor_name());
+        }
+
+        let name = self.names.intern(self.text(name));
+
+        if let [first, .., last] | [first @ last] = rest {
+            let span = self.span(first) + self.span(last);
+            Err(self
                 .errors
-                .parse_error(node.span)
-                .expected_constructor_name()),
+                .parse_error(span)
+                .constructor_parameters_not_after_name())
+        } else {
+            Ok((affix, name))
         }
     }
 }
@@ -1,51 +1,267 @@
-use super::Abstractifier;
-use crate::frontend::parse::cst::{self};
+use super::{children, Abstractifier, Part};
+use crate::frontend::source::Span;
 use crate::frontend::trees::parsed as ast;
+use crate::syntax::green::Kind;
+use crate::syntax::red::Cursor;
+
+/// The arms of a lambda, or the alternatives of one without any arrows.
+pub(super) enum Lambda {
+    Alternatives(Alternatives),
+    Arms(Vec<Arm>, Span),
+}
+
+/// Things separated by pipes, such as an or-pattern.
+pub(super) struct Alternatives {
+    pub parts: Vec<Part>,
+    pub span: Span,
+}
+
+/// A single arm of a lambda, like `a | b => x => y`. The last thing after an
+/// arrow is the body, and those before it are further patterns.
+pub(super) struct Arm {
+    pub pattern: Alternatives,
+    pub arrows: Vec<Part>,
+}
 
 impl<'a, 'src> Abstractifier<'a, 'src, '_> {
-    pub(super) fn cases(
-        &mut self,
-        node: &cst::Thing<'_, 'src>,
-    ) -> &'a [(ast::Pattern<'a, 'src>, ast::Expr<'a, 'src>)] {
-        if let cst::Node::Alt(lambdas) = &node.node {
-            self.alloc
-                .alloc_slice_fill_iter(lambdas.iter().map(|node| self.arrow(node)))
-        } else {
-            self.alloc
-                .alloc_slice_fill_iter(std::iter::once(self.arrow(node)))
+    /// ```abnf
+    /// arrows     = ["|"] arrow *("|" arrow)
+    /// arrow      = or-pattern *("=>" simple)
+    /// or-pattern = simple *("|" simple)
+    /// ```
+    ///
+    /// Only the last arm may lack an arrow, since any pipe after it would be
+    /// part of its or-pattern. If there are no other arms, the lambda is just
+    /// the alternatives of that or-pattern.
+    pub(super) fn lambda(&mut self, cursor: &Cursor) -> Lambda {
+        let children = children(cursor);
+        let opener = children
+            .first()
+            .filter(|first| cursor.kind() == Kind::Disjoined && first.kind() == Kind::Pipe)
+            .map(|pipe| pipe.span(self.source));
+
+        // The arms are only wrapped in a node if there is a leading pipe or
+        // more than one of them, and the first of them then has an arrow
+        let arms = match children.first() {
+            Some(first)
+                if cursor.kind() == Kind::Disjoined
+                    && matches!(first.kind(), Kind::Pipe | Kind::Implied) =>
+            {
+                children
+                    .into_iter()
+                    .filter(|child| child.kind() != Kind::Pipe)
+                    .collect()
+            }
+
+            _ => vec![cursor.clone()],
+        };
+
+        let mut arms: Vec<_> = arms.iter().map(|arm| self.arm(arm)).collect();
+
+        if let [arm] = &arms[..] {
+            if arm.arrows.is_empty() {
+                let arm = arms.pop().unwrap();
+                return Lambda::Alternatives(arm.pattern);
+            }
+        }
+
+        for arm in &mut arms {
+            if arm.arrows.is_empty() {
+                let span = arm.pattern.span;
+                let e = self.errors.parse_error(span).expected_equal_arrow();
+                arm.arrows.push(Err((e, span)));
+            }
         }
+
+        let first = opener.unwrap_or(arms[0].pattern.span);
+        let last = arms
+            .last()
+            .and_then(|arm| arm.arrows.last())
+            .map(|part| self.part_span(part))
+            .unwrap_or(first);
+
+        Lambda::Arms(arms, first + last)
     }
 
-    pub(super) fn arrow(
-        &mut self,
-        node: &cst::Thing<'_, 'src>,
-    ) -> (ast::Pattern<'a, 'src>, ast::Expr<'a, 'src>) {
-        let span = node.span;
-        match &node.node {
-            cst::Node::Invalid(e) => {
-                let node = ast::PatternNode::Invalid(*e);
-                let expr = ast::ExprNode::Invalid(*e);
-                let pattern = ast::Pattern { node, span };
-                let expr = ast::Expr { node: expr, span };
-                (pattern, expr)
+    fn arm(&mut self, cursor: &Cursor) -> Arm {
+        let (pattern, arrows) = match cursor.kind() {
+            Kind::Implied => {
+                let mut parts = self.separated(cursor, Kind::EqualArrow).into_iter();
+                let pattern = parts.next().expect("arrows are separated parts");
+                (pattern, parts.collect())
             }
 
-            cst::Node::Arrow(pattern, body) => {
-                let pattern = self.pattern(pattern);
-                let body = self.expr(body);
-                (pattern, body)
+            _ => (self.part(cursor.clone()), Vec::new()),
+        };
+
+        let parts = match &pattern {
+            Ok(cursor) if cursor.kind() == Kind::Disjoined => self.separated(cursor, Kind::Pipe),
+
+            _ => vec![pattern],
+        };
+
+        let span = self.part_span(&parts[0]) + self.part_span(parts.last().unwrap());
+        Arm {
+            pattern: Alternatives { parts, span },
+            arrows,
+        }
+    }
+
+    /// Get the things separated by the tokens of the given kind, reporting
+    /// those which are missing.
+    fn separated(&mut self, cursor: &Cursor, by: Kind) -> Vec<Part> {
+        let mut parts = Vec::new();
+        let mut after = cursor.offset();
+        let mut expected = true;
+
+        for child in children(cursor) {
+            if child.kind() == by {
+                if expected {
+                    parts.push(Err(self.missing(after)));
+                }
+
+                expected = true;
+            } else {
+                parts.push(self.part(child.clone()));
+                expected = false;
             }
 
-            cst::Node::Group(node) => {
-                return self.arrow(node);
+            after = child.range().end;
+        }
+
+        if expected {
+            parts.push(Err(self.missing(after)));
+        }
+
+        parts
+    }
+
+    /// Abstract the arms of a `case`, or of a lambda, along with their span.
+    pub(super) fn cases(
+        &mut self,
+        cursor: &Cursor,
+    ) -> (&'a [(ast::Pattern<'a, 'src>, ast::Expr<'a, 'src>)], Span) {
+        let (arms, span): (Vec<_>, _) = match cursor.kind() {
+            Kind::Disjoined | Kind::Implied => match self.lambda(cursor) {
+                Lambda::Alternatives(alternatives) => {
+                    let arms = alternatives
+                        .parts
+                        .iter()
+                        .map(|part| self.not_an_arm(part))
+                        .collect();
+                    (arms, alternatives.span)
+                }
+
+                Lambda::Arms(arms, span) => {
+                    let arms = arms.into_iter().map(|arm| self.arrow(arm)).collect();
+                    (arms, span)
+                }
+            },
+
+            _ => (
+                vec![self.not_an_arm(&Ok(cursor.clone()))],
+                self.span(cursor),
+            ),
+        };
+
+        (self.alloc.alloc_slice_fill_iter(arms), span)
+    }
+
+    fn arrow(&mut self, arm: Arm) -> (ast::Pattern<'a, 'src>, ast::Expr<'a, 'src>) {
+        let pattern = self.alternatives(arm.pattern);
+        let body = self.arrows(&arm.arrows);
+        (pattern, body)
+    }
+
+    /// Abstract the things after the arrows of an arm, where all but the
+    /// last are the patterns of further lambdas.
+    fn arrows(&mut self, arrows: &[Part]) -> ast::Expr<'a, 'src> {
+        let [pattern, rest @ ..] = arrows else {
+            unreachable!("arms have at least one arrow");
+        };
+
+        if rest.is_empty() {
+            return self.or_invalid(pattern, Self::expr);
+        }
+
+        let span = self.part_span(pattern) + self.part_span(rest.last().unwrap());
+        let pattern = self.or_invalid(pattern, Self::pattern);
+        let body = self.arrows(rest);
+
+        let arms = self.alloc.alloc([(pattern, body)]);
+        let node = ast::ExprNode::Lambda(arms);
+        ast::Expr { node, span }
+    }
+
+    /// Abstract something which should have been an arm.
+    fn not_an_arm(&mut self, part: &Part) -> (ast::Pattern<'a, 'src>, ast::Expr<'a, 'src>) {
+        match part {
+            Ok(cursor) if cursor.kind() == Kind::ParenGroup => {
+                let (inner, _) = self.group(cursor);
+                self.not_an_arm(&inner)
             }
 
-            _ => {
-                let expr = self.expr(node);
+            Ok(cursor) => {
+                let span = self.span(cursor);
+                let expr = self.expr(cursor);
                 let e = self.errors.parse_error(span).expected_case_arm();
                 let node = ast::PatternNode::Invalid(e);
                 (ast::Pattern { node, span }, expr)
             }
+
+            Err((e, span)) => {
+                let pattern = ast::Pattern {
+                    node: ast::PatternNode::Invalid(*e),
+                    span: *span,
+                };
+
+                let expr = ast::Expr {
+                    node: ast::ExprNode::Invalid(*e),
+                    span: *span,
+                };
+
+                (pattern, expr)
+            }
+        }
+    }
+
+    /// Abstract alternatives as an or-pattern.
+    pub(super) fn alternatives(&mut self, alternatives: Alternatives) -> ast::Pattern<'a, 'src> {
+        let mut result: Option<ast::Pattern> = None;
+
+        for part in &alternatives.parts {
+            let pattern = self.or_invalid(part, Self::pattern);
+            result = Some(match result {
+                None => pattern,
+                Some(prev) => {
+                    let span = prev.span + pattern.span;
+                    let terms = self.alloc.alloc([prev, pattern]);
+                    let node = ast::PatternNode::Or(terms);
+                    ast::Pattern { node, span }
+                }
+            });
+        }
+
+        result.expect("alternatives contain at least one part")
+    }
+}
+
+impl Lambda {
+    pub(super) fn span(&self) -> Span {
+        match self {
+            Lambda::Alternatives(alternatives) => alternatives.span,
+            Lambda::Arms(_, span) => *span,
+        }
+    }
+
+    /// Get every part of the lambda.
+    pub(super) fn into_parts(self) -> Vec<Part> {
+        match self {
+            Lambda::Alternatives(alternatives) => alternatives.parts,
+            Lambda::Arms(arms, _) => arms
+                .into_iter()
+                .flat_map(|arm| arm.pattern.parts.into_iter().chain(arm.arrows))
+                .collect(),
         }
     }
 }
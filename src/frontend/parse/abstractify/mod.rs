@@ -1,28 +1,61 @@
-//! The parser converts a stream of tokens into a bunch of largely unstructured
-//! trees. The job of the abstractifier is to then give some more structure to
-//! those trees, inserting the difference between items, expressions, patterns,
+//! The abstractifier reads the lossless syntax tree and gives it some more
+//! structure, inserting the difference between items, expressions, patterns,
 //! and so on.
+//!
+//! The green parser never reports anything. It marks whatever it expected but
+//! did not find as missing, and wraps whatever it skipped over while
+//! recovering in error nodes. The diagnostics for these are reported here,
+//! each on its own, so that a single syntax error does not invalidate the
+//! entire thing it occurs in. A thing which is invalid where it is found is
+//! not abstracted any further, but the syntax errors within it are still
+//! reported.
 
 mod expr;
 mod items;
 mod lambda;
+mod parts;
 mod pattern;
 mod types;
 
+use std::ops::Range;
+
 use bumpalo::Bump;
 
-use super::cst;
 use crate::frontend::errors::{ErrorId, Errors};
 use crate::frontend::names::{Ident, Names};
-use crate::frontend::source::Span;
+use crate::frontend::source::{Source, SourceId, Span};
 use crate::frontend::trees::parsed as ast;
+use crate::syntax::green::Kind;
+use crate::syntax::red::Cursor;
 
 pub struct Abstractifier<'a, 'src, 'err> {
     alloc: &'a Bump,
     names: &'a Names<'src>,
     errors: &'err mut Errors,
 
-    parse_errors: Vec<(ErrorId, Span)>,
+    text: &'src str,
+    source: SourceId,
+
+    /// The ranges of every token which is not skipped, in order.
+    tokens: Vec<Range<usize>>,
+
+    /// Errors which are not part of any node, along with the offset of the
+    /// token after them. They become invalid items after the item they are
+    /// found in.
+    pending: Vec<(usize, ErrorId, Span)>,
+
+    /// Errors which are neither part of any node nor turned into items.
+    unattached: Vec<(ErrorId, Span)>,
+}
+
+/// A part of the tree which should be there, or the error reported for it
+/// being missing along with the span it is reported at.
+type Part = Result<Cursor, (ErrorId, Span)>;
+
+/// Nodes of the parsed tree which can stand in for a part of the tree which
+/// is missing.
+trait Invalid {
+    fn invalid(e: ErrorId, span: Span) -> Self;
 }
 
 impl<'a, 'src, 'err> Abstractifier<'a, 'src, 'err> {
@@ -30,40 +63,158 @@ impl<'a, 'src, 'err> Abstractifier<'a, 'src, 'err> {
         alloc: &'a Bump,
         names: &'a Names<'src>,
         errors: &'err mut Errors,
-        parse_errors: Vec<(ErrorId, Span)>,
+        source: &'src Source,
     ) -> Self {
         Self {
             alloc,
             names,
             errors,
-            parse_errors,
+            text: &source.content,
+            source: source.id,
+            tokens: Vec::new(),
+            pending: Vec::new(),
+            unattached: Vec::new(),
+        }
+    }
+
+    /// Abstract an entire source tree.
+    pub fn program(mut self, root: &Cursor) -> (&'a [ast::Item<'a, 'src>], Vec<(ErrorId, Span)>) {
+        self.tokens = tokens(root);
+
+        let mut items = bumpalo::collections::Vec::new_in(self.alloc);
+        let mut skipped = None;
+        let mut erred = false;
+
+        let mut children = root.children().peekable();
+        while let Some(child) = children.next() {
+            self.unrecognized(&child, &mut erred);
+
+            if is_absent(&child) {
+                continue;
+            }
+
+            if !starts_thing(&child) {
+                skipped.get_or_insert(self.extent(&child));
+                continue;
+            }
+
+            if let Some(span) = skipped.take() {
+                self.unexpected_item(&mut items, span);
+            }
+
+            // Anything skipped right after a thing belongs to it
+            while let Some(skipped) = children.next_if(|child| child.kind().is_skipped()) {
+                self.unrecognized(&skipped, &mut erred);
+            }
+
+            self.skipped(&child);
+            self.item(&mut items, &child);
+            self.drain_errors(&mut items);
         }
+
+        if let Some(span) = skipped {
+            self.unexpected_item(&mut items, span);
+            self.drain_errors(&mut items);
+        }
+
+        let mut unattached: Vec<_> = self
+            .pending
+            .into_iter()
+            .map(|(_, e, span)| (e, span))
+            .collect();
+        unattached.extend(self.unattached);
+
+        items.shrink_to_fit();
+        (items.into_bump_slice(), unattached)
+    }
+
+    fn unexpected_item(
+        &mut self,
+        into: &mut bumpalo::collections::Vec<ast::Item<'a, 'src>>,
+        span: Span,
+    ) {
+        let e = self.errors.parse_error(span).unexpected_token();
+        let node = ast::ItemNode::Invalid(e);
+        into.push(ast::Item {
+            node,
+            span,
+            doc: None,
+            method_docs: &[],
+        });
     }
 
-    pub fn program(
-        mut self,
-        items: Vec<&cst::Thing<'_, 'src>>,
-    ) -> (&'a [ast::Item<'a, 'src>], Vec<(ErrorId, Span)>) {
-        let mut into = bumpalo::collections::Vec::with_capacity_in(items.len(), self.alloc);
+    /// Turn the errors reported outside of any node into invalid items.
+    fn drain_errors(&mut self, into: &mut bumpalo::collections::Vec<ast::Item<'a, 'src>>) {
+        self.pending.sort_by_key(|(at, ..)| *at);
+        for (_, e, span) in self.pending.drain(..).rev() {
+            let node = ast::ItemNode::Invalid(e);
+            into.push(ast::Item {
+                node,
+                span,
+                doc: None,
+                method_docs: &[],
+            });
+        }
+    }
 
-        for node in items {
-            self.item(&mut into, node);
+    /// Report the tokens within `cursor` which the lexer did not recognize,
+    /// once for every run of them not interrupted by other tokens.
+    fn unrecognized(&mut self, cursor: &Cursor, erred: &mut bool) {
+        if !cursor.is_token() {
+            for child in cursor.children() {
+                self.unrecognized(&child, erred);
+            }
+        } else if cursor.kind() == Kind::Invalid {
+            if !*erred {
+                let span = cursor.span(self.source);
+                let e = self.errors.parse_error(span).unexpected_token();
+                self.pending.push((cursor.offset(), e, span));
+                *erred = true;
+            }
+        } else if !cursor.kind().is_skipped() {
+            *erred = false;
         }
+    }
 
-        into.shrink_to_fit();
-        (into.into_bump_slice(), self.parse_errors)
+    /// Report the tokens within `cursor` which the green parser skipped over
+    /// while recovering, once for every error node.
+    fn skipped(&mut self, cursor: &Cursor) {
+        if cursor.kind() == Kind::Error {
+            let span = self.extent(cursor);
+            let e = self.errors.parse_error(span).unexpected_token();
+            self.pending.push((cursor.offset(), e, span));
+        } else {
+            for child in cursor.children() {
+                self.skipped(&child);
+            }
+        }
     }
 
-    fn normal_name(
+    /// Abstract a part of the tree with `f`, or stand in for it with an
+    /// invalid node if it is missing.
+    fn or_invalid<T: Invalid>(
         &mut self,
-        thing: &cst::Thing<'_, 'src>,
-    ) -> (Result<Ident<'src>, ErrorId>, Span) {
-        let span = thing.span;
-        let ident = match thing.node {
-            cst::Node::Invalid(e) => Err(e),
-            cst::Node::Name(cst::Name::Normal(name)) => Ok(self.names.intern(name)),
-
-            cst::Node::Name(cst::Name::Universal(name)) => Err(self
+        part: &Part,
+        f: impl FnOnce(&mut Self, &Cursor) -> T,
+    ) -> T {
+        match part {
+            Ok(cursor) => f(self, cursor),
+            Err((e, span)) => T::invalid(*e, *span),
+        }
+    }
+
+    fn normal_name(&mut self, part: &Part) -> (Result<Ident<'src>, ErrorId>, Span) {
+        let cursor = match part {
+            Ok(cursor) => cursor,
+            Err((e, span)) => return (Err(*e), *span),
+        };
+
+        let span = self.span(cursor);
+        let name = self.text(cursor);
+        let ident = match cursor.kind() {
+            Kind::Name | Kind::PostTick => Ok(self.names.intern(name)),
+
+            Kind::PreTick => Err(self
                 .errors
                 .parse_error(span)
                 .expected_non_universal_name(name)),
@@ -73,4 +224,237 @@ impl<'a, 'src, 'err> Abstractifier<'a, 'src, 'err> {
 
         (ident, span)
     }
+
+    /// Report the syntax errors within a thing which is invalid where it is
+    /// found. A qualified thing with something other than a name after a dot
+    /// is invalid anywhere, and the error reported for it is returned so that
+    /// it can stand in for the thing.
+    fn misplaced(&mut self, cursor: &Cursor) -> Option<(ErrorId, Span)> {
+        if cursor.kind() != Kind::Qualified {
+            self.syntax_errors(cursor);
+            return None;
+        }
+
+        match self.qualified(cursor) {
+            Ok((base, _)) => {
+                self.part_errors(&base);
+                None
+            }
+
+            Err(invalid) => Some(invalid),
+        }
+    }
+
+    /// Report the syntax errors within a part which is not abstracted.
+    fn part_errors(&mut self, part: &Part) {
+        if let Ok(cursor) = part {
+            self.syntax_errors(cursor);
+        }
+    }
+
+    /// Get the affix denoted by the keyword `cursor`, or `None` if it is not
+    /// an affix keyword. Infix keywords may be followed by a precedence, in
+    /// which case `next` is used and the returned boolean is `true`.
+    fn affix(&mut self, cursor: &Cursor, next: Option<&Cursor>) -> Option<(ast::Affix, bool)> {
+        let assoc = match cursor.kind() {
+            Kind::Infix => ast::Associativity::None,
+            Kind::Infixl => ast::Associativity::Left,
+            Kind::Infixr => ast::Associativity::Right,
+            Kind::Postfix => return Some((ast::Affix::Postfix, false)),
+            _ => return None,
+        };
+
+        let (precedence, consumed) = match next {
+            Some(next) if next.kind() == Kind::Number => {
+                let span = next.span(self.source);
+                (self.precedence(self.text(next), span), true)
+            }
+
+            _ => (ast::Fixity::DEFAULT_PRECEDENCE, false),
+        };
+
+        let fixity = ast::Fixity { assoc, precedence };
+        Some((ast::Affix::Infix(fixity), consumed))
+    }
+
+    fn precedence(&mut self, lit: &str, span: Span) -> u8 {
+        match lit.replace('_', "").parse() {
+            Ok(precedence) if precedence <= ast::Fixity::MAX_PRECEDENCE => precedence,
+            _ => {
+                let e = self
+                    .errors
+                    .parse_error(span)
+                    .invalid_precedence(ast::Fixity::MAX_PRECEDENCE);
+                self.unattached.push((e, span));
+                ast::Fixity::DEFAULT_PRECEDENCE
+            }
+        }
+    }
+
+    /// Take a child which may be missing, reporting it if it is.
+    fn part(&mut self, cursor: Cursor) -> Part {
+        if cursor.kind() == Kind::Missing {
+            Err(self.missing(cursor.offset()))
+        } else {
+            Ok(cursor)
+        }
+    }
+
+    /// Report a missing part which should have come after `offset`.
+    fn missing(&mut self, offset: usize) -> (ErrorId, Span) {
+        let span = self.closest(offset);
+        let e = self.errors.parse_error(span).unexpected_token();
+        (e, span)
+    }
+
+    fn text(&self, cursor: &Cursor) -> &'src str {
+        &self.text[cursor.range()]
+    }
+
+    fn part_span(&self, part: &Part) -> Span {
+        match part {
+            Ok(cursor) => self.span(cursor),
+            Err((_, span)) => *span,
+        }
+    }
+
+    /// Get the span of a thing, from its first token to its last. Definitions
+    /// are taken to extend up to the token after them.
+    fn span(&self, cursor: &Cursor) -> Span {
+        let mut first = cursor.clone();
+        while let Some(child) = significant(&first).into_iter().next() {
+            first = child;
+        }
+
+        if !first.is_token() {
+            return self.closest(cursor.offset());
+        }
+
+        let mut last = cursor.clone();
+        loop {
+            if matches!(last.kind(), Kind::Scoped | Kind::DefinitionGroup) {
+                return first.span(self.source) + self.closest(last.range().end);
+            }
+
+            match significant(&last).pop() {
+                Some(child) => last = child,
+                None => break,
+            }
+        }
+
+        self.source.span(first.offset(), last.range().end)
+    }
+
+    /// Get the span from the first to the last token within `cursor` which is
+    /// not skipped.
+    fn extent(&self, cursor: &Cursor) -> Span {
+        let tokens = tokens(cursor);
+        match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => self.source.span(first.start, last.end),
+            _ => self.closest(cursor.offset()),
+        }
+    }
+
+    /// Get the offset of the first token at or after `offset`, or the end of
+    /// the source if there is none.
+    fn lookahead(&self, offset: usize) -> usize {
+        let index = self.tokens.partition_point(|range| range.start < offset);
+        self.tokens
+            .get(index)
+            .map_or(self.text.len(), |range| range.start)
+    }
+
+    /// Get the span of the first token at or after `offset`, or of the last
+    /// token if there is none.
+    fn closest(&self, offset: usize) -> Span {
+        let index = self.tokens.partition_point(|range| range.start < offset);
+        self.tokens
+            .get(index)
+            .or(self.tokens.last())
+            .map(|range| self.source.span(range.start, range.end))
+            .unwrap_or_else(|| self.source.span(0, 0))
+    }
+}
+
+impl<'a, 'src> Invalid for ast::Expr<'a, 'src> {
+    fn invalid(e: ErrorId, span: Span) -> Self {
+        let node = ast::ExprNode::Invalid(e);
+        Self { node, span }
+    }
+}
+
+impl<'a, 'src> Invalid for ast::Pattern<'a, 'src> {
+    fn invalid(e: ErrorId, span: Span) -> Self {
+        let node = ast::PatternNode::Invalid(e);
+        Self { node, span }
+    }
+}
+
+impl<'a, 'src> Invalid for ast::Type<'a, 'src> {
+    fn invalid(e: ErrorId, span: Span) -> Self {
+        let node = ast::TypeNode::Invalid(e);
+        Self { node, span }
+    }
+}
+
+/// Get the children of `cursor` which are neither trivia nor skipped over by
+/// the green parser. Missing children are included.
+fn children(cursor: &Cursor) -> Vec<Cursor> {
+    cursor
+        .children()
+        .filter(|child| !child.kind().is_skipped() && child.kind() != Kind::Error)
+        .collect()
+}
+
+/// Get the children of `cursor` which are not trivia, missing, or skipped
+/// over by the green parser.
+fn significant(cursor: &Cursor) -> Vec<Cursor> {
+    cursor
+        .children()
+        .filter(|child| !is_absent(child) && child.kind() != Kind::Error)
+        .collect()
+}
+
+/// Returns `true` if `cursor` is trivia or a missing node, neither of which
+/// has any tokens which matter.
+fn is_absent(cursor: &Cursor) -> bool {
+    cursor.kind().is_skipped() || cursor.kind() == Kind::Missing
+}
+
+/// Get the ranges of every token within `cursor` which is not skipped.
+fn tokens(cursor: &Cursor) -> Vec<Range<usize>> {
+    if cursor.is_token() {
+        return if cursor.kind().is_skipped() {
+            Vec::new()
+        } else {
+            vec![cursor.range()]
+        };
+    }
+
+    cursor.children().flat_map(|child| tokens(&child)).collect()
+}
+
+fn starts_thing(cursor: &Cursor) -> bool {
+    (!cursor.is_token() && cursor.kind() != Kind::Error)
+        || matches!(
+            cursor.kind(),
+            Kind::Name
+                | Kind::PreTick
+                | Kind::PostTick
+                | Kind::Number
+                | Kind::Underscore
+                | Kind::Ellipses
+                | Kind::Infix
+                | Kind::Infixl
+                | Kind::Infixr
+                | Kind::Postfix
+                | Kind::LeftParen
+                | Kind::LeftBrace
+                | Kind::Pipe
+                | Kind::Case
+        )
+}
+
+fn is_name(cursor: &Cursor) -> bool {
+    matches!(cursor.kind(), Kind::Name | Kind::PreTick | Kind::PostTick)
 }
@@ -0,0 +1,400 @@
+//! Reading the parts of the nodes of the syntax tree which are shared between
+//! items, expressions, patterns and types. Missing parts are reported as they
+//! are read, so every node is read at most once.
+
+use super::{children, is_name, Abstractifier, Part};
+use crate::frontend::errors::ErrorId;
+use crate::frontend::source::Span;
+use crate::syntax::ast::{self as view, AstNode};
+use crate::syntax::green::Kind;
+use crate::syntax::red::Cursor;
+
+/// A group of definitions like `let a = x and b = y`, possibly followed by
+/// `in thing`.
+pub(super) struct Definitions<'a> {
+    /// The keyword introducing the group, such as `let` or `data`.
+    pub keyword: Kind,
+    pub span: Span,
+    pub definitions: Vec<Definition<'a>>,
+
+    /// The thing after the `in`, if any.
+    pub within: Option<Part>,
+}
+
+/// A single definition, like `pattern = thing`.
+pub(super) struct Definition<'a> {
+    /// The span of the whole definition.
+    pub span: Span,
+
+    /// The thing before the `=`.
+    pub pattern: Part,
+
+    /// The thing after the `=`, or `None` if there is no `=`.
+    pub body: Option<Part>,
+
+    /// The text of the documentation comments before the definition.
+    pub doc: Option<&'a str>,
+}
+
+/// The parts of a `case`.
+pub(super) struct Case {
+    pub span: Span,
+    pub scrutinee: Option<Cursor>,
+
+    /// The arms of the case, or where they should be if there are none.
+    pub arms: Result<Cursor, Span>,
+}
+
+impl<'a> Abstractifier<'a, '_, '_> {
+    /// ```abnf
+    /// definitions = keyword def *("and" def) ["in" thing]
+    /// ```
+    pub(super) fn definitions(&mut self, cursor: &Cursor) -> Definitions<'a> {
+        let (group, within) = match cursor.kind() {
+            Kind::Scoped => {
+                let mut children = children(cursor).into_iter();
+                let group = children
+                    .next()
+                    .expect("scoped things start with definitions");
+                let within = children.next().map(|kw| match children.next() {
+                    Some(thing) => self.part(thing),
+                    None => Err(self.missing(kw.range().end)),
+                });
+
+                (group, within)
+            }
+
+            _ => (cursor.clone(), None),
+        };
+
+        let mut children = children(&group).into_iter();
+        let keyword = children
+            .next()
+            .expect("definition groups start with a keyword");
+        let opener = keyword.span(self.source);
+
+        let mut definitions = Vec::new();
+        let mut intro = opener;
+
+        while let Some(def) = children.next() {
+            definitions.push(self.definition(&def, Some(intro)));
+
+            match children.next() {
+                Some(and) => intro = and.span(self.source),
+                None => break,
+            }
+        }
+
+        Definitions {
+            keyword: keyword.kind(),
+            span: opener + self.closest(cursor.range().end),
+            definitions,
+            within,
+        }
+    }
+
+    /// ```abnf
+    /// def = conjoined ["=" thing]
+    /// ```
+    fn definition(&mut self, cursor: &Cursor, opener: Option<Span>) -> Definition<'a> {
+        let mut children = children(cursor).into_iter().peekable();
+
+        let pattern = match children.next_if(|child| child.kind() != Kind::Equal) {
+            Some(pattern) => self.part(pattern),
+            None => Err(self.missing(cursor.offset())),
+        };
+
+        let body = children.next().map(|equal| match children.next() {
+            Some(thing) => self.part(thing),
+            None => Err(self.missing(equal.range().end)),
+        });
+
+        let doc = view::Definition::cast(cursor.clone())
+            .and_then(|def| def.doc())
+            .map(|doc| &*self.alloc.alloc_str(&doc));
+
+        let start = opener.unwrap_or_else(|| self.part_span(&pattern));
+        Definition {
+            span: start + self.closest(cursor.range().end),
+            pattern,
+            body,
+            doc,
+        }
+    }
+
+    /// ```abnf
+    /// paren-group = "(" thing ")"
+    /// ```
+    ///
+    /// Returns the thing in the group along with the span of the group.
+    pub(super) fn group(&mut self, cursor: &Cursor) -> (Part, Span) {
+        let children = children(cursor);
+
+        let opener = children[0].span(self.source);
+        let inner = match children.get(1) {
+            Some(thing) if thing.kind() != Kind::RightParen => self.part(thing.clone()),
+            _ => Err(self.missing(children[0].range().end)),
+        };
+
+        let closer = children
+            .last()
+            .filter(|closer| closer.kind() == Kind::RightParen);
+
+        let span = if let Some(closer) = closer {
+            opener + closer.span(self.source)
+        } else {
+            let span = self.closest(cursor.range().end);
+            let e = self.errors.parse_error(opener).unclosed_paren(span);
+            self.pending
+                .push((self.lookahead(cursor.range().end), e, span));
+            opener + self.part_span(&inner)
+        };
+
+        (inner, span)
+    }
+
+    /// ```abnf
+    /// brace-group = "{" *(def ",") [def] "}"
+    /// ```
+    ///
+    /// Returns the definitions in the group along with the span of the group.
+    pub(super) fn record(&mut self, cursor: &Cursor) -> (Vec<Definition<'a>>, Span) {
+        let children = children(cursor);
+
+        let opener = children[0].span(self.source);
+        let end = children
+            .last()
+            .filter(|closer| closer.kind() == Kind::RightBrace)
+            .map(|closer| closer.span(self.source));
+
+        let mut definitions = Vec::new();
+        let mut expected_comma = None;
+
+        for child in &children[1..] {
+            match child.kind() {
+                Kind::Comma => expected_comma = None,
+                Kind::Definition => {
+                    if let Some(span) = expected_comma.take() {
+                        let e = self.errors.parse_error(span).expected_comma();
+                        self.pending.push((child.offset(), e, span));
+                    }
+
+                    definitions.push(self.definition(child, None));
+                    expected_comma = Some(self.closest(child.range().end));
+                }
+
+                _ => {}
+            }
+        }
+
+        let span = if let Some(end) = end {
+            opener + end
+        } else {
+            let span = self.closest(cursor.range().end);
+            let e = self.errors.parse_error(opener).unclosed_brace(span);
+            self.pending
+                .push((self.lookahead(cursor.range().end), e, span));
+            definitions.last().map_or(opener, |def| opener + def.span)
+        };
+
+        (definitions, span)
+    }
+
+    /// ```abnf
+    /// case = "case" [conjoined] [arrows] "end"
+    /// ```
+    pub(super) fn case(&mut self, cursor: &Cursor) -> Case {
+        let children = children(cursor);
+
+        let (kw, mut rest) = children
+            .split_first()
+            .expect("case groups start with `case`");
+        let opener = kw.span(self.source);
+
+        let end = match rest.split_last() {
+            Some((end, init)) if end.kind() == Kind::End => {
+                rest = init;
+                Some(end.span(self.source))
+            }
+
+            Some((missing, init)) if missing.kind() == Kind::Missing => {
+                rest = init;
+                None
+            }
+
+            _ => None,
+        };
+
+        let after = rest.last().unwrap_or(kw).range().end;
+
+        let (scrutinee, arms) = match rest {
+            [] => (None, None),
+            [arms] if starts_with_pipe(arms) => (None, Some(arms)),
+            [scrutinee] => (Some(scrutinee), None),
+            [scrutinee, arms, ..] => (Some(scrutinee), Some(arms)),
+        };
+
+        let end = end.unwrap_or_else(|| {
+            let span = self.closest(after);
+            let e = self.errors.parse_error(opener).missing_end("case", span);
+            self.pending.push((self.lookahead(after), e, span));
+            span
+        });
+
+        Case {
+            span: opener + end,
+            scrutinee: scrutinee.cloned(),
+            arms: arms.cloned().ok_or_else(|| self.closest(after)),
+        }
+    }
+
+    /// ```abnf
+    /// conjoined = anno *("&" anno)
+    /// ```
+    pub(super) fn conjoined(&mut self, cursor: &Cursor) -> Vec<Part> {
+        let mut children = children(cursor).into_iter().peekable();
+        let mut operands = Vec::new();
+
+        operands.push(
+            match children.next_if(|child| child.kind() != Kind::Ampersand) {
+                Some(first) => self.part(first),
+                None => Err(self.missing(cursor.offset())),
+            },
+        );
+
+        while let Some(ampersand) = children.next() {
+            operands.push(
+                match children.next_if(|child| child.kind() != Kind::Ampersand) {
+                    Some(rhs) => self.part(rhs),
+                    None => Err(self.missing(ampersand.range().end)),
+                },
+            );
+        }
+
+        operands
+    }
+
+    /// ```abnf
+    /// anno = apply [":" apply]
+    /// ```
+    pub(super) fn annotation(&mut self, cursor: &Cursor) -> (Part, Part) {
+        let mut children = children(cursor).into_iter().peekable();
+
+        let thing = match children.next_if(|child| child.kind() != Kind::Colon) {
+            Some(thing) => self.part(thing),
+            None => Err(self.missing(cursor.offset())),
+        };
+
+        let colon = children.next().expect("annotations have a colon");
+        let annotation = match children.next() {
+            Some(annotation) => self.part(annotation),
+            None => Err(self.missing(colon.range().end)),
+        };
+
+        (thing, annotation)
+    }
+
+    /// ```abnf
+    /// qual = atom *("." name)
+    /// ```
+    ///
+    /// The green tree allows any atom after a dot. Anything other than a name
+    /// is reported, and the thing is not abstracted any further.
+    pub(super) fn qualified(
+        &mut self,
+        cursor: &Cursor,
+    ) -> Result<(Part, Vec<Cursor>), (ErrorId, Span)> {
+        let mut children = children(cursor).into_iter().peekable();
+
+        let base = match children.next_if(|child| child.kind() != Kind::Dot) {
+            Some(base) => self.part(base),
+            None => Err(self.missing(cursor.offset())),
+        };
+
+        let mut fields = Vec::new();
+
+        while let Some(dot) = children.next() {
+            let Some(name) = children.next_if(is_name) else {
+                let span = self.closest(dot.range().end);
+                let e = self.errors.parse_error(span).expected_name();
+
+                if let Ok(base) = &base {
+                    self.syntax_errors(base);
+                }
+
+                for child in children.filter(|child| child.kind() != Kind::Dot) {
+                    if let Ok(child) = self.part(child) {
+                        self.syntax_errors(&child);
+                    }
+                }
+
+                return Err((e, span));
+            };
+
+            fields.push(name);
+        }
+
+        Ok((base, fields))
+    }
+
+    /// Report the syntax errors within a thing which is not abstracted, since
+    /// it is invalid where it is found.
+    pub(super) fn syntax_errors(&mut self, cursor: &Cursor) {
+        let mut parts = Vec::new();
+
+        match cursor.kind() {
+            Kind::ParenGroup => parts.push(self.group(cursor).0),
+
+            Kind::BraceGroup => {
+                for def in self.record(cursor).0 {
+                    parts.push(def.pattern);
+                    parts.extend(def.body);
+                }
+            }
+
+            Kind::CaseGroup => {
+                let case = self.case(cursor);
+                parts.extend(case.scrutinee.map(Ok));
+                parts.extend(case.arms.ok().map(Ok));
+            }
+
+            Kind::Scoped | Kind::DefinitionGroup => {
+                let definitions = self.definitions(cursor);
+                for def in definitions.definitions {
+                    parts.push(def.pattern);
+                    parts.extend(def.body);
+                }
+
+                parts.extend(definitions.within);
+            }
+
+            Kind::Disjoined | Kind::Implied => parts = self.lambda(cursor).into_parts(),
+            Kind::Conjoined => parts = self.conjoined(cursor),
+
+            Kind::Annotate => {
+                let (thing, annotation) = self.annotation(cursor);
+                parts.extend([thing, annotation]);
+            }
+
+            Kind::Apply => parts.extend(children(cursor).into_iter().map(Ok)),
+
+            Kind::Qualified => {
+                if let Ok((base, _)) = self.qualified(cursor) {
+                    parts.push(base);
+                }
+            }
+
+            _ => {}
+        }
+
+        for part in parts.iter().flatten() {
+            self.syntax_errors(part);
+        }
+    }
+}
+
+fn starts_with_pipe(cursor: &Cursor) -> bool {
+    cursor
+        .first_token()
+        .is_some_and(|token| token.kind() == Kind::Pipe)
+}
@@ -1,44 +1,59 @@
-use super::Abstractifier;
-use crate::frontend::parse::cst;
+use super::lambda::Lambda;
+use super::parts::Definition;
+use super::{children, Abstractifier, Part};
+use crate::frontend::errors::ErrorId;
+use crate::frontend::names::Label;
+use crate::frontend::source::Span;
 use crate::frontend::trees::parsed as ast;
+use crate::syntax::green::Kind;
+use crate::syntax::red::Cursor;
 
 impl<'a, 'src> Abstractifier<'a, 'src, '_> {
     /// Abstract an applied sequence of patterns. A resulting slice with a
     /// length longer than 1 does _not_ mean that this is a function definition.
     /// Any type annotations "outside" the spine are collected and returned
     /// separately.
-    pub fn pattern(&mut self, node: &cst::Thing<'_, 'src>) -> ast::Pattern<'a, 'src> {
-        let span = node.span;
-        let node = match &node.node {
-            cst::Node::Invalid(e) => ast::PatternNode::Invalid(*e),
-            cst::Node::Wildcard => ast::PatternNode::Wildcard,
+    pub fn pattern(&mut self, cursor: &Cursor) -> ast::Pattern<'a, 'src> {
+        let span = self.span(cursor);
+        let node = match cursor.kind() {
+            Kind::Underscore => ast::PatternNode::Wildcard,
 
-            cst::Node::Name(_) => return self.affixed_name(ast::Affix::Prefix, node),
+            Kind::Name | Kind::PreTick | Kind::PostTick => {
+                return self.affixed_name(ast::Affix::Prefix, &Ok(cursor.clone()))
+            }
 
-            cst::Node::Anno(pat, ty) => {
-                let pat = self.alloc.alloc(self.pattern(pat));
-                let ty = self.ty(ty);
+            Kind::Annotate => {
+                let (pat, ty) = self.annotation(cursor);
+                let pat = self.or_invalid(&pat, Self::pattern);
+                let pat = self.alloc.alloc(pat);
+                let ty = self.or_invalid(&ty, Self::ty);
                 ast::PatternNode::Anno(pat, ty)
             }
 
-            cst::Node::Apply(terms) => {
+            Kind::Apply => {
+                let terms = children(cursor);
                 let mut nodes = Vec::with_capacity(terms.len());
-                let mut affix = None;
+                let mut terms = &terms[..];
 
-                for node in terms {
-                    if let Some((_, affix)) = affix.take() {
-                        nodes.push(self.affixed_name(affix, node));
-                    } else {
-                        match &node.node {
-                            cst::Node::Infix => affix = Some((node, ast::Affix::Infix)),
-                            cst::Node::Postfix => affix = Some((node, ast::Affix::Postfix)),
-                            _ => nodes.push(self.pattern(node)),
-                        }
+                while let [cursor, rest @ ..] = terms {
+                    terms = rest;
+
+                    let next = rest.first();
+                    let Some((affix, consumed)) = self.affix(cursor, next) else {
+                        nodes.push(self.pattern(cursor));
+                        continue;
+                    };
+
+                    if consumed {
+                        terms = &terms[1..];
                     }
-                }
 
-                if let Some((node, _)) = affix {
-                    nodes.push(self.pattern(node));
+                    if let [name, rest @ ..] = terms {
+                        terms = rest;
+                        nodes.push(self.affixed_name(affix, &Ok(name.clone())));
+                    } else {
+                        nodes.push(self.pattern(cursor));
+                    }
                 }
 
                 if nodes.len() == 1 {
@@ -49,38 +64,60 @@ impl<'a, 'src> Abstractifier<'a, 'src, '_> {
                 ast::PatternNode::Apply(terms)
             }
 
-            cst::Node::Alt(terms) => {
-                let mut result = None;
-
-                for term in terms {
-                    let pattern = self.pattern(term);
-                    match result {
-                        None => result = Some(pattern),
-                        Some(prev) => {
-                            let span = prev.span + pattern.span;
-                            let terms = self.alloc.alloc([prev, pattern]);
-                            let node = ast::PatternNode::Or(terms);
-                            result = Some(ast::Pattern { node, span });
-                        }
+            Kind::Disjoined | Kind::Implied => match self.lambda(cursor) {
+                Lambda::Alternatives(alternatives) => return self.alternatives(alternatives),
+                arms => {
+                    let span = arms.span();
+                    for part in &arms.into_parts() {
+                        self.part_errors(part);
                     }
+
+                    let e = self.errors.parse_error(span).expected_pattern();
+                    let node = ast::PatternNode::Invalid(e);
+                    return ast::Pattern { node, span };
+                }
+            },
+
+            Kind::Conjoined => {
+                let mut operands = self.conjoined(cursor).into_iter();
+                let first = operands.next().expect("conjoined things are non-empty");
+                let mut pattern = self.or_invalid(&first, Self::pattern);
+
+                for operand in operands {
+                    let rhs = self.or_invalid(&operand, Self::pattern);
+                    let span = pattern.span + rhs.span;
+                    let terms = self.alloc.alloc([pattern, rhs]);
+                    let node = ast::PatternNode::And(terms);
+                    pattern = ast::Pattern { node, span };
                 }
 
-                return result.expect("alts contain at least one subterm");
+                return pattern;
             }
 
-            cst::Node::And(lhs, rhs) => {
-                let lhs = self.pattern(lhs);
-                let rhs = self.pattern(rhs);
-                let terms = self.alloc.alloc([lhs, rhs]);
-                ast::PatternNode::And(terms)
+            Kind::ParenGroup => {
+                let (pattern, span) = self.group(cursor);
+                let pattern = self.or_invalid(&pattern, Self::pattern);
+                let node = ast::PatternNode::Group(self.alloc.alloc(pattern));
+                return ast::Pattern { node, span };
             }
 
-            cst::Node::Group(pattern) => {
-                let pattern = self.alloc.alloc(self.pattern(pattern));
-                ast::PatternNode::Group(pattern)
+            Kind::BraceGroup => {
+                let (definitions, span) = self.record(cursor);
+                let fields: Vec<_> = definitions
+                    .iter()
+                    .map(|def| self.record_field_pattern(def))
+                    .collect();
+                let fields = self.alloc.alloc_slice_fill_iter(fields);
+                let node = ast::PatternNode::Record(fields);
+                return ast::Pattern { node, span };
             }
 
             _ => {
+                if let Some((e, span)) = self.misplaced(cursor) {
+                    let node = ast::PatternNode::Invalid(e);
+                    return ast::Pattern { node, span };
+                }
+
                 let e = self.errors.parse_error(span).expected_pattern();
                 ast::PatternNode::Invalid(e)
             }
@@ -89,21 +126,47 @@ impl<'a, 'src> Abstractifier<'a, 'src, '_> {
         ast::Pattern { node, span }
     }
 
-    fn affixed_name(
+    /// Abstract a record pattern field like `a = x` or `a`, where the latter is
+    /// expanded to `a = a`.
+    fn record_field_pattern(
+        &mut self,
+        def: &Definition,
+    ) -> (Result<Label<'src>, ErrorId>, Span, ast::Pattern<'a, 'src>) {
+        let (name, name_span) = self.normal_name(&def.pattern);
+        let name = name.map(Label);
+
+        let pattern = if let Some(pattern) = &def.body {
+            self.part_errors(&def.pattern);
+            self.or_invalid(pattern, Self::pattern)
+        } else {
+            self.or_invalid(&def.pattern, Self::pattern)
+        };
+
+        (name, name_span, pattern)
+    }
+
+    pub(super) fn affixed_name(
         &mut self,
         affix: ast::Affix,
-        suspected_name: &cst::Thing<'_, 'src>,
+        suspected_name: &Part,
     ) -> ast::Pattern<'a, 'src> {
-        let span = suspected_name.span;
-        let node = match &suspected_name.node {
-            cst::Node::Invalid(e) => ast::PatternNode::Invalid(*e),
+        let cursor = match suspected_name {
+            Ok(cursor) => cursor,
+            Err((e, span)) => {
+                let node = ast::PatternNode::Invalid(*e);
+                return ast::Pattern { node, span: *span };
+            }
+        };
 
-            cst::Node::Name(cst::Name::Normal(name)) => {
-                let name = self.names.intern(name);
+        let span = self.span(cursor);
+        let node = match cursor.kind() {
+            Kind::Name | Kind::PostTick => {
+                let name = self.names.intern(self.text(cursor));
                 ast::PatternNode::Bind((affix, name))
             }
 
-            cst::Node::Name(cst::Name::Universal(name)) => {
+            Kind::PreTick => {
+                let name = self.text(cursor);
                 let e = self
                     .errors
                     .parse_error(span)
@@ -111,12 +174,15 @@ impl<'a, 'src> Abstractifier<'a, 'src, '_> {
                 ast::PatternNode::Invalid(e)
             }
 
-            cst::Node::Group(inner) => {
-                let pattern = self.alloc.alloc(self.affixed_name(affix, inner));
-                ast::PatternNode::Group(pattern)
+            Kind::ParenGroup => {
+                let (inner, span) = self.group(cursor);
+                let pattern = self.alloc.alloc(self.affixed_name(affix, &inner));
+                let node = ast::PatternNode::Group(pattern);
+                return ast::Pattern { node, span };
             }
 
             _ => {
+                self.syntax_errors(cursor);
                 let e = self.errors.parse_error(span).expected_name();
                 ast::PatternNode::Invalid(e)
             }
@@ -1,45 +1,52 @@
-use super::Abstractifier;
+use super::parts::Definition;
+use super::{children, Abstractifier, Part};
 use crate::frontend::errors::ErrorId;
 use crate::frontend::names::Label;
-use crate::frontend::parse::cst::{self, ValueDef};
 use crate::frontend::source::Span;
 use crate::frontend::trees::parsed as ast;
+use crate::syntax::green::Kind;
+use crate::syntax::red::Cursor;
 
 impl<'a, 'src> Abstractifier<'a, 'src, '_> {
-    pub fn ty(&mut self, node: &cst::Thing<'_, 'src>) -> ast::Type<'a, 'src> {
-        let span = node.span;
-        let node = match &node.node {
-            cst::Node::Invalid(e) => ast::TypeNode::Invalid(*e),
-            cst::Node::Wildcard => ast::TypeNode::Wildcard,
-
-            cst::Node::Name(cst::Name::Normal(name)) => {
-                let name = self.names.intern(name);
+    pub fn ty(&mut self, cursor: &Cursor) -> ast::Type<'a, 'src> {
+        let span = self.span(cursor);
+        let node = match cursor.kind() {
+            Kind::Underscore => ast::TypeNode::Wildcard,
+
+            Kind::Name | Kind::PostTick => {
+                let name = self.names.intern(self.text(cursor));
                 ast::TypeNode::Named(name)
             }
 
-            cst::Node::Name(cst::Name::Universal(name)) => {
-                let name = self.names.intern(name);
+            Kind::PreTick => {
+                let name = self.names.intern(self.text(cursor));
                 ast::TypeNode::Universal(name)
             }
 
-            cst::Node::Group(node) => {
-                return self.ty(node);
+            Kind::ParenGroup => {
+                let (ty, _) = self.group(cursor);
+                return self.or_invalid(&ty, Self::ty);
             }
 
-            cst::Node::Record { defs } => {
-                let fields: Vec<_> = defs.iter().map(|def| self.field(def)).collect();
+            Kind::BraceGroup => {
+                let (definitions, span) = self.record(cursor);
+                let fields: Vec<_> = definitions.iter().map(|def| self.field(def)).collect();
                 let fields = self.alloc.alloc_slice_fill_iter(fields);
-                ast::TypeNode::Record(fields)
+                let node = ast::TypeNode::Record(fields);
+                return ast::Type { node, span };
             }
 
-            cst::Node::Apply(types) => {
-                let types = self
-                    .alloc
-                    .alloc_slice_fill_iter(types.iter().map(|ty| self.ty(ty)));
-                ast::TypeNode::Apply(types)
+            Kind::Apply => {
+                let types: Vec<_> = children(cursor).iter().map(|ty| self.ty(ty)).collect();
+                ast::TypeNode::Apply(self.alloc.alloc_slice_fill_iter(types))
             }
 
             _ => {
+                if let Some((e, span)) = self.misplaced(cursor) {
+                    let node = ast::TypeNode::Invalid(e);
+                    return ast::Type { node, span };
+                }
+
                 let e = self.errors.parse_error(span).expected_type();
                 ast::TypeNode::Invalid(e)
             }
@@ -48,28 +55,36 @@ impl<'a, 'src> Abstractifier<'a, 'src, '_> {
         ast::Type { node, span }
     }
 
-    fn field(
+    pub(super) fn field(
         &mut self,
-        def: &ValueDef<'_, 'src>,
+        def: &Definition,
     ) -> (Result<Label<'src>, ErrorId>, Span, ast::Type<'a, 'src>) {
-        let (name, ty) = self.anno(def.pattern);
+        let (name, ty) = self.anno(&def.pattern);
         let (name, name_span) = match name {
-            (Ok(name), _) => self.normal_name(name),
+            (Ok(name), _) => {
+                let name = Ok(name);
+                let (ident, span) = self.normal_name(&name);
+                self.part_errors(&name);
+                (ident, span)
+            }
+
             (Err(e), name_span) => (Err(e), name_span),
         };
 
         let name = name.map(Label);
 
         let mut ty = match ty {
-            (Ok(ty), _) => self.ty(ty),
+            (Ok(ty), _) => self.ty(&ty),
             (Err(e), span) => {
                 let node = ast::TypeNode::Invalid(e);
                 ast::Type { node, span }
             }
         };
 
-        if let Some(body) = def.definition {
-            let span = body.span;
+        if let Some(body) = &def.body {
+            self.part_errors(body);
+
+            let span = self.part_span(body);
             let e = self.errors.parse_error(span).record_type_field_definition();
             let node = ast::TypeNode::Invalid(e);
             ty = ast::Type { node, span };
@@ -78,19 +93,32 @@ impl<'a, 'src> Abstractifier<'a, 'src, '_> {
         (name, name_span, ty)
     }
 
-    fn anno<'b, 's>(&mut self, node: &'b cst::Thing<'b, 's>) -> (Bit<'b, 's>, Bit<'b, 's>) {
-        let span = node.span;
-        match &node.node {
-            cst::Node::Invalid(e) => ((Err(*e), span), (Err(*e), span)),
-            cst::Node::Group(node) => self.anno(node),
-            cst::Node::Anno(a, b) => ((Ok(a), a.span), (Ok(b), b.span)),
+    fn anno(&mut self, part: &Part) -> (Bit, Bit) {
+        let cursor = match part {
+            Ok(cursor) => cursor,
+            Err((e, span)) => return ((Err(*e), *span), (Err(*e), *span)),
+        };
+
+        let span = self.span(cursor);
+        match cursor.kind() {
+            Kind::ParenGroup => {
+                let (inner, _) = self.group(cursor);
+                self.anno(&inner)
+            }
+
+            Kind::Annotate => {
+                let (a, b) = self.annotation(cursor);
+                (bit(self, a), bit(self, b))
+            }
 
-            cst::Node::Name(cst::Name::Normal(name) | cst::Name::Universal(name)) => {
+            Kind::Name | Kind::PreTick | Kind::PostTick => {
+                let name = self.text(cursor);
                 let e = self.errors.parse_error(span).expected_annotation(name);
-                ((Ok(node), span), (Err(e), span))
+                ((Ok(cursor.clone()), span), (Err(e), span))
             }
 
             _ => {
+                self.syntax_errors(cursor);
                 let e = self.errors.parse_error(span).expected_annotated_name();
                 ((Err(e), span), (Err(e), span))
             }
@@ -98,4 +126,9 @@ impl<'a, 'src> Abstractifier<'a, 'src, '_> {
     }
 }
 
-type Bit<'a, 's> = (Result<&'a cst::Thing<'a, 's>, ErrorId>, Span);
+type Bit = (Result<Cursor, ErrorId>, Span);
+
+fn bit(abstractifier: &Abstractifier, part: Part) -> Bit {
+    let span = abstractifier.part_span(&part);
+    (part.map_err(|(e, _)| e), span)
+}
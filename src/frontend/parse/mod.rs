@@ -1,48 +1,32 @@
 use log::debug;
-pub use tokens::Token;
 
 mod abstractify;
-mod cst;
-mod parser;
-mod tokens;
+
+#[cfg(test)]
+mod tests;
 
 use bumpalo::Bump;
-use logos::Logos;
 
 use self::abstractify::Abstractifier;
-use self::parser::Parser;
 use crate::frontend::errors::Errors;
 use crate::frontend::names::Names;
-use crate::frontend::source::{Source, Span};
+use crate::frontend::source::Source;
 use crate::frontend::trees::parsed;
-
-pub fn tokens(source: &Source) -> impl Iterator<Item = (Result<Token, ()>, Span)> {
-    Token::lexer(&source.content)
-        .spanned()
-        .map(|(result, span)| (result, source.id.span(span.start, span.end)))
-}
+use crate::syntax::red::Cursor;
 
 pub fn parse<'a, 'src>(
     alloc: &'a Bump,
     names: &'a Names<'src>,
     source: &'src Source,
 ) -> parsed::Source<'a, 'src> {
-    debug!("lexing");
-    let tokens = tokens(source);
-
     debug!("parsing");
-    let mut errors = Errors::new();
-    let concrete_alloc = Bump::new();
-
-    let (concrete, parse_errors) = {
-        let parser = Parser::new(&concrete_alloc, &mut errors, tokens, source.id);
-        parser.program()
-    };
+    let green = crate::syntax::parse::parse(&source.content);
 
     debug!("abstracting");
+    let mut errors = Errors::new();
     let (abstracted, unattached) = {
-        let abstractifier = Abstractifier::new(alloc, names, &mut errors, parse_errors);
-        abstractifier.program(concrete)
+        let abstractifier = Abstractifier::new(alloc, names, &mut errors, source);
+        abstractifier.program(&Cursor::root(green))
     };
 
     parsed::Source {
@@ -0,0 +1,594 @@
+//! Tests checking the parsed trees and diagnostics the abstractifier produces
+//! from the green tree.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use bumpalo::Bump;
+use proptest::collection::vec;
+use proptest::proptest;
+use proptest::sample::select;
+
+use super::parse;
+use crate::frontend::errors::ErrorId;
+use crate::frontend::names::{Label, Names};
+use crate::frontend::prelude;
+use crate::frontend::source::{Sources, Span};
+use crate::frontend::trees::parsed::{
+    Constructor, ConstructorNode, Data, DataNode, Expr, ExprNode, Item, ItemNode, Pattern,
+    PatternNode, Source, Type, TypeNode,
+};
+
+/// Render a parsed source along with its diagnostics. Every node is given its
+/// span and every error its title.
+fn render(source: &Source) -> String {
+    let errors = source.errors.clone().drain().collect();
+    let mut render = Render {
+        out: String::new(),
+        errors,
+    };
+
+    for item in source.items {
+        render.item(item);
+        render.out.push('\n');
+    }
+
+    let mut reported: Vec<_> = render
+        .errors
+        .values()
+        .map(|error| format!("{:?} {}", error.at, error.title))
+        .collect();
+    reported.sort();
+
+    for error in reported {
+        writeln!(render.out, "error {error}").unwrap();
+    }
+
+    for (e, span) in &source.unattached {
+        writeln!(render.out, "unattached {} {span:?}", render.error(e)).unwrap();
+    }
+
+    render.out
+}
+
+/// Parse a source and check that it reports exactly the given diagnostics,
+/// each given by the range of its span and its title. Returns the rendered
+/// items.
+#[track_caller]
+fn check_errors(source: &str, expected: &[(usize, usize, &str)]) -> Vec<String> {
+    let sources = Sources::new();
+    let source = sources.add(source);
+    let names = Names::new();
+
+    let alloc = Bump::new();
+    let parsed = parse(&alloc, &names, &source);
+
+    let mut errors: Vec<_> = parsed
+        .errors
+        .clone()
+        .drain()
+        .map(|(_, error)| (error.at.start, error.at.end, error.title))
+        .collect();
+    errors.sort();
+
+    let expected: Vec<_> = expected
+        .iter()
+        .map(|(start, end, title)| (*start, *end, title.to_string()))
+        .collect();
+    assert_eq!(expected, errors, "in {:?}", source.content);
+
+    render(&parsed).lines().map(String::from).collect()
+}
+
+/// Parse a source and check that it reports no diagnostics.
+#[track_caller]
+fn check(source: &str) {
+    check_errors(source, &[]);
+}
+
+/// Parse a source and check that every invalid node in the parsed tree
+/// stands for a reported diagnostic, which rendering it relies on.
+#[track_caller]
+fn check_recovers(source: &str) {
+    let sources = Sources::new();
+    let source = sources.add(source);
+    let names = Names::new();
+
+    let alloc = Bump::new();
+    let parsed = parse(&alloc, &names, &source);
+    render(&parsed);
+}
+
+#[test]
+fn the_prelude() {
+    check(prelude::source().content.as_str());
+}
+
+#[test]
+fn items() {
+    check("let x = 5");
+    check("let f x y = x\nlet g = f 1 2");
+    check("let x : Int = 5 and y = x");
+    check("data Maybe a = Nothing | Just a");
+    check("data List a = Nil | Cons a (List a)");
+    check("data Unit = Unit\nlet x = Unit");
+    check("type Id = 'a -> 'a");
+    check("type Pair a b = { fst : a, snd : b }");
+    check("class Eq a = { eq : a -> a -> Bool }");
+    check("instance Eq Int = { eq = x => y => x }");
+    check("let infixl 6 + a b = add a b");
+    check("let infix 4 == a b = less a b");
+    check("let postfix ! n = n");
+    check("-- a comment\nlet x = 5 -- another\n");
+}
+
+#[test]
+fn expressions() {
+    check("let x = f (g x) y.a.b");
+    check("let x = { a = 1, b = 2 }");
+    check("let x = { a = 1, b = 2, }");
+    check("let x = {}");
+    check("let x = _");
+    check("let x = let y = 5 in y");
+    check("let x = let y = 5 and z = y in z");
+    check("let x = case y end");
+    check("let x = case y | A => 1 | B => 2 end");
+    check("let x = case | A => 1 end");
+    check("let x = case end");
+    check("let x = y : Int");
+    check("let f = a => b => c");
+    check("let f = A | B => x | C | D => y");
+    check("let f = | A => x | B => y");
+    check("let f = (x => x) 5");
+    check("let f = { a = x, b } => x");
+    check("let f = x : Int => x");
+}
+
+#[test]
+fn errors() {
+    check_errors(
+        "let",
+        &[
+            (0, 3, "expected a `=` and a body"),
+            (0, 3, "unexpected token"),
+        ],
+    );
+    check_errors("let x =", &[(6, 7, "unexpected token")]);
+    check_errors("let x = (5", &[(8, 9, "unclosed parenthesis")]);
+    check_errors("let x = { a = 1", &[(8, 9, "unclosed brace")]);
+    check_errors("let x = case y", &[(8, 12, "`case` has no matching `end`")]);
+    check_errors("let x = a.", &[(9, 10, "expected a name")]);
+    check_errors("let x = let y = 5 in", &[(18, 20, "unexpected token")]);
+    check_errors("let f = |", &[(8, 9, "unexpected token")]);
+    check_errors(") let x = 5", &[(0, 1, "unexpected token")]);
+    check_errors("let x = 5 ) )", &[(10, 13, "unexpected token")]);
+    check_errors("let x = [ 5", &[(8, 9, "unexpected token")]);
+    check_errors("let x = ()", &[(9, 10, "unexpected token")]);
+    check_errors(
+        "let x = 'a",
+        &[(
+            8,
+            10,
+            "ticked names can only be used as types in certain contexts",
+        )],
+    );
+    check_errors("let x = a & b & c", &[(8, 17, "expected an expression")]);
+    check_errors("x y z", &[(0, 5, "expected an item")]);
+    check_errors(
+        "let x = (",
+        &[(8, 9, "unclosed parenthesis"), (8, 9, "unexpected token")],
+    );
+    check_errors("let x = {", &[(8, 9, "unclosed brace")]);
+    check_errors("let x = case", &[(8, 12, "`case` has no matching `end`")]);
+    check_errors(
+        "let x = a &",
+        &[
+            (8, 11, "expected an expression"),
+            (10, 11, "unexpected token"),
+        ],
+    );
+    check_errors("let x = y :", &[(10, 11, "unexpected token")]);
+    check_errors(
+        "let x = 5 and",
+        &[
+            (10, 13, "expected a `=` and a body"),
+            (10, 13, "unexpected token"),
+        ],
+    );
+    check_errors("let f = x =>", &[(10, 12, "unexpected token")]);
+    check_errors(
+        "let f = A | B",
+        &[
+            (8, 9, "expected a case arm"),
+            (12, 13, "expected a case arm"),
+        ],
+    );
+    check_errors("let x = 5 = 6", &[(10, 13, "unexpected token")]);
+    check_errors(
+        "let infix 10 + a b = a",
+        &[(10, 12, "invalid operator precedence")],
+    );
+
+    check_errors(
+        "let x = 9223372036854775808",
+        &[(8, 27, "integer literal is too large")],
+    );
+    check_errors("let x = 9_223_372_036_854_775_807", &[]);
+
+    // `=>|` is a single operator token, so this is the application of an
+    // operator and not an error
+    check_errors("let f = A =>| B", &[]);
+}
+
+#[test]
+fn tokens_before_a_closer_are_skipped() {
+    check_errors("let f = Some (x, y) => x", &[(15, 18, "unexpected token")]);
+    check_errors("let x = { a = 1 = 2 }", &[(16, 19, "unexpected token")]);
+    check_errors(
+        "let x = (case y)",
+        &[(9, 13, "`case` has no matching `end`")],
+    );
+}
+
+#[test]
+fn errors_do_not_cascade() {
+    let source =
+        "let x = (1 + 2\nlet y = 3\nlet z = { a = 1 b = 2 ]\nlet w = case y | A => 1\ndata T = A";
+    let parsed = check_errors(
+        source,
+        &[
+            (8, 9, "unclosed parenthesis"),
+            (33, 34, "unclosed brace"),
+            (43, 46, "unexpected token"),
+            (47, 48, "unexpected token"),
+            (57, 61, "`case` has no matching `end`"),
+        ],
+    );
+
+    let items: Vec<_> = parsed.iter().filter(|item| item.starts_with('(')).collect();
+    assert_eq!(5, items.len(), "{items:#?}");
+}
+
+/// Fragments of programs which are put together at random, so that every
+/// kind of broken input is recovered from.
+const FRAGMENTS: &[&str] = &[
+    "let",
+    "data",
+    "type",
+    "class",
+    "instance",
+    "and",
+    "in",
+    "case",
+    "end",
+    "infix",
+    "infixl",
+    "postfix",
+    "x",
+    "A",
+    "'a",
+    "x'",
+    "+",
+    "5",
+    "_",
+    "...",
+    "=",
+    "=>",
+    "|",
+    "&",
+    ":",
+    ".",
+    ",",
+    "(",
+    ")",
+    "{",
+    "}",
+    "[",
+    "$",
+    "-- comment\n",
+    "\n",
+];
+
+proptest! {
+    #[test]
+    fn fragments(fragments in vec(select(FRAGMENTS), 0..16)) {
+        check_recovers(&fragments.join(" "));
+    }
+}
+
+struct Render {
+    out: String,
+    errors: HashMap<ErrorId, crate::frontend::errors::Error>,
+}
+
+impl Render {
+    fn error(&self, e: &ErrorId) -> String {
+        format!("!({})", self.errors[e].title)
+    }
+
+    fn span(&mut self, span: Span) {
+        write!(self.out, "@{}..{}", span.start, span.end).unwrap();
+    }
+
+    fn label(&mut self, label: &Result<Label, ErrorId>, span: Span) {
+        match label {
+            Ok(label) => write!(self.out, "{label:?}").unwrap(),
+            Err(e) => self.out.push_str(&self.error(e)),
+        }
+
+        self.span(span);
+    }
+
+    fn item(&mut self, item: &Item) {
+        match &item.node {
+            ItemNode::Invalid(e) => self.out.push_str(&self.error(e)),
+            ItemNode::Let(pattern, expr, ()) => {
+                self.out.push_str("(let ");
+                self.pattern(pattern);
+                self.out.push(' ');
+                self.expr(expr);
+                self.out.push(')');
+            }
+
+            ItemNode::Data(pattern, data) => {
+                self.out.push_str("(data ");
+                self.pattern(pattern);
+                self.out.push(' ');
+                self.data(data);
+                self.out.push(')');
+            }
+
+            ItemNode::Alias(pattern, ty) => {
+                self.out.push_str("(type ");
+                self.pattern(pattern);
+                self.out.push(' ');
+                self.ty(ty);
+                self.out.push(')');
+            }
+
+            ItemNode::Class(pattern, methods) => {
+                self.out.push_str("(class ");
+                self.pattern(pattern);
+                for (label, span, ty) in methods.iter() {
+                    self.out.push(' ');
+                    self.label(label, *span);
+                    self.out.push(':');
+                    self.ty(ty);
+                }
+                self.out.push(')');
+            }
+
+            ItemNode::Instance(head, expr, ()) => {
+                self.out.push_str("(instance ");
+                self.ty(head);
+                self.out.push(' ');
+                self.expr(expr);
+                self.out.push(')');
+            }
+        }
+
+        self.span(item.span);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.node {
+            ExprNode::Invalid(e) => self.out.push_str(&self.error(e)),
+            ExprNode::Var(name) => write!(self.out, "{name:?}").unwrap(),
+            ExprNode::Hole => self.out.push('_'),
+            ExprNode::Unit => self.out.push_str("()"),
+            ExprNode::Number(lit) => self.out.push_str(lit),
+            ExprNode::Anno(expr, ty) => {
+                self.out.push_str("(: ");
+                self.expr(expr);
+                self.out.push(' ');
+                self.ty(ty);
+                self.out.push(')');
+            }
+
+            ExprNode::Group(expr) => {
+                self.out.push_str("(group ");
+                self.expr(expr);
+                self.out.push(')');
+            }
+
+            ExprNode::Field(expr, label, span) => {
+                self.out.push_str("(. ");
+                self.expr(expr);
+                self.out.push(' ');
+                self.label(label, *span);
+                self.out.push(')');
+            }
+
+            ExprNode::Record(fields, extends) => {
+                self.out.push_str("(record");
+                for (label, span, expr) in fields.iter() {
+                    self.out.push(' ');
+                    self.label(label, *span);
+                    self.out.push('=');
+                    self.expr(expr);
+                }
+
+                if let Some(extends) = extends {
+                    self.out.push_str(" ...");
+                    self.expr(extends);
+                }
+                self.out.push(')');
+            }
+
+            ExprNode::Restrict(expr, label) => {
+                self.out.push_str("(\\ ");
+                self.expr(expr);
+                write!(self.out, " {label:?})").unwrap();
+            }
+
+            ExprNode::Apply(exprs) => {
+                self.out.push_str("(apply");
+                for expr in exprs.iter() {
+                    self.out.push(' ');
+                    self.expr(expr);
+                }
+                self.out.push(')');
+            }
+
+            ExprNode::Lambda(arms) => {
+                self.out.push_str("(lambda");
+                for (pattern, expr) in arms.iter() {
+                    self.out.push(' ');
+                    self.pattern(pattern);
+                    self.out.push_str(" => ");
+                    self.expr(expr);
+                }
+                self.out.push(')');
+            }
+
+            ExprNode::Let(pattern, [bound, body], ()) => {
+                self.out.push_str("(let ");
+                self.pattern(pattern);
+                self.out.push(' ');
+                self.expr(bound);
+                self.out.push_str(" in ");
+                self.expr(body);
+                self.out.push(')');
+            }
+        }
+
+        self.span(expr.span);
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        match &pattern.node {
+            PatternNode::Invalid(e) => self.out.push_str(&self.error(e)),
+            PatternNode::Wildcard => self.out.push('_'),
+            PatternNode::Unit => self.out.push_str("()"),
+            PatternNode::Bind((affix, name)) => write!(self.out, "{affix:?} {name:?}").unwrap(),
+            PatternNode::Constructor(never) => match *never {},
+            PatternNode::Anno(pattern, ty) => {
+                self.out.push_str("(: ");
+                self.pattern(pattern);
+                self.out.push(' ');
+                self.ty(ty);
+                self.out.push(')');
+            }
+
+            PatternNode::Group(pattern) => {
+                self.out.push_str("(group ");
+                self.pattern(pattern);
+                self.out.push(')');
+            }
+
+            PatternNode::Apply(patterns) => {
+                self.out.push_str("(apply");
+                for pattern in patterns.iter() {
+                    self.out.push(' ');
+                    self.pattern(pattern);
+                }
+                self.out.push(')');
+            }
+
+            PatternNode::Record(fields) => {
+                self.out.push_str("(record");
+                for (label, span, pattern) in fields.iter() {
+                    self.out.push(' ');
+                    self.label(label, *span);
+                    self.out.push('=');
+                    self.pattern(pattern);
+                }
+                self.out.push(')');
+            }
+
+            PatternNode::Or([a, b]) => {
+                self.out.push_str("(or ");
+                self.pattern(a);
+                self.out.push(' ');
+                self.pattern(b);
+                self.out.push(')');
+            }
+
+            PatternNode::And([a, b]) => {
+                self.out.push_str("(and ");
+                self.pattern(a);
+                self.out.push(' ');
+                self.pattern(b);
+                self.out.push(')');
+            }
+        }
+
+        self.span(pattern.span);
+    }
+
+    fn ty(&mut self, ty: &Type) {
+        match &ty.node {
+            TypeNode::Invalid(e) => self.out.push_str(&self.error(e)),
+            TypeNode::Wildcard => self.out.push('_'),
+            TypeNode::Named(name) => write!(self.out, "{name:?}").unwrap(),
+            TypeNode::Universal(name) => write!(self.out, "'{name:?}").unwrap(),
+            TypeNode::Function([t, u]) => {
+                self.out.push_str("(-> ");
+                self.ty(t);
+                self.out.push(' ');
+                self.ty(u);
+                self.out.push(')');
+            }
+
+            TypeNode::Record(fields) => {
+                self.out.push_str("(record");
+                for (label, span, ty) in fields.iter() {
+                    self.out.push(' ');
+                    self.label(label, *span);
+                    self.out.push(':');
+                    self.ty(ty);
+                }
+                self.out.push(')');
+            }
+
+            TypeNode::Group(ty) => {
+                self.out.push_str("(group ");
+                self.ty(ty);
+                self.out.push(')');
+            }
+
+            TypeNode::Apply(types) => {
+                self.out.push_str("(apply");
+                for ty in types.iter() {
+                    self.out.push(' ');
+                    self.ty(ty);
+                }
+                self.out.push(')');
+            }
+        }
+
+        self.span(ty.span);
+    }
+
+    fn data(&mut self, data: &Data) {
+        match &data.node {
+            DataNode::Invalid(e) => self.out.push_str(&self.error(e)),
+            DataNode::Sum(constructors) => {
+                self.out.push_str("(sum");
+                for constructor in constructors.iter() {
+                    self.out.push(' ');
+                    self.constructor(constructor);
+                }
+                self.out.push(')');
+            }
+        }
+
+        self.span(data.span);
+    }
+
+    fn constructor(&mut self, constructor: &Constructor) {
+        match &constructor.node {
+            ConstructorNode::Invalid(e) => self.out.push_str(&self.error(e)),
+            ConstructorNode::Constructor((affix, name), types) => {
+                write!(self.out, "({affix:?} {name:?}").unwrap();
+                for ty in types.iter() {
+                    self.out.push(' ');
+                    self.ty(ty);
+                }
+                self.out.push(')');
+            }
+        }
+
+        self.span(constructor.span);
+    }
+}
@@ -0,0 +1,72 @@
+//! The prelude is a source file bundled with the compiler, defining common
+//! types and functions. It is resolved in a scope enclosing the top level of
+//! every program, so programs can shadow its definitions.
+//!
+//! The prelude is parsed, resolved and checked once, and every program picks
+//! up from there. Since types are not shared between threads, each thread
+//! which checks programs keeps a checked prelude of its own.
+
+use std::sync::OnceLock;
+
+use bumpalo::Bump;
+
+use crate::frontend::names::Names;
+use crate::frontend::parse::parse;
+use crate::frontend::resolve::{self, resolve_prelude};
+use crate::frontend::source::{Source, SourceId};
+use crate::frontend::trees::parsed;
+use crate::frontend::tyck::{self, check_prelude};
+
+/// The path used to refer to the prelude in diagnostics and editors.
+pub const PATH: &str = "prelude.nml";
+
+const CONTENT: &str = include_str!("prelude.nml");
+
+/// Get the source of the prelude.
+pub fn source() -> &'static Source {
+    static SOURCE: OnceLock<Source> = OnceLock::new();
+    SOURCE.get_or_init(|| Source {
+        id: SourceId::PRELUDE,
+        content: CONTENT.into(),
+    })
+}
+
+/// Get the parsed prelude. Parsing only interns identifiers, so the parsed
+/// tree can be shared by every program.
+pub fn parsed() -> &'static parsed::Source<'static, 'static> {
+    static PARSED: OnceLock<parsed::Source<'static, 'static>> = OnceLock::new();
+    PARSED.get_or_init(|| {
+        let alloc: &'static Bump = Box::leak(Box::default());
+        let names: &'static Names<'static> = Box::leak(Box::new(Names::new()));
+        parse(alloc, names, source())
+    })
+}
+
+struct Prelude {
+    resolved: resolve::Prelude<'static, 'static>,
+    checked: tyck::Prelude<'static, 'static>,
+}
+
+thread_local! {
+    static PRELUDE: &'static Prelude = {
+        let alloc: &'static Bump = Box::leak(Box::default());
+        let names: &'static Names<'static> = Box::leak(Box::new(Names::new()));
+
+        let resolved = resolve_prelude(names, alloc, parsed());
+        let checked = check_prelude(alloc, names, &resolved.program);
+        assert!(checked.errors.is_perfect(), "the prelude has no errors");
+
+        Box::leak(Box::new(Prelude { resolved, checked }))
+    };
+}
+
+/// Get the resolved prelude, to resolve programs with.
+pub fn resolved() -> &'static resolve::Prelude<'static, 'static> {
+    PRELUDE.with(|prelude| &prelude.resolved)
+}
+
+/// Get the checked prelude. Programs resolved with the prelude are checked
+/// with it automatically.
+pub fn checked() -> &'static tyck::Prelude<'static, 'static> {
+    PRELUDE.with(|prelude| &prelude.checked)
+}
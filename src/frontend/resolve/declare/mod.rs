@@ -1,8 +1,10 @@
 use std::collections::BTreeMap;
 
-use crate::frontend::trees::{declared, resolved};
+use crate::frontend::names::{Ident, Name};
+use crate::frontend::trees::parsed::Affix;
+use crate::frontend::trees::{declared, parsed, resolved};
 
-use super::{Namespace, Resolver};
+use super::{ItemId, Namekind, Namespace, Resolver};
 
 impl<'a, 'scratch, 'src, 'err> Resolver<'a, 'scratch, 'src, 'err> {
     pub(super) fn declare_item(
@@ -15,7 +17,7 @@ impl<'a, 'scratch, 'src, 'err> Resolver<'a, 'scratch, 'src, 'err> {
             declared::patterns::ItemNode::Invalid(e) => declared::ItemNode::Invalid(e),
             declared::patterns::ItemNode::Let(pattern, expr, ()) => {
                 let mut this_scope = BTreeMap::new();
-                let spine = self.function_spine(id, &mut this_scope, pattern);
+                let spine = self.function_spine(Namespace::Value, id, &mut this_scope, pattern);
                 let spine: declared::Spine<'scratch, 'src, resolved::Pattern<'a, 'src>> =
                     spine.map(|pattern| self.pattern(Namespace::Value, &mut this_scope, &pattern));
 
@@ -24,9 +26,7 @@ impl<'a, 'scratch, 'src, 'err> Resolver<'a, 'scratch, 'src, 'err> {
 
             declared::patterns::ItemNode::Data(pattern, body) => {
                 let mut gen_scope = BTreeMap::new();
-                let spine = self.function_spine(id, &mut gen_scope, pattern);
-                let spine =
-                    spine.map(|pattern| self.pattern(Namespace::Type, &mut gen_scope, &pattern));
+                let spine = self.type_spine(id, &mut gen_scope, pattern);
 
                 if !gen_scope.is_empty() {
                     let span = pattern.span;
@@ -36,8 +36,87 @@ impl<'a, 'scratch, 'src, 'err> Resolver<'a, 'scratch, 'src, 'err> {
                     declared::ItemNode::Data(spine, body)
                 }
             }
+
+            declared::patterns::ItemNode::Alias(pattern, body) => {
+                let mut gen_scope = BTreeMap::new();
+                let spine = self.type_spine(id, &mut gen_scope, pattern);
+
+                if !gen_scope.is_empty() {
+                    let span = pattern.span;
+                    let e = self.errors.name_error(span).implicit_type_var_in_alias();
+                    declared::ItemNode::Invalid(e)
+                } else {
+                    declared::ItemNode::Alias(spine, body)
+                }
+            }
+
+            declared::patterns::ItemNode::Class(pattern, methods) => {
+                let mut gen_scope = BTreeMap::new();
+                let spine = self.type_spine(id, &mut gen_scope, pattern);
+
+                if !gen_scope.is_empty() {
+                    let span = pattern.span;
+                    let e = self.errors.name_error(span).implicit_type_var_in_class();
+                    declared::ItemNode::Invalid(e)
+                } else {
+                    // Methods are values like any other, defined in the scope
+                    // surrounding the class.
+                    let methods = methods
+                        .iter()
+                        .map(|(label, span, ty)| {
+                            let name = label.and_then(|label| {
+                                self.define_value(
+                                    id,
+                                    *span,
+                                    Affix::Prefix,
+                                    label.0,
+                                    Namekind::Value,
+                                )
+                            });
+
+                            (name, *span, ty)
+                        })
+                        .collect();
+
+                    declared::ItemNode::Class(spine, methods)
+                }
+            }
+
+            declared::patterns::ItemNode::Instance(head, body, ()) => {
+                // Instances are anonymous, but the dictionary they elaborate
+                // to still needs a name.
+                let ident = self.names.intern("instance");
+                let name = self.names.name(self.scopes.1.name, ident);
+                self.items.insert(name, id);
+                self.spans.insert(name, head.span);
+
+                declared::ItemNode::Instance((name, head), body, BTreeMap::new())
+            }
         };
 
         declared::Item { node, span, id }
     }
+
+    /// Declare the name of a `data` or `type` item. An annotation on an item
+    /// without parameters, like `data T : Type`, is a kind annotation and is
+    /// kept aside rather than being resolved as a type.
+    fn type_spine(
+        &mut self,
+        id: ItemId,
+        gen_scope: &mut BTreeMap<Ident<'src>, Name>,
+        pattern: &'scratch parsed::Pattern<'scratch, 'src>,
+    ) -> declared::Spine<'scratch, 'src, resolved::Pattern<'a, 'src>> {
+        match self.function_spine(Namespace::Type, id, gen_scope, pattern) {
+            declared::Spine::Single(declared::spined::Pattern {
+                node: declared::spined::PatternNode::Anno(head, anno),
+                ..
+            }) => declared::Spine::Fun {
+                head: self.pattern(Namespace::Type, gen_scope, head),
+                args: Vec::new(),
+                anno: Some(anno),
+            },
+
+            spine => spine.map(|pattern| self.pattern(Namespace::Type, gen_scope, &pattern)),
+        }
+    }
 }
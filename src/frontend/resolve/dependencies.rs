@@ -30,6 +30,37 @@ impl Resolver<'_, '_, '_, '_> {
                 self.in_data_body(&mut ignore, &mut depends, body);
                 depends
             }
+
+            ItemNode::Alias(pattern, body) => {
+                let mut ignore = BTreeSet::new();
+                let mut depends = BTreeSet::new();
+
+                ignore.extend(pattern.args.iter().copied().flat_map(Result::ok));
+
+                self.in_type(&mut ignore, &mut depends, body);
+                depends
+            }
+
+            ItemNode::Class(pattern, methods) => {
+                let mut ignore = BTreeSet::new();
+                let mut depends = BTreeSet::new();
+
+                ignore.extend(pattern.args.iter().copied().flat_map(Result::ok));
+
+                for method in methods.iter() {
+                    self.in_type(&mut ignore, &mut depends, &method.ty);
+                }
+
+                depends
+            }
+
+            ItemNode::Instance((_, head), body, _) => {
+                let mut ignore = BTreeSet::new();
+                let mut depends = BTreeSet::new();
+                self.in_type(&mut ignore, &mut depends, head);
+                self.in_expr(&mut ignore, &mut depends, body);
+                depends
+            }
         }
     }
 
@@ -114,6 +145,12 @@ impl Resolver<'_, '_, '_, '_> {
                 self.in_pattern(ignore, out, a);
                 self.in_pattern(ignore, out, b);
             }
+
+            PatternNode::Record(fields) => {
+                for (_, _, pattern) in fields.iter() {
+                    self.in_pattern(ignore, out, pattern);
+                }
+            }
         }
     }
 
@@ -21,6 +21,7 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
 
             parsed::ExprNode::Var(name) => {
                 if let Some((name, _)) = self.lookup_value(name) {
+                    self.refs.insert(span, name);
                     resolved::ExprNode::Var(name)
                 } else {
                     let name = name.name();
@@ -37,8 +38,13 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
             }
 
             parsed::ExprNode::Group(expr) => {
-                let expr = self.alloc.alloc(self.expr(item, gen_scope, expr));
-                resolved::ExprNode::Group(expr)
+                let expr = if let parsed::ExprNode::Apply(terms) = &expr.node {
+                    self.apply_section_run(item, gen_scope, terms)
+                } else {
+                    self.expr(item, gen_scope, expr)
+                };
+
+                resolved::ExprNode::Group(self.alloc.alloc(expr))
             }
 
             parsed::ExprNode::Field(of, field, field_span) => {
@@ -74,7 +80,8 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
                     self.alloc
                         .alloc_slice_fill_iter(arrows.iter().map(|(pattern, body)| {
                             self.scope(None, |this| {
-                                let pattern = this.single_pattern(item, gen_scope, pattern);
+                                let pattern =
+                                    this.single_pattern(Namespace::Value, item, gen_scope, pattern);
                                 let pattern = this.pattern(Namespace::Value, gen_scope, &pattern);
                                 let body = this.expr(item, gen_scope, body);
                                 (pattern, body)
@@ -87,7 +94,7 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
             parsed::ExprNode::Let(binding, [bound, body], ()) => {
                 let mut this_scope = BTreeMap::new();
 
-                let spine = self.function_spine(item, &mut this_scope, binding);
+                let spine = self.function_spine(Namespace::Value, item, &mut this_scope, binding);
                 let (pattern, bound) = match spine {
                     declared::Spine::Single(pattern) => {
                         // Resolve the pattern after the bound body to allow
@@ -0,0 +1,72 @@
+use crate::frontend::builtins::Builtin;
+use crate::frontend::source::Span;
+use crate::frontend::trees::{parsed as i, resolved as o};
+
+use super::Resolver;
+
+impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
+    /// Kind annotations are parsed as types, so interpret one as a kind.
+    /// Kinds have no names to resolve, so `Type` and `Row` are recognized as
+    /// is and cannot be shadowed.
+    pub fn resolve_kind(&mut self, kind: &i::Type<'_, 'src>) -> o::Kind<'a> {
+        let span = kind.span;
+        let node = match &kind.node {
+            i::TypeNode::Invalid(e) => o::KindNode::Invalid(*e),
+
+            i::TypeNode::Named(name) => match name.name() {
+                "Type" => o::KindNode::Type,
+                "Row" => o::KindNode::Row,
+                name => o::KindNode::Invalid(self.errors.name_error(span).unknown_kind(name)),
+            },
+
+            i::TypeNode::Function([k, l]) => {
+                let k = self.resolve_kind(k);
+                let l = self.resolve_kind(l);
+                o::KindNode::Arrow(self.alloc.alloc([k, l]))
+            }
+
+            i::TypeNode::Group(kind) => return self.resolve_kind(kind),
+            i::TypeNode::Apply(run) => return self.kind_run(span, run),
+
+            i::TypeNode::Wildcard | i::TypeNode::Universal(_) | i::TypeNode::Record(_) => {
+                o::KindNode::Invalid(self.errors.parse_error(span).expected_kind())
+            }
+        };
+
+        o::Kind { node, span }
+    }
+
+    /// A run of kinds must be separated by arrows, which associate to the
+    /// right.
+    fn kind_run(&mut self, span: Span, run: &[i::Type<'_, 'src>]) -> o::Kind<'a> {
+        let arrow = Builtin::Arrow.ident();
+        let kinds: Vec<_> = run
+            .split(|kind| matches!(&kind.node, i::TypeNode::Named(name) if name.name() == arrow))
+            .map(|part| match part {
+                [kind] => self.resolve_kind(kind),
+                [first, .., last] => {
+                    let span = first.span + last.span;
+                    let e = self.errors.parse_error(span).expected_kind();
+                    let node = o::KindNode::Invalid(e);
+                    o::Kind { node, span }
+                }
+
+                [] => {
+                    let e = self.errors.parse_error(span).expected_kind();
+                    let node = o::KindNode::Invalid(e);
+                    o::Kind { node, span }
+                }
+            })
+            .collect();
+
+        kinds
+            .into_iter()
+            .rev()
+            .reduce(|result, param| {
+                let span = param.span + result.span;
+                let node = o::KindNode::Arrow(self.alloc.alloc([param, result]));
+                o::Kind { node, span }
+            })
+            .expect("splitting always produces at least one part")
+    }
+}
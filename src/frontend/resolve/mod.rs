@@ -5,17 +5,22 @@ mod resolve;
 
 mod dependencies;
 mod expr;
+mod kinds;
 mod operators;
 mod pattern;
 mod types;
 
+#[cfg(test)]
+mod tests;
+
 use std::collections::{BTreeMap, BTreeSet};
 
 use bumpalo::Bump;
 use log::debug;
 
+use crate::frontend::builtins::{Builtin, Primitive};
 use crate::frontend::errors::{ErrorId, Errors};
-use crate::frontend::names::{Ident, Name, Names, ScopeName};
+use crate::frontend::names::{Ident, Name, Names, Qualified, ScopeName};
 use crate::frontend::source::{SourceId, Span};
 use crate::frontend::topology;
 use crate::frontend::trees::parsed::Affix;
@@ -33,44 +38,206 @@ where
     'src: 'a,
 {
     let scratch = Bump::new();
-    resolve_program::<'a, '_, 'src>(names, alloc, &scratch, program)
+    resolve_program::<'a, '_, 'src>(names, alloc, &scratch, None, program)
+}
+
+/// Resolve a program with the definitions of `prelude` in scope. Only the
+/// prelude items the program depends on are included in the result, and they
+/// are taken from the resolved prelude rather than resolved again.
+///
+/// The prelude names are imported into `names`, which must not have been used
+/// to resolve anything yet.
+pub fn resolve_with_prelude<'a, 'b, 'src>(
+    names: &'a Names<'src>,
+    alloc: &'a Bump,
+    prelude: &'b Prelude<'a, 'src>,
+    program: &'b parsed::Source<'b, 'src>,
+) -> resolved::Program<'a, 'src>
+where
+    'src: 'a,
+{
+    let scratch = Bump::new();
+    resolve_program::<'a, '_, 'src>(names, alloc, &scratch, Some(prelude), program)
+}
+
+/// A resolved prelude, along with the state of the resolver after resolving
+/// it. Every program resolved with the prelude picks up from that state.
+pub struct Prelude<'a, 'src> {
+    /// Every item of the prelude.
+    pub program: resolved::Program<'a, 'src>,
+
+    names: Vec<(Name, Qualified<'src>)>,
+    state: State<'src>,
+    graph: BTreeMap<ItemId, BTreeSet<ItemId>>,
+}
+
+/// Resolve the prelude on its own, keeping every item of it.
+pub fn resolve_prelude<'a, 'b, 'src>(
+    names: &'a Names<'src>,
+    alloc: &'a Bump,
+    prelude: &'b parsed::Source<'b, 'src>,
+) -> Prelude<'a, 'src>
+where
+    'src: 'a,
+{
+    let scratch = Bump::new();
+    let mut errors = prelude.errors.clone();
+    let mut resolver = Resolver::new(names, alloc, &scratch, &mut errors, prelude.source);
+
+    let mut items = resolver.items(prelude.items);
+    let graph: BTreeMap<_, _> = items
+        .iter()
+        .map(|(id, item)| (*id, resolver.dependencies(item)))
+        .collect();
+
+    let components = components(alloc, &mut items, &graph);
+    let state = resolver.state();
+
+    let program = resolved::Program {
+        items: alloc.alloc_slice_copy(&components),
+        defs: resolver.spans,
+        refs: resolver.refs,
+        docs: resolver.docs,
+        errors,
+        unattached: prelude.unattached.clone(),
+    };
+
+    Prelude {
+        program,
+        names: names.all(),
+        state,
+        graph,
+    }
+}
+
+impl<'a, 'src> Prelude<'a, 'src> {
+    /// Get the components of the prelude which are used by items with the
+    /// given dependencies, in order. Instances are always used, since they
+    /// are never referred to by name.
+    fn used(
+        &self,
+        graph: &BTreeMap<ItemId, BTreeSet<ItemId>>,
+    ) -> Vec<&'a [resolved::Item<'a, 'src>]> {
+        let items = || self.program.items.iter().flat_map(|items| items.iter());
+        let instances = items().filter_map(|item| match item.node {
+            resolved::ItemNode::Instance(..) => Some(item.id),
+            _ => None,
+        });
+
+        let roots = graph
+            .values()
+            .flatten()
+            .copied()
+            .filter(|id| self.graph.contains_key(id))
+            .chain(instances)
+            .collect();
+
+        let used = reachable(self.graph.clone(), roots);
+        self.program
+            .items
+            .iter()
+            .copied()
+            .filter(|items| items.iter().any(|item| used.contains_key(&item.id)))
+            .collect()
+    }
 }
 
 fn resolve_program<'a, 'b: 'b, 'src>(
     names: &'a Names<'src>,
     alloc: &'a Bump,
     scratch: &'b Bump,
+    prelude: Option<&'b Prelude<'a, 'src>>,
     program: &'b parsed::Source<'b, 'src>,
 ) -> resolved::Program<'a, 'src>
 where
     'src: 'a,
 {
     let mut errors = program.errors.clone();
-    let mut resolver = Resolver::new(names, alloc, scratch, &mut errors, program.source);
+
+    let mut resolver = match prelude {
+        Some(prelude) => {
+            names.import(&prelude.names);
+            let mut resolver = Resolver::resume(names, alloc, scratch, &mut errors, &prelude.state);
+            resolver.enter_top_level(program.source);
+            resolver
+        }
+
+        None => Resolver::new(names, alloc, scratch, &mut errors, program.source),
+    };
 
     let mut items = resolver.items(program.items);
-    let graph = items
+    let graph: BTreeMap<_, _> = items
         .iter()
         .map(|(id, item)| (*id, resolver.dependencies(item)))
         .collect();
-    let order = topology::find(&graph);
 
-    let items = alloc.alloc_slice_fill_iter(order.into_iter().map(|component| {
-        &*alloc.alloc_slice_fill_iter(
-            component
-                .into_iter()
-                .map(|id| items.remove(id).expect("all item ids are defined")),
-        )
-    }));
+    // the prelude never depends on the program, so the components it
+    // contributes come first
+    let mut components = prelude
+        .map(|prelude| prelude.used(&graph))
+        .unwrap_or_default();
+    components.extend(self::components(alloc, &mut items, &graph));
 
     resolved::Program {
-        items,
+        items: alloc.alloc_slice_copy(&components),
         defs: resolver.spans,
+        refs: resolver.refs,
+        docs: resolver.docs,
         errors,
         unattached: program.unattached.clone(),
     }
 }
 
+/// Group items into topologically sorted strongly connected components.
+/// Dependencies on items other than `items` are ignored.
+fn components<'a, 'src>(
+    alloc: &'a Bump,
+    items: &mut BTreeMap<ItemId, resolved::Item<'a, 'src>>,
+    graph: &BTreeMap<ItemId, BTreeSet<ItemId>>,
+) -> Vec<&'a [resolved::Item<'a, 'src>]> {
+    let graph = graph
+        .iter()
+        .map(|(id, depends)| {
+            let depends = depends
+                .iter()
+                .copied()
+                .filter(|id| items.contains_key(id))
+                .collect();
+            (*id, depends)
+        })
+        .collect();
+
+    topology::find(&graph)
+        .into_iter()
+        .map(|component| {
+            &*alloc.alloc_slice_fill_iter(
+                component
+                    .into_iter()
+                    .map(|id| items.remove(id).expect("all item ids are defined")),
+            )
+        })
+        .collect()
+}
+
+/// Remove every item from the dependency graph which is not reachable from one
+/// of the `roots`.
+fn reachable(
+    mut graph: BTreeMap<ItemId, BTreeSet<ItemId>>,
+    roots: BTreeSet<ItemId>,
+) -> BTreeMap<ItemId, BTreeSet<ItemId>> {
+    let mut result = BTreeMap::new();
+    let mut stack: Vec<_> = roots.into_iter().collect();
+
+    while let Some(id) = stack.pop() {
+        if let Some(depends) = graph.remove(&id) {
+            stack.extend(depends.iter().copied());
+            result.insert(id, depends);
+        }
+    }
+
+    result
+}
+
 struct Resolver<'a, 'scratch, 'src, 'err> {
     names: &'a Names<'src>,
     alloc: &'a Bump,
@@ -79,6 +246,8 @@ struct Resolver<'a, 'scratch, 'src, 'err> {
 
     items: BTreeMap<Name, ItemId>,
     spans: BTreeMap<Name, Span>,
+    refs: BTreeMap<Span, Name>,
+    docs: BTreeMap<Name, String>,
     affii: BTreeMap<Name, Affix>,
     explicit_universals: BTreeSet<Name>,
 
@@ -96,6 +265,22 @@ impl<'a, 'scratch, 'src, 'err> Resolver<'a, 'scratch, 'src, 'err> {
         source: SourceId,
     ) -> Self {
         let scope = Scope::top_level(source);
+        let mut builtins = Scope::new(ScopeName::Builtin);
+        let mut affii = BTreeMap::new();
+
+        for builtin in Builtin::TYPES {
+            let ident = names.intern(builtin.ident());
+            let name = names.name(ScopeName::Builtin, ident);
+            builtins.types.insert(ident, name);
+            affii.insert(name, builtin.affix());
+        }
+
+        for primitive in Primitive::VALUES {
+            let ident = names.intern(primitive.ident());
+            let name = names.name(ScopeName::Builtin, ident);
+            builtins.values.insert(ident, (name, Namekind::Value));
+            affii.insert(name, Affix::Prefix);
+        }
 
         Self {
             names,
@@ -105,24 +290,88 @@ impl<'a, 'scratch, 'src, 'err> Resolver<'a, 'scratch, 'src, 'err> {
 
             items: BTreeMap::new(),
             spans: BTreeMap::new(),
-            affii: BTreeMap::new(),
+            refs: BTreeMap::new(),
+            docs: BTreeMap::new(),
+            affii,
             explicit_universals: BTreeSet::new(),
 
-            scopes: (Vec::new(), scope),
+            scopes: (vec![builtins], scope),
             counter: 0,
             item_ids: 0,
         }
     }
 
+    /// Pick up from the state of another resolver, which used the same names.
+    fn resume(
+        names: &'a Names<'src>,
+        alloc: &'a Bump,
+        scratch: &'scratch Bump,
+        errors: &'err mut Errors,
+        state: &State<'src>,
+    ) -> Self {
+        Self {
+            names,
+            alloc,
+            scratch,
+            errors,
+
+            items: state.items.clone(),
+            spans: state.spans.clone(),
+            refs: state.refs.clone(),
+            docs: state.docs.clone(),
+            affii: state.affii.clone(),
+            explicit_universals: state.explicit_universals.clone(),
+
+            scopes: state.scopes.clone(),
+            counter: state.counter,
+            item_ids: state.item_ids,
+        }
+    }
+
     pub fn items(
         &mut self,
         items: &'scratch [parsed::Item<'scratch, 'src>],
     ) -> BTreeMap<ItemId, resolved::Item<'a, 'src>> {
+        let first = self.item_ids;
+        let docs: BTreeMap<_, _> = items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| Some((ItemId(first + index), item.doc?)))
+            .collect();
+
+        let method_docs: BTreeMap<_, _> = items
+            .iter()
+            .flat_map(|item| item.method_docs.iter().copied())
+            .collect();
+
         let items: Vec<declared::patterns::Item<'scratch, 'src>> = self.pattern_items(items);
         let items: Vec<declared::Item<'a, 'scratch, 'src>> = self.declare_items(items);
+        self.document(&docs, &method_docs);
         self.resolve_items(items)
     }
 
+    /// Attach the documentation of each item to the names it defines at the
+    /// top level, such that the constructors of a data type or the methods
+    /// of a class share the documentation of the type or class. Methods with
+    /// documentation of their own, keyed by the span of their names, get that
+    /// instead.
+    fn document(&mut self, docs: &BTreeMap<ItemId, &str>, method_docs: &BTreeMap<Span, &str>) {
+        let scope = &self.scopes.1;
+        let names = scope
+            .types
+            .values()
+            .chain(scope.values.values().map(|(name, _)| name));
+
+        for name in names {
+            let own = self.spans.get(name).and_then(|span| method_docs.get(span));
+            let doc = own.or_else(|| self.items.get(name).and_then(|id| docs.get(id)));
+
+            if let Some(doc) = doc {
+                self.docs.insert(*name, doc.to_string());
+            }
+        }
+    }
+
     fn pattern_items(
         &mut self,
         items: &'scratch [parsed::Item<'scratch, 'src>],
@@ -255,6 +504,28 @@ impl<'a, 'scratch, 'src, 'err> Resolver<'a, 'scratch, 'src, 'err> {
         None
     }
 
+    /// Get everything the resolver has learned so far, to resume from later.
+    fn state(&self) -> State<'src> {
+        State {
+            items: self.items.clone(),
+            spans: self.spans.clone(),
+            refs: self.refs.clone(),
+            docs: self.docs.clone(),
+            affii: self.affii.clone(),
+            explicit_universals: self.explicit_universals.clone(),
+            scopes: self.scopes.clone(),
+            counter: self.counter,
+            item_ids: self.item_ids,
+        }
+    }
+
+    /// Make the current top-level scope an enclosing scope of a new top-level
+    /// scope for the given source.
+    fn enter_top_level(&mut self, source: SourceId) {
+        let top = std::mem::replace(&mut self.scopes.1, Scope::top_level(source));
+        self.scopes.0.push(top);
+    }
+
     fn scope<F, T>(&mut self, name: Option<Name>, f: F) -> T
     where
         F: FnOnce(&mut Self) -> T,
@@ -279,6 +550,19 @@ impl<'a, 'scratch, 'src, 'err> Resolver<'a, 'scratch, 'src, 'err> {
     }
 }
 
+/// The state of a resolver, as captured by [`Resolver::state`].
+struct State<'src> {
+    items: BTreeMap<Name, ItemId>,
+    spans: BTreeMap<Name, Span>,
+    refs: BTreeMap<Span, Name>,
+    docs: BTreeMap<Name, String>,
+    affii: BTreeMap<Name, Affix>,
+    explicit_universals: BTreeSet<Name>,
+    scopes: (Vec<Scope<'src>>, Scope<'src>),
+    counter: usize,
+    item_ids: usize,
+}
+
 #[derive(Clone, Copy, Debug)]
 enum Namespace {
     Type,
@@ -291,7 +575,7 @@ enum Namekind {
     Value,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct Scope<'src> {
     name: ScopeName,
     values: BTreeMap<Ident<'src>, (Name, Namekind)>,
@@ -3,12 +3,12 @@ use std::collections::BTreeMap;
 use bumpalo::Bump;
 use log::trace;
 
-use super::{ItemId, Resolver};
+use super::{ItemId, Namekind, Namespace, Resolver};
 use crate::frontend::errors::ErrorId;
 use crate::frontend::names::{Ident, Name};
 use crate::frontend::source::Span;
 use crate::frontend::trees::declared;
-use crate::frontend::trees::parsed::Affix;
+use crate::frontend::trees::parsed::{Affix, Associativity, Fixity};
 use crate::frontend::trees::{parsed, resolved};
 
 enum OneOrMany<T> {
@@ -30,14 +30,129 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
             .map(|expr| self.expr(item_id, gen_scope, expr))
             .collect();
 
+        self.unflatten_exprs(item_id, terms)
+    }
+
+    /// Resolve a parenthesized expression run, which may be an operator
+    /// section if it begins or ends with an infix operator. A section like
+    /// `(+ 1)` becomes `x => x + 1`, and `(1 +)` becomes `x => 1 + x`.
+    pub(super) fn apply_section_run(
+        &mut self,
+        item_id: ItemId,
+        gen_scope: &mut BTreeMap<Ident<'src>, Name>,
+        terms: &'scratch [parsed::Expr<'scratch, 'src>],
+    ) -> resolved::Expr<'a, 'src> {
+        trace!("resolving parenthesized run of {} terms", terms.len());
+
+        let terms: Vec<_> = terms
+            .iter()
+            .map(|expr| self.expr(item_id, gen_scope, expr))
+            .collect();
+
+        if terms.len() > 1 && self.is_infix(&terms[0]) {
+            self.section(item_id, terms, true)
+        } else if terms.len() > 1 && self.is_infix(&terms[terms.len() - 1]) {
+            self.section(item_id, terms, false)
+        } else {
+            self.unflatten_exprs(item_id, terms)
+        }
+    }
+
+    fn unflatten_exprs(
+        &mut self,
+        item_id: ItemId,
+        terms: Vec<resolved::Expr<'a, 'src>>,
+    ) -> resolved::Expr<'a, 'src> {
         match Precedencer::new(self, self.alloc, item_id).unflatten(terms) {
             OneOrMany::Single(expr) => expr,
             OneOrMany::Many(fun, args) => Precedencer::prefixes(self.alloc, item_id, fun, args),
         }
     }
 
+    fn is_infix(&self, expr: &resolved::Expr) -> bool {
+        match &expr.node {
+            resolved::ExprNode::Var(name) => {
+                matches!(self.affii.get(name), Some(Affix::Infix(_)))
+            }
+
+            _ => false,
+        }
+    }
+
+    /// Create a lambda which puts its argument in place of the operand missing
+    /// at the start of the run if `first`, or at the end of it otherwise. The
+    /// run is then unflattened as usual, and the section operator must end up
+    /// as the outermost one: `(+ 1 * 2)` becomes `x => x + 1 * 2`, while
+    /// `(* 2 + 1)` and `(- 1 - 2)` are reported, since they are not sections
+    /// of `*` and `-`.
+    fn section(
+        &mut self,
+        item_id: ItemId,
+        mut terms: Vec<resolved::Expr<'a, 'src>>,
+        first: bool,
+    ) -> resolved::Expr<'a, 'src> {
+        let operator = if first {
+            &terms[0]
+        } else {
+            &terms[terms.len() - 1]
+        };
+
+        let resolved::ExprNode::Var(name) = operator.node else {
+            unreachable!("sections begin or end with an infix operator");
+        };
+
+        let span = operator.span;
+        let whole = terms[0].span + terms[terms.len() - 1].span;
+
+        self.scope(None, |this| {
+            let ident = this.names.intern("x");
+            let param = this.define_value(item_id, span, Affix::Prefix, ident, Namekind::Value);
+            let (param, arg) = match param {
+                Ok(name) => (
+                    resolved::PatternNode::Bind(name),
+                    resolved::ExprNode::Var(name),
+                ),
+
+                Err(e) => (
+                    resolved::PatternNode::Invalid(e),
+                    resolved::ExprNode::Invalid(e),
+                ),
+            };
+
+            let param = resolved::Pattern { node: param, span };
+            let arg = resolved::Expr { node: arg, span };
+
+            if first {
+                terms.insert(0, arg);
+            } else {
+                terms.push(arg);
+            }
+
+            let body = this.unflatten_exprs(item_id, terms);
+
+            if let Some((other, at)) = outermost(&body) {
+                if at != span {
+                    let name = this.names.get_name(&name).name.name();
+                    let other = this.names.get_name(&other).name.name();
+                    let error = this
+                        .errors
+                        .parse_error(whole)
+                        .section_not_outermost(at, name, other);
+
+                    let node = resolved::ExprNode::Invalid(error);
+                    return resolved::Expr { node, span: whole };
+                }
+            }
+
+            let span = body.span;
+            let node = resolved::ExprNode::Lambda(this.alloc.alloc([(param, body)]));
+            resolved::Expr { node, span }
+        })
+    }
+
     pub(super) fn apply_pattern_run(
         &mut self,
+        ns: Namespace,
         item_id: ItemId,
         gen_scope: &mut BTreeMap<Ident<'src>, Name>,
         terms: &'scratch [parsed::Pattern<'scratch, 'src>],
@@ -46,7 +161,7 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
 
         let terms: Vec<_> = terms
             .iter()
-            .map(|pattern| self.single_pattern(item_id, gen_scope, pattern))
+            .map(|pattern| self.single_pattern(ns, item_id, gen_scope, pattern))
             .collect();
 
         match Precedencer::new(self, self.scratch, item_id).unflatten(terms) {
@@ -86,11 +201,37 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
     }
 }
 
+/// Get the outermost infix operator of an unflattened run, along with where it
+/// occurs.
+fn outermost(expr: &resolved::Expr) -> Option<(Name, Span)> {
+    let resolved::ExprNode::Apply([fun, _]) = &expr.node else {
+        return None;
+    };
+
+    let resolved::ExprNode::Apply([op, _]) = &fun.node else {
+        return None;
+    };
+
+    match op.node {
+        resolved::ExprNode::Var(name) => Some((name, op.span)),
+        _ => None,
+    }
+}
+
+/// Unflattens a run of terms into a tree of applications. Prefix application
+/// binds the tightest, followed by postfix operators, which apply to the term
+/// right before them. Infix operators are then combined according to their
+/// [`Fixity`] using the shunting-yard algorithm.
 struct Precedencer<'a, 'scratch, 'src, 'err, 'resolver, 'alloc, A> {
     resolver: &'resolver mut Resolver<'a, 'scratch, 'src, 'err>,
     alloc: &'alloc Bump,
     item_id: ItemId,
-    infix: Option<(Vec<A>, A, Name)>,
+
+    /// Completed operands which are waiting for their operators.
+    operands: Vec<A>,
+    /// Infix operators which are waiting for their right-hand side.
+    operators: Vec<(A, Name, Fixity)>,
+    /// The run of prefix applications currently being built.
     exprs: Vec<A>,
 }
 
@@ -108,7 +249,8 @@ where
             resolver,
             alloc,
             item_id,
-            infix: None,
+            operands: Vec::new(),
+            operators: Vec::new(),
             exprs: Vec::new(),
         }
     }
@@ -118,29 +260,32 @@ where
             self.term(term);
         }
 
-        if let Some((mut lhs, op, name)) = self.infix {
-            let lhs = {
-                let fun = lhs.remove(0);
-                Self::prefixes(self.alloc, self.item_id, fun, lhs)
-            };
-
-            let rhs = if self.exprs.is_empty() {
-                let span = op.span();
-                let name = self.resolver.names.get_name(&name);
-                let name = name.name.name();
-                let error = self.resolver.errors.parse_error(span).infix_function(name);
-                A::invalid(self.item_id, error, span)
-            } else {
-                let fun = self.exprs.remove(0);
-                Self::prefixes(self.alloc, self.item_id, fun, self.exprs)
-            };
+        let Some((op, name, _)) = self.operators.last() else {
+            let fun = self.exprs.remove(0);
+            return OneOrMany::Many(fun, self.exprs);
+        };
 
-            let fun = A::apply(self.alloc, self.item_id, op, lhs);
-            OneOrMany::Single(A::apply(self.alloc, self.item_id, fun, rhs))
+        let rhs = if self.exprs.is_empty() {
+            let span = op.span();
+            let name = self.resolver.names.get_name(name);
+            let name = name.name.name();
+            let error = self.resolver.errors.parse_error(span).infix_function(name);
+            A::invalid(self.item_id, error, span)
         } else {
             let fun = self.exprs.remove(0);
-            OneOrMany::Many(fun, self.exprs)
+            let args = std::mem::take(&mut self.exprs);
+            Self::prefixes(self.alloc, self.item_id, fun, args)
+        };
+
+        self.operands.push(rhs);
+
+        while !self.operators.is_empty() {
+            self.reduce();
         }
+
+        let result = self.operands.pop();
+        debug_assert!(self.operands.is_empty());
+        OneOrMany::Single(result.expect("every operator has two operands"))
     }
 
     fn term(&mut self, term: A) {
@@ -148,7 +293,7 @@ where
         if let Some(name) = name {
             match self.resolver.affii.get(&name) {
                 Some(&Affix::Postfix) => self.postfix_term(term, name),
-                Some(&Affix::Infix) => self.infix_term(term, name),
+                Some(&Affix::Infix(fixity)) => self.infix_term(term, name, fixity),
                 _ => self.exprs.push(term),
             }
         } else {
@@ -156,27 +301,58 @@ where
         }
     }
 
-    fn infix_term(&mut self, term: A, name: Name) {
-        if let Some((_, op, _)) = self.infix.as_ref() {
-            let span = term.span();
-            let error = self
-                .resolver
-                .errors
-                .parse_error(span)
-                .ambiguous_infix_operators(op.span());
-            let expr = A::invalid(self.item_id, error, span);
-            self.exprs.push(expr);
-        } else if self.exprs.is_empty() {
+    fn infix_term(&mut self, mut term: A, name: Name, fixity: Fixity) {
+        if self.exprs.is_empty() {
             let span = term.span();
             let name = self.resolver.names.get_name(&name);
             let name = name.name.name();
             let error = self.resolver.errors.parse_error(span).infix_function(name);
             let expr = A::invalid(self.item_id, error, span);
             self.exprs.push(expr);
-        } else {
-            let lhs = std::mem::take(&mut self.exprs);
-            self.infix = Some((lhs, term, name));
+            return;
+        }
+
+        let fun = self.exprs.remove(0);
+        let args = std::mem::take(&mut self.exprs);
+        let lhs = Self::prefixes(self.alloc, self.item_id, fun, args);
+        self.operands.push(lhs);
+
+        while let Some((prev, prev_name, prev_fixity)) = self.operators.last() {
+            if prev_fixity.precedence < fixity.precedence {
+                break;
+            }
+
+            if prev_fixity.precedence == fixity.precedence {
+                match (prev_fixity.assoc, fixity.assoc) {
+                    (Associativity::Left, Associativity::Left) => {}
+                    (Associativity::Right, Associativity::Right) => break,
+
+                    // Report the ambiguity on the later operator, but keep the
+                    // tree shaped as if it associated to the left.
+                    _ if !term.is_invalid() => {
+                        let span = term.span();
+                        let prev_name = self.resolver.names.get_name(prev_name);
+                        let name = self.resolver.names.get_name(&name);
+                        let error = self
+                            .resolver
+                            .errors
+                            .parse_error(span)
+                            .ambiguous_infix_operators(
+                                prev.span(),
+                                prev_name.name.name(),
+                                name.name.name(),
+                            );
+                        term = A::invalid(self.item_id, error, span);
+                    }
+
+                    _ => {}
+                }
+            }
+
+            self.reduce();
         }
+
+        self.operators.push((term, name, fixity));
     }
 
     fn postfix_term(&mut self, term: A, name: Name) {
@@ -197,6 +373,17 @@ where
         }
     }
 
+    /// Apply the topmost operator to the two topmost operands.
+    fn reduce(&mut self) {
+        let (op, _, _) = self.operators.pop().expect("reduce with an operator");
+        let rhs = self.operands.pop().expect("infix operators have a rhs");
+        let lhs = self.operands.pop().expect("infix operators have a lhs");
+
+        let fun = A::apply(self.alloc, self.item_id, op, lhs);
+        let expr = A::apply(self.alloc, self.item_id, fun, rhs);
+        self.operands.push(expr);
+    }
+
     fn prefixes(alloc: &'alloc Bump, item_id: ItemId, mut fun: A, args: Vec<A>) -> A {
         for arg in args {
             fun = A::apply(alloc, item_id, fun, arg);
@@ -212,6 +399,7 @@ trait Affixable<'a>: Sized {
 
     fn name(self) -> (Self, Option<Name>);
 
+    fn is_invalid(&self) -> bool;
     fn span(&self) -> Span;
 }
 
@@ -236,6 +424,10 @@ impl<'a, 'src> Affixable<'a> for resolved::Expr<'a, 'src> {
         }
     }
 
+    fn is_invalid(&self) -> bool {
+        matches!(self.node, resolved::ExprNode::Invalid(_))
+    }
+
     fn span(&self) -> Span {
         self.span
     }
@@ -270,6 +462,10 @@ impl<'a, 'src> Affixable<'a> for declared::spined::Pattern<'a, 'src> {
         }
     }
 
+    fn is_invalid(&self) -> bool {
+        matches!(self.node, declared::spined::PatternNode::Invalid(_))
+    }
+
     fn span(&self) -> Span {
         self.span
     }
@@ -296,6 +492,10 @@ impl<'a, 'src> Affixable<'a> for resolved::Type<'a, 'src> {
         }
     }
 
+    fn is_invalid(&self) -> bool {
+        matches!(self.node, resolved::TypeNode::Invalid(_))
+    }
+
     fn span(&self) -> Span {
         self.span
     }
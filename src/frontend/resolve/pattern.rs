@@ -23,18 +23,23 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
             | resolved::PatternNode::And([a, b]) => {
                 Resolver::name_of(a).or_else(|| Resolver::name_of(b))
             }
+
+            resolved::PatternNode::Record(fields) => fields
+                .iter()
+                .find_map(|(_, _, pattern)| Resolver::name_of(pattern)),
         }
     }
 
     pub fn function_spine(
         &mut self,
+        ns: Namespace,
         item_id: ItemId,
         gen_scope: &mut BTreeMap<Ident<'src>, Name>,
         pattern: &'scratch parsed::Pattern<'scratch, 'src>,
     ) -> declared::Spine<'scratch, 'src, declared::spined::Pattern<'scratch, 'src>> {
         match &pattern.node {
             parsed::PatternNode::Anno(pattern, ty) => {
-                match self.function_spine(item_id, gen_scope, pattern) {
+                match self.function_spine(ns, item_id, gen_scope, pattern) {
                     declared::Spine::Fun {
                         head,
                         args,
@@ -77,13 +82,16 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
                 }
             }
 
-            parsed::PatternNode::Apply(terms) => self.apply_pattern_run(item_id, gen_scope, terms),
-            _ => declared::Spine::Single(self.single_pattern(item_id, gen_scope, pattern)),
+            parsed::PatternNode::Apply(terms) => {
+                self.apply_pattern_run(ns, item_id, gen_scope, terms)
+            }
+            _ => declared::Spine::Single(self.single_pattern(ns, item_id, gen_scope, pattern)),
         }
     }
 
     pub fn single_pattern(
         &mut self,
+        ns: Namespace,
         item_id: ItemId,
         gen_scope: &mut BTreeMap<Ident<'src>, Name>,
         pattern: &'scratch parsed::Pattern<'scratch, 'src>,
@@ -95,7 +103,14 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
             parsed::PatternNode::Unit => declared::spined::PatternNode::Unit,
 
             parsed::PatternNode::Bind(name) => {
-                if let Some((name, Namekind::Pattern)) = self.lookup_value(&name.1) {
+                // Constructors only live in the value namespace
+                let constructor = match ns {
+                    Namespace::Type => None,
+                    Namespace::Value => self.lookup_value(&name.1),
+                };
+
+                if let Some((name, Namekind::Pattern)) = constructor {
+                    self.refs.insert(span, name);
                     declared::spined::PatternNode::Constructor(name)
                 } else {
                     declared::spined::PatternNode::Bind(*name)
@@ -105,19 +120,19 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
             parsed::PatternNode::Anno(pattern, ty) => {
                 let pattern = self
                     .scratch
-                    .alloc(self.single_pattern(item_id, gen_scope, pattern));
+                    .alloc(self.single_pattern(ns, item_id, gen_scope, pattern));
                 declared::spined::PatternNode::Anno(pattern, ty)
             }
 
             parsed::PatternNode::Group(pattern) => {
                 let pattern = self
                     .scratch
-                    .alloc(self.single_pattern(item_id, gen_scope, pattern));
+                    .alloc(self.single_pattern(ns, item_id, gen_scope, pattern));
                 declared::spined::PatternNode::Group(pattern)
             }
 
             parsed::PatternNode::Apply(terms) => {
-                match self.apply_pattern_run(item_id, gen_scope, terms) {
+                match self.apply_pattern_run(ns, item_id, gen_scope, terms) {
                     declared::Spine::Single(pattern) => return pattern,
                     declared::Spine::Fun { .. } => {
                         let e = self
@@ -130,19 +145,30 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
             }
 
             parsed::PatternNode::Or([a, b]) => {
-                let a = self.single_pattern(item_id, gen_scope, a);
-                let b = self.single_pattern(item_id, gen_scope, b);
+                let a = self.single_pattern(ns, item_id, gen_scope, a);
+                let b = self.single_pattern(ns, item_id, gen_scope, b);
                 let terms = self.scratch.alloc([a, b]);
                 declared::spined::PatternNode::Or(terms)
             }
 
             parsed::PatternNode::And([a, b]) => {
-                let a = self.single_pattern(item_id, gen_scope, a);
-                let b = self.single_pattern(item_id, gen_scope, b);
+                let a = self.single_pattern(ns, item_id, gen_scope, a);
+                let b = self.single_pattern(ns, item_id, gen_scope, b);
                 let terms = self.scratch.alloc([a, b]);
                 declared::spined::PatternNode::And(terms)
             }
 
+            parsed::PatternNode::Record(fields) => {
+                let fields = self.scratch.alloc_slice_fill_iter(fields.iter().map(
+                    |(label, span, pattern)| {
+                        let pattern = self.single_pattern(ns, item_id, gen_scope, pattern);
+                        (*label, *span, pattern)
+                    },
+                ));
+
+                declared::spined::PatternNode::Record(fields)
+            }
+
             parsed::PatternNode::Constructor(v) => match *v {},
         };
 
@@ -292,6 +318,25 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
                 let terms = self.alloc.alloc([a, b]);
                 (resolved::PatternNode::And(terms), a_names)
             }
+
+            declared::spined::PatternNode::Record(fields) => {
+                let mut names = BTreeMap::new();
+                let fields = self.alloc.alloc_slice_fill_iter(fields.iter().map(
+                    |(label, span, pattern)| {
+                        let (pattern, field_names) =
+                            self.declare_pattern(ns, gen_scope, pattern, known);
+
+                        for (ident, name) in field_names {
+                            let prev = names.insert(ident, name);
+                            debug_assert!(prev.is_none());
+                        }
+
+                        (*label, *span, pattern)
+                    },
+                ));
+
+                (resolved::PatternNode::Record(fields), names)
+            }
         };
 
         (resolved::Pattern { node, span }, names)
@@ -19,6 +19,18 @@ impl<'a, 'scratch, 'src, 'err> Resolver<'a, 'scratch, 'src, 'err> {
                 let body = self.constructor_data(id, body);
                 declared::patterns::ItemNode::Data(pattern, body)
             }
+
+            parsed::ItemNode::Alias(pattern, body) => {
+                declared::patterns::ItemNode::Alias(pattern, body)
+            }
+
+            parsed::ItemNode::Class(pattern, methods) => {
+                declared::patterns::ItemNode::Class(pattern, methods)
+            }
+
+            parsed::ItemNode::Instance(head, body, ()) => {
+                declared::patterns::ItemNode::Instance(head, body, ())
+            }
         };
 
         declared::patterns::Item { node, span, id }
@@ -1,8 +1,9 @@
 use std::collections::BTreeMap;
 
 use crate::frontend::errors::ErrorId;
-use crate::frontend::names::Name;
+use crate::frontend::names::{Ident, Name};
 use crate::frontend::resolve::Namespace;
+use crate::frontend::source::Span;
 use crate::frontend::trees::{declared, parsed, resolved};
 
 use super::{ItemId, Resolver};
@@ -51,6 +52,8 @@ impl<'a, 'scratch, 'src, 'err> Resolver<'a, 'scratch, 'src, 'err> {
                         let pattern = resolved::DataPattern {
                             name: self.resolve_data_pattern_name(&pattern),
                             args: self.alloc.alloc([]),
+                            kinds: self.alloc.alloc([]),
+                            anno: None,
                         };
 
                         let body = self.resolve_data(id, body);
@@ -58,39 +61,127 @@ impl<'a, 'scratch, 'src, 'err> Resolver<'a, 'scratch, 'src, 'err> {
                     }
 
                     declared::Spine::Fun { head, args, anno } => {
-                        let name = if let Some(anno) = anno {
-                            Err(self
-                                .errors
-                                .parse_error(anno.span)
-                                .kind_annotations_unsupported())
-                        } else {
-                            self.resolve_data_pattern_name(&head)
-                        };
+                        let name = self.resolve_data_pattern_name(&head);
+                        let anno = anno.map(|anno| self.resolve_kind(anno));
 
-                        let (args, body) = self.scope(name.ok(), |this| {
-                            let args =
-                                this.alloc
-                                    .alloc_slice_fill_iter(args.into_iter().map(|pattern| {
-                                        let mut gen_scope = BTreeMap::new();
-                                        let pattern =
-                                            this.pattern(Namespace::Type, &mut gen_scope, &pattern);
-                                        this.resolve_data_pattern_name(&pattern)
-                                    }));
+                        let (args, kinds, body) = self.scope(name.ok(), |this| {
+                            let (args, kinds) = this.type_params(&args);
 
                             this.explicit_universals
                                 .extend(args.iter().flat_map(|name| name.ok()));
 
                             let body = this.resolve_data(id, body);
-                            (&*args, body)
+                            (args, kinds, body)
                         });
 
-                        let pattern = resolved::DataPattern { name, args };
+                        let pattern = resolved::DataPattern {
+                            name,
+                            args,
+                            kinds,
+                            anno,
+                        };
+
                         (pattern, body)
                     }
                 };
 
                 resolved::ItemNode::Data(pattern, body)
             }
+
+            declared::ItemNode::Alias(spine, body) => {
+                let (pattern, body) = match spine {
+                    declared::Spine::Single(pattern) => {
+                        let pattern = resolved::DataPattern {
+                            name: self.resolve_data_pattern_name(&pattern),
+                            args: self.alloc.alloc([]),
+                            kinds: self.alloc.alloc([]),
+                            anno: None,
+                        };
+
+                        let body = self.resolve_alias(id, BTreeMap::new(), body);
+                        (pattern, body)
+                    }
+
+                    declared::Spine::Fun { head, args, anno } => {
+                        let name = self.resolve_data_pattern_name(&head);
+                        let anno = anno.map(|anno| self.resolve_kind(anno));
+
+                        let (args, kinds, body) = self.scope(name.ok(), |this| {
+                            let (args, kinds) = this.type_params(&args);
+
+                            // Ticked parameters like `'a` are referred to as
+                            // universals, while normal ones are looked up like
+                            // any other type name.
+                            let mut params = BTreeMap::new();
+                            for name in args.iter().flat_map(|name| name.ok()) {
+                                let ident = this.names.get_name(&name).name;
+                                if ident.name().starts_with('\'') {
+                                    params.insert(ident, name);
+                                } else {
+                                    this.explicit_universals.insert(name);
+                                }
+                            }
+
+                            let body = this.resolve_alias(id, params, body);
+                            (args, kinds, body)
+                        });
+
+                        let pattern = resolved::DataPattern {
+                            name,
+                            args,
+                            kinds,
+                            anno,
+                        };
+
+                        (pattern, body)
+                    }
+                };
+
+                resolved::ItemNode::Alias(pattern, body)
+            }
+
+            declared::ItemNode::Class(spine, methods) => {
+                let (head, args, anno) = match spine {
+                    declared::Spine::Single(pattern) => (pattern, Vec::new(), None),
+                    declared::Spine::Fun { head, args, anno } => (head, args, anno),
+                };
+
+                let name = self.resolve_data_pattern_name(&head);
+                let anno = anno.map(|anno| self.resolve_kind(anno));
+
+                let (args, kinds, methods) = self.scope(name.ok(), |this| {
+                    let (args, kinds) = this.type_params(&args);
+
+                    this.explicit_universals
+                        .extend(args.iter().flat_map(|name| name.ok()));
+
+                    let methods = this.alloc.alloc_slice_fill_iter(
+                        methods
+                            .into_iter()
+                            .map(|(name, span, ty)| this.resolve_method(id, name, span, ty)),
+                    );
+
+                    (args, kinds, methods)
+                });
+
+                let pattern = resolved::DataPattern {
+                    name,
+                    args,
+                    kinds,
+                    anno,
+                };
+
+                resolved::ItemNode::Class(pattern, methods)
+            }
+
+            declared::ItemNode::Instance((name, head), body, mut this_scope) => {
+                self.scope(Some(name), |this| {
+                    let head = this.resolve_type(id, &mut this_scope, head);
+                    let body = this.expr(id, &mut this_scope, body);
+                    let scope = this.alloc.alloc_slice_fill_iter(this_scope.into_values());
+                    resolved::ItemNode::Instance((name, head), body, scope)
+                })
+            }
         };
 
         resolved::Item { id, node, span }
@@ -110,6 +201,7 @@ impl<'a, 'scratch, 'src, 'err> Resolver<'a, 'scratch, 'src, 'err> {
             | resolved::PatternNode::Constructor(_)
             | resolved::PatternNode::Anno(_, _)
             | resolved::PatternNode::Apply(_)
+            | resolved::PatternNode::Record(_)
             | resolved::PatternNode::Or(_)
             | resolved::PatternNode::And(_) => {
                 let span = pattern.span;
@@ -119,6 +211,90 @@ impl<'a, 'scratch, 'src, 'err> Resolver<'a, 'scratch, 'src, 'err> {
         }
     }
 
+    /// Resolve the parameters of a `data` or `type` item, along with their
+    /// kind annotations.
+    fn type_params(
+        &mut self,
+        params: &[declared::spined::Pattern<'scratch, 'src>],
+    ) -> (
+        &'a [Result<Name, ErrorId>],
+        &'a [Option<resolved::Kind<'a>>],
+    ) {
+        let (names, kinds): (Vec<_>, Vec<_>) = params
+            .iter()
+            .map(|pattern| self.type_param(pattern))
+            .unzip();
+
+        (
+            self.alloc.alloc_slice_fill_iter(names),
+            self.alloc.alloc_slice_fill_iter(kinds),
+        )
+    }
+
+    fn type_param(
+        &mut self,
+        pattern: &declared::spined::Pattern<'scratch, 'src>,
+    ) -> (Result<Name, ErrorId>, Option<resolved::Kind<'a>>) {
+        match &pattern.node {
+            declared::spined::PatternNode::Group(pattern) => self.type_param(pattern),
+            declared::spined::PatternNode::Anno(pattern, kind) => {
+                let mut gen_scope = BTreeMap::new();
+                let pattern = self.pattern(Namespace::Type, &mut gen_scope, pattern);
+                let name = self.resolve_data_pattern_name(&pattern);
+                (name, Some(self.resolve_kind(kind)))
+            }
+
+            _ => {
+                let mut gen_scope = BTreeMap::new();
+                let pattern = self.pattern(Namespace::Type, &mut gen_scope, pattern);
+                (self.resolve_data_pattern_name(&pattern), None)
+            }
+        }
+    }
+
+    /// Resolve the type of a class method. Every method gets its own scope
+    /// for the universals it implicitly defines.
+    fn resolve_method(
+        &mut self,
+        item: ItemId,
+        name: Result<Name, ErrorId>,
+        span: Span,
+        ty: &'scratch parsed::Type<'scratch, 'src>,
+    ) -> resolved::Method<'a, 'src> {
+        self.scope(None, |this| {
+            let mut gen_scope = BTreeMap::new();
+            let ty = this.resolve_type(item, &mut gen_scope, ty);
+            let scope = this.alloc.alloc_slice_fill_iter(gen_scope.into_values());
+            resolved::Method {
+                name,
+                span,
+                ty,
+                scope,
+            }
+        })
+    }
+
+    /// Resolve the body of a type alias, where `params` contains the ticked
+    /// parameters of the alias. Any other universal type is an error.
+    fn resolve_alias(
+        &mut self,
+        item: ItemId,
+        mut params: BTreeMap<Ident<'src>, Name>,
+        body: &'scratch parsed::Type<'scratch, 'src>,
+    ) -> resolved::Type<'a, 'src> {
+        let count = params.len();
+        let body = self.resolve_type(item, &mut params, body);
+
+        if params.len() > count {
+            let span = body.span;
+            let e = self.errors.name_error(span).implicit_type_var_in_alias();
+            let node = resolved::TypeNode::Invalid(e);
+            resolved::Type { node, span }
+        } else {
+            body
+        }
+    }
+
     fn resolve_data(
         &mut self,
         item: ItemId,
@@ -0,0 +1,312 @@
+use bumpalo::Bump;
+
+use super::{resolve, resolve_with_prelude};
+use crate::frontend::names::Names;
+use crate::frontend::parse::parse;
+use crate::frontend::prelude;
+use crate::frontend::source::{SourceId, Sources};
+use crate::frontend::trees::resolved::{Expr, ExprNode, ItemNode, Pattern, PatternNode};
+
+/// Resolve `source` and render the body of the item `let {name} = ...` with
+/// every application made explicit. Invalid nodes are rendered as `!`.
+fn render(source: &str, name: &str) -> String {
+    let sources = Sources::new();
+    let source = sources.add(source);
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let program = resolve(&names, &alloc, &parsed);
+
+    let body = program
+        .items
+        .iter()
+        .flat_map(|items| items.iter())
+        .find_map(|item| match &item.node {
+            ItemNode::Let(pattern, body, _) => match pattern.node {
+                PatternNode::Bind(bound) if names.get_name(&bound).name.name() == name => {
+                    Some(body)
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .expect("item is defined");
+
+    expr(&names, body)
+}
+
+fn expr(names: &Names, expr_: &Expr) -> String {
+    match &expr_.node {
+        ExprNode::Invalid(_) => "!".into(),
+        ExprNode::Var(name) => names.get_name(name).name.name().into(),
+        ExprNode::Number(number) => number.to_string(),
+        ExprNode::Group(inner) => expr(names, inner),
+        ExprNode::Apply([fun, arg]) => format!("({} {})", expr(names, fun), expr(names, arg)),
+        ExprNode::Let(_, [_, body], _) => expr(names, body),
+        ExprNode::Lambda([(param, body)]) => {
+            format!("({} => {})", pattern(names, param), expr(names, body))
+        }
+        _ => unimplemented!(),
+    }
+}
+
+fn pattern(names: &Names, pattern_: &Pattern) -> String {
+    match &pattern_.node {
+        PatternNode::Invalid(_) => "!".into(),
+        PatternNode::Bind(name) | PatternNode::Constructor(name) => {
+            names.get_name(name).name.name().into()
+        }
+        PatternNode::Group(inner) => pattern(names, inner),
+        PatternNode::Apply([fun, arg]) => {
+            format!("({} {})", pattern(names, fun), pattern(names, arg))
+        }
+        _ => unimplemented!(),
+    }
+}
+
+const OPERATORS: &str = "
+    let infixl 6 + a b = a
+    let infixl 6 - a b = a
+    let infixl 7 * a b = a
+    let infixr 5 ++ a b = a
+    let infixr 6 ~ a b = a
+    let infix 4 == a b = a
+    let postfix ! a = a
+";
+
+#[test]
+fn precedence() {
+    let source = format!("{OPERATORS} let x = 1 + 2 * 3 - 4");
+    assert_eq!("((- ((+ 1) ((* 2) 3))) 4)", render(&source, "x"));
+}
+
+#[test]
+fn associativity() {
+    let source = format!("{OPERATORS} let x = 1 ++ 2 ++ 3 == 4");
+    assert_eq!("((== ((++ 1) ((++ 2) 3))) 4)", render(&source, "x"));
+}
+
+#[test]
+fn application_and_postfix_bind_tighter() {
+    let source = format!("{OPERATORS} let x = 1 2 ! + 3 4");
+    assert_eq!("((+ (1 (! 2))) (3 4))", render(&source, "x"));
+}
+
+#[test]
+fn non_associative_chain() {
+    let source = format!("{OPERATORS} let x = 1 == 2 == 3");
+    assert_eq!("((! ((== 1) 2)) 3)", render(&source, "x"));
+}
+
+#[test]
+fn mixed_associativity() {
+    let source = format!("{OPERATORS} let x = 1 + 2 ~ 3");
+    assert_eq!("((! ((+ 1) 2)) 3)", render(&source, "x"));
+}
+
+#[test]
+fn fixity_is_scoped() {
+    let source = format!(
+        "{OPERATORS} let x = 1 * 2 + 3
+         let y z = let infixr 9 + a b = a in 1 * 2 + 3"
+    );
+
+    assert_eq!("((+ ((* 1) 2)) 3)", render(&source, "x"));
+    assert_eq!("(z => ((* 1) ((+ 2) 3)))", render(&source, "y"));
+}
+
+#[test]
+fn infix_constructors() {
+    let source = "
+        data list a = nil | (infixr 5 ::) a (list a)
+        let x = 1 :: 2 :: nil
+    ";
+
+    assert_eq!("((:: 1) ((:: 2) nil))", render(source, "x"));
+}
+
+#[test]
+fn constructor_patterns() {
+    let source = "
+        data list a = nil | (infixr 5 ::) a (list a)
+        let infixl 6 + a b = a
+        let f = x :: y :: r => x + y
+    ";
+
+    assert_eq!("(((:: x) ((:: y) r)) => ((+ x) y))", render(source, "f"));
+}
+
+#[test]
+fn sections() {
+    let source = format!(
+        "{OPERATORS}
+         let x = (+ 1 * 2)
+         let y = (1 2 ++)
+         let z = (+)"
+    );
+
+    assert_eq!("(x => ((+ x) ((* 1) 2)))", render(&source, "x"));
+    assert_eq!("(x => ((++ (1 2)) x))", render(&source, "y"));
+    assert_eq!("+", render(&source, "z"));
+}
+
+#[test]
+fn section_operators_are_outermost() {
+    let source = format!(
+        "{OPERATORS}
+         let x = (- 1 * 2)
+         let y = (1 * 2 -)
+         let z = (1 - 2 -)
+         let w = (++ 1 ++ 2)"
+    );
+
+    assert_eq!("(x => ((- x) ((* 1) 2)))", render(&source, "x"));
+    assert_eq!("(x => ((- ((* 1) 2)) x))", render(&source, "y"));
+    assert_eq!("(x => ((- ((- 1) 2)) x))", render(&source, "z"));
+    assert_eq!("(x => ((++ x) ((++ 1) 2)))", render(&source, "w"));
+}
+
+#[test]
+fn sections_which_are_not_outermost() {
+    let source = format!(
+        "{OPERATORS}
+         let x = (- 1 - 2)
+         let y = (* 1 + 2)
+         let z = (1 + 2 *)"
+    );
+
+    assert_eq!("!", render(&source, "x"));
+    assert_eq!("!", render(&source, "y"));
+    assert_eq!("!", render(&source, "z"));
+}
+
+#[test]
+fn postfix_is_not_a_section() {
+    let source = format!("{OPERATORS} let x = (1 !)");
+    assert_eq!("(! 1)", render(&source, "x"));
+}
+
+/// Resolve `source` with the prelude, returning the number of errors and the
+/// start of every item from the prelude in the result.
+fn prelude_items(source: &str) -> (usize, Vec<String>) {
+    let sources = Sources::new();
+    let source = sources.add(source);
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let program = resolve_with_prelude(&names, &alloc, prelude::resolved(), &parsed);
+
+    let mut items: Vec<_> = program
+        .items
+        .iter()
+        .flat_map(|items| items.iter())
+        .filter(|item| item.span.source == SourceId::PRELUDE)
+        .map(|item| {
+            let text = &prelude::source().content[item.span.start..item.span.end];
+            text.split_whitespace()
+                .take(2)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+
+    items.sort();
+    (program.errors.num_errors(), items)
+}
+
+#[test]
+fn only_used_prelude_items_are_included() {
+    let (errors, items) = prelude_items("let x = not True");
+    assert_eq!(0, errors);
+    assert_eq!(vec!["data Bool", "let not"], items);
+}
+
+#[test]
+fn programs_shadow_the_prelude() {
+    let (errors, items) = prelude_items(
+        "
+        data Bool = No | Yes
+        let not = No => Yes | Yes => No
+        let x = not No
+        ",
+    );
+
+    assert_eq!(0, errors);
+    assert!(items.is_empty(), "{items:?}");
+}
+
+#[test]
+fn top_level_names_are_documented() {
+    let sources = Sources::new();
+    let source = sources.add(
+        "
+        --| Truth values.
+        data Bool = No | Yes
+
+        --| Negation.
+        let not x = case x | No => Yes | Yes => No end
+
+        let y = not No
+        ",
+    );
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let program = resolve(&names, &alloc, &parsed);
+
+    let mut docs: Vec<_> = program
+        .docs
+        .iter()
+        .map(|(name, doc)| (names.get_name(name).name.name(), doc.as_str()))
+        .collect();
+
+    docs.sort();
+    assert_eq!(
+        vec![
+            ("Bool", "Truth values."),
+            ("No", "Truth values."),
+            ("Yes", "Truth values."),
+            ("not", "Negation."),
+        ],
+        docs
+    );
+}
+
+#[test]
+fn methods_are_documented() {
+    let sources = Sources::new();
+    let source = sources.add(
+        "
+        --| Equality.
+        class Eq a = {
+          --| Whether two things are equal.
+          eq : a -> a -> Bool,
+          ne : a -> a -> Bool
+        }
+        ",
+    );
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let program = resolve(&names, &alloc, &parsed);
+
+    let mut docs: Vec<_> = program
+        .docs
+        .iter()
+        .map(|(name, doc)| (names.get_name(name).name.name(), doc.as_str()))
+        .collect();
+
+    docs.sort();
+    assert_eq!(
+        vec![
+            ("Eq", "Equality."),
+            ("eq", "Whether two things are equal."),
+            ("ne", "Equality."),
+        ],
+        docs
+    );
+}
@@ -19,6 +19,7 @@ impl<'a, 'scratch, 'src> Resolver<'a, 'scratch, 'src, '_> {
 
             i::TypeNode::Named(name) => {
                 if let Some(name) = self.lookup_type(name) {
+                    self.refs.insert(span, name);
                     if self.explicit_universals.contains(&name) {
                         o::TypeNode::Universal(name)
                     } else {
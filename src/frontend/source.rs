@@ -6,6 +6,9 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 pub struct SourceId(usize);
 
 impl SourceId {
+    /// The source of the prelude, which is never handed out by [`Sources`].
+    pub const PRELUDE: SourceId = SourceId(usize::MAX);
+
     pub fn span(&self, start: usize, end: usize) -> Span {
         Span {
             source: *self,
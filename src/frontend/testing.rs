@@ -0,0 +1,40 @@
+//! Helpers for tests of the passes after the frontend, which start from a
+//! program that went through all of it.
+
+use bumpalo::Bump;
+
+use crate::frontend::elaborate::elaborate;
+use crate::frontend::names::Names;
+use crate::frontend::parse::parse;
+use crate::frontend::prelude;
+use crate::frontend::resolve::{resolve, resolve_with_prelude};
+use crate::frontend::source::{Source, Sources};
+use crate::frontend::trees::typed::Program;
+use crate::frontend::tyck;
+
+/// Parse, resolve, check and elaborate the given (well typed) source, with the
+/// prelude in scope if `with_prelude` is true, and pass the program to `f`
+/// along with everything it borrows from.
+pub fn elaborated<T>(
+    source: &str,
+    with_prelude: bool,
+    f: impl for<'a, 'src> FnOnce(&'a Bump, &'a Names<'src>, &'src Source, Program<'a, 'src>) -> T,
+) -> T {
+    let sources = Sources::new();
+    let source = sources.add(source);
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let resolved = if with_prelude {
+        resolve_with_prelude(&names, &alloc, prelude::resolved(), &parsed)
+    } else {
+        resolve(&names, &alloc, &parsed)
+    };
+
+    let typed = tyck::check(&alloc, &names, &resolved);
+    let program = elaborate(&alloc, &names, typed);
+    assert_eq!(0, program.errors.num_errors());
+
+    f(&alloc, &names, &source, program)
+}
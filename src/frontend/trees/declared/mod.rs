@@ -48,6 +48,7 @@ pub(crate) mod spined;
 use std::collections::BTreeMap;
 
 use super::{nodes, parsed, resolved};
+use crate::frontend::errors::ErrorId;
 use crate::frontend::names::{Ident, Name};
 use crate::frontend::resolve::ItemId;
 use crate::frontend::source::Span;
@@ -62,6 +63,13 @@ type Expr<'parsed, 'src> = &'parsed parsed::Expr<'parsed, 'src>;
 type Pattern<'a, 'parsed, 'src> = Spine<'parsed, 'src, resolved::Pattern<'a, 'src>>;
 type TypePattern<'a, 'parsed, 'src> = Spine<'parsed, 'src, resolved::Pattern<'a, 'src>>;
 type Data<'parsed, 'src> = patterns::Data<'parsed, 'src>;
+type AliasBody<'parsed, 'src> = &'parsed parsed::Type<'parsed, 'src>;
+type ClassBody<'parsed, 'src> = Vec<(
+    Result<Name, ErrorId>,
+    Span,
+    &'parsed parsed::Type<'parsed, 'src>,
+)>;
+type InstanceHead<'parsed, 'src> = (Name, &'parsed parsed::Type<'parsed, 'src>);
 type GenScope<'src> = BTreeMap<Ident<'src>, Name>;
 
 pub(crate) type ItemNode<'a, 'parsed, 'src> = nodes::ItemNode<
@@ -69,6 +77,9 @@ pub(crate) type ItemNode<'a, 'parsed, 'src> = nodes::ItemNode<
     Pattern<'a, 'parsed, 'src>,
     TypePattern<'a, 'parsed, 'src>,
     Data<'parsed, 'src>,
+    AliasBody<'parsed, 'src>,
+    ClassBody<'parsed, 'src>,
+    InstanceHead<'parsed, 'src>,
     GenScope<'src>,
 >;
 
@@ -1,4 +1,5 @@
-use crate::frontend::names::Name;
+use crate::frontend::errors::ErrorId;
+use crate::frontend::names::{Label, Name};
 use crate::frontend::resolve::ItemId;
 use crate::frontend::source::Span;
 
@@ -25,6 +26,9 @@ pub(crate) type ItemNode<'parsed, 'src> = nodes::ItemNode<
     Pattern<'parsed, 'src>,
     TypePattern<'parsed, 'src>,
     Data<'parsed, 'src>,
+    AliasBody<'parsed, 'src>,
+    ClassBody<'parsed, 'src>,
+    InstanceHead<'parsed, 'src>,
     GenScope,
 >;
 
@@ -36,4 +40,11 @@ pub(crate) type ConstructorNode<'parsed, 'src> =
 type Expr<'parsed, 'src> = &'parsed parsed::Expr<'parsed, 'src>;
 type Pattern<'parsed, 'src> = &'parsed parsed::Pattern<'parsed, 'src>;
 type TypePattern<'parsed, 'src> = &'parsed parsed::Pattern<'parsed, 'src>;
+type AliasBody<'parsed, 'src> = &'parsed parsed::Type<'parsed, 'src>;
+type ClassBody<'parsed, 'src> = &'parsed [(
+    Result<Label<'src>, ErrorId>,
+    Span,
+    parsed::Type<'parsed, 'src>,
+)];
+type InstanceHead<'parsed, 'src> = &'parsed parsed::Type<'parsed, 'src>;
 type GenScope = ();
@@ -16,6 +16,7 @@ pub(crate) struct Pattern<'scratch, 'src> {
 
 pub(crate) type PatternNode<'scratch, 'src> = nodes::PatternNode<
     'scratch,
+    'src,
     Pattern<'scratch, 'src>,
     Type<'scratch, 'src>,
     Var<'src>,
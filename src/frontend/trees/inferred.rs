@@ -9,11 +9,18 @@ use crate::frontend::errors::{ErrorId, Errors};
 use crate::frontend::names::Name;
 use crate::frontend::resolve::ItemId;
 use crate::frontend::source::Span;
-use crate::frontend::tyck::{Generic, Scheme, Type};
+use crate::frontend::tyck::{DictVar, Generic, Scheme, Type, TypeArg};
 
 pub struct Program<'a, 'src> {
     pub items: &'a [&'a [Item<'a, 'src>]],
     pub defs: BTreeMap<Name, Span>,
+
+    /// The name referred to by every use of a name, keyed by its span.
+    pub refs: BTreeMap<Span, Name>,
+
+    /// The documentation of every documented top-level name.
+    pub docs: BTreeMap<Name, String>,
+
     pub errors: Errors,
     pub unattached: Vec<(ErrorId, Span)>,
 }
@@ -41,12 +48,22 @@ pub struct Constructor<'a> {
 }
 
 /// A pattern with a generalized type.
+#[derive(Clone)]
 pub struct PolyPattern<'a> {
     pub node: PolyPatternNode<'a>,
     pub span: Span,
     pub scheme: Scheme<'a>,
 }
 
+/// A variable, along with the arguments its scheme is instantiated with and a
+/// placeholder for the dictionary passed for each predicate in its scheme.
+#[derive(Clone, Copy)]
+pub struct Var<'a> {
+    pub name: Name,
+    pub args: &'a [TypeArg<'a>],
+    pub dicts: &'a [DictVar],
+}
+
 /// A pattern with a not yet generalized type.
 pub struct MonoPattern<'a> {
     pub node: MonoPatternNode<'a>,
@@ -56,14 +73,30 @@ pub struct MonoPattern<'a> {
 
 type TypeSyntax = Infallible;
 type TypePattern<'a> = Scheme<'a>;
+type AliasBody<'a> = &'a Type<'a>;
+type ClassBody<'a> = &'a [(Name, Scheme<'a>)];
 type ConstructorName = Name;
 type ApplyExpr<'a, 'src> = &'a [Expr<'a, 'src>; 2];
 type ApplyPolyPattern<'a> = &'a [PolyPattern<'a>; 2];
 type ApplyMonoPattern<'a> = &'a [MonoPattern<'a>; 2];
-type GenScope = ();
 
-pub type ItemNode<'a, 'src> =
-    nodes::ItemNode<Expr<'a, 'src>, PolyPattern<'a>, TypePattern<'a>, Data<'a>, GenScope>;
+/// An instance is the name of its dictionary along with the type of it.
+type InstanceHead<'a> = (Name, Scheme<'a>);
+
+/// The names of the dictionary parameters of a generalized binding, one for
+/// each predicate of its scheme.
+type GenScope<'a> = &'a [Name];
+
+pub type ItemNode<'a, 'src> = nodes::ItemNode<
+    Expr<'a, 'src>,
+    PolyPattern<'a>,
+    TypePattern<'a>,
+    Data<'a>,
+    AliasBody<'a>,
+    ClassBody<'a>,
+    InstanceHead<'a>,
+    GenScope<'a>,
+>;
 
 pub type ExprNode<'a, 'src> = nodes::ExprNode<
     'a,
@@ -71,12 +104,13 @@ pub type ExprNode<'a, 'src> = nodes::ExprNode<
     Expr<'a, 'src>,
     PolyPattern<'a>,
     TypeSyntax,
-    Name,
+    Var<'a>,
     ApplyExpr<'a, 'src>,
-    GenScope,
+    GenScope<'a>,
 >;
 
 pub type PolyPatternNode<'a> = nodes::PatternNode<
+    'a,
     'a,
     PolyPattern<'a>,
     TypeSyntax,
@@ -86,6 +120,7 @@ pub type PolyPatternNode<'a> = nodes::PatternNode<
 >;
 
 pub type MonoPatternNode<'a> = nodes::PatternNode<
+    'a,
     'a,
     MonoPattern<'a>,
     TypeSyntax,
@@ -106,5 +141,13 @@ pub(crate) struct BoundItem<'a, E> {
 
 type BoundGenScope<'a> = &'a [Generic];
 
-pub(crate) type BoundItemNode<'a, E> =
-    nodes::ItemNode<E, MonoPattern<'a>, TypePattern<'a>, Data<'a>, BoundGenScope<'a>>;
+pub(crate) type BoundItemNode<'a, E> = nodes::ItemNode<
+    E,
+    MonoPattern<'a>,
+    TypePattern<'a>,
+    Data<'a>,
+    AliasBody<'a>,
+    ClassBody<'a>,
+    InstanceHead<'a>,
+    BoundGenScope<'a>,
+>;
@@ -3,3 +3,4 @@ pub mod inferred;
 pub mod nodes;
 pub mod parsed;
 pub mod resolved;
+pub mod typed;
@@ -6,6 +6,11 @@
 //! - `Expr` - expression trees
 //! - `Pattern` - pattern trees
 //! - `Type` - type trees (but not the _semantic objects_ of types, themselves)
+//! - `TypePattern` - the name and parameters of a `data` or `type` item
+//! - `DataBody` - the constructors of a `data` item
+//! - `AliasBody` - the type a `type` item is an alias for
+//! - `ClassBody` - the method signatures of a `class` item
+//! - `InstanceHead` - the class and type an `instance` item is for
 //! - `Var` - a value name
 //! - `Constructor` - a resolved constrcutor name
 //! - `Universal` - a resolved, implicitly defined universal type parameter,
@@ -19,7 +24,16 @@ use crate::frontend::errors::ErrorId;
 use crate::frontend::names::Label;
 use crate::frontend::source::Span;
 
-pub enum ItemNode<Expr, Pattern, DataPattern, DataBody, GenScope> {
+pub enum ItemNode<
+    Expr,
+    Pattern,
+    TypePattern,
+    DataBody,
+    AliasBody,
+    ClassBody,
+    InstanceHead,
+    GenScope,
+> {
     /// Something fishy
     Invalid(ErrorId),
 
@@ -27,7 +41,16 @@ pub enum ItemNode<Expr, Pattern, DataPattern, DataBody, GenScope> {
     Let(Pattern, Expr, GenScope),
 
     /// `data a = t`
-    Data(DataPattern, DataBody),
+    Data(TypePattern, DataBody),
+
+    /// `type a = t`
+    Alias(TypePattern, AliasBody),
+
+    /// `class c a = { m : t }`
+    Class(TypePattern, ClassBody),
+
+    /// `instance c t = { m = x }`
+    Instance(InstanceHead, Expr, GenScope),
 }
 
 pub enum ExprNode<'a, 'src, Expr, Pattern, Type, Name, ApplyExpr, GenScope> {
@@ -76,7 +99,7 @@ pub enum ExprNode<'a, 'src, Expr, Pattern, Type, Name, ApplyExpr, GenScope> {
     Let(Pattern, &'a [Expr; 2], GenScope),
 }
 
-pub enum PatternNode<'a, Pattern, Type, Name, ConstructorName, ApplyPattern> {
+pub enum PatternNode<'a, 'src, Pattern, Type, Name, ConstructorName, ApplyPattern> {
     /// Something fishy.
     Invalid(ErrorId),
 
@@ -101,6 +124,9 @@ pub enum PatternNode<'a, Pattern, Type, Name, ConstructorName, ApplyPattern> {
     /// A pattern application
     Apply(ApplyPattern),
 
+    /// `{ a = x, b = y }`
+    Record(&'a [(Result<Label<'src>, ErrorId>, Span, Pattern)]),
+
     /// Either the first or second pattern.
     Or(&'a [Pattern; 2]),
 
@@ -152,24 +178,30 @@ pub enum ConstructorNode<'a, Name, Type> {
 
 /* Copy and Clone impls ----------------------------------------------------- */
 
-impl<Pattern, Expr, TypePattern, DataBody, GenScope> Copy
-    for ItemNode<Pattern, Expr, TypePattern, DataBody, GenScope>
+impl<Pattern, Expr, TypePattern, DataBody, AliasBody, ClassBody, InstanceHead, GenScope> Copy
+    for ItemNode<Pattern, Expr, TypePattern, DataBody, AliasBody, ClassBody, InstanceHead, GenScope>
 where
     Pattern: Copy,
     Expr: Copy,
     TypePattern: Copy,
     DataBody: Copy,
+    AliasBody: Copy,
+    ClassBody: Copy,
+    InstanceHead: Copy,
     GenScope: Copy,
 {
 }
 
-impl<Pattern, Expr, TypePattern, DataBody, GenScope> Clone
-    for ItemNode<Pattern, Expr, TypePattern, DataBody, GenScope>
+impl<Pattern, Expr, TypePattern, DataBody, AliasBody, ClassBody, InstanceHead, GenScope> Clone
+    for ItemNode<Pattern, Expr, TypePattern, DataBody, AliasBody, ClassBody, InstanceHead, GenScope>
 where
     Pattern: Copy,
     Expr: Copy,
     TypePattern: Copy,
     DataBody: Copy,
+    AliasBody: Copy,
+    ClassBody: Copy,
+    InstanceHead: Copy,
     GenScope: Copy,
 {
     fn clone(&self) -> Self {
@@ -203,7 +235,7 @@ where
 }
 
 impl<Pattern, Type, PatternName, Name, ApplyPattern> Copy
-    for PatternNode<'_, Pattern, Type, PatternName, Name, ApplyPattern>
+    for PatternNode<'_, '_, Pattern, Type, PatternName, Name, ApplyPattern>
 where
     Type: Copy,
     PatternName: Copy,
@@ -213,7 +245,7 @@ where
 }
 
 impl<Pattern, Type, PatternName, Name, ApplyPattern> Clone
-    for PatternNode<'_, Pattern, Type, PatternName, Name, ApplyPattern>
+    for PatternNode<'_, '_, Pattern, Type, PatternName, Name, ApplyPattern>
 where
     Type: Copy,
     PatternName: Copy,
@@ -287,7 +319,16 @@ mod variance_checking {
     use super::{ExprNode, ItemNode, PatternNode, TypeNode};
 
     struct Item<'a, 'src>(
-        ItemNode<Expr<'a, 'src>, Pattern<'a, 'src>, Infallible, Infallible, Infallible>,
+        ItemNode<
+            Expr<'a, 'src>,
+            Pattern<'a, 'src>,
+            Infallible,
+            Infallible,
+            Type<'a, 'src>,
+            Infallible,
+            Type<'a, 'src>,
+            Infallible,
+        >,
     );
 
     struct Expr<'a, 'src>(
@@ -304,7 +345,7 @@ mod variance_checking {
     );
 
     struct Pattern<'a, 'src>(
-        PatternNode<'a, Self, Type<'a, 'src>, Infallible, Infallible, Infallible>,
+        PatternNode<'a, 'src, Self, Type<'a, 'src>, Infallible, Infallible, Infallible>,
     );
 
     struct Type<'a, 'src>(TypeNode<'a, 'src, Self, Infallible, Infallible, Infallible>);
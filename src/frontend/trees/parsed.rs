@@ -1,7 +1,7 @@
 use std::convert::Infallible;
 
 use crate::frontend::errors::{ErrorId, Errors};
-use crate::frontend::names::Ident;
+use crate::frontend::names::{Ident, Label};
 use crate::frontend::source::{SourceId, Span};
 
 use super::nodes;
@@ -16,6 +16,13 @@ pub struct Source<'a, 'src> {
 pub struct Item<'a, 'src> {
     pub node: ItemNode<'a, 'src>,
     pub span: Span,
+
+    /// The text of the documentation comments before the item.
+    pub doc: Option<&'a str>,
+
+    /// The documentation of the methods of a `class` item, keyed by the span
+    /// of their names.
+    pub method_docs: &'a [(Span, &'a str)],
 }
 
 pub struct Expr<'a, 'src> {
@@ -47,6 +54,9 @@ pub struct Constructor<'a, 'src> {
     pub span: Span,
 }
 
+/// The methods of a `class` item are the fields of a record type.
+type ClassBody<'a, 'src> = &'a [(Result<Label<'src>, ErrorId>, Span, Type<'a, 'src>)];
+
 type GenScope = ();
 type Name<'src> = Ident<'src>;
 type PatternVar<'src> = (Affix, Ident<'src>);
@@ -57,8 +67,16 @@ type ApplyExpr<'a, 'src> = &'a [Expr<'a, 'src>];
 type ApplyPattern<'a, 'src> = &'a [Pattern<'a, 'src>];
 type ApplyType<'a, 'src> = &'a [Type<'a, 'src>];
 
-pub type ItemNode<'a, 'src> =
-    nodes::ItemNode<Expr<'a, 'src>, Pattern<'a, 'src>, Pattern<'a, 'src>, Data<'a, 'src>, GenScope>;
+pub type ItemNode<'a, 'src> = nodes::ItemNode<
+    Expr<'a, 'src>,
+    Pattern<'a, 'src>,
+    Pattern<'a, 'src>,
+    Data<'a, 'src>,
+    Type<'a, 'src>,
+    ClassBody<'a, 'src>,
+    Type<'a, 'src>,
+    GenScope,
+>;
 
 pub type ExprNode<'a, 'src> = nodes::ExprNode<
     'a,
@@ -73,6 +91,7 @@ pub type ExprNode<'a, 'src> = nodes::ExprNode<
 
 pub type PatternNode<'a, 'src> = nodes::PatternNode<
     'a,
+    'src,
     Pattern<'a, 'src>,
     Type<'a, 'src>,
     PatternVar<'src>,
@@ -91,6 +110,32 @@ pub type ConstructorNode<'a, 'src> =
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Affix {
     Prefix,
-    Infix,
+    Infix(Fixity),
     Postfix,
 }
+
+/// The precedence and associativity of an infix operator. Operators with a
+/// higher precedence bind more tightly than those with a lower one.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Fixity {
+    pub assoc: Associativity,
+    pub precedence: u8,
+}
+
+impl Fixity {
+    /// The precedence of an infix operator declared without one.
+    pub const DEFAULT_PRECEDENCE: u8 = 9;
+
+    /// The largest precedence an operator may be declared with.
+    pub const MAX_PRECEDENCE: u8 = 9;
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Associativity {
+    /// `infixl`
+    Left,
+    /// `infixr`
+    Right,
+    /// `infix`
+    None,
+}
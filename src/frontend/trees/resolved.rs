@@ -9,6 +9,13 @@ use crate::frontend::source::Span;
 pub struct Program<'a, 'src> {
     pub items: &'a [&'a [Item<'a, 'src>]],
     pub defs: BTreeMap<Name, Span>,
+
+    /// The name referred to by every use of a name, keyed by its span.
+    pub refs: BTreeMap<Span, Name>,
+
+    /// The documentation of every documented top-level name.
+    pub docs: BTreeMap<Name, String>,
+
     pub errors: Errors,
     pub unattached: Vec<(ErrorId, Span)>,
 }
@@ -37,6 +44,41 @@ pub struct Type<'a, 'src> {
 pub struct DataPattern<'a> {
     pub name: Result<Name, ErrorId>,
     pub args: &'a [Result<Name, ErrorId>],
+
+    /// The kind annotation of each argument, if any.
+    pub kinds: &'a [Option<Kind<'a>>],
+
+    /// The kind annotation of the fully applied type, if any.
+    pub anno: Option<Kind<'a>>,
+}
+
+/// A kind annotation, like `Type -> Type`.
+pub struct Kind<'a> {
+    pub node: KindNode<'a>,
+    pub span: Span,
+}
+
+pub enum KindNode<'a> {
+    Invalid(ErrorId),
+
+    /// `Type`
+    Type,
+
+    /// `Row`
+    Row,
+
+    /// `k -> l`
+    Arrow(&'a [Kind<'a>; 2]),
+}
+
+/// A method signature of a `class` item.
+pub struct Method<'a, 'src> {
+    pub name: Result<Name, ErrorId>,
+    pub span: Span,
+    pub ty: Type<'a, 'src>,
+
+    /// The implicitly defined universals of the method type.
+    pub scope: &'a [Name],
 }
 
 pub struct Data<'a, 'src> {
@@ -54,6 +96,8 @@ type Universal = Name;
 type ApplyExpr<'a, 'src> = &'a [Expr<'a, 'src>; 2];
 type ApplyPattern<'a, 'src> = &'a [Pattern<'a, 'src>; 2];
 type ApplyType<'a, 'src> = &'a [Type<'a, 'src>; 2];
+type ClassBody<'a, 'src> = &'a [Method<'a, 'src>];
+type InstanceHead<'a, 'src> = (Name, Type<'a, 'src>);
 type GenScope<'a> = &'a [Name];
 
 pub type ItemNode<'a, 'src> = nodes::ItemNode<
@@ -61,6 +105,9 @@ pub type ItemNode<'a, 'src> = nodes::ItemNode<
     Pattern<'a, 'src>,
     DataPattern<'a>,
     Data<'a, 'src>,
+    Type<'a, 'src>,
+    ClassBody<'a, 'src>,
+    InstanceHead<'a, 'src>,
     GenScope<'a>,
 >;
 
@@ -77,6 +124,7 @@ pub type ExprNode<'a, 'src> = nodes::ExprNode<
 
 pub type PatternNode<'a, 'src> = nodes::PatternNode<
     'a,
+    'src,
     Pattern<'a, 'src>,
     Type<'a, 'src>,
     Name,
@@ -0,0 +1,100 @@
+//! A typed program is an inferred program after the final substitution has
+//! been applied to it. Its types contain no unification variables; every
+//! binding carries its generalized scheme, every expression its fully applied
+//! type, and every variable the arguments its scheme is instantiated with.
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+
+use super::inferred::Data;
+use super::nodes;
+use crate::frontend::errors::Errors;
+use crate::frontend::names::Name;
+use crate::frontend::resolve::ItemId;
+use crate::frontend::source::Span;
+use crate::frontend::tyck::{Dict, DictVar, Scheme, Type, TypeArg};
+
+pub struct Program<'a, 'src> {
+    pub items: &'a [&'a [Item<'a, 'src>]],
+
+    /// The evidence for each predicate of every instantiated scheme.
+    pub dicts: BTreeMap<DictVar, Evidence<'a>>,
+
+    pub errors: Errors,
+}
+
+pub struct Item<'a, 'src> {
+    pub node: ItemNode<'a, 'src>,
+    pub span: Span,
+    pub id: ItemId,
+}
+
+pub struct Expr<'a, 'src> {
+    pub node: ExprNode<'a, 'src>,
+    pub span: Span,
+    pub ty: &'a Type<'a>,
+}
+
+#[derive(Clone)]
+pub struct Pattern<'a> {
+    pub node: PatternNode<'a>,
+    pub span: Span,
+    pub scheme: Scheme<'a>,
+}
+
+/// A variable along with the arguments its scheme is instantiated with, one
+/// for each parameter of the scheme, and the dictionary passed for each
+/// predicate of its scheme.
+#[derive(Clone, Copy)]
+pub struct Var<'a> {
+    pub name: Name,
+    pub args: &'a [TypeArg<'a>],
+    pub dicts: &'a [DictVar],
+}
+
+/// The dictionary solving a predicate, along with the type of the dictionary.
+/// The dictionary of an instance is polymorphic if the type of the instance
+/// has parameters, in which case `args` are the arguments it is instantiated
+/// with.
+#[derive(Clone, Copy)]
+pub struct Evidence<'a> {
+    pub dict: Dict,
+    pub args: &'a [TypeArg<'a>],
+    pub ty: &'a Type<'a>,
+}
+
+type TypeSyntax = Infallible;
+type TypePattern<'a> = Scheme<'a>;
+type DataBody<'a> = &'a Data<'a>;
+type AliasBody<'a> = &'a Type<'a>;
+type ClassBody<'a> = &'a [(Name, Scheme<'a>)];
+type InstanceHead<'a> = (Name, Scheme<'a>);
+type GenScope<'a> = &'a [Name];
+type ConstructorName = Name;
+type ApplyExpr<'a, 'src> = &'a [Expr<'a, 'src>; 2];
+type ApplyPattern<'a> = &'a [Pattern<'a>; 2];
+
+pub type ItemNode<'a, 'src> = nodes::ItemNode<
+    Expr<'a, 'src>,
+    Pattern<'a>,
+    TypePattern<'a>,
+    DataBody<'a>,
+    AliasBody<'a>,
+    ClassBody<'a>,
+    InstanceHead<'a>,
+    GenScope<'a>,
+>;
+
+pub type ExprNode<'a, 'src> = nodes::ExprNode<
+    'a,
+    'src,
+    Expr<'a, 'src>,
+    Pattern<'a>,
+    TypeSyntax,
+    Var<'a>,
+    ApplyExpr<'a, 'src>,
+    GenScope<'a>,
+>;
+
+pub type PatternNode<'a> =
+    nodes::PatternNode<'a, 'a, Pattern<'a>, TypeSyntax, Name, ConstructorName, ApplyPattern<'a>>;
@@ -0,0 +1,231 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::types::{Generic, Row};
+use super::{Checker, Scheme, Type};
+use crate::frontend::names::Name;
+use crate::frontend::source::Span;
+use crate::frontend::topology;
+use crate::frontend::trees::resolved;
+
+/// A type alias is a list of parameters and a body which may refer to them.
+#[derive(Clone, Debug)]
+pub struct Alias<'a> {
+    pub params: Vec<Generic>,
+    pub body: &'a Type<'a>,
+}
+
+impl<'a, 'err, 'src, 'p> Checker<'a, 'err, 'src, 'p> {
+    /// Lower the bodies of every type alias in a set of mutually recursive
+    /// items. This is done in dependency order, such that every alias is
+    /// expanded in the body of the aliases which refer to it. Aliases which
+    /// refer to themselves (directly or indirectly) are reported as errors,
+    /// since their expansion would never terminate.
+    pub(super) fn check_aliases(&mut self, items: &[resolved::Item<'_, 'src>]) {
+        let aliases: BTreeMap<_, _> = items
+            .iter()
+            .filter_map(|item| match &item.node {
+                resolved::ItemNode::Alias(pattern, body) => {
+                    let name = pattern.name.ok()?;
+                    Some((name, (item.span, pattern, body)))
+                }
+
+                _ => None,
+            })
+            .collect();
+
+        let graph = aliases
+            .iter()
+            .map(|(name, (_, _, body))| {
+                let mut references = BTreeSet::new();
+                alias_references(&mut references, body);
+                references.retain(|name| aliases.contains_key(name));
+                (*name, references)
+            })
+            .collect();
+
+        for component in topology::find(&graph) {
+            let recursive = component.len() > 1
+                || component
+                    .iter()
+                    .any(|name| graph.get(*name).is_some_and(|refs| refs.contains(name)));
+
+            for name in component {
+                let (span, pattern, body) = aliases
+                    .get(name)
+                    .expect("the graph only contains alias names");
+
+                let params: Result<Vec<_>, _> = pattern
+                    .args
+                    .iter()
+                    .map(|arg| arg.map(Generic::Ticked))
+                    .collect();
+
+                let (params, body) = match params {
+                    Ok(_) if recursive => {
+                        let alias = self.names.get_name(name).name;
+                        let e = self.errors.type_error(*span).recursive_alias(alias.name());
+                        (Vec::new(), &*self.alloc.alloc(Type::Invalid(e)))
+                    }
+
+                    Ok(params) => (params, self.lower(body)),
+                    Err(e) => (Vec::new(), &*self.alloc.alloc(Type::Invalid(e))),
+                };
+
+                self.aliases.insert(*name, Alias { params, body });
+            }
+        }
+    }
+
+    /// Get the scheme of the head of an already checked alias, like that of a
+    /// data type, along with its body.
+    pub(super) fn alias_scheme(
+        &mut self,
+        pattern: &resolved::DataPattern,
+    ) -> (Scheme<'a>, &'a Type<'a>) {
+        let alias = match pattern.name {
+            Ok(name) => self.aliases.get(&name).map(|alias| alias.body),
+            Err(_) => None,
+        };
+
+        if let Some(body) = alias {
+            (self.type_pattern(pattern), body)
+        } else {
+            let e = pattern
+                .name
+                .err()
+                .or_else(|| pattern.args.iter().find_map(|arg| arg.err()));
+
+            let e = e.expect("only invalid alias patterns are not checked");
+            let ty = &*self.alloc.alloc(Type::Invalid(e));
+            (Scheme::mono(ty), ty)
+        }
+    }
+
+    /// Expand the alias `name` applied to `args`. Any arguments beyond the
+    /// parameters of the alias are applied to its expansion.
+    pub(super) fn expand_alias(
+        &mut self,
+        span: Span,
+        name: Name,
+        args: &[&resolved::Type<'_, 'src>],
+    ) -> &'a Type<'a> {
+        let alias = self
+            .aliases
+            .get(&name)
+            .expect("only aliases are expanded")
+            .clone();
+
+        let args: Vec<_> = args.iter().map(|arg| self.lower(arg)).collect();
+
+        if args.len() < alias.params.len() {
+            let ident = self.names.get_name(&name).name;
+            let e = self.errors.type_error(span).partial_alias_application(
+                ident.name(),
+                alias.params.len(),
+                args.len(),
+            );
+
+            return self.alloc.alloc(Type::Invalid(e));
+        }
+
+        let (args, extra) = args.split_at(alias.params.len());
+        let subst = alias
+            .params
+            .iter()
+            .copied()
+            .zip(args.iter().copied())
+            .collect();
+        let body = self.substitute(&subst, alias.body);
+
+        let args = self.alloc.alloc_slice_copy(args);
+        let mut ty = &*self.alloc.alloc(Type::Alias(name, args, body));
+
+        for arg in extra {
+            ty = self.alloc.alloc(Type::Apply(ty, arg));
+        }
+
+        ty
+    }
+
+    pub(super) fn substitute(
+        &self,
+        subst: &BTreeMap<Generic, &'a Type<'a>>,
+        ty: &'a Type<'a>,
+    ) -> &'a Type<'a> {
+        match ty {
+            Type::Invalid(_)
+            | Type::Var(..)
+            | Type::Named(_)
+            | Type::Unit
+            | Type::Integer
+            | Type::Arrow => ty,
+
+            Type::Param(generic) => subst.get(generic).copied().unwrap_or(ty),
+
+            Type::Record(row) => {
+                let row = self.substitute_row(subst, row);
+                self.alloc.alloc(Type::Record(row))
+            }
+
+            Type::Variant(row) => {
+                let row = self.substitute_row(subst, row);
+                self.alloc.alloc(Type::Variant(row))
+            }
+
+            Type::Apply(t, u) => {
+                let t = self.substitute(subst, t);
+                let u = self.substitute(subst, u);
+                self.alloc.alloc(Type::Apply(t, u))
+            }
+
+            Type::Alias(name, args, body) => {
+                let args = self
+                    .alloc
+                    .alloc_slice_fill_iter(args.iter().map(|arg| self.substitute(subst, arg)));
+                let body = self.substitute(subst, body);
+                self.alloc.alloc(Type::Alias(*name, args, body))
+            }
+        }
+    }
+
+    fn substitute_row(
+        &self,
+        subst: &BTreeMap<Generic, &'a Type<'a>>,
+        row: &'a Row<'a>,
+    ) -> &'a Row<'a> {
+        match row {
+            Row::Invalid(_) | Row::Empty | Row::Var(..) | Row::Param(_) => row,
+            Row::Extend(label, ty, rest) => {
+                let ty = self.substitute(subst, ty);
+                let rest = self.substitute_row(subst, rest);
+                self.alloc.alloc(Row::Extend(*label, ty, rest))
+            }
+        }
+    }
+}
+
+/// Collect every type name referred to by a type.
+fn alias_references(names: &mut BTreeSet<Name>, ty: &resolved::Type) {
+    match &ty.node {
+        resolved::TypeNode::Invalid(_)
+        | resolved::TypeNode::Wildcard
+        | resolved::TypeNode::Universal(_) => {}
+
+        resolved::TypeNode::Named(name) => {
+            names.insert(*name);
+        }
+
+        resolved::TypeNode::Record(fields) => {
+            for (_, _, ty) in fields.iter() {
+                alias_references(names, ty);
+            }
+        }
+
+        resolved::TypeNode::Group(ty) => alias_references(names, ty),
+
+        resolved::TypeNode::Function([t, u]) | resolved::TypeNode::Apply([t, u]) => {
+            alias_references(names, t);
+            alias_references(names, u);
+        }
+    }
+}
@@ -0,0 +1,419 @@
+//! Type classes are declared with a single parameter and a record of method
+//! signatures, like
+//!
+//! ```nml
+//! class Functor f = { map : ('a -> 'b) -> f 'a -> f 'b }
+//! instance Functor List = { map = ... }
+//! ```
+//!
+//! Every method gets a scheme with the predicate `Functor f` on it, which is
+//! solved when the binding using the method is generalized. An instance is
+//! for a type constructor applied to distinct type variables, and there may be
+//! at most one instance of a class for each type constructor.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::types::{Generic, Predicate};
+use super::{Checker, Row, Scheme, Type};
+use crate::frontend::builtins::Builtin;
+use crate::frontend::errors::ErrorId;
+use crate::frontend::names::{Label, Name};
+use crate::frontend::source::{SourceId, Span};
+use crate::frontend::trees::{inferred, resolved};
+
+/// A class is a type parameter and the methods which refer to it.
+#[derive(Clone, Debug)]
+pub struct Class<'a> {
+    pub param: Generic,
+    pub methods: BTreeMap<Label<'a>, Scheme<'a>>,
+}
+
+/// The type constructor an instance is for.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Head {
+    Named(Name),
+    Integer,
+    Arrow,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Classes<'a> {
+    classes: BTreeMap<Name, Class<'a>>,
+
+    /// The dictionary name and span of every instance by class and head.
+    instances: BTreeMap<(Name, Head), (Name, Span)>,
+
+    /// The class of every instance by its dictionary name, or the error if its
+    /// head is malformed.
+    heads: BTreeMap<Name, Result<Name, ErrorId>>,
+}
+
+impl Classes<'_> {
+    /// Get the dictionary of the instance of `class` for `head`, if any.
+    pub fn instance(&self, class: Name, head: Head) -> Option<Name> {
+        self.instances.get(&(class, head)).map(|(dict, _)| *dict)
+    }
+}
+
+impl<'a, 'err, 'src, 'p> Checker<'a, 'err, 'src, 'p> {
+    /// Register the head of every instance in the program. Since instances
+    /// aren't referred to by name, this is done up front such that a predicate
+    /// can be solved by any instance, regardless of where it is defined.
+    pub(super) fn declare_instances(&mut self, program: &resolved::Program<'_, 'src>) {
+        let items = || program.items.iter().flat_map(|items| items.iter());
+
+        let classes: BTreeSet<_> = items()
+            .filter_map(|item| match &item.node {
+                resolved::ItemNode::Class(pattern, _) => pattern.name.ok(),
+                _ => None,
+            })
+            .collect();
+
+        let aliases: BTreeSet<_> = items()
+            .filter_map(|item| match &item.node {
+                resolved::ItemNode::Alias(pattern, _) => pattern.name.ok(),
+                _ => None,
+            })
+            .collect();
+
+        for item in items() {
+            let resolved::ItemNode::Instance((dict, head), _, _) = &item.node else {
+                continue;
+            };
+
+            // the instances of the prelude were declared when it was checked
+            if self.prelude.is_some() && item.span.source == SourceId::PRELUDE {
+                continue;
+            }
+
+            let class = match self.instance_head(&classes, &aliases, head) {
+                Ok((class, key)) => {
+                    if let Some((_, prev)) = self.classes.instances.get(&(class, key)) {
+                        let name = self.names.get_name(&class).name;
+                        let e = self
+                            .errors
+                            .type_error(head.span)
+                            .overlapping_instances(*prev, name.name());
+                        Err(e)
+                    } else {
+                        self.classes
+                            .instances
+                            .insert((class, key), (*dict, head.span));
+                        Ok(class)
+                    }
+                }
+
+                Err(e) => Err(e),
+            };
+
+            self.classes.heads.insert(*dict, class);
+        }
+    }
+
+    /// Check that an instance head is a class applied to a type constructor,
+    /// which in turn is applied to distinct type variables.
+    fn instance_head(
+        &mut self,
+        classes: &BTreeSet<Name>,
+        aliases: &BTreeSet<Name>,
+        head: &resolved::Type<'_, 'src>,
+    ) -> Result<(Name, Head), ErrorId> {
+        let (class, args) = type_spine(head);
+        let class = match &class.node {
+            resolved::TypeNode::Invalid(e) => return Err(*e),
+            resolved::TypeNode::Named(name) if classes.contains(name) => *name,
+            resolved::TypeNode::Named(name) => {
+                let name = self.names.get_name(name).name;
+                return Err(self.errors.type_error(class.span).not_a_class(name.name()));
+            }
+
+            _ => return Err(self.errors.type_error(class.span).invalid_instance_head()),
+        };
+
+        let [ty] = &args[..] else {
+            let name = self.names.get_name(&class).name;
+            let e = self
+                .errors
+                .type_error(head.span)
+                .class_arguments(name.name(), args.len());
+            return Err(e);
+        };
+
+        let (key, params) = match type_spine(ty) {
+            (
+                resolved::Type {
+                    node: resolved::TypeNode::Invalid(e),
+                    ..
+                },
+                _,
+            ) => return Err(*e),
+
+            (
+                resolved::Type {
+                    node: resolved::TypeNode::Function([t, u]),
+                    ..
+                },
+                params,
+            ) if params.is_empty() => (Head::Arrow, vec![t, u]),
+
+            (
+                resolved::Type {
+                    node: resolved::TypeNode::Named(name),
+                    ..
+                },
+                params,
+            ) => match Builtin::from_name(self.names, name) {
+                Some(Builtin::Integer) => (Head::Integer, params),
+                Some(Builtin::Arrow) => (Head::Arrow, params),
+                None if aliases.contains(name) || classes.contains(name) => {
+                    return Err(self.errors.type_error(ty.span).invalid_instance_head())
+                }
+                None => (Head::Named(*name), params),
+            },
+
+            _ => return Err(self.errors.type_error(ty.span).invalid_instance_head()),
+        };
+
+        let mut seen = BTreeSet::new();
+        for param in params {
+            match &strip_groups(param).node {
+                resolved::TypeNode::Invalid(e) => return Err(*e),
+                resolved::TypeNode::Universal(name) if seen.insert(*name) => {}
+                _ => return Err(self.errors.type_error(param.span).invalid_instance_head()),
+            }
+        }
+
+        Ok((class, key))
+    }
+
+    /// Bind the methods of a class to their schemes. Returns the type of the
+    /// dictionaries of the class, along with the name and scheme of every
+    /// method.
+    pub(super) fn check_class(
+        &mut self,
+        span: Span,
+        pattern: &resolved::DataPattern,
+        methods: &[resolved::Method<'_, 'src>],
+    ) -> (Scheme<'a>, &'a [(Name, Scheme<'a>)]) {
+        let scheme = self.type_pattern(pattern);
+
+        let class = match (pattern.name, &scheme.params[..]) {
+            (Ok(name), [param]) => Ok((name, *param)),
+            (Ok(name), params) => {
+                let ident = self.names.get_name(&name).name;
+                let e = self
+                    .errors
+                    .type_error(span)
+                    .class_parameters(ident.name(), params.len());
+                Err(e)
+            }
+
+            (Err(e), _) => Err(e),
+        };
+
+        let mut schemes = BTreeMap::new();
+        let mut bound = Vec::with_capacity(methods.len());
+
+        for method in methods {
+            let Ok(name) = method.name else {
+                continue;
+            };
+
+            let scheme = match class {
+                Ok((class, param)) => {
+                    let params = std::iter::once(param)
+                        .chain(method.scope.iter().copied().map(Generic::Ticked))
+                        .collect();
+                    let ty = self.alloc.alloc(Type::Param(param));
+                    let preds = vec![Predicate { class, ty }];
+                    let ty = self.lower(&method.ty);
+
+                    if mentions(ty, &param) {
+                        Scheme { params, preds, ty }
+                    } else {
+                        let name = self.names.get_name(&name).name;
+                        let e = self
+                            .errors
+                            .type_error(method.span)
+                            .ambiguous_method(name.name());
+                        Scheme::mono(self.alloc.alloc(Type::Invalid(e)))
+                    }
+                }
+
+                Err(e) => Scheme::mono(self.alloc.alloc(Type::Invalid(e))),
+            };
+
+            let label = Label(self.names.get_name(&name).name);
+            schemes.insert(label, scheme.clone());
+            self.env.insert(name, scheme.clone());
+            bound.push((name, scheme));
+        }
+
+        if let Ok((class, param)) = class {
+            let methods = schemes;
+            self.classes.classes.insert(class, Class { param, methods });
+        }
+
+        (scheme, self.alloc.alloc_slice_fill_iter(bound))
+    }
+
+    /// Get the type of the dictionary of an instance.
+    pub(super) fn instance_scheme(
+        &mut self,
+        dict: &Name,
+        head: &resolved::Type<'_, 'src>,
+        scope: &[Name],
+    ) -> Scheme<'a> {
+        let ty = match self.classes.heads.get(dict) {
+            Some(Ok(_)) => self.lower(head),
+            Some(Err(e)) => self.alloc.alloc(Type::Invalid(*e)),
+            None => unreachable!("all instances are declared before being checked"),
+        };
+
+        Scheme {
+            params: scope.iter().copied().map(Generic::Ticked).collect(),
+            preds: Vec::new(),
+            ty,
+        }
+    }
+
+    /// Check that the body of an instance is a record which defines every
+    /// method of its class with the appropriate type.
+    pub(super) fn check_instance(
+        &mut self,
+        scheme: &Scheme<'a>,
+        body: &resolved::Expr<'_, 'src>,
+    ) -> inferred::Expr<'a, 'src> {
+        let (class, ty) = match scheme.ty {
+            Type::Apply(Type::Named(class), ty) => (*class, *ty),
+            _ => return self.infer(body),
+        };
+
+        let Some(info) = self.classes.classes.get(&class).cloned() else {
+            return self.infer(body);
+        };
+
+        let fields = match &strip_expr_groups(body).node {
+            resolved::ExprNode::Record(fields, None) => fields,
+            _ => {
+                let e = self.errors.type_error(body.span).expected_method_record();
+                let node = inferred::ExprNode::Invalid(e);
+                let ty = &*self.alloc.alloc(Type::Invalid(e));
+                return inferred::Expr {
+                    node,
+                    span: body.span,
+                    ty,
+                };
+            }
+        };
+
+        let class_name = self.names.get_name(&class).name;
+        let subst = BTreeMap::from([(info.param, ty)]);
+        let mut defined = BTreeSet::new();
+
+        let fields = self
+            .alloc
+            .alloc_slice_fill_iter(fields.iter().map(|(label, span, field)| {
+                let field = self.infer(field);
+
+                if let Ok(label) = label {
+                    if let Some(method) = info.methods.get(label) {
+                        if defined.insert(*label) {
+                            let expected = self.substitute(&subst, method.ty);
+                            self.unify(field.span, expected, field.ty);
+                        } else {
+                            let _ = self
+                                .errors
+                                .type_error(*span)
+                                .duplicate_method(label.0.name());
+                        }
+                    } else {
+                        let _ = self
+                            .errors
+                            .type_error(*span)
+                            .unknown_method(class_name.name(), label.0.name());
+                    }
+                }
+
+                (*label, *span, field)
+            }));
+
+        let missing: Vec<_> = info
+            .methods
+            .keys()
+            .filter(|label| !defined.contains(*label))
+            .map(|label| label.0.name())
+            .collect();
+
+        if !missing.is_empty() {
+            let _ = self
+                .errors
+                .type_error(body.span)
+                .missing_methods(class_name.name(), &missing);
+        }
+
+        inferred::Expr {
+            node: inferred::ExprNode::Record(fields, None),
+            span: body.span,
+            ty: scheme.ty,
+        }
+    }
+}
+
+/// Check whether the type parameter `param` occurs in `ty`. The type of
+/// every method must mention the class parameter, since the instance to use
+/// could never be determined otherwise.
+fn mentions(ty: &Type, param: &Generic) -> bool {
+    fn in_row(row: &Row, param: &Generic) -> bool {
+        match row {
+            Row::Extend(_, ty, rest) => mentions(ty, param) || in_row(rest, param),
+            Row::Invalid(_) | Row::Empty | Row::Var(..) | Row::Param(_) => false,
+        }
+    }
+
+    match ty {
+        Type::Invalid(_) => true,
+        Type::Param(other) => other == param,
+        Type::Record(row) | Type::Variant(row) => in_row(row, param),
+        Type::Apply(t, u) => mentions(t, param) || mentions(u, param),
+        Type::Alias(_, _, ty) => mentions(ty, param),
+        Type::Var(..) | Type::Named(_) | Type::Unit | Type::Integer | Type::Arrow => false,
+    }
+}
+
+/// Split a type into its head and the arguments it is applied to.
+fn type_spine<'b, 'src>(
+    ty: &'b resolved::Type<'b, 'src>,
+) -> (
+    &'b resolved::Type<'b, 'src>,
+    Vec<&'b resolved::Type<'b, 'src>>,
+) {
+    let mut args = Vec::new();
+    let mut ty = strip_groups(ty);
+
+    while let resolved::TypeNode::Apply([t, u]) = &ty.node {
+        args.push(u);
+        ty = strip_groups(t);
+    }
+
+    args.reverse();
+    (ty, args)
+}
+
+fn strip_groups<'b, 'src>(mut ty: &'b resolved::Type<'b, 'src>) -> &'b resolved::Type<'b, 'src> {
+    while let resolved::TypeNode::Group(inner) = &ty.node {
+        ty = inner;
+    }
+
+    ty
+}
+
+fn strip_expr_groups<'b, 'src>(
+    mut expr: &'b resolved::Expr<'b, 'src>,
+) -> &'b resolved::Expr<'b, 'src> {
+    while let resolved::ExprNode::Group(inner) = &expr.node {
+        expr = inner;
+    }
+
+    expr
+}
@@ -12,7 +12,7 @@ impl<'a, 'src> Checker<'a, '_, 'src, '_> {
         let span = expr.span;
         let (node, ty) = match &expr.node {
             i::ExprNode::Invalid(e) => self.invalid_expr(e),
-            i::ExprNode::Var(name) => self.var(name),
+            i::ExprNode::Var(name) => self.var(name, span),
             i::ExprNode::Hole => self.hole(span),
             i::ExprNode::Unit => self.unit(),
             i::ExprNode::Number(v) => self.number(v),
@@ -54,12 +54,20 @@ impl<'a, 'src> Checker<'a, '_, 'src, '_> {
     /// ----------------
     /// G => x : inst(T)
     /// ```
-    fn var(&mut self, name: &Name) -> (o::ExprNode<'a, 'src>, &'a Type<'a>) {
+    ///
+    /// Any predicates of `T` are wanted, and are solved when the enclosing
+    /// binding is generalized.
+    fn var(&mut self, name: &Name, span: Span) -> (o::ExprNode<'a, 'src>, &'a Type<'a>) {
         trace!("infer var");
-        let ty = self.instantiate_name(name);
-        let ty = &*self.alloc.alloc(ty);
+        let (ty, args, dicts) = self.instantiate_var(span, name);
         trace!("done var");
-        (o::ExprNode::Var(*name), ty)
+        let var = o::Var {
+            name: *name,
+            args,
+            dicts,
+        };
+
+        (o::ExprNode::Var(var), ty)
     }
 
     /// ```types
@@ -106,17 +114,20 @@ impl<'a, 'src> Checker<'a, '_, 'src, '_> {
     ) -> o::Expr<'a, 'src> {
         trace!("infer anno");
         let expr = self.infer(expr);
-        let ty = self.lower(ty);
+        let ty = self.lower_annotation(ty);
         self.unify(span, ty, expr.ty);
         trace!("done anno");
         expr
     }
 
     /// ```types
-    /// G => e : { f : 'a | r }
-    /// -----------------------
-    ///      G => e.f : 'a
+    /// G => e : { f : 'a | r }    G => e : T    T wraps { f : 'a | r }
+    /// -----------------------    ---------------------------------
+    ///      G => e.f : 'a                   G => e.f : 'a
     /// ```
+    ///
+    /// Fields of nominal records are only accessible if the type of `e` is
+    /// known to be the nominal record at this point.
     fn field(
         &mut self,
         record: &i::Expr<'_, 'src>,
@@ -134,7 +145,8 @@ impl<'a, 'src> Checker<'a, '_, 'src, '_> {
                 let r = self.fresh_row();
                 let record_ty = self.alloc.alloc(Row::Extend(*label, t, r));
                 let record_ty = self.alloc.alloc(Type::Record(record_ty));
-                self.unify(span, record.ty, record_ty);
+                let actual = self.nominal_record(record.ty).unwrap_or(record.ty);
+                self.unify(span, actual, record_ty);
                 (Ok(*label), t)
             }
 
@@ -325,12 +337,16 @@ impl<'a, 'src> Checker<'a, '_, 'src, '_> {
             .alloc
             .alloc_slice_fill_iter(scope.iter().copied().map(Generic::Ticked));
 
-        let pattern = self.generalize_pattern(scope, &pattern);
+        let (patterns, dicts) = self.generalize_patterns(&[(scope, &pattern)]);
+        let pattern = patterns
+            .into_iter()
+            .next()
+            .expect("one pattern is generalized");
 
         trace!("done let");
         let body = self.infer(body);
         let ty = body.ty;
         let terms = self.alloc.alloc([bound, body]);
-        (o::ExprNode::Let(pattern, terms, ()), ty)
+        (o::ExprNode::Let(pattern, terms, dicts), ty)
     }
 }
@@ -1,8 +1,8 @@
 use crate::frontend::errors::ErrorId;
-use crate::frontend::names::Name;
+use crate::frontend::names::{Label, Name};
 use crate::frontend::source::Span;
 use crate::frontend::trees::{inferred as o, resolved as i};
-use crate::frontend::tyck::{Checker, Scheme, Type};
+use crate::frontend::tyck::{Checker, Row, Scheme, Type};
 
 impl<'a, 'src> Checker<'a, '_, 'src, '_> {
     /// When inferring patterns, we also keep track of a set of `wildcards` -
@@ -36,6 +36,7 @@ impl<'a, 'src> Checker<'a, '_, 'src, '_> {
             i::PatternNode::Apply([ctr, arg]) => self.apply_pattern(wildcards, ctr, arg, span),
             i::PatternNode::Or([a, b]) => self.or_pattern(a, b, wildcards, span),
             i::PatternNode::And([a, b]) => self.and_pattern(a, b, wildcards, span),
+            i::PatternNode::Record(fields) => self.record_pattern(fields, wildcards),
 
             i::PatternNode::Group(pattern) => return self.infer_pattern(wildcards, pattern),
         };
@@ -123,7 +124,7 @@ impl<'a, 'src> Checker<'a, '_, 'src, '_> {
         span: Span,
     ) -> o::MonoPattern<'a> {
         let pattern = self.infer_pattern(wildcards, pattern);
-        let ty = self.lower(ty);
+        let ty = self.lower_annotation(ty);
         self.unify(span, ty, pattern.ty);
         pattern
     }
@@ -198,6 +199,49 @@ impl<'a, 'src> Checker<'a, '_, 'src, '_> {
         (o::MonoPatternNode::And(terms), res_ty)
     }
 
+    /// Record patterns only match the given fields, so the rest of the record
+    /// is treated as a wildcard.
+    ///
+    /// ```types
+    ///   G => a1 : t1   ...   G => aN : tN    'r fresh
+    /// ---------------------------------------------------
+    /// G => { f1 = a1, ..., fN = aN } : { f1 : t1, ..., fN : tN | 'r }
+    /// ```
+    fn record_pattern(
+        &mut self,
+        fields: &[(Result<Label<'src>, ErrorId>, Span, i::Pattern<'_, 'src>)],
+        wildcards: &mut Vec<&'a Type<'a>>,
+    ) -> (o::MonoPatternNode<'a>, &'a Type<'a>) {
+        let mut row = self.fresh_row();
+        wildcards.push(self.alloc.alloc(Type::Record(row)));
+
+        let fields = self.alloc.alloc_slice_fill_iter(fields.iter().rev().map(
+            |(label, label_span, pattern)| {
+                let pattern = self.infer_pattern(wildcards, pattern);
+
+                match label {
+                    Ok(label) => {
+                        row = self.alloc.alloc(Row::Extend(*label, pattern.ty, row));
+                        (Ok(*label), *label_span, pattern)
+                    }
+
+                    Err(e) => {
+                        let t = self.alloc.alloc(Type::Invalid(*e));
+                        self.unify(*label_span, pattern.ty, t);
+                        (Err(*e), *label_span, pattern)
+                    }
+                }
+            },
+        ));
+
+        fields.reverse();
+
+        (
+            o::MonoPatternNode::Record(fields),
+            &*self.alloc.alloc(Type::Record(row)),
+        )
+    }
+
     /// Create a fresh type and add it as a wildcard type.
     fn wildcard_type(&mut self, wildcards: &mut Vec<&'a Type<'a>>) -> &'a Type<'a> {
         let ty = self.fresh();
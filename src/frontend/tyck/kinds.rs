@@ -0,0 +1,394 @@
+//! Kinds are the types of types. Every type which can hold values has the kind
+//! `Type`, while type constructors like `List` have arrow kinds, like
+//! `Type -> Type`. The kinds of the parameters of `data` and `type` items are
+//! inferred for each group of mutually recursive items, with any parameter
+//! whose kind is left unconstrained defaulting to `Type`.
+
+use std::collections::BTreeMap;
+
+use super::{Checker, Type};
+use crate::frontend::builtins::Builtin;
+use crate::frontend::errors::ErrorId;
+use crate::frontend::names::Name;
+use crate::frontend::source::Span;
+use crate::frontend::trees::resolved;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Kind<'a> {
+    Type,
+    Row,
+    Var(usize),
+    Arrow(&'a Kind<'a>, &'a Kind<'a>),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Kinds<'a> {
+    /// The kind of every type name and type parameter seen so far.
+    of: BTreeMap<Name, &'a Kind<'a>>,
+
+    /// The number of parameters of every type alias.
+    arities: BTreeMap<Name, usize>,
+
+    /// The solution of each kind variable, if any.
+    subst: Vec<Option<&'a Kind<'a>>>,
+}
+
+impl<'a, 'err, 'src, 'p> Checker<'a, 'err, 'src, 'p> {
+    /// Infer the kinds of every `data`, `type` and `class` item in a set of
+    /// mutually recursive items, and check that their bodies and the heads of
+    /// any instances are well-kinded.
+    pub(super) fn check_kinds(&mut self, items: &[resolved::Item<'_, 'src>]) {
+        let mut names = Vec::new();
+        let mut bodies = Vec::new();
+
+        for item in items {
+            let (pattern, result) = match &item.node {
+                resolved::ItemNode::Data(pattern, _) | resolved::ItemNode::Class(pattern, _) => {
+                    (pattern, &Kind::Type)
+                }
+
+                resolved::ItemNode::Alias(pattern, _) => (pattern, self.fresh_kind()),
+
+                resolved::ItemNode::Instance(..) => {
+                    bodies.push((item, &Kind::Type));
+                    continue;
+                }
+
+                resolved::ItemNode::Invalid(_) | resolved::ItemNode::Let(..) => continue,
+            };
+
+            if let Some(anno) = &pattern.anno {
+                if let Ok(kind) = self.lower_kind(anno) {
+                    let _ = self.unify_kinds(anno.span, kind, result);
+                }
+            }
+
+            let mut kind = result;
+            for (arg, anno) in pattern.args.iter().zip(pattern.kinds).rev() {
+                let param = match anno.as_ref().map(|anno| self.lower_kind(anno)) {
+                    Some(Ok(kind)) => kind,
+                    Some(Err(_)) | None => self.fresh_kind(),
+                };
+
+                if let Ok(arg) = arg {
+                    self.kinds.of.insert(*arg, param);
+                    names.push(*arg);
+                }
+
+                kind = self.alloc.alloc(Kind::Arrow(param, kind));
+            }
+
+            if let Ok(name) = pattern.name {
+                if let resolved::ItemNode::Alias(..) = &item.node {
+                    self.kinds.arities.insert(name, pattern.args.len());
+                }
+
+                self.kinds.of.insert(name, kind);
+                names.push(name);
+            }
+
+            bodies.push((item, result));
+        }
+
+        for (item, result) in bodies {
+            match &item.node {
+                resolved::ItemNode::Data(_, data) => {
+                    let resolved::DataNode::Sum(ctors) = &data.node else {
+                        continue;
+                    };
+
+                    for ctor in ctors.iter() {
+                        if let resolved::ConstructorNode::Constructor(_, params) = &ctor.node {
+                            for param in params.iter() {
+                                let _ = self.check_kind(param, &Kind::Type);
+                            }
+                        }
+                    }
+                }
+
+                resolved::ItemNode::Alias(_, body) => {
+                    let _ = self.check_kind(body, result);
+                }
+
+                resolved::ItemNode::Class(_, methods) => {
+                    for method in methods.iter() {
+                        let _ = self.check_kind(&method.ty, &Kind::Type);
+                    }
+                }
+
+                resolved::ItemNode::Instance((_, head), _, _) => {
+                    let _ = self.check_kind(head, result);
+                }
+
+                resolved::ItemNode::Invalid(_) | resolved::ItemNode::Let(..) => unreachable!(),
+            }
+        }
+
+        for name in names {
+            let kind = self.kinds.of[&name];
+            let kind = self.default_kind(kind);
+            self.kinds.of.insert(name, kind);
+        }
+    }
+
+    /// Lower a type annotation, which must have the kind `Type`.
+    pub(super) fn lower_annotation(&mut self, ty: &resolved::Type<'_, 'src>) -> &'a Type<'a> {
+        match self.check_kind(ty, &Kind::Type) {
+            Ok(()) => self.lower(ty),
+            Err(e) => self.alloc.alloc(Type::Invalid(e)),
+        }
+    }
+
+    /// Check that the type `ty` has the kind `expected`.
+    fn check_kind(
+        &mut self,
+        ty: &resolved::Type<'_, 'src>,
+        expected: &'a Kind<'a>,
+    ) -> Result<(), ErrorId> {
+        match self.infer_kind(ty)? {
+            Some(actual) => self.unify_kinds(ty.span, expected, actual),
+            None => Ok(()),
+        }
+    }
+
+    /// Infer the kind of a type, or `None` if the type is erroneous in a way
+    /// which has already been or will be reported.
+    fn infer_kind(
+        &mut self,
+        ty: &resolved::Type<'_, 'src>,
+    ) -> Result<Option<&'a Kind<'a>>, ErrorId> {
+        // Partially applied aliases are reported when they are expanded.
+        if self.partial_alias(ty, 0) {
+            Ok(None)
+        } else {
+            self.spine_kind(ty)
+        }
+    }
+
+    fn spine_kind(
+        &mut self,
+        ty: &resolved::Type<'_, 'src>,
+    ) -> Result<Option<&'a Kind<'a>>, ErrorId> {
+        let kind = match &ty.node {
+            resolved::TypeNode::Invalid(_) | resolved::TypeNode::Wildcard => return Ok(None),
+
+            resolved::TypeNode::Named(name) => match Builtin::from_name(self.names, name) {
+                Some(Builtin::Integer) => &Kind::Type,
+                Some(Builtin::Arrow) => {
+                    let result = self.alloc.alloc(Kind::Arrow(&Kind::Type, &Kind::Type));
+                    self.alloc.alloc(Kind::Arrow(&Kind::Type, result))
+                }
+
+                None => self.kind_of(name),
+            },
+
+            resolved::TypeNode::Universal(name) => self.kind_of(name),
+            resolved::TypeNode::Group(ty) => return self.spine_kind(ty),
+
+            resolved::TypeNode::Function([t, u]) => {
+                let t = self.check_kind(t, &Kind::Type);
+                let u = self.check_kind(u, &Kind::Type);
+                t.and(u)?;
+                &Kind::Type
+            }
+
+            resolved::TypeNode::Record(fields) => {
+                let checked: Vec<_> = fields
+                    .iter()
+                    .map(|(_, _, ty)| self.check_kind(ty, &Kind::Type))
+                    .collect();
+
+                checked.into_iter().collect::<Result<(), _>>()?;
+                &Kind::Type
+            }
+
+            resolved::TypeNode::Apply([t, u]) => {
+                let head = self.spine_kind(t);
+                let arg = self.infer_kind(u);
+                let (Some(head), arg) = (head?, arg?) else {
+                    return Ok(None);
+                };
+
+                match self.shallow_kind(head) {
+                    Kind::Arrow(param, result) => {
+                        if let Some(arg) = arg {
+                            self.unify_kinds(u.span, param, arg)?;
+                        }
+
+                        result
+                    }
+
+                    Kind::Var(_) => {
+                        let param = arg.unwrap_or_else(|| self.fresh_kind());
+                        let result = self.fresh_kind();
+                        let arrow = self.alloc.alloc(Kind::Arrow(param, result));
+                        self.unify_kinds(t.span, arrow, head)?;
+                        result
+                    }
+
+                    Kind::Type | Kind::Row => {
+                        let kind = self.show_kind(head);
+                        return Err(self
+                            .errors
+                            .type_error(ty.span)
+                            .too_many_type_arguments(kind));
+                    }
+                }
+            }
+        };
+
+        Ok(Some(kind))
+    }
+
+    /// Check whether `ty` is an alias applied to fewer than all of its
+    /// parameters, given that it is applied to `applied` more arguments.
+    fn partial_alias(&self, ty: &resolved::Type<'_, 'src>, applied: usize) -> bool {
+        match &ty.node {
+            resolved::TypeNode::Named(name) => self
+                .kinds
+                .arities
+                .get(name)
+                .is_some_and(|arity| applied < *arity),
+
+            resolved::TypeNode::Group(ty) => self.partial_alias(ty, applied),
+            resolved::TypeNode::Apply([t, _]) => self.partial_alias(t, applied + 1),
+            _ => false,
+        }
+    }
+
+    /// Get the kind of a type name or parameter.
+    fn kind_of(&mut self, name: &Name) -> &'a Kind<'a> {
+        if let Some(kind) = self.kinds.of.get(name) {
+            kind
+        } else {
+            let kind = self.fresh_kind();
+            self.kinds.of.insert(*name, kind);
+            kind
+        }
+    }
+
+    fn lower_kind(&mut self, kind: &resolved::Kind) -> Result<&'a Kind<'a>, ErrorId> {
+        match &kind.node {
+            resolved::KindNode::Invalid(e) => Err(*e),
+            resolved::KindNode::Type => Ok(&Kind::Type),
+            resolved::KindNode::Row => Ok(&Kind::Row),
+            resolved::KindNode::Arrow([k, l]) => {
+                let k = self.lower_kind(k)?;
+                let l = self.lower_kind(l)?;
+                Ok(self.alloc.alloc(Kind::Arrow(k, l)))
+            }
+        }
+    }
+
+    fn fresh_kind(&mut self) -> &'a Kind<'a> {
+        let var = self.kinds.subst.len();
+        self.kinds.subst.push(None);
+        self.alloc.alloc(Kind::Var(var))
+    }
+
+    /// Follow the solutions of kind variables until reaching a kind which is
+    /// either not a variable or is an unsolved one.
+    fn shallow_kind(&self, mut kind: &'a Kind<'a>) -> &'a Kind<'a> {
+        while let Kind::Var(var) = kind {
+            match self.kinds.subst[*var] {
+                Some(solution) => kind = solution,
+                None => break,
+            }
+        }
+
+        kind
+    }
+
+    /// Solve every unsolved kind variable in `kind` as `Type`.
+    fn default_kind(&mut self, kind: &'a Kind<'a>) -> &'a Kind<'a> {
+        match self.shallow_kind(kind) {
+            Kind::Var(var) => {
+                self.kinds.subst[*var] = Some(&Kind::Type);
+                &Kind::Type
+            }
+
+            Kind::Arrow(k, l) => {
+                let k = self.default_kind(k);
+                let l = self.default_kind(l);
+                self.alloc.alloc(Kind::Arrow(k, l))
+            }
+
+            kind => kind,
+        }
+    }
+
+    fn unify_kinds(
+        &mut self,
+        span: Span,
+        expected: &'a Kind<'a>,
+        actual: &'a Kind<'a>,
+    ) -> Result<(), ErrorId> {
+        match self.unify_kinds_inner(span, expected, actual) {
+            Ok(()) => Ok(()),
+            Err(Some(e)) => Err(e),
+            Err(None) => {
+                let expected = self.show_kind(expected);
+                let actual = self.show_kind(actual);
+                Err(self.errors.type_error(span).inequal_kinds(expected, actual))
+            }
+        }
+    }
+
+    /// Returns `Err(None)` if the two kinds are incompatible, which is
+    /// reported by the caller since it knows the full kinds involved.
+    fn unify_kinds_inner(
+        &mut self,
+        span: Span,
+        k: &'a Kind<'a>,
+        l: &'a Kind<'a>,
+    ) -> Result<(), Option<ErrorId>> {
+        let k = self.shallow_kind(k);
+        let l = self.shallow_kind(l);
+
+        match (k, l) {
+            (Kind::Var(v), Kind::Var(w)) if v == w => Ok(()),
+
+            (Kind::Var(var), kind) | (kind, Kind::Var(var)) => {
+                if self.occurs_kind(*var, kind) {
+                    Err(Some(self.errors.type_error(span).recursive_kind()))
+                } else {
+                    self.kinds.subst[*var] = Some(kind);
+                    Ok(())
+                }
+            }
+
+            (Kind::Type, Kind::Type) | (Kind::Row, Kind::Row) => Ok(()),
+
+            (Kind::Arrow(k1, k2), Kind::Arrow(l1, l2)) => {
+                self.unify_kinds_inner(span, k1, l1)?;
+                self.unify_kinds_inner(span, k2, l2)
+            }
+
+            _ => Err(None),
+        }
+    }
+
+    fn occurs_kind(&self, var: usize, kind: &'a Kind<'a>) -> bool {
+        match self.shallow_kind(kind) {
+            Kind::Var(other) => var == *other,
+            Kind::Arrow(k, l) => self.occurs_kind(var, k) || self.occurs_kind(var, l),
+            Kind::Type | Kind::Row => false,
+        }
+    }
+
+    fn show_kind(&self, kind: &'a Kind<'a>) -> String {
+        match self.shallow_kind(kind) {
+            Kind::Type => "Type".into(),
+            Kind::Row => "Row".into(),
+            Kind::Var(_) => "_".into(),
+            Kind::Arrow(k, l) => {
+                let l = self.show_kind(l);
+                if let Kind::Arrow(..) = self.shallow_kind(k) {
+                    format!("({}) -> {l}", self.show_kind(k))
+                } else {
+                    format!("{} -> {l}", self.show_kind(k))
+                }
+            }
+        }
+    }
+}
@@ -1,13 +1,23 @@
 use super::{types as o, Checker};
+use crate::frontend::builtins::Builtin;
+use crate::frontend::names::Name;
 use crate::frontend::trees::resolved as i;
 
 impl<'a, 'err, 'src, 'p> Checker<'a, 'err, 'src, 'p> {
     /// Lower a type expression into its semantic equivalent.
     pub(super) fn lower(&mut self, ty: &i::Type<'_, 'src>) -> &'a o::Type<'a> {
+        if let Some((name, args)) = self.alias_spine(ty) {
+            return self.expand_alias(ty.span, name, &args);
+        }
+
         let ty = match &ty.node {
             i::TypeNode::Invalid(e) => o::Type::Invalid(*e),
             i::TypeNode::Wildcard => return self.fresh(),
-            i::TypeNode::Named(name) => o::Type::Named(*name),
+            i::TypeNode::Named(name) => match Builtin::from_name(self.names, name) {
+                Some(Builtin::Integer) => o::Type::Integer,
+                Some(Builtin::Arrow) => o::Type::Arrow,
+                None => o::Type::Named(*name),
+            },
             i::TypeNode::Universal(name) => o::Type::Param(o::Generic::Ticked(*name)),
             i::TypeNode::Group(ty) => return self.lower(ty),
 
@@ -41,4 +51,30 @@ impl<'a, 'err, 'src, 'p> Checker<'a, 'err, 'src, 'p> {
 
         self.alloc.alloc(ty)
     }
+
+    /// If `ty` is a (possibly applied) type alias, get its name and arguments.
+    fn alias_spine<'b>(
+        &self,
+        mut ty: &'b i::Type<'b, 'src>,
+    ) -> Option<(Name, Vec<&'b i::Type<'b, 'src>>)> {
+        let mut args = Vec::new();
+
+        loop {
+            match &ty.node {
+                i::TypeNode::Named(name) if self.aliases.contains_key(name) => {
+                    args.reverse();
+                    return Some((*name, args));
+                }
+
+                i::TypeNode::Apply([t, u]) => {
+                    args.push(u);
+                    ty = t;
+                }
+
+                i::TypeNode::Group(inner) => ty = inner,
+
+                _ => return None,
+            }
+        }
+    }
 }
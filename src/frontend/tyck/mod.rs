@@ -1,23 +1,37 @@
 pub use self::pretty::{Prettifier, Pretty};
-pub use self::types::{Env, Generic, Row, Scheme, Type};
+pub use self::solve::{Dict, DictVar};
+pub use self::types::{Env, Generic, Predicate, Row, Scheme, Type, TypeArg, VarKind};
 
+mod aliases;
+mod classes;
 mod infer;
+mod kinds;
 mod lower;
 mod pattern;
 mod pretty;
+mod primitives;
+mod records;
 mod solve;
 mod types;
+mod zonk;
 
 #[cfg(test)]
 mod tests;
 
+use std::collections::BTreeMap;
+
 use bumpalo::Bump;
 
-use self::solve::Solver;
+use self::aliases::Alias;
+use self::classes::Classes;
+use self::kinds::Kinds;
+use self::solve::{Dicts, Solver};
 use crate::frontend::errors::{ErrorId, Errors};
 use crate::frontend::names::{Name, Names};
-use crate::frontend::source::Span;
-use crate::frontend::trees::{inferred, resolved};
+use crate::frontend::prelude;
+use crate::frontend::resolve::ItemId;
+use crate::frontend::source::{SourceId, Span};
+use crate::frontend::trees::{inferred, resolved, typed};
 
 pub fn infer<'a, 'src>(
     alloc: &'a Bump,
@@ -28,28 +42,145 @@ pub fn infer<'a, 'src>(
     let mut pretty = Pretty::new(names)
         .with_show_levels(false)
         .with_show_error_id(false);
-    let mut checker = Checker::new(alloc, &mut errors, &mut pretty);
+    let mut checker = Checker::new(alloc, names, &mut errors, &mut pretty);
+    checker.resume(prelude_of(program));
 
-    let items =
-        alloc.alloc_slice_fill_iter(program.items.iter().map(|items| checker.check_items(items)));
+    let (items, _) = checker.check_program(program);
 
     inferred::Program {
         items,
         defs: program.defs.clone(),
+        refs: program.refs.clone(),
+        docs: program.docs.clone(),
         errors,
         unattached: program.unattached.clone(),
     }
 }
 
-struct Reporting<'a, 'b, 'c, 'd> {
+/// Infer the types of a program and apply the final substitution to it,
+/// producing a typed program without any unification variables.
+pub fn check<'a, 'src>(
+    alloc: &'a Bump,
+    names: &'a Names<'src>,
+    program: &resolved::Program<'_, 'src>,
+) -> typed::Program<'a, 'src> {
+    let mut errors = program.errors.clone();
+    let mut pretty = Pretty::new(names)
+        .with_show_levels(false)
+        .with_show_error_id(false);
+    let mut checker = Checker::new(alloc, names, &mut errors, &mut pretty);
+    checker.resume(prelude_of(program));
+
+    let (items, dicts) = checker.check_program(program);
+    let (items, dicts) = checker.zonk_program(items, dicts);
+
+    typed::Program {
+        items,
+        dicts,
+        errors,
+    }
+}
+
+/// A checked prelude, along with the state of the checker after checking it.
+/// Programs which use the prelude are checked starting from that state, and
+/// the items they use from the prelude are not checked again.
+pub struct Prelude<'a, 'src> {
+    /// The errors found in the prelude.
+    pub errors: Errors,
+
+    env: Env<'a>,
+    aliases: BTreeMap<Name, Alias<'a>>,
+    records: BTreeMap<Name, Scheme<'a>>,
+    kinds: Kinds<'a>,
+    classes: Classes<'a>,
+    solver: usize,
+
+    /// The inferred and typed items of every component of the prelude, by the
+    /// id of its first item.
+    components: BTreeMap<ItemId, (&'a [inferred::Item<'a, 'src>], &'a [typed::Item<'a, 'src>])>,
+    dicts: BTreeMap<DictVar, typed::Evidence<'a>>,
+}
+
+impl<'a, 'src> Prelude<'a, 'src> {
+    /// Get the inferred and typed items of a component of the prelude.
+    fn component(
+        &self,
+        first: Option<(ItemId, Span)>,
+    ) -> Option<(&'a [inferred::Item<'a, 'src>], &'a [typed::Item<'a, 'src>])> {
+        let (id, span) = first?;
+        if span.source == SourceId::PRELUDE {
+            self.components.get(&id).copied()
+        } else {
+            None
+        }
+    }
+}
+
+/// Check a resolved prelude on its own. Every item is kept, so that programs
+/// can use whichever of them they need.
+pub fn check_prelude<'a, 'src>(
+    alloc: &'a Bump,
+    names: &'a Names<'src>,
+    prelude: &resolved::Program<'_, 'src>,
+) -> Prelude<'a, 'src> {
+    let mut errors = prelude.errors.clone();
+    let mut pretty = Pretty::new(names)
+        .with_show_levels(false)
+        .with_show_error_id(false);
+    let mut checker = Checker::new(alloc, names, &mut errors, &mut pretty);
+
+    let (inferred, dicts) = checker.check_program(prelude);
+    let (typed, dicts) = checker.zonk_program(inferred, dicts);
+
+    let components = inferred
+        .iter()
+        .zip(typed.iter())
+        .filter_map(|(inferred, typed)| Some((inferred.first()?.id, (*inferred, *typed))))
+        .collect();
+
+    Prelude {
+        env: checker.env,
+        aliases: checker.aliases,
+        records: checker.records,
+        kinds: checker.kinds,
+        classes: checker.classes,
+        solver: checker.solver.counter(),
+        components,
+        dicts,
+        errors,
+    }
+}
+
+/// Get the checked prelude if the program was resolved with it, which is when
+/// it contains any items from the prelude.
+fn prelude_of(program: &resolved::Program) -> Option<&'static Prelude<'static, 'static>> {
+    program
+        .items
+        .iter()
+        .flat_map(|items| items.iter())
+        .any(|item| item.span.source == SourceId::PRELUDE)
+        .then(prelude::checked)
+}
+
+struct Reporting<'a, 'b, 'c, 'd, 'e> {
     pretty: &'a mut Prettifier<'b, 'c, 'd>,
     errors: &'a mut Errors,
     at: Span,
+
+    /// The nominal records, to explain mismatches between them and structural
+    /// records.
+    records: &'a BTreeMap<Name, Scheme<'e>>,
 }
 
 struct Checker<'a, 'err, 'src, 'p> {
     alloc: &'a Bump,
+    prelude: Option<&'p Prelude<'a, 'src>>,
+    names: &'a Names<'src>,
     env: Env<'a>,
+    aliases: BTreeMap<Name, Alias<'a>>,
+    records: BTreeMap<Name, Scheme<'a>>,
+    kinds: Kinds<'a>,
+    classes: Classes<'a>,
     solver: Solver<'a>,
     errors: &'err mut Errors,
     pretty: &'p mut Pretty<'a, 'src>,
@@ -59,12 +190,19 @@ struct Checker<'a, 'err, 'src, 'p> {
 impl<'a, 'err, 'src, 'p> Checker<'a, 'err, 'src, 'p> {
     pub fn new(
         alloc: &'a Bump,
+        names: &'a Names<'src>,
         errors: &'err mut Errors,
         pretty: &'p mut Pretty<'a, 'src>,
     ) -> Self {
         Self {
             alloc,
+            prelude: None,
+            names,
             env: Env::new(),
+            aliases: BTreeMap::new(),
+            records: BTreeMap::new(),
+            kinds: Kinds::default(),
+            classes: Classes::default(),
             solver: Solver::new(),
             errors,
             pretty,
@@ -72,6 +210,51 @@ impl<'a, 'err, 'src, 'p> Checker<'a, 'err, 'src, 'p> {
         }
     }
 
+    /// Pick up from the state left behind by checking the prelude.
+    fn resume(&mut self, prelude: Option<&'p Prelude<'a, 'src>>) {
+        let Some(prelude) = prelude else {
+            return;
+        };
+
+        self.prelude = Some(prelude);
+        self.env = prelude.env.clone();
+        self.aliases = prelude.aliases.clone();
+        self.records = prelude.records.clone();
+        self.kinds = prelude.kinds.clone();
+        self.classes = prelude.classes.clone();
+        self.solver = Solver::starting_at(prelude.solver);
+    }
+
+    /// Get the inferred and typed items of a component, if it was checked
+    /// along with the prelude.
+    fn prelude_component(
+        &self,
+        first: Option<(ItemId, Span)>,
+    ) -> Option<(&'a [inferred::Item<'a, 'src>], &'a [typed::Item<'a, 'src>])> {
+        self.prelude?.component(first)
+    }
+
+    /// Check every item of a program, returning the inferred items along with
+    /// the solution of every dictionary variable.
+    fn check_program(
+        &mut self,
+        program: &resolved::Program<'_, 'src>,
+    ) -> (&'a [&'a [inferred::Item<'a, 'src>]], Dicts<'a>) {
+        self.declare_instances(program);
+
+        let items = self
+            .alloc
+            .alloc_slice_fill_iter(program.items.iter().map(|items| {
+                let first = items.first().map(|item| (item.id, item.span));
+                match self.prelude_component(first) {
+                    Some((inferred, _)) => inferred,
+                    None => self.check_items(items),
+                }
+            }));
+
+        (items, self.solver.take_dicts())
+    }
+
     /// Check a set of mutually recursive items.
     pub fn check_items<'b>(
         &mut self,
@@ -79,6 +262,9 @@ impl<'a, 'err, 'src, 'p> Checker<'a, 'err, 'src, 'p> {
     ) -> &'a [inferred::Item<'a, 'src>] {
         let mut inferred_items = Vec::with_capacity(items.len());
 
+        self.check_kinds(items);
+        self.check_aliases(items);
+
         self.enter(|this| {
             let mut typed_items = Vec::with_capacity(items.len());
 
@@ -107,8 +293,24 @@ impl<'a, 'err, 'src, 'p> Checker<'a, 'err, 'src, 'p> {
                     resolved::ItemNode::Data(pattern, body) => {
                         let ty = this.type_pattern(pattern);
                         let body = this.check_data(&ty, body);
+                        this.register_record(&ty, &body);
                         inferred::BoundItemNode::Data(ty, body)
                     }
+
+                    resolved::ItemNode::Alias(pattern, _) => {
+                        let (scheme, body) = this.alias_scheme(pattern);
+                        inferred::BoundItemNode::Alias(scheme, body)
+                    }
+
+                    resolved::ItemNode::Class(pattern, methods) => {
+                        let (scheme, methods) = this.check_class(item.span, pattern, methods);
+                        inferred::BoundItemNode::Class(scheme, methods)
+                    }
+
+                    resolved::ItemNode::Instance((dict, head), body, scope) => {
+                        let scheme = this.instance_scheme(dict, head, scope);
+                        inferred::BoundItemNode::Instance((*dict, scheme), body, &[])
+                    }
                 };
 
                 let item = inferred::BoundItem {
@@ -132,6 +334,19 @@ impl<'a, 'err, 'src, 'p> Checker<'a, 'err, 'src, 'p> {
                     inferred::BoundItemNode::Data(ty, body) => {
                         inferred::BoundItemNode::Data(ty, body)
                     }
+
+                    inferred::BoundItemNode::Alias(scheme, body) => {
+                        inferred::BoundItemNode::Alias(scheme, body)
+                    }
+
+                    inferred::BoundItemNode::Class(scheme, methods) => {
+                        inferred::BoundItemNode::Class(scheme, methods)
+                    }
+
+                    inferred::BoundItemNode::Instance((dict, scheme), body, scope) => {
+                        let body = this.check_instance(&scheme, body);
+                        inferred::BoundItemNode::Instance((dict, scheme), body, scope)
+                    }
                 };
 
                 inferred_items.push(inferred::BoundItem {
@@ -143,18 +358,42 @@ impl<'a, 'err, 'src, 'p> Checker<'a, 'err, 'src, 'p> {
         });
 
         // Generalize!
+        let patterns: Vec<_> = inferred_items
+            .iter()
+            .filter_map(|item| match &item.node {
+                inferred::BoundItemNode::Let(pattern, _, scope) => Some((*scope, pattern)),
+                _ => None,
+            })
+            .collect();
+
+        let (patterns, dicts) = self.generalize_patterns(&patterns);
+        let mut patterns = patterns.into_iter();
+
         self.alloc
             .alloc_slice_fill_iter(inferred_items.into_iter().map(|item| {
                 let id = item.id;
                 let span = item.span;
                 let node = match item.node {
                     inferred::BoundItemNode::Invalid(e) => inferred::ItemNode::Invalid(e),
-                    inferred::BoundItemNode::Let(pattern, expr, scope) => {
-                        let pattern = self.generalize_pattern(scope, &pattern);
-                        inferred::ItemNode::Let(pattern, expr, ())
+                    inferred::BoundItemNode::Let(_, expr, _) => {
+                        let pattern = patterns
+                            .next()
+                            .expect("every let item has a generalized pattern");
+                        inferred::ItemNode::Let(pattern, expr, dicts)
                     }
 
                     inferred::BoundItemNode::Data(ty, body) => inferred::ItemNode::Data(ty, body),
+                    inferred::BoundItemNode::Alias(scheme, body) => {
+                        inferred::ItemNode::Alias(scheme, body)
+                    }
+
+                    inferred::BoundItemNode::Class(scheme, methods) => {
+                        inferred::ItemNode::Class(scheme, methods)
+                    }
+
+                    inferred::BoundItemNode::Instance(head, body, _) => {
+                        inferred::ItemNode::Instance(head, body, &[])
+                    }
                 };
 
                 inferred::Item { node, span, id }
@@ -179,7 +418,11 @@ impl<'a, 'err, 'src, 'p> Checker<'a, 'err, 'src, 'p> {
 
                 let ty = self.alloc.alloc(ty);
 
-                Scheme { ty, params }
+                Scheme {
+                    ty,
+                    params,
+                    preds: Vec::new(),
+                }
             }
 
             Err(e) => {
@@ -306,6 +549,8 @@ fn alpha_equal<'a>(t: &'a Type<'a>, u: &'a Type<'a>) -> bool {
                 inner(subst, t1, u1) && inner(subst, t2, u2)
             }
 
+            (Type::Alias(_, _, t), u) | (u, Type::Alias(_, _, t)) => inner(subst, t, u),
+
             _ => false,
         }
     }
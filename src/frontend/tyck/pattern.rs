@@ -1,15 +1,60 @@
-use super::{Checker, Generic, Scheme};
+use super::{Checker, Generic, Scheme, Type};
+use crate::frontend::names::Name;
 use crate::frontend::trees::inferred::{MonoPattern, PolyPattern};
 use crate::frontend::trees::nodes::PatternNode;
 
 impl<'a, 'src> Checker<'a, '_, 'src, '_> {
-    pub(super) fn generalize_pattern(
+    /// Generalize the patterns of a group of bindings, each with its explicitly
+    /// scoped type parameters. Any predicates wanted by the bindings are solved
+    /// at this point, and those on the generalized parameters are shared by the
+    /// entire group. Returns the generalized patterns along with the names of
+    /// the dictionary parameters for those predicates.
+    pub(super) fn generalize_patterns(
         &mut self,
-        explicit: &[Generic],
-        pattern: &MonoPattern<'a>,
-    ) -> PolyPattern<'a> {
-        let scheme = self.generalize(explicit, pattern.ty);
-        self.gen_pattern(&scheme, pattern)
+        patterns: &[(&[Generic], &MonoPattern<'a>)],
+    ) -> (Vec<PolyPattern<'a>>, &'a [Name]) {
+        let mut schemes: Vec<_> = patterns
+            .iter()
+            .map(|(explicit, pattern)| self.generalize(explicit, pattern.ty))
+            .collect();
+
+        let params = schemes
+            .iter()
+            .flat_map(|scheme| scheme.params.iter().copied())
+            .collect();
+
+        let (preds, dicts) = self.solve_predicates(&params);
+
+        if !preds.is_empty() {
+            for (_, pattern) in patterns {
+                if !is_single_bind(pattern) {
+                    let _ = self
+                        .errors
+                        .type_error(pattern.span)
+                        .constrained_pattern_binding();
+                }
+            }
+
+            for scheme in schemes.iter_mut() {
+                for pred in preds.iter() {
+                    if let Type::Param(param) = pred.ty {
+                        if !scheme.params.contains(param) {
+                            scheme.params.push(*param);
+                        }
+                    }
+                }
+
+                scheme.preds.clone_from(&preds);
+            }
+        }
+
+        let patterns = patterns
+            .iter()
+            .zip(schemes)
+            .map(|((_, pattern), scheme)| self.gen_pattern(&scheme, pattern))
+            .collect();
+
+        (patterns, dicts)
     }
 
     pub(super) fn monomorphic(&mut self, pattern: &MonoPattern<'a>) -> PolyPattern<'a> {
@@ -50,6 +95,14 @@ impl<'a, 'src> Checker<'a, '_, 'src, '_> {
                 PatternNode::And(terms)
             }
 
+            PatternNode::Record(fields) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(label, span, pattern)| (*label, *span, self.monomorphic(pattern)))
+                    .collect();
+                PatternNode::Record(self.alloc.alloc_slice_fill_iter(fields))
+            }
+
             PatternNode::Anno(_, v) => match *v {},
         };
 
@@ -95,9 +148,28 @@ impl<'a, 'src> Checker<'a, '_, 'src, '_> {
                 PatternNode::And(terms)
             }
 
+            PatternNode::Record(fields) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(label, span, pattern)| {
+                        (*label, *span, self.gen_pattern(&scheme, pattern))
+                    })
+                    .collect();
+                PatternNode::Record(self.alloc.alloc_slice_fill_iter(fields))
+            }
+
             PatternNode::Anno(_, v) => match *v {},
         };
 
         PolyPattern { node, span, scheme }
     }
 }
+
+/// Whether a pattern binds just a single name and nothing else.
+fn is_single_bind(pattern: &MonoPattern) -> bool {
+    match &pattern.node {
+        PatternNode::Bind(_) => true,
+        PatternNode::Group(pattern) => is_single_bind(pattern),
+        _ => false,
+    }
+}
@@ -4,7 +4,7 @@ use crate::frontend::errors::ErrorId;
 use crate::frontend::names::{Label, Name, Names};
 
 use super::solve::{Level, TypeVar};
-use super::types::{Generic, Row, VarKind};
+use super::types::{Generic, Predicate, Row, VarKind};
 use super::{to_name, Scheme, Type};
 
 pub struct Pretty<'a, 'src> {
@@ -77,10 +77,25 @@ impl Prettifier<'_, '_, '_> {
                 .map(|(idx, generic)| (*generic, format!("'{idx}")))
                 .collect();
 
-            self.ty_with_subst(scheme.ty, &subst)
+            let ty = self.ty_with_subst(scheme.ty, &subst);
+            let preds: Vec<_> = scheme
+                .preds
+                .iter()
+                .map(|pred| self.pred_with_subst(pred, &subst))
+                .collect();
+
+            match &preds[..] {
+                [] => ty,
+                [pred] => format!("{pred} => {ty}"),
+                preds => format!("({}) => {ty}", preds.join(", ")),
+            }
         }
     }
 
+    pub fn pred(&mut self, pred: &Predicate) -> String {
+        self.pred_with_subst(pred, &BTreeMap::new())
+    }
+
     pub fn ty(&mut self, ty: &Type) -> String {
         self.ty_with_subst(ty, &BTreeMap::new())
     }
@@ -89,6 +104,17 @@ impl Prettifier<'_, '_, '_> {
         self.record_with_subst(row, &BTreeMap::new())
     }
 
+    /// A constructor applied to its argument types, as in `Cons a (List a)`.
+    pub fn constructor(&mut self, name: &Name, args: &[Type]) -> String {
+        let mut result = self.name(name);
+        for arg in args {
+            result.push(' ');
+            result.push_str(&self.simple(arg, &BTreeMap::new()));
+        }
+
+        result
+    }
+
     pub fn label(&self, label: &Label) -> String {
         label.0.name().into()
     }
@@ -106,7 +132,15 @@ impl Prettifier<'_, '_, '_> {
         String::from(name)
     }
 
-    fn ty_with_subst(&mut self, ty: &Type, subst: &BTreeMap<Generic, String>) -> String {
+    fn pred_with_subst(&mut self, pred: &Predicate, subst: &BTreeMap<Generic, String>) -> String {
+        let class = self.name(&pred.class);
+        let ty = self.simple(pred.ty, subst);
+        format!("{class} {ty}")
+    }
+
+    /// Pretty-print a type mentioning the parameters of schemes, named by
+    /// `subst`, as the types within a generalized binding do.
+    pub fn ty_with_subst(&mut self, ty: &Type, subst: &BTreeMap<Generic, String>) -> String {
         self.arrow(ty, subst)
     }
 
@@ -138,6 +172,15 @@ impl Prettifier<'_, '_, '_> {
                 format!("{t} {u}")
             }
 
+            Type::Alias(name, args, _) if !args.is_empty() => {
+                let mut result = self.name(name);
+                for arg in args.iter() {
+                    result.push(' ');
+                    result.push_str(&self.simple(arg, subst));
+                }
+                result
+            }
+
             _ => self.simple(ty, subst),
         }
     }
@@ -147,7 +190,7 @@ impl Prettifier<'_, '_, '_> {
             Type::Invalid(e) => self.error(e),
             Type::Var(var, level) => self.var(var, Some(level)),
             Type::Param(name) => self.param(name, subst),
-            Type::Named(name) => self.name(name),
+            Type::Named(name) | Type::Alias(name, [], _) => self.name(name),
             Type::Unit => "unit".into(),
             Type::Integer => "int".into(),
             Type::Arrow => "(->)".into(),
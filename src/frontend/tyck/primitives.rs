@@ -0,0 +1,37 @@
+//! The types of the [primitive operations](Primitive).
+
+use super::{Checker, Generic, Scheme, Type};
+use crate::frontend::builtins::Primitive;
+use crate::frontend::names::ScopeName;
+
+impl<'a> Checker<'a, '_, '_, '_> {
+    /// Get the type scheme of a primitive operation.
+    pub fn primitive(&self, primitive: Primitive) -> Scheme<'a> {
+        let int = &*self.alloc.alloc(Type::Integer);
+
+        match primitive {
+            Primitive::Add | Primitive::Sub | Primitive::Mul => {
+                Scheme::mono(self.arrows(&[int, int], int))
+            }
+
+            Primitive::Less => {
+                let param =
+                    Generic::Ticked(self.names.name(ScopeName::Builtin, self.names.intern("a")));
+                let a = &*self.alloc.alloc(Type::Param(param));
+                Scheme {
+                    params: vec![param],
+                    preds: Vec::new(),
+                    ty: self.arrows(&[int, int, a, a], a),
+                }
+            }
+        }
+    }
+
+    /// Make the type of a function taking the given arguments.
+    fn arrows(&self, args: &[&'a Type<'a>], result: &'a Type<'a>) -> &'a Type<'a> {
+        args.iter().rev().fold(result, |result, arg| {
+            let arrow = self.alloc.alloc(Type::Apply(&Type::Arrow, arg));
+            self.alloc.alloc(Type::Apply(arrow, result))
+        })
+    }
+}
@@ -0,0 +1,69 @@
+//! Nominal records are data types with a single constructor whose only
+//! parameter is a record, like
+//!
+//! ```nml
+//! data Person = Person { name : int, age : int }
+//! ```
+//!
+//! Fields of such a data type may be accessed directly, such that `p.name`
+//! works for any `p : Person`.
+
+use std::collections::BTreeMap;
+
+use super::{Checker, Scheme, Type};
+use crate::frontend::trees::inferred;
+
+impl<'a, 'err, 'src, 'p> Checker<'a, 'err, 'src, 'p> {
+    /// Remember the record type of `data` if it is a nominal record.
+    pub(super) fn register_record(&mut self, scheme: &Scheme<'a>, data: &inferred::Data<'a>) {
+        let inferred::DataNode::Sum([ctor]) = &data.node else {
+            return;
+        };
+
+        let inferred::ConstructorNode::Constructor(_, [field]) = &ctor.node else {
+            return;
+        };
+
+        if !matches!(field.expand(), Type::Record(_)) {
+            return;
+        }
+
+        let (Type::Named(name), _) = spine(scheme.ty) else {
+            return;
+        };
+
+        self.records.insert(*name, scheme.onto(field));
+    }
+
+    /// If `ty` is (currently known to be) a nominal record, get the record type
+    /// it wraps.
+    pub(super) fn nominal_record(&mut self, ty: &'a Type<'a>) -> Option<&'a Type<'a>> {
+        let ty = self.apply(ty);
+        let (Type::Named(name), args) = spine(ty) else {
+            return None;
+        };
+
+        let record = self.records.get(name)?;
+        if record.params.len() != args.len() {
+            return None;
+        }
+
+        let subst: BTreeMap<_, _> = record.params.iter().copied().zip(args).collect();
+        let ty = record.ty;
+        Some(self.substitute(&subst, ty))
+    }
+}
+
+/// Split a type into its head and the arguments it is applied to.
+pub(super) fn spine<'b>(ty: &'b Type<'b>) -> (&'b Type<'b>, Vec<&'b Type<'b>>) {
+    let mut args = Vec::new();
+    let mut ty = ty.expand();
+
+    while let Type::Apply(t, u) = ty {
+        args.push(*u);
+        ty = t.expand();
+    }
+
+    args.reverse();
+    (ty, args)
+}
@@ -42,6 +42,14 @@ impl<'a> Solver<'a> {
                 let u = alloc.alloc(self.apply(alloc, u));
                 Type::Apply(t, u)
             }
+
+            Type::Alias(name, args, body) => {
+                let args = alloc.alloc_slice_fill_iter(
+                    args.iter().map(|arg| &*alloc.alloc(self.apply(alloc, arg))),
+                );
+                let body = alloc.alloc(self.apply(alloc, body));
+                Type::Alias(*name, args, body)
+            }
         }
     }
 
@@ -23,7 +23,11 @@ impl<'a> Solver<'a> {
         let mut subst = explicit.iter().copied().collect();
         let ty = alloc.alloc(self.gen_ty(alloc, &mut subst, ty));
         let params = subst.into_iter().collect();
-        Scheme { params, ty }
+        Scheme {
+            params,
+            preds: Vec::new(),
+            ty,
+        }
     }
 
     fn gen_ty(
@@ -70,6 +74,16 @@ impl<'a> Solver<'a> {
                 let u = alloc.alloc(self.gen_ty(alloc, subst, u));
                 Type::Apply(t, u)
             }
+
+            Type::Alias(name, args, body) => {
+                let args: Vec<_> = args
+                    .iter()
+                    .map(|arg| &*alloc.alloc(self.gen_ty(alloc, subst, arg)))
+                    .collect();
+                let args = alloc.alloc_slice_copy(&args);
+                let body = alloc.alloc(self.gen_ty(alloc, subst, body));
+                Type::Alias(*name, args, body)
+            }
         }
     }
 
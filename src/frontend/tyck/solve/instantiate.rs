@@ -4,7 +4,7 @@ use bumpalo::Bump;
 use log::trace;
 
 use crate::frontend::tyck::pretty::Prettifier;
-use crate::frontend::tyck::types::{Generic, Row, VarKind};
+use crate::frontend::tyck::types::{Generic, Predicate, Row, TypeArg, VarKind};
 use crate::frontend::tyck::{Scheme, Type};
 
 use super::{Level, Solver, TypeVar};
@@ -16,22 +16,43 @@ impl<'a> Solver<'a> {
         pretty: &mut Prettifier,
         alloc: &'a Bump,
         scheme: &Scheme<'a>,
-    ) -> Type<'a> {
+    ) -> (Type<'a>, Vec<TypeArg<'a>>, Vec<Predicate<'a>>) {
         trace!(
             "ins {}",
-            pretty.scheme(&Scheme {
-                params: scheme.params.clone(),
-                ty: alloc.alloc(self.apply(alloc, scheme.ty)),
-            })
+            pretty.scheme(&scheme.onto(alloc.alloc(self.apply(alloc, scheme.ty))))
         );
 
-        let subst = scheme
+        let subst: BTreeMap<_, _> = scheme
             .params
             .iter()
             .map(|name| (name, self.new_var(VarKind::Type)))
             .collect();
 
-        self.inst_ty(alloc, &subst, scheme.ty)
+        let args = scheme
+            .params
+            .iter()
+            .map(|name| {
+                let (var, level) = subst[name].clone();
+                match name {
+                    Generic::Implicit(TypeVar(_, VarKind::Row)) => {
+                        TypeArg::Row(alloc.alloc(Row::Var(var, level)))
+                    }
+
+                    _ => TypeArg::Type(alloc.alloc(Type::Var(var, level))),
+                }
+            })
+            .collect();
+
+        let preds = scheme
+            .preds
+            .iter()
+            .map(|pred| Predicate {
+                class: pred.class,
+                ty: alloc.alloc(self.inst_ty(alloc, &subst, pred.ty)),
+            })
+            .collect();
+
+        (self.inst_ty(alloc, &subst, scheme.ty), args, preds)
     }
 
     fn inst_ty(
@@ -74,6 +95,15 @@ impl<'a> Solver<'a> {
                 let u = alloc.alloc(self.inst_ty(alloc, subst, u));
                 Type::Apply(t, u)
             }
+
+            Type::Alias(name, args, body) => {
+                let args = alloc.alloc_slice_fill_iter(
+                    args.iter()
+                        .map(|arg| &*alloc.alloc(self.inst_ty(alloc, subst, arg))),
+                );
+                let body = alloc.alloc(self.inst_ty(alloc, subst, body));
+                Type::Alias(*name, args, body)
+            }
         }
     }
 
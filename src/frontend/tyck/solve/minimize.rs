@@ -54,6 +54,8 @@ impl<'a> Solver<'a> {
             }
 
             Type::Record(row) | Type::Variant(row) => self.minimize_row(alloc, keep, row),
+
+            Type::Alias(_, _, body) => self.minimize_ty(alloc, keep, body),
         }
     }
 
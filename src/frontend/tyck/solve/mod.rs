@@ -1,20 +1,25 @@
+use crate::frontend::builtins::Primitive;
 use crate::frontend::names::Name;
 use crate::frontend::source::Span;
 
+pub use self::predicates::{Dict, DictVar, Dicts};
 pub use self::vars::Level;
 
 mod apply;
 mod generalize;
 mod instantiate;
 mod minimize;
+mod predicates;
 mod unify;
 mod vars;
+mod zonk;
 
 use std::collections::{BTreeMap, BTreeSet};
 
-use super::types::{Row, VarKind};
+use self::predicates::Wanted;
+use super::types::{Row, TypeArg, VarKind};
 use super::Type;
-use super::{Checker, Generic, Scheme};
+use super::{Checker, Generic, Reporting, Scheme};
 
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct TypeVar(usize, pub(super) VarKind);
@@ -23,6 +28,11 @@ pub struct Solver<'a> {
     subst: BTreeMap<TypeVar, &'a Type<'a>>,
     row_subst: BTreeMap<TypeVar, &'a Row<'a>>,
 
+    /// Predicates which have yet to be solved, and the solutions of those
+    /// which have.
+    wanted: Vec<Wanted<'a>>,
+    dicts: Dicts<'a>,
+
     counter: usize,
     level: usize,
 }
@@ -32,10 +42,25 @@ impl<'a> Solver<'a> {
         Self {
             subst: BTreeMap::new(),
             row_subst: BTreeMap::new(),
+            wanted: Vec::new(),
+            dicts: BTreeMap::new(),
             counter: 0,
             level: 0,
         }
     }
+
+    /// Create a solver whose variables are all numbered after `counter`, such
+    /// that they are distinct from those of an earlier solver.
+    pub fn starting_at(counter: usize) -> Self {
+        Self {
+            counter,
+            ..Self::new()
+        }
+    }
+
+    pub fn counter(&self) -> usize {
+        self.counter
+    }
 }
 
 impl<'a, 'src> Checker<'a, '_, 'src, '_> {
@@ -44,6 +69,18 @@ impl<'a, 'src> Checker<'a, '_, 'src, '_> {
         self.alloc.alloc(ty)
     }
 
+    /// Apply the final substitution to a type, defaulting any unsolved
+    /// variables.
+    pub fn zonk(&self, ty: &'a Type<'a>) -> &'a Type<'a> {
+        let ty = self.solver.zonk(self.alloc, ty);
+        self.alloc.alloc(ty)
+    }
+
+    pub fn zonk_row(&self, row: &'a Row<'a>) -> &'a Row<'a> {
+        let row = self.solver.zonk_row(self.alloc, row);
+        self.alloc.alloc(row)
+    }
+
     pub fn generalize(&mut self, explicit: &[Generic], ty: &'a Type<'a>) -> Scheme<'a> {
         let mut pretty = self.pretty.build();
         self.solver
@@ -53,10 +90,44 @@ impl<'a, 'src> Checker<'a, '_, 'src, '_> {
     pub fn instantiate_name(&mut self, name: &Name) -> &'a Type<'a> {
         let scheme = self.env.lookup(name);
         let mut pretty = self.pretty.build();
-        let ty = self.solver.instantiate(&mut pretty, self.alloc, scheme);
+        let (ty, _, preds) = self.solver.instantiate(&mut pretty, self.alloc, scheme);
+        debug_assert!(preds.is_empty());
         self.alloc.alloc(ty)
     }
 
+    /// Instantiate the scheme of a variable, returning the arguments it was
+    /// instantiated with along with a dictionary variable for each of its
+    /// predicates which must be solved at some later point.
+    pub fn instantiate_var(
+        &mut self,
+        at: Span,
+        name: &Name,
+    ) -> (&'a Type<'a>, &'a [TypeArg<'a>], &'a [DictVar]) {
+        let primitive;
+        let scheme = match Primitive::from_name(self.names, name) {
+            Some(op) => {
+                primitive = self.primitive(op);
+                &primitive
+            }
+
+            None => self.env.lookup(name),
+        };
+
+        let mut pretty = self.pretty.build();
+        let (ty, args, preds) = self.solver.instantiate(&mut pretty, self.alloc, scheme);
+
+        let dicts: Vec<_> = preds
+            .into_iter()
+            .map(|pred| self.solver.want(at, pred))
+            .collect();
+
+        (
+            self.alloc.alloc(ty),
+            self.alloc.alloc_slice_copy(&args),
+            self.alloc.alloc_slice_copy(&dicts),
+        )
+    }
+
     pub fn minimize(&mut self, keep: &BTreeSet<TypeVar>, ty: &'a Type<'a>) {
         let mut pretty = self.pretty.build();
         self.solver.minimize(&mut pretty, self.alloc, keep, ty)
@@ -64,8 +135,14 @@ impl<'a, 'src> Checker<'a, '_, 'src, '_> {
 
     pub fn unify(&mut self, at: Span, lhs: &'a Type<'a>, rhs: &'a Type<'a>) {
         let mut pretty = self.pretty.build();
-        self.solver
-            .unify(&mut pretty, self.alloc, self.errors, at, lhs, rhs)
+        let mut reporting = Reporting {
+            pretty: &mut pretty,
+            errors: self.errors,
+            at,
+            records: &self.records,
+        };
+
+        self.solver.unify(&mut reporting, self.alloc, lhs, rhs)
     }
 
     pub fn vars_in_ty(&self, ty: &Type) -> BTreeSet<TypeVar> {
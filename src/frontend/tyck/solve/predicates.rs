@@ -0,0 +1,159 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::Solver;
+use crate::frontend::errors::ErrorId;
+use crate::frontend::names::{Name, ScopeName};
+use crate::frontend::source::Span;
+use crate::frontend::tyck::classes::Head;
+use crate::frontend::tyck::types::{Generic, Predicate};
+use crate::frontend::tyck::{Checker, Type};
+
+/// A placeholder for the dictionary passed as evidence for a predicate.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct DictVar(usize);
+
+/// The dictionary a predicate was solved with.
+#[derive(Clone, Copy, Debug)]
+pub enum Dict {
+    /// The predicate could not be solved.
+    Invalid(ErrorId),
+
+    /// A dictionary parameter of an enclosing binding.
+    Param(Name),
+
+    /// The dictionary of an instance.
+    Instance(Name),
+}
+
+/// The solution of every dictionary variable, along with the type of the
+/// dictionary.
+pub type Dicts<'a> = BTreeMap<DictVar, (Dict, &'a Type<'a>)>;
+
+/// A predicate arising from instantiating the scheme of a variable.
+pub(super) struct Wanted<'a> {
+    var: DictVar,
+    pred: Predicate<'a>,
+    at: Span,
+}
+
+impl<'a> Solver<'a> {
+    pub(super) fn want(&mut self, at: Span, pred: Predicate<'a>) -> DictVar {
+        self.counter += 1;
+        let var = DictVar(self.counter);
+        self.wanted.push(Wanted { var, pred, at });
+        var
+    }
+
+    /// Take the solution of every dictionary variable solved so far, along with
+    /// the type of the dictionary.
+    pub(in crate::frontend::tyck) fn take_dicts(&mut self) -> Dicts<'a> {
+        std::mem::take(&mut self.dicts)
+    }
+}
+
+/// Predicate solving
+impl<'a> Checker<'a, '_, '_, '_> {
+    /// Solve the wanted predicates after a group of bindings have been
+    /// generalized over `params`. A predicate is either
+    ///
+    /// - on one of `params`, in which case it becomes a predicate of the
+    ///   bindings and is solved by a dictionary parameter,
+    /// - on some type constructor, in which case it is solved by the instance
+    ///   for that constructor,
+    /// - on a type variable from an enclosing binding, in which case it is
+    ///   deferred until that binding is generalized, or
+    /// - ambiguous, in which case the type is defaulted to `int` if possible.
+    ///
+    /// Returns the predicates of the bindings and the names of their dictionary
+    /// parameters.
+    pub(in crate::frontend::tyck) fn solve_predicates(
+        &mut self,
+        params: &BTreeSet<Generic>,
+    ) -> (Vec<Predicate<'a>>, &'a [Name]) {
+        let mut preds = Vec::new();
+        let mut names = Vec::new();
+        let mut given = BTreeMap::new();
+
+        for wanted in std::mem::take(&mut self.solver.wanted) {
+            let class = wanted.pred.class;
+            let ty = self.apply(wanted.pred.ty).expand();
+
+            let dict = match ty {
+                Type::Invalid(e) => Dict::Invalid(*e),
+
+                Type::Param(generic) if params.contains(generic) => {
+                    let name = *given.entry((class, *generic)).or_insert_with(|| {
+                        let ident = self.names.get_name(&class).name;
+                        let name = self.names.name(ScopeName::Item(class), ident);
+                        preds.push(Predicate { class, ty });
+                        names.push(name);
+                        name
+                    });
+
+                    Dict::Param(name)
+                }
+
+                Type::Param(_) if self.solver.level > 0 => {
+                    self.solver.wanted.push(wanted);
+                    continue;
+                }
+
+                Type::Var(_, level) if !level.can_generalize(self.solver.level) => {
+                    self.solver.wanted.push(wanted);
+                    continue;
+                }
+
+                Type::Var(..) => self.default_predicate(wanted.at, class, ty),
+                _ => self.resolve_instance(wanted.at, class, ty),
+            };
+
+            let named = self.alloc.alloc(Type::Named(class));
+            let dict_ty = &*self.alloc.alloc(Type::Apply(named, ty));
+            self.solver.dicts.insert(wanted.var, (dict, dict_ty));
+        }
+
+        (preds, self.alloc.alloc_slice_copy(&names))
+    }
+
+    /// Solve a predicate on a type constructor with its instance.
+    fn resolve_instance(&mut self, at: Span, class: Name, ty: &'a Type<'a>) -> Dict {
+        let mut head = ty.expand();
+        while let Type::Apply(t, _) = head {
+            head = t.expand();
+        }
+
+        let head = match head {
+            Type::Invalid(e) => return Dict::Invalid(*e),
+            Type::Named(name) => Some(Head::Named(*name)),
+            Type::Integer => Some(Head::Integer),
+            Type::Arrow => Some(Head::Arrow),
+            _ => None,
+        };
+
+        match head.and_then(|head| self.classes.instance(class, head)) {
+            Some(dict) => Dict::Instance(dict),
+            None => {
+                let pred = self.pretty.build().pred(&Predicate { class, ty });
+                let e = self.errors.type_error(at).no_instance(pred);
+                Dict::Invalid(e)
+            }
+        }
+    }
+
+    /// Solve an ambiguous predicate by defaulting its type to `int`.
+    fn default_predicate(&mut self, at: Span, class: Name, ty: &'a Type<'a>) -> Dict {
+        match self.classes.instance(class, Head::Integer) {
+            Some(dict) => {
+                let int = self.alloc.alloc(Type::Integer);
+                self.unify(at, ty, int);
+                Dict::Instance(dict)
+            }
+
+            None => {
+                let pred = self.pretty.build().pred(&Predicate { class, ty });
+                let e = self.errors.type_error(at).ambiguous_predicate(pred);
+                Dict::Invalid(e)
+            }
+        }
+    }
+}
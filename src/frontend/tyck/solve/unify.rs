@@ -1,12 +1,12 @@
+use std::collections::BTreeMap;
+
 use bumpalo::Bump;
 use log::trace;
 
-use crate::frontend::errors::Errors;
-use crate::frontend::names::Label;
-use crate::frontend::source::Span;
-use crate::frontend::tyck::pretty::Prettifier;
+use crate::frontend::names::{Label, Name};
+use crate::frontend::tyck::records::spine;
 use crate::frontend::tyck::types::Row;
-use crate::frontend::tyck::{Reporting, Type};
+use crate::frontend::tyck::{Reporting, Scheme, Type};
 
 use super::{Level, Solver, TypeVar};
 
@@ -14,19 +14,17 @@ use super::{Level, Solver, TypeVar};
 impl<'a> Solver<'a> {
     pub(super) fn unify(
         &mut self,
-        pretty: &mut Prettifier,
+        reporting: &mut Reporting,
         alloc: &'a Bump,
-        errors: &mut Errors,
-        at: Span,
         lhs: &'a Type<'a>,
         rhs: &'a Type<'a>,
     ) {
         trace!(
             "uni {}  ~  {}",
-            pretty.ty(&self.apply(alloc, lhs)),
-            pretty.ty(&self.apply(alloc, rhs))
+            reporting.pretty.ty(&self.apply(alloc, lhs)),
+            reporting.pretty.ty(&self.apply(alloc, rhs))
         );
-        self.unify_ty(&mut Reporting { pretty, errors, at }, alloc, lhs, rhs)
+        self.unify_ty(reporting, alloc, lhs, rhs)
     }
 
     fn unify_ty(
@@ -85,6 +83,30 @@ impl<'a> Solver<'a> {
                 self.set(reporting, alloc, var, level, ty)
             }
 
+            // Aliases are unified by their expansions, but mismatches are
+            // reported with the alias itself.
+            (Type::Alias(..), _) | (_, Type::Alias(..)) => {
+                let t = lhs.expand();
+                let u = rhs.expand();
+
+                if !conflicting(t, u) {
+                    return self.unify_ty(reporting, alloc, t, u);
+                }
+
+                let e = {
+                    let lhs = reporting.pretty.ty(lhs);
+                    let rhs = reporting.pretty.ty(rhs);
+                    let e = reporting
+                        .errors
+                        .type_error(reporting.at)
+                        .inequal_types(lhs, rhs);
+                    alloc.alloc(Type::Invalid(e))
+                };
+
+                self.unify_ty(reporting, alloc, t, e);
+                self.unify_ty(reporting, alloc, e, u);
+            }
+
             (Type::Invalid(_), Type::Invalid(_)) => {}
 
             // Use the exhaustiveness check to ensure termination when unifying
@@ -108,12 +130,16 @@ impl<'a> Solver<'a> {
                 | Type::Apply(..),
             ) => {
                 let e = {
+                    let nominal = nominal_record(reporting.records, lhs, rhs)
+                        .or_else(|| nominal_record(reporting.records, rhs, lhs))
+                        .map(|name| reporting.pretty.ty(name));
                     let lhs = reporting.pretty.ty(lhs);
                     let rhs = reporting.pretty.ty(rhs);
-                    let e = reporting
-                        .errors
-                        .type_error(reporting.at)
-                        .inequal_types(lhs, rhs);
+                    let mut errors = reporting.errors.type_error(reporting.at);
+                    let e = match nominal {
+                        Some(name) => errors.inequal_nominal_record(lhs, rhs, name),
+                        None => errors.inequal_types(lhs, rhs),
+                    };
                     alloc.alloc(Type::Invalid(e))
                 };
 
@@ -191,7 +217,7 @@ impl<'a> Solver<'a> {
         level: &Level,
         ty: &'a Type<'a>,
     ) {
-        if let Type::Var(v, l2) = ty {
+        if let Type::Var(v, l2) = ty.expand() {
             l2.set_min(level);
             if v == var {
                 return;
@@ -275,6 +301,8 @@ impl<'a> Solver<'a> {
             Type::Variant(row) => self.occurs_row(var, l1, row),
 
             Type::Apply(t, u) => self.occurs(var, l1, t) || self.occurs(var, l1, u),
+
+            Type::Alias(_, _, body) => self.occurs(var, l1, body),
         }
     }
 
@@ -359,6 +387,38 @@ impl<'a> Solver<'a> {
     }
 }
 
+/// If `ty` is a nominal record and `other` a structural one, get the name of
+/// the nominal record. Fields of nominal records are only accessible where their
+/// type is known, so such a mismatch usually means that was not the case.
+fn nominal_record<'b>(
+    records: &BTreeMap<Name, Scheme>,
+    ty: &'b Type<'b>,
+    other: &Type,
+) -> Option<&'b Type<'b>> {
+    let Type::Record(_) = other.expand() else {
+        return None;
+    };
+
+    match spine(ty) {
+        (head @ Type::Named(name), _) if records.contains_key(name) => Some(head),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the two (expanded) types definitely cannot be unified
+/// because they have different outermost type constructors.
+fn conflicting(lhs: &Type, rhs: &Type) -> bool {
+    match (lhs, rhs) {
+        (Type::Invalid(_) | Type::Var(..) | Type::Alias(..), _)
+        | (_, Type::Invalid(_) | Type::Var(..) | Type::Alias(..)) => false,
+
+        (Type::Param(t), Type::Param(u)) => t != u,
+        (Type::Named(n), Type::Named(m)) => n != m,
+
+        (lhs, rhs) => std::mem::discriminant(lhs) != std::mem::discriminant(rhs),
+    }
+}
+
 pub(super) fn row_tail<'b>(row: &'b Row<'b>) -> Option<&'b TypeVar> {
     match row {
         Row::Var(var, _) => Some(var),
@@ -54,6 +54,8 @@ impl Solver<'_> {
                 .collect(),
 
             Type::Record(row) | Type::Variant(row) => self.vars_in_row(row),
+
+            Type::Alias(_, _, body) => self.vars_in_ty(body),
         }
     }
 
@@ -0,0 +1,76 @@
+use bumpalo::Bump;
+
+use crate::frontend::tyck::types::Row;
+use crate::frontend::tyck::Type;
+
+use super::Solver;
+
+/// Zonking
+impl<'a> Solver<'a> {
+    /// Apply the final substitution to the given type. Any unification
+    /// variable which is still unsolved at this point is entirely
+    /// unconstrained, so it is defaulted to the unit type, or to the empty row
+    /// for row variables.
+    pub(super) fn zonk(&self, alloc: &'a Bump, ty: &'a Type<'a>) -> Type<'a> {
+        match ty {
+            Type::Invalid(_)
+            | Type::Unit
+            | Type::Integer
+            | Type::Param(_)
+            | Type::Named(_)
+            | Type::Arrow => ty.clone(),
+
+            Type::Var(v, _) => {
+                if let Some(ty) = self.subst.get(v) {
+                    self.zonk(alloc, ty)
+                } else {
+                    Type::Unit
+                }
+            }
+
+            Type::Record(row) => {
+                let row = alloc.alloc(self.zonk_row(alloc, row));
+                Type::Record(row)
+            }
+
+            Type::Variant(row) => {
+                let row = alloc.alloc(self.zonk_row(alloc, row));
+                Type::Variant(row)
+            }
+
+            Type::Apply(t, u) => {
+                let t = alloc.alloc(self.zonk(alloc, t));
+                let u = alloc.alloc(self.zonk(alloc, u));
+                Type::Apply(t, u)
+            }
+
+            Type::Alias(name, args, body) => {
+                let args = alloc.alloc_slice_fill_iter(
+                    args.iter().map(|arg| &*alloc.alloc(self.zonk(alloc, arg))),
+                );
+                let body = alloc.alloc(self.zonk(alloc, body));
+                Type::Alias(*name, args, body)
+            }
+        }
+    }
+
+    pub(super) fn zonk_row(&self, alloc: &'a Bump, row: &'a Row<'a>) -> Row<'a> {
+        match row {
+            Row::Invalid(_) | Row::Empty | Row::Param(_) => row.clone(),
+
+            Row::Var(v, _) => {
+                if let Some(record) = self.row_subst.get(v) {
+                    self.zonk_row(alloc, record)
+                } else {
+                    Row::Empty
+                }
+            }
+
+            Row::Extend(label, ty, rest) => {
+                let ty = alloc.alloc(self.zonk(alloc, ty));
+                let rest = alloc.alloc(self.zonk_row(alloc, rest));
+                Row::Extend(*label, ty, rest)
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use super::check;
+
+#[test]
+fn simple_alias() {
+    let source = "
+        type Point = { x : int, y : int }
+        let origin : Point = { x = 0, y = 0 }
+    ";
+
+    let (ty, errors) = check(source, "origin");
+    assert_eq!("Point", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn parameterized_alias() {
+    let source = "
+        type Pair 'a = { fst : 'a, snd : 'a }
+        let swap (p : Pair 'a) : Pair 'a = { fst = p.snd, snd = p.fst }
+    ";
+
+    let (ty, errors) = check(source, "swap");
+    assert_eq!("Pair 'a -> { fst: 'a, snd: 'a }", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn aliases_refer_to_aliases() {
+    let source = "
+        type Endo a = a -> a
+        type IntEndo = Endo int
+        let f : IntEndo = x => x
+        let g = f 5
+    ";
+
+    let (ty, errors) = check(source, "g");
+    assert_eq!("int", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn mismatch_mentions_alias() {
+    let source = "
+        type Point = { x : int, y : int }
+        let bad : Point = 5
+    ";
+
+    let (_, errors) = check(source, "bad");
+    assert_eq!(
+        vec!["incompatible types", "expected `int`", " but got `Point`"],
+        errors
+    );
+}
+
+#[test]
+fn recursive_alias() {
+    let source = "
+        type A = B and B = { a : A }
+        let x = 5
+    ";
+
+    let (_, errors) = check(source, "x");
+    let recursive = errors
+        .iter()
+        .filter(|error| error.ends_with("refers to itself"))
+        .count();
+    assert_eq!(2, recursive);
+}
+
+#[test]
+fn partially_applied_alias() {
+    let source = "
+        type Pair 'a = { fst : 'a, snd : 'a }
+        let p : Pair = 5
+    ";
+
+    let (ty, errors) = check(source, "p");
+    assert_eq!("<error>", ty);
+    assert_eq!(
+        vec![
+            "type alias `Pair` is not fully applied",
+            "`Pair` takes 1 argument, but is given 0"
+        ],
+        errors
+    );
+}
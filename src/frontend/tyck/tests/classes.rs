@@ -0,0 +1,200 @@
+use super::check;
+
+const LIST: &str = "
+    data List a = Nil | Cons a (List a)
+
+    class Functor f = { map : ('a -> 'b) -> f 'a -> f 'b }
+
+    instance Functor List = {
+        map = f => (Nil => Nil | Cons x xs => Cons (f x) (map f xs))
+    }
+";
+
+#[test]
+fn functor_over_list() {
+    let source = format!("{LIST} let singletons = map (x => Cons x Nil) (Cons 1 Nil)");
+    let (ty, errors) = check(&source, "singletons");
+    assert_eq!("List (List int)", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn method_scheme() {
+    let source = format!("{LIST} let f = map");
+    let (ty, errors) = check(&source, "f");
+    assert_eq!("Functor '0 => ('1 -> '2) -> '0 '1 -> '0 '2", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn infer_predicate() {
+    let source = "
+        class Show a = { show : a -> int }
+        let twice x = { a = show x, b = show x }
+    ";
+
+    let (ty, errors) = check(source, "twice");
+    assert_eq!("Show '0 => '0 -> { a: int, b: int }", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn local_predicate_deferred() {
+    let source = "
+        class Show a = { show : a -> int }
+        let f x = let y = show x in y
+    ";
+
+    let (ty, errors) = check(source, "f");
+    assert_eq!("Show '0 => '0 -> int", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn multiple_predicates() {
+    let source = "
+        class Show a = { show : a -> int }
+        class Default a = { default : a }
+        instance Default int = { default = 0 }
+        let f x = { a = show x, b = default }
+    ";
+
+    let (ty, errors) = check(source, "f");
+    assert_eq!("(Default '1, Show '0) => '0 -> { a: int, b: '1 }", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn missing_instance() {
+    let source = format!(
+        "{LIST}
+        class Show a = {{ show : a -> int }}
+        instance Show int = {{ show = x => x }}
+        let x = show (Cons 1 Nil)
+    "
+    );
+
+    let (_, errors) = check(&source, "x");
+    assert_eq!(vec!["no instance for `Show (List int)`"], errors);
+}
+
+#[test]
+fn overlapping_instances() {
+    let source = "
+        class Show a = { show : a -> int }
+        instance Show int = { show = x => x }
+        instance Show int = { show = x => 0 }
+        let x = show 5
+    ";
+
+    let (ty, errors) = check(source, "x");
+    assert_eq!("int", ty);
+    assert_eq!(vec!["overlapping instances of `Show`"], errors);
+}
+
+#[test]
+fn missing_and_unknown_methods() {
+    let source = "
+        class Eq a = { eq : a -> a -> int, ne : a -> a -> int }
+        instance Eq int = { eq = x => y => 1, lt = x => y => 0 }
+        let x = eq 1 2
+    ";
+
+    let (_, errors) = check(source, "x");
+    assert_eq!(
+        vec![
+            "class `Eq` has no method `lt`",
+            "instance of `Eq` is missing methods",
+            "missing `ne`"
+        ],
+        errors
+    );
+}
+
+#[test]
+fn method_type_mismatch() {
+    let source = "
+        class Show a = { show : a -> int }
+        instance Show int = { show = { a = 1 } }
+        let x = 5
+    ";
+
+    let (_, errors) = check(source, "x");
+    assert_eq!(
+        vec![
+            "incompatible types",
+            "expected `int -> int`",
+            " but got `{ a: int }`"
+        ],
+        errors
+    );
+}
+
+#[test]
+fn default_to_int() {
+    let source = "
+        class Default a = { default : a }
+        instance Default int = { default = 0 }
+        let x = (_ => 1) default
+    ";
+
+    let (ty, errors) = check(source, "x");
+    assert_eq!("int", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn ambiguous_predicate() {
+    let source = "
+        class Default a = { default : a }
+        let x = (_ => 1) default
+    ";
+
+    let (_, errors) = check(source, "x");
+    assert_eq!(
+        vec![
+            "ambiguous constraint `Default $a`",
+            "the type it constrains is not known here",
+            "add a type annotation"
+        ],
+        errors
+    );
+}
+
+#[test]
+fn invalid_instance_head() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        class Show a = { show : a -> int }
+        instance Show (List int) = { show = x => 0 }
+        let x = 5
+    ";
+
+    let (_, errors) = check(source, "x");
+    assert_eq!(
+        vec![
+            "invalid instance head",
+            "an instance must be for a type constructor applied to distinct type variables"
+        ],
+        errors
+    );
+}
+
+#[test]
+fn constrained_pattern_binding() {
+    let source = "
+        class Show a = { show : a -> int }
+        let { f = f, g = g } = { f = show, g = show }
+        let x = 5
+    ";
+
+    let (_, errors) = check(source, "x");
+    assert_eq!(
+        vec![
+            "constrained bindings must bind a single name",
+            "the type of this binding has class constraints, so it must be a function of its dictionaries",
+            "split the pattern into separate bindings"
+        ],
+        errors
+    );
+}
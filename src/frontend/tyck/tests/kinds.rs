@@ -0,0 +1,125 @@
+use super::check;
+
+#[test]
+fn applied_too_many_arguments() {
+    let source = "
+        let x : int int = 5
+    ";
+
+    let (ty, errors) = check(source, "x");
+    assert_eq!("<error>", ty);
+    assert_eq!(
+        vec![
+            "type applied to too many arguments",
+            "a type of kind `Type` takes no arguments"
+        ],
+        errors
+    );
+}
+
+#[test]
+fn missing_argument() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        let x : List = Nil
+    ";
+
+    let (ty, errors) = check(source, "x");
+    assert_eq!("<error>", ty);
+    assert_eq!(
+        vec![
+            "incompatible kinds",
+            "expected a type of kind `Type`",
+            " but got one of kind `Type -> Type`"
+        ],
+        errors
+    );
+}
+
+#[test]
+fn infer_higher_kind() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        data Wrap f a = Wrap (f a)
+        let w : Wrap List int = Wrap Nil
+        let bad : Wrap int int = 5
+    ";
+
+    let (ty, errors) = check(source, "w");
+    assert_eq!("Wrap List int", ty);
+    assert_eq!(
+        vec![
+            "incompatible kinds",
+            "expected a type of kind `Type -> Type`",
+            " but got one of kind `Type`"
+        ],
+        errors
+    );
+}
+
+#[test]
+fn kind_annotations() {
+    let source = "
+        data Fix (f : Type -> Type) = Fix (f (Fix f))
+        data Maybe a : Type = Nothing | Just a
+        let x : Fix Maybe = Fix Nothing
+    ";
+
+    let (ty, errors) = check(source, "x");
+    assert_eq!("Fix Maybe", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn row_is_not_type() {
+    let source = "
+        data R (r : Row) = R r
+        let x = 5
+    ";
+
+    let (_, errors) = check(source, "x");
+    assert_eq!(
+        vec![
+            "incompatible kinds",
+            "expected a type of kind `Type`",
+            " but got one of kind `Row`"
+        ],
+        errors
+    );
+}
+
+#[test]
+fn infinite_kind() {
+    let source = "
+        data Bad a = Bad (a a)
+        let x = 5
+    ";
+
+    let (_, errors) = check(source, "x");
+    assert_eq!(
+        vec![
+            "infinite kind",
+            "the kind of this type would have to contain itself"
+        ],
+        errors
+    );
+}
+
+#[test]
+fn mutually_recursive_kinds() {
+    let source = "
+        data Rose f a = Rose a (Forest f a)
+        and Forest f a = Forest (f (Rose f a))
+        let bad : Rose int int = 5
+    ";
+
+    let (_, errors) = check(source, "bad");
+    assert_eq!(
+        vec![
+            "incompatible kinds",
+            "expected a type of kind `Type -> Type`",
+            " but got one of kind `Type`"
+        ],
+        errors
+    );
+}
@@ -1,6 +1,13 @@
+mod aliases;
+mod classes;
 mod generalize;
+mod kinds;
+mod prelude;
+mod records;
 mod rows;
+mod sections;
 mod sums;
+mod zonk;
 
 use std::cell::RefCell;
 use std::collections::BTreeMap;
@@ -9,10 +16,13 @@ use bumpalo::Bump;
 
 use super::pretty::Pretty;
 use super::types::Row;
-use super::{Checker, Type};
+use super::{infer, Checker, Type};
 use crate::frontend::errors::Errors;
 use crate::frontend::names::{Name, Names, ScopeName};
-use crate::frontend::source::SourceId;
+use crate::frontend::parse::parse;
+use crate::frontend::resolve::resolve;
+use crate::frontend::source::{SourceId, Sources};
+use crate::frontend::trees::inferred::{ItemNode, PolyPatternNode};
 use crate::frontend::trees::resolved::{Expr, ExprNode, Pattern, PatternNode};
 
 struct Store<'a> {
@@ -44,7 +54,7 @@ impl<'a> Store<'a> {
             .with_show_levels(true)
             .with_show_error_id(true);
 
-        let checker = Checker::new(&alloc, &mut errors, &mut pretty);
+        let checker = Checker::new(&alloc, &names, &mut errors, &mut pretty);
         f(this, checker)
     }
 
@@ -197,3 +207,43 @@ impl<'a> Store<'a> {
         rest
     }
 }
+
+/// Check `source` and return the type of the item `let {name} = ...` along
+/// with the titles and notes of every reported error.
+fn check(source: &str, name: &str) -> (String, Vec<String>) {
+    let sources = Sources::new();
+    let source = sources.add(source);
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let resolved = resolve(&names, &alloc, &parsed);
+    let mut program = infer(&alloc, &names, &resolved);
+
+    let mut pretty = Pretty::new(&names);
+    let ty = program
+        .items
+        .iter()
+        .flat_map(|items| items.iter())
+        .find_map(|item| match &item.node {
+            ItemNode::Let(pattern, _, _) => match pattern.node {
+                PolyPatternNode::Bind(bound) if names.get_name(&bound).name.name() == name => {
+                    Some(pretty.build().scheme(&pattern.scheme))
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .expect("item is defined");
+
+    let mut errors: Vec<_> = program.errors.drain().collect();
+    errors.sort_by_key(|(id, _)| *id);
+    let errors = errors
+        .into_iter()
+        .flat_map(|(_, error)| {
+            std::iter::once(error.title).chain(error.notes.into_iter().map(|(note, _)| note))
+        })
+        .collect();
+
+    (ty, errors)
+}
@@ -0,0 +1,61 @@
+use bumpalo::Bump;
+
+use super::check;
+use crate::frontend::names::Names;
+use crate::frontend::parse::parse;
+use crate::frontend::prelude;
+use crate::frontend::resolve::resolve_with_prelude;
+use crate::frontend::source::{SourceId, Sources};
+use crate::frontend::tyck;
+
+#[test]
+fn the_prelude_is_well_typed() {
+    let source = &prelude::source().content;
+
+    let (ty, errors) = check(source, "map");
+    assert_eq!("('0 -> '1) -> List '0 -> List '1", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let (ty, _) = check(source, "fold");
+    assert_eq!("('0 -> '1 -> '0) -> '0 -> List '1 -> '0", ty);
+}
+
+#[test]
+fn primitives() {
+    let (ty, errors) = check("let f x = less x 0 { a = x } { a = mul x x }", "f");
+    assert_eq!("int -> { a: int }", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let (_, errors) = check("let f x = less x 0 x { a = x }", "f");
+    assert!(!errors.is_empty());
+}
+
+/// Check a program with the prelude, returning the address of every component
+/// of typed prelude items in it.
+fn prelude_components(source: &str) -> Vec<usize> {
+    let sources = Sources::new();
+    let source = sources.add(source);
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let resolved = resolve_with_prelude(&names, &alloc, prelude::resolved(), &parsed);
+    let program = tyck::check(&alloc, &names, &resolved);
+    assert_eq!(0, program.errors.num_errors());
+
+    program
+        .items
+        .iter()
+        .filter(|items| items[0].span.source == SourceId::PRELUDE)
+        .map(|items| items.as_ptr() as usize)
+        .collect()
+}
+
+#[test]
+fn the_prelude_is_checked_once() {
+    let first = prelude_components("let x = not True");
+    let second = prelude_components("let y = not (not False)");
+
+    assert_eq!(2, first.len());
+    assert_eq!(first, second);
+}
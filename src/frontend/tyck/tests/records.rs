@@ -0,0 +1,83 @@
+use super::check;
+
+#[test]
+fn construct_nominal_record() {
+    let source = "
+        data Person = Person { name : int, age : int }
+        let p = Person { name = 1, age = 2 }
+    ";
+
+    let (ty, errors) = check(source, "p");
+    assert_eq!("Person", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn access_nominal_field() {
+    let source = "
+        data Person = Person { name : int, age : int }
+        let age (p : Person) = p.age
+    ";
+
+    let (ty, errors) = check(source, "age");
+    assert_eq!("Person -> int", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn access_parameterized_field() {
+    let source = "
+        data Box a = Box { value : a }
+        let unbox (b : Box 'a) = b.value
+    ";
+
+    let (ty, errors) = check(source, "unbox");
+    assert_eq!("Box 'a -> 'a", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn record_pattern() {
+    let source = "
+        data Person = Person { name : int, age : int }
+        let name (Person { name }) = name
+    ";
+
+    let (ty, errors) = check(source, "name");
+    assert_eq!("Person -> int", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn missing_nominal_field() {
+    let source = "
+        data Person = Person { name : int, age : int }
+        let height (p : Person) = p.height
+    ";
+
+    let (ty, errors) = check(source, "height");
+    assert_eq!("Person -> <error>", ty);
+    assert_eq!("incompatible types", errors[0]);
+}
+
+#[test]
+fn unannotated_nominal_field() {
+    let source = "
+        data Person = Person { name : int, age : int }
+        let older p = Person { name = p.name, age = p.age }
+        let x = older (Person { name = 1, age = 2 })
+    ";
+
+    let (_, errors) = check(source, "x");
+    assert_eq!("incompatible types", errors[0]);
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.contains("`Person` is a nominal record")),
+        "{errors:?}"
+    );
+    assert!(
+        errors.iter().any(|e| e.contains("`(p : Person)`")),
+        "{errors:?}"
+    );
+}
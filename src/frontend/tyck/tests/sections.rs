@@ -0,0 +1,40 @@
+use super::check;
+
+const OPERATORS: &str = "
+    let infixl 6 + a b = add a b
+    let infixl 7 * a b = mul a b
+    let infixr 5 <> a b = { fst = a, snd = b }
+";
+
+#[test]
+fn sections_are_functions() {
+    let source = format!("{OPERATORS} let f = (<> 1)");
+    let (ty, errors) = check(&source, "f");
+    assert_eq!("'0 -> { fst: '0, snd: int }", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let source = format!("{OPERATORS} let g = (1 <>)");
+    let (ty, errors) = check(&source, "g");
+    assert_eq!("'0 -> { fst: int, snd: '0 }", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn sections_with_several_operators() {
+    let source = format!("{OPERATORS} let f = (+ 2 * 1)");
+    let (ty, errors) = check(&source, "f");
+    assert_eq!("int -> int", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let source = format!("{OPERATORS} let g = (<> 1 + 2)");
+    let (ty, errors) = check(&source, "g");
+    assert_eq!("'0 -> { fst: '0, snd: int }", ty);
+    assert!(errors.is_empty(), "{errors:?}");
+}
+
+#[test]
+fn ill_typed_sections() {
+    let source = format!("{OPERATORS} let f = (+ {{ a = 1 }})");
+    let (_, errors) = check(&source, "f");
+    assert!(!errors.is_empty());
+}
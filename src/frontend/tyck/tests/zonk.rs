@@ -0,0 +1,213 @@
+use bumpalo::Bump;
+
+use super::super::{check, Pretty, Row, Type, TypeArg};
+use crate::frontend::names::Names;
+use crate::frontend::parse::parse;
+use crate::frontend::resolve::resolve;
+use crate::frontend::source::Sources;
+use crate::frontend::trees::typed::{Expr, ExprNode, Item, ItemNode, Pattern, PatternNode};
+
+/// Check the given source and call `f` with the bound expression of the item
+/// named `name`.
+fn with_bound<F, T>(source: &str, name: &str, f: F) -> T
+where
+    F: for<'a> FnOnce(&Expr<'a, '_>, &mut Pretty) -> T,
+{
+    let sources = Sources::new();
+    let source = sources.add(source);
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let resolved = resolve(&names, &alloc, &parsed);
+    let program = check(&alloc, &names, &resolved);
+
+    assert_eq!(0, program.errors.num_errors());
+    assert!(program
+        .items
+        .iter()
+        .all(|items| items.iter().all(item_is_zonked)));
+
+    let expr = program
+        .items
+        .iter()
+        .flat_map(|items| items.iter())
+        .find_map(|item| match &item.node {
+            ItemNode::Let(pattern, expr, _) => match pattern.node {
+                PatternNode::Bind(bound) if names.get_name(&bound).name.name() == name => {
+                    Some(expr)
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .expect("item is defined");
+
+    let mut pretty = Pretty::new(&names);
+    f(expr, &mut pretty)
+}
+
+fn item_is_zonked(item: &Item) -> bool {
+    match &item.node {
+        ItemNode::Let(pattern, expr, _) => pattern_is_zonked(pattern) && expr_is_zonked(expr),
+        ItemNode::Instance(_, expr, _) => expr_is_zonked(expr),
+        _ => true,
+    }
+}
+
+fn expr_is_zonked(expr: &Expr) -> bool {
+    type_is_zonked(expr.ty)
+        && match &expr.node {
+            ExprNode::Var(var) => var.args.iter().all(|arg| match arg {
+                TypeArg::Type(ty) => type_is_zonked(ty),
+                TypeArg::Row(row) => row_is_zonked(row),
+            }),
+
+            ExprNode::Group(expr) | ExprNode::Field(expr, ..) | ExprNode::Restrict(expr, _) => {
+                expr_is_zonked(expr)
+            }
+
+            ExprNode::Record(fields, extend) => {
+                fields.iter().all(|(_, _, field)| expr_is_zonked(field))
+                    && extend.map(expr_is_zonked).unwrap_or(true)
+            }
+
+            ExprNode::Apply([fun, arg]) => expr_is_zonked(fun) && expr_is_zonked(arg),
+            ExprNode::Lambda(arrows) => arrows
+                .iter()
+                .all(|(pattern, body)| pattern_is_zonked(pattern) && expr_is_zonked(body)),
+
+            ExprNode::Let(pattern, [bound, body], _) => {
+                pattern_is_zonked(pattern) && expr_is_zonked(bound) && expr_is_zonked(body)
+            }
+
+            _ => true,
+        }
+}
+
+fn pattern_is_zonked(pattern: &Pattern) -> bool {
+    type_is_zonked(pattern.scheme.ty)
+        && match &pattern.node {
+            PatternNode::Group(pattern) => pattern_is_zonked(pattern),
+            PatternNode::Apply([a, b]) | PatternNode::Or([a, b]) | PatternNode::And([a, b]) => {
+                pattern_is_zonked(a) && pattern_is_zonked(b)
+            }
+
+            PatternNode::Record(fields) => {
+                fields.iter().all(|(_, _, field)| pattern_is_zonked(field))
+            }
+
+            _ => true,
+        }
+}
+
+fn type_is_zonked(ty: &Type) -> bool {
+    match ty {
+        Type::Var(..) => false,
+        Type::Apply(t, u) => type_is_zonked(t) && type_is_zonked(u),
+        Type::Record(row) => row_is_zonked(row),
+        _ => true,
+    }
+}
+
+fn row_is_zonked(row: &Row) -> bool {
+    match row {
+        Row::Var(..) => false,
+        Row::Extend(_, ty, rest) => type_is_zonked(ty) && row_is_zonked(rest),
+        _ => true,
+    }
+}
+
+#[test]
+fn instantiation_args() {
+    let source = "
+        let id x = x
+        let y = id 5
+    ";
+
+    let args = with_bound(source, "y", |expr, pretty| {
+        let ExprNode::Apply([fun, _]) = &expr.node else {
+            panic!("expected an application");
+        };
+
+        let ExprNode::Var(var) = &fun.node else {
+            panic!("expected a variable");
+        };
+
+        var.args
+            .iter()
+            .map(|arg| match arg {
+                TypeArg::Type(ty) => pretty.build().ty(ty),
+                TypeArg::Row(row) => pretty.build().record(row),
+            })
+            .collect::<Vec<_>>()
+    });
+
+    assert_eq!(vec!["int".to_string()], args);
+}
+
+#[test]
+fn row_instantiation_args() {
+    let source = "
+        let get r = r.a
+        let y = get { a = 5, b = 6 }
+    ";
+
+    let args = with_bound(source, "y", |expr, pretty| {
+        let ExprNode::Apply([fun, _]) = &expr.node else {
+            panic!("expected an application");
+        };
+
+        let ExprNode::Var(var) = &fun.node else {
+            panic!("expected a variable");
+        };
+
+        var.args
+            .iter()
+            .map(|arg| match arg {
+                TypeArg::Type(ty) => pretty.build().ty(ty),
+                TypeArg::Row(row) => pretty.build().record(row),
+            })
+            .collect::<Vec<_>>()
+    });
+
+    assert_eq!(2, args.len());
+    assert!(args.contains(&"int".to_string()), "{args:?}");
+    assert!(args.contains(&"{ b: int }".to_string()), "{args:?}");
+}
+
+#[test]
+fn unsolved_defaults_to_unit() {
+    let source = "let x = (_ => 1) (y => y)";
+
+    let ty = with_bound(source, "x", |expr, pretty| {
+        let ExprNode::Apply([_, arg]) = &expr.node else {
+            panic!("expected an application");
+        };
+
+        pretty.build().ty(arg.ty)
+    });
+
+    assert_eq!("unit -> unit", ty);
+}
+
+#[test]
+fn local_schemes_are_zonked() {
+    let source = "let f x = let g y = { a = x, b = y } in g 5";
+
+    let params = with_bound(source, "f", |expr, _| {
+        let ExprNode::Lambda([(_, body)]) = &expr.node else {
+            panic!("expected a lambda");
+        };
+
+        let ExprNode::Let(pattern, _, _) = &body.node else {
+            panic!("expected a let expression");
+        };
+
+        pattern.scheme.params.len()
+    });
+
+    // The type of `x` is a parameter of `f`, so only the type of `y` is
+    // generalized in `g`.
+    assert_eq!(1, params);
+}
@@ -23,6 +23,20 @@ pub enum Type<'a> {
     Variant(&'a Row<'a>),
 
     Apply(&'a Type<'a>, &'a Type<'a>),
+
+    /// A type alias applied to some arguments, along with the type it expands
+    /// to. The alias is kept around only for the sake of printing it.
+    Alias(Name, &'a [&'a Type<'a>], &'a Type<'a>),
+}
+
+impl<'a> Type<'a> {
+    /// Get the type this type ultimately expands to, if it is an alias.
+    pub fn expand(&self) -> &Type<'a> {
+        match self {
+            Self::Alias(_, _, body) => body.expand(),
+            ty => ty,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -47,9 +61,39 @@ pub enum Generic {
     Ticked(Name),
 }
 
+impl Generic {
+    /// Whether this parameter stands for a type or a row. Ticked parameters
+    /// always stand for types.
+    pub fn kind(&self) -> VarKind {
+        match self {
+            Self::Implicit(var) => var.1,
+            Self::Ticked(_) => VarKind::Type,
+        }
+    }
+}
+
+/// The argument a scheme is instantiated with for one of its parameters.
+#[derive(Clone, Copy, Debug)]
+pub enum TypeArg<'a> {
+    Type(&'a Type<'a>),
+    Row(&'a Row<'a>),
+}
+
+/// A predicate `C t` requires the type `t` to be an instance of the class
+/// `C`.
+#[derive(Clone, Copy, Debug)]
+pub struct Predicate<'a> {
+    pub class: Name,
+    pub ty: &'a Type<'a>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Scheme<'a> {
     pub params: Vec<Generic>,
+
+    /// The predicates which must hold for any instantiation of this scheme.
+    pub preds: Vec<Predicate<'a>>,
+
     pub ty: &'a Type<'a>,
 }
 
@@ -57,6 +101,7 @@ impl<'a> Scheme<'a> {
     pub fn mono(ty: &'a Type<'a>) -> Self {
         Self {
             params: Vec::new(),
+            preds: Vec::new(),
             ty,
         }
     }
@@ -65,16 +110,18 @@ impl<'a> Scheme<'a> {
         self.params.is_empty()
     }
 
-    /// Use the type parameters from this scheme on another type.
+    /// Use the type parameters and predicates from this scheme on another
+    /// type.
     pub fn onto(&self, ty: &'a Type<'a>) -> Self {
         Self {
             params: self.params.clone(),
+            preds: self.preds.clone(),
             ty,
         }
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Env<'a> {
     context: BTreeMap<Name, Scheme<'a>>,
 }
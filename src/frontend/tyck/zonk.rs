@@ -0,0 +1,280 @@
+//! Zonking applies the final substitution to an inferred program, producing a
+//! typed program without any unification variables. The substitution only
+//! lives as long as the checker, so this is done right after inference.
+
+use std::collections::BTreeMap;
+
+use super::solve::Dicts;
+use super::types::TypeArg;
+use super::{Checker, Dict, DictVar, Generic, Scheme, Type};
+use crate::frontend::trees::nodes::{ExprNode, ItemNode, PatternNode};
+use crate::frontend::trees::{inferred, typed};
+
+impl<'a, 'src> Checker<'a, '_, 'src, '_> {
+    pub(super) fn zonk_program(
+        &mut self,
+        items: &'a [&'a [inferred::Item<'a, 'src>]],
+        dicts: Dicts<'a>,
+    ) -> (
+        &'a [&'a [typed::Item<'a, 'src>]],
+        BTreeMap<DictVar, typed::Evidence<'a>>,
+    ) {
+        let instances: BTreeMap<_, _> = items
+            .iter()
+            .flat_map(|items| items.iter())
+            .filter_map(|item| match &item.node {
+                ItemNode::Instance((dict, scheme), _, _) => Some((*dict, scheme)),
+                _ => None,
+            })
+            .collect();
+
+        let dicts = dicts
+            .into_iter()
+            .map(|(var, (dict, ty))| {
+                let ty = self.zonk(ty);
+                let args = match dict {
+                    Dict::Instance(name) => instances
+                        .get(&name)
+                        .map(|scheme| self.instance_args(scheme, ty))
+                        .unwrap_or(&[]),
+                    Dict::Param(_) | Dict::Invalid(_) => &[],
+                };
+
+                (var, typed::Evidence { dict, args, ty })
+            })
+            .collect();
+
+        let mut dicts: BTreeMap<_, _> = dicts;
+        if let Some(prelude) = self.prelude {
+            dicts.extend(
+                prelude
+                    .dicts
+                    .iter()
+                    .map(|(var, evidence)| (*var, *evidence)),
+            );
+        }
+
+        let items = self.alloc.alloc_slice_fill_iter(items.iter().map(|items| {
+            let first = items.first().map(|item| (item.id, item.span));
+            match self.prelude_component(first) {
+                Some((_, typed)) => typed,
+                None => &*self
+                    .alloc
+                    .alloc_slice_fill_iter(items.iter().map(|item| self.zonk_item(item))),
+            }
+        }));
+
+        (items, dicts)
+    }
+
+    fn zonk_item(&mut self, item: &'a inferred::Item<'a, 'src>) -> typed::Item<'a, 'src> {
+        let node: typed::ItemNode = match &item.node {
+            ItemNode::Invalid(e) => ItemNode::Invalid(*e),
+            ItemNode::Let(pattern, expr, dicts) => {
+                ItemNode::Let(self.zonk_pattern(pattern), self.zonk_expr(expr), *dicts)
+            }
+
+            ItemNode::Data(scheme, data) => ItemNode::Data(self.zonk_scheme(scheme), data),
+            ItemNode::Alias(scheme, body) => {
+                ItemNode::Alias(self.zonk_scheme(scheme), self.zonk(body))
+            }
+
+            ItemNode::Class(scheme, methods) => {
+                let methods = self.alloc.alloc_slice_fill_iter(
+                    methods
+                        .iter()
+                        .map(|(name, scheme)| (*name, self.zonk_scheme(scheme))),
+                );
+
+                ItemNode::Class(self.zonk_scheme(scheme), methods)
+            }
+
+            ItemNode::Instance((dict, scheme), body, dicts) => {
+                let scheme = self.zonk_scheme(scheme);
+                ItemNode::Instance((*dict, scheme), self.zonk_expr(body), *dicts)
+            }
+        };
+
+        typed::Item {
+            node,
+            span: item.span,
+            id: item.id,
+        }
+    }
+
+    fn zonk_expr(&mut self, expr: &inferred::Expr<'a, 'src>) -> typed::Expr<'a, 'src> {
+        let node: typed::ExprNode = match &expr.node {
+            ExprNode::Invalid(e) => ExprNode::Invalid(*e),
+            ExprNode::Var(var) => {
+                let args = self
+                    .alloc
+                    .alloc_slice_fill_iter(var.args.iter().map(|arg| self.zonk_arg(arg)));
+
+                ExprNode::Var(typed::Var {
+                    name: var.name,
+                    args,
+                    dicts: var.dicts,
+                })
+            }
+
+            ExprNode::Hole => ExprNode::Hole,
+            ExprNode::Unit => ExprNode::Unit,
+            ExprNode::Number(v) => ExprNode::Number(v),
+            ExprNode::Anno(_, v) => match *v {},
+            ExprNode::Group(expr) => ExprNode::Group(self.alloc.alloc(self.zonk_expr(expr))),
+
+            ExprNode::Field(record, label, span) => {
+                let record = self.zonk_expr(record);
+                ExprNode::Field(self.alloc.alloc(record), *label, *span)
+            }
+
+            ExprNode::Record(fields, extend) => {
+                let fields = self.alloc.alloc_slice_fill_iter(
+                    fields
+                        .iter()
+                        .map(|(label, span, field)| (*label, *span, self.zonk_expr(field))),
+                );
+
+                let extend = extend.map(|extend| &*self.alloc.alloc(self.zonk_expr(extend)));
+                ExprNode::Record(fields, extend)
+            }
+
+            ExprNode::Restrict(record, label) => {
+                let record = self.zonk_expr(record);
+                ExprNode::Restrict(self.alloc.alloc(record), *label)
+            }
+
+            ExprNode::Apply([fun, arg]) => {
+                let fun = self.zonk_expr(fun);
+                let arg = self.zonk_expr(arg);
+                ExprNode::Apply(self.alloc.alloc([fun, arg]))
+            }
+
+            ExprNode::Lambda(arrows) => {
+                let arrows = self.alloc.alloc_slice_fill_iter(
+                    arrows
+                        .iter()
+                        .map(|(pattern, body)| (self.zonk_pattern(pattern), self.zonk_expr(body))),
+                );
+
+                ExprNode::Lambda(arrows)
+            }
+
+            ExprNode::Let(pattern, [bound, body], dicts) => {
+                let pattern = self.zonk_pattern(pattern);
+                let bound = self.zonk_expr(bound);
+                let body = self.zonk_expr(body);
+                ExprNode::Let(pattern, self.alloc.alloc([bound, body]), *dicts)
+            }
+        };
+
+        typed::Expr {
+            node,
+            span: expr.span,
+            ty: self.zonk(expr.ty),
+        }
+    }
+
+    fn zonk_pattern(&mut self, pattern: &inferred::PolyPattern<'a>) -> typed::Pattern<'a> {
+        let node: typed::PatternNode = match &pattern.node {
+            PatternNode::Invalid(e) => PatternNode::Invalid(*e),
+            PatternNode::Wildcard => PatternNode::Wildcard,
+            PatternNode::Unit => PatternNode::Unit,
+            PatternNode::Bind(name) => PatternNode::Bind(*name),
+            PatternNode::Constructor(name) => PatternNode::Constructor(*name),
+            PatternNode::Anno(_, v) => match *v {},
+            PatternNode::Group(pattern) => {
+                PatternNode::Group(self.alloc.alloc(self.zonk_pattern(pattern)))
+            }
+
+            PatternNode::Apply([a, b]) => {
+                let a = self.zonk_pattern(a);
+                let b = self.zonk_pattern(b);
+                PatternNode::Apply(self.alloc.alloc([a, b]))
+            }
+
+            PatternNode::Or([a, b]) => {
+                let a = self.zonk_pattern(a);
+                let b = self.zonk_pattern(b);
+                PatternNode::Or(self.alloc.alloc([a, b]))
+            }
+
+            PatternNode::And([a, b]) => {
+                let a = self.zonk_pattern(a);
+                let b = self.zonk_pattern(b);
+                PatternNode::And(self.alloc.alloc([a, b]))
+            }
+
+            PatternNode::Record(fields) => {
+                let fields = self.alloc.alloc_slice_fill_iter(
+                    fields
+                        .iter()
+                        .map(|(label, span, pattern)| (*label, *span, self.zonk_pattern(pattern))),
+                );
+
+                PatternNode::Record(fields)
+            }
+        };
+
+        typed::Pattern {
+            node,
+            span: pattern.span,
+            scheme: self.zonk_scheme(&pattern.scheme),
+        }
+    }
+
+    fn zonk_scheme(&mut self, scheme: &Scheme<'a>) -> Scheme<'a> {
+        let mut preds = scheme.preds.clone();
+        for pred in preds.iter_mut() {
+            pred.ty = self.zonk(pred.ty);
+        }
+
+        Scheme {
+            params: scheme.params.clone(),
+            preds,
+            ty: self.zonk(scheme.ty),
+        }
+    }
+
+    fn zonk_arg(&mut self, arg: &TypeArg<'a>) -> TypeArg<'a> {
+        match arg {
+            TypeArg::Type(ty) => TypeArg::Type(self.zonk(ty)),
+            TypeArg::Row(row) => TypeArg::Row(self.zonk_row(row)),
+        }
+    }
+
+    /// Find the arguments the scheme of an instance dictionary is instantiated
+    /// with to get the dictionary type `ty`. The parameters of an instance are
+    /// always the distinct type variables the type constructor of its head is
+    /// applied to, so they are found by matching the two types.
+    fn instance_args(&mut self, scheme: &Scheme<'a>, ty: &'a Type<'a>) -> &'a [TypeArg<'a>] {
+        fn bind<'a>(
+            subst: &mut BTreeMap<Generic, &'a Type<'a>>,
+            pattern: &'a Type<'a>,
+            ty: &'a Type<'a>,
+        ) {
+            match (pattern.expand(), ty.expand()) {
+                (Type::Param(param), _) => {
+                    subst.insert(*param, ty);
+                }
+
+                (Type::Apply(t1, u1), Type::Apply(t2, u2)) => {
+                    bind(subst, t1, t2);
+                    bind(subst, u1, u2);
+                }
+
+                _ => {}
+            }
+        }
+
+        let mut subst = BTreeMap::new();
+        bind(&mut subst, scheme.ty, ty);
+
+        self.alloc.alloc_slice_fill_iter(
+            scheme
+                .params
+                .iter()
+                .map(|param| TypeArg::Type(subst.get(param).copied().unwrap_or(&Type::Unit))),
+        )
+    }
+}
@@ -0,0 +1,365 @@
+//! Tests of whole programs. Every `.nml` file in `tests/ui` is parsed,
+//! resolved and checked with the prelude in scope, and the result is compared
+//! against the expectations written in the program itself:
+//!
+//! - `-- error: text` expects an error reported on the line above whose title
+//!   contains `text`,
+//! - `-- type: scheme` expects a name bound on the line above to have exactly
+//!   the scheme `scheme`.
+//!
+//! A `^` before the kind of expectation pins it to the column of the caret, as
+//! in `-- ^ error: unknown name`. Lines of expectations are skipped over when
+//! looking for the line above, so several of them can follow the same line.
+//!
+//! Every error which is not expected inline is compared against the `.stderr`
+//! file next to the program, which is empty if there is none. Running
+//! `cargo xtask bless` writes those errors to the `.stderr` file instead,
+//! removing it if there are none. The task runs these tests with the `BLESS`
+//! environment variable set, which can also be set by hand.
+
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bumpalo::Bump;
+
+use crate::frontend::errors::{Error, NoteType, Severity};
+use crate::frontend::names::Names;
+use crate::frontend::parse::parse;
+use crate::frontend::prelude;
+use crate::frontend::resolve::resolve_with_prelude;
+use crate::frontend::source::{Source, SourceId, Sources, Span};
+use crate::frontend::trees::nodes::{ExprNode, ItemNode, PatternNode};
+use crate::frontend::trees::typed::{Expr, Item, Pattern};
+use crate::frontend::tyck::{self, Pretty};
+
+#[test]
+fn ui() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("ui");
+    let bless = std::env::var_os("BLESS").is_some();
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .expect("the ui test directory exists")
+        .map(|entry| entry.expect("the ui test directory is readable").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "nml"))
+        .collect();
+
+    paths.sort();
+    assert!(!paths.is_empty(), "there are ui tests in {}", dir.display());
+
+    let mut failures = String::new();
+
+    for path in paths {
+        let problems = run(&path, bless);
+        if !problems.is_empty() {
+            let name = path.strip_prefix(&dir).unwrap_or(&path).display();
+            writeln!(failures, "{name}:").unwrap();
+
+            for problem in problems {
+                writeln!(failures, "  {}", problem.replace('\n', "\n  ")).unwrap();
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "ui tests failed:\n{failures}");
+}
+
+/// Check the program at `path` against its expectations, returning what did
+/// not go as expected.
+fn run(path: &Path, bless: bool) -> Vec<String> {
+    let content = fs::read_to_string(path).expect("ui tests are readable");
+    let sources = Sources::new();
+    let source = sources.add(content);
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let resolved = resolve_with_prelude(&names, &alloc, prelude::resolved(), &parsed);
+    let mut program = tyck::check(&alloc, &names, &resolved);
+
+    let mut errors: Vec<_> = program.errors.drain().collect();
+    errors.sort_by_key(|(id, error)| (error.at, *id));
+    let mut errors: Vec<_> = errors.into_iter().map(|(_, error)| Some(error)).collect();
+
+    let mut pretty = Pretty::new(&names);
+    let mut bindings = Vec::new();
+    for items in program.items {
+        for item in items.iter().filter(|item| item.span.source == source.id) {
+            item_bindings(item, &mut bindings);
+        }
+    }
+
+    bindings.sort_by_key(|(span, _)| *span);
+
+    let mut problems = Vec::new();
+
+    for expectation in expectations(&source.content) {
+        let matches = |span: &Span| {
+            let (line, column) = position(&source, span.start);
+            line == expectation.line && expectation.column.is_none_or(|col| col == column)
+        };
+
+        let at = match expectation.column {
+            Some(column) => format!("{}:{}", expectation.line + 1, column + 1),
+            None => format!("{}", expectation.line + 1),
+        };
+
+        match &expectation.kind {
+            Expected::Error(text) => {
+                let found = errors.iter_mut().find(|error| {
+                    error.as_ref().is_some_and(|error| {
+                        error.severity == Severity::Error
+                            && matches(&error.at)
+                            && error.title.contains(text)
+                    })
+                });
+
+                match found {
+                    Some(error) => *error = None,
+                    None => {
+                        problems.push(format!("expected an error at {at} containing \"{text}\""))
+                    }
+                }
+            }
+
+            Expected::Type(expected) => match bindings.iter().find(|(span, _)| matches(span)) {
+                Some((_, pattern)) => {
+                    let actual = pretty.build().scheme(&pattern.scheme);
+                    if actual != *expected {
+                        problems.push(format!(
+                            "expected the name bound at {at} to have type `{expected}`, \
+                             but it has type `{actual}`"
+                        ));
+                    }
+                }
+
+                None => problems.push(format!("expected a name bound at {at}")),
+            },
+        }
+    }
+
+    let mut actual = String::new();
+    for error in errors.into_iter().flatten() {
+        if !actual.is_empty() {
+            actual.push('\n');
+        }
+
+        render(&mut actual, &source, &error);
+    }
+
+    let snapshot = path.with_extension("stderr");
+
+    if bless {
+        if actual.is_empty() {
+            if snapshot.exists() {
+                fs::remove_file(&snapshot).expect("snapshots are removable");
+            }
+        } else {
+            fs::write(&snapshot, &actual).expect("snapshots are writable");
+        }
+    } else {
+        let expected = fs::read_to_string(&snapshot).unwrap_or_default();
+        if expected != actual {
+            problems.push(format!(
+                "unexpected errors (run `cargo xtask bless` to update the snapshot)\n\
+                 expected:\n{expected}\nactual:\n{actual}"
+            ));
+        }
+    }
+
+    problems
+}
+
+/// An expectation written in a comment.
+struct Expectation {
+    /// The line the expectation is about, counting from zero.
+    line: usize,
+
+    /// The column of the caret, if any, counting from zero.
+    column: Option<usize>,
+
+    kind: Expected,
+}
+
+enum Expected {
+    Error(String),
+    Type(String),
+}
+
+/// Find every expectation in a source. Comments which do not look like
+/// expectations are ignored.
+fn expectations(source: &str) -> Vec<Expectation> {
+    let mut result = Vec::new();
+    let mut target = 0;
+
+    for (line, text) in source.lines().enumerate() {
+        match expectation(text) {
+            Some((column, kind)) => result.push(Expectation {
+                line: target,
+                column,
+                kind,
+            }),
+
+            None => target = line,
+        }
+    }
+
+    result
+}
+
+fn expectation(line: &str) -> Option<(Option<usize>, Expected)> {
+    let comment = line.trim_start();
+    let rest = comment.strip_prefix("--")?;
+    if rest.starts_with('|') {
+        return None;
+    }
+
+    let rest = rest.trim_start();
+    let (column, rest) = match rest.strip_prefix('^') {
+        Some(after) => (Some(line.len() - rest.len()), after.trim_start()),
+        None => (None, rest),
+    };
+
+    let (kind, text) = rest.split_once(':')?;
+    let text = text.trim().to_string();
+
+    let kind = match kind {
+        "error" => Expected::Error(text),
+        "type" => Expected::Type(text),
+        _ => return None,
+    };
+
+    Some((column, kind))
+}
+
+/// Collect the names bound by patterns in an item along with their span.
+fn item_bindings<'a>(item: &'a Item, into: &mut Vec<(Span, &'a Pattern<'a>)>) {
+    match &item.node {
+        ItemNode::Let(pattern, expr, _) => {
+            pattern_bindings(pattern, into);
+            expr_bindings(expr, into);
+        }
+
+        ItemNode::Instance(_, expr, _) => expr_bindings(expr, into),
+
+        ItemNode::Invalid(_) | ItemNode::Data(..) | ItemNode::Alias(..) | ItemNode::Class(..) => {}
+    }
+}
+
+fn expr_bindings<'a>(expr: &'a Expr, into: &mut Vec<(Span, &'a Pattern<'a>)>) {
+    match &expr.node {
+        ExprNode::Invalid(_)
+        | ExprNode::Var(_)
+        | ExprNode::Hole
+        | ExprNode::Unit
+        | ExprNode::Number(_) => {}
+
+        ExprNode::Anno(_, v) => match *v {},
+
+        ExprNode::Group(expr) | ExprNode::Field(expr, _, _) | ExprNode::Restrict(expr, _) => {
+            expr_bindings(expr, into)
+        }
+
+        ExprNode::Record(fields, rest) => {
+            for (_, _, expr) in fields.iter() {
+                expr_bindings(expr, into);
+            }
+
+            if let Some(rest) = rest {
+                expr_bindings(rest, into);
+            }
+        }
+
+        ExprNode::Apply([f, x]) => {
+            expr_bindings(f, into);
+            expr_bindings(x, into);
+        }
+
+        ExprNode::Lambda(arms) => {
+            for (pattern, expr) in arms.iter() {
+                pattern_bindings(pattern, into);
+                expr_bindings(expr, into);
+            }
+        }
+
+        ExprNode::Let(pattern, [bound, body], _) => {
+            pattern_bindings(pattern, into);
+            expr_bindings(bound, into);
+            expr_bindings(body, into);
+        }
+    }
+}
+
+fn pattern_bindings<'a>(pattern: &'a Pattern, into: &mut Vec<(Span, &'a Pattern<'a>)>) {
+    match &pattern.node {
+        PatternNode::Invalid(_)
+        | PatternNode::Wildcard
+        | PatternNode::Unit
+        | PatternNode::Constructor(_) => {}
+
+        PatternNode::Bind(_) => into.push((pattern.span, pattern)),
+        PatternNode::Group(pattern) => pattern_bindings(pattern, into),
+
+        PatternNode::Apply([a, b]) | PatternNode::Or([a, b]) | PatternNode::And([a, b]) => {
+            pattern_bindings(a, into);
+            pattern_bindings(b, into);
+        }
+
+        PatternNode::Record(fields) => {
+            for (_, _, pattern) in fields.iter() {
+                pattern_bindings(pattern, into);
+            }
+        }
+
+        PatternNode::Anno(_, v) => match *v {},
+    }
+}
+
+/// Write an error like `error: title`, followed by where it is and its labels
+/// and notes, each on a line of its own.
+fn render(into: &mut String, source: &Source, error: &Error) {
+    let location = |span: &Span| {
+        if span.source == source.id {
+            let (line, column) = position(source, span.start);
+            format!("{}:{}", line + 1, column + 1)
+        } else if span.source == SourceId::PRELUDE {
+            format!("{}@{}", prelude::PATH, span.start)
+        } else {
+            format!("?@{}", span.start)
+        }
+    };
+
+    writeln!(into, "{}: {}", severity_name(error.severity), error.title).unwrap();
+    writeln!(into, "  at {}", location(&error.at)).unwrap();
+
+    for (label, span) in &error.labels {
+        writeln!(into, "  {}: {label}", location(span)).unwrap();
+    }
+
+    for (note, ty) in &error.notes {
+        let ty = match ty {
+            NoteType::Note => "note",
+            NoteType::Help => "help",
+        };
+
+        writeln!(into, "  {ty}: {note}").unwrap();
+    }
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "info",
+    }
+}
+
+/// Get the line and column of an offset, both counting from zero.
+fn position(source: &Source, offset: usize) -> (usize, usize) {
+    let before = &source.content[..offset];
+    let line = before.matches('\n').count();
+    let column = offset - before.rfind('\n').map_or(0, |newline| newline + 1);
+    (line, column)
+}
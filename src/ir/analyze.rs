@@ -0,0 +1,150 @@
+//! Facts about core programs which are needed by several passes, such as the
+//! optimizer and the backends.
+
+use std::collections::BTreeSet;
+
+use super::{Expr, Item, Program};
+use crate::frontend::names::{Label, Name};
+
+/// Get every variable occurring free in an expression, in the order they first
+/// occur.
+pub fn free_vars(expr: &Expr) -> Vec<Name> {
+    fn go(free: &mut Vec<Name>, bound: &mut Vec<Name>, expr: &Expr) {
+        match expr {
+            Expr::Var(name) => {
+                if !bound.contains(name) && !free.contains(name) {
+                    free.push(*name);
+                }
+            }
+
+            Expr::Unit | Expr::Number(_) | Expr::Fail(_) => {}
+
+            Expr::Field(expr, ..)
+            | Expr::Restrict(expr, _)
+            | Expr::TypeApply(expr, _)
+            | Expr::TypeLambda(_, expr) => go(free, bound, expr),
+
+            Expr::Record(fields, extend) => {
+                for (_, field) in fields.iter() {
+                    go(free, bound, field);
+                }
+
+                if let Some(extend) = extend {
+                    go(free, bound, extend);
+                }
+            }
+
+            Expr::Apply([fun, arg]) => {
+                go(free, bound, fun);
+                go(free, bound, arg);
+            }
+
+            Expr::Primitive(_, args) => {
+                for arg in args.iter() {
+                    go(free, bound, arg);
+                }
+            }
+
+            Expr::Lambda(name, _, body) => {
+                bound.push(*name);
+                go(free, bound, body);
+                bound.pop();
+            }
+
+            Expr::Let(binding, body) => {
+                go(free, bound, &binding.body);
+                bound.push(binding.name);
+                go(free, bound, body);
+                bound.pop();
+            }
+
+            Expr::Case(scrutinee, alts, default) => {
+                go(free, bound, scrutinee);
+
+                for alt in alts.iter() {
+                    let len = bound.len();
+                    bound.extend(alt.binds.iter().map(|(name, _)| *name));
+                    go(free, bound, &alt.body);
+                    bound.truncate(len);
+                }
+
+                if let Some(default) = default {
+                    go(free, bound, default);
+                }
+            }
+        }
+    }
+
+    let mut free = Vec::new();
+    go(&mut free, &mut Vec::new(), expr);
+    free
+}
+
+/// Get every label mentioned by the expressions of a program. Labels are
+/// ordered by their names.
+pub fn labels<'a>(program: &Program<'a, '_>) -> BTreeSet<Label<'a>> {
+    fn go<'a>(labels: &mut BTreeSet<Label<'a>>, expr: &Expr<'a, '_>) {
+        match expr {
+            Expr::Var(_) | Expr::Unit | Expr::Number(_) | Expr::Fail(_) => {}
+
+            Expr::Field(expr, _, label) | Expr::Restrict(expr, label) => {
+                labels.insert(*label);
+                go(labels, expr);
+            }
+
+            Expr::TypeApply(expr, _) | Expr::TypeLambda(_, expr) | Expr::Lambda(_, _, expr) => {
+                go(labels, expr)
+            }
+
+            Expr::Record(fields, extend) => {
+                for (label, field) in fields.iter() {
+                    labels.insert(*label);
+                    go(labels, field);
+                }
+
+                if let Some(extend) = extend {
+                    go(labels, extend);
+                }
+            }
+
+            Expr::Apply([fun, arg]) => {
+                go(labels, fun);
+                go(labels, arg);
+            }
+
+            Expr::Primitive(_, args) => {
+                for arg in args.iter() {
+                    go(labels, arg);
+                }
+            }
+
+            Expr::Let(binding, body) => {
+                go(labels, &binding.body);
+                go(labels, body);
+            }
+
+            Expr::Case(scrutinee, alts, default) => {
+                go(labels, scrutinee);
+
+                for alt in alts.iter() {
+                    go(labels, &alt.body);
+                }
+
+                if let Some(default) = default {
+                    go(labels, default);
+                }
+            }
+        }
+    }
+
+    let mut labels = BTreeSet::new();
+    for item in program.items {
+        if let Item::Group(bindings) = item {
+            for binding in bindings.iter() {
+                go(&mut labels, &binding.body);
+            }
+        }
+    }
+
+    labels
+}
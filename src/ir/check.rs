@@ -0,0 +1,591 @@
+//! The core type checker. Every binder in the core language is annotated with
+//! its type, so checking a program is just a matter of computing the type of
+//! each expression bottom-up and comparing it against the annotations. A well
+//! typed program from the frontend should always lower to a well typed core
+//! program, so any error found here is a bug in the compiler.
+
+use std::collections::BTreeMap;
+
+use bumpalo::Bump;
+
+use super::lower::{field_type, substitute};
+use super::pretty::Printer;
+use super::{Alt, Binding, Data, Expr, Item, Program, Row, Type, TypeArg};
+use crate::frontend::builtins::Primitive;
+use crate::frontend::names::{Label, Name, Names};
+use crate::frontend::tyck::{Generic, VarKind};
+
+/// Check a core program, returning a description of every type error in it.
+pub fn check<'a>(alloc: &'a Bump, names: &Names, program: &Program<'a, '_>) -> Vec<String> {
+    let mut checker = Checker {
+        alloc,
+        printer: Printer::new(names),
+        names,
+        datas: BTreeMap::new(),
+        values: BTreeMap::new(),
+        params: Vec::new(),
+        within: None,
+        errors: Vec::new(),
+    };
+
+    for item in program.items {
+        match item {
+            Item::Data(data) => checker.data(data),
+            Item::Group(bindings) => checker.group(bindings),
+        }
+    }
+
+    checker.errors
+}
+
+struct Checker<'a, 'n, 'src> {
+    alloc: &'a Bump,
+    printer: Printer<'n, 'src>,
+    names: &'n Names<'src>,
+
+    datas: BTreeMap<Name, &'a Data<'a>>,
+    values: BTreeMap<Name, &'a Type<'a>>,
+
+    /// The type parameters currently in scope.
+    params: Vec<Generic>,
+
+    /// The item currently being checked.
+    within: Option<Name>,
+    errors: Vec<String>,
+}
+
+impl<'a> Checker<'a, '_, '_> {
+    fn data(&mut self, data: &'a Data<'a>) {
+        self.within = Some(data.name);
+        self.datas.insert(data.name, data);
+
+        let mut ty = &*self.alloc.alloc(Type::Named(data.name));
+        for param in data.params {
+            ty = self.alloc.alloc(Type::Apply(ty, self.param(param)));
+        }
+
+        self.params.extend(data.params.iter().copied());
+
+        for ctor in data.ctors {
+            let mut ctor_ty = ty;
+            for field in ctor.fields.iter().rev() {
+                self.well_formed(field);
+                ctor_ty = self.arrow(field, ctor_ty);
+            }
+
+            let ctor_ty = self.forall(data.params, ctor_ty);
+            self.values.insert(ctor.name, ctor_ty);
+        }
+
+        self.params.clear();
+    }
+
+    fn group(&mut self, bindings: &'a [Binding<'a, '_>]) {
+        for binding in bindings {
+            self.within = Some(binding.name);
+            self.well_formed(binding.ty);
+            self.values.insert(binding.name, binding.ty);
+        }
+
+        for binding in bindings {
+            self.within = Some(binding.name);
+            self.binding(binding);
+        }
+    }
+
+    /// Check that the body of a binding has the type it is annotated with.
+    fn binding(&mut self, binding: &Binding<'a, '_>) {
+        if let Some(ty) = self.expr(&binding.body) {
+            if !equal(&mut Vec::new(), ty, binding.ty) {
+                let name = self.name(&binding.name);
+                let expected = self.printer.ty(binding.ty);
+                let actual = self.printer.ty(ty);
+                self.error(format!(
+                    "`{name}` is annotated with `{expected}`, but its body has type `{actual}`"
+                ));
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr<'a, '_>) -> Option<&'a Type<'a>> {
+        match expr {
+            Expr::Var(name) => match self.values.get(name) {
+                Some(ty) => Some(ty),
+                None => {
+                    let name = self.name(name);
+                    self.error(format!("`{name}` is not defined"));
+                    None
+                }
+            },
+
+            Expr::Unit => Some(self.alloc.alloc(Type::Unit)),
+            Expr::Number(_) => Some(self.alloc.alloc(Type::Integer)),
+            Expr::Fail(ty) => {
+                self.well_formed(ty);
+                Some(ty)
+            }
+
+            Expr::Field(record, record_ty, label) => {
+                let ty = self.expr(record)?;
+                if !equal(&mut Vec::new(), record_ty, ty) {
+                    let expected = self.printer.ty(record_ty);
+                    let actual = self.printer.ty(ty);
+                    self.error(format!(
+                        "expected a record of type `{expected}`, but found `{actual}`"
+                    ));
+                }
+
+                match field_type(ty, label) {
+                    Some(ty) => Some(ty),
+                    None => {
+                        let ty = self.printer.ty(ty);
+                        let label = label.0.name();
+                        self.error(format!("`{ty}` has no field `{label}`"));
+                        None
+                    }
+                }
+            }
+
+            Expr::Record(fields, extend) => {
+                let mut row = match extend {
+                    Some(extend) => match self.expr(extend)? {
+                        Type::Record(row) => *row,
+                        ty => {
+                            let ty = self.printer.ty(ty);
+                            self.error(format!("cannot extend `{ty}`, which is not a record"));
+                            return None;
+                        }
+                    },
+
+                    None => &Row::Empty,
+                };
+
+                let mut types = Vec::with_capacity(fields.len());
+                for (label, field) in fields.iter() {
+                    types.push((*label, self.expr(field)?));
+                }
+
+                for (label, ty) in types.into_iter().rev() {
+                    row = self.alloc.alloc(Row::Extend(label, ty, row));
+                }
+
+                Some(self.alloc.alloc(Type::Record(row)))
+            }
+
+            Expr::Restrict(record, label) => {
+                let ty = self.expr(record)?;
+                match self.restrict(ty, label) {
+                    Some(ty) => Some(ty),
+                    None => {
+                        let ty = self.printer.ty(ty);
+                        let label = label.0.name();
+                        self.error(format!("`{ty}` has no field `{label}` to remove"));
+                        None
+                    }
+                }
+            }
+
+            Expr::Apply([fun, arg]) => {
+                let fun_ty = self.expr(fun)?;
+                let arg_ty = self.expr(arg)?;
+
+                let Some((param_ty, result_ty)) = fun_ty.as_arrow() else {
+                    let ty = self.printer.ty(fun_ty);
+                    self.error(format!("cannot apply `{ty}`, which is not a function type"));
+                    return None;
+                };
+
+                if !equal(&mut Vec::new(), param_ty, arg_ty) {
+                    let expected = self.printer.ty(param_ty);
+                    let actual = self.printer.ty(arg_ty);
+                    self.error(format!(
+                        "expected an argument of type `{expected}`, but found `{actual}`"
+                    ));
+                }
+
+                Some(result_ty)
+            }
+
+            Expr::Primitive(primitive, args) => {
+                let tys = args
+                    .iter()
+                    .map(|arg| self.expr(arg))
+                    .collect::<Option<Vec<_>>>()?;
+
+                if tys.len() != primitive.arity() {
+                    let name = primitive.ident();
+                    let arity = primitive.arity();
+                    let count = tys.len();
+                    self.error(format!(
+                        "`{name}` takes {arity} arguments, but is applied to {count}"
+                    ));
+                    return None;
+                }
+
+                for ty in &tys[..2] {
+                    if !matches!(ty, Type::Integer) {
+                        let actual = self.printer.ty(ty);
+                        self.error(format!(
+                            "expected an argument of type `int`, but found `{actual}`"
+                        ));
+                    }
+                }
+
+                match primitive {
+                    Primitive::Add | Primitive::Sub | Primitive::Mul => {
+                        Some(self.alloc.alloc(Type::Integer))
+                    }
+
+                    Primitive::Less => {
+                        if !equal(&mut Vec::new(), tys[2], tys[3]) {
+                            let expected = self.printer.ty(tys[2]);
+                            let actual = self.printer.ty(tys[3]);
+                            self.error(format!(
+                                "expected an argument of type `{expected}`, but found `{actual}`"
+                            ));
+                        }
+
+                        Some(tys[2])
+                    }
+                }
+            }
+
+            Expr::Lambda(name, ty, body) => {
+                self.well_formed(ty);
+                let body = self.bind(*name, ty, |this| this.expr(body))?;
+                Some(self.arrow(ty, body))
+            }
+
+            Expr::TypeApply(expr, args) => {
+                let ty = self.expr(expr)?;
+                let Type::Forall(params, body) = ty else {
+                    let ty = self.printer.ty(ty);
+                    self.error(format!(
+                        "cannot apply `{ty}` to types, which is not polymorphic"
+                    ));
+                    return None;
+                };
+
+                if params.len() != args.len() {
+                    let ty = self.printer.ty(ty);
+                    let (expected, actual) = (params.len(), args.len());
+                    self.error(format!(
+                        "`{ty}` takes {expected} type arguments, but was given {actual}"
+                    ));
+                    return None;
+                }
+
+                let mut subst = BTreeMap::new();
+                for (param, arg) in params.iter().zip(args.iter()) {
+                    match (param.kind(), arg) {
+                        (VarKind::Type, TypeArg::Type(ty)) => self.well_formed(ty),
+                        (VarKind::Row, TypeArg::Row(row)) => self.well_formed_row(row),
+                        _ => {
+                            let param = self.printer.ty(&Type::Param(*param));
+                            self.error(format!("the argument for `{param}` is of the wrong kind"));
+                            return None;
+                        }
+                    }
+
+                    subst.insert(*param, *arg);
+                }
+
+                Some(substitute(self.alloc, &subst, body))
+            }
+
+            Expr::TypeLambda(params, body) => {
+                let len = self.params.len();
+                self.params.extend(params.iter().copied());
+                let body = self.expr(body);
+                self.params.truncate(len);
+                Some(self.forall(params, body?))
+            }
+
+            Expr::Let(binding, body) => {
+                self.well_formed(binding.ty);
+                self.binding(binding);
+                self.bind(binding.name, binding.ty, |this| this.expr(body))
+            }
+
+            Expr::Case(scrutinee, alts, default) => self.case(scrutinee, alts, default),
+        }
+    }
+
+    fn case(
+        &mut self,
+        scrutinee: &Expr<'a, '_>,
+        alts: &[Alt<'a, '_>],
+        default: &Option<&Expr<'a, '_>>,
+    ) -> Option<&'a Type<'a>> {
+        let ty = self.expr(scrutinee)?;
+        let (head, args) = ty.spine();
+        let data = match head {
+            Type::Named(name) => self
+                .datas
+                .get(name)
+                .copied()
+                .filter(|data| data.params.len() == args.len()),
+            _ => None,
+        };
+
+        let Some(data) = data else {
+            let ty = self.printer.ty(ty);
+            self.error(format!("cannot match on `{ty}`, which is not a data type"));
+            return None;
+        };
+
+        let subst: BTreeMap<_, _> = data
+            .params
+            .iter()
+            .copied()
+            .zip(args.into_iter().map(TypeArg::Type))
+            .collect();
+
+        let mut result: Option<&'a Type<'a>> = None;
+
+        for alt in alts {
+            let Some(ctor) = data.ctors.iter().find(|ctor| ctor.name == alt.ctor) else {
+                let ctor = self.name(&alt.ctor);
+                let data = self.name(&data.name);
+                self.error(format!("`{ctor}` is not a constructor of `{data}`"));
+                continue;
+            };
+
+            if ctor.fields.len() != alt.binds.len() {
+                let name = self.name(&ctor.name);
+                let (expected, actual) = (ctor.fields.len(), alt.binds.len());
+                self.error(format!(
+                    "`{name}` has {expected} fields, but {actual} are bound"
+                ));
+                continue;
+            }
+
+            for (field, (_, ty)) in ctor.fields.iter().zip(alt.binds.iter()) {
+                self.well_formed(ty);
+                let field = substitute(self.alloc, &subst, field);
+                if !equal(&mut Vec::new(), field, ty) {
+                    let name = self.name(&ctor.name);
+                    let expected = self.printer.ty(field);
+                    let actual = self.printer.ty(ty);
+                    self.error(format!(
+                        "a field of `{name}` has type `{expected}`, but is bound as `{actual}`"
+                    ));
+                }
+            }
+
+            let body = self.bind_all(alt.binds, |this| this.expr(&alt.body));
+            if let Some(body) = body {
+                self.same_arms(&mut result, body);
+            }
+        }
+
+        if let Some(default) = default {
+            if let Some(body) = self.expr(default) {
+                self.same_arms(&mut result, body);
+            }
+        }
+
+        if alts.is_empty() && default.is_none() {
+            self.error("a case expression must have at least one alternative".into());
+        }
+
+        result
+    }
+
+    fn same_arms(&mut self, result: &mut Option<&'a Type<'a>>, ty: &'a Type<'a>) {
+        match result {
+            Some(result) if !equal(&mut Vec::new(), result, ty) => {
+                let expected = self.printer.ty(result);
+                let actual = self.printer.ty(ty);
+                self.error(format!(
+                    "the alternatives of a case have different types `{expected}` and `{actual}`"
+                ));
+            }
+
+            Some(_) => {}
+            None => *result = Some(ty),
+        }
+    }
+
+    /// Remove the first field with the given label from a record type.
+    fn restrict(&self, ty: &'a Type<'a>, label: &Label) -> Option<&'a Type<'a>> {
+        fn go<'a>(alloc: &'a Bump, row: &'a Row<'a>, label: &Label) -> Option<&'a Row<'a>> {
+            match row {
+                Row::Extend(other, _, rest) if other == label => Some(rest),
+                Row::Extend(other, ty, rest) => {
+                    let rest = go(alloc, rest, label)?;
+                    Some(alloc.alloc(Row::Extend(*other, ty, rest)))
+                }
+
+                Row::Empty | Row::Param(_) => None,
+            }
+        }
+
+        let Type::Record(row) = ty else {
+            return None;
+        };
+
+        let row = go(self.alloc, row, label)?;
+        Some(self.alloc.alloc(Type::Record(row)))
+    }
+
+    /// Check that every type parameter in a type is in scope and that every
+    /// named type is defined.
+    fn well_formed(&mut self, ty: &Type) {
+        match ty {
+            Type::Param(param) => {
+                if !self.params.contains(param) {
+                    let param = self.printer.ty(ty);
+                    self.error(format!("the type parameter `{param}` is not in scope"));
+                }
+            }
+
+            Type::Named(name) => {
+                if !self.datas.contains_key(name) {
+                    let name = self.name(name);
+                    self.error(format!("the type `{name}` is not defined"));
+                }
+            }
+
+            Type::Unit | Type::Integer | Type::Arrow => {}
+            Type::Record(row) => self.well_formed_row(row),
+            Type::Apply(t, u) => {
+                self.well_formed(t);
+                self.well_formed(u);
+            }
+
+            Type::Forall(params, body) => {
+                let len = self.params.len();
+                self.params.extend(params.iter().copied());
+                self.well_formed(body);
+                self.params.truncate(len);
+            }
+        }
+    }
+
+    fn well_formed_row(&mut self, row: &Row) {
+        match row {
+            Row::Empty => {}
+            Row::Param(param) => self.well_formed(&Type::Param(*param)),
+            Row::Extend(_, ty, rest) => {
+                self.well_formed(ty);
+                self.well_formed_row(rest);
+            }
+        }
+    }
+
+    fn bind<T>(&mut self, name: Name, ty: &'a Type<'a>, f: impl FnOnce(&mut Self) -> T) -> T {
+        let prev = self.values.insert(name, ty);
+        let result = f(self);
+
+        match prev {
+            Some(prev) => self.values.insert(name, prev),
+            None => self.values.remove(&name),
+        };
+
+        result
+    }
+
+    fn bind_all<T>(&mut self, binds: &[(Name, &'a Type<'a>)], f: impl FnOnce(&mut Self) -> T) -> T {
+        match binds {
+            [] => f(self),
+            [(name, ty), rest @ ..] => self.bind(*name, ty, |this| this.bind_all(rest, f)),
+        }
+    }
+
+    fn param(&self, param: &Generic) -> &'a Type<'a> {
+        self.alloc.alloc(Type::Param(*param))
+    }
+
+    fn arrow(&self, t: &'a Type<'a>, u: &'a Type<'a>) -> &'a Type<'a> {
+        let arrow = self.alloc.alloc(Type::Arrow);
+        let arrow = self.alloc.alloc(Type::Apply(arrow, t));
+        self.alloc.alloc(Type::Apply(arrow, u))
+    }
+
+    fn forall(&self, params: &'a [Generic], ty: &'a Type<'a>) -> &'a Type<'a> {
+        if params.is_empty() {
+            ty
+        } else {
+            self.alloc.alloc(Type::Forall(params, ty))
+        }
+    }
+
+    fn name(&self, name: &Name) -> String {
+        self.names.get_name(name).name.name().into()
+    }
+
+    fn error(&mut self, message: String) {
+        let message = match self.within {
+            Some(name) => format!("in `{}`: {message}", self.name(&name)),
+            None => message,
+        };
+
+        self.errors.push(message);
+    }
+}
+
+/// Check whether two types are equal up to the names of the parameters bound by
+/// `forall`s and the order of differently labelled fields in records. `bound`
+/// pairs up the parameters bound by enclosing `forall`s.
+fn equal(bound: &mut Vec<(Generic, Generic)>, a: &Type, b: &Type) -> bool {
+    match (a, b) {
+        (Type::Param(p), Type::Param(q)) => equal_params(bound, p, q),
+        (Type::Named(x), Type::Named(y)) => x == y,
+        (Type::Unit, Type::Unit) | (Type::Integer, Type::Integer) | (Type::Arrow, Type::Arrow) => {
+            true
+        }
+
+        (Type::Record(r), Type::Record(s)) => equal_rows(bound, r, s),
+        (Type::Apply(t1, u1), Type::Apply(t2, u2)) => equal(bound, t1, t2) && equal(bound, u1, u2),
+
+        (Type::Forall(ps, t), Type::Forall(qs, u)) => {
+            if ps.len() != qs.len() || ps.iter().zip(qs.iter()).any(|(p, q)| p.kind() != q.kind()) {
+                return false;
+            }
+
+            let len = bound.len();
+            bound.extend(ps.iter().copied().zip(qs.iter().copied()));
+            let result = equal(bound, t, u);
+            bound.truncate(len);
+            result
+        }
+
+        _ => false,
+    }
+}
+
+fn equal_params(bound: &[(Generic, Generic)], p: &Generic, q: &Generic) -> bool {
+    match bound.iter().rev().find(|(x, y)| x == p || y == q) {
+        Some((x, y)) => x == p && y == q,
+        None => p == q,
+    }
+}
+
+fn equal_rows(bound: &mut Vec<(Generic, Generic)>, r: &Row, s: &Row) -> bool {
+    fn flatten<'r, 'a>(mut row: &'r Row<'a>) -> (Vec<(Label<'a>, &'r Type<'a>)>, &'r Row<'a>) {
+        let mut fields = Vec::new();
+        while let Row::Extend(label, ty, rest) = row {
+            fields.push((*label, *ty));
+            row = rest;
+        }
+
+        // Only the relative order of fields with the same label matters.
+        fields.sort_by_key(|(label, _)| *label);
+        (fields, row)
+    }
+
+    let (r_fields, r_rest) = flatten(r);
+    let (s_fields, s_rest) = flatten(s);
+
+    r_fields.len() == s_fields.len()
+        && r_fields
+            .iter()
+            .zip(s_fields.iter())
+            .all(|((l1, t1), (l2, t2))| l1 == l2 && equal(bound, t1, t2))
+        && match (r_rest, s_rest) {
+            (Row::Empty, Row::Empty) => true,
+            (Row::Param(p), Row::Param(q)) => equal_params(bound, p, q),
+            _ => false,
+        }
+}
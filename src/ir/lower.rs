@@ -0,0 +1,1113 @@
+//! Lowering turns an elaborated, typed program into a core program. Type
+//! abstractions are introduced at every generalized binding and type
+//! applications at every use of one, patterns are compiled into nested `case`
+//! expressions, and nominal records and class dictionaries become data types
+//! which are unwrapped before their fields are accessed.
+//!
+//! Patterns are compiled into [decision trees](crate::matching), and each
+//! switch of a tree becomes a `case` expression. To avoid duplicating code, the
+//! body of an arm chosen in several leaves of a tree is bound to a local
+//! function which is called wherever it is chosen.
+//!
+//! Only programs without any errors can be lowered.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bumpalo::Bump;
+
+use super::{Alt, Binding, Constructor, Data, Expr, Item, Program, Row, Type, TypeArg};
+use crate::frontend::builtins::Primitive;
+use crate::frontend::names::{Label, Name, Names, ScopeName};
+use crate::frontend::trees::{inferred, typed};
+use crate::frontend::tyck::{self, Generic, Scheme, VarKind};
+use crate::matching::{self, Access, Constructors, Decision, Match, Occurrence};
+
+pub fn lower<'a, 'src>(
+    alloc: &'a Bump,
+    names: &'a Names<'src>,
+    program: &typed::Program<'a, 'src>,
+) -> Program<'a, 'src> {
+    let mut lowerer = Lowerer {
+        alloc,
+        names,
+        polys: BTreeMap::new(),
+        records: BTreeMap::new(),
+        constructors: Constructors::new(program),
+        group: BTreeSet::new(),
+    };
+
+    let mut items = Vec::new();
+
+    for group in program.items.iter() {
+        let mut bindings = Vec::new();
+
+        for item in group.iter() {
+            match &item.node {
+                typed::ItemNode::Data(scheme, data) => {
+                    if let Some(data) = lowerer.data(scheme, data) {
+                        items.push(Item::Data(data));
+                    }
+                }
+
+                typed::ItemNode::Class(scheme, methods) => {
+                    if let Some(data) = lowerer.class(scheme, methods) {
+                        items.push(Item::Data(data));
+                    }
+                }
+
+                typed::ItemNode::Let(..) | typed::ItemNode::Instance(..) => bindings.push(item),
+                typed::ItemNode::Alias(..) | typed::ItemNode::Invalid(_) => {}
+            }
+        }
+
+        if !bindings.is_empty() {
+            let bindings = lowerer.group(&bindings);
+            items.push(Item::Group(alloc.alloc_slice_fill_iter(bindings)));
+        }
+    }
+
+    Program {
+        items: alloc.alloc_slice_fill_iter(items),
+    }
+}
+
+/// A polymorphic binding.
+struct Poly<'a> {
+    /// The parameters of the scheme of the binding in the typed program, which
+    /// are the ones it is instantiated with at every use.
+    scheme: Vec<Generic>,
+
+    /// The parameters the binding abstracts over in the core program. Every
+    /// binding in a recursive group abstracts over the parameters of the whole
+    /// group, since the bindings may refer to each other at those types.
+    params: &'a [Generic],
+}
+
+/// The code evaluated when an arm of a match is chosen.
+enum Target<'a, 'src> {
+    /// The body of the arm itself, if it is only chosen once or is trivial.
+    Inline(Expr<'a, 'src>),
+
+    /// A call to the local function the body is bound to, passing the names
+    /// bound by the pattern (or unit, if there are none).
+    Join(Name, Option<Vec<Name>>),
+}
+
+/// A data type with a single constructor wrapping a record.
+struct Nominal<'a> {
+    ctor: Name,
+    params: &'a [Generic],
+    record: &'a Type<'a>,
+}
+
+struct Lowerer<'a, 'src> {
+    alloc: &'a Bump,
+    names: &'a Names<'src>,
+
+    polys: BTreeMap<Name, Poly<'a>>,
+    records: BTreeMap<Name, Nominal<'a>>,
+
+    constructors: Constructors,
+
+    /// The names bound by the current group of items. Recursive references
+    /// within a group are monomorphic, so they are applied to the parameters of
+    /// the group.
+    group: BTreeSet<Name>,
+}
+
+impl<'a, 'src> Lowerer<'a, 'src> {
+    fn data(&mut self, scheme: &Scheme<'a>, data: &inferred::Data<'a>) -> Option<Data<'a>> {
+        let Type::Named(name) = self.ty(scheme.ty).spine().0 else {
+            return None;
+        };
+
+        let params = &*self.alloc.alloc_slice_clone(&scheme.params);
+        let inferred::DataNode::Sum(ctors) = &data.node else {
+            return None;
+        };
+
+        let ctors: Vec<_> = ctors
+            .iter()
+            .filter_map(|ctor| match &ctor.node {
+                inferred::ConstructorNode::Constructor(name, fields) => {
+                    let fields = self
+                        .alloc
+                        .alloc_slice_fill_iter(fields.iter().map(|field| self.ty(field)));
+                    Some(Constructor {
+                        name: *name,
+                        fields,
+                    })
+                }
+
+                inferred::ConstructorNode::Invalid(_) => None,
+            })
+            .collect();
+
+        for ctor in ctors.iter() {
+            let scheme = params.to_vec();
+            self.polys.insert(ctor.name, Poly { scheme, params });
+        }
+
+        if let [Constructor {
+            name: ctor,
+            fields: [record @ Type::Record(_)],
+        }] = &ctors[..]
+        {
+            let nominal = Nominal {
+                ctor: *ctor,
+                params,
+                record,
+            };
+
+            self.records.insert(*name, nominal);
+        }
+
+        let ctors = self.alloc.alloc_slice_fill_iter(ctors);
+        Some(Data {
+            name: *name,
+            params,
+            ctors,
+        })
+    }
+
+    /// The dictionary of a class is a data type with a single constructor,
+    /// named after the class, wrapping a record of its methods. The type of a
+    /// method is generalized over every parameter but that of the class.
+    fn class(&mut self, scheme: &Scheme<'a>, methods: &[(Name, Scheme<'a>)]) -> Option<Data<'a>> {
+        let (Type::Named(name), _) = self.ty(scheme.ty).spine() else {
+            return None;
+        };
+
+        let params = &*self.alloc.alloc_slice_clone(&scheme.params);
+
+        let mut row = &*self.alloc.alloc(Row::Empty);
+        for (method, method_scheme) in methods.iter().rev() {
+            let label = Label(self.names.get_name(method).name);
+            let own: Vec<_> = method_scheme
+                .params
+                .iter()
+                .filter(|param| !params.contains(param))
+                .copied()
+                .collect();
+
+            let ty = self.ty(method_scheme.ty);
+            let ty = self.forall(&own, ty);
+            row = self.alloc.alloc(Row::Extend(label, ty, row));
+        }
+
+        let record = &*self.alloc.alloc(Type::Record(row));
+        let fields = self.alloc.alloc_slice_fill_iter([record]);
+        let ctor = Constructor {
+            name: *name,
+            fields,
+        };
+
+        self.polys.insert(
+            *name,
+            Poly {
+                scheme: params.to_vec(),
+                params,
+            },
+        );
+
+        self.records.insert(
+            *name,
+            Nominal {
+                ctor: *name,
+                params,
+                record,
+            },
+        );
+
+        Some(Data {
+            name: *name,
+            params,
+            ctors: self.alloc.alloc_slice_fill_iter([ctor]),
+        })
+    }
+
+    fn group(&mut self, items: &[&typed::Item<'a, 'src>]) -> Vec<Binding<'a, 'src>> {
+        let mut params: Vec<Generic> = Vec::new();
+        for item in items {
+            if let typed::ItemNode::Let(pattern, _, _) = &item.node {
+                for param in pattern.scheme.params.iter() {
+                    if !params.contains(param) {
+                        params.push(*param);
+                    }
+                }
+            }
+        }
+
+        let params = &*self.alloc.alloc_slice_fill_iter(params);
+
+        self.group.clear();
+        for item in items {
+            match &item.node {
+                typed::ItemNode::Let(pattern, _, _) => {
+                    for (name, _) in bound_names(pattern) {
+                        let scheme = pattern.scheme.params.clone();
+                        self.polys.insert(name, Poly { scheme, params });
+                        self.group.insert(name);
+                    }
+                }
+
+                typed::ItemNode::Instance((dict, scheme), _, _) => {
+                    let params = &*self.alloc.alloc_slice_clone(&scheme.params);
+                    let scheme = scheme.params.clone();
+                    self.polys.insert(*dict, Poly { scheme, params });
+                }
+
+                _ => {}
+            }
+        }
+
+        let mut bindings = Vec::new();
+        for item in items {
+            match &item.node {
+                typed::ItemNode::Let(pattern, expr, _) => {
+                    self.let_item(&mut bindings, params, pattern, expr);
+                }
+
+                typed::ItemNode::Instance((dict, scheme), body, _) => {
+                    let params = &*self.alloc.alloc_slice_clone(&scheme.params);
+                    let ty = self.ty(scheme.ty);
+                    let body = self.instance(ty, body);
+                    bindings.push(Binding {
+                        name: *dict,
+                        ty: self.forall(params, ty),
+                        body: self.type_lambda(params, body),
+                    });
+                }
+
+                _ => {}
+            }
+        }
+
+        self.group.clear();
+        bindings
+    }
+
+    /// Lower a `let` item. A binding with a pattern other than a single name
+    /// is split into a binding of the value and a binding of each name in the
+    /// pattern.
+    fn let_item(
+        &mut self,
+        bindings: &mut Vec<Binding<'a, 'src>>,
+        params: &'a [Generic],
+        pattern: &typed::Pattern<'a>,
+        expr: &typed::Expr<'a, 'src>,
+    ) {
+        let body = self.expr(expr);
+        let body = self.type_lambda(params, body);
+
+        if let Some(name) = bind_name(pattern) {
+            let ty = self.ty(pattern.scheme.ty);
+            let ty = self.forall(params, ty);
+            bindings.push(Binding { name, ty, body });
+            return;
+        }
+
+        let value = self.fresh("value");
+        let ty = self.ty(expr.ty);
+        bindings.push(Binding {
+            name: value,
+            ty: self.forall(params, ty),
+            body,
+        });
+
+        for binding in self.destructure(params, value, ty, pattern) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Bind each name of a pattern matched against the polymorphic value
+    /// `value`.
+    fn destructure(
+        &mut self,
+        params: &'a [Generic],
+        value: Name,
+        ty: &'a Type<'a>,
+        pattern: &typed::Pattern<'a>,
+    ) -> Vec<Binding<'a, 'src>> {
+        bound_names(pattern)
+            .into_iter()
+            .map(|(name, name_ty)| {
+                let name_ty = self.ty(name_ty);
+                let body = if params.is_empty() {
+                    self.arms(value, &[(pattern, Expr::Var(name))], name_ty)
+                } else {
+                    let scrutinee = self.fresh("value");
+                    let binding = Binding {
+                        name: scrutinee,
+                        ty,
+                        body: self.type_apply(params, Expr::Var(value)),
+                    };
+
+                    let body = self.arms(scrutinee, &[(pattern, Expr::Var(name))], name_ty);
+                    Expr::Let(self.alloc.alloc(binding), self.alloc.alloc(body))
+                };
+
+                let body = self.type_lambda(params, body);
+
+                Binding {
+                    name,
+                    ty: self.forall(params, name_ty),
+                    body,
+                }
+            })
+            .collect()
+    }
+
+    /// Construct the dictionary of an instance, generalizing each method over
+    /// the parameters of its type in the class.
+    fn instance(&mut self, ty: &'a Type<'a>, body: &typed::Expr<'a, 'src>) -> Expr<'a, 'src> {
+        let Some((ctor, record)) = self.nominal(ty) else {
+            return self.expr(body);
+        };
+
+        let typed::ExprNode::Record(fields, None) = &strip_groups(body).node else {
+            return self.expr(body);
+        };
+
+        let fields: Vec<_> = fields
+            .iter()
+            .filter_map(|(label, _, field)| {
+                let label = (*label).ok()?;
+                let field = self.expr(field);
+                let field = match field_type(record, &label) {
+                    Some(Type::Forall(params, _)) => self.type_lambda(params, field),
+                    _ => field,
+                };
+
+                Some((label, field))
+            })
+            .collect();
+
+        let (_, args) = ty.spine();
+        let args: Vec<_> = args.into_iter().map(TypeArg::Type).collect();
+        let ctor = Expr::TypeApply(
+            self.alloc.alloc(Expr::Var(ctor)),
+            self.alloc.alloc_slice_fill_iter(args),
+        );
+
+        let record = Expr::Record(self.alloc.alloc_slice_fill_iter(fields), None);
+        Expr::Apply(self.alloc.alloc([ctor, record]))
+    }
+
+    fn expr(&mut self, expr: &typed::Expr<'a, 'src>) -> Expr<'a, 'src> {
+        match &expr.node {
+            typed::ExprNode::Invalid(_) | typed::ExprNode::Hole => Expr::Fail(self.ty(expr.ty)),
+            typed::ExprNode::Var(var) => self.var(var),
+            typed::ExprNode::Unit => Expr::Unit,
+            typed::ExprNode::Number(value) => Expr::Number(value),
+            typed::ExprNode::Anno(_, v) => match *v {},
+            typed::ExprNode::Group(expr) => self.expr(expr),
+
+            typed::ExprNode::Field(record, label, _) => {
+                let Ok(label) = label else {
+                    return Expr::Fail(self.ty(expr.ty));
+                };
+
+                let record_ty = self.ty(record.ty);
+                let record = self.expr(record);
+                let ty = self.ty(expr.ty);
+                self.project(record, record_ty, *label, ty)
+            }
+
+            typed::ExprNode::Record(fields, extend) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .filter_map(|(label, _, field)| Some(((*label).ok()?, self.expr(field))))
+                    .collect();
+
+                let fields = self.alloc.alloc_slice_fill_iter(fields);
+                let extend = extend.map(|extend| &*self.alloc.alloc(self.expr(extend)));
+                Expr::Record(fields, extend)
+            }
+
+            typed::ExprNode::Restrict(record, label) => {
+                let record = self.expr(record);
+                Expr::Restrict(self.alloc.alloc(record), *label)
+            }
+
+            typed::ExprNode::Apply([fun, arg]) => {
+                let fun = self.expr(fun);
+                let arg = self.expr(arg);
+                Expr::Apply(self.alloc.alloc([fun, arg]))
+            }
+
+            typed::ExprNode::Lambda(arrows) => {
+                let ty = self.ty(expr.ty);
+                let (param_ty, result_ty) = ty
+                    .as_arrow()
+                    .expect("the type of a lambda is a function type");
+
+                if let [(pattern, body)] = arrows {
+                    if let Some(name) = bind_name(pattern) {
+                        let body = self.expr(body);
+                        return Expr::Lambda(name, param_ty, self.alloc.alloc(body));
+                    }
+                }
+
+                let param = self.fresh("x");
+                let arms: Vec<_> = arrows
+                    .iter()
+                    .map(|(pattern, body)| (pattern, self.expr(body)))
+                    .collect();
+                let body = self.arms(param, &arms, result_ty);
+                Expr::Lambda(param, param_ty, self.alloc.alloc(body))
+            }
+
+            typed::ExprNode::Let(pattern, [bound, body], _) => {
+                let params = &*self.alloc.alloc_slice_clone(&pattern.scheme.params);
+
+                if let Some(name) = bind_name(pattern) {
+                    if !params.is_empty() {
+                        let scheme = params.to_vec();
+                        self.polys.insert(name, Poly { scheme, params });
+                    }
+
+                    let ty = self.ty(pattern.scheme.ty);
+                    let bound = self.expr(bound);
+                    let binding = Binding {
+                        name,
+                        ty: self.forall(params, ty),
+                        body: self.type_lambda(params, bound),
+                    };
+
+                    let body = self.expr(body);
+                    return Expr::Let(self.alloc.alloc(binding), self.alloc.alloc(body));
+                }
+
+                let value = self.fresh("value");
+                let ty = self.ty(bound.ty);
+                let bound = self.expr(bound);
+                let binding = Binding {
+                    name: value,
+                    ty: self.forall(params, ty),
+                    body: self.type_lambda(params, bound),
+                };
+
+                let result_ty = self.ty(expr.ty);
+                let body = if params.is_empty() {
+                    let body = self.expr(body);
+                    self.arms(value, &[(pattern, body)], result_ty)
+                } else {
+                    for (name, _) in bound_names(pattern) {
+                        let scheme = params.to_vec();
+                        self.polys.insert(name, Poly { scheme, params });
+                    }
+
+                    let bindings = self.destructure(params, value, ty, pattern);
+                    let body = self.expr(body);
+                    bindings.into_iter().rev().fold(body, |body, binding| {
+                        Expr::Let(self.alloc.alloc(binding), self.alloc.alloc(body))
+                    })
+                };
+
+                Expr::Let(self.alloc.alloc(binding), self.alloc.alloc(body))
+            }
+        }
+    }
+
+    /// Apply a variable to the type arguments its scheme is instantiated with.
+    fn var(&mut self, var: &typed::Var<'a>) -> Expr<'a, 'src> {
+        if let Some(primitive) = Primitive::from_name(self.names, &var.name) {
+            return self.primitive(primitive, var);
+        }
+
+        let expr = Expr::Var(var.name);
+        let Some(poly) = self.polys.get(&var.name) else {
+            return expr;
+        };
+
+        let params = poly.params;
+        if var.args.is_empty() && self.group.contains(&var.name) {
+            return self.type_apply(params, expr);
+        }
+
+        let args: Vec<_> = params
+            .iter()
+            .map(|param| {
+                let arg = poly
+                    .scheme
+                    .iter()
+                    .position(|other| other == param)
+                    .and_then(|index| var.args.get(index));
+
+                match arg {
+                    Some(tyck::TypeArg::Type(ty)) => TypeArg::Type(self.ty(ty)),
+                    Some(tyck::TypeArg::Row(row)) => TypeArg::Row(self.row(row)),
+                    None => default_arg(param),
+                }
+            })
+            .collect();
+
+        if args.is_empty() {
+            expr
+        } else {
+            let args = self.alloc.alloc_slice_fill_iter(args);
+            Expr::TypeApply(self.alloc.alloc(expr), args)
+        }
+    }
+
+    /// Turn a primitive operation into a curried function applying it.
+    fn primitive(&mut self, primitive: Primitive, var: &typed::Var<'a>) -> Expr<'a, 'src> {
+        let int = &*self.alloc.alloc(Type::Integer);
+        let params: Vec<_> = match primitive {
+            Primitive::Add | Primitive::Sub | Primitive::Mul => vec![int, int],
+            Primitive::Less => {
+                let ty = match var.args.first() {
+                    Some(tyck::TypeArg::Type(ty)) => self.ty(ty),
+                    _ => &*self.alloc.alloc(Type::Unit),
+                };
+
+                vec![int, int, ty, ty]
+            }
+        };
+
+        let params: Vec<_> = params.into_iter().map(|ty| (self.fresh("x"), ty)).collect();
+
+        let args = params.iter().map(|(name, _)| Expr::Var(*name));
+        let args = self.alloc.alloc_slice_fill_iter(args);
+
+        params
+            .into_iter()
+            .rev()
+            .fold(Expr::Primitive(primitive, args), |body, (name, ty)| {
+                Expr::Lambda(name, ty, self.alloc.alloc(body))
+            })
+    }
+
+    /// Access the field `label` of a record, unwrapping it first if it is a
+    /// nominal record. If the field is polymorphic, it is instantiated to get
+    /// the type `ty`.
+    fn project(
+        &mut self,
+        record: Expr<'a, 'src>,
+        record_ty: &'a Type<'a>,
+        label: Label<'a>,
+        ty: &'a Type<'a>,
+    ) -> Expr<'a, 'src> {
+        if let Some((ctor, fields)) = self.nominal(record_ty) {
+            let name = self.fresh("record");
+            let body = self.project(Expr::Var(name), fields, label, ty);
+            let binds = self.alloc.alloc_slice_fill_iter([(name, fields)]);
+            let alts = self
+                .alloc
+                .alloc_slice_fill_iter([Alt { ctor, binds, body }]);
+            return Expr::Case(self.alloc.alloc(record), alts, None);
+        }
+
+        let field = Expr::Field(self.alloc.alloc(record), record_ty, label);
+        match field_type(record_ty, &label) {
+            Some(Type::Forall(params, pattern)) => {
+                let mut subst = BTreeMap::new();
+                bind_params(&mut subst, params, pattern, ty);
+                let args = self.alloc.alloc_slice_fill_iter(
+                    params
+                        .iter()
+                        .map(|param| subst.get(param).copied().unwrap_or(default_arg(param))),
+                );
+
+                Expr::TypeApply(self.alloc.alloc(field), args)
+            }
+
+            _ => field,
+        }
+    }
+
+    /// If `ty` is a nominal record, get its constructor and the record type it
+    /// wraps.
+    fn nominal(&self, ty: &'a Type<'a>) -> Option<(Name, &'a Type<'a>)> {
+        let (Type::Named(name), args) = ty.spine() else {
+            return None;
+        };
+
+        let nominal = self.records.get(name)?;
+        if nominal.params.len() != args.len() {
+            return None;
+        }
+
+        let subst = nominal
+            .params
+            .iter()
+            .copied()
+            .zip(args.into_iter().map(TypeArg::Type))
+            .collect();
+
+        Some((nominal.ctor, substitute(self.alloc, &subst, nominal.record)))
+    }
+
+    /// Match the variable `scrutinee` against the patterns of each arm,
+    /// evaluating to the body of the first arm which matches. An arm chosen in
+    /// several places of the decision tree is bound to a local function of the
+    /// names its pattern binds, which is called wherever it is chosen.
+    fn arms(
+        &mut self,
+        scrutinee: Name,
+        arms: &[(&typed::Pattern<'a>, Expr<'a, 'src>)],
+        result: &'a Type<'a>,
+    ) -> Expr<'a, 'src> {
+        let Some((first, _)) = arms.first() else {
+            return Expr::Fail(result);
+        };
+
+        let tree = matching::compile(
+            self.alloc,
+            &self.constructors,
+            first.scheme.ty,
+            arms.iter().map(|(pattern, _)| *pattern),
+        );
+
+        let uses = tree.uses(arms.len());
+        let mut joins = Vec::new();
+        let targets: Vec<_> = arms
+            .iter()
+            .zip(uses)
+            .map(|((pattern, body), uses)| {
+                let bound = bound_names(pattern);
+                if uses <= 1 || is_trivial(body) {
+                    return Target::Inline(*body);
+                }
+
+                let (join, params) = self.join(&bound, *body, result);
+                joins.push(join);
+                Target::Join(join.name, params)
+            })
+            .collect();
+
+        let mut vars = BTreeMap::from([(Occurrence::SCRUTINEE, scrutinee)]);
+        let body = self.decision(&tree, &tree.tree, &targets, &mut vars, result);
+
+        joins.into_iter().rev().fold(body, |body, join| {
+            Expr::Let(self.alloc.alloc(join), self.alloc.alloc(body))
+        })
+    }
+
+    /// Lower a decision tree, where `vars` holds the variable bound to each
+    /// occurrence so far.
+    fn decision(
+        &mut self,
+        tree: &Match<'a>,
+        decision: &Decision<'a>,
+        targets: &[Target<'a, 'src>],
+        vars: &mut BTreeMap<Occurrence, Name>,
+        result: &'a Type<'a>,
+    ) -> Expr<'a, 'src> {
+        let mut lets = Vec::new();
+
+        let body = match decision {
+            Decision::Fail => Expr::Fail(result),
+
+            Decision::Success(arm, binds) => match &targets[*arm] {
+                Target::Inline(body) => {
+                    let binds: Vec<_> = binds
+                        .iter()
+                        .map(|(name, at)| Binding {
+                            name: *name,
+                            ty: self.ty(tree.ty(*at)),
+                            body: Expr::Var(self.occurrence(tree, *at, vars, &mut lets)),
+                        })
+                        .collect();
+
+                    binds.into_iter().rev().fold(*body, |body, binding| {
+                        Expr::Let(self.alloc.alloc(binding), self.alloc.alloc(body))
+                    })
+                }
+
+                Target::Join(join, params) => {
+                    let args: Vec<_> = match params {
+                        None => vec![Expr::Unit],
+                        Some(params) => params
+                            .iter()
+                            .map(|param| {
+                                let (_, at) = binds
+                                    .iter()
+                                    .find(|(name, _)| name == param)
+                                    .expect("every alternative binds the same names");
+                                Expr::Var(self.occurrence(tree, *at, vars, &mut lets))
+                            })
+                            .collect(),
+                    };
+
+                    args.into_iter().fold(Expr::Var(*join), |fun, arg| {
+                        Expr::Apply(self.alloc.alloc([fun, arg]))
+                    })
+                }
+            },
+
+            Decision::Switch(at, cases, default) => {
+                let scrutinee = self.occurrence(tree, *at, vars, &mut lets);
+
+                let alts: Vec<_> = cases
+                    .iter()
+                    .map(|case| {
+                        let binds: Vec<_> = case
+                            .args
+                            .iter()
+                            .map(|arg| {
+                                let name = self.fresh("field");
+                                vars.insert(*arg, name);
+                                (name, self.ty(tree.ty(*arg)))
+                            })
+                            .collect();
+
+                        let body = self.decision(tree, &case.tree, targets, vars, result);
+                        Alt {
+                            ctor: case.ctor,
+                            binds: self.alloc.alloc_slice_fill_iter(binds),
+                            body,
+                        }
+                    })
+                    .collect();
+
+                let default = default.map(|default| {
+                    let default = self.decision(tree, default, targets, vars, result);
+                    &*self.alloc.alloc(default)
+                });
+
+                let alts = self.alloc.alloc_slice_fill_iter(alts);
+                Expr::Case(self.alloc.alloc(Expr::Var(scrutinee)), alts, default)
+            }
+        };
+
+        lets.into_iter().rev().fold(body, |body, binding| {
+            Expr::Let(self.alloc.alloc(binding), self.alloc.alloc(body))
+        })
+    }
+
+    /// Get the variable bound to an occurrence. Record fields are bound the
+    /// first time they are needed, by pushing a binding to `lets`.
+    fn occurrence(
+        &mut self,
+        tree: &Match<'a>,
+        at: Occurrence,
+        vars: &mut BTreeMap<Occurrence, Name>,
+        lets: &mut Vec<Binding<'a, 'src>>,
+    ) -> Name {
+        if let Some(name) = vars.get(&at) {
+            return *name;
+        }
+
+        let Access::Field(record_at, label) = tree.access(at) else {
+            unreachable!("constructor arguments are bound by the case testing the constructor");
+        };
+
+        let record = self.occurrence(tree, record_at, vars, lets);
+        let name = self.fresh("field");
+        lets.push(Binding {
+            name,
+            ty: self.ty(tree.ty(at)),
+            body: Expr::Field(
+                self.alloc.alloc(Expr::Var(record)),
+                self.ty(tree.ty(record_at)),
+                label,
+            ),
+        });
+
+        vars.insert(at, name);
+        name
+    }
+
+    /// Bind `body` to a local function of the names bound by a pattern (or of
+    /// unit, if there are none). Returns the binding along with the names it
+    /// is a function of.
+    fn join(
+        &mut self,
+        bound: &[(Name, &'a tyck::Type<'a>)],
+        body: Expr<'a, 'src>,
+        result: &'a Type<'a>,
+    ) -> (Binding<'a, 'src>, Option<Vec<Name>>) {
+        let name = self.fresh("join");
+        let (params, names) = if bound.is_empty() {
+            (
+                vec![(self.fresh("unit"), &*self.alloc.alloc(Type::Unit))],
+                None,
+            )
+        } else {
+            let params = bound
+                .iter()
+                .map(|(name, ty)| (*name, self.ty(ty)))
+                .collect();
+            let names = bound.iter().map(|(name, _)| *name).collect();
+            (params, Some(names))
+        };
+
+        let (ty, body) =
+            params
+                .into_iter()
+                .rev()
+                .fold((result, body), |(ty, body), (param, param_ty)| {
+                    let ty = self.arrow(param_ty, ty);
+                    let body = Expr::Lambda(param, param_ty, self.alloc.alloc(body));
+                    (ty, body)
+                });
+
+        (Binding { name, ty, body }, names)
+    }
+
+    fn ty(&self, ty: &tyck::Type<'a>) -> &'a Type<'a> {
+        let ty = match ty.expand() {
+            tyck::Type::Param(param) => Type::Param(*param),
+            tyck::Type::Named(name) => Type::Named(*name),
+            tyck::Type::Unit => Type::Unit,
+            tyck::Type::Integer => Type::Integer,
+            tyck::Type::Arrow => Type::Arrow,
+            tyck::Type::Record(row) => Type::Record(self.row(row)),
+            tyck::Type::Apply(t, u) => Type::Apply(self.ty(t), self.ty(u)),
+
+            tyck::Type::Invalid(_) => unreachable!("only programs without errors are lowered"),
+            tyck::Type::Var(..) => unreachable!("typed programs have no unification variables"),
+            tyck::Type::Variant(_) => unreachable!("there are no variant types yet"),
+            tyck::Type::Alias(..) => unreachable!("aliases are expanded"),
+        };
+
+        self.alloc.alloc(ty)
+    }
+
+    fn row(&self, row: &tyck::Row<'a>) -> &'a Row<'a> {
+        let row = match row {
+            tyck::Row::Empty => Row::Empty,
+            tyck::Row::Param(param) => Row::Param(*param),
+            tyck::Row::Extend(label, ty, rest) => Row::Extend(*label, self.ty(ty), self.row(rest)),
+
+            tyck::Row::Invalid(_) => unreachable!("only programs without errors are lowered"),
+            tyck::Row::Var(..) => unreachable!("typed programs have no unification variables"),
+        };
+
+        self.alloc.alloc(row)
+    }
+
+    fn arrow(&self, t: &'a Type<'a>, u: &'a Type<'a>) -> &'a Type<'a> {
+        let arrow = self.alloc.alloc(Type::Arrow);
+        let arrow = self.alloc.alloc(Type::Apply(arrow, t));
+        self.alloc.alloc(Type::Apply(arrow, u))
+    }
+
+    fn forall(&self, params: &[Generic], ty: &'a Type<'a>) -> &'a Type<'a> {
+        if params.is_empty() {
+            ty
+        } else {
+            let params = self.alloc.alloc_slice_copy(params);
+            self.alloc.alloc(Type::Forall(params, ty))
+        }
+    }
+
+    fn type_lambda(&self, params: &[Generic], body: Expr<'a, 'src>) -> Expr<'a, 'src> {
+        if params.is_empty() {
+            body
+        } else {
+            let params = self.alloc.alloc_slice_copy(params);
+            Expr::TypeLambda(params, self.alloc.alloc(body))
+        }
+    }
+
+    fn type_apply(&self, params: &[Generic], expr: Expr<'a, 'src>) -> Expr<'a, 'src> {
+        if params.is_empty() {
+            return expr;
+        }
+
+        let args =
+            self.alloc
+                .alloc_slice_fill_iter(params.iter().map(|param| match param.kind() {
+                    VarKind::Type => TypeArg::Type(self.alloc.alloc(Type::Param(*param))),
+                    VarKind::Row => TypeArg::Row(self.alloc.alloc(Row::Param(*param))),
+                }));
+
+        Expr::TypeApply(self.alloc.alloc(expr), args)
+    }
+
+    /// Make a fresh name. Fresh names are put in the first anonymous scope,
+    /// which the resolver never uses.
+    fn fresh(&self, name: &'static str) -> Name {
+        self.names
+            .name(ScopeName::Anonymous(0), self.names.intern(name))
+    }
+}
+
+/// Substitute the given arguments for their parameters in a type.
+pub(crate) fn substitute<'a>(
+    alloc: &'a Bump,
+    subst: &BTreeMap<Generic, TypeArg<'a>>,
+    ty: &'a Type<'a>,
+) -> &'a Type<'a> {
+    match ty {
+        Type::Param(param) => match subst.get(param) {
+            Some(TypeArg::Type(ty)) => ty,
+            _ => ty,
+        },
+
+        Type::Named(_) | Type::Unit | Type::Integer | Type::Arrow => ty,
+
+        Type::Record(row) => alloc.alloc(Type::Record(substitute_row(alloc, subst, row))),
+        Type::Apply(t, u) => {
+            let t = substitute(alloc, subst, t);
+            let u = substitute(alloc, subst, u);
+            alloc.alloc(Type::Apply(t, u))
+        }
+
+        Type::Forall(params, body) => {
+            let mut subst = subst.clone();
+            for param in params.iter() {
+                subst.remove(param);
+            }
+
+            alloc.alloc(Type::Forall(params, substitute(alloc, &subst, body)))
+        }
+    }
+}
+
+pub(super) fn substitute_row<'a>(
+    alloc: &'a Bump,
+    subst: &BTreeMap<Generic, TypeArg<'a>>,
+    row: &'a Row<'a>,
+) -> &'a Row<'a> {
+    match row {
+        Row::Empty => row,
+        Row::Param(param) => match subst.get(param) {
+            Some(TypeArg::Row(row)) => row,
+            _ => row,
+        },
+
+        Row::Extend(label, ty, rest) => {
+            let ty = substitute(alloc, subst, ty);
+            let rest = substitute_row(alloc, subst, rest);
+            alloc.alloc(Row::Extend(*label, ty, rest))
+        }
+    }
+}
+
+/// Get the type of the first field with the given label in a record type.
+pub(super) fn field_type<'a>(record: &'a Type<'a>, label: &Label) -> Option<&'a Type<'a>> {
+    let Type::Record(mut row) = record else {
+        return None;
+    };
+
+    loop {
+        match row {
+            Row::Extend(other, ty, _) if other == label => return Some(ty),
+            Row::Extend(_, _, rest) => row = rest,
+            Row::Empty | Row::Param(_) => return None,
+        }
+    }
+}
+
+/// Find the arguments for `params` making `pattern` equal to `ty`.
+fn bind_params<'a>(
+    subst: &mut BTreeMap<Generic, TypeArg<'a>>,
+    params: &[Generic],
+    pattern: &'a Type<'a>,
+    ty: &'a Type<'a>,
+) {
+    match (pattern, ty) {
+        (Type::Param(param), _) if params.contains(param) => {
+            subst.insert(*param, TypeArg::Type(ty));
+        }
+
+        (Type::Apply(t1, u1), Type::Apply(t2, u2)) => {
+            bind_params(subst, params, t1, t2);
+            bind_params(subst, params, u1, u2);
+        }
+
+        (Type::Record(row1), Type::Record(row2)) => {
+            let mut row1 = *row1;
+            loop {
+                match row1 {
+                    Row::Extend(label, t1, rest) => {
+                        if let Some(t2) = field_type(ty, label) {
+                            bind_params(subst, params, t1, t2);
+                        }
+
+                        row1 = rest;
+                    }
+
+                    Row::Param(param) if params.contains(param) => {
+                        let mut row2 = *row2;
+                        while let Row::Extend(label, _, rest) = row2 {
+                            if field_type(pattern, label).is_none() {
+                                break;
+                            }
+
+                            row2 = rest;
+                        }
+
+                        subst.insert(*param, TypeArg::Row(row2));
+                        break;
+                    }
+
+                    Row::Empty | Row::Param(_) => break,
+                }
+            }
+        }
+
+        _ => {}
+    }
+}
+
+fn default_arg<'a>(param: &Generic) -> TypeArg<'a> {
+    match param.kind() {
+        VarKind::Type => TypeArg::Type(&Type::Unit),
+        VarKind::Row => TypeArg::Row(&Row::Empty),
+    }
+}
+
+/// Whether an expression is small enough to be duplicated.
+fn is_trivial(expr: &Expr) -> bool {
+    match expr {
+        Expr::Var(_) | Expr::Unit | Expr::Fail(_) => true,
+        Expr::Apply([fun, arg]) => is_trivial(fun) && matches!(arg, Expr::Var(_) | Expr::Unit),
+        _ => false,
+    }
+}
+
+/// If the pattern binds just a single name and nothing else, get that name.
+pub(crate) fn bind_name(pattern: &typed::Pattern) -> Option<Name> {
+    match &pattern.node {
+        typed::PatternNode::Bind(name) => Some(*name),
+        typed::PatternNode::Group(pattern) => bind_name(pattern),
+        _ => None,
+    }
+}
+
+/// Get every name bound by a pattern along with its type.
+pub(crate) fn bound_names<'a>(pattern: &typed::Pattern<'a>) -> Vec<(Name, &'a tyck::Type<'a>)> {
+    fn go<'a>(names: &mut Vec<(Name, &'a tyck::Type<'a>)>, pattern: &typed::Pattern<'a>) {
+        match &pattern.node {
+            typed::PatternNode::Bind(name) => names.push((*name, pattern.scheme.ty)),
+            typed::PatternNode::Group(pattern) => go(names, pattern),
+            typed::PatternNode::Apply([a, b]) | typed::PatternNode::And([a, b]) => {
+                go(names, a);
+                go(names, b);
+            }
+
+            // Both alternatives bind the same names.
+            typed::PatternNode::Or([a, _]) => go(names, a),
+            typed::PatternNode::Record(fields) => {
+                for (_, _, field) in fields.iter() {
+                    go(names, field);
+                }
+            }
+
+            typed::PatternNode::Invalid(_)
+            | typed::PatternNode::Wildcard
+            | typed::PatternNode::Unit
+            | typed::PatternNode::Constructor(_) => {}
+
+            typed::PatternNode::Anno(_, v) => match *v {},
+        }
+    }
+
+    let mut names = Vec::new();
+    go(&mut names, pattern);
+    names
+}
+
+pub(crate) fn strip_groups<'p, 'a, 'src>(
+    expr: &'p typed::Expr<'a, 'src>,
+) -> &'p typed::Expr<'a, 'src> {
+    match &expr.node {
+        typed::ExprNode::Group(expr) => strip_groups(expr),
+        _ => expr,
+    }
+}
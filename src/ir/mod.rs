@@ -0,0 +1,174 @@
+//! The core language is a small, explicitly typed language in the style of
+//! System F, extended with records and data types. It sits between the typed
+//! trees of the frontend and any backend:
+//!
+//! - every binder is annotated with its type,
+//! - type abstraction and application are explicit,
+//! - patterns are desugared into flat `case` expressions over a single
+//!   constructor at a time, and
+//! - classes and instances are plain data types and values.
+//!
+//! Since the core language is so small, it has its own [type checker](check)
+//! which is useful for catching bugs in the passes producing it. Before being
+//! handed to a backend, core programs may be [optimized](optimize).
+
+pub mod analyze;
+pub mod check;
+pub mod lower;
+pub mod optimize;
+pub mod pretty;
+
+#[cfg(test)]
+mod tests;
+
+use crate::frontend::builtins::Primitive;
+use crate::frontend::names::{Label, Name};
+use crate::frontend::tyck::Generic;
+
+pub struct Program<'a, 'src> {
+    pub items: &'a [Item<'a, 'src>],
+}
+
+pub enum Item<'a, 'src> {
+    /// A data type along with its constructors.
+    Data(Data<'a>),
+
+    /// A group of possibly mutually recursive bindings.
+    Group(&'a [Binding<'a, 'src>]),
+}
+
+/// `data n params = c1 t1 ... tn | ...`
+#[derive(Clone, Copy)]
+pub struct Data<'a> {
+    pub name: Name,
+    pub params: &'a [Generic],
+    pub ctors: &'a [Constructor<'a>],
+}
+
+pub struct Constructor<'a> {
+    pub name: Name,
+    pub fields: &'a [&'a Type<'a>],
+}
+
+/// `name : ty = body`
+#[derive(Clone, Copy)]
+pub struct Binding<'a, 'src> {
+    pub name: Name,
+    pub ty: &'a Type<'a>,
+    pub body: Expr<'a, 'src>,
+}
+
+#[derive(Clone, Copy)]
+pub enum Expr<'a, 'src> {
+    /// A variable or constructor.
+    Var(Name),
+
+    /// `()`
+    Unit,
+
+    /// Some integer
+    Number(&'src str),
+
+    /// A failed pattern match.
+    Fail(&'a Type<'a>),
+
+    /* Records -------------------------------------------------------------- */
+    /// `x.a`, where `x` has the given record type.
+    Field(&'a Expr<'a, 'src>, &'a Type<'a>, Label<'a>),
+
+    /// `{ a = x, b = y | r }`
+    Record(
+        &'a [(Label<'a>, Expr<'a, 'src>)],
+        Option<&'a Expr<'a, 'src>>,
+    ),
+
+    /// `x \ a`
+    Restrict(&'a Expr<'a, 'src>, Label<'a>),
+
+    /* Functions ------------------------------------------------------------ */
+    /// `x y`
+    Apply(&'a [Expr<'a, 'src>; 2]),
+
+    /// `\(a : t) -> x`
+    Lambda(Name, &'a Type<'a>, &'a Expr<'a, 'src>),
+
+    /// `x [t, u]`
+    TypeApply(&'a Expr<'a, 'src>, &'a [TypeArg<'a>]),
+
+    /// `/\a b. x`
+    TypeLambda(&'a [Generic], &'a Expr<'a, 'src>),
+
+    /// `op# x y`, a primitive operation applied to all of its arguments.
+    Primitive(Primitive, &'a [Expr<'a, 'src>]),
+
+    /// `let a : t = x in y`
+    Let(&'a Binding<'a, 'src>, &'a Expr<'a, 'src>),
+
+    /// `case x of c a b -> y | ... | _ -> z`
+    Case(
+        &'a Expr<'a, 'src>,
+        &'a [Alt<'a, 'src>],
+        Option<&'a Expr<'a, 'src>>,
+    ),
+}
+
+/// `c (a : t) (b : u) -> x`
+#[derive(Clone, Copy)]
+pub struct Alt<'a, 'src> {
+    pub ctor: Name,
+    pub binds: &'a [(Name, &'a Type<'a>)],
+    pub body: Expr<'a, 'src>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Type<'a> {
+    Param(Generic),
+    Named(Name),
+
+    Unit,
+    Integer,
+    Arrow,
+    Record(&'a Row<'a>),
+
+    Apply(&'a Type<'a>, &'a Type<'a>),
+
+    /// `forall a b. t`
+    Forall(&'a [Generic], &'a Type<'a>),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Row<'a> {
+    Empty,
+    Param(Generic),
+    Extend(Label<'a>, &'a Type<'a>, &'a Row<'a>),
+}
+
+/// The argument a type abstraction is applied to for one of its parameters.
+#[derive(Clone, Copy, Debug)]
+pub enum TypeArg<'a> {
+    Type(&'a Type<'a>),
+    Row(&'a Row<'a>),
+}
+
+impl<'a> Type<'a> {
+    /// If this is a function type `t -> u`, get `t` and `u`.
+    pub fn as_arrow(&self) -> Option<(&'a Type<'a>, &'a Type<'a>)> {
+        match self {
+            Self::Apply(Self::Apply(Self::Arrow, t), u) => Some((t, u)),
+            _ => None,
+        }
+    }
+
+    /// Split a type into its head and the arguments it is applied to.
+    pub fn spine(&'a self) -> (&'a Type<'a>, Vec<&'a Type<'a>>) {
+        match self {
+            Self::Apply(t, u) => {
+                let (head, mut args) = t.spine();
+                args.push(u);
+                (head, args)
+            }
+
+            ty => (ty, Vec::new()),
+        }
+    }
+}
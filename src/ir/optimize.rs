@@ -0,0 +1,750 @@
+//! The optimizer simplifies core programs before they are handed to a backend.
+//! It repeatedly rewrites the program bottom-up until nothing changes (or some
+//! number of rounds have passed), using these rules:
+//!
+//! - *beta reduction*: a lambda applied to an argument becomes a `let`, and a
+//!   type abstraction applied to types has the types substituted in its body,
+//! - *inlining*: uses of a `let` binding bound to a variable or literal are
+//!   replaced by it, and at the higher level so are uses of small functions
+//!   bound by non-recursive local or top-level bindings,
+//! - *case of known constructor*: a `case` of a constructor application (or of
+//!   a variable bound to one) becomes the alternative for that constructor,
+//! - *known projection*: selecting a field of a record literal (or of a
+//!   variable bound to one) becomes the value of that field, and
+//! - *dead binding elimination*: unused local bindings are removed.
+//!
+//! Evaluating an expression may fail or never finish, so the rules never drop
+//! or duplicate work other than [pure](is_pure) expressions. Top-level
+//! bindings are always kept, since they may be exported.
+//!
+//! Inlined code is copied with fresh names for the variables it binds, so
+//! every binder in the program stays unique.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bumpalo::Bump;
+
+use super::analyze::free_vars;
+use super::lower::substitute;
+use super::{Alt, Binding, Expr, Item, Program, TypeArg};
+use crate::frontend::names::{Label, Name, Names};
+use crate::frontend::topology;
+use crate::frontend::tyck::Generic;
+
+/// Functions of at most this size are inlined at [`Level::Inline`].
+const INLINE_SIZE: usize = 40;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Level {
+    /// Only apply rules which never make the program larger.
+    Simplify,
+
+    /// Also inline small functions.
+    Inline,
+}
+
+impl Level {
+    /// The maximum number of times the program is rewritten.
+    fn rounds(&self) -> usize {
+        match self {
+            Self::Simplify => 4,
+            Self::Inline => 8,
+        }
+    }
+}
+
+pub fn optimize<'a, 'src>(
+    alloc: &'a Bump,
+    names: &Names<'src>,
+    program: &Program<'a, 'src>,
+    level: Level,
+) -> Program<'a, 'src> {
+    let mut optimizer = Optimizer {
+        alloc,
+        names,
+        level,
+        arities: BTreeMap::new(),
+        recursive: recursive(program),
+        inline: BTreeMap::new(),
+        known: BTreeMap::new(),
+        changed: false,
+    };
+
+    for item in program.items {
+        if let Item::Data(data) = item {
+            for ctor in data.ctors {
+                optimizer.arities.insert(ctor.name, ctor.fields.len());
+            }
+        }
+    }
+
+    let mut program = Program {
+        items: program.items,
+    };
+
+    for _ in 0..level.rounds() {
+        optimizer.changed = false;
+        program = optimizer.program(&program);
+
+        if !optimizer.changed {
+            break;
+        }
+    }
+
+    program
+}
+
+/// Get every top-level binding which refers to itself, directly or through
+/// other bindings.
+fn recursive(program: &Program) -> BTreeSet<Name> {
+    let mut graph = BTreeMap::new();
+    for item in program.items {
+        if let Item::Group(bindings) = item {
+            for binding in bindings.iter() {
+                graph.insert(binding.name, free_vars(&binding.body));
+            }
+        }
+    }
+
+    let graph: BTreeMap<_, BTreeSet<_>> = graph
+        .iter()
+        .map(|(name, uses)| {
+            let uses = uses
+                .iter()
+                .filter(|other| graph.contains_key(other))
+                .copied()
+                .collect();
+            (*name, uses)
+        })
+        .collect();
+
+    let mut recursive = BTreeSet::new();
+    for component in topology::find(&graph) {
+        let first = **component.first().expect("components are never empty");
+        if component.len() > 1 || graph[&first].contains(&first) {
+            recursive.extend(component.into_iter().copied());
+        }
+    }
+
+    recursive
+}
+
+struct Optimizer<'a, 'n, 'src> {
+    alloc: &'a Bump,
+    names: &'n Names<'src>,
+    level: Level,
+
+    /// The number of fields of every constructor.
+    arities: BTreeMap<Name, usize>,
+
+    /// The top-level bindings which are recursive, and so are never inlined.
+    recursive: BTreeSet<Name>,
+
+    /// The bindings in scope whose uses are replaced by their definitions.
+    inline: BTreeMap<Name, Expr<'a, 'src>>,
+
+    /// The bindings in scope bound to constructions or records of atoms, which
+    /// cases and projections can look through.
+    known: BTreeMap<Name, Expr<'a, 'src>>,
+
+    /// Whether anything was rewritten in the current round.
+    changed: bool,
+}
+
+impl<'a, 'src> Optimizer<'a, '_, 'src> {
+    fn program(&mut self, program: &Program<'a, 'src>) -> Program<'a, 'src> {
+        self.inline.clear();
+
+        let items = program.items.iter().map(|item| match item {
+            Item::Data(data) => Item::Data(*data),
+            Item::Group(bindings) => {
+                let bindings: Vec<_> = bindings
+                    .iter()
+                    .map(|binding| Binding {
+                        body: self.expr(&binding.body),
+                        ..*binding
+                    })
+                    .collect();
+
+                for binding in bindings.iter() {
+                    let inline = self.level == Level::Inline
+                        && !self.recursive.contains(&binding.name)
+                        && self.is_small_function(&binding.body);
+
+                    if inline {
+                        self.inline.insert(binding.name, binding.body);
+                    }
+                }
+
+                Item::Group(self.alloc.alloc_slice_fill_iter(bindings))
+            }
+        });
+
+        let items: Vec<_> = items.collect();
+        Program {
+            items: self.alloc.alloc_slice_fill_iter(items),
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr<'a, 'src>) -> Expr<'a, 'src> {
+        match expr {
+            Expr::Var(name) => match self.inline.get(name) {
+                Some(def) => {
+                    self.changed = true;
+                    let def = *def;
+                    self.copy(&mut BTreeMap::new(), &def)
+                }
+
+                None => *expr,
+            },
+
+            Expr::Unit | Expr::Number(_) | Expr::Fail(_) => *expr,
+
+            Expr::Field(record, ty, label) => {
+                let record = self.expr(record);
+                match record {
+                    Expr::Let(binding, body) => {
+                        self.changed = true;
+                        let body = Expr::Field(self.alloc.alloc(*body), ty, *label);
+                        Expr::Let(binding, self.alloc.alloc(body))
+                    }
+
+                    record => match self.project(&self.lookup(&record), label) {
+                        Some(value) => {
+                            self.changed = true;
+                            value
+                        }
+
+                        None => Expr::Field(self.alloc.alloc(record), ty, *label),
+                    },
+                }
+            }
+
+            Expr::Record(fields, extend) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(label, field)| (*label, self.expr(field)))
+                    .collect();
+                let fields = self.alloc.alloc_slice_fill_iter(fields);
+                let extend = extend.map(|extend| &*self.alloc.alloc(self.expr(extend)));
+                Expr::Record(fields, extend)
+            }
+
+            Expr::Restrict(record, label) => {
+                let record = self.expr(record);
+                Expr::Restrict(self.alloc.alloc(record), *label)
+            }
+
+            Expr::Apply([fun, arg]) => {
+                let fun = self.expr(fun);
+                let arg = self.expr(arg);
+
+                match fun {
+                    Expr::Lambda(name, ty, body) => {
+                        self.changed = true;
+                        let binding = self.alloc.alloc(Binding {
+                            name,
+                            ty,
+                            body: arg,
+                        });
+                        Expr::Let(binding, body)
+                    }
+
+                    Expr::Let(binding, body) => {
+                        self.changed = true;
+                        let body = Expr::Apply(self.alloc.alloc([*body, arg]));
+                        Expr::Let(binding, self.alloc.alloc(body))
+                    }
+
+                    fun => Expr::Apply(self.alloc.alloc([fun, arg])),
+                }
+            }
+
+            Expr::Lambda(name, ty, body) => {
+                let body = self.expr(body);
+                Expr::Lambda(*name, ty, self.alloc.alloc(body))
+            }
+
+            Expr::Primitive(primitive, args) => {
+                let args: Vec<_> = args.iter().map(|arg| self.expr(arg)).collect();
+                Expr::Primitive(*primitive, self.alloc.alloc_slice_fill_iter(args))
+            }
+
+            Expr::TypeApply(inner, args) => match self.expr(inner) {
+                Expr::TypeLambda(params, body) if params.len() == args.len() => {
+                    self.changed = true;
+                    let subst = params.iter().copied().zip(args.iter().copied()).collect();
+                    self.instantiate(&subst, body)
+                }
+
+                inner => Expr::TypeApply(self.alloc.alloc(inner), args),
+            },
+
+            Expr::TypeLambda(params, body) => {
+                let body = self.expr(body);
+                Expr::TypeLambda(params, self.alloc.alloc(body))
+            }
+
+            Expr::Let(binding, body) => {
+                let value = self.expr(&binding.body);
+
+                if self.is_atom(&value)
+                    || (self.level == Level::Inline && self.is_small_function(&value))
+                {
+                    self.changed = true;
+                    self.inline.insert(binding.name, value);
+                    let body = self.expr(body);
+                    self.inline.remove(&binding.name);
+                    return body;
+                }
+
+                let known = self.is_known(&value);
+                if known {
+                    self.known.insert(binding.name, value);
+                }
+
+                let body = self.expr(body);
+                if known {
+                    self.known.remove(&binding.name);
+                }
+
+                if self.is_pure(&value) && !free_vars(&body).contains(&binding.name) {
+                    self.changed = true;
+                    return body;
+                }
+
+                let binding = self.alloc.alloc(Binding {
+                    body: value,
+                    ..**binding
+                });
+                Expr::Let(binding, self.alloc.alloc(body))
+            }
+
+            Expr::Case(scrutinee, alts, default) => match self.expr(scrutinee) {
+                Expr::Let(binding, body) => {
+                    self.changed = true;
+                    let case = Expr::Case(body, alts, *default);
+                    let case = self.expr(&case);
+                    Expr::Let(binding, self.alloc.alloc(case))
+                }
+
+                scrutinee => {
+                    let value = self.lookup(&scrutinee);
+                    if let Some(known) = self.known_case(&value, alts, *default) {
+                        self.changed = true;
+                        return self.expr(&known);
+                    }
+
+                    let alts: Vec<_> = alts
+                        .iter()
+                        .map(|alt| Alt {
+                            body: self.expr(&alt.body),
+                            ..*alt
+                        })
+                        .collect();
+                    let alts = self.alloc.alloc_slice_fill_iter(alts);
+                    let default = default.map(|default| &*self.alloc.alloc(self.expr(default)));
+                    Expr::Case(self.alloc.alloc(scrutinee), alts, default)
+                }
+            },
+        }
+    }
+
+    /// If the scrutinee of a case is a constructor applied to all of its
+    /// arguments, get the alternative for it with its arguments bound.
+    fn known_case(
+        &mut self,
+        scrutinee: &Expr<'a, 'src>,
+        alts: &[Alt<'a, 'src>],
+        default: Option<&Expr<'a, 'src>>,
+    ) -> Option<Expr<'a, 'src>> {
+        let (ctor, args) = self.construction(scrutinee)?;
+
+        match alts.iter().find(|alt| alt.ctor == ctor) {
+            Some(alt) => {
+                let mut body = alt.body;
+                for (arg, (name, ty)) in args.into_iter().zip(alt.binds.iter()).rev() {
+                    let binding = self.alloc.alloc(Binding {
+                        name: *name,
+                        ty,
+                        body: arg,
+                    });
+                    body = Expr::Let(binding, self.alloc.alloc(body));
+                }
+
+                Some(body)
+            }
+
+            None if args.iter().all(|arg| self.is_pure(arg)) => default.copied(),
+            None => None,
+        }
+    }
+
+    /// If an expression is a constructor applied to all of its arguments, get
+    /// the constructor and the arguments.
+    fn construction(&self, expr: &Expr<'a, 'src>) -> Option<(Name, Vec<Expr<'a, 'src>>)> {
+        let mut args = Vec::new();
+        let mut head = expr;
+        loop {
+            match head {
+                Expr::Apply([fun, arg]) => {
+                    args.push(*arg);
+                    head = fun;
+                }
+
+                Expr::TypeApply(expr, _) => head = expr,
+                _ => break,
+            }
+        }
+
+        let Expr::Var(name) = head else {
+            return None;
+        };
+
+        if *self.arities.get(name)? != args.len() {
+            return None;
+        }
+
+        args.reverse();
+        Some((*name, args))
+    }
+
+    /// If a record is a literal with the given field and the rest of it is
+    /// pure, get the value of the field.
+    fn project(&self, record: &Expr<'a, 'src>, label: &Label) -> Option<Expr<'a, 'src>> {
+        let Expr::Record(fields, extend) = record else {
+            return None;
+        };
+
+        let (_, value) = fields.iter().find(|(other, _)| other == label)?;
+        let rest_pure = fields.iter().all(|(_, field)| self.is_pure(field))
+            && extend.is_none_or(|extend| self.is_pure(extend));
+
+        rest_pure.then_some(*value)
+    }
+
+    /// Get the value a variable is known to be bound to, or the expression
+    /// itself.
+    fn lookup(&self, expr: &Expr<'a, 'src>) -> Expr<'a, 'src> {
+        match expr {
+            Expr::Var(name) => self.known.get(name).copied().unwrap_or(*expr),
+            _ => *expr,
+        }
+    }
+
+    /// Whether an expression is a construction or record whose parts are all
+    /// atoms, so that its parts can be duplicated where it is matched on.
+    fn is_known(&self, expr: &Expr<'a, 'src>) -> bool {
+        match expr {
+            Expr::Record(fields, None) => fields.iter().all(|(_, field)| self.is_atom(field)),
+            expr => self
+                .construction(expr)
+                .is_some_and(|(_, args)| args.iter().all(|arg| self.is_atom(arg))),
+        }
+    }
+
+    /// Whether an expression is a variable or literal, which can be duplicated
+    /// freely.
+    fn is_atom(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Var(_) | Expr::Unit | Expr::Number(_) => true,
+            Expr::TypeApply(expr, _) => self.is_atom(expr),
+            _ => false,
+        }
+    }
+
+    /// Whether an expression is a function small enough to be inlined.
+    fn is_small_function(&self, expr: &Expr) -> bool {
+        let mut function = expr;
+        while let Expr::TypeLambda(_, body) = function {
+            function = body;
+        }
+
+        matches!(function, Expr::Lambda(..)) && size(expr) <= INLINE_SIZE
+    }
+
+    /// Whether evaluating an expression always succeeds without doing any
+    /// work besides allocating, so that it can be removed if its value is not
+    /// needed.
+    fn is_pure(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Var(_) | Expr::Unit | Expr::Number(_) | Expr::Lambda(..) => true,
+            Expr::Fail(_) | Expr::Let(..) | Expr::Case(..) => false,
+
+            Expr::Field(expr, ..)
+            | Expr::Restrict(expr, _)
+            | Expr::TypeApply(expr, _)
+            | Expr::TypeLambda(_, expr) => self.is_pure(expr),
+
+            Expr::Record(fields, extend) => {
+                fields.iter().all(|(_, field)| self.is_pure(field))
+                    && extend.is_none_or(|extend| self.is_pure(extend))
+            }
+
+            Expr::Primitive(_, args) => args.iter().all(|arg| self.is_pure(arg)),
+
+            // Applying a constructor to at most as many arguments as it takes
+            // only allocates.
+            Expr::Apply(_) => {
+                let mut args = 0;
+                let mut head = expr;
+                loop {
+                    match head {
+                        Expr::Apply([fun, arg]) => {
+                            if !self.is_pure(arg) {
+                                return false;
+                            }
+
+                            args += 1;
+                            head = fun;
+                        }
+
+                        Expr::TypeApply(expr, _) => head = expr,
+                        _ => break,
+                    }
+                }
+
+                match head {
+                    Expr::Var(name) => self.arities.get(name).is_some_and(|arity| args <= *arity),
+                    _ => false,
+                }
+            }
+        }
+    }
+
+    /// Copy an expression, giving fresh names to the variables it binds.
+    fn copy(&self, renames: &mut BTreeMap<Name, Name>, expr: &Expr<'a, 'src>) -> Expr<'a, 'src> {
+        match expr {
+            Expr::Var(name) => Expr::Var(renames.get(name).copied().unwrap_or(*name)),
+            Expr::Unit | Expr::Number(_) | Expr::Fail(_) => *expr,
+
+            Expr::Field(record, ty, label) => {
+                let record = self.copy(renames, record);
+                Expr::Field(self.alloc.alloc(record), ty, *label)
+            }
+
+            Expr::Record(fields, extend) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(label, field)| (*label, self.copy(renames, field)))
+                    .collect();
+                let fields = self.alloc.alloc_slice_fill_iter(fields);
+                let extend = extend.map(|extend| &*self.alloc.alloc(self.copy(renames, extend)));
+                Expr::Record(fields, extend)
+            }
+
+            Expr::Restrict(record, label) => {
+                let record = self.copy(renames, record);
+                Expr::Restrict(self.alloc.alloc(record), *label)
+            }
+
+            Expr::Apply([fun, arg]) => {
+                let fun = self.copy(renames, fun);
+                let arg = self.copy(renames, arg);
+                Expr::Apply(self.alloc.alloc([fun, arg]))
+            }
+
+            Expr::Lambda(name, ty, body) => {
+                let name = self.rename(renames, name);
+                let body = self.copy(renames, body);
+                Expr::Lambda(name, ty, self.alloc.alloc(body))
+            }
+
+            Expr::Primitive(primitive, args) => {
+                let args: Vec<_> = args.iter().map(|arg| self.copy(renames, arg)).collect();
+                Expr::Primitive(*primitive, self.alloc.alloc_slice_fill_iter(args))
+            }
+
+            Expr::TypeApply(expr, args) => {
+                let expr = self.copy(renames, expr);
+                Expr::TypeApply(self.alloc.alloc(expr), args)
+            }
+
+            Expr::TypeLambda(params, body) => {
+                let body = self.copy(renames, body);
+                Expr::TypeLambda(params, self.alloc.alloc(body))
+            }
+
+            Expr::Let(binding, body) => {
+                let value = self.copy(renames, &binding.body);
+                let name = self.rename(renames, &binding.name);
+                let body = self.copy(renames, body);
+                let binding = self.alloc.alloc(Binding {
+                    name,
+                    ty: binding.ty,
+                    body: value,
+                });
+                Expr::Let(binding, self.alloc.alloc(body))
+            }
+
+            Expr::Case(scrutinee, alts, default) => {
+                let scrutinee = self.copy(renames, scrutinee);
+                let alts: Vec<_> = alts
+                    .iter()
+                    .map(|alt| {
+                        let binds: Vec<_> = alt
+                            .binds
+                            .iter()
+                            .map(|(name, ty)| (self.rename(renames, name), *ty))
+                            .collect();
+                        Alt {
+                            ctor: alt.ctor,
+                            binds: self.alloc.alloc_slice_fill_iter(binds),
+                            body: self.copy(renames, &alt.body),
+                        }
+                    })
+                    .collect();
+                let alts = self.alloc.alloc_slice_fill_iter(alts);
+                let default =
+                    default.map(|default| &*self.alloc.alloc(self.copy(renames, default)));
+                Expr::Case(self.alloc.alloc(scrutinee), alts, default)
+            }
+        }
+    }
+
+    fn rename(&self, renames: &mut BTreeMap<Name, Name>, name: &Name) -> Name {
+        let qualified = self.names.get_name(name);
+        let fresh = self.names.name(qualified.parent, qualified.name);
+        renames.insert(*name, fresh);
+        fresh
+    }
+
+    /// Substitute the given arguments for their parameters in the types of an
+    /// expression.
+    fn instantiate(
+        &self,
+        subst: &BTreeMap<Generic, TypeArg<'a>>,
+        expr: &Expr<'a, 'src>,
+    ) -> Expr<'a, 'src> {
+        let ty = |ty| substitute(self.alloc, subst, ty);
+
+        match expr {
+            Expr::Var(_) | Expr::Unit | Expr::Number(_) => *expr,
+            Expr::Fail(t) => Expr::Fail(ty(t)),
+
+            Expr::Field(record, t, label) => {
+                let record = self.instantiate(subst, record);
+                Expr::Field(self.alloc.alloc(record), ty(t), *label)
+            }
+
+            Expr::Record(fields, extend) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(label, field)| (*label, self.instantiate(subst, field)))
+                    .collect();
+                let fields = self.alloc.alloc_slice_fill_iter(fields);
+                let extend =
+                    extend.map(|extend| &*self.alloc.alloc(self.instantiate(subst, extend)));
+                Expr::Record(fields, extend)
+            }
+
+            Expr::Restrict(record, label) => {
+                let record = self.instantiate(subst, record);
+                Expr::Restrict(self.alloc.alloc(record), *label)
+            }
+
+            Expr::Apply([fun, arg]) => {
+                let fun = self.instantiate(subst, fun);
+                let arg = self.instantiate(subst, arg);
+                Expr::Apply(self.alloc.alloc([fun, arg]))
+            }
+
+            Expr::Lambda(name, t, body) => {
+                let body = self.instantiate(subst, body);
+                Expr::Lambda(*name, ty(t), self.alloc.alloc(body))
+            }
+
+            Expr::Primitive(primitive, args) => {
+                let args: Vec<_> = args
+                    .iter()
+                    .map(|arg| self.instantiate(subst, arg))
+                    .collect();
+                Expr::Primitive(*primitive, self.alloc.alloc_slice_fill_iter(args))
+            }
+
+            Expr::TypeApply(expr, args) => {
+                let expr = self.instantiate(subst, expr);
+                let args: Vec<_> = args
+                    .iter()
+                    .map(|arg| match arg {
+                        TypeArg::Type(t) => TypeArg::Type(ty(t)),
+                        TypeArg::Row(row) => {
+                            TypeArg::Row(super::lower::substitute_row(self.alloc, subst, row))
+                        }
+                    })
+                    .collect();
+                Expr::TypeApply(
+                    self.alloc.alloc(expr),
+                    self.alloc.alloc_slice_fill_iter(args),
+                )
+            }
+
+            Expr::TypeLambda(params, body) => {
+                let mut subst = subst.clone();
+                for param in params.iter() {
+                    subst.remove(param);
+                }
+
+                let body = self.instantiate(&subst, body);
+                Expr::TypeLambda(params, self.alloc.alloc(body))
+            }
+
+            Expr::Let(binding, body) => {
+                let value = self.instantiate(subst, &binding.body);
+                let body = self.instantiate(subst, body);
+                let binding = self.alloc.alloc(Binding {
+                    name: binding.name,
+                    ty: ty(binding.ty),
+                    body: value,
+                });
+                Expr::Let(binding, self.alloc.alloc(body))
+            }
+
+            Expr::Case(scrutinee, alts, default) => {
+                let scrutinee = self.instantiate(subst, scrutinee);
+                let alts: Vec<_> = alts
+                    .iter()
+                    .map(|alt| {
+                        let binds: Vec<_> =
+                            alt.binds.iter().map(|(name, t)| (*name, ty(t))).collect();
+                        Alt {
+                            ctor: alt.ctor,
+                            binds: self.alloc.alloc_slice_fill_iter(binds),
+                            body: self.instantiate(subst, &alt.body),
+                        }
+                    })
+                    .collect();
+                let alts = self.alloc.alloc_slice_fill_iter(alts);
+                let default =
+                    default.map(|default| &*self.alloc.alloc(self.instantiate(subst, default)));
+                Expr::Case(self.alloc.alloc(scrutinee), alts, default)
+            }
+        }
+    }
+}
+
+/// The number of nodes in an expression.
+fn size(expr: &Expr) -> usize {
+    match expr {
+        Expr::Var(_) | Expr::Unit | Expr::Number(_) | Expr::Fail(_) => 1,
+
+        Expr::Field(expr, ..)
+        | Expr::Restrict(expr, _)
+        | Expr::Lambda(_, _, expr)
+        | Expr::TypeApply(expr, _)
+        | Expr::TypeLambda(_, expr) => 1 + size(expr),
+
+        Expr::Record(fields, extend) => {
+            1 + fields.iter().map(|(_, field)| size(field)).sum::<usize>() + extend.map_or(0, size)
+        }
+
+        Expr::Apply([fun, arg]) => 1 + size(fun) + size(arg),
+        Expr::Primitive(_, args) => 1 + args.iter().map(size).sum::<usize>(),
+        Expr::Let(binding, body) => 1 + size(&binding.body) + size(body),
+        Expr::Case(scrutinee, alts, default) => {
+            1 + size(scrutinee)
+                + alts.iter().map(|alt| size(&alt.body)).sum::<usize>()
+                + default.map_or(0, size)
+        }
+    }
+}
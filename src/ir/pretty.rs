@@ -0,0 +1,287 @@
+//! Pretty printing of core programs, mostly for debugging the passes producing
+//! and consuming them.
+
+use std::collections::BTreeMap;
+
+use super::{Alt, Binding, Data, Expr, Item, Program, Row, Type, TypeArg};
+use crate::frontend::names::{Name, Names};
+use crate::frontend::tyck::Generic;
+
+const INDENT: &str = "    ";
+
+pub struct Printer<'n, 'src> {
+    names: &'n Names<'src>,
+    generics: BTreeMap<Generic, String>,
+}
+
+impl<'n, 'src> Printer<'n, 'src> {
+    pub fn new(names: &'n Names<'src>) -> Self {
+        Self {
+            names,
+            generics: BTreeMap::new(),
+        }
+    }
+
+    pub fn program(&mut self, program: &Program) -> String {
+        let items: Vec<_> = program.items.iter().map(|item| self.item(item)).collect();
+        items.join("\n\n")
+    }
+
+    pub fn item(&mut self, item: &Item) -> String {
+        match item {
+            Item::Data(data) => self.data(data),
+            Item::Group(bindings) => {
+                let bindings: Vec<_> = bindings
+                    .iter()
+                    .map(|binding| self.binding(binding, ""))
+                    .collect();
+                bindings.join("\n")
+            }
+        }
+    }
+
+    pub fn ty(&mut self, ty: &Type) -> String {
+        match ty {
+            Type::Forall(params, ty) => {
+                let params: Vec<_> = params.iter().map(|param| self.generic(param)).collect();
+                format!("forall {}. {}", params.join(" "), self.ty(ty))
+            }
+
+            ty => self.arrow(ty),
+        }
+    }
+
+    pub fn row(&mut self, row: &Row) -> String {
+        let mut fields = Vec::new();
+        let mut row = row;
+
+        let rest = loop {
+            match row {
+                Row::Empty => break None,
+                Row::Param(param) => break Some(self.generic(param)),
+                Row::Extend(label, ty, rest) => {
+                    fields.push(format!("{} : {}", label.0.name(), self.ty(ty)));
+                    row = rest;
+                }
+            }
+        };
+
+        match (&fields[..], rest) {
+            ([], None) => "{}".into(),
+            ([], Some(rest)) => format!("{{ | {rest} }}"),
+            (fields, None) => format!("{{ {} }}", fields.join(", ")),
+            (fields, Some(rest)) => format!("{{ {} | {rest} }}", fields.join(", ")),
+        }
+    }
+
+    fn data(&mut self, data: &Data) -> String {
+        let mut head = self.name(&data.name);
+        for param in data.params {
+            head.push(' ');
+            head.push_str(&self.generic(param));
+        }
+
+        let ctors: Vec<_> = data
+            .ctors
+            .iter()
+            .map(|ctor| {
+                let mut ctor_str = self.name(&ctor.name);
+                for field in ctor.fields {
+                    ctor_str.push(' ');
+                    ctor_str.push_str(&self.simple(field));
+                }
+                ctor_str
+            })
+            .collect();
+
+        format!("data {head} = {}", ctors.join(" | "))
+    }
+
+    fn binding(&mut self, binding: &Binding, indent: &str) -> String {
+        let name = self.name(&binding.name);
+        let ty = self.ty(binding.ty);
+        let inner = format!("{indent}{INDENT}");
+        let body = self.open(&binding.body, &inner);
+        format!("{name} : {ty} =\n{inner}{body}")
+    }
+
+    /// Print an expression which extends as far to the right as possible.
+    fn open(&mut self, expr: &Expr, indent: &str) -> String {
+        match expr {
+            Expr::Lambda(name, ty, body) => {
+                let name = self.name(name);
+                let ty = self.ty(ty);
+                let body = self.open(body, indent);
+                format!("\\({name} : {ty}) -> {body}")
+            }
+
+            Expr::TypeLambda(params, body) => {
+                let params: Vec<_> = params.iter().map(|param| self.generic(param)).collect();
+                let body = self.open(body, indent);
+                format!("/\\{}. {body}", params.join(" "))
+            }
+
+            Expr::Let(binding, body) => {
+                let name = self.name(&binding.name);
+                let ty = self.ty(binding.ty);
+                let bound = self.open(&binding.body, &format!("{indent}{INDENT}"));
+                let body = self.open(body, indent);
+                format!("let {name} : {ty} = {bound} in\n{indent}{body}")
+            }
+
+            Expr::Case(scrutinee, alts, default) => {
+                let scrutinee = self.open(scrutinee, indent);
+                let inner = format!("{indent}{INDENT}");
+                let mut result = format!("case {scrutinee} of");
+
+                for alt in alts.iter() {
+                    result.push('\n');
+                    result.push_str(&inner);
+                    result.push_str(&self.alt(alt, &inner));
+                }
+
+                if let Some(default) = default {
+                    let deeper = format!("{inner}{INDENT}");
+                    let default = self.open(default, &deeper);
+                    result.push_str(&format!("\n{inner}_ ->\n{deeper}{default}"));
+                }
+
+                result
+            }
+
+            Expr::Restrict(record, label) => {
+                format!("{} \\ {}", self.apply(record, indent), label.0.name())
+            }
+
+            expr => self.apply(expr, indent),
+        }
+    }
+
+    fn alt(&mut self, alt: &Alt, indent: &str) -> String {
+        let mut pattern = self.name(&alt.ctor);
+        for (name, ty) in alt.binds {
+            let name = self.name(name);
+            let ty = self.ty(ty);
+            pattern.push_str(&format!(" ({name} : {ty})"));
+        }
+
+        let inner = format!("{indent}{INDENT}");
+        let body = self.open(&alt.body, &inner);
+        format!("{pattern} ->\n{inner}{body}")
+    }
+
+    fn apply(&mut self, expr: &Expr, indent: &str) -> String {
+        match expr {
+            Expr::Apply([fun, arg]) => {
+                let fun = self.apply(fun, indent);
+                let arg = self.atom(arg, indent);
+                format!("{fun} {arg}")
+            }
+
+            Expr::TypeApply(expr, args) => {
+                let expr = self.apply(expr, indent);
+                let args: Vec<_> = args
+                    .iter()
+                    .map(|arg| match arg {
+                        TypeArg::Type(ty) => self.ty(ty),
+                        TypeArg::Row(row) => self.row(row),
+                    })
+                    .collect();
+                format!("{expr} [{}]", args.join(", "))
+            }
+
+            Expr::Primitive(primitive, args) => {
+                let args: Vec<_> = args.iter().map(|arg| self.atom(arg, indent)).collect();
+                format!("{}# {}", primitive.ident(), args.join(" "))
+            }
+
+            expr => self.atom(expr, indent),
+        }
+    }
+
+    fn atom(&mut self, expr: &Expr, indent: &str) -> String {
+        match expr {
+            Expr::Var(name) => self.name(name),
+            Expr::Unit => "()".into(),
+            Expr::Number(value) => value.to_string(),
+            Expr::Fail(ty) => format!("(fail : {})", self.ty(ty)),
+
+            Expr::Field(record, _, label) => {
+                format!("{}.{}", self.atom(record, indent), label.0.name())
+            }
+
+            Expr::Record(fields, extend) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(label, field)| {
+                        format!("{} = {}", label.0.name(), self.open(field, indent))
+                    })
+                    .collect();
+
+                let extend = extend.map(|extend| self.open(extend, indent));
+                match (&fields[..], extend) {
+                    ([], None) => "{}".into(),
+                    ([], Some(extend)) => format!("{{ | {extend} }}"),
+                    (fields, None) => format!("{{ {} }}", fields.join(", ")),
+                    (fields, Some(extend)) => format!("{{ {} | {extend} }}", fields.join(", ")),
+                }
+            }
+
+            expr => format!("({})", self.open(expr, indent)),
+        }
+    }
+
+    fn arrow(&mut self, ty: &Type) -> String {
+        match ty.as_arrow() {
+            Some((t, u)) => {
+                let t = match t {
+                    Type::Forall(..) => format!("({})", self.ty(t)),
+                    t => self.applied(t),
+                };
+
+                format!("{t} -> {}", self.arrow(u))
+            }
+
+            None => self.applied(ty),
+        }
+    }
+
+    fn applied(&mut self, ty: &Type) -> String {
+        match ty {
+            Type::Apply(t, u) if ty.as_arrow().is_none() => {
+                format!("{} {}", self.applied(t), self.simple(u))
+            }
+
+            ty => self.simple(ty),
+        }
+    }
+
+    fn simple(&mut self, ty: &Type) -> String {
+        match ty {
+            Type::Param(param) => self.generic(param),
+            Type::Named(name) => self.name(name),
+            Type::Unit => "unit".into(),
+            Type::Integer => "int".into(),
+            Type::Arrow => "(->)".into(),
+            Type::Record(row) => self.row(row),
+            ty => format!("({})", self.ty(ty)),
+        }
+    }
+
+    fn generic(&mut self, generic: &Generic) -> String {
+        match generic {
+            Generic::Ticked(name) => self.name(name),
+            Generic::Implicit(_) => {
+                let count = self.generics.len();
+                self.generics
+                    .entry(*generic)
+                    .or_insert_with(|| format!("'{count}"))
+                    .clone()
+            }
+        }
+    }
+
+    fn name(&self, name: &Name) -> String {
+        self.names.get_name(name).name.name().into()
+    }
+}
@@ -0,0 +1,259 @@
+use bumpalo::Bump;
+
+use super::optimize::{optimize, Level};
+use super::pretty::Printer;
+use super::{check, lower, Binding, Expr, Item, Program, Type};
+use crate::bytecode::compile::compile;
+use crate::bytecode::vm;
+use crate::frontend::names::{Names, ScopeName};
+use crate::frontend::testing::elaborated;
+
+/// Lower the given (well typed) source and assert that the resulting core
+/// program type checks.
+fn lowers_well_typed(source: &str) {
+    elaborated(source, false, |alloc, names, _, program| {
+        let core = lower::lower(alloc, names, &program);
+        let errors = check::check(alloc, names, &core);
+        assert!(
+            errors.is_empty(),
+            "{errors:#?}\n\n{}",
+            Printer::new(names).program(&core)
+        );
+    });
+}
+
+/// Optimize the given (well typed) source at every level, asserting that the
+/// optimized programs type check and run like the unoptimized one. Returns the
+/// program optimized at the highest level.
+fn optimizes_faithfully(source: &str) -> String {
+    elaborated(source, false, |alloc, names, _, program| {
+        let core = lower::lower(alloc, names, &program);
+        let run = |program: &Program| {
+            let program = compile(names, program);
+            vm::run(&program).map(|value| value.map(|value| vm::show(&program, &value, false)))
+        };
+
+        let expected = run(&core);
+        let mut printed = String::new();
+
+        for level in [Level::Simplify, Level::Inline] {
+            let optimized = optimize(alloc, names, &core, level);
+            let errors = check::check(alloc, names, &optimized);
+            assert!(
+                errors.is_empty(),
+                "{errors:#?}\n\n{}",
+                Printer::new(names).program(&optimized)
+            );
+
+            assert_eq!(expected, run(&optimized));
+            printed = Printer::new(names).program(&optimized);
+        }
+
+        printed
+    })
+}
+
+#[test]
+fn polymorphic_functions() {
+    lowers_well_typed(
+        "
+        let id x = x
+        let const x _ = x
+        let y = const (id 5) (id id)
+    ",
+    );
+}
+
+#[test]
+fn mutually_recursive_group() {
+    lowers_well_typed(
+        "
+        let f x = g x
+        and g x = f x
+        let y : int = f 5
+    ",
+    );
+}
+
+#[test]
+fn constructor_patterns() {
+    lowers_well_typed(
+        "
+        data List a = Nil | Cons a (List a)
+
+        let map f = Nil => Nil | Cons x xs => Cons (f x) (map f xs)
+        let second = Cons _ (Cons x _) => x | _ => 0
+    ",
+    );
+}
+
+#[test]
+fn or_and_patterns() {
+    lowers_well_typed(
+        "
+        data List a = Nil | Cons a (List a)
+
+        let both = (Cons x Nil | Cons _ (Cons x _)) => x | _ => 0
+        let whole = (Cons x _ & xs) => Cons x xs | Nil => Nil
+    ",
+    );
+}
+
+#[test]
+fn shared_arms() {
+    lowers_well_typed(
+        "
+        data List a = Nil | Cons a (List a)
+
+        let f = (Cons x Nil | Cons _ (Cons x _)) => Cons x Nil | _ => Nil
+        let g = Cons _ Nil => 1 | Cons _ (Cons _ Nil) => 2 | _ => 3
+    ",
+    );
+}
+
+#[test]
+fn record_patterns() {
+    lowers_well_typed(
+        "
+        data Option a = None | Some a
+
+        let get { a, b = Some c } = { a, c }
+        let { p, q } = { p = 1, q = x => x }
+        let r = q p
+    ",
+    );
+}
+
+#[test]
+fn row_polymorphism() {
+    lowers_well_typed(
+        "
+        let get r = r.a
+        let extend r = { c = 5, ... r }
+        let x = get (extend { a = 1, b = 2 })
+    ",
+    );
+}
+
+#[test]
+fn nominal_records() {
+    lowers_well_typed(
+        "
+        data Person = Person { name : int, age : int }
+
+        let person = Person { name = 1, age = 2 }
+        let age = person.age
+    ",
+    );
+}
+
+#[test]
+fn classes_and_instances() {
+    lowers_well_typed(
+        "
+        data List a = Nil | Cons a (List a)
+
+        class Functor f = { map : ('a -> 'b) -> f 'a -> f 'b }
+
+        instance Functor List = {
+            map = f => (Nil => Nil | Cons x xs => Cons (f x) (map f xs))
+        }
+
+        class Show a = { show : a -> int }
+        instance Show int = { show = x => x }
+
+        let twice x = { a = show x, b = show x }
+        let y = map twice (Cons 1 Nil)
+    ",
+    );
+}
+
+#[test]
+fn ill_typed_core_is_reported() {
+    let alloc = Bump::new();
+    let names = Names::new();
+    let x = names.name(ScopeName::Anonymous(0), names.intern("x"));
+
+    let bindings = alloc.alloc([Binding {
+        name: x,
+        ty: alloc.alloc(Type::Integer),
+        body: Expr::Unit,
+    }]);
+
+    let items = alloc.alloc([Item::Group(bindings)]);
+    let errors = check::check(&alloc, &names, &Program { items });
+
+    assert_eq!(1, errors.len());
+    assert!(errors[0].starts_with("in `x`"), "{errors:?}");
+}
+
+#[test]
+fn known_values_are_simplified() {
+    let source = "
+        data Option a = None | Some a
+        let get = Some x => x | None => 0
+        let main = get (Some { a = 5, b = 6 }.b)
+    ";
+
+    let optimized = optimizes_faithfully(source);
+    assert!(optimized.contains("main : int =\n    6"), "{optimized}");
+}
+
+#[test]
+fn optimizing_lists_and_classes() {
+    optimizes_faithfully(
+        "
+        data List a = Nil | Cons a (List a)
+
+        class Functor f = { map : ('a -> 'b) -> f 'a -> f 'b }
+
+        instance Functor List = {
+            map = f => (Nil => Nil | Cons x xs => Cons (f x) (map f xs))
+        }
+
+        let id x = x
+        let compose f g x = f (g x)
+        let append = Nil => ys => ys | Cons x xs => ys => Cons x (append xs ys)
+        let main = map (compose id (x => { x })) (append (Cons 1 Nil) (Cons 2 Nil))
+    ",
+    );
+}
+
+#[test]
+fn optimizing_records() {
+    optimizes_faithfully(
+        "
+        data Person = Person { name : int, age : int }
+        let swap = Person { name, age } => Person { name = age, age = name }
+        let extend r = { a = 5, ... r }
+        let main = {
+            person = swap (Person { name = 1, age = 2 }),
+            extended = extend { a = 1, b = 2 },
+            field = { x = 1, y = 2 }.y,
+        }
+    ",
+    );
+}
+
+#[test]
+fn optimizing_or_patterns() {
+    optimizes_faithfully(
+        "
+        data List a = Nil | Cons a (List a)
+        let second = (Cons x Nil | Cons _ (Cons x _)) => x | _ => 0
+        let main = { one = second (Cons 1 Nil), two = second (Cons 1 (Cons 2 Nil)), none = second Nil }
+    ",
+    );
+}
+
+#[test]
+fn failures_are_preserved() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        let first xs = let Cons x _ = xs in x
+        let main = let x = first Nil in 5
+    ";
+
+    let optimized = optimizes_faithfully(source);
+    assert!(!optimized.contains("main : int =\n    5"), "{optimized}");
+}
@@ -2,7 +2,8 @@ use bumpalo::Bump;
 
 use crate::frontend::names::Names;
 use crate::frontend::parse::parse;
-use crate::frontend::resolve::resolve;
+use crate::frontend::prelude;
+use crate::frontend::resolve::resolve_with_prelude;
 use crate::frontend::source::Source;
 use crate::frontend::trees::inferred;
 use crate::frontend::tyck;
@@ -10,6 +11,8 @@ use crate::frontend::tyck;
 use super::Server;
 
 impl Server {
+    /// Infer the types of a source with the prelude in scope. The prelude is
+    /// only resolved and checked once, and every call starts from there.
     pub fn check_source<'a, 'src>(
         &'src self,
         names: &'a Names<'src>,
@@ -17,7 +20,7 @@ impl Server {
         source: &'src Source,
     ) -> inferred::Program<'a, 'src> {
         let parsed = parse(alloc, names, source);
-        let resolved = resolve(names, alloc, &parsed);
+        let resolved = resolve_with_prelude(names, alloc, prelude::resolved(), &parsed);
         let inferred = tyck::infer(alloc, names, &resolved);
         inferred
     }
@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+
+use lsp_types as lsp;
+
+use bumpalo::Bump;
+
+use super::Server;
+use crate::frontend::names::{Names, ScopeName};
+use crate::frontend::source::{Source, SourceId};
+
+impl Server {
+    /// Complete the top-level names of the source and the prelude, along with
+    /// their documentation. Names in the source shadow those in the prelude.
+    pub fn complete(&self, source: &Source) -> Vec<lsp::CompletionItem> {
+        let alloc = Bump::new();
+        let names = Names::new();
+        let program = self.check_source(&names, &alloc, source);
+
+        let mut items = BTreeMap::new();
+        for name in program.defs.keys() {
+            let qualified = names.get_name(name);
+            let label = qualified.name.name();
+
+            let ScopeName::TopLevel(id) = qualified.parent else {
+                continue;
+            };
+
+            // Instances are named by their keyword, which is never in scope
+            if label == "instance" || (id != source.id && id != SourceId::PRELUDE) {
+                continue;
+            }
+
+            if id == SourceId::PRELUDE && items.contains_key(label) {
+                continue;
+            }
+
+            let documentation = program.docs.get(name).map(|doc| {
+                lsp::Documentation::MarkupContent(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: doc.clone(),
+                })
+            });
+
+            items.insert(
+                label,
+                lsp::CompletionItem {
+                    label: label.into(),
+                    documentation,
+                    ..Default::default()
+                },
+            );
+        }
+
+        items.into_values().collect()
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+
+use lsp_document::{IndexedText, Pos, TextAdapter, TextMap};
+use lsp_types as lsp;
+
+use bumpalo::Bump;
+
+use super::Server;
+use crate::frontend::names::{Name, Names};
+use crate::frontend::source::{Source, Span};
+
+impl Server {
+    /// Find the definition of the name used at the given position, if any.
+    /// Builtin names have no definition.
+    pub fn find_definition(
+        &self,
+        source: &Source,
+        position: lsp::Position,
+    ) -> Option<lsp::Location> {
+        let offset = position_to_offset(&IndexedText::new(source.content.as_str()), position)?;
+
+        let alloc = Bump::new();
+        let names = Names::new();
+        let program = self.check_source(&names, &alloc, source);
+
+        let name = name_at(&program.refs, source, offset)?;
+        let span = *program.defs.get(&name)?;
+        self.span_to_location(span)
+    }
+
+    fn span_to_location(&self, span: Span) -> Option<lsp::Location> {
+        let uri = self.names.get(&span.source)?;
+        let source = self.tracked.get(uri)?;
+        let text = IndexedText::new(source.content.as_str());
+
+        Some(lsp::Location {
+            uri: uri.clone(),
+            range: lsp::Range {
+                start: offset_to_position(&text, span.start)?,
+                end: offset_to_position(&text, span.end)?,
+            },
+        })
+    }
+}
+
+/// Find the innermost use of a name in `source` around the given offset.
+pub(super) fn name_at(refs: &BTreeMap<Span, Name>, source: &Source, offset: usize) -> Option<Name> {
+    refs.iter()
+        .filter(|(span, _)| span.source == source.id && span.start <= offset && offset <= span.end)
+        .min_by_key(|(span, _)| span.length())
+        .map(|(_, name)| *name)
+}
+
+/// Get the byte offset of a position, whose character counts UTF-16 code units.
+/// Positions past the end of their line are clamped to it.
+pub(super) fn position_to_offset(
+    text: &IndexedText<&str>,
+    position: lsp::Position,
+) -> Option<usize> {
+    let line = text.substr(text.line_range(position.line)?)?;
+    let line = line.trim_end_matches(['\n', '\r']);
+
+    let mut character = 0;
+    for c in line.chars() {
+        let next = character + c.len_utf16() as u32;
+        if next > position.character {
+            break;
+        }
+
+        character = next;
+    }
+
+    // `lsp_document` has its own version of `lsp_types`, so start from one of
+    // its positions instead of converting ours.
+    let mut start = text.pos_to_lsp_pos(&Pos::new(position.line, 0))?;
+    start.character = character;
+
+    let pos = text.lsp_pos_to_pos(&start)?;
+    text.substr(Pos::new(0, 0)..pos).map(str::len)
+}
+
+/// Get the position of a byte offset, with its character counting UTF-16 code
+/// units.
+pub(super) fn offset_to_position(text: &IndexedText<&str>, offset: usize) -> Option<lsp::Position> {
+    let pos = text.pos_to_lsp_pos(&text.offset_to_pos(offset)?)?;
+    Some(lsp::Position {
+        line: pos.line,
+        character: pos.character,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_document::IndexedText;
+    use lsp_types as lsp;
+
+    use super::{offset_to_position, position_to_offset};
+
+    const TEXT: &str = "let x = 1\nlet 日本 = {- 😀 -} x\n";
+
+    fn position(line: u32, character: u32) -> lsp::Position {
+        lsp::Position { line, character }
+    }
+
+    #[test]
+    fn characters_count_utf16_code_units() {
+        let text = IndexedText::new(TEXT);
+        let x = TEXT.rfind('x').unwrap();
+
+        assert_eq!(Some(x), position_to_offset(&text, position(1, 18)));
+        assert_eq!(Some(position(1, 18)), offset_to_position(&text, x));
+    }
+
+    #[test]
+    fn positions_are_clamped_to_their_line() {
+        let text = IndexedText::new(TEXT);
+
+        assert_eq!(Some(9), position_to_offset(&text, position(0, 40)));
+        assert_eq!(
+            Some(TEXT.len() - 1),
+            position_to_offset(&text, position(1, 40))
+        );
+        assert_eq!(None, position_to_offset(&text, position(4, 0)));
+    }
+
+    #[test]
+    fn positions_inside_a_character_are_moved_before_it() {
+        let text = IndexedText::new(TEXT);
+        let emoji = TEXT.find('😀').unwrap();
+
+        assert_eq!(Some(emoji), position_to_offset(&text, position(1, 13)));
+    }
+}
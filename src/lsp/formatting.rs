@@ -0,0 +1,94 @@
+use std::ops::Range;
+
+use lsp_document::IndexedText;
+use lsp_types as lsp;
+
+use super::definition::{offset_to_position, position_to_offset};
+use super::Server;
+use crate::frontend::source::Source;
+use crate::syntax::format::{format, Config};
+use crate::syntax::green::{diff, TextEdit};
+use crate::syntax::parse::parse;
+
+impl Server {
+    /// Compute the edits which format the source, keeping only those touching
+    /// `range` if there is one. Sources which cannot be formatted are left
+    /// as they are.
+    pub fn format_source(&self, source: &Source, range: Option<lsp::Range>) -> Vec<lsp::TextEdit> {
+        format_text(&source.content, range)
+    }
+}
+
+fn format_text(text: &str, range: Option<lsp::Range>) -> Vec<lsp::TextEdit> {
+    let Ok(formatted) = format(text, &Config::default()) else {
+        return Vec::new();
+    };
+
+    let index = IndexedText::new(text);
+    let range = match range {
+        Some(range) => {
+            let start = position_to_offset(&index, range.start);
+            let end = position_to_offset(&index, range.end);
+            match (start, end) {
+                (Some(start), Some(end)) => Some(start..end),
+                _ => return Vec::new(),
+            }
+        }
+
+        None => None,
+    };
+
+    diff(&parse(text), &parse(&formatted))
+        .into_iter()
+        .filter(|edit| range.as_ref().is_none_or(|range| touches(range, edit)))
+        .filter_map(|edit| {
+            Some(lsp::TextEdit {
+                range: lsp::Range {
+                    start: offset_to_position(&index, edit.range.start)?,
+                    end: offset_to_position(&index, edit.range.end)?,
+                },
+                new_text: edit.text,
+            })
+        })
+        .collect()
+}
+
+/// Check whether the edit touches the range, including edits right at its
+/// ends.
+fn touches(range: &Range<usize>, edit: &TextEdit) -> bool {
+    range.start <= edit.range.end && edit.range.start <= range.end
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types as lsp;
+
+    use super::format_text;
+
+    fn range(line: u32, start: u32, end: u32) -> lsp::Range {
+        lsp::Range {
+            start: lsp::Position {
+                line,
+                character: start,
+            },
+            end: lsp::Position {
+                line,
+                character: end,
+            },
+        }
+    }
+
+    #[test]
+    fn edits_count_utf16_code_units() {
+        let edits = format_text("let x = {- 日本 -}   1\n", None);
+        let ranges: Vec<_> = edits.into_iter().map(|edit| edit.range).collect();
+        assert_eq!(vec![range(0, 17, 19)], ranges);
+    }
+
+    #[test]
+    fn ranges_count_utf16_code_units() {
+        let text = "let x = {- 日本 -}   1\nlet y =   2\n";
+        assert_eq!(1, format_text(text, Some(range(0, 16, 19))).len());
+        assert_eq!(1, format_text(text, Some(range(1, 0, 11))).len());
+    }
+}
@@ -11,6 +11,7 @@ use lsp_types::notification::{self, Notification as _};
 use lsp_types::request::{self, Request as _};
 
 use super::log::AtomicTraceValue;
+use super::prelude::VirtualDocument;
 use super::{LspError, Server};
 
 /// Initialize and run the given server on standard IO.
@@ -174,6 +175,36 @@ impl Loop {
                 );
             }
 
+            (_, m) if m == request::GotoDefinition::METHOD => {
+                let (id, params) = request.extract(request::GotoDefinition::METHOD)?;
+                let result = self.server.goto_definition(params);
+                self.client.respond(id, result);
+            }
+
+            (_, m) if m == request::HoverRequest::METHOD => {
+                let (id, params) = request.extract(request::HoverRequest::METHOD)?;
+                let result = self.server.hover(params);
+                self.client.respond(id, result);
+            }
+
+            (_, m) if m == request::Completion::METHOD => {
+                let (id, params) = request.extract(request::Completion::METHOD)?;
+                let result = self.server.completion(params);
+                self.client.respond(id, result);
+            }
+
+            (_, m) if m == request::Formatting::METHOD => {
+                let (id, params) = request.extract(request::Formatting::METHOD)?;
+                let result = self.server.formatting(params);
+                self.client.respond(id, result);
+            }
+
+            (_, m) if m == request::RangeFormatting::METHOD => {
+                let (id, params) = request.extract(request::RangeFormatting::METHOD)?;
+                let result = self.server.range_formatting(params);
+                self.client.respond(id, result);
+            }
+
             (_, m) if m == request::InlayHintRequest::METHOD => {
                 let (id, params) = request.extract(request::InlayHintRequest::METHOD)?;
                 let result = self.server.inlay_hints(params);
@@ -186,6 +217,12 @@ impl Loop {
                 self.client.respond(id, result);
             }
 
+            (_, m) if m == VirtualDocument::METHOD => {
+                let (id, params) = request.extract(VirtualDocument::METHOD)?;
+                let result = self.server.virtual_document(params);
+                self.client.respond(id, result);
+            }
+
             (_, m) => {
                 self.client.respond(
                     request.id,
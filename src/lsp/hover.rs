@@ -0,0 +1,40 @@
+use lsp_document::IndexedText;
+use lsp_types as lsp;
+
+use bumpalo::Bump;
+
+use super::definition::{name_at, position_to_offset};
+use super::Server;
+use crate::frontend::names::Names;
+use crate::frontend::source::Source;
+
+impl Server {
+    /// Show the documentation of the name used or defined at the given
+    /// position, if it has any.
+    pub fn find_hover(&self, source: &Source, position: lsp::Position) -> Option<lsp::Hover> {
+        let offset = position_to_offset(&IndexedText::new(source.content.as_str()), position)?;
+
+        let alloc = Bump::new();
+        let names = Names::new();
+        let program = self.check_source(&names, &alloc, source);
+
+        let name = name_at(&program.refs, source, offset).or_else(|| {
+            program
+                .defs
+                .iter()
+                .find(|(_, span)| {
+                    span.source == source.id && span.start <= offset && offset <= span.end
+                })
+                .map(|(name, _)| *name)
+        })?;
+
+        let doc = program.docs.get(&name)?;
+        Some(lsp::Hover {
+            contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                kind: lsp::MarkupKind::Markdown,
+                value: doc.clone(),
+            }),
+            range: None,
+        })
+    }
+}
@@ -1,8 +1,9 @@
-use lsp_document::{IndexedText, TextMap};
+use lsp_document::IndexedText;
 use lsp_types as lsp;
 
 use bumpalo::Bump;
 
+use super::definition::offset_to_position;
 use super::Server;
 use crate::frontend::names::Names;
 use crate::frontend::source::{Source, Span};
@@ -18,7 +19,7 @@ impl Server {
         let mut builder = HintsBuilder::new(&names, source.content.as_str());
 
         for items in program.items {
-            builder.items(items);
+            builder.items(source, items);
         }
 
         builder.hints
@@ -40,14 +41,18 @@ impl<'a, 'src> HintsBuilder<'a, 'src> {
         }
     }
 
-    pub fn items(&mut self, items: &[Item]) {
-        for item in items {
+    /// Add hints for the items defined in the given source.
+    pub fn items(&mut self, source: &Source, items: &[Item]) {
+        for item in items.iter().filter(|item| item.span.source == source.id) {
             match &item.node {
                 ItemNode::Invalid(_) => {}
                 ItemNode::Let(pattern, _, _) => {
                     self.pattern(pattern);
                 }
-                ItemNode::Data(_, _) => {}
+                ItemNode::Data(_, _)
+                | ItemNode::Alias(_, _)
+                | ItemNode::Class(_, _)
+                | ItemNode::Instance(_, _, _) => {}
             }
         }
     }
@@ -72,6 +77,12 @@ impl<'a, 'src> HintsBuilder<'a, 'src> {
                 self.pattern(b);
             }
 
+            PolyPatternNode::Record(fields) => {
+                for (_, _, pattern) in fields.iter() {
+                    self.pattern(pattern);
+                }
+            }
+
             PolyPatternNode::Anno(_, v) => match *v {},
         }
     }
@@ -101,9 +112,6 @@ impl<'a, 'src> HintsBuilder<'a, 'src> {
     }
 
     fn span_to_end_position(&self, span: Span) -> Option<lsp::Position> {
-        self.index.offset_to_pos(span.end).map(|pos| lsp::Position {
-            line: pos.line,
-            character: pos.col,
-        })
+        offset_to_position(&self.index, span.end)
     }
 }
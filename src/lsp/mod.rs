@@ -1,11 +1,16 @@
 pub use self::lsp_error::LspError;
 
 mod check;
+mod completion;
+mod definition;
 mod diagnostics;
+mod formatting;
 mod framework;
+mod hover;
 mod inlay_hints;
 mod log;
 mod lsp_error;
+mod prelude;
 mod sync;
 mod tokens;
 
@@ -51,6 +56,12 @@ impl framework::Builder for Builder {
         });
 
         let capabilities = lsp::ServerCapabilities {
+            completion_provider: Some(lsp::CompletionOptions::default()),
+            definition_provider: Some(lsp::OneOf::Left(true)),
+            document_formatting_provider: Some(lsp::OneOf::Left(true)),
+            document_range_formatting_provider: Some(lsp::OneOf::Left(true)),
+            hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+
             inlay_hint_provider: Some(lsp::OneOf::Right(
                 lsp::InlayHintServerCapabilities::RegistrationOptions(
                     lsp::InlayHintRegistrationOptions {
@@ -93,14 +104,17 @@ struct Server {
 
 impl Server {
     fn new(client: Client) -> Self {
-        Self {
+        let mut server = Self {
             client,
             tracked: HashMap::new(),
             names: HashMap::new(),
             sources: Sources::new(),
 
             errors: HashSet::new(),
-        }
+        };
+
+        server.track_prelude();
+        server
     }
 }
 
@@ -174,6 +188,52 @@ impl Server {
         )))
     }
 
+    /// `textDocument/definition`
+    fn goto_definition(
+        &mut self,
+        params: lsp::GotoDefinitionParams,
+    ) -> Result<Option<lsp::GotoDefinitionResponse>, Error> {
+        let params = params.text_document_position_params;
+        let name = params.text_document.uri;
+
+        let source = self
+            .tracked
+            .get(&name)
+            .ok_or_else(|| Error::InvalidRequest(format!("unknown document `{name}`")))?;
+
+        Ok(self
+            .find_definition(source, params.position)
+            .map(lsp::GotoDefinitionResponse::Scalar))
+    }
+
+    /// `textDocument/hover`
+    fn hover(&mut self, params: lsp::HoverParams) -> Result<Option<lsp::Hover>, Error> {
+        let params = params.text_document_position_params;
+        let name = params.text_document.uri;
+
+        let source = self
+            .tracked
+            .get(&name)
+            .ok_or_else(|| Error::InvalidRequest(format!("unknown document `{name}`")))?;
+
+        Ok(self.find_hover(source, params.position))
+    }
+
+    /// `textDocument/completion`
+    fn completion(
+        &mut self,
+        params: lsp::CompletionParams,
+    ) -> Result<Option<lsp::CompletionResponse>, Error> {
+        let name = params.text_document_position.text_document.uri;
+
+        let source = self
+            .tracked
+            .get(&name)
+            .ok_or_else(|| Error::InvalidRequest(format!("unknown document `{name}`")))?;
+
+        Ok(Some(lsp::CompletionResponse::Array(self.complete(source))))
+    }
+
     /// `textDocument/inlayHints`
     fn inlay_hints(
         &mut self,
@@ -189,6 +249,36 @@ impl Server {
         Ok(Some(self.make_hints(source)))
     }
 
+    /// `textDocument/formatting`
+    fn formatting(
+        &mut self,
+        params: lsp::DocumentFormattingParams,
+    ) -> Result<Option<Vec<lsp::TextEdit>>, Error> {
+        let name = params.text_document.uri;
+
+        let source = self
+            .tracked
+            .get(&name)
+            .ok_or_else(|| Error::InvalidRequest(format!("unknown document `{name}`")))?;
+
+        Ok(Some(self.format_source(source, None)))
+    }
+
+    /// `textDocument/rangeFormatting`
+    fn range_formatting(
+        &mut self,
+        params: lsp::DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<lsp::TextEdit>>, Error> {
+        let name = params.text_document.uri;
+
+        let source = self
+            .tracked
+            .get(&name)
+            .ok_or_else(|| Error::InvalidRequest(format!("unknown document `{name}`")))?;
+
+        Ok(Some(self.format_source(source, Some(params.range))))
+    }
+
     /// `shutdown`
     fn shutdown(&mut self) {}
 }
@@ -0,0 +1,54 @@
+//! The prelude is not a file on disk, so editors show it as a read-only
+//! virtual document with the `nml` scheme, fetched with a custom request.
+
+use lsp_types as lsp;
+use lsp_types::request::Request;
+use lsp_types::Url;
+
+use super::framework::Error;
+use super::Server;
+use crate::frontend::prelude;
+use crate::frontend::source::{Source, SourceId};
+
+/// `nml/virtualDocument`, which gets the content of a virtual document.
+pub enum VirtualDocument {}
+
+impl Request for VirtualDocument {
+    type Params = lsp::TextDocumentIdentifier;
+    type Result = String;
+    const METHOD: &'static str = "nml/virtualDocument";
+}
+
+/// Get the uri of the prelude.
+pub fn uri() -> Url {
+    Url::parse(&format!("nml:{}", prelude::PATH)).expect("the prelude uri is valid")
+}
+
+impl Server {
+    /// Track the prelude like any other document, so its spans can be mapped
+    /// to locations.
+    pub fn track_prelude(&mut self) {
+        let source = Source {
+            id: SourceId::PRELUDE,
+            content: prelude::source().content.clone(),
+        };
+
+        self.names.insert(source.id, uri());
+        self.tracked.insert(uri(), source);
+    }
+
+    /// `nml/virtualDocument`
+    pub fn virtual_document(
+        &mut self,
+        params: lsp::TextDocumentIdentifier,
+    ) -> Result<String, Error> {
+        if params.uri == uri() {
+            Ok(prelude::source().content.clone())
+        } else {
+            Err(Error::InvalidRequest(format!(
+                "unknown virtual document `{}`",
+                params.uri
+            )))
+        }
+    }
+}
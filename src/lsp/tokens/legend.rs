@@ -1,6 +1,6 @@
 use lsp_types::{SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend};
 
-use crate::frontend::parse::Token;
+use crate::syntax::green::Kind;
 
 pub fn get() -> SemanticTokensLegend {
     SemanticTokensLegend {
@@ -9,44 +9,41 @@ pub fn get() -> SemanticTokensLegend {
     }
 }
 
-/// Get the semantic token type and the modifiers for the given token type,
+/// Get the semantic token type and the modifiers for the given kind of token,
 /// if it is to be highlighted.
-pub fn for_token(token: Result<Token, ()>) -> Option<(u32, u32)> {
-    let Ok(token) = token else {
-        return None;
-    };
+pub fn for_token(kind: Kind) -> Option<(u32, u32)> {
+    match kind {
+        Kind::Comment | Kind::BlockComment => Some((types::COMMENT, mods::NONE)),
+        Kind::DocComment => Some((types::COMMENT, mods::DOCUMENTATION)),
+        Kind::PreTick => Some((types::TYPE_PARAMETER, mods::NONE)),
 
-    match token {
-        Token::Comment(_) => Some((types::COMMENT, mods::NONE)),
-        Token::Name(_) | Token::Symbol(_) => None,
-        Token::Universal(_) => Some((types::TYPE_PARAMETER, mods::NONE)),
+        Kind::Number => Some((types::NUMBER, mods::NONE)),
 
-        Token::Number(_) => Some((types::NUMBER, mods::NONE)),
+        Kind::And
+        | Kind::Case
+        | Kind::Class
+        | Kind::Data
+        | Kind::End
+        | Kind::In
+        | Kind::Infix
+        | Kind::Instance
+        | Kind::Infixl
+        | Kind::Infixr
+        | Kind::Let
+        | Kind::Postfix
+        | Kind::Type => Some((types::KEYWORD, mods::NONE)),
 
-        Token::And
-        | Token::Case
-        | Token::Data
-        | Token::End
-        | Token::In
-        | Token::Infix
-        | Token::Let
-        | Token::Postfix => Some((types::KEYWORD, mods::NONE)),
+        Kind::Ampersand
+        | Kind::Comma
+        | Kind::Dot
+        | Kind::Ellipses
+        | Kind::Colon
+        | Kind::Equal
+        | Kind::EqualArrow
+        | Kind::Pipe
+        | Kind::Underscore => Some((types::OPERATOR, mods::NONE)),
 
-        Token::Ampersand
-        | Token::Comma
-        | Token::Dot
-        | Token::Ellipses
-        | Token::Colon
-        | Token::Equal
-        | Token::EqualArrow
-        | Token::Pipe
-        | Token::Underscore => Some((types::OPERATOR, mods::NONE)),
-
-        Token::LeftParen
-        | Token::RightParen
-        | Token::LeftBrace
-        | Token::RightBrace
-        | Token::Whitespace(_) => None,
+        _ => None,
     }
 }
 
@@ -72,5 +69,5 @@ mod types {
 
 mod mods {
     pub const NONE: u32 = 0;
-    //pub const DOCUMENTATION: u32 = 1;
+    pub const DOCUMENTATION: u32 = 1;
 }
@@ -2,8 +2,9 @@ pub mod legend;
 
 use lsp_types::{SemanticToken, SemanticTokens};
 
-use crate::frontend::parse::{tokens, Token};
 use crate::frontend::source::{Source, Span};
+use crate::syntax::green::Kind;
+use crate::syntax::parse::tokenize;
 
 use super::Server;
 
@@ -11,8 +12,11 @@ impl Server {
     pub fn compute_tokens(&self, source: &Source) -> SemanticTokens {
         let mut builder = SemanticTokensBuilder::new(&source.content);
 
-        for (token, span) in tokens(source) {
-            builder.add_token(token, span);
+        let mut start = 0;
+        for token in tokenize(&source.content) {
+            let end = start + token.width();
+            builder.add_token(token.kind(), source.id.span(start, end));
+            start = end;
         }
 
         builder.build()
@@ -54,12 +58,23 @@ impl<'s> SemanticTokensBuilder<'s> {
         }
     }
 
-    /// Add a token to this list of semantic tokens.
-    pub fn add_token(&mut self, token: Result<Token, ()>, span: Span) {
-        if let Some((ty, modifiers)) = legend::for_token(token) {
-            let relative = self.translate_span(span);
-            self.previous = Some(span.start);
-            self.push_token(relative, ty, modifiers);
+    /// Add a token to this list of semantic tokens. Tokens spanning multiple
+    /// lines, like block comments, are added as one token per line.
+    pub fn add_token(&mut self, kind: Kind, span: Span) {
+        let Some((ty, modifiers)) = legend::for_token(kind) else {
+            return;
+        };
+
+        let mut start = span.start;
+        for line in self.source[span.start..span.end].split_inclusive('\n') {
+            let end = start + line.trim_end_matches(['\n', '\r']).len();
+            if start < end {
+                let relative = self.translate_span(span.source.span(start, end));
+                self.previous = Some(start);
+                self.push_token(relative, ty, modifiers);
+            }
+
+            start += line.len();
         }
     }
 
@@ -1,21 +1,31 @@
 #![feature(if_let_guard, lint_reasons)]
 
 mod args;
+mod backend;
 mod batch;
+mod bytecode;
+mod doc;
 mod frontend;
+mod ir;
 mod lsp;
+mod matching;
 mod meta;
 mod modules;
 mod syntax;
 
 use std::process::ExitCode;
 
-use self::args::{Args, Check, Command, Lsp};
+use argh::{EarlyExit, FromArgs};
+
+use self::args::{Args, Build, Check, Command, Doc, Dump, Fmt, LogLevel, Lsp, Run};
 use self::batch::BatchError;
+use self::bytecode::file::DecodeError;
+use self::bytecode::vm;
 use self::lsp::LspError;
+use self::syntax::format;
 
 fn main() -> ExitCode {
-    let args: Args = argh::from_env();
+    let args = parse_args();
 
     match args.command {
         Command::Lsp(Lsp { log, stdio: true }) => {
@@ -25,17 +35,120 @@ fn main() -> ExitCode {
 
         Command::Lsp(_) => lsp_error(Err(LspError::NoChannel)),
 
-        Command::Check(Check { path, log }) => {
-            if let Some(log) = log {
-                if let Some(level) = log.to_level_filter().to_level() {
-                    simple_logger::init_with_level(level).expect("this is the only logger");
-                }
-            } else if std::env::var("RUST_LOG").is_ok() {
-                simple_logger::init_with_env().expect("this is the only logger");
+        Command::Build(Build {
+            path,
+            log,
+            no_prelude,
+            target,
+            output,
+            export,
+            opt_level,
+        }) => {
+            init_logger(log);
+            let output = output.unwrap_or_else(|| path.with_extension(target.extension()));
+            batch_error(batch::build(
+                &path,
+                !no_prelude,
+                target,
+                opt_level,
+                &output,
+                &export,
+            ))
+        }
+
+        Command::Check(Check {
+            path,
+            log,
+            no_prelude,
+            verify_core,
+        }) => {
+            init_logger(log);
+            batch_error(batch::run(&path, !no_prelude, verify_core))
+        }
+
+        Command::Doc(Doc {
+            path,
+            log,
+            no_prelude,
+            markdown,
+            output,
+        }) => {
+            init_logger(log);
+            let output = output.unwrap_or_else(|| "doc".into());
+            batch_error(batch::doc(&path, !no_prelude, markdown, &output))
+        }
+
+        Command::Dump(Dump {
+            path,
+            log,
+            no_prelude,
+            stage,
+            decision_trees,
+            bytecode,
+        }) => {
+            init_logger(log);
+            batch_error(batch::dump(
+                &path,
+                !no_prelude,
+                stage,
+                decision_trees,
+                bytecode,
+            ))
+        }
+
+        Command::Fmt(Fmt { paths, check }) => batch_error(batch::format(&paths, check)),
+
+        Command::Run(Run {
+            path,
+            log,
+            no_prelude,
+            opt_level,
+            differential,
+        }) => {
+            init_logger(log);
+            batch_error(batch::execute(&path, !no_prelude, opt_level, differential))
+        }
+    }
+}
+
+/// Parse the command line arguments like [`argh::from_env`], but also accept
+/// optimization levels written directly after the flag, as in `-O2`.
+fn parse_args() -> Args {
+    let mut args = std::env::args();
+    let command = args.next().unwrap_or_else(|| "nmlc".into());
+
+    let args: Vec<String> = args
+        .flat_map(|arg| match arg.strip_prefix("-O") {
+            Some(level) if !level.is_empty() => vec!["-O".into(), level.into()],
+            _ => vec![arg],
+        })
+        .collect();
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match Args::from_args(&[&command], &args) {
+        Ok(args) => args,
+        Err(EarlyExit { output, status }) => match status {
+            Ok(()) => {
+                println!("{output}");
+                std::process::exit(0);
             }
 
-            batch_error(batch::run(&path))
+            Err(()) => {
+                eprintln!("{output}\nRun {command} --help for more information.");
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+fn init_logger(log: Option<LogLevel>) {
+    if let Some(log) = log {
+        if let Some(level) = log.to_level_filter().to_level() {
+            simple_logger::init_with_level(level).expect("this is the only logger");
         }
+    } else if std::env::var("RUST_LOG").is_ok() {
+        simple_logger::init_with_env().expect("this is the only logger");
     }
 }
 
@@ -47,6 +160,66 @@ fn batch_error(result: Result<(), BatchError>) -> ExitCode {
             eprintln!("io error: {err}");
         }
 
+        Err(BatchError::IllTypedCore(errors)) => {
+            for error in errors.iter() {
+                eprintln!("core error: {error}");
+            }
+
+            let es = if errors.len() != 1 { "s" } else { "" };
+            eprintln!(
+                "internal compiler error: found {} core error{es}",
+                errors.len()
+            );
+        }
+
+        Err(BatchError::UnknownExport(name)) => {
+            eprintln!("cannot export `{name}`, since it is not a top-level binding");
+        }
+
+        Err(BatchError::InvalidBytecode(DecodeError::NotBytecode)) => {
+            eprintln!("not a bytecode file");
+        }
+
+        Err(BatchError::InvalidBytecode(DecodeError::UnsupportedVersion(version))) => {
+            eprintln!("unsupported bytecode version {version}");
+        }
+
+        Err(BatchError::InvalidBytecode(DecodeError::Malformed)) => {
+            eprintln!("malformed bytecode file");
+        }
+
+        Err(BatchError::RuntimeError(vm::Error::PatternMatchFailure)) => {
+            eprintln!("pattern match failure");
+        }
+
+        Err(BatchError::RuntimeError(vm::Error::Malformed)) => {
+            eprintln!("malformed bytecode");
+        }
+
+        Err(BatchError::OptimizationMismatch { expected, actual }) => {
+            eprintln!("internal compiler error: the optimized program produced {actual}, but the unoptimized program produced {expected}");
+        }
+
+        Err(BatchError::Unformatted(paths)) => {
+            for path in paths.iter() {
+                eprintln!("{} is not formatted", path.display());
+            }
+        }
+
+        Err(BatchError::CannotFormat(path, format::Error::Unrecognized)) => {
+            eprintln!(
+                "cannot format {}, since it contains unrecognized input",
+                path.display()
+            );
+        }
+
+        Err(BatchError::CannotFormat(path, format::Error::ChangedTokens)) => {
+            eprintln!(
+                "internal compiler error: formatting {} would change its tokens",
+                path.display()
+            );
+        }
+
         Err(BatchError::CompilerError {
             num_errors,
             num_warnings,
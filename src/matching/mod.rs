@@ -0,0 +1,489 @@
+//! Pattern matching compilation turns the arms of a lambda into a decision
+//! tree, which tests the constructor at each position of the scrutinee at most
+//! once along any path through it.
+//!
+//! The arms are compiled as a matrix of rows, where each row holds the tests
+//! left to do for one arm. Names, wildcards, records and the like never fail,
+//! so they are simplified away until only constructor tests remain. The tree
+//! then switches on the position tested first by the first row, specializing
+//! the rows for each constructor tested there, until the first row has nothing
+//! left to test and its arm is chosen. Or-patterns are expanded into one row
+//! per alternative, so the same arm may be chosen in several leaves of the
+//! tree.
+//!
+//! Since this works on the typed tree, the trees are shared by every consumer
+//! of it, such as the lowering to the core language.
+
+pub mod pretty;
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+
+use bumpalo::Bump;
+
+use crate::frontend::names::{Label, Name};
+use crate::frontend::source::Span;
+use crate::frontend::trees::{inferred, typed};
+use crate::frontend::tyck::Type;
+
+/// The constructors of every data type in a program.
+pub struct Constructors {
+    /// The index into `types` of the data type of each constructor.
+    siblings: BTreeMap<Name, usize>,
+    types: Vec<Vec<Name>>,
+}
+
+/// A position within the scrutinee of a match.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Occurrence(usize);
+
+/// How an occurrence is reached from the scrutinee.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Access<'a> {
+    /// The scrutinee itself.
+    Scrutinee,
+
+    /// The given argument of a constructor at another occurrence.
+    Argument(Occurrence, Name, usize),
+
+    /// A field of the record at another occurrence.
+    Field(Occurrence, Label<'a>),
+}
+
+/// A compiled match.
+pub struct Match<'a> {
+    /// How each occurrence is reached, along with its type.
+    pub occurrences: Vec<(Access<'a>, &'a Type<'a>)>,
+    pub tree: Decision<'a>,
+}
+
+pub enum Decision<'a> {
+    /// None of the arms match.
+    Fail,
+
+    /// The given arm matches, binding each name to the value at an occurrence.
+    Success(usize, &'a [(Name, Occurrence)]),
+
+    /// Test the constructor of the value at an occurrence, falling back to the
+    /// default if none of the cases match. There is no default if the cases
+    /// cover every constructor.
+    Switch(Occurrence, &'a [Case<'a>], Option<&'a Decision<'a>>),
+}
+
+pub struct Case<'a> {
+    pub ctor: Name,
+
+    /// The occurrences of the arguments of the constructor.
+    pub args: &'a [Occurrence],
+    pub tree: Decision<'a>,
+}
+
+impl Constructors {
+    pub fn new(program: &typed::Program) -> Self {
+        let mut siblings = BTreeMap::new();
+        let mut types = Vec::new();
+
+        for item in program.items.iter().flat_map(|items| items.iter()) {
+            let typed::ItemNode::Data(_, data) = &item.node else {
+                continue;
+            };
+
+            let inferred::DataNode::Sum(ctors) = &data.node else {
+                continue;
+            };
+
+            let ctors: Vec<_> = ctors
+                .iter()
+                .filter_map(|ctor| match &ctor.node {
+                    inferred::ConstructorNode::Constructor(name, _) => Some(*name),
+                    inferred::ConstructorNode::Invalid(_) => None,
+                })
+                .collect();
+
+            for ctor in ctors.iter() {
+                siblings.insert(*ctor, types.len());
+            }
+
+            types.push(ctors);
+        }
+
+        Self { siblings, types }
+    }
+
+    /// Whether the given constructors include every constructor of their
+    /// type.
+    fn complete(&self, ctors: &[Name]) -> bool {
+        let Some(index) = ctors.first().and_then(|ctor| self.siblings.get(ctor)) else {
+            return false;
+        };
+
+        self.types[*index].iter().all(|ctor| ctors.contains(ctor))
+    }
+}
+
+impl Occurrence {
+    /// The occurrence of the scrutinee itself.
+    pub const SCRUTINEE: Occurrence = Occurrence(0);
+}
+
+impl<'a> Match<'a> {
+    pub fn access(&self, occurrence: Occurrence) -> Access<'a> {
+        self.occurrences[occurrence.0].0
+    }
+
+    pub fn ty(&self, occurrence: Occurrence) -> &'a Type<'a> {
+        self.occurrences[occurrence.0].1
+    }
+
+    /// The number of leaves choosing each arm.
+    pub fn uses(&self, arms: usize) -> Vec<usize> {
+        fn go(uses: &mut [usize], tree: &Decision) {
+            match tree {
+                Decision::Fail => {}
+                Decision::Success(arm, _) => uses[*arm] += 1,
+                Decision::Switch(_, cases, default) => {
+                    for case in cases.iter() {
+                        go(uses, &case.tree);
+                    }
+
+                    if let Some(default) = default {
+                        go(uses, default);
+                    }
+                }
+            }
+        }
+
+        let mut uses = vec![0; arms];
+        go(&mut uses, &self.tree);
+        uses
+    }
+}
+
+/// Compile a match of a scrutinee of type `ty` against the given patterns,
+/// where the first matching pattern is chosen.
+pub fn compile<'a, 'p>(
+    alloc: &'a Bump,
+    ctors: &Constructors,
+    ty: &'a Type<'a>,
+    patterns: impl IntoIterator<Item = &'p typed::Pattern<'a>>,
+) -> Match<'a>
+where
+    'a: 'p,
+{
+    let mut compiler = Compiler {
+        alloc,
+        ctors,
+        occurrences: vec![(Access::Scrutinee, ty)],
+        accesses: BTreeMap::from([(Access::Scrutinee, Occurrence::SCRUTINEE)]),
+    };
+
+    let mut rows = Vec::new();
+    for (arm, pattern) in patterns.into_iter().enumerate() {
+        let row = Row {
+            tests: vec![(Occurrence::SCRUTINEE, pattern)],
+            binds: Vec::new(),
+            arm,
+        };
+
+        compiler.simplify(row, &mut rows);
+    }
+
+    let tree = compiler.compile(rows);
+    Match {
+        occurrences: compiler.occurrences,
+        tree,
+    }
+}
+
+/// Compile the arms of every lambda and the pattern of every destructuring
+/// `let` in a program, along with the span of the lambda or `let`.
+pub fn program<'a>(
+    alloc: &'a Bump,
+    ctors: &Constructors,
+    program: &typed::Program<'a, '_>,
+) -> Vec<(Span, Match<'a>)> {
+    let mut finder = Finder {
+        alloc,
+        ctors,
+        matches: Vec::new(),
+    };
+
+    for item in program.items.iter().flat_map(|items| items.iter()) {
+        match &item.node {
+            typed::ItemNode::Let(pattern, expr, _) => {
+                finder.destructure(item.span, pattern);
+                finder.expr(expr);
+            }
+
+            typed::ItemNode::Instance(_, expr, _) => finder.expr(expr),
+
+            typed::ItemNode::Invalid(_)
+            | typed::ItemNode::Data(..)
+            | typed::ItemNode::Alias(..)
+            | typed::ItemNode::Class(..) => {}
+        }
+    }
+
+    finder.matches
+}
+
+/// A row of the matrix, holding the tests left to do before its arm is chosen.
+#[derive(Clone)]
+struct Row<'p, 'a> {
+    tests: Vec<(Occurrence, &'p typed::Pattern<'a>)>,
+    binds: Vec<(Name, Occurrence)>,
+    arm: usize,
+}
+
+struct Compiler<'a, 'c> {
+    alloc: &'a Bump,
+    ctors: &'c Constructors,
+    occurrences: Vec<(Access<'a>, &'a Type<'a>)>,
+    accesses: BTreeMap<Access<'a>, Occurrence>,
+}
+
+impl<'a> Compiler<'a, '_> {
+    fn compile(&mut self, rows: Vec<Row<'_, 'a>>) -> Decision<'a> {
+        let Some(first) = rows.first() else {
+            return Decision::Fail;
+        };
+
+        let Some((occurrence, _)) = first.tests.first() else {
+            let binds = self.alloc.alloc_slice_copy(&first.binds);
+            return Decision::Success(first.arm, binds);
+        };
+
+        let occurrence = *occurrence;
+
+        // The constructors tested at this occurrence, in the order they are
+        // first met, along with the arguments they are first met with.
+        let mut tested: Vec<(Name, Vec<&typed::Pattern<'a>>)> = Vec::new();
+        for row in rows.iter() {
+            if let Some((ctor, args)) = Self::test(row, occurrence) {
+                if !tested.iter().any(|(other, _)| *other == ctor) {
+                    tested.push((ctor, args));
+                }
+            }
+        }
+
+        let mut cases = Vec::with_capacity(tested.len());
+        for (ctor, args) in tested.iter() {
+            let args: Vec<_> = args
+                .iter()
+                .enumerate()
+                .map(|(index, arg)| {
+                    let access = Access::Argument(occurrence, *ctor, index);
+                    self.occurrence(access, arg.scheme.ty)
+                })
+                .collect();
+
+            let mut specialized = Vec::new();
+            for row in rows.iter() {
+                let Some(index) = row.tests.iter().position(|(at, _)| *at == occurrence) else {
+                    specialized.push(row.clone());
+                    continue;
+                };
+
+                let (other, patterns) = constructor_spine(row.tests[index].1)
+                    .expect("only constructor tests are left after simplifying");
+                if other != *ctor {
+                    continue;
+                }
+
+                let mut row = row.clone();
+                row.tests
+                    .splice(index..=index, args.iter().copied().zip(patterns));
+                self.simplify(row, &mut specialized);
+            }
+
+            cases.push(Case {
+                ctor: *ctor,
+                args: self.alloc.alloc_slice_copy(&args),
+                tree: self.compile(specialized),
+            });
+        }
+
+        let ctors: Vec<_> = tested.iter().map(|(ctor, _)| *ctor).collect();
+        let default = if self.ctors.complete(&ctors) {
+            None
+        } else {
+            let rest = rows
+                .into_iter()
+                .filter(|row| Self::test(row, occurrence).is_none())
+                .collect();
+
+            Some(&*self.alloc.alloc(self.compile(rest)))
+        };
+
+        Decision::Switch(occurrence, self.alloc.alloc_slice_fill_iter(cases), default)
+    }
+
+    /// Simplify every test of a row which cannot fail, pushing the resulting
+    /// row (or rows, for or-patterns) to `rows`.
+    fn simplify<'p>(&mut self, mut row: Row<'p, 'a>, rows: &mut Vec<Row<'p, 'a>>) {
+        let Some(index) = row
+            .tests
+            .iter()
+            .position(|(_, pattern)| constructor_spine(pattern).is_none())
+        else {
+            rows.push(row);
+            return;
+        };
+
+        let (occurrence, pattern) = row.tests.remove(index);
+        match &pattern.node {
+            typed::PatternNode::Invalid(_)
+            | typed::PatternNode::Wildcard
+            | typed::PatternNode::Unit
+            | typed::PatternNode::Constructor(_)
+            | typed::PatternNode::Apply(_) => {}
+
+            typed::PatternNode::Bind(name) => row.binds.push((*name, occurrence)),
+            typed::PatternNode::Anno(_, v) => match *v {},
+            typed::PatternNode::Group(pattern) => row.tests.insert(index, (occurrence, pattern)),
+
+            typed::PatternNode::And([a, b]) => {
+                row.tests
+                    .splice(index..index, [(occurrence, a), (occurrence, b)]);
+            }
+
+            typed::PatternNode::Record(fields) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .filter_map(|(label, _, field)| {
+                        let access = Access::Field(occurrence, (*label).ok()?);
+                        Some((self.occurrence(access, field.scheme.ty), field))
+                    })
+                    .collect();
+
+                row.tests.splice(index..index, fields);
+            }
+
+            typed::PatternNode::Or([a, b]) => {
+                let mut other = row.clone();
+                row.tests.insert(index, (occurrence, a));
+                other.tests.insert(index, (occurrence, b));
+                self.simplify(row, rows);
+                self.simplify(other, rows);
+                return;
+            }
+        }
+
+        self.simplify(row, rows);
+    }
+
+    /// Get the constructor a row tests for at an occurrence, if any, along with
+    /// its argument patterns.
+    fn test<'p>(
+        row: &Row<'p, 'a>,
+        occurrence: Occurrence,
+    ) -> Option<(Name, Vec<&'p typed::Pattern<'a>>)> {
+        let (_, pattern) = row.tests.iter().find(|(at, _)| *at == occurrence)?;
+        constructor_spine(pattern)
+    }
+
+    fn occurrence(&mut self, access: Access<'a>, ty: &'a Type<'a>) -> Occurrence {
+        *self.accesses.entry(access).or_insert_with(|| {
+            self.occurrences.push((access, ty));
+            Occurrence(self.occurrences.len() - 1)
+        })
+    }
+}
+
+/// Finds and compiles every match in a program.
+struct Finder<'a, 'c> {
+    alloc: &'a Bump,
+    ctors: &'c Constructors,
+    matches: Vec<(Span, Match<'a>)>,
+}
+
+impl<'a> Finder<'a, '_> {
+    fn expr(&mut self, expr: &typed::Expr<'a, '_>) {
+        match &expr.node {
+            typed::ExprNode::Invalid(_)
+            | typed::ExprNode::Var(_)
+            | typed::ExprNode::Hole
+            | typed::ExprNode::Unit
+            | typed::ExprNode::Number(_) => {}
+
+            typed::ExprNode::Anno(_, v) => match *v {},
+
+            typed::ExprNode::Group(expr)
+            | typed::ExprNode::Field(expr, ..)
+            | typed::ExprNode::Restrict(expr, _) => self.expr(expr),
+
+            typed::ExprNode::Record(fields, extend) => {
+                for (_, _, field) in fields.iter() {
+                    self.expr(field);
+                }
+
+                if let Some(extend) = extend {
+                    self.expr(extend);
+                }
+            }
+
+            typed::ExprNode::Apply([fun, arg]) => {
+                self.expr(fun);
+                self.expr(arg);
+            }
+
+            typed::ExprNode::Lambda(arrows) => {
+                let trivial = matches!(arrows, [(pattern, _)] if is_name(pattern));
+                if !trivial {
+                    if let Type::Apply(arrow, _) = expr.ty.expand() {
+                        if let Type::Apply(_, ty) = arrow.expand() {
+                            let patterns = arrows.iter().map(|(pattern, _)| pattern);
+                            let tree = compile(self.alloc, self.ctors, ty, patterns);
+                            self.matches.push((expr.span, tree));
+                        }
+                    }
+                }
+
+                for (_, body) in arrows.iter() {
+                    self.expr(body);
+                }
+            }
+
+            typed::ExprNode::Let(pattern, [bound, body], _) => {
+                self.destructure(expr.span, pattern);
+                self.expr(bound);
+                self.expr(body);
+            }
+        }
+    }
+
+    fn destructure(&mut self, span: Span, pattern: &typed::Pattern<'a>) {
+        if !is_name(pattern) {
+            let tree = compile(self.alloc, self.ctors, pattern.scheme.ty, [pattern]);
+            self.matches.push((span, tree));
+        }
+    }
+}
+
+/// Whether the pattern binds just a single name and nothing else.
+fn is_name(pattern: &typed::Pattern) -> bool {
+    match &pattern.node {
+        typed::PatternNode::Bind(_) => true,
+        typed::PatternNode::Group(pattern) => is_name(pattern),
+        _ => false,
+    }
+}
+
+/// Split a constructor pattern into the constructor and its arguments.
+fn constructor_spine<'p, 'a>(
+    pattern: &'p typed::Pattern<'a>,
+) -> Option<(Name, Vec<&'p typed::Pattern<'a>>)> {
+    match &pattern.node {
+        typed::PatternNode::Constructor(name) => Some((*name, Vec::new())),
+        typed::PatternNode::Group(pattern) => constructor_spine(pattern),
+        typed::PatternNode::Apply([fun, arg]) => {
+            let (ctor, mut args) = constructor_spine(fun)?;
+            args.push(arg);
+            Some((ctor, args))
+        }
+
+        _ => None,
+    }
+}
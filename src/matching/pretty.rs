@@ -0,0 +1,83 @@
+//! Pretty printing of decision trees, for inspecting the code generated for
+//! pattern matches.
+
+use super::{Access, Decision, Match, Occurrence};
+use crate::frontend::names::{Name, Names};
+
+const INDENT: &str = "    ";
+
+pub struct Printer<'n, 'src> {
+    names: &'n Names<'src>,
+}
+
+impl<'n, 'src> Printer<'n, 'src> {
+    pub fn new(names: &'n Names<'src>) -> Self {
+        Self { names }
+    }
+
+    /// Print the decision tree of a match. The scrutinee is written `$`, the
+    /// `n`th argument of a constructor at `o` is written `o.n`, and the field
+    /// `a` of a record at `o` is written `o.a`.
+    pub fn tree(&self, tree: &Match) -> String {
+        let mut result = String::new();
+        self.decision(&mut result, tree, &tree.tree, "");
+        result
+    }
+
+    fn decision(&self, result: &mut String, tree: &Match, decision: &Decision, indent: &str) {
+        match decision {
+            Decision::Fail => result.push_str("fail"),
+
+            Decision::Success(arm, binds) => {
+                result.push_str(&format!("arm {arm}"));
+
+                let binds: Vec<_> = binds
+                    .iter()
+                    .map(|(name, at)| {
+                        format!("{} = {}", self.name(name), self.occurrence(tree, at))
+                    })
+                    .collect();
+
+                if !binds.is_empty() {
+                    result.push_str(&format!(" with {}", binds.join(", ")));
+                }
+            }
+
+            Decision::Switch(at, cases, default) => {
+                let inner = format!("{indent}{INDENT}");
+                let deeper = format!("{inner}{INDENT}");
+
+                result.push_str(&format!("case {} of", self.occurrence(tree, at)));
+
+                for case in cases.iter() {
+                    result.push_str(&format!("\n{inner}{}", self.name(&case.ctor)));
+                    for arg in case.args.iter() {
+                        result.push_str(&format!(" {}", self.occurrence(tree, arg)));
+                    }
+
+                    result.push_str(&format!(" ->\n{deeper}"));
+                    self.decision(result, tree, &case.tree, &deeper);
+                }
+
+                if let Some(default) = default {
+                    result.push_str(&format!("\n{inner}_ ->\n{deeper}"));
+                    self.decision(result, tree, default, &deeper);
+                }
+            }
+        }
+    }
+
+    fn occurrence(&self, tree: &Match, occurrence: &Occurrence) -> String {
+        match tree.access(*occurrence) {
+            Access::Scrutinee => "$".into(),
+            Access::Argument(of, _, index) => format!("{}.{index}", self.occurrence(tree, &of)),
+            Access::Field(of, label) => {
+                format!("{}.{}", self.occurrence(tree, &of), label.0.name())
+            }
+        }
+    }
+
+    fn name(&self, name: &Name) -> String {
+        self.names.get_name(name).name.name().into()
+    }
+}
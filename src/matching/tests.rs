@@ -0,0 +1,162 @@
+use std::collections::BTreeSet;
+
+use bumpalo::Bump;
+
+use super::pretty::Printer;
+use super::{Constructors, Decision, Occurrence};
+use crate::frontend::names::Names;
+use crate::frontend::parse::parse;
+use crate::frontend::resolve::resolve;
+use crate::frontend::source::Sources;
+use crate::frontend::tyck::check;
+
+/// Compile every match in the given (well typed) source, checking that no
+/// occurrence is tested twice along any path, and print the decision trees.
+fn trees(source: &str) -> Vec<String> {
+    let sources = Sources::new();
+    let source = sources.add(source);
+    let alloc = Bump::new();
+    let names = Names::new();
+
+    let parsed = parse(&alloc, &names, &source);
+    let resolved = resolve(&names, &alloc, &parsed);
+    let program = check(&alloc, &names, &resolved);
+    assert_eq!(0, program.errors.num_errors());
+
+    let ctors = Constructors::new(&program);
+    let printer = Printer::new(&names);
+
+    super::program(&alloc, &ctors, &program)
+        .into_iter()
+        .map(|(_, tree)| {
+            assert_tested_once(&tree.tree, &mut BTreeSet::new());
+            printer.tree(&tree)
+        })
+        .collect()
+}
+
+fn assert_tested_once(decision: &Decision, tested: &mut BTreeSet<Occurrence>) {
+    let Decision::Switch(at, cases, default) = decision else {
+        return;
+    };
+
+    assert!(tested.insert(*at), "{at:?} is tested twice");
+
+    for case in cases.iter() {
+        assert_tested_once(&case.tree, tested);
+    }
+
+    if let Some(default) = default {
+        assert_tested_once(default, tested);
+    }
+
+    tested.remove(at);
+}
+
+#[test]
+fn exhaustive_switch_has_no_default() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        let f = Nil => 0 | Cons x _ => x
+    ";
+
+    let expected = "\
+case $ of
+    Nil ->
+        arm 0
+    Cons $.0 $.1 ->
+        arm 1 with x = $.0";
+
+    assert_eq!(vec![expected], trees(source));
+}
+
+#[test]
+fn nested_patterns_test_each_position_once() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        let f = Cons _ Nil => 1 | Cons _ (Cons _ Nil) => 2 | _ => 3
+    ";
+
+    let expected = "\
+case $ of
+    Cons $.0 $.1 ->
+        case $.1 of
+            Nil ->
+                arm 0
+            Cons $.1.0 $.1.1 ->
+                case $.1.1 of
+                    Nil ->
+                        arm 1
+                    _ ->
+                        arm 2
+    _ ->
+        arm 2";
+
+    assert_eq!(vec![expected], trees(source));
+}
+
+#[test]
+fn or_patterns_choose_the_same_arm() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        let f = (Cons x Nil | Cons _ (Cons x _)) => x | _ => 0
+    ";
+
+    let expected = "\
+case $ of
+    Cons $.0 $.1 ->
+        case $.1 of
+            Nil ->
+                arm 0 with x = $.0
+            Cons $.1.0 $.1.1 ->
+                arm 0 with x = $.1.0
+    _ ->
+        arm 1";
+
+    assert_eq!(vec![expected], trees(source));
+}
+
+#[test]
+fn and_patterns_bind_the_same_occurrence() {
+    let source = "
+        data List a = Nil | Cons a (List a)
+        let f = (Cons x _ & xs) => Cons x xs | Nil => Nil
+    ";
+
+    let expected = "\
+case $ of
+    Cons $.0 $.1 ->
+        arm 0 with xs = $, x = $.0
+    Nil ->
+        arm 1";
+
+    assert_eq!(vec![expected], trees(source));
+}
+
+#[test]
+fn record_fields_are_not_tested() {
+    let source = "
+        data Option a = None | Some a
+        let f = { a = Some x, b = _ } => x | { a = None, b } => b
+    ";
+
+    let expected = "\
+case $.a of
+    Some $.a.0 ->
+        arm 0 with x = $.a.0
+    None ->
+        arm 1 with b = $.b";
+
+    assert_eq!(vec![expected], trees(source));
+}
+
+#[test]
+fn destructuring_lets() {
+    let source = "
+        let { p, q } = { p = 1, q = 2 }
+        let f x = let { a } = x in a
+    ";
+
+    let expected = vec!["arm 0 with p = $.p, q = $.q", "arm 0 with a = $.a"];
+    assert_eq!(expected, trees(source));
+}
@@ -0,0 +1,77 @@
+//! Typed views over the syntax tree. Each view wraps a [`Cursor`] pointing at
+//! a node of the matching kind, and has accessors for what can be read from
+//! that node alone.
+//!
+//! Lowering to the abstract syntax tree reads the parts of nodes straight from
+//! the cursors, so only those which carry something beyond their parts, such
+//! as the documentation of a definition, have a view.
+
+#[cfg(test)]
+mod tests;
+
+use super::green::Kind;
+use super::red::Cursor;
+
+/// A typed view of some element in the syntax tree.
+pub trait AstNode: Sized {
+    /// View `cursor` as this type, if it is of the right kind.
+    fn cast(cursor: Cursor) -> Option<Self>;
+
+    /// Get the element this is a view of.
+    fn syntax(&self) -> &Cursor;
+}
+
+/// `pattern = thing`
+#[derive(Clone, Debug)]
+pub struct Definition(Cursor);
+
+impl AstNode for Definition {
+    fn cast(cursor: Cursor) -> Option<Self> {
+        (cursor.kind() == Kind::Definition).then_some(Self(cursor))
+    }
+
+    fn syntax(&self) -> &Cursor {
+        &self.0
+    }
+}
+
+impl Definition {
+    /// The text of the documentation comments right before the keyword or
+    /// `and` introducing this definition, or right before it as a field of a
+    /// record, one line per comment.
+    pub fn doc(&self) -> Option<String> {
+        let intro = self.syntax().prev_sibling()?;
+        let parent = self.syntax().parent()?;
+        let end = match intro.kind() {
+            Kind::And | Kind::Class | Kind::Data | Kind::Instance | Kind::Let | Kind::Type => {
+                intro.offset()
+            }
+
+            Kind::LeftBrace | Kind::Comma if parent.kind() == Kind::BraceGroup => {
+                self.syntax().offset()
+            }
+
+            _ => return None,
+        };
+
+        let before: Vec<_> = parent
+            .children()
+            .take_while(|child| child.offset() < end)
+            .collect();
+
+        let lines: Vec<_> = before
+            .iter()
+            .rev()
+            .take_while(|child| matches!(child.kind(), Kind::Whitespace | Kind::DocComment))
+            .filter_map(|child| child.text()?.strip_prefix("--|"))
+            .map(|line| line.strip_prefix(' ').unwrap_or(line).trim_end())
+            .collect();
+
+        if lines.is_empty() {
+            return None;
+        }
+
+        let lines: Vec<_> = lines.into_iter().rev().collect();
+        Some(lines.join("\n"))
+    }
+}
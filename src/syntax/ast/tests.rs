@@ -0,0 +1,56 @@
+use super::{AstNode, Definition};
+use crate::syntax::parse::parse;
+use crate::syntax::red::Cursor;
+
+/// Get the views of every definition in `source`, in order.
+fn definitions(source: &str) -> Vec<Definition> {
+    fn walk(cursor: Cursor, into: &mut Vec<Definition>) {
+        into.extend(Definition::cast(cursor.clone()));
+        for child in cursor.children() {
+            walk(child, into);
+        }
+    }
+
+    let mut definitions = Vec::new();
+    walk(Cursor::root(parse(source)), &mut definitions);
+    definitions
+}
+
+#[test]
+fn documentation() {
+    let source = "--| The answer.\n--|\n--|  Indented.\nlet x = 42\n-- Not documentation\nand y = 5\n--| Why.\nand z = 6";
+    let docs: Vec<_> = definitions(source).iter().map(Definition::doc).collect();
+    assert_eq!(
+        vec![
+            Some("The answer.\n\n Indented.".into()),
+            None,
+            Some("Why.".into())
+        ],
+        docs
+    );
+}
+
+#[test]
+fn documentation_of_items() {
+    let source = "--| A pair.\ndata pair a b = a, b\n\n--| The first.\nlet fst (a, _) = a";
+    let docs: Vec<_> = definitions(source).iter().map(Definition::doc).collect();
+    assert_eq!(
+        vec![Some("A pair.".into()), Some("The first.".into())],
+        docs
+    );
+}
+
+#[test]
+fn documentation_of_fields() {
+    let source = "--| Equality.\nclass Eq a = {\n  --| Whether two things are equal.\n  eq : a -> a -> Bool,\n  ne : a -> a -> Bool,\n  --| Unrelated.\n\n  --| Whether two things differ.\n  --|  Or not.\n  differ : a -> a -> Bool }";
+    let docs: Vec<_> = definitions(source).iter().map(Definition::doc).collect();
+    assert_eq!(
+        vec![
+            Some("Equality.".into()),
+            Some("Whether two things are equal.".into()),
+            None,
+            Some("Unrelated.\nWhether two things differ.\n Or not.".into())
+        ],
+        docs
+    );
+}
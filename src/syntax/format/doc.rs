@@ -0,0 +1,244 @@
+//! A small Wadler-style pretty printing document. A [`Doc`] describes every
+//! layout the formatted code may have, and the printer picks the one where
+//! as many groups as possible are laid out on a single line without going
+//! past the maximum width.
+
+/// A document to be laid out.
+#[derive(Clone, Debug)]
+pub enum Doc {
+    Nil,
+    Text(String),
+
+    /// A single space, which is dropped at the start of a line or after
+    /// another space.
+    Space,
+
+    /// A space if the enclosing group is flat, a line break otherwise.
+    Line,
+
+    /// Always a line break, forcing the enclosing groups to break.
+    HardLine,
+
+    /// An empty line, forcing the enclosing groups to break.
+    BlankLine,
+
+    /// A comment on a line of its own, forcing the enclosing groups to break.
+    /// If `blank` is true, it is preceded by an empty line.
+    OwnLineComment {
+        text: String,
+        blank: bool,
+    },
+
+    /// A comment at the end of the line containing the preceding text, which
+    /// forces the enclosing groups to break.
+    TrailingComment(String),
+
+    /// A comment at the start of a line, followed by more text on that line.
+    /// Like [`Doc::OwnLineComment`], it forces the enclosing groups to break.
+    LeadingComment {
+        text: String,
+        blank: bool,
+    },
+
+    /// Increase the indentation of the line breaks within the document.
+    Nest(usize, Box<Doc>),
+
+    /// Lay out the document on a single line if it fits.
+    Group(Box<Doc>),
+
+    Concat(Vec<Doc>),
+}
+
+impl Doc {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text(text.into())
+    }
+
+    pub fn nest(indent: usize, doc: Doc) -> Self {
+        Self::Nest(indent, Box::new(doc))
+    }
+
+    pub fn group(doc: Doc) -> Self {
+        Self::Group(Box::new(doc))
+    }
+
+    pub fn concat(docs: impl IntoIterator<Item = Doc>) -> Self {
+        Self::Concat(docs.into_iter().collect())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// Lay out a document such that lines are at most `width` characters wide
+/// where possible.
+pub fn print(doc: &Doc, width: usize) -> String {
+    let mut printer = Printer {
+        width,
+        output: String::new(),
+        column: 0,
+        pending: None,
+    };
+
+    printer.print(doc);
+    printer.finish()
+}
+
+struct Printer {
+    width: usize,
+    output: String,
+    column: usize,
+
+    /// The number of line breaks to write before the next text, and the
+    /// indentation of the line after them.
+    pending: Option<(usize, usize)>,
+}
+
+impl Printer {
+    fn print(&mut self, doc: &Doc) {
+        let mut stack = vec![(0, Mode::Break, doc)];
+
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Nil => {}
+                Doc::Text(text) => self.write(text),
+
+                Doc::Space => self.space(),
+
+                Doc::Line if mode == Mode::Flat => self.space(),
+                Doc::Line | Doc::HardLine => self.newlines(1, indent),
+                Doc::BlankLine => self.newlines(2, indent),
+
+                Doc::OwnLineComment { text, blank } => {
+                    self.newlines(if *blank { 2 } else { 1 }, indent);
+                    self.write(text);
+                    self.newlines(1, indent);
+                }
+
+                Doc::LeadingComment { text, blank } => {
+                    self.newlines(if *blank { 2 } else { 1 }, indent);
+                    self.write(text);
+                    self.space();
+                }
+
+                Doc::TrailingComment(text) => {
+                    self.trim_line();
+                    self.output.push(' ');
+                    self.output.push_str(text);
+                    self.newlines(1, indent);
+                }
+
+                Doc::Nest(more, doc) => stack.push((indent + more, mode, doc)),
+
+                Doc::Group(doc) => {
+                    // Any pending line break comes before the group
+                    let column = self.pending.map_or(self.column, |(_, indent)| indent);
+                    let rest = self.width.saturating_sub(column);
+                    let mode = if fits(rest, (indent, Mode::Flat, doc), &stack) {
+                        Mode::Flat
+                    } else {
+                        Mode::Break
+                    };
+
+                    stack.push((indent, mode, doc));
+                }
+
+                Doc::Concat(docs) => {
+                    stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.trim_line();
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+
+        self.output
+    }
+
+    fn write(&mut self, text: &str) {
+        if let Some((count, indent)) = self.pending.take() {
+            self.trim_line();
+
+            if !self.output.is_empty() {
+                self.output.extend(std::iter::repeat_n('\n', count));
+                self.output.extend(std::iter::repeat_n(' ', indent));
+                self.column = indent;
+            }
+        }
+
+        self.output.push_str(text);
+        self.column += text.chars().count();
+    }
+
+    fn space(&mut self) {
+        if self.pending.is_none() && !self.output.ends_with(' ') {
+            self.write(" ");
+        }
+    }
+
+    /// Request `count` line breaks before the next text. Line breaks at the
+    /// very start of the output are dropped.
+    fn newlines(&mut self, count: usize, indent: usize) {
+        let count = self
+            .pending
+            .map_or(count, |(pending, _)| pending.max(count));
+        self.pending = Some((count, indent));
+    }
+
+    /// Remove trailing whitespace from the current line.
+    fn trim_line(&mut self) {
+        let trimmed = self.output.trim_end_matches(' ').len();
+        self.output.truncate(trimmed);
+    }
+}
+
+/// Check whether the rest of the line fits within `width` characters if
+/// `next` is laid out flat. The line ends at the first line break in the rest
+/// of the document, while forced line breaks within `next` mean it does not
+/// fit at all.
+fn fits(width: usize, next: (usize, Mode, &Doc), rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut width = width as isize;
+    let mut stack = vec![(next, false)];
+    let mut rest = rest.iter().rev();
+
+    loop {
+        let Some(((indent, mode, doc), in_rest)) = stack
+            .pop()
+            .or_else(|| rest.next().map(|&next| (next, true)))
+        else {
+            return true;
+        };
+
+        match doc {
+            Doc::Nil => {}
+            Doc::Text(text) => width -= text.chars().count() as isize,
+            Doc::Space => width -= 1,
+
+            Doc::Line if mode == Mode::Flat => width -= 1,
+            Doc::Line => return true,
+
+            Doc::HardLine
+            | Doc::BlankLine
+            | Doc::OwnLineComment { .. }
+            | Doc::LeadingComment { .. }
+            | Doc::TrailingComment(_) => return in_rest,
+
+            Doc::Nest(more, doc) => stack.push(((indent + more, mode, doc), in_rest)),
+            Doc::Group(doc) => stack.push(((indent, Mode::Flat, doc), in_rest)),
+            Doc::Concat(docs) => {
+                stack.extend(docs.iter().rev().map(|doc| ((indent, mode, doc), in_rest)))
+            }
+        }
+
+        if width < 0 {
+            return false;
+        }
+    }
+}
@@ -0,0 +1,605 @@
+//! The formatter lays out the lossless syntax tree in a canonical way. Since
+//! whitespace carries no meaning, it only ever changes the whitespace between
+//! tokens, and keeps every comment in its place relative to the tokens
+//! around it.
+
+mod doc;
+
+#[cfg(test)]
+mod tests;
+
+use smol_str::SmolStr;
+
+use self::doc::Doc;
+use super::green::{Kind, Node};
+use super::parse::{parse, tokenize};
+
+/// How to lay out formatted code.
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// The number of columns to try to fit each line within.
+    pub width: usize,
+
+    /// The number of spaces to indent nested lines by.
+    pub indent: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            width: 80,
+            indent: 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The source contains input which is not a token, and which could not
+    /// be laid out without changing it.
+    Unrecognized,
+
+    /// The formatted code has different tokens than the source, which is a
+    /// bug in the formatter.
+    ChangedTokens,
+}
+
+/// Format the given source code.
+pub fn format(source: &str, config: &Config) -> Result<String, Error> {
+    let tree = parse(source);
+    if tokens(&tree).any(|token| token.kind() == Kind::Invalid) {
+        return Err(Error::Unrecognized);
+    }
+
+    let doc = Formatter::new(config).source(&tree);
+    let formatted = doc::print(&doc, config.width);
+
+    if significant(&tree) != significant(&parse(&formatted)) {
+        return Err(Error::ChangedTokens);
+    }
+
+    Ok(formatted)
+}
+
+/// How to separate two parts of a layout.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Sep {
+    /// No space at all, as long as the tokens on either side stay apart.
+    Glue,
+    Space,
+    Line,
+}
+
+/// A significant child of a node, along with the comments preceding it.
+#[derive(Clone)]
+struct Child {
+    kind: Kind,
+    node: Node,
+    doc: Doc,
+    before: Vec<Doc>,
+
+    /// The formatted children of a disjoined child, which may be laid out as
+    /// a part of the parent instead.
+    parts: Vec<Child>,
+
+    /// The number of line breaks directly before the child.
+    newlines: usize,
+}
+
+struct Formatter {
+    indent: usize,
+
+    /// Whether no significant token has been seen yet.
+    at_start: bool,
+
+    /// The number of line breaks since the last token or comment.
+    newlines: usize,
+}
+
+impl Formatter {
+    fn new(config: &Config) -> Self {
+        Self {
+            indent: config.indent,
+            at_start: true,
+            newlines: 0,
+        }
+    }
+
+    /// Every item goes on its own line, and a single empty line between
+    /// items is kept.
+    fn source(&mut self, node: &Node) -> Doc {
+        let (children, rest) = self.children(node);
+        let mut docs = Vec::new();
+
+        for child in children {
+            docs.push(Doc::HardLine);
+            docs.extend(child.before);
+
+            if child.newlines >= 2 {
+                docs.push(Doc::BlankLine);
+            }
+
+            docs.push(child.doc);
+        }
+
+        docs.extend(rest);
+        Doc::concat(docs)
+    }
+
+    fn node(&mut self, node: &Node) -> Doc {
+        match node.kind() {
+            Kind::Source => self.source(node),
+
+            Kind::DefinitionGroup => self.layout(node, 0, None, |prev, next| match (prev, next) {
+                (Kind::And, _) => Sep::Space,
+                (_, Kind::And) => Sep::Line,
+                _ => Sep::Space,
+            }),
+
+            Kind::Definition => self.layout(node, self.indent, None, |prev, _| match prev {
+                Kind::Equal => Sep::Line,
+                _ => Sep::Space,
+            }),
+
+            Kind::Scoped => self.layout(node, 0, None, |prev, _| match prev {
+                Kind::In => Sep::Line,
+                _ => Sep::Space,
+            }),
+
+            Kind::Disjoined => self.layout(node, 0, None, arms),
+
+            Kind::Implied => self.implied(node),
+
+            Kind::Conjoined => {
+                self.layout(node, self.indent, None, |prev, next| match (prev, next) {
+                    (Kind::Ampersand, _) => Sep::Space,
+                    _ => Sep::Line,
+                })
+            }
+
+            Kind::Annotate => self.layout(node, self.indent, None, |prev, _| match prev {
+                Kind::Colon => Sep::Line,
+                _ => Sep::Space,
+            }),
+
+            Kind::Apply => self.apply(node),
+
+            Kind::Qualified => self.layout(node, 0, None, |prev, next| match (prev, next) {
+                (Kind::Dot, _) | (_, Kind::Dot) => Sep::Glue,
+                _ => Sep::Space,
+            }),
+
+            Kind::ParenGroup => self.layout(
+                node,
+                self.indent,
+                Some(Kind::RightParen),
+                |prev, next| match (prev, next) {
+                    (Kind::LeftParen, _) | (_, Kind::RightParen) => Sep::Glue,
+                    _ => Sep::Space,
+                },
+            ),
+
+            Kind::BraceGroup => self.layout(
+                node,
+                self.indent,
+                Some(Kind::RightBrace),
+                |prev, next| match (prev, next) {
+                    (_, Kind::Comma) => Sep::Glue,
+                    (Kind::LeftBrace, Kind::RightBrace) => Sep::Glue,
+                    _ => Sep::Line,
+                },
+            ),
+
+            Kind::CaseGroup => self.case(node),
+
+            Kind::Error => self.layout(node, self.indent, None, |_, _| Sep::Space),
+
+            _ => self.token(node),
+        }
+    }
+
+    /// Lay out a case with its arms on separate lines, as in
+    ///
+    /// ```nml
+    /// case x
+    ///   | A => a
+    ///   | B => b
+    /// end
+    /// ```
+    fn case(&mut self, node: &Node) -> Doc {
+        let (children, rest) = self.children(node);
+        debug_assert!(rest.is_empty());
+
+        let middle = children
+            .iter()
+            .filter(|child| !matches!(child.kind, Kind::Case | Kind::End | Kind::Error))
+            .count();
+
+        let mut head = Vec::new();
+        let mut tail = Vec::new();
+        let mut closing = Vec::new();
+        let mut prev = None;
+
+        for child in children {
+            let is_arms = match child.kind {
+                Kind::Case | Kind::End | Kind::Error => false,
+                _ if middle == 2 => prev.is_some_and(|prev| prev != Kind::Case),
+                _ => starts_with(&child.node, Kind::Pipe),
+            };
+
+            let (sep, docs) = match child.kind {
+                Kind::Case if prev.is_none() => (Doc::Nil, &mut head),
+                Kind::End => (Doc::Line, &mut closing),
+                _ if is_arms && child.kind == Kind::Disjoined => {
+                    // The arms are laid out as a part of the case itself, so
+                    // that they break along with it. Comments before them
+                    // stay on the line of the case.
+                    if !child.before.is_empty() {
+                        tail.push(Doc::Space);
+                    }
+
+                    tail.extend(child.before);
+                    let mut prev_arm = None;
+                    for arm in child.parts {
+                        let sep = match prev_arm {
+                            Some(Kind::Pipe) => Sep::Space,
+                            _ => Sep::Line,
+                        };
+
+                        tail.push(self.sep(sep, None, &arm));
+                        tail.extend(arm.before);
+                        tail.push(arm.doc);
+                        prev_arm = Some(arm.kind);
+                    }
+
+                    prev = Some(child.kind);
+                    continue;
+                }
+
+                // Skipped tokens are indented along with the arms, so that
+                // they are never moved to the start of a line
+                Kind::Error => (Doc::Line, &mut tail),
+                _ if is_arms => (Doc::Line, &mut tail),
+                _ if tail.is_empty() => (Doc::Space, &mut head),
+                _ => (Doc::Line, &mut tail),
+            };
+
+            docs.push(sep);
+            docs.extend(child.before);
+            docs.push(child.doc);
+            prev = Some(child.kind);
+        }
+
+        Doc::group(Doc::concat([
+            Doc::concat(head),
+            Doc::nest(self.indent, Doc::concat(tail)),
+            Doc::concat(closing),
+        ]))
+    }
+
+    /// Lay out the patterns of a lambda on the first line, and its body on
+    /// the next line if it does not fit, as in
+    ///
+    /// ```nml
+    /// a => b => c =>
+    ///   body
+    /// ```
+    fn implied(&mut self, node: &Node) -> Doc {
+        let (children, rest) = self.children(node);
+        debug_assert!(rest.is_empty());
+
+        let last = children
+            .iter()
+            .rposition(|child| child.kind == Kind::EqualArrow);
+
+        let mut head = Vec::new();
+        let mut tail = Vec::new();
+        for (index, child) in children.into_iter().enumerate() {
+            let docs = match last {
+                Some(last) if index > last => {
+                    tail.push(Doc::Line);
+                    &mut tail
+                }
+
+                _ => {
+                    if index > 0 {
+                        head.push(Doc::Space);
+                    }
+
+                    &mut head
+                }
+            };
+
+            docs.extend(child.before);
+            docs.push(child.doc);
+        }
+
+        Doc::group(Doc::concat([
+            Doc::concat(head),
+            Doc::nest(self.indent, Doc::concat(tail)),
+        ]))
+    }
+
+    /// Lay out an application with the operands between infix operators
+    /// grouped together, breaking before the operators first, as in
+    ///
+    /// ```nml
+    /// f x y
+    ///   + g z
+    ///   * h
+    /// ```
+    fn apply(&mut self, node: &Node) -> Doc {
+        let (children, rest) = self.children(node);
+        debug_assert!(rest.is_empty());
+
+        let mut operands = vec![(None, Vec::new())];
+        for child in children {
+            if is_operator(&child.node) {
+                operands.push((Some(child), Vec::new()));
+            } else if let Some((_, run)) = operands.last_mut() {
+                run.push(child);
+            }
+        }
+
+        // A leading operator starts the first line
+        if operands.len() > 1 && operands[0].1.is_empty() {
+            operands.remove(0);
+        }
+
+        let mut head = Vec::new();
+        let mut tail = Vec::new();
+        for (index, (operator, run)) in operands.into_iter().enumerate() {
+            let docs = if index == 0 { &mut head } else { &mut tail };
+            if index > 0 {
+                docs.push(Doc::Line);
+            }
+
+            if let Some(operator) = operator {
+                docs.extend(operator.before);
+                docs.push(operator.doc);
+
+                if !run.is_empty() {
+                    docs.push(Doc::Space);
+                }
+            }
+
+            if !run.is_empty() {
+                docs.push(self.arrange(run, self.indent, None, |_, _| Sep::Line));
+            }
+        }
+
+        Doc::group(Doc::concat([
+            Doc::concat(head),
+            Doc::nest(self.indent, Doc::concat(tail)),
+        ]))
+    }
+
+    fn layout(
+        &mut self,
+        node: &Node,
+        indent: usize,
+        closing: Option<Kind>,
+        sep: impl Fn(Kind, Kind) -> Sep,
+    ) -> Doc {
+        let (children, rest) = self.children(node);
+        debug_assert!(rest.is_empty());
+        self.arrange(children, indent, closing, sep)
+    }
+
+    /// Lay out `children` as a group, where the first child is not indented,
+    /// the rest are indented by `indent`, and a last child of the `closing`
+    /// kind is not indented. The separator between two children is picked by
+    /// `sep` from their kinds.
+    fn arrange(
+        &self,
+        children: Vec<Child>,
+        indent: usize,
+        closing: Option<Kind>,
+        sep: impl Fn(Kind, Kind) -> Sep,
+    ) -> Doc {
+        let mut head = Vec::new();
+        let mut tail = Vec::new();
+        let mut last = Vec::new();
+        let mut prev: Option<Node> = None;
+
+        let count = children.len();
+        for (index, child) in children.into_iter().enumerate() {
+            let docs = if index == 0 {
+                &mut head
+            } else if index + 1 == count && Some(child.kind) == closing {
+                &mut last
+            } else {
+                &mut tail
+            };
+
+            if let Some(prev) = &prev {
+                docs.push(self.sep(sep(prev.kind(), child.kind), Some(prev), &child));
+            }
+
+            docs.extend(child.before);
+            docs.push(child.doc);
+            prev = Some(child.node);
+        }
+
+        Doc::group(Doc::concat([
+            Doc::concat(head),
+            Doc::nest(indent, Doc::concat(tail)),
+            Doc::concat(last),
+        ]))
+    }
+
+    /// Make the separator between `prev` and `next`. Tokens are only glued
+    /// together if they would still be lexed as the same tokens.
+    fn sep(&self, sep: Sep, prev: Option<&Node>, next: &Child) -> Doc {
+        match sep {
+            Sep::Glue if prev.is_some_and(|prev| glues(prev, &next.node)) => Doc::Nil,
+            Sep::Glue | Sep::Space => Doc::Space,
+            Sep::Line => Doc::Line,
+        }
+    }
+
+    /// Format the significant children of `node`, along with the comments
+    /// before each of them. Trivia at the end of a child is moved out of it,
+    /// so that comments are laid out at the outermost level possible. Any
+    /// trivia after the last child is returned separately.
+    fn children(&mut self, node: &Node) -> (Vec<Child>, Vec<Doc>) {
+        let mut children = Vec::new();
+        let mut before = Vec::new();
+        let mut pending: Vec<Node> = Vec::new();
+
+        for child in node.children() {
+            if child.kind() == Kind::Missing {
+                continue;
+            }
+
+            if child.kind().is_skipped() {
+                pending.push(child.clone());
+                continue;
+            }
+
+            before.extend(self.trivia(&std::mem::take(&mut pending), Some(child)));
+
+            let (inner, trailing) = child.split_trailing_trivia();
+            let newlines = self.newlines;
+
+            let (doc, parts) = if inner.kind() == Kind::Disjoined {
+                let (parts, rest) = self.children(&inner);
+                debug_assert!(rest.is_empty());
+                (self.arrange(parts.clone(), 0, None, arms), parts)
+            } else {
+                (self.node(&inner), Vec::new())
+            };
+
+            children.push(Child {
+                kind: inner.kind(),
+                node: inner,
+                doc,
+                before: std::mem::take(&mut before),
+                parts,
+                newlines,
+            });
+
+            pending = trailing;
+        }
+
+        before.extend(self.trivia(&pending, None));
+        (children, before)
+    }
+
+    /// Format the comments in a run of trivia before `next`. A block comment
+    /// followed by more code on its line stays in front of that code, and
+    /// within a line it flows with the code around it, unless it spans
+    /// several lines. If there is no code after the run within its node, a
+    /// comment ending the run is taken to end its line.
+    fn trivia(&mut self, run: &[Node], next: Option<&Node>) -> Vec<Doc> {
+        let mut docs = Vec::new();
+
+        for (index, node) in run.iter().enumerate() {
+            let text = node.to_string();
+
+            if !matches!(
+                node.kind(),
+                Kind::Comment | Kind::DocComment | Kind::BlockComment
+            ) {
+                self.newlines += text.matches('\n').count();
+                continue;
+            }
+
+            let text = text.trim_end().to_string();
+            let own_line = self.at_start || self.newlines > 0;
+            let blank = self.newlines >= 2;
+            self.newlines = 0;
+
+            let ends_line = match run[index + 1..]
+                .iter()
+                .find(|node| !is_blank(node) && node.kind() != Kind::Missing)
+            {
+                Some(after) => after.to_string().contains('\n'),
+                None => next.is_none_or(|next| tokens(next).next().is_none()),
+            };
+
+            let inline = node.kind() == Kind::BlockComment && !ends_line && !text.contains('\n');
+
+            docs.push(match (own_line, inline) {
+                (true, true) => Doc::LeadingComment { text, blank },
+                (true, false) => Doc::OwnLineComment { text, blank },
+                (false, true) => Doc::concat([Doc::text(text), Doc::Space]),
+                (false, false) => Doc::TrailingComment(text),
+            });
+        }
+
+        docs
+    }
+
+    fn token(&mut self, node: &Node) -> Doc {
+        self.at_start = false;
+        self.newlines = 0;
+        Doc::text(node.to_string())
+    }
+}
+
+/// The separators between the arms of a lambda, which are lined up when they
+/// do not fit on a single line.
+fn arms(prev: Kind, next: Kind) -> Sep {
+    match (prev, next) {
+        (Kind::Pipe, _) => Sep::Space,
+        (_, Kind::Pipe) => Sep::Line,
+        _ => Sep::Space,
+    }
+}
+
+/// Check whether the last token of `prev` and the first token of `next` are
+/// still the same tokens when written without a space between them.
+fn glues(prev: &Node, next: &Node) -> bool {
+    let (Some(left), Some(right)) = (tokens(prev).last(), tokens(next).next()) else {
+        return false;
+    };
+
+    let joined = format!("{left}{right}");
+    let lexed: Vec<_> = tokenize(&joined).map(|token| token.kind()).collect();
+    lexed == [left.kind(), right.kind()]
+}
+
+/// Check whether a node is whitespace within a line.
+fn is_blank(node: &Node) -> bool {
+    node.kind() == Kind::Whitespace && !node.to_string().contains('\n')
+}
+
+/// Check whether a node is an infix operator, which are names made of
+/// symbols.
+fn is_operator(node: &Node) -> bool {
+    node.kind() == Kind::Name
+        && !node
+            .to_string()
+            .starts_with(|c: char| c.is_alphabetic() || c == '_')
+}
+
+fn starts_with(node: &Node, kind: Kind) -> bool {
+    tokens(node)
+        .next()
+        .is_some_and(|token| token.kind() == kind)
+}
+
+/// Iterate over every token of a tree, in order.
+fn tokens(node: &Node) -> impl DoubleEndedIterator<Item = &Node> {
+    let mut tokens = Vec::new();
+    let mut stack = vec![node];
+
+    while let Some(node) = stack.pop() {
+        if node.is_token() {
+            tokens.push(node);
+        } else {
+            stack.extend(node.children().iter().rev());
+        }
+    }
+
+    tokens.into_iter()
+}
+
+/// The tokens and comments of a tree, which formatting must not change.
+fn significant(node: &Node) -> Vec<(Kind, SmolStr)> {
+    tokens(node)
+        .filter(|token| token.kind() != Kind::Whitespace)
+        .map(|token| (token.kind(), token.to_string().trim_end().into()))
+        .collect()
+}
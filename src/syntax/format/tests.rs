@@ -0,0 +1,183 @@
+use proptest::{prop_assert_eq, proptest};
+
+use super::{format, Config, Error};
+
+fn fmt(source: &str) -> String {
+    format(source, &Config::default()).unwrap()
+}
+
+fn narrow(source: &str) -> String {
+    let config = Config {
+        width: 20,
+        ..Default::default()
+    };
+
+    format(source, &config).unwrap()
+}
+
+#[test]
+fn items_on_separate_lines() {
+    assert_eq!("let x = 1\nlet y = 2\n", fmt("let  x=1 let y =\n 2"));
+}
+
+#[test]
+fn blank_lines_between_items() {
+    assert_eq!(
+        "let x = 1\n\nlet y = 2\n",
+        fmt("let x = 1\n\n\n\nlet y = 2")
+    );
+}
+
+#[test]
+fn comments_are_kept() {
+    let source =
+        "-- header\n\n-- about x\nlet x = 1 -- one\n\n-- Booleans\n\ndata Bool = False | True\n";
+    assert_eq!(source, fmt(source));
+}
+
+#[test]
+fn doc_and_block_comments_are_kept() {
+    let source = "{- header\n   {- nested -}\n-}\n\n--| The answer.\n--| Really.\nlet x = 42\n--| Not 42.\nand y = 1 {- one -}\n";
+    assert_eq!(source, fmt(source));
+}
+
+#[test]
+fn trailing_comments_stay_on_their_line() {
+    assert_eq!(
+        "let f =\n  a -- first\n    b\n",
+        fmt("let f = a -- first\n b")
+    );
+}
+
+#[test]
+fn inline_block_comments_flow_with_the_code() {
+    assert_eq!("let y = f {- note -} x\n", fmt("let y = f {- note -} x"));
+    assert_eq!("let v = ({- c -} x)\n", fmt("let v = ( {- c -} x)"));
+    assert_eq!(
+        "let y =\n  something\n    {- note -} x\n",
+        narrow("let y = something {- note -} x")
+    );
+}
+
+#[test]
+fn line_ending_and_multiline_block_comments_break() {
+    assert_eq!(
+        "let z =\n  g {- a -}\n    x\n",
+        fmt("let z = g {- a -}\n x")
+    );
+    assert_eq!(
+        "let w =\n  h {- multi\n  line -}\n    x\n",
+        fmt("let w = h {- multi\n  line -} x")
+    );
+}
+
+#[test]
+fn long_definitions_break_after_the_equals() {
+    assert_eq!(
+        "let f x =\n  something x\n",
+        narrow("let f x = something x")
+    );
+}
+
+#[test]
+fn scoped_bodies() {
+    assert_eq!("let x = 5 in f x\n", fmt("let x = 5\nin f x"));
+    assert_eq!(
+        "let x = 5 in\nlet y = 6 in f x y\n",
+        narrow("let x = 5 in let y = 6 in f x y")
+    );
+}
+
+#[test]
+fn case_arms() {
+    assert_eq!("case x | A => a end\n", fmt("case x\n| A => a end"));
+    assert_eq!(
+        "case p x\n  | True => a\n  | False => b\nend\n",
+        narrow("case p x | True => a | False => b end")
+    );
+}
+
+#[test]
+fn lambda_arms() {
+    assert_eq!(
+        "let not =\n  False => True\n  | True => False\n",
+        narrow("let not = False => True | True => False")
+    );
+}
+
+#[test]
+fn lambda_chains() {
+    assert_eq!(
+        "let f =\n  a => b => c =>\n    something a\n",
+        narrow("let f = a => b => c => something a")
+    );
+}
+
+#[test]
+fn applications_break_at_operators() {
+    assert_eq!(
+        "let f =\n  g x y\n    + h a b\n    * k c\n",
+        narrow("let f = g x y + h a b * k c")
+    );
+    assert_eq!("let f = + a b\n", fmt("let f = + a  b"));
+}
+
+#[test]
+fn records() {
+    assert_eq!("let r = { x = 1, y = 2 }\n", fmt("let r = {x=1,y=2}"));
+    assert_eq!("let r = {}\n", fmt("let r = { }"));
+    assert_eq!(
+        "let r =\n  {\n    first = 1,\n    second = 2\n  }\n",
+        narrow("let r = { first = 1, second = 2 }")
+    );
+}
+
+#[test]
+fn groups_and_qualified_names() {
+    assert_eq!("let y = f (a.b c) ()\n", fmt("let y = f ( a . b c ) ( )"));
+}
+
+#[test]
+fn tokens_are_not_merged() {
+    assert_eq!("let x = + .\n", fmt("let x = + ."));
+}
+
+#[test]
+fn unrecognized_input() {
+    assert_eq!(
+        Err(Error::Unrecognized),
+        format("let x = [", &Config::default())
+    );
+}
+
+#[test]
+fn the_prelude_is_stable() {
+    let prelude = include_str!("../../frontend/prelude.nml");
+    let formatted = fmt(prelude);
+    assert_eq!(formatted, fmt(&formatted));
+}
+
+const FRAGMENTS: &[&str] = &[
+    "let", "data", "and", "in", "case", "end", "x", "f", "A", "1", "_", "=", "=>", "|", "&", ":",
+    ".", ",", "(", ")", "{", "}", "+", "-- c", "--| d", "{- b -}", "{-\n-}", "\n", "\n\n", " ",
+];
+
+proptest! {
+    #[test]
+    fn formatting_is_idempotent(s in r"[^\[\]$]*") {
+        if let Ok(formatted) = format(&s, &Config::default()) {
+            prop_assert_eq!(&formatted, &fmt(&formatted));
+        }
+    }
+
+    #[test]
+    fn formatting_fragments(
+        fragments in proptest::collection::vec(proptest::sample::select(FRAGMENTS), 0..40),
+        width in 10..60usize,
+    ) {
+        let source = fragments.join(" ");
+        let config = Config { width, ..Default::default() };
+        let formatted = format(&source, &config).unwrap();
+        prop_assert_eq!(&formatted, &format(&formatted, &config).unwrap());
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::sync::Arc;
+
+use super::{Data, Node};
+
+/// A replacement of a range of bytes in the original text.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// Compute a set of edits which turn the text of `old` into the text of
+/// `new`. The edits are sorted and do not overlap, and their ranges refer to
+/// the text of `old`.
+///
+/// Since unchanged nodes are shared between the trees, only the nodes which
+/// actually changed need to be looked at. Where the shape of the tree changed
+/// (such as when a node is wrapped in parentheses), the tokens of the changed
+/// nodes are compared instead.
+pub fn diff(old: &Node, new: &Node) -> Vec<TextEdit> {
+    let mut differ = Differ {
+        hashes: HashMap::new(),
+        edits: Vec::new(),
+    };
+
+    differ.node(old, new, 0);
+    differ.edits
+}
+
+struct Differ {
+    /// The hash of every inner node seen so far, by address. Both trees stay
+    /// borrowed for the whole diff, so the addresses are stable.
+    hashes: HashMap<*const Node, u64>,
+    edits: Vec<TextEdit>,
+}
+
+impl Differ {
+    fn node(&mut self, old: &Node, new: &Node, offset: usize) {
+        if self.equal(old, new) {
+            return;
+        }
+
+        match (&old.data, &new.data) {
+            (Data::Token(old), Data::Token(new)) => replace(offset, old, new, &mut self.edits),
+            (Data::Node(old_children), Data::Node(new_children)) if old.kind == new.kind => {
+                self.children(old_children, new_children, offset)
+            }
+
+            _ => self.children(&tokens(old), &tokens(new), offset),
+        }
+    }
+
+    /// Diff two lists of siblings by matching up the longest common
+    /// subsequence of equal nodes, and diffing the gaps between them.
+    fn children(&mut self, old: &[Node], new: &[Node], offset: usize) {
+        let old_hashes: Vec<_> = old.iter().map(|node| self.hash(node)).collect();
+        let new_hashes: Vec<_> = new.iter().map(|node| self.hash(node)).collect();
+
+        let mut matches = Vec::new();
+        common(&old_hashes, &new_hashes, (0, 0), &mut matches);
+
+        let (mut i, mut j) = (0, 0);
+        let mut offset = offset;
+
+        for (next_i, next_j) in matches {
+            offset = self.gap(&old[i..next_i], &new[j..next_j], offset);
+
+            // equal hashes almost always mean equal nodes, but diffing the
+            // pair stays correct if they do not
+            self.node(&old[next_i], &new[next_j], offset);
+            offset += old[next_i].width;
+            (i, j) = (next_i + 1, next_j + 1);
+        }
+
+        self.gap(&old[i..], &new[j..], offset);
+    }
+
+    /// Diff a run of siblings which have no equal nodes in common, returning
+    /// the offset after it.
+    fn gap(&mut self, old: &[Node], new: &[Node], offset: usize) -> usize {
+        let end = offset + old.iter().map(|node| node.width).sum::<usize>();
+
+        if old.len() == new.len() {
+            let mut offset = offset;
+            for (old, new) in old.iter().zip(new) {
+                self.node(old, new, offset);
+                offset += old.width;
+            }
+        } else {
+            let old: String = old.iter().map(Node::to_string).collect();
+            let new: String = new.iter().map(Node::to_string).collect();
+            replace(offset, &old, &new, &mut self.edits);
+        }
+
+        end
+    }
+
+    /// Check whether two nodes are equal, looking at their hashes before
+    /// comparing them in full.
+    fn equal(&mut self, old: &Node, new: &Node) -> bool {
+        if old.kind != new.kind || old.width != new.width {
+            return false;
+        }
+
+        match (&old.data, &new.data) {
+            (Data::Node(a), Data::Node(b)) if Arc::ptr_eq(a, b) => true,
+            _ => self.hash(old) == self.hash(new) && old == new,
+        }
+    }
+
+    /// Hash a node, remembering the hash of inner nodes so that each one is
+    /// only hashed once.
+    fn hash(&mut self, node: &Node) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        node.kind.hash(&mut hasher);
+
+        match &node.data {
+            Data::Token(text) => text.hash(&mut hasher),
+            Data::Node(children) => {
+                if let Some(hash) = self.hashes.get(&(node as *const Node)) {
+                    return *hash;
+                }
+
+                for child in children.iter() {
+                    self.hash(child).hash(&mut hasher);
+                }
+            }
+        }
+
+        let hash = hasher.finish();
+        if !node.is_token() {
+            self.hashes.insert(node, hash);
+        }
+
+        hash
+    }
+}
+
+/// Find a longest common subsequence of `a` and `b`, pushing the indices of
+/// its elements (shifted by `at`) onto `into` in order.
+///
+/// This is the linear space refinement of Myers' O(ND) algorithm: the common
+/// prefix and suffix are matched directly, and whatever is left is split
+/// around its middle snake.
+fn common(a: &[u64], b: &[u64], at: (usize, usize), into: &mut Vec<(usize, usize)>) {
+    let prefix = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+    into.extend((0..prefix).map(|k| (at.0 + k, at.1 + k)));
+
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let at = (at.0 + prefix, at.1 + prefix);
+
+    let suffix = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+
+    // with the prefix and suffix gone, either side being empty means there
+    // is nothing left in common, and otherwise the middle snake splits the
+    // rest into two strictly easier problems
+    if !a.is_empty() && !b.is_empty() {
+        let (x, y, u, v) = middle_snake(a, b);
+        common(&a[..x], &b[..y], at, into);
+        into.extend((0..u - x).map(|k| (at.0 + x + k, at.1 + y + k)));
+        common(&a[u..], &b[v..], (at.0 + u, at.1 + v), into);
+    }
+
+    let at = (at.0 + a.len(), at.1 + b.len());
+    into.extend((0..suffix).map(|k| (at.0 + k, at.1 + k)));
+}
+
+/// Find the middle snake of an optimal edit script between `a` and `b`, by
+/// searching from both ends at once until the searches overlap. The snake is
+/// returned as its start `(x, y)` and end `(u, v)`.
+fn middle_snake(a: &[u64], b: &[u64]) -> (usize, usize, usize, usize) {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+
+    // forward[k] is the furthest `x` reached on the diagonal `x - y = k` from
+    // the start, and backward[k] the same from the end, with both `x` and `y`
+    // counted backwards
+    let mut forward = vec![0; (2 * max + 3) as usize];
+    let mut backward = vec![0; (2 * max + 3) as usize];
+    let index = |k: isize| (k + max + 1) as usize;
+
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let (x0, x) = furthest(&forward, index, k, d, |x, y| {
+                x < n && y < m && a[x as usize] == b[y as usize]
+            });
+
+            forward[index(k)] = x;
+
+            let back = delta - k;
+            if odd && back.abs() < d && x + backward[index(back)] >= n {
+                return (x0 as usize, (x0 - k) as usize, x as usize, (x - k) as usize);
+            }
+        }
+
+        for k in (-d..=d).step_by(2) {
+            let (x0, x) = furthest(&backward, index, k, d, |x, y| {
+                x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize]
+            });
+
+            backward[index(k)] = x;
+
+            let front = delta - k;
+            if !odd && front.abs() <= d && x + forward[index(front)] >= n {
+                let (u, v) = (n - x0, m - x0 + k);
+                return (
+                    (n - x) as usize,
+                    (m - x + k) as usize,
+                    u as usize,
+                    v as usize,
+                );
+            }
+        }
+    }
+
+    unreachable!("the searches overlap once half of the edits are made")
+}
+
+/// Take one more step along the diagonal `k` after `d` edits, and follow the
+/// snake of equal elements from there. Returns where the snake starts and
+/// ends.
+fn furthest(
+    furthest: &[isize],
+    index: impl Fn(isize) -> usize,
+    k: isize,
+    d: isize,
+    equal: impl Fn(isize, isize) -> bool,
+) -> (isize, isize) {
+    let start = if k == -d || (k != d && furthest[index(k - 1)] < furthest[index(k + 1)]) {
+        furthest[index(k + 1)]
+    } else {
+        furthest[index(k - 1)] + 1
+    };
+
+    let mut x = start;
+    while equal(x, x - k) {
+        x += 1;
+    }
+
+    (start, x)
+}
+
+/// Add an edit replacing `old` at `offset` with `new`, ignoring any common
+/// prefix or suffix.
+fn replace(offset: usize, old: &str, new: &str, edits: &mut Vec<TextEdit>) {
+    let prefix = common_prefix(old, new);
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix = common_suffix(old, new);
+    let (old, new) = (&old[..old.len() - suffix], &new[..new.len() - suffix]);
+
+    if old.is_empty() && new.is_empty() {
+        return;
+    }
+
+    let start = offset + prefix;
+    edits.push(TextEdit {
+        range: start..start + old.len(),
+        text: new.into(),
+    });
+}
+
+fn common_prefix(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(a.len().min(b.len()), |((index, _), _)| index)
+}
+
+fn common_suffix(a: &str, b: &str) -> usize {
+    a.chars()
+        .rev()
+        .zip(b.chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum()
+}
+
+/// Get every token within `node`, in order.
+fn tokens(node: &Node) -> Vec<Node> {
+    fn go(node: &Node, tokens: &mut Vec<Node>) {
+        match &node.data {
+            Data::Token(_) => tokens.push(node.clone()),
+            Data::Node(children) => children.iter().for_each(|child| go(child, tokens)),
+        }
+    }
+
+    let mut tokens = Vec::new();
+    go(node, &mut tokens);
+    tokens
+}
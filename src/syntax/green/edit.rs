@@ -0,0 +1,111 @@
+//! Green nodes are immutable, so every edit produces a new node. Children
+//! which are not touched by the edit are shared with the original tree.
+
+use std::ops::RangeBounds;
+
+use super::{Data, Kind, Node};
+
+impl Node {
+    /// Replace the children in `range` with `replacement`. Tokens have no
+    /// children, so they cannot be spliced.
+    pub fn splice(
+        &self,
+        range: impl RangeBounds<usize>,
+        replacement: impl IntoIterator<Item = Node>,
+    ) -> Node {
+        assert!(
+            matches!(self.data, Data::Node(_)),
+            "cannot splice the children of a token"
+        );
+
+        let mut children = self.children().to_vec();
+        children.splice(range, replacement);
+        Node::new(self.kind, children)
+    }
+
+    pub fn replace_child(&self, index: usize, child: Node) -> Node {
+        self.splice(index..=index, [child])
+    }
+
+    pub fn insert_child(&self, index: usize, child: Node) -> Node {
+        self.splice(index..index, [child])
+    }
+
+    pub fn remove_child(&self, index: usize) -> Node {
+        self.splice(index..=index, [])
+    }
+
+    /// Split off the trivia at the very end of this node, which may be nested
+    /// arbitrarily deep within its last children. Missing nodes have no text,
+    /// and are split off along with the trivia around them.
+    pub fn split_trailing_trivia(&self) -> (Node, Vec<Node>) {
+        let Data::Node(children) = &self.data else {
+            return (self.clone(), Vec::new());
+        };
+
+        let significant = children
+            .iter()
+            .rposition(|child| !child.kind.is_skipped() && child.kind != Kind::Missing)
+            .map_or(0, |index| index + 1);
+
+        let mut trivia = children[significant..].to_vec();
+        let mut children = children[..significant].to_vec();
+
+        if let Some(last) = children.pop() {
+            let (last, mut inner) = last.split_trailing_trivia();
+            inner.append(&mut trivia);
+            trivia = inner;
+            children.push(last);
+        }
+
+        (Node::new(self.kind, children), trivia)
+    }
+
+    /// Wrap this node in parentheses. Any trailing trivia is kept after the
+    /// closing parenthesis.
+    pub fn parenthesized(&self) -> Node {
+        let (inner, trivia) = self.split_trailing_trivia();
+        let mut children = vec![Node::token(Kind::LeftParen, "("), inner];
+        children.push(Node::token(Kind::RightParen, ")"));
+        children.extend(trivia);
+        Node::new(Kind::ParenGroup, children)
+    }
+
+    /// Insert `definition` into this definition group, such that it becomes
+    /// the definition at `index`. The definitions are separated by `and`.
+    pub fn with_definition(&self, index: usize, definition: Node) -> Node {
+        assert_eq!(Kind::DefinitionGroup, self.kind);
+        assert_eq!(Kind::Definition, definition.kind);
+
+        let positions: Vec<_> = self
+            .children()
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| child.kind == Kind::Definition)
+            .map(|(position, _)| position)
+            .collect();
+
+        assert!(index <= positions.len(), "definition index out of bounds");
+
+        let space = || Node::token(Kind::Whitespace, " ");
+        let and = || Node::token(Kind::And, "and");
+
+        if let Some(&position) = positions.get(index) {
+            return self.splice(position..position, [definition, space(), and(), space()]);
+        }
+
+        // Appending after the trailing trivia of the group would put the new
+        // definition after any comments or newlines ending the group
+        let (group, trivia) = self.split_trailing_trivia();
+        let mut children = group.children().to_vec();
+
+        if !positions.is_empty() {
+            children.extend([space(), and()]);
+        }
+
+        children.push(space());
+        children.push(definition);
+        children.extend(trivia);
+        Node::new(self.kind, children)
+    }
+}
@@ -1,7 +1,13 @@
 #![expect(dead_code)]
 
 mod debug;
+mod diff;
+mod edit;
 
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
 use std::sync::Arc;
 
 use smol_str::SmolStr;
@@ -24,11 +30,73 @@ pub enum Data {
     Token(SmolStr),
 }
 
+pub use self::diff::{diff, TextEdit};
+
+impl Node {
+    /// Create a token with the given text.
+    pub fn token(kind: Kind, text: impl Into<SmolStr>) -> Self {
+        let text = text.into();
+        Self {
+            width: text.len(),
+            kind,
+            data: Data::Token(text),
+        }
+    }
+
+    /// Create a node with the given children.
+    pub fn new(kind: Kind, children: impl IntoIterator<Item = Node>) -> Self {
+        let children: Arc<[Node]> = children.into_iter().collect();
+        Self {
+            width: children.iter().map(|child| child.width).sum(),
+            kind,
+            data: Data::Node(children),
+        }
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn is_token(&self) -> bool {
+        matches!(self.data, Data::Token(_))
+    }
+
+    /// The children of this node. Tokens have no children.
+    pub fn children(&self) -> &[Node] {
+        match &self.data {
+            Data::Node(children) => children,
+            Data::Token(_) => &[],
+        }
+    }
+}
+
+/// Writes the source text the node was created from.
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.data {
+            Data::Token(lexeme) => f.write_str(lexeme),
+            Data::Node(children) => children.iter().try_for_each(|child| child.fmt(f)),
+        }
+    }
+}
+
 impl Kind {
     /// Returns `true` if this represents something without semantic
-    /// significance (other than as a token separator)
+    /// significance (other than as a token separator). Unrecognized input is
+    /// skipped as well, so that it does not disturb the surrounding tree.
     pub fn is_skipped(&self) -> bool {
-        matches!(self, Kind::Whitespace | Kind::Comment)
+        matches!(
+            self,
+            Kind::Invalid
+                | Kind::Whitespace
+                | Kind::Comment
+                | Kind::DocComment
+                | Kind::BlockComment
+        )
     }
 }
 
@@ -43,6 +111,12 @@ pub enum Kind {
     /// A line comment
     Comment,
 
+    /// A line comment documenting the following definition
+    DocComment,
+
+    /// A possibly nested, possibly multi-line comment
+    BlockComment,
+
     /// An identifier
     Name,
 
@@ -61,6 +135,9 @@ pub enum Kind {
     /// `case`
     Case,
 
+    /// `class`
+    Class,
+
     /// `data`
     Data,
 
@@ -73,12 +150,24 @@ pub enum Kind {
     /// `infix`
     Infix,
 
+    /// `instance`
+    Instance,
+
+    /// `infixl`
+    Infixl,
+
+    /// `infixr`
+    Infixr,
+
     /// `let`
     Let,
 
     /// `postfix`
     Postfix,
 
+    /// `type`
+    Type,
+
     /// `&`
     Ampersand,
 
@@ -122,6 +211,13 @@ pub enum Kind {
     /// A source file
     Source,
 
+    /// An empty node standing in for a token or tree which was expected but
+    /// not found
+    Missing,
+
+    /// Tokens which were skipped over while recovering from a syntax error
+    Error,
+
     /// A tree surrounded by parentheses
     ParenGroup,
 
@@ -0,0 +1,115 @@
+use proptest::{prop_assert_eq, proptest};
+
+use super::{diff, Kind, Node, TextEdit};
+use crate::syntax::parse::parse;
+
+fn apply(text: &str, edits: &[TextEdit]) -> String {
+    let mut result = text.to_string();
+    for edit in edits.iter().rev() {
+        result.replace_range(edit.range.clone(), &edit.text);
+    }
+
+    result
+}
+
+fn edit(range: std::ops::Range<usize>, text: &str) -> TextEdit {
+    TextEdit {
+        range,
+        text: text.into(),
+    }
+}
+
+/// Parse `source` and get its first top-level node.
+fn item(source: &str) -> Node {
+    parse(source).children()[0].clone()
+}
+
+/// Parse a definition on its own.
+fn definition(source: &str) -> Node {
+    let group = item(&format!("let {source}"));
+    group
+        .children()
+        .iter()
+        .find(|child| child.kind() == Kind::Definition)
+        .unwrap()
+        .clone()
+}
+
+#[test]
+fn splicing_keeps_the_kind() {
+    let node = item("f a b");
+    let spliced = node.splice(1..4, [Node::token(Kind::Whitespace, " ")]);
+
+    assert_eq!(Kind::Apply, spliced.kind());
+    assert_eq!("f b", spliced.to_string());
+    assert_eq!(3, spliced.width());
+}
+
+#[test]
+fn parenthesizing_keeps_trivia_outside() {
+    let node = item("f a -- arg\n");
+    assert_eq!("(f a) -- arg\n", node.parenthesized().to_string());
+}
+
+#[test]
+fn inserting_definitions() {
+    let group = item("let x = 1 -- one\n");
+
+    let first = group.with_definition(0, definition("y = 2"));
+    assert_eq!("let y = 2 and x = 1 -- one\n", first.to_string());
+
+    let last = first.with_definition(2, definition("z = 3"));
+    assert_eq!("let y = 2 and x = 1 and z = 3 -- one\n", last.to_string());
+}
+
+#[test]
+fn diffs_are_minimal() {
+    let old = parse("let f = a b -- keep\nlet g = c\n");
+    let new = old.replace_child(1, old.children()[1].parenthesized());
+
+    assert_eq!(vec![edit(20..20, "("), edit(29..29, ")")], diff(&old, &new));
+}
+
+#[test]
+fn unchanged_trees_have_no_edits() {
+    let old = parse("let x = 1");
+    assert!(diff(&old, &old.clone()).is_empty());
+}
+
+proptest! {
+    #[test]
+    fn diffs_reproduce_the_new_text(s in r".*", index: usize, text in r"[a-z ]*") {
+        let old = parse(&s);
+        let new = if old.children().is_empty() {
+            old.insert_child(0, Node::token(Kind::Name, text))
+        } else {
+            let index = index % old.children().len();
+            old.replace_child(index, old.children()[index].parenthesized())
+        };
+
+        let edits = diff(&old, &new);
+        prop_assert_eq!(new.to_string(), apply(&s, &edits));
+    }
+
+    #[test]
+    fn diffs_between_unrelated_trees(a in r".*", b in r".*") {
+        let edits = diff(&parse(&a), &parse(&b));
+        prop_assert_eq!(b, apply(&a, &edits));
+    }
+}
+
+#[test]
+fn diffs_of_large_trees() {
+    let old: String = (0..10_000).map(|i| format!("let x{i} = {i}\n")).collect();
+    let new: String = (0..10_000)
+        .map(|i| match i % 100 {
+            0 => format!("let x{i} = ({i})\n"),
+            50 => String::new(),
+            _ => format!("let x{i} = {i}\n"),
+        })
+        .collect();
+
+    let edits = diff(&parse(&old), &parse(&new));
+    assert_eq!(new, apply(&old, &edits));
+    assert_eq!(200, edits.len());
+}
@@ -1,2 +1,5 @@
-mod green;
-mod parse;
+pub mod ast;
+pub mod format;
+pub mod green;
+pub mod parse;
+pub mod red;
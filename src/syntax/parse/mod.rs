@@ -12,7 +12,9 @@ pub fn parse(source: &str) -> Node {
     parsing::parse(tokenize(source))
 }
 
-fn tokenize(source: &str) -> impl Iterator<Item = Node> + '_ {
+/// Split a source into tokens, including those which are skipped by the
+/// parser.
+pub fn tokenize(source: &str) -> impl Iterator<Item = Node> + '_ {
     use logos::Logos;
 
     tokens::Token::lexer(source)
@@ -27,12 +29,17 @@ fn tokenize(source: &str) -> impl Iterator<Item = Node> + '_ {
                 Ok(Token::Number) => Kind::Number,
                 Ok(Token::And) => Kind::And,
                 Ok(Token::Case) => Kind::Case,
+                Ok(Token::Class) => Kind::Class,
                 Ok(Token::Data) => Kind::Data,
                 Ok(Token::End) => Kind::End,
                 Ok(Token::In) => Kind::In,
                 Ok(Token::Infix) => Kind::Infix,
+                Ok(Token::Instance) => Kind::Instance,
+                Ok(Token::Infixl) => Kind::Infixl,
+                Ok(Token::Infixr) => Kind::Infixr,
                 Ok(Token::Let) => Kind::Let,
                 Ok(Token::Postfix) => Kind::Postfix,
+                Ok(Token::Type) => Kind::Type,
                 Ok(Token::Ampersand) => Kind::Ampersand,
                 Ok(Token::Comma) => Kind::Comma,
                 Ok(Token::Dot) => Kind::Dot,
@@ -47,6 +54,8 @@ fn tokenize(source: &str) -> impl Iterator<Item = Node> + '_ {
                 Ok(Token::LeftBrace) => Kind::LeftBrace,
                 Ok(Token::RightBrace) => Kind::RightBrace,
                 Ok(Token::Comment) => Kind::Comment,
+                Ok(Token::DocComment) => Kind::DocComment,
+                Ok(Token::BlockComment) => Kind::BlockComment,
                 Ok(Token::Whitespace) => Kind::Whitespace,
             };
 
@@ -10,14 +10,19 @@ pub fn parse(tokens: impl IntoIterator<Item = Node>) -> Node {
         tokens,
         current: None,
         next: None,
+        current_starts_line: false,
+        next_starts_line: false,
+        leading: Vec::new(),
         trailing: Vec::new(),
         stack: Vec::new(),
+        closers: Vec::new(),
     };
 
     SOURCE.parse(&mut parser);
 
     debug_assert!(parser.current.is_none());
     debug_assert!(parser.next.is_none());
+    debug_assert!(parser.leading.is_empty());
     debug_assert!(parser.trailing.is_empty());
     debug_assert_eq!(1, parser.stack.len());
 
@@ -29,9 +34,21 @@ struct Parser<I> {
     current: Option<Node>,
     next: Option<Node>,
 
+    /// Whether `current` and `next` are the first thing on their line.
+    current_starts_line: bool,
+    next_starts_line: bool,
+
+    /// Documentation comments before `current`, which are kept with it rather
+    /// than with the token before them.
+    leading: Vec<Node>,
+
     /// Skippables between `current` and `next`
     trailing: Vec<Node>,
     stack: Vec<Node>,
+
+    /// The closing tokens of the groups being parsed, which error recovery
+    /// stops at so that the group can be closed.
+    closers: Vec<Kind>,
 }
 
 /// Parser bases
@@ -40,9 +57,20 @@ where
     I: Iterator<Item = Node>,
 {
     fn advance(&mut self) {
+        self.stack.append(&mut self.leading);
         self.stack.extend(self.current.take());
+
+        // Documentation comments end up in the same node as the definition
+        // they document, since it starts with the keyword after them
+        if let Some(next) = &self.next {
+            if next.kind == Kind::And || DefGroup::FIRST.contains(&next.kind) {
+                self.leading = self.trailing.split_off(docs_start(&self.trailing));
+            }
+        }
+
         self.stack.append(&mut self.trailing);
         self.current = self.next.take();
+        self.current_starts_line = self.next_starts_line;
 
         loop {
             let Some(node) = self.tokens.next() else {
@@ -56,6 +84,11 @@ where
 
             self.trailing.push(node);
         }
+
+        self.next_starts_line = match self.trailing.last() {
+            Some(node) => matches!(&node.data, Data::Token(text) if text.ends_with(['\n', '\r'])),
+            None => self.current.is_none() && self.stack.is_empty(),
+        };
     }
 
     fn is_done(&self) -> bool {
@@ -89,10 +122,51 @@ where
         self.expect_any(&[kind])
     }
 
+    /// Consume one of `kinds`. Any tokens before it are wrapped in an
+    /// [`Kind::Error`] node, unless the parser can synchronize there, in which
+    /// case the token is marked as [`Kind::Missing`] instead.
     fn expect_any(&mut self, kinds: &[Kind]) {
+        self.skip_until(|parser| parser.peek_any(kinds) || parser.at_sync());
+
         if !self.consume_any(kinds) {
-            // TODO: deal with errors somehow
+            self.missing();
+        }
+    }
+
+    /// Mark an expected token or tree as missing without consuming anything.
+    fn missing(&mut self) {
+        self.stack.push(Node::new(Kind::Missing, []));
+    }
+
+    /// Wrap tokens in an [`Kind::Error`] node until `stop` holds or the input
+    /// is done.
+    fn skip_until(&mut self, stop: impl Fn(&Self) -> bool) {
+        if self.is_done() || stop(self) {
+            return;
         }
+
+        self.always_collect(Kind::Error, |parser| {
+            while !parser.is_done() && !stop(parser) {
+                parser.advance();
+            }
+        });
+    }
+
+    /// Returns `true` if the current token closes an enclosing group or is a
+    /// definition keyword at the start of a line, such that recovery should
+    /// stop there. Such a keyword is taken to start a new item rather than
+    /// continue the broken one.
+    fn at_sync(&self) -> bool {
+        (self.current_starts_line && self.peek_any(DefGroup::FIRST)) || self.peek_any(&self.closers)
+    }
+
+    /// Parse a group closed by `closer`, so that recovery within it stops at
+    /// the closer.
+    fn closed_by(&mut self, closer: Kind, body: impl FnOnce(&mut Self)) {
+        self.closers.push(closer);
+        body(self);
+        self.closers.pop();
+        self.expect(closer);
     }
 
     fn collect(&mut self, kind: Kind, body: impl FnOnce(&mut Self)) {
@@ -135,6 +209,20 @@ where
     }
 }
 
+/// Find the start of the documentation comments at the end of `trivia`,
+/// along with the whitespace between and after them.
+fn docs_start(trivia: &[Node]) -> usize {
+    let run = trivia
+        .iter()
+        .rposition(|node| !matches!(node.kind, Kind::Whitespace | Kind::DocComment))
+        .map_or(0, |index| index + 1);
+
+    trivia[run..]
+        .iter()
+        .position(|node| node.kind == Kind::DocComment)
+        .map_or(trivia.len(), |index| run + index)
+}
+
 trait Production {
     const FIRST: &'static [Kind];
     fn parse<I: Iterator<Item = Node>>(&self, parser: &mut Parser<I>);
@@ -178,6 +266,10 @@ macro_rules! constcat {
 /// ```abnf
 /// source = thing *thing
 /// ```
+///
+/// Tokens which cannot start a thing are wrapped in an [`Kind::Error`] node
+/// up until the next definition keyword, or the next token at the start of a
+/// line which can start a thing.
 const SOURCE: Source = Source;
 
 struct Source;
@@ -193,13 +285,14 @@ impl Production for Source {
             // Put the first non-empty token into `current`
             parser.advance();
 
-            loop {
+            while !parser.is_done() {
                 if parser.peek_any(Thing::FIRST) {
                     THING.parse(parser);
-                } else if parser.is_done() {
-                    break;
                 } else {
-                    parser.advance();
+                    parser.skip_until(|parser| {
+                        parser.peek_any(DefGroup::FIRST)
+                            || (parser.current_starts_line && parser.peek_any(Thing::FIRST))
+                    });
                 }
             }
         })
@@ -225,11 +318,10 @@ impl Production for Thing {
             CASE.parse(parser)
         } else if parser.peek_any(Scoped::FIRST) {
             SCOPED.parse(parser)
-        } else if parser.peek_any(Arrows::FIRST) {
+        } else if parser.peek_any(Arrows::FIRST) || parser.peek_any(Arrows::OPERATORS) {
             ARROWS.parse(parser)
         } else {
-            // TODO: better error recovery here
-            parser.expect_any(Self::FIRST);
+            parser.missing()
         }
     }
 }
@@ -252,11 +344,10 @@ impl Production for Simple {
             CASE.parse(parser)
         } else if parser.peek_any(Scoped::FIRST) {
             SCOPED.parse(parser)
-        } else if parser.peek_any(Conjoined::FIRST) {
+        } else if parser.peek_any(Conjoined::FIRST) || parser.peek_any(CONJOINED_OPERATORS) {
             CONJOINED.parse(parser)
         } else {
-            // TODO: better error recovery here
-            parser.expect_any(Self::FIRST);
+            parser.missing()
         }
     }
 }
@@ -274,15 +365,16 @@ impl Production for Case {
         parser.collect(Kind::CaseGroup, |parser| {
             parser.expect(Kind::Case);
 
-            if parser.peek_any(Conjoined::FIRST) {
-                CONJOINED.parse(parser);
-            }
-
-            if parser.peek_any(Arrows::FIRST) {
-                ARROWS.parse(parser);
-            }
+            parser.closed_by(Kind::End, |parser| {
+                if parser.peek_any(Conjoined::FIRST) {
+                    CONJOINED.parse(parser);
+                }
 
-            parser.expect(Kind::End);
+                // Arms starting with a keyword would be mistaken for items
+                if parser.peek_any(Conjoined::FIRST) || parser.peek(Kind::Pipe) {
+                    ARROWS.parse(parser);
+                }
+            });
         })
     }
 }
@@ -295,13 +387,19 @@ const SCOPED: Scoped = pair(Kind::Scoped, DEF_GROUP, Kind::In, THING);
 type Scoped = Pair<DefGroup, Thing>;
 
 /// ```abnf
-/// def-group = ("data" / "let") def *("and" def)
+/// def-group = ("class" / "data" / "instance" / "let" / "type") def *("and" def)
 /// ```
 const DEF_GROUP: DefGroup = DefGroup;
 struct DefGroup;
 
 impl Production for DefGroup {
-    const FIRST: &'static [Kind] = &[Kind::Data, Kind::Let];
+    const FIRST: &'static [Kind] = &[
+        Kind::Class,
+        Kind::Data,
+        Kind::Instance,
+        Kind::Let,
+        Kind::Type,
+    ];
 
     fn parse<I: Iterator<Item = Node>>(&self, parser: &mut Parser<I>) {
         parser.collect(Kind::DefinitionGroup, |parser| {
@@ -313,27 +411,23 @@ impl Production for DefGroup {
                 if !parser.consume(Kind::And) {
                     break;
                 }
-
-                if !parser.peek_any(Def::FIRST) {
-                    break;
-                }
             }
         })
     }
 }
 
 /// ```abnf
-/// def = apply ["=" thing]
+/// def = conjoined ["=" thing]
 /// ```
 const DEF: Def = Def;
 struct Def;
 
 impl Production for Def {
-    const FIRST: &'static [Kind] = Apply::FIRST;
+    const FIRST: &'static [Kind] = Conjoined::FIRST;
 
     fn parse<I: Iterator<Item = Node>>(&self, parser: &mut Parser<I>) {
         parser.always_collect(Kind::Definition, |parser| {
-            APPLY.parse(parser);
+            CONJOINED.parse(parser);
 
             if parser.consume(Kind::Equal) {
                 THING.parse(parser);
@@ -350,6 +444,16 @@ impl Production for Def {
 const ARROWS: Arrows = Arrows;
 struct Arrows;
 
+impl Arrows {
+    /// The operators which may follow a missing operand. These are parsed
+    /// as if the operand was there, so that whatever follows them still ends
+    /// up in the tree.
+    const OPERATORS: &'static [Kind] = constcat!(Kind::Ampersand;
+        CONJOINED_OPERATORS,
+        &[Kind::EqualArrow]
+    );
+}
+
 impl Production for Arrows {
     const FIRST: &'static [Kind] = constcat!(Kind::Ampersand; Simple::FIRST, &[Kind::Pipe]);
 
@@ -388,9 +492,13 @@ impl Production for Arrows {
 /// ```abnf
 /// conjoined = apply *("&" apply)
 /// ```
-const CONJOINED: Conjoined = separated(Kind::Conjoined, Kind::Ampersand, ANNO);
+const CONJOINED: Conjoined = separated(Kind::Conjoined, Kind::Ampersand, CONJOINED_OPERATORS, ANNO);
 type Conjoined = Separated<Anno>;
 
+/// The operators which may follow a missing operand of a `conjoined`
+/// production.
+const CONJOINED_OPERATORS: &[Kind] = &[Kind::Ampersand, Kind::Colon, Kind::Dot];
+
 /// ```abnf
 /// anno = apply [":" apply]
 /// ```
@@ -406,12 +514,12 @@ type Apply = Repeated<Qual>;
 /// ```abnf
 /// qual = atom *("." atom)
 /// ```
-const QUAL: Qual = separated(Kind::Qualified, Kind::Dot, ATOM);
+const QUAL: Qual = separated(Kind::Qualified, Kind::Dot, &[], ATOM);
 type Qual = Separated<Atom>;
 
 /// ```abnf
 /// atom  = NAME / PRE-TICK / POST-TICK
-/// atom =/ NUMBER / "_" / "..." / "infix" / "postfix"
+/// atom =/ NUMBER / "_" / "..." / "infix" / "infixl" / "infixr" / "postfix"
 /// atom =/ paren-group / brace-group
 /// ````
 const ATOM: Atom = Atom;
@@ -426,6 +534,8 @@ impl Production for Atom {
         Kind::Underscore,
         Kind::Ellipses,
         Kind::Infix,
+        Kind::Infixl,
+        Kind::Infixr,
         Kind::Postfix,
         Kind::LeftParen,
         Kind::LeftBrace,
@@ -436,8 +546,8 @@ impl Production for Atom {
             PAREN_GROUP.parse(parser)
         } else if parser.peek_any(BraceGroup::FIRST) {
             BRACE_GROUP.parse(parser)
-        } else {
-            parser.expect_any(Self::FIRST)
+        } else if !parser.consume_any(Self::FIRST) {
+            parser.missing()
         }
     }
 }
@@ -454,8 +564,7 @@ impl Production for ParenGroup {
     fn parse<I: Iterator<Item = Node>>(&self, parser: &mut Parser<I>) {
         parser.collect(Kind::ParenGroup, |parser| {
             parser.expect(Kind::LeftParen);
-            THING.parse(parser);
-            parser.expect(Kind::RightParen);
+            parser.closed_by(Kind::RightParen, |parser| THING.parse(parser));
         })
     }
 }
@@ -463,6 +572,9 @@ impl Production for ParenGroup {
 /// ```abnf
 /// brace-group = "{" *(def ",") [def] "}"
 /// ```
+///
+/// A missing comma between two definitions does not end the group, so that
+/// it can be reported later.
 const BRACE_GROUP: BraceGroup = BraceGroup;
 struct BraceGroup;
 
@@ -473,17 +585,15 @@ impl Production for BraceGroup {
         parser.collect(Kind::BraceGroup, |parser| {
             parser.expect(Kind::LeftBrace);
 
-            loop {
-                if parser.peek_any(Def::FIRST) {
+            parser.closed_by(Kind::RightBrace, |parser| {
+                while parser.peek_any(Def::FIRST) {
                     DEF.parse(parser);
-                }
 
-                if !parser.consume(Kind::Comma) {
-                    break;
+                    if !parser.consume(Kind::Comma) && !parser.peek_any(Def::FIRST) {
+                        break;
+                    }
                 }
-            }
-
-            parser.expect(Kind::RightBrace);
+            });
         })
     }
 }
@@ -553,10 +663,19 @@ impl<P: Production> Production for Repeated<P> {
 /// ```abnf
 /// separated{by, inner} = inner *(by inner)
 /// ```
-const fn separated<P>(wrapping: Kind, by: Kind, production: P) -> Separated<P> {
+///
+/// A missing `inner` after a `by` is still parsed if the next token is in
+/// `recover`, so that whatever operators follow it end up in this tree.
+const fn separated<P>(
+    wrapping: Kind,
+    by: Kind,
+    recover: &'static [Kind],
+    production: P,
+) -> Separated<P> {
     Separated {
         wrapping,
         by,
+        recover,
         inner: production,
     }
 }
@@ -564,6 +683,7 @@ const fn separated<P>(wrapping: Kind, by: Kind, production: P) -> Separated<P> {
 struct Separated<P> {
     wrapping: Kind,
     by: Kind,
+    recover: &'static [Kind],
     inner: P,
 }
 
@@ -578,7 +698,7 @@ impl<P: Production> Production for Separated<P> {
                 break;
             }
 
-            if !parser.peek_any(P::FIRST) {
+            if !parser.peek_any(P::FIRST) && !parser.peek_any(self.recover) {
                 break;
             }
         })
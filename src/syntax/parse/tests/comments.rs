@@ -0,0 +1,46 @@
+//! Documentation comments are kept in the same node as the definition they
+//! document, right before the keyword or `and` which introduces it.
+
+use crate::syntax::parse::parse;
+
+#[track_caller]
+fn check(source: &str, expected: &str) {
+    assert_eq!(expected, format!("{:?}", parse(source)));
+}
+
+#[test]
+fn doc_comments_start_the_definition_group() {
+    check(
+        "let x = 1\n\n--| y\nlet y = 2",
+        r#"(Source (DefinitionGroup "let" " " (Definition "x" " " "=" " " "1" "\n" "\n")) (DefinitionGroup "--| y" "\n" "let" " " (Definition "y" " " "=" " " "2")))"#,
+    );
+}
+
+#[test]
+fn doc_comments_at_the_start_of_the_source() {
+    check(
+        "--| x\n--| more\nlet x = 1",
+        r#"(Source (DefinitionGroup "--| x" "\n" "--| more" "\n" "let" " " (Definition "x" " " "=" " " "1")))"#,
+    );
+}
+
+#[test]
+fn doc_comments_before_and() {
+    check(
+        "let x = 1\n--| y\nand y = 2",
+        r#"(Source (DefinitionGroup "let" " " (Definition "x" " " "=" " " "1" "\n") "--| y" "\n" "and" " " (Definition "y" " " "=" " " "2")))"#,
+    );
+}
+
+#[test]
+fn other_comments_stay_where_they_are() {
+    check(
+        "x -- x\n{- y -}\nlet y = 1",
+        r#"(Source "x" " " "-- x" "\n" "{- y -}" "\n" (DefinitionGroup "let" " " (Definition "y" " " "=" " " "1")))"#,
+    );
+
+    check(
+        "--| x\n-- x\nlet x = 1",
+        r#"(Source "--| x" "\n" "-- x" "\n" (DefinitionGroup "let" " " (Definition "x" " " "=" " " "1")))"#,
+    );
+}
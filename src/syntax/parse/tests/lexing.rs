@@ -1,11 +1,13 @@
-//! The main invariant for the lexer is that no token spans multiple lines. We
-//! can check this by parsing the entire string at once, and on a line-by-line
-//! basis, and check that we get the exact same result.
+//! The main invariant for the lexer is that no token spans multiple lines,
+//! except for block comments. We can check this by parsing the entire string
+//! at once, and on a line-by-line basis, and check that we get the exact same
+//! result.
 
 use proptest::prelude::prop;
 use proptest::strategy::Strategy;
 use proptest::{prop_assert_eq, proptest};
 
+use crate::syntax::green::Kind;
 use crate::syntax::parse::tokenize;
 
 proptest! {
@@ -21,7 +23,61 @@ proptest! {
 }
 
 /// A strategy for generating a vec of arbitrary strings with one or more
-/// newlines at the end.
+/// newlines at the end, none of which start a block comment.
 fn lines() -> impl Strategy<Value = Vec<String>> {
-    prop::collection::vec(r".*\n+", 0..50)
+    let line = r".*\n+".prop_filter("block comments span lines", |line: &String| {
+        !line.contains("{-")
+    });
+
+    prop::collection::vec(line, 0..50)
+}
+
+#[test]
+fn block_comments_nest() {
+    let kinds: Vec<_> = tokenize("{- a {- b -} c -} d")
+        .map(|node| (node.kind, node.to_string()))
+        .collect();
+
+    assert_eq!(
+        vec![
+            (Kind::BlockComment, "{- a {- b -} c -}".into()),
+            (Kind::Whitespace, " ".into()),
+            (Kind::Name, "d".into()),
+        ],
+        kinds
+    );
+}
+
+#[test]
+fn unterminated_block_comments_are_invalid() {
+    let kinds: Vec<_> = tokenize("x {- a {- b -}\nc")
+        .map(|node| (node.kind, node.to_string()))
+        .collect();
+
+    assert_eq!(
+        vec![
+            (Kind::Name, "x".into()),
+            (Kind::Whitespace, " ".into()),
+            (Kind::Invalid, "{- a {- b -}\nc".into()),
+        ],
+        kinds
+    );
+}
+
+#[test]
+fn doc_comments_are_distinct_from_line_comments() {
+    let kinds: Vec<_> = tokenize("--| doc\n-- line\n---| line")
+        .map(|node| node.kind)
+        .collect();
+
+    assert_eq!(
+        vec![
+            Kind::DocComment,
+            Kind::Whitespace,
+            Kind::Comment,
+            Kind::Whitespace,
+            Kind::Comment,
+        ],
+        kinds
+    );
 }
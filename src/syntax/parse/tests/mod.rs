@@ -3,11 +3,13 @@ use smol_str::SmolStr;
 use super::parse;
 use crate::syntax::green::{Data, Kind, Node};
 
+mod comments;
 mod impls;
 mod invariants;
 mod junk;
 mod lexing;
 mod lossless;
+mod recovery;
 
 #[test]
 fn single_item() {
@@ -0,0 +1,59 @@
+//! The parser marks what it expected but did not find as missing, and skips
+//! over what it did not expect until it can synchronize again.
+
+use crate::syntax::parse::parse;
+
+#[track_caller]
+fn check(source: &str, expected: &str) {
+    assert_eq!(expected, format!("{:?}", parse(source)));
+}
+
+#[test]
+fn unclosed_groups_are_missing_their_closer() {
+    check(
+        "(case x",
+        r#"(Source (ParenGroup "(" (CaseGroup "case" " " "x" (Missing)) (Missing)))"#,
+    );
+}
+
+#[test]
+fn recovery_stops_at_enclosing_closers() {
+    check(
+        "(case x)",
+        r#"(Source (ParenGroup "(" (CaseGroup "case" " " "x" (Missing)) ")"))"#,
+    );
+}
+
+#[test]
+fn tokens_before_a_closer_are_skipped() {
+    check(
+        "(a = b)",
+        r#"(Source (ParenGroup "(" "a" " " (Error "=" " " "b") ")"))"#,
+    );
+
+    check(
+        "{ a = 1 = 2 }",
+        r#"(Source (BraceGroup "{" " " (Definition "a" " " "=" " " "1" " ") (Error "=" " " "2" " ") "}"))"#,
+    );
+}
+
+#[test]
+fn recovery_stops_at_items_at_the_start_of_a_line() {
+    check(
+        "let x = (1 y\nlet y = 2",
+        r#"(Source (DefinitionGroup "let" " " (Definition "x" " " "=" " " (ParenGroup "(" (Apply "1" " " "y" "\n") (Missing)))) (DefinitionGroup "let" " " (Definition "y" " " "=" " " "2")))"#,
+    );
+
+    check(
+        "let x = (1 y\n  let y = 2)",
+        r#"(Source (DefinitionGroup "let" " " (Definition "x" " " "=" " " (ParenGroup "(" (Apply "1" " " "y" "\n" "  ") (Error "let" " " "y" " " "=" " " "2") ")"))))"#,
+    );
+}
+
+#[test]
+fn junk_between_items_is_skipped_until_the_next_line() {
+    check(
+        "let x = 1 ) y\nz",
+        r#"(Source (DefinitionGroup "let" " " (Definition "x" " " "=" " " "1" " ")) (Error ")" " " "y" "\n") "z")"#,
+    );
+}
@@ -1,4 +1,4 @@
-use logos::Logos;
+use logos::{Lexer, Logos};
 
 #[derive(Logos, Clone, Debug, Eq, PartialEq)]
 pub enum Token {
@@ -19,6 +19,8 @@ pub enum Token {
     And,
     #[token("case")]
     Case,
+    #[token("class")]
+    Class,
     #[token("data")]
     Data,
     #[token("end")]
@@ -27,10 +29,18 @@ pub enum Token {
     In,
     #[token("infix")]
     Infix,
+    #[token("instance")]
+    Instance,
+    #[token("infixl")]
+    Infixl,
+    #[token("infixr")]
+    Infixr,
     #[token("let")]
     Let,
     #[token("postfix")]
     Postfix,
+    #[token("type")]
+    Type,
 
     #[token("&")]
     Ampersand,
@@ -64,9 +74,40 @@ pub enum Token {
     #[regex(r"--[^\n]*")]
     Comment,
 
+    #[regex(r"--\|[^\n]*")]
+    DocComment,
+
+    #[token("{-", block_comment)]
+    BlockComment,
+
     // Whitespace is tokenized as a bunch of non-lineshifts followed by a
     // single lineshift to ensure lexing produces the same result if done on a
     // line-by-line basis or on the entire string.
     #[regex(r"[\s--[\n\r]]+[\n\r]?|[\n\r]")]
     Whitespace,
 }
+
+/// Skip past the end of a block comment, which may contain nested block
+/// comments. An unterminated comment extends to the end of the input, and is
+/// an error.
+fn block_comment(lexer: &mut Lexer<Token>) -> bool {
+    let mut depth = 1;
+    let mut chars = lexer.remainder().char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some((_, '-'))) => depth += 1,
+            ('-', Some((_, '}'))) => depth -= 1,
+            _ => continue,
+        }
+
+        chars.next();
+        if depth == 0 {
+            lexer.bump(index + 2);
+            return true;
+        }
+    }
+
+    lexer.bump(lexer.remainder().len());
+    false
+}
@@ -0,0 +1,279 @@
+#![expect(dead_code)]
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+
+use super::green::{Data, Kind, Node};
+use crate::frontend::source::{SourceId, Span};
+
+/// A cursor points at a particular element (node or token) of a green tree,
+/// and knows its absolute offset and parent. Cursors are created on demand
+/// while navigating the tree, and are cheap to clone.
+#[derive(Clone)]
+pub struct Cursor(Rc<Inner>);
+
+struct Inner {
+    green: Node,
+    offset: usize,
+
+    /// The index of this element among the children of its parent.
+    index: usize,
+    parent: Option<Cursor>,
+}
+
+/// The tokens found at a particular offset. An offset on the boundary between
+/// two tokens touches both of them.
+#[derive(Clone, Debug)]
+pub enum TokenAtOffset {
+    None,
+    Single(Cursor),
+    Between(Cursor, Cursor),
+}
+
+impl Cursor {
+    /// Create a cursor at the root of a tree.
+    pub fn root(green: Node) -> Self {
+        Self(Rc::new(Inner {
+            green,
+            offset: 0,
+            index: 0,
+            parent: None,
+        }))
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Node {
+        &self.0.green
+    }
+
+    /// The text of this element if it is a token.
+    pub fn text(&self) -> Option<&str> {
+        match &self.0.green.data {
+            Data::Token(lexeme) => Some(lexeme),
+            Data::Node(_) => None,
+        }
+    }
+
+    pub fn is_token(&self) -> bool {
+        matches!(self.0.green.data, Data::Token(_))
+    }
+
+    /// The absolute offset of the start of this element in bytes.
+    pub fn offset(&self) -> usize {
+        self.0.offset
+    }
+
+    pub fn width(&self) -> usize {
+        self.0.green.width
+    }
+
+    /// The absolute range of bytes covered by this element.
+    pub fn range(&self) -> Range<usize> {
+        self.offset()..self.offset() + self.width()
+    }
+
+    /// Get the span of this element in the given source.
+    pub fn span(&self, source: SourceId) -> Span {
+        source.span(self.offset(), self.offset() + self.width())
+    }
+
+    pub fn parent(&self) -> Option<Cursor> {
+        self.0.parent.clone()
+    }
+
+    /// Iterate over this element and its ancestors, from the innermost out.
+    pub fn ancestors(&self) -> impl Iterator<Item = Cursor> {
+        std::iter::successors(Some(self.clone()), Cursor::parent)
+    }
+
+    /// Iterate over the children of this element, including trivia. Tokens
+    /// have no children.
+    pub fn children(&self) -> Children {
+        Children {
+            parent: self.clone(),
+            index: 0,
+            offset: self.offset(),
+        }
+    }
+
+    /// Get the next sibling of this element which is not trivia.
+    pub fn next_sibling(&self) -> Option<Cursor> {
+        let parent = self.0.parent.as_ref()?;
+        let siblings = parent.green_children();
+
+        let mut offset = self.offset() + self.width();
+        for (index, green) in siblings.iter().enumerate().skip(self.0.index + 1) {
+            if !green.kind.is_skipped() {
+                return Some(parent.child(green.clone(), offset, index));
+            }
+
+            offset += green.width;
+        }
+
+        None
+    }
+
+    /// Get the previous sibling of this element which is not trivia.
+    pub fn prev_sibling(&self) -> Option<Cursor> {
+        let parent = self.0.parent.as_ref()?;
+        let siblings = parent.green_children();
+
+        let mut offset = self.offset();
+        for (index, green) in siblings[..self.0.index].iter().enumerate().rev() {
+            offset -= green.width;
+
+            if !green.kind.is_skipped() {
+                return Some(parent.child(green.clone(), offset, index));
+            }
+        }
+
+        None
+    }
+
+    /// Get the first token within this element, which may be trivia.
+    pub fn first_token(&self) -> Option<Cursor> {
+        let mut cursor = self.clone();
+        while !cursor.is_token() {
+            cursor = cursor.children().next()?;
+        }
+
+        Some(cursor)
+    }
+
+    /// Get the last token within this element, which may be trivia.
+    pub fn last_token(&self) -> Option<Cursor> {
+        let mut cursor = self.clone();
+        while !cursor.is_token() {
+            cursor = cursor.children().last()?;
+        }
+
+        Some(cursor)
+    }
+
+    /// Find the tokens touching the given absolute offset.
+    pub fn token_at_offset(&self, offset: usize) -> TokenAtOffset {
+        let left = offset
+            .checked_sub(1)
+            .and_then(|before| self.token_containing(before));
+        let right = self.token_containing(offset);
+
+        match (left, right) {
+            (Some(left), Some(right)) if left.offset() == right.offset() => {
+                TokenAtOffset::Single(left)
+            }
+
+            (Some(left), Some(right)) => TokenAtOffset::Between(left, right),
+            (Some(token), None) | (None, Some(token)) => TokenAtOffset::Single(token),
+            (None, None) => TokenAtOffset::None,
+        }
+    }
+
+    /// Find the innermost element which covers the entire given range, which
+    /// must lie within this element.
+    pub fn covering_element(&self, range: Range<usize>) -> Cursor {
+        let own = self.range();
+        assert!(
+            own.start <= range.start && range.end <= own.end,
+            "the range {range:?} is not within the element at {own:?}"
+        );
+
+        let mut cursor = self.clone();
+        'descend: loop {
+            for child in cursor.children() {
+                let covered = child.range();
+                if covered.start <= range.start && range.end <= covered.end {
+                    cursor = child;
+                    continue 'descend;
+                }
+            }
+
+            return cursor;
+        }
+    }
+
+    /// Replace this element with `green`, and get the root of the new tree.
+    /// Only the ancestors of this element are rebuilt, and everything else
+    /// is shared with the original tree.
+    pub fn replace(&self, green: Node) -> Node {
+        let mut cursor = self;
+        let mut green = green;
+
+        while let Some(parent) = &cursor.0.parent {
+            green = parent.0.green.replace_child(cursor.0.index, green);
+            cursor = parent;
+        }
+
+        green
+    }
+
+    /// Find the token whose range includes the byte at `offset`.
+    fn token_containing(&self, offset: usize) -> Option<Cursor> {
+        if !self.range().contains(&offset) {
+            return None;
+        }
+
+        let mut cursor = self.clone();
+        while !cursor.is_token() {
+            cursor = cursor
+                .children()
+                .find(|child| child.range().contains(&offset))?;
+        }
+
+        Some(cursor)
+    }
+
+    fn green_children(&self) -> &[Node] {
+        match &self.0.green.data {
+            Data::Node(children) => children,
+            Data::Token(_) => &[],
+        }
+    }
+
+    fn child(&self, green: Node, offset: usize, index: usize) -> Cursor {
+        Cursor(Rc::new(Inner {
+            green,
+            offset,
+            index,
+            parent: Some(self.clone()),
+        }))
+    }
+}
+
+impl fmt::Debug for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{:?}", self.kind(), self.range())?;
+
+        if let Some(text) = self.text() {
+            write!(f, " {text:?}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An iterator over the children of an element.
+pub struct Children {
+    parent: Cursor,
+    index: usize,
+    offset: usize,
+}
+
+impl Iterator for Children {
+    type Item = Cursor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let green = self.parent.green_children().get(self.index)?.clone();
+        let child = self.parent.child(green, self.offset, self.index);
+
+        self.index += 1;
+        self.offset += child.width();
+        Some(child)
+    }
+}
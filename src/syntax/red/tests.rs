@@ -0,0 +1,173 @@
+use proptest::{prop_assert, prop_assert_eq, proptest};
+
+use super::{Cursor, TokenAtOffset};
+use crate::frontend::source::SourceId;
+use crate::syntax::green::{Kind, Node};
+use crate::syntax::parse::parse;
+
+fn root(source: &str) -> Cursor {
+    Cursor::root(parse(source))
+}
+
+/// Describe a token as its text and range.
+fn token(cursor: &Cursor) -> String {
+    format!("{}@{:?}", cursor.text().unwrap(), cursor.range())
+}
+
+#[test]
+fn children_have_absolute_offsets() {
+    let root = root("let x = 1\nlet y = 2");
+    let items: Vec<_> = root
+        .children()
+        .map(|child| (child.kind(), child.range()))
+        .collect();
+
+    assert_eq!(
+        vec![
+            (Kind::DefinitionGroup, 0..10),
+            (Kind::DefinitionGroup, 10..19),
+        ],
+        items
+    );
+
+    let second = root.children().nth(1).unwrap();
+    let tokens: Vec<_> = second
+        .children()
+        .nth(2)
+        .unwrap()
+        .children()
+        .filter(Cursor::is_token)
+        .map(|child| token(&child))
+        .collect();
+
+    assert_eq!(
+        vec!["y@14..15", " @15..16", "=@16..17", " @17..18", "2@18..19"],
+        tokens
+    );
+}
+
+#[test]
+fn parents_and_ancestors() {
+    let root = root("let f = a b");
+    let TokenAtOffset::Single(b) = root.token_at_offset(11) else {
+        panic!("expected a single token");
+    };
+
+    assert_eq!(Kind::Apply, b.parent().unwrap().kind());
+
+    let kinds: Vec<_> = b.ancestors().map(|cursor| cursor.kind()).collect();
+    assert_eq!(
+        vec![
+            Kind::Name,
+            Kind::Apply,
+            Kind::Definition,
+            Kind::DefinitionGroup,
+            Kind::Source
+        ],
+        kinds
+    );
+
+    assert!(root.parent().is_none());
+}
+
+#[test]
+fn tokens_at_offsets() {
+    let root = root("let x = 12");
+
+    let TokenAtOffset::Single(number) = root.token_at_offset(9) else {
+        panic!("expected a single token");
+    };
+    assert_eq!("12@8..10", token(&number));
+
+    let TokenAtOffset::Between(left, right) = root.token_at_offset(5) else {
+        panic!("expected two tokens");
+    };
+    assert_eq!("x@4..5", token(&left));
+    assert_eq!(" @5..6", token(&right));
+
+    let TokenAtOffset::Single(number) = root.token_at_offset(10) else {
+        panic!("expected a single token");
+    };
+    assert_eq!("12@8..10", token(&number));
+
+    assert!(matches!(root.token_at_offset(11), TokenAtOffset::None));
+}
+
+#[test]
+fn siblings_skip_trivia() {
+    let root = root("let f = ab -- comment\n  c");
+    let TokenAtOffset::Single(ab) = root.token_at_offset(9) else {
+        panic!("expected a single token");
+    };
+
+    let c = ab.next_sibling().unwrap();
+    assert_eq!("c@24..25", token(&c));
+    assert!(c.next_sibling().is_none());
+
+    let ab = c.prev_sibling().unwrap();
+    assert_eq!("ab@8..10", token(&ab));
+    assert!(ab.prev_sibling().is_none());
+}
+
+#[test]
+fn covering_elements() {
+    let root = root("let f = a b c");
+
+    let covering = root.covering_element(8..11);
+    assert_eq!((Kind::Apply, 8..13), (covering.kind(), covering.range()));
+
+    let covering = root.covering_element(10..11);
+    assert_eq!("b@10..11", token(&covering));
+
+    let covering = root.covering_element(0..13);
+    assert_eq!(Kind::DefinitionGroup, covering.kind());
+}
+
+#[test]
+fn first_and_last_tokens() {
+    let root = root("let f = a b\n");
+    assert_eq!("let@0..3", token(&root.first_token().unwrap()));
+    assert_eq!("\n@11..12", token(&root.last_token().unwrap()));
+}
+
+#[test]
+fn spans() {
+    let source = SourceId::new(3);
+    let root = root("let x = 1");
+    let covering = root.covering_element(4..5);
+    assert_eq!(source.span(4, 5), covering.span(source));
+}
+
+proptest! {
+    #[test]
+    fn every_offset_has_a_token(s in r".*") {
+        let root = root(&s);
+
+        for offset in 0..s.len() {
+            let found = match root.token_at_offset(offset) {
+                TokenAtOffset::Single(token) | TokenAtOffset::Between(_, token) => token,
+                TokenAtOffset::None => panic!("no token at {offset}"),
+            };
+
+            prop_assert!(found.range().contains(&offset));
+            prop_assert_eq!(found.text().unwrap(), &s[found.range()]);
+        }
+    }
+}
+
+#[test]
+fn replacing_rebuilds_the_ancestors() {
+    let root = root("let f = a bb -- keep\nlet g = c");
+    let TokenAtOffset::Single(b) = root.token_at_offset(11) else {
+        panic!("expected a single token");
+    };
+
+    let replaced = b.replace(Node::token(Kind::Name, "bee"));
+    assert_eq!("let f = a bee -- keep\nlet g = c", replaced.to_string());
+
+    // The untouched definition group is shared with the original tree
+    assert!(std::ptr::eq(
+        &root.green().children()[1].children()[0],
+        &replaced.children()[1].children()[0]
+    ));
+}
@@ -0,0 +1,41 @@
+//! Development tasks, run with `cargo xtask <task>`:
+//!
+//! - `bless` runs the ui tests, writing the errors which are not expected
+//!   inline to the `.stderr` file next to each program.
+
+use std::env;
+use std::process::{Command, ExitCode};
+
+fn main() -> ExitCode {
+    match env::args().nth(1).as_deref() {
+        Some("bless") => bless(),
+
+        Some(task) => {
+            eprintln!("unknown task `{task}`");
+            ExitCode::FAILURE
+        }
+
+        None => {
+            eprintln!("usage: cargo xtask bless");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Run the ui tests, updating their snapshots instead of comparing them.
+fn bless() -> ExitCode {
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let status = Command::new(cargo)
+        .args(["test", "--package", "nmlc", "frontend::ui"])
+        .env("BLESS", "1")
+        .status();
+
+    match status {
+        Ok(status) if status.success() => ExitCode::SUCCESS,
+        Ok(_) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("could not run cargo: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}